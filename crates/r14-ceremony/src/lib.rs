@@ -0,0 +1,280 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! r14-ceremony: Groth16 phase-2 ceremony for the transfer circuit.
+//!
+//! `R14Client`, the CLI and the prover all derive their keys from
+//! `StdRng::seed_from_u64(42)`, so anyone can recompute the toxic waste and
+//! forge proofs. A ceremony replaces that with parameters nobody knows the
+//! trapdoor of, as long as one contributor is honest:
+//!
+//! 1. [`Ceremony::init_transfer`] runs the circuit-specific setup with
+//!    fresh OS randomness.
+//! 2. Each contributor calls [`Ceremony::contribute`]. It multiplies δ by a
+//!    random δ', divides the `h` and `l` queries by δ', and appends a
+//!    proof of knowledge of δ' bound to the transcript so far.
+//! 3. Anyone runs [`Ceremony::verify`] over the whole chain before the
+//!    final keys are exported and registered.
+//!
+//! Phase 2 only protects δ. α, β and τ come from the initial setup, and
+//! whoever knows τ can still recover `δ⁻¹` from the `h` query. Until phase-1
+//! parameters can be imported from a powers-of-tau ceremony, the party
+//! running `init` must be trusted to discard its randomness.
+//!
+//! ```rust,no_run
+//! use r14_ceremony::Ceremony;
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut c = Ceremony::init_transfer(&mut r14_ceremony::os_rng(b""))?;
+//! let receipt = c.contribute(&mut r14_ceremony::os_rng(b"dice rolls: 4 1 6 6 2"));
+//! c.verify()?;
+//! assert!(c.contains(&receipt));
+//! let pk = c.proving_key();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{Read, Write};
+
+use anyhow::{bail, ensure, Context, Result};
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{rngs::StdRng, CryptoRng, RngCore, SeedableRng};
+use sha2::{Digest, Sha256};
+
+/// File magic, followed by a format version byte
+const MAGIC: &[u8; 8] = b"r14cerem";
+const VERSION: u8 = 1;
+
+/// One contributor's update of δ and its proof of knowledge
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution {
+    /// `delta_g1` after this contribution
+    pub delta_after: G1Affine,
+    /// Random `s` and `s·δ'`
+    pub s: G1Affine,
+    pub s_delta: G1Affine,
+    /// `r·δ'`, where `r` is hashed from the transcript, `s` and `s·δ'`
+    pub r_delta: G2Affine,
+}
+
+/// Transcript hash after a contribution; contributors keep it to check
+/// their update made it into the final parameters
+pub type Receipt = [u8; 32];
+
+pub struct Ceremony {
+    /// Parameters from `init`, before any contribution
+    pub initial: ProvingKey<Bls12_381>,
+    /// Parameters after the last contribution
+    pub current: ProvingKey<Bls12_381>,
+    pub contributions: Vec<Contribution>,
+}
+
+impl Ceremony {
+    pub fn new(initial: ProvingKey<Bls12_381>) -> Self {
+        Self {
+            current: initial.clone(),
+            initial,
+            contributions: Vec::new(),
+        }
+    }
+
+    /// Start a ceremony for the transfer circuit
+    pub fn init_transfer<R: RngCore + CryptoRng>(rng: &mut R) -> Result<Self> {
+        let (pk, _vk) = r14_circuit::setup(rng)?;
+        Ok(Self::new(pk))
+    }
+
+    /// Apply a contribution drawn from `rng`. The randomness must be
+    /// discarded afterwards.
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> Receipt {
+        let delta = loop {
+            let d = Fr::rand(rng);
+            if let Some(inv) = d.inverse() {
+                break (d, inv);
+            }
+        };
+        let (d, d_inv) = delta;
+
+        let transcript = self.transcript(self.contributions.len());
+        let s = G1Projective::rand(rng).into_affine();
+        let s_delta = (s * d).into_affine();
+        let r = hash_to_g2(&transcript, &s, &s_delta);
+
+        let pk = &mut self.current;
+        pk.delta_g1 = (pk.delta_g1 * d).into_affine();
+        pk.vk.delta_g2 = (pk.vk.delta_g2 * d).into_affine();
+        pk.h_query = scale(&pk.h_query, d_inv);
+        pk.l_query = scale(&pk.l_query, d_inv);
+
+        self.contributions.push(Contribution {
+            delta_after: pk.delta_g1,
+            s,
+            s_delta,
+            r_delta: (r * d).into_affine(),
+        });
+        self.transcript(self.contributions.len())
+    }
+
+    /// Check every contribution and that the current parameters follow
+    /// from the initial ones.
+    pub fn verify(&self) -> Result<()> {
+        let (init, cur) = (&self.initial, &self.current);
+        ensure!(
+            cur.vk.alpha_g1 == init.vk.alpha_g1
+                && cur.vk.beta_g2 == init.vk.beta_g2
+                && cur.vk.gamma_g2 == init.vk.gamma_g2
+                && cur.vk.gamma_abc_g1 == init.vk.gamma_abc_g1
+                && cur.beta_g1 == init.beta_g1
+                && cur.a_query == init.a_query
+                && cur.b_g1_query == init.b_g1_query
+                && cur.b_g2_query == init.b_g2_query,
+            "parameters other than delta changed"
+        );
+        ensure!(
+            cur.h_query.len() == init.h_query.len() && cur.l_query.len() == init.l_query.len(),
+            "query lengths changed"
+        );
+
+        let mut prev = init.delta_g1;
+        for (i, c) in self.contributions.iter().enumerate() {
+            ensure!(
+                !c.s.is_zero() && !c.s_delta.is_zero() && !c.delta_after.is_zero(),
+                "contribution {i}: zero point"
+            );
+            let r = hash_to_g2(&self.transcript(i), &c.s, &c.s_delta);
+            // r·δ' pairs with s as r pairs with s·δ', so the contributor knew δ'
+            ensure!(
+                same_ratio((c.s, c.s_delta), (r, c.r_delta)),
+                "contribution {i}: bad proof of knowledge"
+            );
+            // and δ moved by that same δ'
+            ensure!(
+                same_ratio((prev, c.delta_after), (r, c.r_delta)),
+                "contribution {i}: delta update doesn't match its proof"
+            );
+            prev = c.delta_after;
+        }
+
+        ensure!(cur.delta_g1 == prev, "delta_g1 doesn't match the last contribution");
+        ensure!(
+            // arkworks picks random generators, so compare against the
+            // initial pair rather than the standard ones
+            same_ratio(
+                (init.delta_g1, cur.delta_g1),
+                (init.vk.delta_g2, cur.vk.delta_g2)
+            ),
+            "delta_g1 and delta_g2 disagree"
+        );
+
+        // h and l were divided by exactly the δ ratio: check a random
+        // combination of each query
+        let mut rng = os_rng(b"r14-ceremony verify");
+        for (name, old, new) in [
+            ("h_query", &init.h_query, &cur.h_query),
+            ("l_query", &init.l_query, &cur.l_query),
+        ] {
+            let rho: Vec<Fr> = (0..old.len()).map(|_| Fr::rand(&mut rng)).collect();
+            let old = G1Projective::msm(old, &rho).expect("lengths match").into_affine();
+            let new = G1Projective::msm(new, &rho).expect("lengths match").into_affine();
+            ensure!(
+                same_ratio((new, old), (init.vk.delta_g2, cur.vk.delta_g2)),
+                "{name} doesn't match delta"
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether `receipt` is the transcript after one of the contributions
+    pub fn contains(&self, receipt: &Receipt) -> bool {
+        (1..=self.contributions.len()).any(|n| &self.transcript(n) == receipt)
+    }
+
+    pub fn proving_key(&self) -> &ProvingKey<Bls12_381> {
+        &self.current
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey<Bls12_381> {
+        &self.current.vk
+    }
+
+    /// Hash of the initial parameters and the first `n` contributions
+    pub fn transcript(&self, n: usize) -> Receipt {
+        let mut h = Sha256::new();
+        let mut bytes = Vec::new();
+        self.initial
+            .serialize_compressed(&mut bytes)
+            .expect("serializing into a Vec");
+        h.update(&bytes);
+        for c in &self.contributions[..n] {
+            bytes.clear();
+            c.serialize_compressed(&mut bytes).expect("serializing into a Vec");
+            h.update(&bytes);
+        }
+        h.finalize().into()
+    }
+
+    pub fn write<W: Write>(&self, mut w: W) -> Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        self.initial.serialize_compressed(&mut w)?;
+        self.current.serialize_compressed(&mut w)?;
+        self.contributions.serialize_compressed(&mut w)?;
+        Ok(())
+    }
+
+    /// Read a ceremony file. Points are subgroup-checked; call
+    /// [`Ceremony::verify`] before trusting the contents.
+    pub fn read<R: Read>(mut r: R) -> Result<Self> {
+        let mut header = [0u8; 9];
+        r.read_exact(&mut header).context("reading header")?;
+        if &header[..8] != MAGIC {
+            bail!("not an r14 ceremony file");
+        }
+        if header[8] != VERSION {
+            bail!("unsupported ceremony file version {}", header[8]);
+        }
+        Ok(Self {
+            initial: ProvingKey::deserialize_compressed(&mut r).context("initial parameters")?,
+            current: ProvingKey::deserialize_compressed(&mut r).context("current parameters")?,
+            contributions: Vec::deserialize_compressed(&mut r).context("contributions")?,
+        })
+    }
+}
+
+/// RNG seeded from the OS mixed with contributor-supplied entropy
+pub fn os_rng(entropy: &[u8]) -> StdRng {
+    use rand::Rng;
+
+    let mut os = [0u8; 32];
+    rand::rng().fill_bytes(&mut os);
+    let mut h = Sha256::new();
+    h.update(os);
+    h.update(entropy);
+    StdRng::from_seed(h.finalize().into())
+}
+
+/// e(a.0, b.1) == e(a.1, b.0), i.e. a.1/a.0 and b.1/b.0 share one exponent
+fn same_ratio(a: (G1Affine, G1Affine), b: (G2Affine, G2Affine)) -> bool {
+    Bls12_381::pairing(a.0, b.1) == Bls12_381::pairing(a.1, b.0)
+}
+
+/// Point in G2 with no known discrete log, derived from the transcript
+fn hash_to_g2(transcript: &Receipt, s: &G1Affine, s_delta: &G1Affine) -> G2Affine {
+    let mut h = Sha256::new();
+    h.update(b"r14-ceremony r");
+    h.update(transcript);
+    let mut bytes = Vec::new();
+    s.serialize_compressed(&mut bytes).expect("serializing into a Vec");
+    s_delta.serialize_compressed(&mut bytes).expect("serializing into a Vec");
+    h.update(&bytes);
+    G2Projective::rand(&mut StdRng::from_seed(h.finalize().into())).into_affine()
+}
+
+fn scale(points: &[G1Affine], by: Fr) -> Vec<G1Affine> {
+    let scaled: Vec<G1Projective> = points.iter().map(|p| *p * by).collect();
+    G1Projective::normalize_batch(&scaled)
+}
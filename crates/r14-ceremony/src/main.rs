@@ -0,0 +1,136 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ark_serialize::CanonicalSerialize;
+use clap::{Parser, Subcommand};
+use r14_ceremony::{os_rng, Ceremony};
+use r14_sdk::HashFamily;
+
+#[derive(Parser)]
+#[command(name = "r14-ceremony", about = "Phase-2 trusted setup for the Root14 transfer circuit", version)]
+struct Cli {
+    /// Hash family of the deployment: poseidon, poseidon2 or circom
+    /// (default: r14.toml's, else poseidon)
+    #[arg(long, global = true, env = "R14_HASH")]
+    hash: Option<HashFamily>,
+    #[command(subcommand)]
+    command: Cmd,
+}
+
+#[derive(Subcommand)]
+enum Cmd {
+    /// Start a ceremony from a fresh transfer-circuit setup
+    Init {
+        /// Ceremony file to create
+        out: PathBuf,
+    },
+    /// Add a contribution
+    Contribute {
+        /// Ceremony file to read
+        input: PathBuf,
+        /// Ceremony file to write
+        out: PathBuf,
+        /// Extra entropy mixed with OS randomness (dice rolls, keyboard mashing)
+        #[arg(long, default_value = "")]
+        entropy: String,
+    },
+    /// Verify every contribution in a ceremony file
+    Verify {
+        file: PathBuf,
+        /// Also check that this receipt (hex) is part of the chain
+        #[arg(long)]
+        receipt: Option<String>,
+    },
+    /// Verify, then write the final proving key and VK
+    Export {
+        file: PathBuf,
+        /// Proving key, in the format `R14_PROVER_KEY_CACHE` loads
+        #[arg(long, default_value = "transfer.pk")]
+        pk: PathBuf,
+        /// VK JSON for `r14 init-contract --vk`
+        #[arg(long, default_value = "transfer.vk.json")]
+        vk: PathBuf,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = select_hash_family(cli.hash).and_then(|()| run(cli.command)) {
+        eprintln!("error: {e:#}");
+        std::process::exit(1);
+    }
+}
+
+fn run(cmd: Cmd) -> Result<()> {
+    match cmd {
+        Cmd::Init { out } => {
+            eprintln!("running transfer circuit setup...");
+            let c = Ceremony::init_transfer(&mut os_rng(b""))?;
+            save(&c, &out)?;
+            println!("initial transcript: {}", hex::encode(c.transcript(0)));
+        }
+        Cmd::Contribute { input, out, entropy } => {
+            let mut c = load(&input)?;
+            let receipt = c.contribute(&mut os_rng(entropy.as_bytes()));
+            save(&c, &out)?;
+            println!("contribution #{}", c.contributions.len());
+            println!("receipt: {}", hex::encode(receipt));
+        }
+        Cmd::Verify { file, receipt } => {
+            let c = load(&file)?;
+            c.verify()?;
+            if let Some(receipt) = receipt {
+                let receipt: [u8; 32] = hex::decode(&receipt)
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .context("receipt must be 32 bytes of hex")?;
+                anyhow::ensure!(c.contains(&receipt), "receipt not found in this ceremony");
+            }
+            println!("ok: {} contributions verified", c.contributions.len());
+        }
+        Cmd::Export { file, pk, vk } => {
+            let c = load(&file)?;
+            c.verify()?;
+            let mut bytes = Vec::new();
+            c.proving_key().serialize_uncompressed(&mut bytes)?;
+            std::fs::write(&pk, bytes).with_context(|| format!("writing {}", pk.display()))?;
+
+            let svk = r14_sdk::serialize::serialize_vk_for_soroban(c.verifying_key(), r14_sdk::MERKLE_DEPTH);
+            // same unified-IC JSON `r14 init-contract` builds
+            let ic: Vec<String> = svk.ic.iter().map(|s| format!("\"{s}\"")).collect();
+            let json = format!(
+                r#"{{"alpha_g1":"{}","beta_g2":"{}","gamma_g2":"{}","delta_g2":"{}","ic":[{}],"depth":{}}}"#,
+                svk.alpha_g1, svk.beta_g2, svk.gamma_g2, svk.delta_g2, ic.join(","), svk.depth
+            );
+            std::fs::write(&vk, json).with_context(|| format!("writing {}", vk.display()))?;
+            println!("circuit_id: {}", r14_sdk::serialize::circuit_id(&svk));
+        }
+    }
+    Ok(())
+}
+
+/// Set the family before the transfer circuit is synthesized, so `init`
+/// sets up the circuit the deployment verifies
+fn select_hash_family(explicit: Option<HashFamily>) -> Result<()> {
+    let hash = r14_sdk::deployment::resolve_hash_family(explicit)?;
+    r14_sdk::set_hash_family(hash).expect("nothing has hashed yet");
+    eprintln!("hash family: {}", hash.name());
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Ceremony> {
+    let f = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    Ceremony::read(BufReader::new(f)).with_context(|| format!("reading {}", path.display()))
+}
+
+fn save(c: &Ceremony, path: &Path) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    c.write(BufWriter::new(File::create(&tmp)?))
+        .with_context(|| format!("writing {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("writing {}", path.display()))
+}
@@ -0,0 +1,131 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Ceremony tests on a toy circuit; the transfer circuit's setup is too
+//! slow for a debug test run and exercises nothing extra here.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_groth16::Groth16;
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_ceremony::Ceremony;
+
+/// Knowledge of `x, y` with `x · y + x = z`, `z` public
+#[derive(Clone)]
+struct Toy {
+    x: Fr,
+    y: Fr,
+}
+
+impl ConstraintSynthesizer<Fr> for Toy {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let z = FpVar::new_input(cs.clone(), || Ok(self.x * self.y + self.x))?;
+        let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+        let y = FpVar::new_witness(cs, || Ok(self.y))?;
+        (&x * &y + &x).enforce_equal(&z)
+    }
+}
+
+fn toy() -> Toy {
+    Toy { x: Fr::from(3u64), y: Fr::from(5u64) }
+}
+
+fn ceremony(contributions: u64) -> Ceremony {
+    let mut rng = StdRng::seed_from_u64(42);
+    let (pk, _vk) = Groth16::<Bls12_381>::circuit_specific_setup(toy(), &mut rng).unwrap();
+    let mut c = Ceremony::new(pk);
+    for i in 0..contributions {
+        c.contribute(&mut StdRng::seed_from_u64(100 + i));
+    }
+    c
+}
+
+#[test]
+fn contribution_chain_verifies() {
+    let c = ceremony(3);
+    c.verify().unwrap();
+    assert_ne!(c.current.delta_g1, c.initial.delta_g1);
+    assert_ne!(c.current.h_query, c.initial.h_query);
+}
+
+#[test]
+fn empty_ceremony_verifies() {
+    ceremony(0).verify().unwrap();
+}
+
+#[test]
+fn final_keys_prove_and_verify() {
+    let c = ceremony(2);
+    let mut rng = StdRng::seed_from_u64(7);
+    let proof = Groth16::<Bls12_381>::prove(c.proving_key(), toy(), &mut rng).unwrap();
+    assert!(Groth16::<Bls12_381>::verify(c.verifying_key(), &[Fr::from(18u64)], &proof).unwrap());
+    assert!(!Groth16::<Bls12_381>::verify(c.verifying_key(), &[Fr::from(19u64)], &proof).unwrap());
+
+    // the initial VK no longer matches
+    assert!(!Groth16::<Bls12_381>::verify(&c.initial.vk, &[Fr::from(18u64)], &proof).unwrap());
+}
+
+#[test]
+fn receipts_are_in_the_chain() {
+    let mut c = ceremony(1);
+    let receipt = c.contribute(&mut StdRng::seed_from_u64(9));
+    assert!(c.contains(&receipt));
+    assert_eq!(receipt, c.transcript(c.contributions.len()));
+    assert!(!c.contains(&c.transcript(0)));
+}
+
+#[test]
+fn tampered_delta_fails() {
+    let mut c = ceremony(2);
+    // swap delta for a value with no matching proof
+    let g = G1Affine::generator();
+    c.current.delta_g1 = (g * Fr::from(7u64)).into_affine();
+    assert!(c.verify().is_err());
+}
+
+#[test]
+fn tampered_query_fails() {
+    let mut c = ceremony(2);
+    c.current.h_query[0] = (c.current.h_query[0] * Fr::from(2u64)).into_affine();
+    let err = c.verify().unwrap_err().to_string();
+    assert!(err.contains("h_query"), "{err}");
+}
+
+#[test]
+fn tampered_proof_of_knowledge_fails() {
+    let mut c = ceremony(2);
+    c.contributions[0].s_delta = (c.contributions[0].s_delta * Fr::from(2u64)).into_affine();
+    assert!(c.verify().is_err());
+}
+
+#[test]
+fn dropped_contribution_fails() {
+    let mut c = ceremony(3);
+    c.contributions.remove(1);
+    assert!(c.verify().is_err());
+}
+
+#[test]
+fn changed_alpha_fails() {
+    let mut c = ceremony(1);
+    c.current.vk.alpha_g1 = G1Affine::generator();
+    let err = c.verify().unwrap_err().to_string();
+    assert!(err.contains("other than delta"), "{err}");
+}
+
+#[test]
+fn file_roundtrip() {
+    let c = ceremony(2);
+    let mut bytes = Vec::new();
+    c.write(&mut bytes).unwrap();
+    let back = Ceremony::read(bytes.as_slice()).unwrap();
+    back.verify().unwrap();
+    assert_eq!(back.contributions, c.contributions);
+    assert_eq!(back.transcript(2), c.transcript(2));
+
+    bytes[0] ^= 1;
+    assert!(Ceremony::read(bytes.as_slice()).is_err());
+}
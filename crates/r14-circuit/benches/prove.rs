@@ -0,0 +1,79 @@
+//! Transfer proving time, split into witness generation (synthesizing the
+//! constraint system with a witness) and a whole `prove()`. With the
+//! `parallel` feature it also proves on a one-thread rayon pool, so the
+//! speedup on this machine shows next to the default pool.
+//!
+//! cargo bench -p r14-circuit --bench prove
+//! cargo bench -p r14-circuit --bench prove --features parallel
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use criterion::{criterion_group, criterion_main, Criterion};
+use r14_circuit::TransferCircuit;
+use r14_types::{MerklePath, Note, SecretKey, MERKLE_DEPTH};
+
+struct Witness {
+    sk: Fr,
+    consumed: Note,
+    path: MerklePath,
+    created: [Note; 2],
+}
+
+fn witness(rng: &mut StdRng) -> Witness {
+    let sk = SecretKey::random(rng);
+    let owner = r14_poseidon::owner_hash(&sk).0;
+    Witness {
+        sk: sk.0,
+        consumed: Note::new(1000, 1, owner, rng),
+        path: MerklePath {
+            siblings: (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect(),
+            indices: (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect(),
+        },
+        created: [Note::new(700, 1, owner, rng), Note::new(300, 1, owner, rng)],
+    }
+}
+
+fn bench_prove(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let (pk, _) = r14_circuit::setup(&mut rng).unwrap();
+    let w = witness(&mut rng);
+    let prove = |rng: &mut StdRng| {
+        r14_circuit::prove(&pk, w.sk, w.consumed.clone(), w.path.clone(), w.created.clone(), 0, rng).unwrap()
+    };
+
+    let mut group = c.benchmark_group("transfer");
+    group.sample_size(10);
+
+    // what Groth16 runs before the witness map and MSMs
+    group.bench_function("witness", |b| {
+        b.iter(|| {
+            let circuit: TransferCircuit = TransferCircuit {
+                secret_key: Some(w.sk),
+                consumed_note: Some(w.consumed.clone()),
+                merkle_path: Some(w.path.clone()),
+                created_notes: Some(w.created.clone()),
+                current_ledger: Some(0),
+            };
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            cs.set_optimization_goal(OptimizationGoal::Constraints);
+            circuit.generate_constraints(cs.clone()).unwrap();
+            cs.finalize();
+            cs
+        })
+    });
+
+    group.bench_function("prove", |b| b.iter(|| prove(&mut rng)));
+
+    #[cfg(feature = "parallel")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        group.bench_function("prove/1 thread", |b| pool.install(|| b.iter(|| prove(&mut rng))));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_prove);
+criterion_main!(benches);
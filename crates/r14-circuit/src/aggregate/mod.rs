@@ -0,0 +1,307 @@
+//! SnarkPack-style aggregation of Groth16 proofs.
+//!
+//! A relayer holding n transfer proofs for the same verifying key folds
+//! them into one [`AggregateProof`]. Its size grows with log n, and
+//! checking it takes a fixed number of pairings plus one multi-scalar
+//! multiplication over the public inputs, where checking the proofs one by
+//! one takes n + 3 pairings.
+//!
+//! The prover commits to the proofs' A, B and C points under keys from an
+//! [`AggregationSrs`], draws a challenge r, and claims
+//! `Z = ∏ e(Aᵢ, Bᵢ)^(rⁱ)` and `Σ rⁱ·Cᵢ`. It then folds the vectors in
+//! halves, one challenge per round, until one proof is left, and opens the
+//! folded keys with KZG. If every round checks out, Z pairs to what the
+//! Groth16 equation predicts for n valid proofs, except with negligible
+//! probability. A batch that isn't a power of two is padded by repeating
+//! its last proof, on both sides.
+//!
+//! r14-core can't check an aggregate: the verifier multiplies and
+//! exponentiates pairing outputs, and Soroban only offers `pairing_check`.
+//! Aggregates are verified off-chain:
+//!
+//! ```rust,ignore
+//! let srs = r14_circuit::aggregate::AggregationSrs::setup(64, &mut rng);
+//! let inputs: Vec<Vec<Fr>> = public_inputs.iter().map(|pi| pi.to_vec()).collect();
+//! let aggregate = r14_circuit::aggregate::aggregate(&srs, &vk, &proofs, &inputs)?;
+//! assert!(r14_circuit::aggregate::verify_offchain(&srs.key(), &vk, &inputs, &aggregate));
+//! ```
+
+mod prover;
+mod srs;
+mod verifier;
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+use ark_ec::pairing::PairingOutput;
+use ark_ff::{Field, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::transcript::Transcript;
+use crate::CircuitError;
+
+pub use srs::{AggregationKey, AggregationSrs};
+
+type Gt = PairingOutput<Bls12_381>;
+
+/// Cross terms of one folding round, each as `[left, right]`
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Round {
+    /// `e(A_R, B_L)` and `e(A_L, B_R)`
+    pub z_ab: [Gt; 2],
+    /// The AB commitment's cross terms under the a and b keys
+    pub com_ab: [[Gt; 2]; 2],
+    /// `⟨C_R, r_L⟩` and `⟨C_L, r_R⟩`
+    pub z_c: [G1Affine; 2],
+    /// The C commitment's cross terms under the a and b keys
+    pub com_c: [[Gt; 2]; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregateProof {
+    /// Commitments to the A and B points, and to the C points
+    pub com_ab: [Gt; 2],
+    pub com_c: [Gt; 2],
+    /// `∏ e(Aᵢ, Bᵢ)^(rⁱ)` and `Σ rⁱ·Cᵢ`
+    pub z_ab: Gt,
+    pub z_c: G1Affine,
+    pub rounds: Vec<Round>,
+    /// The proof left after folding
+    pub a: G1Affine,
+    pub b: G2Affine,
+    pub c: G1Affine,
+    /// The folded commitment keys, and KZG openings of each
+    pub v: [G2Affine; 2],
+    pub w: [G1Affine; 2],
+    pub v_openings: [G2Affine; 2],
+    pub w_openings: [G1Affine; 2],
+}
+
+/// Aggregate `proofs`, each verifying against `vk` with the matching entry
+/// of `public_inputs`. Proofs that don't verify still aggregate; the
+/// aggregate just won't verify either.
+///
+/// Fails with `CircuitError::InvalidWitness` if there are no proofs, the
+/// counts differ, or the batch (padded to a power of two) is larger than
+/// `srs` supports.
+pub fn aggregate(
+    srs: &AggregationSrs,
+    vk: &VerifyingKey<Bls12_381>,
+    proofs: &[Proof<Bls12_381>],
+    public_inputs: &[Vec<Fr>],
+) -> Result<AggregateProof, CircuitError> {
+    if proofs.is_empty() || proofs.len() != public_inputs.len() {
+        return Err(CircuitError::InvalidWitness(format!(
+            "{} proofs with {} sets of public inputs",
+            proofs.len(),
+            public_inputs.len()
+        )));
+    }
+    let proofs = padded(proofs);
+    if proofs.len() > srs.max_proofs() {
+        return Err(CircuitError::InvalidWitness(format!(
+            "{} proofs (padded) but the reference string holds {}",
+            proofs.len(),
+            srs.max_proofs()
+        )));
+    }
+    Ok(prover::aggregate(srs, transcript(vk, &padded(public_inputs)), &proofs))
+}
+
+/// Verify an aggregate of proofs for `public_inputs`, in the order they
+/// were aggregated
+pub fn verify_offchain(
+    key: &AggregationKey,
+    vk: &VerifyingKey<Bls12_381>,
+    public_inputs: &[Vec<Fr>],
+    proof: &AggregateProof,
+) -> bool {
+    if public_inputs.is_empty() {
+        return false;
+    }
+    let public_inputs = padded(public_inputs);
+    verifier::verify(key, vk, transcript(vk, &public_inputs), &public_inputs, proof)
+}
+
+/// `items`, with the last repeated up to the next power of two
+fn padded<T: Clone>(items: &[T]) -> Vec<T> {
+    let mut items = items.to_vec();
+    if let Some(last) = items.last().cloned() {
+        items.resize(items.len().next_power_of_two(), last);
+    }
+    items
+}
+
+/// The transcript both sides start from: the key and every statement
+fn transcript(vk: &VerifyingKey<Bls12_381>, public_inputs: &[Vec<Fr>]) -> Transcript {
+    let mut transcript = Transcript::new(b"r14-aggregate-v1");
+    transcript.append(vk);
+    transcript.append(public_inputs);
+    transcript
+}
+
+/// `1, x, x², …` (`n` terms)
+fn powers(x: Fr, n: usize) -> Vec<Fr> {
+    let mut powers = Vec::with_capacity(n);
+    let mut power = Fr::ONE;
+    for _ in 0..n {
+        powers.push(power);
+        power *= x;
+    }
+    powers
+}
+
+/// Coefficients of `∏ⱼ (1 + cⱼ·X^(hⱼ))`, the polynomial folding builds
+/// into a key: round j halves the vectors to length hⱼ, and
+/// `cⱼ = coefficient(xⱼ, hⱼ)` for its challenge xⱼ
+fn fold_coefficients(challenges: &[Fr], coefficient: impl Fn(Fr, usize) -> Fr) -> Vec<Fr> {
+    let mut poly = vec![Fr::ONE];
+    for x in challenges.iter().rev() {
+        let c = coefficient(*x, poly.len());
+        let upper: Vec<Fr> = poly.iter().map(|p| c * p).collect();
+        poly.extend(upper);
+    }
+    poly
+}
+
+/// [`fold_coefficients`]' polynomial at `point`, without expanding it
+fn evaluate_folded(challenges: &[Fr], point: Fr, coefficient: impl Fn(Fr, usize) -> Fr) -> Fr {
+    let rounds = challenges.len();
+    let mut product = Fr::ONE;
+    for (j, x) in challenges.iter().enumerate() {
+        let h = 1usize << (rounds - 1 - j);
+        product *= Fr::ONE + coefficient(*x, h) * point.pow([h as u64]);
+    }
+    product
+}
+
+/// `(p(X) − p(z)) / (X − z)`
+fn divide_by_linear(p: &[Fr], z: Fr) -> Vec<Fr> {
+    let mut quotient = vec![Fr::zero(); p.len().saturating_sub(1)];
+    let mut carry = Fr::zero();
+    for i in (1..p.len()).rev() {
+        carry = p[i] + carry * z;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_groth16::Groth16;
+    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_snark::SNARK;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::{MerklePath, Note, SecretKey, MERKLE_DEPTH};
+
+    /// `out = x·y + x`, `out` public
+    #[derive(Clone)]
+    struct Toy {
+        x: Option<Fr>,
+        y: Option<Fr>,
+    }
+
+    impl ConstraintSynthesizer<Fr> for Toy {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let out = self.x.zip(self.y).map(|(x, y)| x * y + x);
+            let out = FpVar::new_input(cs.clone(), || out.ok_or(SynthesisError::AssignmentMissing))?;
+            let x = FpVar::new_witness(cs.clone(), || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = FpVar::new_witness(cs, || self.y.ok_or(SynthesisError::AssignmentMissing))?;
+            (&x * &y + &x).enforce_equal(&out)
+        }
+    }
+
+    type Batch = (VerifyingKey<Bls12_381>, Vec<Proof<Bls12_381>>, Vec<Vec<Fr>>);
+
+    fn toy_batch(n: usize, rng: &mut StdRng) -> Batch {
+        let (pk, vk) = Groth16::<Bls12_381>::circuit_specific_setup(Toy { x: None, y: None }, rng).unwrap();
+        let (mut proofs, mut inputs) = (Vec::new(), Vec::new());
+        for _ in 0..n {
+            let (x, y) = (Fr::rand(rng), Fr::rand(rng));
+            proofs.push(Groth16::<Bls12_381>::prove(&pk, Toy { x: Some(x), y: Some(y) }, rng).unwrap());
+            inputs.push(vec![x * y + x]);
+        }
+        (vk, proofs, inputs)
+    }
+
+    #[test]
+    fn aggregates_verify_and_bind_every_statement() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let srs = AggregationSrs::setup(8, &mut rng);
+        let key = srs.key();
+        // five proofs pad to eight
+        let (vk, proofs, inputs) = toy_batch(5, &mut rng);
+        let agg = aggregate(&srs, &vk, &proofs, &inputs).unwrap();
+        assert_eq!(agg.rounds.len(), 3);
+        assert!(verify_offchain(&key, &vk, &inputs, &agg));
+
+        let mut bytes = Vec::new();
+        agg.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(AggregateProof::deserialize_compressed(&bytes[..]).unwrap(), agg);
+
+        let mut wrong = inputs.clone();
+        wrong[2][0] += Fr::ONE;
+        assert!(!verify_offchain(&key, &vk, &wrong, &agg));
+        let mut reordered = inputs.clone();
+        reordered.swap(0, 1);
+        assert!(!verify_offchain(&key, &vk, &reordered, &agg));
+        assert!(!verify_offchain(&key, &vk, &inputs[..4], &agg));
+
+        let mut forged = agg.clone();
+        forged.z_c = (forged.z_c + forged.c).into();
+        assert!(!verify_offchain(&key, &vk, &inputs, &forged));
+        let mut forged = agg;
+        forged.rounds[1].z_ab.swap(0, 1);
+        assert!(!verify_offchain(&key, &vk, &inputs, &forged));
+    }
+
+    #[test]
+    fn an_invalid_proof_spoils_the_aggregate() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let srs = AggregationSrs::setup(4, &mut rng);
+        let (vk, mut proofs, inputs) = toy_batch(4, &mut rng);
+        // a valid proof of the wrong statement
+        proofs.swap(1, 3);
+        let agg = aggregate(&srs, &vk, &proofs, &inputs).unwrap();
+        assert!(!verify_offchain(&srs.key(), &vk, &inputs, &agg));
+
+        // a single proof needs no folding
+        let agg = aggregate(&srs, &vk, &proofs[..1], &inputs[..1]).unwrap();
+        assert!(agg.rounds.is_empty());
+        assert!(verify_offchain(&srs.key(), &vk, &inputs[..1], &agg));
+
+        let err = aggregate(&srs, &vk, &[], &[]).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+        let (vk, proofs, inputs) = toy_batch(5, &mut rng);
+        let err = aggregate(&srs, &vk, &proofs, &inputs).err().unwrap();
+        assert!(err.to_string().contains("reference string holds 4"), "{err}");
+    }
+
+    #[test]
+    fn transfer_proofs_aggregate() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (pk, vk) = crate::setup(&mut rng).unwrap();
+        let (mut proofs, mut inputs) = (Vec::new(), Vec::new());
+        for value in [500, 800] {
+            let sk = SecretKey::random(&mut rng);
+            let owner = r14_poseidon::owner_hash(&sk).0;
+            let consumed = Note::new(value, 1, owner, &mut rng);
+            let path = MerklePath {
+                siblings: (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect(),
+                indices: (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect(),
+            };
+            let outputs = [Note::new(value - 100, 1, Fr::rand(&mut rng), &mut rng), Note::new(100, 1, owner, &mut rng)];
+            let (proof, pi) = crate::prove(&pk, sk.0, consumed, path, outputs, 0, &mut rng).unwrap();
+            proofs.push(proof);
+            inputs.push(pi.to_vec());
+        }
+
+        let srs = AggregationSrs::setup(2, &mut rng);
+        let agg = aggregate(&srs, &vk, &proofs, &inputs).unwrap();
+        assert!(verify_offchain(&srs.key(), &vk, &inputs, &agg));
+        inputs[1][1] = Fr::rand(&mut rng);
+        assert!(!verify_offchain(&srs.key(), &vk, &inputs, &agg));
+    }
+}
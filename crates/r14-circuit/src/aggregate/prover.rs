@@ -0,0 +1,125 @@
+//! Commit, fold the proofs in halves until one is left, then open the
+//! folded commitment keys
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field, Zero};
+use ark_groth16::Proof;
+
+use super::{divide_by_linear, fold_coefficients, powers, AggregateProof, AggregationSrs, Gt, Round};
+use crate::transcript::Transcript;
+
+pub(crate) fn aggregate(srs: &AggregationSrs, mut transcript: Transcript, proofs: &[Proof<Bls12_381>]) -> AggregateProof {
+    let m = proofs.len();
+    let mut a: Vec<G1Affine> = proofs.iter().map(|p| p.a).collect();
+    let mut b: Vec<G2Affine> = proofs.iter().map(|p| p.b).collect();
+    let mut c: Vec<G1Affine> = proofs.iter().map(|p| p.c).collect();
+    let mut v = srs.g2.clone().map(|mut g2| {
+        g2.truncate(m);
+        g2
+    });
+    let mut w = srs.g1.clone().map(|g1| g1[m..2 * m].to_vec());
+
+    let com_ab = [0, 1].map(|k| pair(&a, &v[k]) + pair(&w[k], &b));
+    let com_c = [0, 1].map(|k| pair(&c, &v[k]));
+    transcript.append(&com_ab);
+    transcript.append(&com_c);
+    let r = transcript.challenge();
+
+    // e(Aᵢ, Bᵢ)^(rⁱ) through Bᵢ·rⁱ; the w keys take r⁻ⁱ so the AB
+    // commitment still opens to the same value
+    let mut rs = powers(r, m);
+    let mut r_inv = rs.clone();
+    batch_inversion(&mut r_inv);
+    b = scale(&b, &rs);
+    for w in &mut w {
+        *w = scale(w, &r_inv);
+    }
+    let z_ab = pair(&a, &b);
+    let z_c: G1Affine = G1Projective::msm_unchecked(&c, &rs).into();
+    transcript.append(&z_ab);
+    transcript.append(&z_c);
+
+    let mut rounds = Vec::new();
+    let mut challenges = Vec::new();
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_l, a_r) = a.split_at(half);
+        let (b_l, b_r) = b.split_at(half);
+        let (c_l, c_r) = c.split_at(half);
+        let (r_l, r_r) = rs.split_at(half);
+        let round = Round {
+            z_ab: [pair(a_r, b_l), pair(a_l, b_r)],
+            com_ab: [0, 1].map(|k| {
+                let (v_l, v_r) = v[k].split_at(half);
+                let (w_l, w_r) = w[k].split_at(half);
+                [pair(a_r, v_l) + pair(w_r, b_l), pair(a_l, v_r) + pair(w_l, b_r)]
+            }),
+            z_c: [G1Projective::msm_unchecked(c_r, r_l).into(), G1Projective::msm_unchecked(c_l, r_r).into()],
+            com_c: [0, 1].map(|k| {
+                let (v_l, v_r) = v[k].split_at(half);
+                [pair(c_r, v_l), pair(c_l, v_r)]
+            }),
+        };
+        transcript.append(&round);
+        let x = transcript.challenge();
+        let x_inv = x.inverse().expect("a hash output is zero with negligible probability");
+
+        a = fold(a_l, a_r, x);
+        b = fold(b_l, b_r, x_inv);
+        c = fold(c_l, c_r, x);
+        rs = r_l.iter().zip(r_r).map(|(l, r)| *l + x_inv * r).collect();
+        for k in 0..2 {
+            let (v_l, v_r) = v[k].split_at(half);
+            let (w_l, w_r) = w[k].split_at(half);
+            (v[k], w[k]) = (fold(v_l, v_r, x_inv), fold(w_l, w_r, x));
+        }
+        rounds.push(round);
+        challenges.push(x);
+    }
+
+    let (v, w) = (v.map(|v| v[0]), w.map(|w| w[0]));
+    transcript.append(&(a[0], b[0], c[0], v, w));
+    let z = transcript.challenge();
+
+    // v* = [f_v(s)]₂ and w* = [sᵐ·f_w(s)]₁ for the key's secret s
+    let f_v = fold_coefficients(&challenges, |x, _| x.inverse().expect("checked when folding"));
+    let f_w = fold_coefficients(&challenges, |x, h| x * r.pow([h as u64]).inverse().expect("r is nonzero"));
+    let mut shifted = vec![Fr::zero(); m];
+    shifted.extend(f_w);
+    let (q_v, q_w) = (divide_by_linear(&f_v, z), divide_by_linear(&shifted, z));
+    let v_openings = [0, 1].map(|k| G2Projective::msm_unchecked(&srs.g2[k][..q_v.len()], &q_v).into());
+    let w_openings = [0, 1].map(|k| G1Projective::msm_unchecked(&srs.g1[k][..q_w.len()], &q_w).into());
+
+    AggregateProof {
+        com_ab,
+        com_c,
+        z_ab,
+        z_c,
+        rounds,
+        a: a[0],
+        b: b[0],
+        c: c[0],
+        v,
+        w,
+        v_openings,
+        w_openings,
+    }
+}
+
+/// `∏ e(gᵢ, hᵢ)`
+fn pair(g: &[G1Affine], h: &[G2Affine]) -> Gt {
+    Bls12_381::multi_pairing(g, h)
+}
+
+/// `lᵢ + x·rᵢ`
+fn fold<P: AffineRepr<ScalarField = Fr>>(l: &[P], r: &[P], x: Fr) -> Vec<P> {
+    let folded: Vec<P::Group> = l.iter().zip(r).map(|(l, r)| *r * x + l).collect();
+    P::Group::normalize_batch(&folded)
+}
+
+/// `pᵢ·sᵢ`
+fn scale<P: AffineRepr<ScalarField = Fr>>(points: &[P], scalars: &[Fr]) -> Vec<P> {
+    let scaled: Vec<P::Group> = points.iter().zip(scalars).map(|(p, s)| *p * s).collect();
+    P::Group::normalize_batch(&scaled)
+}
@@ -0,0 +1,64 @@
+//! Reference string for aggregation: powers of two secrets a and b in both
+//! groups
+
+use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{scalar_mul::ScalarMul, PrimeGroup};
+use ark_ff::{Field, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{CryptoRng, RngCore};
+
+/// `[sⁱ]₁` for i < 2n and `[sⁱ]₂` for i < n, for s = a and s = b. The
+/// commitment keys are the first n G2 powers and the G1 powers from n on,
+/// so the two halves of a commitment never share an exponent.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregationSrs {
+    pub g1: [Vec<G1Affine>; 2],
+    pub g2: [Vec<G2Affine>; 2],
+}
+
+/// What the verifier needs from an [`AggregationSrs`]: the generators and
+/// the first power of each secret
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregationKey {
+    pub g: G1Affine,
+    pub h: G2Affine,
+    pub g1: [G1Affine; 2],
+    pub g2: [G2Affine; 2],
+}
+
+impl AggregationSrs {
+    /// Sample a and b and compute the powers for up to `max_proofs` proofs,
+    /// rounded up to a power of two. Whoever runs this could forge
+    /// aggregates, so production strings come from a ceremony.
+    pub fn setup<R: RngCore + CryptoRng>(max_proofs: usize, rng: &mut R) -> Self {
+        let n = max_proofs.max(2).next_power_of_two();
+        let powers = |s: Fr| -> Vec<Fr> {
+            let mut powers = Vec::with_capacity(2 * n);
+            let mut power = Fr::ONE;
+            for _ in 0..2 * n {
+                powers.push(power);
+                power *= s;
+            }
+            powers
+        };
+        let [a, b] = [Fr::rand(rng), Fr::rand(rng)].map(powers);
+        Self {
+            g1: [&a, &b].map(|p| G1Projective::generator().batch_mul(p)),
+            g2: [&a, &b].map(|p| G2Projective::generator().batch_mul(&p[..n])),
+        }
+    }
+
+    /// Most proofs one aggregate can hold
+    pub fn max_proofs(&self) -> usize {
+        self.g2[0].len()
+    }
+
+    pub fn key(&self) -> AggregationKey {
+        AggregationKey {
+            g: self.g1[0][0],
+            h: self.g2[0][0],
+            g1: [self.g1[0][1], self.g1[1][1]],
+            g2: [self.g2[0][1], self.g2[1][1]],
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Replay the transcript, fold the claimed values with the round
+//! challenges, and check them against the final proof and the opened keys
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_groth16::VerifyingKey;
+
+use super::{evaluate_folded, AggregateProof, AggregationKey};
+use crate::transcript::Transcript;
+
+pub(crate) fn verify(
+    key: &AggregationKey,
+    vk: &VerifyingKey<Bls12_381>,
+    mut transcript: Transcript,
+    public_inputs: &[Vec<Fr>],
+    proof: &AggregateProof,
+) -> bool {
+    let m = public_inputs.len();
+    if !m.is_power_of_two() || proof.rounds.len() != m.trailing_zeros() as usize {
+        return false;
+    }
+    if public_inputs.iter().any(|x| x.len() + 1 != vk.gamma_abc_g1.len()) {
+        return false;
+    }
+    let g1_points = [proof.z_c, proof.a, proof.c, proof.w[0], proof.w[1], proof.w_openings[0], proof.w_openings[1]];
+    let g2_points = [proof.b, proof.v[0], proof.v[1], proof.v_openings[0], proof.v_openings[1]];
+    let round_points = proof.rounds.iter().flat_map(|r| r.z_c);
+    let g1_ok = |p: G1Affine| p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve();
+    let g2_ok = |p: G2Affine| p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve();
+    if !g1_points.into_iter().chain(round_points).all(g1_ok) || !g2_points.into_iter().all(g2_ok) {
+        return false;
+    }
+
+    let (mut com_ab, mut com_c, mut z_ab) = (proof.com_ab, proof.com_c, proof.z_ab);
+    let mut z_c = proof.z_c.into_group();
+    transcript.append(&proof.com_ab);
+    transcript.append(&proof.com_c);
+    let r = transcript.challenge();
+    transcript.append(&proof.z_ab);
+    transcript.append(&proof.z_c);
+
+    let mut challenges = Vec::with_capacity(proof.rounds.len());
+    for round in &proof.rounds {
+        transcript.append(round);
+        let x = transcript.challenge();
+        let Some(x_inv) = x.inverse() else {
+            return false;
+        };
+        z_ab += round.z_ab[0] * x + round.z_ab[1] * x_inv;
+        z_c += round.z_c[0] * x + round.z_c[1] * x_inv;
+        for k in 0..2 {
+            com_ab[k] += round.com_ab[k][0] * x + round.com_ab[k][1] * x_inv;
+            com_c[k] += round.com_c[k][0] * x + round.com_c[k][1] * x_inv;
+        }
+        challenges.push(x);
+    }
+    transcript.append(&(proof.a, proof.b, proof.c, proof.v, proof.w));
+    let z = transcript.challenge();
+    let Some(r_inv) = r.inverse() else {
+        return false;
+    };
+
+    // the folded values are the final single proof's
+    let pair = |g: G1Affine, h: G2Affine| Bls12_381::pairing(g, h);
+    for k in 0..2 {
+        if com_ab[k] != pair(proof.a, proof.v[k]) + pair(proof.w[k], proof.b) || com_c[k] != pair(proof.c, proof.v[k])
+        {
+            return false;
+        }
+    }
+    let inverse = |x: Fr, _| x.inverse().expect("checked when folding");
+    let r_star = evaluate_folded(&challenges, r, inverse);
+    if z_ab != pair(proof.a, proof.b) || z_c != proof.c * r_star {
+        return false;
+    }
+
+    // ... under keys that open to the folding polynomials at z
+    let f_v = evaluate_folded(&challenges, z, inverse);
+    let f_w = z.pow([m as u64]) * evaluate_folded(&challenges, z, |x, h| x * r_inv.pow([h as u64]));
+    let (g, h) = (key.g.into_group(), key.h.into_group());
+    for k in 0..2 {
+        let v_ok = Bls12_381::multi_pairing(
+            [(key.g1[k] - g * z).into_affine(), -key.g],
+            [proof.v_openings[k], (proof.v[k] - h * f_v).into_affine()],
+        );
+        let w_ok = Bls12_381::multi_pairing(
+            [(proof.w[k] - g * f_w).into_affine(), -proof.w_openings[k]],
+            [key.h, (key.g2[k] - h * z).into_affine()],
+        );
+        if !v_ok.is_zero() || !w_ok.is_zero() {
+            return false;
+        }
+    }
+
+    // and ∏ e(Aᵢ, Bᵢ)^(rⁱ) is what m valid Groth16 proofs pair to
+    let rs = super::powers(r, m);
+    let mut scalars = vec![rs.iter().sum::<Fr>()];
+    scalars.extend((0..vk.gamma_abc_g1.len() - 1).map(|j| rs.iter().zip(public_inputs).map(|(r, x)| *r * x[j]).sum::<Fr>()));
+    let inputs: G1Affine = G1Projective::msm_unchecked(&vk.gamma_abc_g1, &scalars).into();
+    let expected = pair(vk.alpha_g1, vk.beta_g2) * scalars[0] + pair(inputs, vk.gamma_g2) + pair(proof.z_c, vk.delta_g2);
+    proof.z_ab == expected
+}
@@ -0,0 +1,271 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, fields::fp::FpVar};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_types::{MerklePath, Note, MERKLE_DEPTH};
+
+use crate::merkle_gadget::verify_merkle_path;
+use crate::transfer::{enforce_unaudited, TransferCircuit};
+use crate::{check_merkle_path, synthesis_error, CircuitError, PublicInputs};
+
+/// Transfer that also proves the consumed note is in an association set.
+///
+/// An association set is a Merkle tree of deposit commitments that some
+/// provider has approved. The proof shows the spent note is one of them
+/// without saying which, so an integrator can accept only funds from an
+/// approved set. The set uses the same depth and hashing as the commitment
+/// tree, and its root is a sixth public input after the transfer's five.
+#[derive(Clone)]
+pub struct AssociationCircuit {
+    pub transfer: TransferCircuit,
+    // Private witness
+    pub association_path: Option<MerklePath>,
+}
+
+impl AssociationCircuit {
+    /// Create a circuit with None witnesses (for setup)
+    pub fn empty() -> Self {
+        Self {
+            transfer: TransferCircuit::empty(),
+            association_path: None,
+        }
+    }
+}
+
+/// Public inputs for an association proof
+pub struct AssociationPublicInputs {
+    pub transfer: PublicInputs,
+    pub association_root: Fr,
+}
+
+impl AssociationPublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        let mut v = self.transfer.to_vec();
+        v.push(self.association_root);
+        v
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for AssociationCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let consumed = self.transfer.consumed_note.as_ref();
+        let leaf = consumed.map(r14_poseidon::commitment);
+        let app_tag = consumed.map(|n| n.app_tag);
+
+        // === Constraints 1-8: the transfer itself ===
+        let vars = self.transfer.synthesize(cs.clone())?;
+        enforce_unaudited(cs.clone(), &vars.app_tag, app_tag)?;
+
+        // === Public input 6: association_root ===
+        let association_root_pub = FpVar::new_input(cs.clone(), || {
+            let leaf = leaf.ok_or(SynthesisError::AssignmentMissing)?;
+            let path = self.association_path.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(crate::compute_root(leaf, path))
+        })?;
+
+        let witnesses_ns = ns!(cs, "witnesses");
+        let mut path_vars: Vec<(FpVar<Fr>, Boolean<Fr>)> = Vec::with_capacity(MERKLE_DEPTH);
+        for i in 0..MERKLE_DEPTH {
+            let sibling = FpVar::new_witness(cs.clone(), || {
+                let path = self.association_path.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+                path.siblings.get(i).copied().ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let index_bit = Boolean::new_witness(cs.clone(), || {
+                let path = self.association_path.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+                path.indices.get(i).copied().ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            path_vars.push((sibling, index_bit));
+        }
+        drop(witnesses_ns);
+
+        // === Constraint 9: Association membership ===
+        // the same commitment hashes up to association_root
+        let _ns = ns!(cs, "association");
+        verify_merkle_path(cs.clone(), &vars.consumed_cm, &path_vars, &association_root_pub)
+    }
+}
+
+/// Run Groth16 trusted setup for the association circuit
+pub fn setup_association<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>), CircuitError> {
+    let circuit = AssociationCircuit::empty();
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).map_err(synthesis_error)
+}
+
+/// Generate a Groth16 proof for a transfer whose input is in an association
+/// set. `association_path` leads from the consumed note's commitment to the
+/// set's root.
+///
+/// Fails like [`crate::prove`], and also when `association_path` isn't
+/// `MERKLE_DEPTH` long.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_association<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    association_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<Bls12_381>, AssociationPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = association_witness(
+        secret_key,
+        consumed_note,
+        merkle_path,
+        association_path,
+        created_notes,
+        current_ledger,
+    )?;
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).map_err(synthesis_error)?;
+    Ok((proof, public_inputs))
+}
+
+/// Check the witness and build the association circuit and its public inputs
+pub(crate) fn association_witness(
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    association_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+) -> Result<(AssociationCircuit, AssociationPublicInputs), CircuitError> {
+    check_merkle_path(&merkle_path)?;
+    check_merkle_path(&association_path)?;
+    if consumed_note.unlock_after > current_ledger {
+        return Err(CircuitError::InvalidWitness(format!(
+            "note is locked until ledger {}, proving at {current_ledger}",
+            consumed_note.unlock_after
+        )));
+    }
+    crate::check_unaudited(&consumed_note)?;
+
+    let leaf = r14_poseidon::commitment(&consumed_note);
+    let public_inputs = AssociationPublicInputs {
+        transfer: PublicInputs {
+            old_root: crate::compute_root(leaf, &merkle_path),
+            nullifier: r14_poseidon::nullifier_hash(secret_key, consumed_note.nonce, merkle_path.leaf_index()),
+            out_commitment_0: r14_poseidon::commitment(&created_notes[0]).0,
+            out_commitment_1: r14_poseidon::commitment(&created_notes[1]).0,
+            current_ledger: Fr::from(current_ledger),
+        },
+        association_root: crate::compute_root(leaf, &association_path),
+    };
+
+    let circuit = AssociationCircuit {
+        transfer: TransferCircuit {
+            secret_key: Some(secret_key),
+            consumed_note: Some(consumed_note),
+            merkle_path: Some(merkle_path),
+            created_notes: Some(created_notes),
+            current_ledger: Some(current_ledger),
+        },
+        association_path: Some(association_path),
+    };
+
+    Ok((circuit, public_inputs))
+}
+
+/// Verify an association proof off-chain
+pub fn verify_association_offchain(
+    vk: &VerifyingKey<Bls12_381>,
+    proof: &ark_groth16::Proof<Bls12_381>,
+    public_inputs: &AssociationPublicInputs,
+) -> bool {
+    let pvk = PreparedVerifyingKey::from(vk.clone());
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs.to_vec(), proof)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::SecretKey;
+
+    fn random_path(rng: &mut StdRng) -> MerklePath {
+        let siblings: Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect();
+        let indices: Vec<bool> = (0..MERKLE_DEPTH).map(|_| bool::rand(rng)).collect();
+        MerklePath { siblings, indices }
+    }
+
+    fn scenario(rng: &mut StdRng) -> AssociationCircuit {
+        let sk = SecretKey::random(rng);
+        let owner = r14_poseidon::owner_hash(&sk);
+        let consumed = Note::new(1000, 1, owner.0, rng);
+        let created = [
+            Note::new(700, 1, Fr::rand(rng), rng),
+            Note::new(300, 1, owner.0, rng),
+        ];
+        AssociationCircuit {
+            transfer: TransferCircuit {
+                secret_key: Some(sk.0),
+                consumed_note: Some(consumed),
+                merkle_path: Some(random_path(rng)),
+                created_notes: Some(created),
+                current_ledger: Some(0),
+            },
+            association_path: Some(random_path(rng)),
+        }
+    }
+
+    fn is_satisfied(c: AssociationCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        c.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_member_note() {
+        let mut rng = StdRng::seed_from_u64(42);
+        assert!(is_satisfied(scenario(&mut rng)));
+    }
+
+    #[test]
+    fn test_transfer_constraints_still_apply() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut c = scenario(&mut rng);
+        c.transfer.secret_key = Some(Fr::rand(&mut rng));
+        assert!(!is_satisfied(c), "should fail: wrong secret key");
+    }
+
+    #[test]
+    fn test_wrapped_output_value() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let failures = crate::transfer::tests::wrapped_output_failures(scenario(&mut rng));
+        assert!(failures.iter().any(|s| s == "value_conservation"), "{failures:?}");
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let c = scenario(&mut rng);
+        let t = c.transfer;
+        let (pk, vk) = setup_association(&mut rng).unwrap();
+        let (proof, mut pi) = prove_association(
+            &pk,
+            t.secret_key.unwrap(),
+            t.consumed_note.unwrap(),
+            t.merkle_path.unwrap(),
+            c.association_path.unwrap(),
+            t.created_notes.unwrap(),
+            0,
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(vk.gamma_abc_g1.len(), 7);
+        assert!(verify_association_offchain(&vk, &proof, &pi));
+
+        // a note outside the set would need a different root
+        pi.association_root = Fr::rand(&mut rng);
+        assert!(!verify_association_offchain(&vk, &proof, &pi));
+    }
+}
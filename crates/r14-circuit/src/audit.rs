@@ -0,0 +1,423 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::{PrimeField, UniformRand};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    groups::CurveVar,
+};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_poseidon::AUDIT_DOMAIN;
+use r14_types::{MerklePath, Note};
+
+use crate::jubjub_gadget::{generator_mul, scalar_bits_witness};
+use crate::poseidon_gadget::poseidon_hash_var;
+use crate::transfer::TransferCircuit;
+use crate::{check_merkle_path, synthesis_error, CircuitError, PublicInputs};
+
+/// Auditor decryption key, a Jubjub scalar
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditorSecretKey(pub JubjubScalar);
+
+/// Auditor encryption key, `sk·G` on Jubjub. Its coordinates are
+/// BLS12-381 scalars, so the contract stores them like any other `Fr`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuditorPublicKey(pub EdwardsAffine);
+
+/// Outputs encrypted to an auditor: ElGamal over Jubjub for the shared
+/// point, Poseidon pads for the payload
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuditCiphertext {
+    /// `r·G`; the auditor recovers the shared point as `sk·ephemeral`
+    pub ephemeral: EdwardsAffine,
+    /// `[value_0, owner_0, value_1, owner_1]` plus their pads
+    pub data: [Fr; 4],
+}
+
+/// One output as the auditor sees it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditedOutput {
+    pub value: u64,
+    pub owner: Fr,
+}
+
+impl AuditorSecretKey {
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(JubjubScalar::rand(rng))
+    }
+
+    pub fn public_key(&self) -> AuditorPublicKey {
+        AuditorPublicKey((EdwardsAffine::generator() * self.0).into_affine())
+    }
+
+    /// Decrypt both outputs. `None` if a value doesn't fit in a `u64`,
+    /// which is what the wrong key produces.
+    pub fn decrypt(&self, ct: &AuditCiphertext) -> Option<[AuditedOutput; 2]> {
+        let shared = (ct.ephemeral * self.0).into_affine();
+        let pads = pads(&shared);
+        let m: Vec<Fr> = ct.data.iter().zip(&pads).map(|(c, p)| *c - p).collect();
+        let value = |f: &Fr| -> Option<u64> {
+            let bigint = f.into_bigint();
+            let limbs = bigint.as_ref();
+            limbs[1..].iter().all(|l| *l == 0).then_some(limbs[0])
+        };
+        Some([
+            AuditedOutput { value: value(&m[0])?, owner: m[1] },
+            AuditedOutput { value: value(&m[2])?, owner: m[3] },
+        ])
+    }
+}
+
+impl AuditorPublicKey {
+    /// `[x, y]`, the order the circuit takes them as public inputs
+    pub fn to_fields(&self) -> [Fr; 2] {
+        [self.0.x, self.0.y]
+    }
+}
+
+/// Pad `i` is Poseidon(domain, shared.x, shared.y, i)
+fn pads(shared: &EdwardsAffine) -> [Fr; 4] {
+    core::array::from_fn(|i| {
+        r14_poseidon::poseidon_hash(&[Fr::from(AUDIT_DOMAIN), shared.x, shared.y, Fr::from(i as u64)])
+    })
+}
+
+fn encrypt(pk: &AuditorPublicKey, r: JubjubScalar, outputs: &[Note; 2]) -> AuditCiphertext {
+    let shared = (pk.0 * r).into_affine();
+    let pads = pads(&shared);
+    let m = [
+        Fr::from(outputs[0].value),
+        outputs[0].owner,
+        Fr::from(outputs[1].value),
+        outputs[1].owner,
+    ];
+    AuditCiphertext {
+        ephemeral: (EdwardsAffine::generator() * r).into_affine(),
+        data: core::array::from_fn(|i| m[i] + pads[i]),
+    }
+}
+
+/// Transfer that also discloses both outputs' (value, owner) to an auditor.
+///
+/// Only notes with an audited app tag (`AUDITED_APP_TAG` set) go through
+/// this circuit, and only this circuit accepts them. The app tag becomes
+/// public so the contract can look up that app's auditor key. Public
+/// inputs, after the transfer's five: `app_tag`, the auditor key (x, y),
+/// the ephemeral point (x, y), and the four ciphertext elements.
+#[derive(Clone)]
+pub struct AuditedTransferCircuit {
+    pub transfer: TransferCircuit,
+    // Public input
+    pub auditor: Option<AuditorPublicKey>,
+    // Private witness: encryption randomness
+    pub randomness: Option<JubjubScalar>,
+}
+
+impl AuditedTransferCircuit {
+    /// Create a circuit with None witnesses (for setup)
+    pub fn empty() -> Self {
+        Self {
+            transfer: TransferCircuit::empty(),
+            auditor: None,
+            randomness: None,
+        }
+    }
+}
+
+/// Public inputs for an audited transfer proof
+pub struct AuditedPublicInputs {
+    pub transfer: PublicInputs,
+    pub app_tag: u32,
+    pub auditor: AuditorPublicKey,
+    pub audit: AuditCiphertext,
+}
+
+impl AuditedPublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        let mut v = self.transfer.to_vec();
+        v.push(Fr::from(self.app_tag as u64));
+        v.extend(self.auditor.to_fields());
+        v.extend([self.audit.ephemeral.x, self.audit.ephemeral.y]);
+        v.extend(self.audit.data);
+        v
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for AuditedTransferCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let app_tag = self.transfer.consumed_note.as_ref().map(|n| n.app_tag);
+        let created = self.transfer.created_notes.clone();
+        let ciphertext = match (&self.auditor, self.randomness, &created) {
+            (Some(pk), Some(r), Some(notes)) => Some(encrypt(pk, r, notes)),
+            _ => None,
+        };
+
+        // === Constraints 1-8: the transfer itself ===
+        let vars = self.transfer.synthesize(cs.clone())?;
+
+        // === Public inputs 6-14 ===
+        let app_tag_pub = FpVar::new_input(cs.clone(), || {
+            app_tag.map(|t| Fr::from(t as u64)).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        // on-curve checked; the contract pins the key itself
+        let auditor_pub = EdwardsVar::new_input(ns!(cs, "auditor"), || {
+            self.auditor.map(|pk| pk.0).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let ephemeral_pub = EdwardsVar::new_input(ns!(cs, "ephemeral"), || {
+            ciphertext.map(|ct| ct.ephemeral).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let mut data_pub = Vec::with_capacity(4);
+        for i in 0..4 {
+            data_pub.push(FpVar::new_input(cs.clone(), || {
+                ciphertext.map(|ct| ct.data[i]).ok_or(SynthesisError::AssignmentMissing)
+            })?);
+        }
+
+        // === Private witnesses ===
+        let witnesses_ns = ns!(cs, "witnesses");
+        let r_bits = scalar_bits_witness(cs.clone(), self.randomness)?;
+        drop(witnesses_ns);
+
+        // === Constraint 9: Audit ===
+        let _ns = ns!(cs, "audit");
+        vars.app_tag.enforce_equal(&app_tag_pub)?;
+
+        // ephemeral == r·G
+        generator_mul(&r_bits)?.enforce_equal(&ephemeral_pub)?;
+
+        // shared = r·auditor, then data[i] == message[i] + pad(shared, i)
+        let shared = auditor_pub.scalar_mul_le(r_bits.iter())?;
+        let message = [
+            &vars.created_values[0],
+            &vars.created_owners[0],
+            &vars.created_values[1],
+            &vars.created_owners[1],
+        ];
+        for (i, (m, c)) in message.into_iter().zip(&data_pub).enumerate() {
+            let pad = poseidon_hash_var(
+                cs.clone(),
+                &[
+                    FpVar::constant(Fr::from(AUDIT_DOMAIN)),
+                    shared.x.clone(),
+                    shared.y.clone(),
+                    FpVar::constant(Fr::from(i as u64)),
+                ],
+            )?;
+            (m + &pad).enforce_equal(c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run Groth16 trusted setup for the audited transfer circuit
+pub fn setup_audited<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>), CircuitError> {
+    let circuit = AuditedTransferCircuit::empty();
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).map_err(synthesis_error)
+}
+
+/// Generate a Groth16 proof for a transfer of an audited note, encrypting
+/// its outputs to `auditor`.
+///
+/// Fails like [`crate::prove`], except that the consumed note's app tag must
+/// be audited instead of plain.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_audited<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    auditor: &AuditorPublicKey,
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<Bls12_381>, AuditedPublicInputs), CircuitError> {
+    let (circuit, public_inputs) =
+        audited_witness(auditor, secret_key, consumed_note, merkle_path, created_notes, current_ledger, rng)?;
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).map_err(synthesis_error)?;
+    Ok((proof, public_inputs))
+}
+
+/// Check the witness, sample the encryption randomness, and build the
+/// audited circuit and its public inputs
+pub(crate) fn audited_witness<R: RngCore + CryptoRng>(
+    auditor: &AuditorPublicKey,
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(AuditedTransferCircuit, AuditedPublicInputs), CircuitError> {
+    check_merkle_path(&merkle_path)?;
+    if consumed_note.unlock_after > current_ledger {
+        return Err(CircuitError::InvalidWitness(format!(
+            "note is locked until ledger {}, proving at {current_ledger}",
+            consumed_note.unlock_after
+        )));
+    }
+    if !consumed_note.is_audited() {
+        return Err(CircuitError::InvalidWitness(format!(
+            "app_tag {:#x} isn't audited; prove with prove",
+            consumed_note.app_tag
+        )));
+    }
+
+    let randomness = JubjubScalar::rand(rng);
+    let public_inputs = AuditedPublicInputs {
+        transfer: PublicInputs {
+            old_root: crate::compute_root(r14_poseidon::commitment(&consumed_note), &merkle_path),
+            nullifier: r14_poseidon::nullifier_hash(secret_key, consumed_note.nonce, merkle_path.leaf_index()),
+            out_commitment_0: r14_poseidon::commitment(&created_notes[0]).0,
+            out_commitment_1: r14_poseidon::commitment(&created_notes[1]).0,
+            current_ledger: Fr::from(current_ledger),
+        },
+        app_tag: consumed_note.app_tag,
+        auditor: *auditor,
+        audit: encrypt(auditor, randomness, &created_notes),
+    };
+
+    let circuit = AuditedTransferCircuit {
+        transfer: TransferCircuit {
+            secret_key: Some(secret_key),
+            consumed_note: Some(consumed_note),
+            merkle_path: Some(merkle_path),
+            created_notes: Some(created_notes),
+            current_ledger: Some(current_ledger),
+        },
+        auditor: Some(*auditor),
+        randomness: Some(randomness),
+    };
+
+    Ok((circuit, public_inputs))
+}
+
+/// Verify an audited transfer proof off-chain
+pub fn verify_audited_offchain(
+    vk: &VerifyingKey<Bls12_381>,
+    proof: &ark_groth16::Proof<Bls12_381>,
+    public_inputs: &AuditedPublicInputs,
+) -> bool {
+    let pvk = PreparedVerifyingKey::from(vk.clone());
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs.to_vec(), proof)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::{SecretKey, AUDITED_APP_TAG, MERKLE_DEPTH};
+
+    const TAG: u32 = 7 | AUDITED_APP_TAG;
+
+    fn scenario(rng: &mut StdRng) -> TransferCircuit {
+        let sk = SecretKey::random(rng);
+        let owner = r14_poseidon::owner_hash(&sk);
+        let siblings: Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect();
+        let indices: Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 3 == 0).collect();
+        TransferCircuit {
+            secret_key: Some(sk.0),
+            consumed_note: Some(Note::new(1000, TAG, owner.0, rng)),
+            merkle_path: Some(MerklePath { siblings, indices }),
+            created_notes: Some([
+                Note::new(650, TAG, Fr::rand(rng), rng),
+                Note::new(350, TAG, owner.0, rng),
+            ]),
+            current_ledger: Some(0),
+        }
+    }
+
+    fn is_satisfied(c: impl ConstraintSynthesizer<Fr>) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        c.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let auditor = AuditorSecretKey::random(&mut rng);
+        let notes = scenario(&mut rng).created_notes.unwrap();
+        let ct = encrypt(&auditor.public_key(), JubjubScalar::rand(&mut rng), &notes);
+
+        let [out_0, out_1] = auditor.decrypt(&ct).unwrap();
+        assert_eq!((out_0.value, out_0.owner), (650, notes[0].owner));
+        assert_eq!((out_1.value, out_1.owner), (350, notes[1].owner));
+
+        let other = AuditorSecretKey::random(&mut rng);
+        assert_ne!(other.decrypt(&ct).map(|o| o[1].owner), Some(notes[1].owner));
+    }
+
+    #[test]
+    fn test_audited_circuit_satisfied() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let auditor = AuditorSecretKey::random(&mut rng).public_key();
+        let c = AuditedTransferCircuit {
+            transfer: scenario(&mut rng),
+            auditor: Some(auditor),
+            randomness: Some(JubjubScalar::rand(&mut rng)),
+        };
+        assert!(is_satisfied(c));
+    }
+
+    #[test]
+    fn test_wrapped_output_value() {
+        // a wrapped output would mint, and decrypt() would return None for it
+        let mut rng = StdRng::seed_from_u64(42);
+        let auditor = AuditorSecretKey::random(&mut rng).public_key();
+        let c = AuditedTransferCircuit {
+            transfer: scenario(&mut rng),
+            auditor: Some(auditor),
+            randomness: Some(JubjubScalar::rand(&mut rng)),
+        };
+        let failures = crate::transfer::tests::wrapped_output_failures(c);
+        assert!(failures.iter().any(|s| s == "value_conservation"), "{failures:?}");
+    }
+
+    #[test]
+    fn test_plain_circuit_rejects_audited_tag() {
+        let mut rng = StdRng::seed_from_u64(42);
+        assert!(
+            !is_satisfied(scenario(&mut rng)),
+            "should fail: audited app_tag"
+        );
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let auditor = AuditorSecretKey::random(&mut rng);
+        let t = scenario(&mut rng);
+        let (pk, vk) = setup_audited(&mut rng).unwrap();
+        assert_eq!(vk.gamma_abc_g1.len(), 15);
+
+        let (proof, mut pi) = prove_audited(
+            &pk,
+            &auditor.public_key(),
+            t.secret_key.unwrap(),
+            t.consumed_note.unwrap(),
+            t.merkle_path.unwrap(),
+            t.created_notes.unwrap(),
+            0,
+            &mut rng,
+        )
+        .unwrap();
+        assert!(verify_audited_offchain(&vk, &proof, &pi));
+        assert_eq!(auditor.decrypt(&pi.audit).unwrap()[0].value, 650);
+
+        // the ciphertext is bound to the proof
+        pi.audit.data[0] += Fr::from(1u64);
+        assert!(!verify_audited_offchain(&vk, &proof, &pi));
+    }
+}
@@ -0,0 +1,58 @@
+//! The transfer circuit on BN254 (`bn254` feature).
+//!
+//! For verifiers without BLS12-381 support: EVM-style precompiles and
+//! off-chain services. Soroban contracts only verify BLS12-381 proofs, so
+//! nothing here can be registered on r14-core. Poseidon parameters are
+//! derived from the field, so BN254 commitments, nullifiers and roots are
+//! unrelated to their BLS12-381 counterparts.
+//!
+//! [`crate::prove`] and [`crate::verify_offchain`] pick the curve from the
+//! key, so only setup needs a BN254 entry point:
+//!
+//! ```rust,ignore
+//! let (pk, vk) = r14_circuit::bn254::setup(&mut rng)?;
+//! let (proof, pi) = r14_circuit::prove(&pk, sk, consumed, path, outputs, current_ledger, &mut rng)?;
+//! assert!(r14_circuit::verify_offchain(&vk, &proof, &pi));
+//! ```
+
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::CircuitError;
+
+pub use ark_bn254::{Bn254, Fr};
+
+/// Run Groth16 trusted setup for the transfer circuit on BN254
+pub fn setup<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), CircuitError> {
+    crate::setup_for::<Bn254, R>(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::{MerklePath, Note, MERKLE_DEPTH};
+
+    #[test]
+    fn bn254_transfer_proves_and_verifies() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let sk = Fr::rand(&mut rng);
+        let owner = r14_poseidon::poseidon_hash(&[sk]);
+        let consumed = Note::new(1000, 1, owner, &mut rng);
+        let path = MerklePath {
+            siblings: (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect(),
+            indices: (0..MERKLE_DEPTH).map(|i| i % 3 == 0).collect(),
+        };
+        let outputs = [Note::new(600, 1, Fr::rand(&mut rng), &mut rng), Note::new(400, 1, owner, &mut rng)];
+
+        let (pk, vk) = setup(&mut rng).unwrap();
+        let (proof, mut pi) = crate::prove(&pk, sk, consumed, path, outputs, 0, &mut rng).unwrap();
+        assert!(crate::verify_offchain(&vk, &proof, &pi));
+
+        pi.nullifier = Fr::rand(&mut rng);
+        assert!(!crate::verify_offchain(&vk, &proof, &pi));
+    }
+}
@@ -0,0 +1,356 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, fields::FieldVar};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_types::{MerklePath, Note, OwnerHash, SecretKey, SessionPolicy};
+
+use crate::{check_merkle_path, synthesis_error, CircuitError};
+use crate::poseidon_gadget::poseidon_hash_var;
+use crate::range_gadget::enforce_u64;
+use crate::transfer::{enforce_unaudited, Ownership, TransferCircuit};
+
+/// 1-in-2-out transfer spent by a session key instead of the main key.
+///
+/// The consumed note is owned by `session_owner(session_sk, policy, refund_owner)`.
+/// Output 0 is the payment and output 1 the change, which stays with the
+/// session. A payment to `refund_owner` (revocation sweep) bypasses the
+/// amount cap and expiry.
+#[derive(Clone)]
+pub struct DelegatedTransferCircuit {
+    /// The spend. Its `secret_key` holds the session key, and its
+    /// `current_ledger` the `valid_until` bound the contract checks.
+    pub transfer: TransferCircuit,
+    // Private witnesses
+    pub policy: Option<SessionPolicy>,
+    pub refund_owner: Option<Fr>,
+}
+
+impl DelegatedTransferCircuit {
+    /// Create a circuit with None witnesses (for setup)
+    pub fn empty() -> Self {
+        Self {
+            transfer: TransferCircuit::empty(),
+            policy: None,
+            refund_owner: None,
+        }
+    }
+}
+
+/// Public inputs for a delegated transfer proof
+pub struct DelegatedPublicInputs {
+    pub old_root: Fr,
+    pub nullifier: Fr,
+    pub out_commitment_0: Fr,
+    pub out_commitment_1: Fr,
+    pub valid_until: Fr,
+}
+
+impl DelegatedPublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        vec![
+            self.old_root,
+            self.nullifier,
+            self.out_commitment_0,
+            self.out_commitment_1,
+            self.valid_until,
+        ]
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for DelegatedTransferCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let app_tag = self.transfer.consumed_note.as_ref().map(|n| n.app_tag);
+        let payment = self.transfer.created_notes.as_ref().map(|n| (n[0].value, n[0].owner));
+        let valid_until = self.transfer.current_ledger;
+
+        // === Constraints 2-9: the transfer, with valid_until as its ledger ===
+        let vars = self.transfer.synthesize_with(cs.clone(), Ownership::Deferred)?;
+        enforce_unaudited(cs.clone(), &vars.app_tag, app_tag)?;
+        let valid_until_pub = &vars.public_inputs[4];
+
+        // === Private witnesses ===
+        let witnesses_ns = ns!(cs, "witnesses");
+        let policy = self.policy.as_ref();
+        let max_amount = FpVar::new_witness(cs.clone(), || {
+            policy.map(|p| Fr::from(p.max_amount)).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let policy_app_tag = FpVar::new_witness(cs.clone(), || {
+            policy.map(|p| Fr::from(p.app_tag as u64)).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let expiry = FpVar::new_witness(cs.clone(), || {
+            policy.map(|p| Fr::from(p.expiry)).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let refund_owner = FpVar::new_witness(cs.clone(), || {
+            self.refund_owner.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        drop(witnesses_ns);
+
+        // === Constraint 1: Ownership, by delegation ===
+        // consumed.owner == Poseidon(Poseidon(session_sk), max_amount, app_tag, expiry, refund_owner)
+        {
+            let _ns = ns!(cs, "delegation");
+            let session_pub = poseidon_hash_var(cs.clone(), std::slice::from_ref(&vars.nullifier_key))?;
+            let delegated_owner = poseidon_hash_var(
+                cs.clone(),
+                &[session_pub, max_amount.clone(), policy_app_tag.clone(), expiry.clone(), refund_owner.clone()],
+            )?;
+            delegated_owner.enforce_equal(&vars.consumed_owner)?;
+        }
+
+        // === Constraint 10: Session policy ===
+        let _ns = ns!(cs, "session_policy");
+        // app tag scoped to the policy, change kept under the same delegation
+        vars.app_tag.enforce_equal(&policy_app_tag)?;
+        vars.created_owners[1].enforce_equal(&vars.consumed_owner)?;
+
+        // no timelocks: valid_until isn't a ledger the lock could be checked against
+        let zero = FpVar::zero();
+        vars.consumed_unlock.enforce_equal(&zero)?;
+        for unlock in &vars.created_unlocks {
+            unlock.enforce_equal(&zero)?;
+        }
+
+        // amount cap and expiry, waived for refunds
+        let is_refund = vars.created_owners[0].is_eq(&refund_owner)?;
+        let refund_native = match (payment, self.refund_owner) {
+            (Some((_, owner)), Some(r)) => Some(owner == r),
+            _ => None,
+        };
+
+        let cap_slack = is_refund.select(&zero, &(&max_amount - &vars.created_values[0]))?;
+        let cap_native = match (&self.policy, payment, refund_native) {
+            (Some(_), Some(_), Some(true)) => Some(0),
+            (Some(p), Some((value, _)), Some(false)) => Some(p.max_amount.wrapping_sub(value)),
+            _ => None,
+        };
+        enforce_u64(cs.clone(), &cap_slack, cap_native)?;
+
+        let expiry_slack = is_refund.select(&zero, &(&expiry - valid_until_pub))?;
+        let expiry_native = match (&self.policy, valid_until, refund_native) {
+            (Some(_), Some(_), Some(true)) => Some(0),
+            (Some(p), Some(v), Some(false)) => Some(p.expiry.wrapping_sub(v)),
+            _ => None,
+        };
+        enforce_u64(cs, &expiry_slack, expiry_native)
+    }
+}
+
+/// Run Groth16 trusted setup for the delegated transfer circuit
+pub fn setup_delegated<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>), CircuitError> {
+    let circuit = DelegatedTransferCircuit::empty();
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).map_err(synthesis_error)
+}
+
+/// Generate a Groth16 proof for a transfer spent by a session key.
+///
+/// `valid_until` is the ledger bound the contract checks against; it must not
+/// exceed `policy.expiry` unless output 0 pays `refund_owner`.
+/// Like [`crate::prove`], a Merkle path of the wrong length is
+/// `CircuitError::InvalidWitness`. So is a timelocked input or output, or
+/// an audited app tag: the delegated circuit only handles unlocked,
+/// unaudited notes.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_delegated<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    session_key: &SecretKey,
+    policy: &SessionPolicy,
+    refund_owner: &OwnerHash,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    valid_until: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<Bls12_381>, DelegatedPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = delegated_witness(
+        session_key,
+        policy,
+        refund_owner,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        valid_until,
+    )?;
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).map_err(synthesis_error)?;
+    Ok((proof, public_inputs))
+}
+
+/// Check the witness and build the delegated circuit and its public inputs
+pub(crate) fn delegated_witness(
+    session_key: &SecretKey,
+    policy: &SessionPolicy,
+    refund_owner: &OwnerHash,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    valid_until: u64,
+) -> Result<(DelegatedTransferCircuit, DelegatedPublicInputs), CircuitError> {
+    check_merkle_path(&merkle_path)?;
+    if consumed_note.is_locked() || created_notes.iter().any(Note::is_locked) {
+        return Err(CircuitError::InvalidWitness(
+            "session keys can't spend or create timelocked notes".into(),
+        ));
+    }
+    crate::check_unaudited(&consumed_note)?;
+
+    let circuit = DelegatedTransferCircuit {
+        transfer: TransferCircuit {
+            secret_key: Some(session_key.0),
+            consumed_note: Some(consumed_note),
+            merkle_path: Some(merkle_path),
+            created_notes: Some(created_notes),
+            current_ledger: Some(valid_until),
+        },
+        policy: Some(policy.clone()),
+        refund_owner: Some(refund_owner.0),
+    };
+    let pi = circuit.transfer.native_public_inputs().expect("circuit has a witness");
+    let public_inputs = DelegatedPublicInputs {
+        old_root: pi.old_root,
+        nullifier: pi.nullifier,
+        out_commitment_0: pi.out_commitment_0,
+        out_commitment_1: pi.out_commitment_1,
+        valid_until: pi.current_ledger,
+    };
+
+    Ok((circuit, public_inputs))
+}
+
+/// Verify a delegated transfer proof off-chain
+pub fn verify_delegated_offchain(
+    vk: &VerifyingKey<Bls12_381>,
+    proof: &ark_groth16::Proof<Bls12_381>,
+    public_inputs: &DelegatedPublicInputs,
+) -> bool {
+    let pvk = PreparedVerifyingKey::from(vk.clone());
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs.to_vec(), proof)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::MERKLE_DEPTH;
+
+    struct Scenario {
+        session_key: SecretKey,
+        policy: SessionPolicy,
+        refund_owner: OwnerHash,
+        consumed: Note,
+        path: MerklePath,
+    }
+
+    fn scenario(rng: &mut StdRng) -> Scenario {
+        let sk = SecretKey::random(rng);
+        let session_key = r14_poseidon::session_key(&sk, 0);
+        let policy = SessionPolicy::new(400, 1, 1_000);
+        let refund_owner = r14_poseidon::owner_hash(&sk);
+        let owner = r14_poseidon::session_owner(&session_key, &policy, &refund_owner);
+        let consumed = Note::new(1000, 1, owner.0, rng);
+        let siblings: Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect();
+        let indices: Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+        Scenario {
+            session_key,
+            policy,
+            refund_owner,
+            consumed,
+            path: MerklePath { siblings, indices },
+        }
+    }
+
+    fn circuit(s: Scenario, pay_to: Fr, pay: u64, valid_until: u64, rng: &mut StdRng) -> DelegatedTransferCircuit {
+        let change_owner = s.consumed.owner;
+        let created = [
+            Note::new(pay, 1, pay_to, rng),
+            Note::new(s.consumed.value - pay, 1, change_owner, rng),
+        ];
+        DelegatedTransferCircuit {
+            transfer: TransferCircuit {
+                secret_key: Some(s.session_key.0),
+                consumed_note: Some(s.consumed),
+                merkle_path: Some(s.path),
+                created_notes: Some(created),
+                current_ledger: Some(valid_until),
+            },
+            policy: Some(s.policy),
+            refund_owner: Some(s.refund_owner.0),
+        }
+    }
+
+    fn is_satisfied(c: DelegatedTransferCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        c.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_within_policy() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let recipient = Fr::rand(&mut rng);
+        assert!(is_satisfied(circuit(s, recipient, 400, 1_000, &mut rng)));
+    }
+
+    #[test]
+    fn test_over_max_amount() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let recipient = Fr::rand(&mut rng);
+        assert!(!is_satisfied(circuit(s, recipient, 401, 1_000, &mut rng)), "should fail: over cap");
+    }
+
+    #[test]
+    fn test_expired_session() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let recipient = Fr::rand(&mut rng);
+        assert!(!is_satisfied(circuit(s, recipient, 100, 1_001, &mut rng)), "should fail: expired");
+    }
+
+    #[test]
+    fn test_refund_bypasses_limits() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let refund = s.refund_owner.0;
+        assert!(is_satisfied(circuit(s, refund, 1000, 5_000, &mut rng)));
+    }
+
+    #[test]
+    fn test_wrapped_output_value() {
+        // -1 would also slip under the amount cap
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let recipient = Fr::rand(&mut rng);
+        let failures = crate::transfer::tests::wrapped_output_failures(circuit(s, recipient, 400, 1_000, &mut rng));
+        assert!(failures.iter().any(|s| s == "value_conservation"), "{failures:?}");
+    }
+
+    #[test]
+    fn test_locked_output() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let recipient = Fr::rand(&mut rng);
+        let mut c = circuit(s, recipient, 400, 1_000, &mut rng);
+        let notes = c.transfer.created_notes.as_mut().unwrap();
+        notes[0] = notes[0].clone().locked_until(500);
+        assert!(!is_satisfied(c), "should fail: session keys can't create timelocked notes");
+    }
+
+    #[test]
+    fn test_wrong_session_key() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut s = scenario(&mut rng);
+        s.session_key = SecretKey::random(&mut rng);
+        let recipient = Fr::rand(&mut rng);
+        assert!(!is_satisfied(circuit(s, recipient, 100, 1_000, &mut rng)), "should fail: wrong key");
+    }
+}
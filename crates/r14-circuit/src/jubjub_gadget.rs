@@ -0,0 +1,84 @@
+use ark_bls12_381::Fr;
+use ark_ec::{AffineRepr};
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::{BigInteger, PrimeField};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    convert::ToBitsGadget,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    groups::CurveVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use r14_poseidon::SIGNATURE_DOMAIN;
+
+use crate::poseidon_gadget::poseidon_hash_var;
+
+/// Allocate a Jubjub scalar as little-endian Boolean witnesses.
+///
+/// `scalar` supplies the witness; it is `None` during setup.
+pub fn scalar_bits_witness(
+    cs: ConstraintSystemRef<Fr>,
+    scalar: Option<JubjubScalar>,
+) -> Result<Vec<Boolean<Fr>>, SynthesisError> {
+    (0..JubjubScalar::MODULUS_BIT_SIZE as usize)
+        .map(|i| {
+            Boolean::new_witness(cs.clone(), || {
+                let s = scalar.ok_or(SynthesisError::AssignmentMissing)?;
+                Ok(s.into_bigint().get_bit(i))
+            })
+        })
+        .collect()
+}
+
+/// `scalar·G` for the Jubjub generator, from the scalar's little-endian bits
+pub fn generator_mul(bits: &[Boolean<Fr>]) -> Result<EdwardsVar, SynthesisError> {
+    let mut base = EdwardsAffine::generator().into_group();
+    let powers: Vec<_> = bits
+        .iter()
+        .map(|_| {
+            let p = base;
+            base += base;
+            p
+        })
+        .collect();
+    let mut result = EdwardsVar::zero();
+    result.precomputed_base_scalar_mul_le(bits.iter().zip(&powers))?;
+    Ok(result)
+}
+
+/// Verify a Schnorr signature `(r, s)` by `key` over `digest`:
+/// `s·G == r + c·key` with `c = Poseidon(domain, r, key, digest)`.
+///
+/// `key` isn't checked to be in the prime-order subgroup; callers bind it
+/// to something the key's owner committed to.
+pub fn verify_schnorr(
+    cs: ConstraintSystemRef<Fr>,
+    key: &EdwardsVar,
+    digest: &FpVar<Fr>,
+    r: &EdwardsVar,
+    s_bits: &[Boolean<Fr>],
+) -> Result<(), SynthesisError> {
+    let c = poseidon_hash_var(
+        cs.clone(),
+        &[
+            FpVar::constant(Fr::from(SIGNATURE_DOMAIN)),
+            r.x.clone(),
+            r.y.clone(),
+            key.x.clone(),
+            key.y.clone(),
+            digest.clone(),
+        ],
+    )?;
+    let lhs = generator_mul(s_bits)?;
+    let rhs = key.scalar_mul_le(c.to_bits_le()?.iter())? + r;
+    lhs.enforce_equal(&rhs)
+}
+
+/// Native counterpart of the challenge in [`verify_schnorr`], reduced into
+/// the Jubjub scalar field
+pub(crate) fn schnorr_challenge(r: &EdwardsAffine, key: &EdwardsAffine, digest: Fr) -> JubjubScalar {
+    let c = r14_poseidon::poseidon_hash(&[Fr::from(SIGNATURE_DOMAIN), r.x, r.y, key.x, key.y, digest]);
+    JubjubScalar::from_le_bytes_mod_order(&c.into_bigint().to_bytes_le())
+}
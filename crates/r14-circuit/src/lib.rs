@@ -1,102 +1,366 @@
+pub mod aggregate;
+pub mod association;
+pub mod audit;
+#[cfg(feature = "bn254")]
+pub mod bn254;
+pub mod delegated;
+pub mod jubjub_gadget;
 pub mod merkle_gadget;
+#[cfg(feature = "plonk")]
+pub mod plonk;
 pub mod poseidon_gadget;
+pub mod profile;
+pub mod progress;
+pub mod range_gadget;
+pub mod spend_auth;
+pub mod swap;
 pub mod transfer;
+mod transcript;
 
 use ark_bls12_381::{Bls12_381, Fr};
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
 use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
 use ark_snark::SNARK;
 use ark_std::rand::{CryptoRng, RngCore};
-use r14_types::{MerklePath, Note};
-
+use r14_types::{Commitment, MerklePath, Note, MERKLE_DEPTH};
+
+pub use r14_errors::CircuitError;
+
+pub use association::{
+    prove_association, setup_association, verify_association_offchain, AssociationCircuit,
+    AssociationPublicInputs,
+};
+pub use audit::{
+    prove_audited, setup_audited, verify_audited_offchain, AuditCiphertext, AuditedOutput,
+    AuditedPublicInputs, AuditedTransferCircuit, AuditorPublicKey, AuditorSecretKey,
+};
+pub use delegated::{
+    prove_delegated, setup_delegated, verify_delegated_offchain, DelegatedPublicInputs,
+    DelegatedTransferCircuit,
+};
+pub use profile::{profile, profile_circuit, ConstraintProfile};
+pub use progress::{CancelToken, ProveProgress};
+pub use spend_auth::{
+    prove_signed, setup_signed, signed_public_inputs, spend_digest, SignedTransferCircuit, SpendAuthKey,
+    SpendAuthPublicKey, SpendAuthSignature,
+};
+pub use swap::{
+    prove_swap, setup_swap, swap_digest, swap_public_inputs, verify_swap_offchain, SwapCircuit, SwapLeg,
+    SwapPublicInputs,
+};
 pub use transfer::TransferCircuit;
 
 /// Public inputs for a transfer proof
-pub struct PublicInputs {
-    pub old_root: Fr,
-    pub nullifier: Fr,
-    pub out_commitment_0: Fr,
-    pub out_commitment_1: Fr,
+pub struct PublicInputs<F = Fr> {
+    pub old_root: F,
+    pub nullifier: F,
+    pub out_commitment_0: F,
+    pub out_commitment_1: F,
+    pub current_ledger: F,
 }
 
-impl PublicInputs {
-    pub fn to_vec(&self) -> Vec<Fr> {
-        vec![self.old_root, self.nullifier, self.out_commitment_0, self.out_commitment_1]
+impl<F: Copy> PublicInputs<F> {
+    pub fn to_vec(&self) -> Vec<F> {
+        vec![
+            self.old_root,
+            self.nullifier,
+            self.out_commitment_0,
+            self.out_commitment_1,
+            self.current_ledger,
+        ]
     }
 }
 
-/// Run Groth16 trusted setup for the transfer circuit
-pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
-    let circuit = TransferCircuit::empty();
-    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+/// Run Groth16 trusted setup for the transfer circuit on BLS12-381, the
+/// curve Soroban verifies
+pub fn setup<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>), CircuitError> {
+    setup_for::<Bls12_381, R>(rng)
 }
 
-/// Generate a Groth16 proof for a private transfer
-pub fn prove<R: RngCore + CryptoRng>(
-    pk: &ProvingKey<Bls12_381>,
-    secret_key: Fr,
-    consumed_note: Note,
-    merkle_path: MerklePath,
-    created_notes: [Note; 2],
+/// Run Groth16 trusted setup for the transfer circuit on pairing engine `E`
+pub fn setup_for<E: Pairing, R: RngCore + CryptoRng>(
     rng: &mut R,
-) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
-    // Compute public inputs natively
-    let cm = r14_poseidon::commitment(&consumed_note);
-
-    let mut current = cm;
-    for i in 0..merkle_path.siblings.len() {
-        if merkle_path.indices[i] {
-            current = r14_poseidon::hash2(merkle_path.siblings[i], current);
-        } else {
-            current = r14_poseidon::hash2(current, merkle_path.siblings[i]);
-        }
-    }
-    let old_root = current;
+) -> Result<(ProvingKey<E>, VerifyingKey<E>), CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    setup_at_depth::<E, MERKLE_DEPTH, R>(rng)
+}
+
+/// Run Groth16 trusted setup for the transfer circuit over a `DEPTH`-level
+/// note tree. The keys only prove paths of exactly that length; register
+/// the VK with the same depth so its circuit_id records it.
+pub fn setup_at_depth<E: Pairing, const DEPTH: usize, R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<E>, VerifyingKey<E>), CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    let circuit = TransferCircuit::<E::ScalarField, DEPTH>::empty();
+    Groth16::<E>::circuit_specific_setup(circuit, rng).map_err(synthesis_error)
+}
 
-    let nullifier = r14_poseidon::poseidon_hash(&[secret_key, consumed_note.nonce]);
-    let out_cm_0 = r14_poseidon::commitment(&created_notes[0]);
-    let out_cm_1 = r14_poseidon::commitment(&created_notes[1]);
+/// Generate a Groth16 proof for a private transfer. The curve follows `pk`.
+///
+/// `current_ledger` is the ledger the proof claims to be spent at; the
+/// contract rejects it if it is ahead of the real sequence, and the circuit
+/// rejects it if it is before the consumed note's `unlock_after`.
+///
+/// Fails with `CircuitError::InvalidWitness` if the Merkle path isn't
+/// `MERKLE_DEPTH` long, the note is still locked at `current_ledger`, or
+/// its app tag is audited (use [`prove_audited`] for those).
+/// Any other witness that fails the circuit's constraints still produces a
+/// proof; it just won't verify.
+#[allow(clippy::too_many_arguments)]
+pub fn prove<E: Pairing, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<E>,
+    secret_key: E::ScalarField,
+    consumed_note: Note<E::ScalarField>,
+    merkle_path: MerklePath<E::ScalarField>,
+    created_notes: [Note<E::ScalarField>; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<E>, PublicInputs<E::ScalarField>), CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    prove_at_depth::<E, MERKLE_DEPTH, R>(pk, secret_key, consumed_note, merkle_path, created_notes, current_ledger, rng)
+}
+
+/// [`prove`] with keys from [`setup_at_depth`]; the Merkle path must be
+/// `DEPTH` long
+#[allow(clippy::too_many_arguments)]
+pub fn prove_at_depth<E: Pairing, const DEPTH: usize, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<E>,
+    secret_key: E::ScalarField,
+    consumed_note: Note<E::ScalarField>,
+    merkle_path: MerklePath<E::ScalarField>,
+    created_notes: [Note<E::ScalarField>; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<E>, PublicInputs<E::ScalarField>), CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    let (circuit, public_inputs) =
+        transfer_witness::<E, DEPTH>(secret_key, consumed_note, merkle_path, created_notes, current_ledger)?;
+    let proof = Groth16::<E>::prove(pk, circuit, rng).map_err(synthesis_error)?;
+    Ok((proof, public_inputs))
+}
 
-    let circuit = TransferCircuit {
+/// [`prove`], reporting each proving stage to `on_progress` (see
+/// [`progress`]); for progress bars on the seconds-long prove
+#[allow(clippy::too_many_arguments)]
+pub fn prove_with_progress<E: Pairing, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<E>,
+    secret_key: E::ScalarField,
+    consumed_note: Note<E::ScalarField>,
+    merkle_path: MerklePath<E::ScalarField>,
+    created_notes: [Note<E::ScalarField>; 2],
+    current_ledger: u64,
+    rng: &mut R,
+    on_progress: impl FnMut(ProveProgress),
+) -> Result<(ark_groth16::Proof<E>, PublicInputs<E::ScalarField>), CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    prove_cancellable(
+        pk,
+        secret_key,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        current_ledger,
+        rng,
+        &CancelToken::new(),
+        on_progress,
+    )
+}
+
+/// [`prove_with_progress`] that stops with `CircuitError::Cancelled` once
+/// `cancel` is set; see [`progress::create_proof_cancellable`]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_cancellable<E: Pairing, R: RngCore + CryptoRng>(
+    pk: &ProvingKey<E>,
+    secret_key: E::ScalarField,
+    consumed_note: Note<E::ScalarField>,
+    merkle_path: MerklePath<E::ScalarField>,
+    created_notes: [Note<E::ScalarField>; 2],
+    current_ledger: u64,
+    rng: &mut R,
+    cancel: &CancelToken,
+    mut on_progress: impl FnMut(ProveProgress),
+) -> Result<(ark_groth16::Proof<E>, PublicInputs<E::ScalarField>), CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    let (circuit, public_inputs) = transfer_witness::<E, MERKLE_DEPTH>(
+        secret_key,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        current_ledger,
+    )?;
+    let proof = progress::create_proof_cancellable(pk, circuit, rng, cancel, &mut on_progress)?;
+    Ok((proof, public_inputs))
+}
+
+/// A transfer circuit with its witness assigned, and its public inputs
+type TransferWitness<F, const DEPTH: usize> = (TransferCircuit<F, DEPTH>, PublicInputs<F>);
+
+/// Check the witness and build the circuit and public inputs for a
+/// transfer over a `DEPTH`-level tree
+fn transfer_witness<E: Pairing, const DEPTH: usize>(
+    secret_key: E::ScalarField,
+    consumed_note: Note<E::ScalarField>,
+    merkle_path: MerklePath<E::ScalarField>,
+    created_notes: [Note<E::ScalarField>; 2],
+    current_ledger: u64,
+) -> Result<TransferWitness<E::ScalarField, DEPTH>, CircuitError>
+where
+    E::ScalarField: Absorb,
+{
+    check_path_depth(&merkle_path, DEPTH)?;
+    if consumed_note.unlock_after > current_ledger {
+        return Err(CircuitError::InvalidWitness(format!(
+            "note is locked until ledger {}, proving at {current_ledger}",
+            consumed_note.unlock_after
+        )));
+    }
+    check_unaudited(&consumed_note)?;
+
+    let circuit = TransferCircuit::<_, DEPTH> {
         secret_key: Some(secret_key),
         consumed_note: Some(consumed_note),
         merkle_path: Some(merkle_path),
         created_notes: Some(created_notes),
+        current_ledger: Some(current_ledger),
     };
+    let public_inputs = circuit.native_public_inputs().expect("circuit has a witness");
 
-    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
-
-    let public_inputs = PublicInputs {
-        old_root,
-        nullifier,
-        out_commitment_0: out_cm_0,
-        out_commitment_1: out_cm_1,
-    };
-
-    (proof, public_inputs)
+    Ok((circuit, public_inputs))
 }
 
 /// Verify a proof off-chain
-pub fn verify_offchain(
-    vk: &VerifyingKey<Bls12_381>,
-    proof: &ark_groth16::Proof<Bls12_381>,
-    public_inputs: &PublicInputs,
+pub fn verify_offchain<E: Pairing>(
+    vk: &VerifyingKey<E>,
+    proof: &ark_groth16::Proof<E>,
+    public_inputs: &PublicInputs<E::ScalarField>,
 ) -> bool {
     let pvk = PreparedVerifyingKey::from(vk.clone());
-    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs.to_vec(), proof)
+    Groth16::<E>::verify_with_processed_vk(&pvk, &public_inputs.to_vec(), proof)
         .unwrap_or(false)
 }
 
+/// Walk a Merkle path natively from `leaf` to the root
+pub(crate) fn compute_root<F: PrimeField + Absorb>(leaf: Commitment<F>, path: &MerklePath<F>) -> F {
+    let mut current = leaf.0;
+    for (&sibling, &is_right) in path.siblings.iter().zip(&path.indices) {
+        if is_right {
+            current = r14_poseidon::hash2(sibling, current);
+        } else {
+            current = r14_poseidon::hash2(current, sibling);
+        }
+    }
+    current
+}
+
+/// The circuits allocate exactly `MERKLE_DEPTH` levels
+pub(crate) fn check_merkle_path<F>(path: &MerklePath<F>) -> Result<(), CircuitError> {
+    check_path_depth(path, MERKLE_DEPTH)
+}
+
+pub(crate) fn check_path_depth<F>(path: &MerklePath<F>, depth: usize) -> Result<(), CircuitError> {
+    if path.siblings.len() != depth || path.indices.len() != depth {
+        return Err(CircuitError::InvalidWitness(format!(
+            "merkle path has {} siblings and {} indices, expected {depth}",
+            path.siblings.len(),
+            path.indices.len(),
+        )));
+    }
+    Ok(())
+}
+
+/// Audited app tags only prove through the audited circuit
+pub(crate) fn check_unaudited<F>(note: &Note<F>) -> Result<(), CircuitError>
+where
+    F: PrimeField,
+{
+    if note.is_audited() {
+        return Err(CircuitError::InvalidWitness(format!(
+            "app_tag {:#x} is audited; prove with prove_audited",
+            note.app_tag
+        )));
+    }
+    Ok(())
+}
+
+/// `rayon::join` with the `parallel` feature; one after the other without
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    #[cfg(feature = "parallel")]
+    return rayon::join(a, b);
+    #[cfg(not(feature = "parallel"))]
+    (a(), b())
+}
+
+pub(crate) fn synthesis_error(e: ark_relations::r1cs::SynthesisError) -> CircuitError {
+    CircuitError::Synthesis(e.to_string())
+}
+
 /// Count constraints in the transfer circuit
 pub fn constraint_count() -> usize {
     let cs = ConstraintSystem::<Fr>::new_ref();
     cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
     cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
-    let circuit = TransferCircuit::empty();
+    let circuit = TransferCircuit::<Fr>::empty();
     circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
     cs.num_constraints()
 }
 
+/// sha256 of the transfer circuit's R1CS matrices. Every setup of the same
+/// circuit shares it, so it tells a cached proving key from one for an older
+/// version of the circuit.
+pub fn transfer_digest() -> [u8; 32] {
+    use ark_serialize::CanonicalSerialize;
+    use sha2::{Digest, Sha256};
+
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
+    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
+    TransferCircuit::<Fr>::empty()
+        .generate_constraints(cs.clone())
+        .expect("constraint generation failed");
+    cs.finalize();
+    let m = cs.to_matrices().expect("setup-mode constraint system has matrices");
+
+    let mut hasher = Sha256::new();
+    for n in [m.num_instance_variables, m.num_witness_variables, m.num_constraints] {
+        hasher.update((n as u64).to_le_bytes());
+    }
+    let mut buf = Vec::new();
+    for row in m.a.iter().chain(&m.b).chain(&m.c) {
+        hasher.update((row.len() as u64).to_le_bytes());
+        for (coeff, var) in row {
+            buf.clear();
+            coeff.serialize_compressed(&mut buf).expect("serializing into a Vec");
+            hasher.update(&buf);
+            hasher.update((*var as u64).to_le_bytes());
+        }
+    }
+    hasher.finalize().into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,22 +398,84 @@ mod tests {
         let mut rng = test_rng();
         let (sk, consumed, path, created) = test_scenario(&mut rng);
 
-        let (pk, vk) = setup(&mut rng);
-        let (proof, pi) = prove(&pk, sk, consumed, path, created, &mut rng);
+        let (pk, vk) = setup(&mut rng).unwrap();
+        let (proof, pi) = prove(&pk, sk, consumed, path, created, 0, &mut rng).unwrap();
         assert!(verify_offchain(&vk, &proof, &pi));
     }
 
+    #[test]
+    fn test_prove_with_progress_matches_prove() {
+        let mut rng = test_rng();
+        let (sk, consumed, path, created) = test_scenario(&mut rng);
+        let (pk, vk) = setup(&mut rng).unwrap();
+
+        let mut stages = Vec::new();
+        let (proof, pi) = prove_with_progress(
+            &pk,
+            sk,
+            consumed.clone(),
+            path.clone(),
+            created.clone(),
+            0,
+            &mut StdRng::seed_from_u64(7),
+            |p| stages.push(p),
+        )
+        .unwrap();
+        assert!(verify_offchain(&vk, &proof, &pi));
+        // same randomness, same proof
+        let (expected, _) = prove(&pk, sk, consumed, path, created, 0, &mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(proof, expected);
+
+        assert_eq!(stages[0], ProveProgress::KeyLoaded);
+        assert_eq!(stages[1], ProveProgress::WitnessBuilt { constraints: constraint_count() });
+        assert_eq!(stages[2], ProveProgress::WitnessMapped);
+        assert_eq!(stages.last(), Some(&ProveProgress::Done));
+        let msm: Vec<_> = stages[3..stages.len() - 1]
+            .iter()
+            .map(|p| match p {
+                ProveProgress::Msm { done, total } => (*done, *total),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert!(msm.len() > 5 && msm.windows(2).all(|w| w[0].0 < w[1].0));
+        let (done, total) = msm[msm.len() - 1];
+        assert_eq!(done, total);
+    }
+
+    #[test]
+    fn test_cancelled_prove_stops() {
+        let mut rng = test_rng();
+        let (sk, consumed, path, created) = test_scenario(&mut rng);
+        let (pk, _) = setup(&mut rng).unwrap();
+
+        // cancel from the first MSM chunk's report
+        let cancel = CancelToken::new();
+        let mut msm_steps = 0;
+        let err = prove_cancellable(&pk, sk, consumed, path, created, 0, &mut rng, &cancel, |p| {
+            if let ProveProgress::Msm { .. } = p {
+                msm_steps += 1;
+                cancel.cancel();
+            }
+        })
+        .err()
+        .unwrap();
+        assert!(matches!(err, CircuitError::Cancelled), "{err}");
+        assert_eq!(msm_steps, 1);
+        assert_eq!(err.code(), r14_errors::ErrorCode::Cancelled);
+    }
+
     #[test]
     fn test_wrong_secret_key() {
         let mut rng = test_rng();
         let (_, consumed, path, created) = test_scenario(&mut rng);
         let wrong_sk = Fr::rand(&mut rng); // wrong key
 
-        let circuit = TransferCircuit {
+        let circuit = TransferCircuit::<Fr> {
             secret_key: Some(wrong_sk),
             consumed_note: Some(consumed),
             merkle_path: Some(path),
             created_notes: Some(created),
+            current_ledger: Some(0),
         };
 
         let cs = ConstraintSystem::<Fr>::new_ref();
@@ -167,13 +493,83 @@ mod tests {
         // The circuit will compute a different root than what gets set as public input
         // We need to test at the proof level — the circuit itself always computes consistently
         // So instead: use prove() which computes root from the bad path, then tamper the root
-        let (pk, vk) = setup(&mut rng);
-        let (proof, mut pi) = prove(&pk, sk, consumed, path, created, &mut rng);
+        let (pk, vk) = setup(&mut rng).unwrap();
+        let (proof, mut pi) = prove(&pk, sk, consumed, path, created, 0, &mut rng).unwrap();
         // Tamper with root to simulate inclusion failure
         pi.old_root = Fr::rand(&mut rng);
         assert!(!verify_offchain(&vk, &proof, &pi), "should fail: wrong root");
     }
 
+    #[test]
+    fn test_short_merkle_path_is_invalid_witness() {
+        let mut rng = test_rng();
+        let (sk, consumed, mut path, created) = test_scenario(&mut rng);
+        path.siblings.pop();
+
+        let (pk, _vk) = setup(&mut rng).unwrap();
+        let err = prove(&pk, sk, consumed, path, created, 0, &mut rng).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+    }
+
+    fn timelock_satisfied(unlock_after: u64, current_ledger: u64) -> bool {
+        let mut rng = test_rng();
+        let (sk, consumed, path, created) = test_scenario(&mut rng);
+        let circuit = TransferCircuit::<Fr> {
+            secret_key: Some(sk),
+            consumed_note: Some(consumed.locked_until(unlock_after)),
+            merkle_path: Some(path),
+            created_notes: Some(created),
+            current_ledger: Some(current_ledger),
+        };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_timelock() {
+        assert!(timelock_satisfied(0, 0));
+        assert!(timelock_satisfied(500, 500));
+        assert!(timelock_satisfied(500, 501));
+        assert!(!timelock_satisfied(500, 499), "should fail: still locked");
+    }
+
+    #[test]
+    fn test_locked_note_is_invalid_witness() {
+        let mut rng = test_rng();
+        let (sk, consumed, path, created) = test_scenario(&mut rng);
+        let (pk, _vk) = setup(&mut rng).unwrap();
+        let err = prove(&pk, sk, consumed.locked_until(500), path, created, 499, &mut rng)
+            .err()
+            .unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+    }
+
+    #[test]
+    fn test_audited_note_is_invalid_witness() {
+        let mut rng = test_rng();
+        let (sk, mut consumed, path, created) = test_scenario(&mut rng);
+        consumed.app_tag |= r14_types::AUDITED_APP_TAG;
+        let (pk, _vk) = setup(&mut rng).unwrap();
+        let err = prove(&pk, sk, consumed, path, created, 0, &mut rng).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+    }
+
+    #[test]
+    fn test_locked_output_proves() {
+        let mut rng = test_rng();
+        let (sk, consumed, path, [note_0, note_1]) = test_scenario(&mut rng);
+        let created = [note_0.locked_until(10_000), note_1];
+
+        let (pk, vk) = setup(&mut rng).unwrap();
+        let (proof, mut pi) = prove(&pk, sk, consumed.locked_until(40), path, created, 41, &mut rng).unwrap();
+        assert!(verify_offchain(&vk, &proof, &pi));
+
+        // the contract supplies current_ledger; a different value must not verify
+        pi.current_ledger = Fr::from(42u64);
+        assert!(!verify_offchain(&vk, &proof, &pi));
+    }
+
     #[test]
     fn test_value_mismatch() {
         let mut rng = test_rng();
@@ -188,11 +584,12 @@ mod tests {
         let note_0 = Note::new(600, 1, recipient_owner.0, &mut rng);
         let note_1 = Note::new(300, 1, owner.0, &mut rng);
 
-        let circuit = TransferCircuit {
+        let circuit = TransferCircuit::<Fr> {
             secret_key: Some(sk.0),
             consumed_note: Some(consumed),
             merkle_path: Some(path),
             created_notes: Some([note_0, note_1]),
+            current_ledger: Some(0),
         };
 
         let cs = ConstraintSystem::<Fr>::new_ref();
@@ -200,6 +597,25 @@ mod tests {
         assert!(!cs.is_satisfied().unwrap(), "should fail: value mismatch");
     }
 
+    #[test]
+    fn test_shallow_tree() {
+        let mut rng = test_rng();
+        let (sk, consumed, path, created) = test_scenario(&mut rng);
+        let shallow = MerklePath {
+            siblings: path.siblings[..4].to_vec(),
+            indices: path.indices[..4].to_vec(),
+        };
+
+        let (pk, vk) = setup_at_depth::<Bls12_381, 4, _>(&mut rng).unwrap();
+        let (proof, pi) =
+            prove_at_depth::<_, 4, _>(&pk, sk, consumed.clone(), shallow, created.clone(), 0, &mut rng).unwrap();
+        assert!(verify_offchain(&vk, &proof, &pi));
+
+        // a full-depth path doesn't fit the shallow circuit
+        let err = prove_at_depth::<_, 4, _>(&pk, sk, consumed, path, created, 0, &mut rng).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+    }
+
     #[test]
     fn test_constraint_count() {
         let count = constraint_count();
@@ -213,14 +629,14 @@ mod tests {
         let mut rng = test_rng();
         let (sk, consumed, path, created) = test_scenario(&mut rng);
 
-        let (pk, vk) = setup(&mut rng);
-        let (proof, pi) = prove(&pk, sk, consumed, path, created, &mut rng);
+        let (pk, vk) = setup(&mut rng).unwrap();
+        let (proof, pi) = prove(&pk, sk, consumed, path, created, 0, &mut rng).unwrap();
 
-        let svk = r14_sdk::serialize::serialize_vk_for_soroban(&vk);
+        let svk = r14_sdk::serialize::serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
         let (sp, spi) = r14_sdk::serialize::serialize_proof_for_soroban(&proof, &pi.to_vec());
 
-        // IC length = 5 (1 constant + 4 public inputs)
-        assert_eq!(svk.ic.len(), 5, "IC length should be 5 for 4 public inputs");
+        // IC length = 6 (1 constant + 5 public inputs)
+        assert_eq!(svk.ic.len(), 6, "IC length should be 6 for 5 public inputs");
 
         // G1 = 96 bytes = 192 hex chars
         assert_eq!(svk.alpha_g1.len(), 192);
@@ -235,7 +651,7 @@ mod tests {
         assert_eq!(sp.b.len(), 384);
 
         // Fr = 32 bytes = 64 hex chars
-        assert_eq!(spi.len(), 4);
+        assert_eq!(spi.len(), 5);
         for pi_hex in &spi {
             assert_eq!(pi_hex.len(), 64);
         }
@@ -255,11 +671,12 @@ mod tests {
         let note_0 = Note::new(700, 2, recipient_owner.0, &mut rng);
         let note_1 = Note::new(300, 1, owner.0, &mut rng);
 
-        let circuit = TransferCircuit {
+        let circuit = TransferCircuit::<Fr> {
             secret_key: Some(sk.0),
             consumed_note: Some(consumed),
             merkle_path: Some(path),
             created_notes: Some([note_0, note_1]),
+            current_ledger: Some(0),
         };
 
         let cs = ConstraintSystem::<Fr>::new_ref();
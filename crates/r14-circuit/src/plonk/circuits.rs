@@ -0,0 +1,230 @@
+//! `setup`/`prove`/`verify_offchain` per circuit, checking witnesses the
+//! same way their Groth16 counterparts at the crate root do
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_types::{MerklePath, Note, OwnerHash, SecretKey, SessionPolicy, MERKLE_DEPTH};
+
+use super::{prove_circuit, setup_circuit, verify, Proof, ProvingKey, UniversalSrs, VerifyingKey};
+use crate::{
+    AssociationCircuit, AssociationPublicInputs, AuditedPublicInputs, AuditedTransferCircuit, AuditorPublicKey,
+    CircuitError, DelegatedPublicInputs, DelegatedTransferCircuit, PublicInputs, SignedTransferCircuit,
+    SpendAuthPublicKey, SpendAuthSignature, SwapCircuit, SwapLeg, SwapPublicInputs, TransferCircuit,
+};
+
+type Keys = Result<(ProvingKey, VerifyingKey), CircuitError>;
+
+/// Set up the transfer circuit from `srs`
+pub fn setup(srs: &UniversalSrs) -> Keys {
+    setup_circuit(srs, TransferCircuit::<Fr, MERKLE_DEPTH>::empty())
+}
+
+/// PLONK proof for a private transfer. Fails like [`crate::prove`], and
+/// also when the witness doesn't satisfy the circuit.
+pub fn prove<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(Proof, PublicInputs), CircuitError> {
+    let (circuit, public_inputs) = crate::transfer_witness::<Bls12_381, MERKLE_DEPTH>(
+        secret_key,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        current_ledger,
+    )?;
+    Ok((prove_circuit(pk, circuit, rng)?, public_inputs))
+}
+
+/// Verify a transfer proof, or a signed one against the signed circuit's key
+pub fn verify_offchain(vk: &VerifyingKey, proof: &Proof, public_inputs: &PublicInputs) -> bool {
+    verify(vk, &public_inputs.to_vec(), proof)
+}
+
+/// Set up the association circuit from `srs`
+pub fn setup_association(srs: &UniversalSrs) -> Keys {
+    setup_circuit(srs, AssociationCircuit::empty())
+}
+
+/// PLONK counterpart of [`crate::prove_association`]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_association<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    association_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(Proof, AssociationPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = crate::association::association_witness(
+        secret_key,
+        consumed_note,
+        merkle_path,
+        association_path,
+        created_notes,
+        current_ledger,
+    )?;
+    Ok((prove_circuit(pk, circuit, rng)?, public_inputs))
+}
+
+pub fn verify_association_offchain(vk: &VerifyingKey, proof: &Proof, public_inputs: &AssociationPublicInputs) -> bool {
+    verify(vk, &public_inputs.to_vec(), proof)
+}
+
+/// Set up the audited transfer circuit from `srs`
+pub fn setup_audited(srs: &UniversalSrs) -> Keys {
+    setup_circuit(srs, AuditedTransferCircuit::empty())
+}
+
+/// PLONK counterpart of [`crate::prove_audited`]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_audited<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    auditor: &AuditorPublicKey,
+    secret_key: Fr,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(Proof, AuditedPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = crate::audit::audited_witness(
+        auditor,
+        secret_key,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        current_ledger,
+        rng,
+    )?;
+    Ok((prove_circuit(pk, circuit, rng)?, public_inputs))
+}
+
+pub fn verify_audited_offchain(vk: &VerifyingKey, proof: &Proof, public_inputs: &AuditedPublicInputs) -> bool {
+    verify(vk, &public_inputs.to_vec(), proof)
+}
+
+/// Set up the delegated transfer circuit from `srs`
+pub fn setup_delegated(srs: &UniversalSrs) -> Keys {
+    setup_circuit(srs, DelegatedTransferCircuit::empty())
+}
+
+/// PLONK counterpart of [`crate::prove_delegated`]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_delegated<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    session_key: &SecretKey,
+    policy: &SessionPolicy,
+    refund_owner: &OwnerHash,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    valid_until: u64,
+    rng: &mut R,
+) -> Result<(Proof, DelegatedPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = crate::delegated::delegated_witness(
+        session_key,
+        policy,
+        refund_owner,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        valid_until,
+    )?;
+    Ok((prove_circuit(pk, circuit, rng)?, public_inputs))
+}
+
+pub fn verify_delegated_offchain(vk: &VerifyingKey, proof: &Proof, public_inputs: &DelegatedPublicInputs) -> bool {
+    verify(vk, &public_inputs.to_vec(), proof)
+}
+
+/// Set up the signed transfer circuit from `srs`
+pub fn setup_signed(srs: &UniversalSrs) -> Keys {
+    setup_circuit(srs, SignedTransferCircuit::empty())
+}
+
+/// PLONK counterpart of [`crate::prove_signed`]; verify with
+/// [`verify_offchain`]
+#[allow(clippy::too_many_arguments)]
+pub fn prove_signed<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    auth_key: &SpendAuthPublicKey,
+    nullifier_key: Fr,
+    signature: &SpendAuthSignature,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(Proof, PublicInputs), CircuitError> {
+    let (circuit, public_inputs) = crate::spend_auth::signed_witness(
+        auth_key,
+        nullifier_key,
+        signature,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        current_ledger,
+    )?;
+    Ok((prove_circuit(pk, circuit, rng)?, public_inputs))
+}
+
+/// Set up the swap circuit from `srs`
+pub fn setup_swap(srs: &UniversalSrs) -> Keys {
+    setup_circuit(srs, SwapCircuit::empty())
+}
+
+/// PLONK counterpart of [`crate::prove_swap`]
+pub fn prove_swap<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    legs: [SwapLeg; 2],
+    signatures: [SpendAuthSignature; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(Proof, SwapPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = crate::swap::swap_witness(legs, signatures, current_ledger)?;
+    Ok((prove_circuit(pk, circuit, rng)?, public_inputs))
+}
+
+pub fn verify_swap_offchain(vk: &VerifyingKey, proof: &Proof, public_inputs: &SwapPublicInputs) -> bool {
+    verify(vk, &public_inputs.to_vec(), proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn plonk_transfer_proves_and_verifies() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let sk = SecretKey::random(&mut rng);
+        let owner = r14_poseidon::owner_hash(&sk).0;
+        let consumed = Note::new(1000, 1, owner, &mut rng);
+        let path = MerklePath {
+            siblings: (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect(),
+            indices: (0..MERKLE_DEPTH).map(|i| i % 3 == 0).collect(),
+        };
+        let outputs = [Note::new(600, 1, Fr::rand(&mut rng), &mut rng), Note::new(400, 1, owner, &mut rng)];
+
+        let srs = UniversalSrs::setup(1 << 17, &mut rng);
+        let (pk, vk) = setup(&srs).unwrap();
+        let (proof, mut pi) = prove(&pk, sk.0, consumed.clone(), path.clone(), outputs.clone(), 0, &mut rng).unwrap();
+        assert!(verify_offchain(&vk, &proof, &pi));
+
+        pi.nullifier = Fr::rand(&mut rng);
+        assert!(!verify_offchain(&vk, &proof, &pi));
+
+        // a witness the circuit rejects never becomes a proof
+        let overspend = [Note::new(900, 1, owner, &mut rng), Note::new(400, 1, owner, &mut rng)];
+        let err = prove(&pk, sk.0, consumed, path, overspend, 0, &mut rng).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+    }
+}
@@ -0,0 +1,199 @@
+//! R1CS → PLONK gates.
+//!
+//! Every gate is `q_m·a·b + q_l·a + q_r·b + q_o·c + q_c = 0` over three
+//! wires. The first gates pin the public inputs. Each R1CS row `A·B = C`
+//! then becomes one multiplication gate, after any linear combination with
+//! two or more variables is summed into a fresh variable by a chain of
+//! addition gates. Constants fold into the selectors.
+
+use ark_bls12_381::Fr;
+use ark_ff::{Field, Zero};
+use ark_relations::r1cs::ConstraintMatrices;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+/// Variable 0 is R1CS's constant one. Gates never read it as an operand;
+/// it fills wires whose selectors are zero.
+pub(crate) const FILLER: usize = 0;
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub(crate) struct Gate {
+    pub q_m: Fr,
+    pub q_l: Fr,
+    pub q_r: Fr,
+    pub q_o: Fr,
+    pub q_c: Fr,
+    pub wires: [usize; 3],
+    /// Addition gate whose output wire is a new variable,
+    /// `c = q_l·a + q_r·b + q_c`
+    pub defines_output: bool,
+}
+
+impl Gate {
+    fn filler() -> Self {
+        Self {
+            q_m: Fr::zero(),
+            q_l: Fr::zero(),
+            q_r: Fr::zero(),
+            q_o: Fr::zero(),
+            q_c: Fr::zero(),
+            wires: [FILLER; 3],
+            defines_output: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub(crate) struct Compiled {
+    /// Public inputs, not counting the constant one
+    pub num_public: usize,
+    /// R1CS variables: the constant one, instance, then witness
+    pub num_r1cs_vars: usize,
+    /// R1CS variables plus the ones the addition chains introduce
+    pub num_vars: usize,
+    pub gates: Vec<Gate>,
+}
+
+/// `coeff·var + constant`, or just `constant` without a variable
+struct Term {
+    coeff: Fr,
+    var: Option<usize>,
+    constant: Fr,
+}
+
+pub(crate) fn compile(m: &ConstraintMatrices<Fr>) -> Compiled {
+    let num_r1cs_vars = m.num_instance_variables + m.num_witness_variables;
+    let mut c = Compiled {
+        num_public: m.num_instance_variables - 1,
+        num_r1cs_vars,
+        num_vars: num_r1cs_vars,
+        gates: Vec::with_capacity(m.num_constraints * 2),
+    };
+
+    // public input i sits on wire a of gate i, checked against PI(X)
+    for i in 1..m.num_instance_variables {
+        c.gates.push(Gate {
+            q_l: Fr::ONE,
+            wires: [i, FILLER, FILLER],
+            ..Gate::filler()
+        });
+    }
+
+    for row in 0..m.num_constraints {
+        let a = c.reduce(&m.a[row]);
+        let b = c.reduce(&m.b[row]);
+        let out = c.reduce(&m.c[row]);
+        c.mul_gate(a, b, out);
+    }
+    c
+}
+
+impl Compiled {
+    fn fresh(&mut self) -> usize {
+        self.num_vars += 1;
+        self.num_vars - 1
+    }
+
+    /// A linear combination as at most one variable
+    fn reduce(&mut self, lc: &[(Fr, usize)]) -> Term {
+        let mut constant = Fr::zero();
+        let mut vars = Vec::with_capacity(lc.len());
+        for &(coeff, var) in lc {
+            if var == FILLER {
+                constant += coeff;
+            } else if !coeff.is_zero() {
+                vars.push((coeff, var));
+            }
+        }
+        let Some(&(first_coeff, first)) = vars.first() else {
+            return Term { coeff: Fr::zero(), var: None, constant };
+        };
+        if vars.len() == 1 {
+            return Term { coeff: first_coeff, var: Some(first), constant };
+        }
+        let mut acc = (first_coeff, first);
+        for &(coeff, var) in &vars[1..] {
+            let sum = self.fresh();
+            self.gates.push(Gate {
+                q_l: acc.0,
+                q_r: coeff,
+                q_o: -Fr::ONE,
+                wires: [acc.1, var, sum],
+                defines_output: true,
+                ..Gate::filler()
+            });
+            acc = (Fr::ONE, sum);
+        }
+        Term { coeff: Fr::ONE, var: Some(acc.1), constant }
+    }
+
+    /// `(α·a + k₁)(β·b + k₂) = γ·c + k₃` as one gate
+    fn mul_gate(&mut self, a: Term, b: Term, c: Term) {
+        self.gates.push(Gate {
+            q_m: a.coeff * b.coeff,
+            q_l: a.coeff * b.constant,
+            q_r: b.coeff * a.constant,
+            q_o: -c.coeff,
+            q_c: a.constant * b.constant - c.constant,
+            wires: [a.var.unwrap_or(FILLER), b.var.unwrap_or(FILLER), c.var.unwrap_or(FILLER)],
+            defines_output: false,
+        });
+    }
+
+    /// Gates padded with zero gates to `n` rows
+    pub fn rows(&self, n: usize) -> impl Iterator<Item = Gate> + '_ {
+        self.gates.iter().cloned().chain(std::iter::repeat_with(Gate::filler)).take(n)
+    }
+
+    /// Extend an R1CS assignment (the constant one, instance, witness) with
+    /// the values of the addition chains' variables
+    pub fn assign(&self, mut values: Vec<Fr>) -> Vec<Fr> {
+        debug_assert_eq!(values.len(), self.num_r1cs_vars);
+        values.resize(self.num_vars, Fr::zero());
+        for gate in self.gates.iter().filter(|g| g.defines_output) {
+            let [a, b, out] = gate.wires;
+            values[out] = gate.q_l * values[a] + gate.q_r * values[b] + gate.q_c;
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+    use ark_relations::r1cs::{ConstraintSystem, OptimizationGoal};
+    use ark_std::test_rng;
+
+    /// Every gate holds on `values`
+    fn satisfied(c: &Compiled, values: &[Fr]) -> bool {
+        c.gates.iter().enumerate().all(|(i, g)| {
+            let [a, b, out] = g.wires.map(|w| values[w]);
+            let pi = if i < c.num_public { -values[i + 1] } else { Fr::zero() };
+            g.q_m * a * b + g.q_l * a + g.q_r * b + g.q_o * out + g.q_c + pi == Fr::zero()
+        })
+    }
+
+    #[test]
+    fn compiled_gates_hold_exactly_when_r1cs_does() {
+        let mut rng = test_rng();
+        let (x, y) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        let out = FpVar::new_input(cs.clone(), || Ok((x + Fr::from(3u64)) * (x + y) - y)).unwrap();
+        let x = FpVar::new_witness(cs.clone(), || Ok(x)).unwrap();
+        let y = FpVar::new_witness(cs.clone(), || Ok(y)).unwrap();
+        ((&x + Fr::from(3u64)) * (&x + &y) - &y).enforce_equal(&out).unwrap();
+        cs.finalize();
+
+        let compiled = compile(&cs.to_matrices().unwrap());
+        assert_eq!(compiled.num_public, 1);
+        let cs = cs.borrow().unwrap();
+        let values = compiled.assign([cs.instance_assignment.clone(), cs.witness_assignment.clone()].concat());
+        assert!(satisfied(&compiled, &values));
+
+        let mut wrong = values.clone();
+        wrong[1] += Fr::ONE;
+        assert!(!satisfied(&compiled, &compiled.assign(wrong[..compiled.num_r1cs_vars].to_vec())));
+    }
+}
@@ -0,0 +1,373 @@
+//! PLONK backend over a universal KZG setup (`plonk` feature).
+//!
+//! Groth16 keys are tied to one circuit, so every circuit change needs a
+//! new trusted setup. Here one [`UniversalSrs`] serves every circuit up to
+//! its size: changing a circuit only reruns the deterministic
+//! [`setup_circuit`] and registers the new verifying key, with no new
+//! ceremony.
+//!
+//! Circuits are the same R1CS synthesizers the Groth16 path uses. Each
+//! R1CS row compiles to one multiplication gate, plus addition gates for
+//! linear combinations of two or more variables (see `compile`). Proofs are
+//! nine G1 points and thirteen scalars, with SHA-256 Fiat–Shamir.
+//!
+//! r14-core only verifies Groth16, so these proofs are checked off-chain:
+//!
+//! ```rust,ignore
+//! let srs = r14_circuit::plonk::UniversalSrs::setup(1 << 17, &mut rng);
+//! let (pk, vk) = r14_circuit::plonk::setup(&srs)?;
+//! let (proof, pi) = r14_circuit::plonk::prove(&pk, sk, consumed, path, outputs, current_ledger, &mut rng)?;
+//! assert!(r14_circuit::plonk::verify_offchain(&vk, &proof, &pi));
+//! ```
+
+mod circuits;
+mod compile;
+mod prover;
+mod srs;
+mod verifier;
+
+use ark_bls12_381::{Fr, G1Affine, G2Affine};
+use ark_ff::{FftField, Field, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::{synthesis_error, CircuitError};
+use crate::transcript::Transcript;
+use compile::{compile, Compiled};
+
+pub use circuits::{
+    prove, prove_association, prove_audited, prove_delegated, prove_signed, prove_swap, setup, setup_association,
+    setup_audited, setup_delegated, setup_signed, setup_swap, verify_association_offchain, verify_audited_offchain,
+    verify_delegated_offchain, verify_offchain, verify_swap_offchain,
+};
+pub use srs::UniversalSrs;
+
+/// Commitments to one circuit's selector and permutation polynomials
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKey {
+    /// Rows in the gate table, a power of two
+    pub domain_size: u64,
+    pub num_public: u64,
+    pub q_m: G1Affine,
+    pub q_l: G1Affine,
+    pub q_r: G1Affine,
+    pub q_o: G1Affine,
+    pub q_c: G1Affine,
+    pub s_1: G1Affine,
+    pub s_2: G1Affine,
+    pub s_3: G1Affine,
+    pub g1: G1Affine,
+    pub g2: G2Affine,
+    pub tau_g2: G2Affine,
+}
+
+/// The verifying key plus what the prover needs: the compiled gates, the
+/// polynomials behind each commitment, and the powers to commit with
+#[derive(Clone, Debug, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingKey {
+    pub vk: VerifyingKey,
+    compiled: Compiled,
+    /// Coefficients of q_m, q_l, q_r, q_o, q_c
+    selectors: [Vec<Fr>; 5],
+    /// Coefficients of s_1, s_2, s_3
+    sigmas: [Vec<Fr>; 3],
+    /// s_1, s_2, s_3 on the domain, one column after another
+    sigma_evals: Vec<Fr>,
+    powers_g1: Vec<G1Affine>,
+}
+
+/// Openings at the evaluation challenge ζ, and of the permutation
+/// polynomial at ζω
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Evaluations {
+    pub a: Fr,
+    pub b: Fr,
+    pub c: Fr,
+    pub s_1: Fr,
+    pub s_2: Fr,
+    pub s_3: Fr,
+    pub q_m: Fr,
+    pub q_l: Fr,
+    pub q_r: Fr,
+    pub q_o: Fr,
+    pub q_c: Fr,
+    pub z: Fr,
+    pub z_omega: Fr,
+}
+
+impl Evaluations {
+    /// The openings at ζ, in the order they're batched
+    fn at_zeta(&self) -> [Fr; 12] {
+        [
+            self.a, self.b, self.c, self.s_1, self.s_2, self.s_3, self.q_m, self.q_l, self.q_r, self.q_o, self.q_c,
+            self.z,
+        ]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Proof {
+    /// Wire polynomials
+    pub a: G1Affine,
+    pub b: G1Affine,
+    pub c: G1Affine,
+    /// Permutation polynomial
+    pub z: G1Affine,
+    /// Quotient polynomial, in three pieces
+    pub t_lo: G1Affine,
+    pub t_mid: G1Affine,
+    pub t_hi: G1Affine,
+    /// Opening proofs at ζ and ζω
+    pub w_zeta: G1Affine,
+    pub w_zeta_omega: G1Affine,
+    pub evals: Evaluations,
+}
+
+/// Compile `circuit` and commit to its gates with `srs`. Deterministic:
+/// anyone holding the string can recompute the keys and check a VK.
+///
+/// Fails with `CircuitError::Synthesis` if the circuit needs more gates
+/// than `srs` supports.
+pub fn setup_circuit<C: ConstraintSynthesizer<Fr>>(
+    srs: &UniversalSrs,
+    circuit: C,
+) -> Result<(ProvingKey, VerifyingKey), CircuitError> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone()).map_err(synthesis_error)?;
+    cs.finalize();
+    let matrices = cs
+        .to_matrices()
+        .ok_or_else(|| CircuitError::Synthesis("constraint matrices weren't built".into()))?;
+    let compiled = compile(&matrices);
+
+    let n = compiled.gates.len().max(2).next_power_of_two();
+    if n > srs.max_gates() {
+        return Err(CircuitError::Synthesis(format!(
+            "circuit compiles to {} gates, the reference string supports {}",
+            compiled.gates.len(),
+            srs.max_gates()
+        )));
+    }
+    let domain = domain(n).expect("a power of two below the field's two-adicity");
+
+    let mut selector_evals: [Vec<Fr>; 5] = Default::default();
+    for gate in compiled.rows(n) {
+        for (column, q) in selector_evals.iter_mut().zip([gate.q_m, gate.q_l, gate.q_r, gate.q_o, gate.q_c]) {
+            column.push(q);
+        }
+    }
+    let sigma_evals = permutation(&compiled, &domain);
+
+    let selectors = selector_evals.map(|evals| domain.ifft(&evals));
+    let sigmas = [0, 1, 2].map(|j| domain.ifft(&sigma_evals[j * n..(j + 1) * n]));
+    let powers_g1 = srs.powers_g1[..n + srs::BLINDING_DEGREE].to_vec();
+    let commit = |p: &Vec<Fr>| srs::commit(&powers_g1, p);
+
+    let vk = VerifyingKey {
+        domain_size: n as u64,
+        num_public: compiled.num_public as u64,
+        q_m: commit(&selectors[0]),
+        q_l: commit(&selectors[1]),
+        q_r: commit(&selectors[2]),
+        q_o: commit(&selectors[3]),
+        q_c: commit(&selectors[4]),
+        s_1: commit(&sigmas[0]),
+        s_2: commit(&sigmas[1]),
+        s_3: commit(&sigmas[2]),
+        g1: srs.powers_g1[0],
+        g2: srs.g2,
+        tau_g2: srs.tau_g2,
+    };
+    let pk = ProvingKey {
+        vk: vk.clone(),
+        compiled,
+        selectors,
+        sigmas,
+        sigma_evals,
+        powers_g1,
+    };
+    Ok((pk, vk))
+}
+
+/// Prove `circuit` with its witness assigned.
+///
+/// Unlike Groth16, an unsatisfied witness doesn't produce a proof: it fails
+/// with `CircuitError::InvalidWitness`. A circuit other than the one `pk`
+/// was set up for fails with `CircuitError::Synthesis`.
+pub fn prove_circuit<C: ConstraintSynthesizer<Fr>, R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    circuit: C,
+    rng: &mut R,
+) -> Result<Proof, CircuitError> {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Prove { construct_matrices: false });
+    circuit.generate_constraints(cs.clone()).map_err(synthesis_error)?;
+    cs.finalize();
+    let cs = cs.into_inner().ok_or_else(|| CircuitError::Synthesis("constraint system still borrowed".into()))?;
+
+    let values = [cs.instance_assignment, cs.witness_assignment].concat();
+    if values.len() != pk.compiled.num_r1cs_vars {
+        return Err(CircuitError::Synthesis("circuit doesn't match the proving key".into()));
+    }
+    prover::prove(pk, &pk.compiled.assign(values), rng)
+}
+
+/// Check `proof` against `vk` and the circuit's public inputs
+pub fn verify(vk: &VerifyingKey, public_inputs: &[Fr], proof: &Proof) -> bool {
+    verifier::verify(vk, public_inputs, proof)
+}
+
+fn domain(n: usize) -> Option<Radix2EvaluationDomain<Fr>> {
+    Radix2EvaluationDomain::new(n).filter(|d| d.size() == n)
+}
+
+/// Multipliers of the domain for the three wire columns; the cosets
+/// `H`, `k₁H` and `k₂H` are disjoint since 7 generates the whole group
+fn coset_shifts() -> [Fr; 3] {
+    [Fr::ONE, Fr::GENERATOR, Fr::GENERATOR.square()]
+}
+
+/// σ on the domain: each wire position maps to the next position holding
+/// the same variable, cycling back to the first. Position `(j, i)` is
+/// labelled `k_j·ωⁱ`.
+fn permutation(compiled: &Compiled, domain: &Radix2EvaluationDomain<Fr>) -> Vec<Fr> {
+    let n = domain.size();
+    let mut sigma: Vec<usize> = (0..3 * n).collect();
+    let mut first = vec![usize::MAX; compiled.num_vars];
+    let mut last = vec![usize::MAX; compiled.num_vars];
+    for (row, gate) in compiled.rows(n).enumerate() {
+        for (column, &var) in gate.wires.iter().enumerate() {
+            let position = column * n + row;
+            if last[var] == usize::MAX {
+                first[var] = position;
+            } else {
+                sigma[last[var]] = position;
+            }
+            last[var] = position;
+        }
+    }
+    for (&first, &last) in first.iter().zip(&last) {
+        if last != usize::MAX {
+            sigma[last] = first;
+        }
+    }
+
+    let shifts = coset_shifts();
+    let omegas: Vec<Fr> = domain.elements().collect();
+    sigma.into_iter().map(|p| shifts[p / n] * omegas[p % n]).collect()
+}
+
+/// The transcript both sides start from: the key and the public inputs
+fn transcript(vk: &VerifyingKey, public_inputs: &[Fr]) -> Transcript {
+    let mut transcript = Transcript::new(b"r14-plonk-v1");
+    transcript.append(vk);
+    transcript.append(public_inputs);
+    transcript
+}
+
+fn evaluate(coeffs: &[Fr], x: Fr) -> Fr {
+    coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    /// `out = (x + 3)(x + y) - y`, `out` public
+    struct Toy {
+        x: Option<Fr>,
+        y: Option<Fr>,
+        out: Option<Fr>,
+    }
+
+    impl ConstraintSynthesizer<Fr> for Toy {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let out = FpVar::new_input(cs.clone(), || self.out.ok_or(SynthesisError::AssignmentMissing))?;
+            let x = FpVar::new_witness(cs.clone(), || self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = FpVar::new_witness(cs, || self.y.ok_or(SynthesisError::AssignmentMissing))?;
+            ((&x + Fr::from(3u64)) * (&x + &y) - &y).enforce_equal(&out)
+        }
+    }
+
+    fn toy(x: Fr, y: Fr) -> Toy {
+        Toy { x: Some(x), y: Some(y), out: Some((x + Fr::from(3u64)) * (x + y) - y) }
+    }
+
+    #[test]
+    fn toy_circuit_proves_and_verifies() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let srs = UniversalSrs::setup(16, &mut rng);
+        let (pk, vk) = setup_circuit(&srs, Toy { x: None, y: None, out: None }).unwrap();
+
+        let (x, y) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let circuit = toy(x, y);
+        let out = circuit.out.unwrap();
+        let proof = prove_circuit(&pk, circuit, &mut rng).unwrap();
+        assert!(verify(&vk, &[out], &proof));
+
+        assert!(!verify(&vk, &[out + Fr::ONE], &proof));
+        assert!(!verify(&vk, &[], &proof));
+        let mut tampered = proof.clone();
+        tampered.evals.a += Fr::ONE;
+        assert!(!verify(&vk, &[out], &tampered));
+        let mut tampered = proof;
+        tampered.z = tampered.a;
+        assert!(!verify(&vk, &[out], &tampered));
+    }
+
+    #[test]
+    fn unsatisfied_witness_is_rejected() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let srs = UniversalSrs::setup(16, &mut rng);
+        let (pk, _) = setup_circuit(&srs, Toy { x: None, y: None, out: None }).unwrap();
+
+        let mut circuit = toy(Fr::from(2u64), Fr::from(5u64));
+        circuit.out = circuit.out.map(|o| o + Fr::ONE);
+        let err = prove_circuit(&pk, circuit, &mut rng).unwrap_err();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+    }
+
+    #[test]
+    fn setup_rejects_a_circuit_larger_than_the_srs() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let srs = UniversalSrs::setup(2, &mut rng);
+        let err = setup_circuit(&srs, Toy { x: None, y: None, out: None }).unwrap_err();
+        assert!(matches!(err, CircuitError::Synthesis(_)), "{err}");
+    }
+
+    #[test]
+    fn one_srs_serves_circuits_of_different_sizes() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let srs = UniversalSrs::setup(64, &mut rng);
+        let (_, small) = setup_circuit(&srs, Toy { x: None, y: None, out: None }).unwrap();
+        let (pk, vk) = setup_circuit(&srs, Padded(None)).unwrap();
+        assert!(vk.domain_size > small.domain_size);
+
+        let proof = prove_circuit(&pk, Padded(Some(Fr::from(9u64))), &mut rng).unwrap();
+        assert!(verify(&vk, &[Fr::from(9u64).pow([20])], &proof));
+    }
+
+    /// `x^20` public, by twenty multiplications
+    struct Padded(Option<Fr>);
+
+    impl ConstraintSynthesizer<Fr> for Padded {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let out = FpVar::new_input(cs.clone(), || Ok(self.0.ok_or(SynthesisError::AssignmentMissing)?.pow([20])))?;
+            let x = FpVar::new_witness(cs, || self.0.ok_or(SynthesisError::AssignmentMissing))?;
+            let mut acc = x.clone();
+            for _ in 1..20 {
+                acc *= &x;
+            }
+            acc.enforce_equal(&out)
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! The five prover rounds: wires, permutation, quotient, openings,
+//! opening proofs
+
+use ark_bls12_381::{Fr, G1Affine};
+use ark_ff::{batch_inversion, FftField, Field, One, UniformRand, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_std::rand::{CryptoRng, RngCore};
+
+use super::{coset_shifts, evaluate, srs, Evaluations, Proof, ProvingKey};
+use crate::CircuitError;
+
+pub(crate) fn prove<R: RngCore + CryptoRng>(
+    pk: &ProvingKey,
+    values: &[Fr],
+    rng: &mut R,
+) -> Result<Proof, CircuitError> {
+    let n = pk.vk.domain_size as usize;
+    let domain = super::domain(n).expect("setup checked the domain");
+    let public = &values[1..=pk.compiled.num_public];
+    let commit = |p: &[Fr]| -> G1Affine { srs::commit(&pk.powers_g1, p) };
+
+    let mut wires = [vec![Fr::zero(); n], vec![Fr::zero(); n], vec![Fr::zero(); n]];
+    for (row, gate) in pk.compiled.rows(n).enumerate() {
+        let [a, b, c] = gate.wires.map(|w| values[w]);
+        let pi = if row < public.len() { -public[row] } else { Fr::zero() };
+        if gate.q_m * a * b + gate.q_l * a + gate.q_r * b + gate.q_o * c + gate.q_c + pi != Fr::zero() {
+            return Err(CircuitError::InvalidWitness("witness doesn't satisfy the circuit".into()));
+        }
+        for (column, value) in wires.iter_mut().zip([a, b, c]) {
+            column[row] = value;
+        }
+    }
+    let mut transcript = super::transcript(&pk.vk, public);
+
+    // round 1: wire polynomials
+    let [a, b, c] = [0, 1, 2].map(|j| blind(&domain, &wires[j], 2, rng));
+    let (a_cm, b_cm, c_cm) = (commit(&a), commit(&b), commit(&c));
+    transcript.append(&[a_cm, b_cm, c_cm]);
+    let beta = transcript.challenge();
+    let gamma = transcript.challenge();
+
+    // round 2: the permutation's running product
+    let shifts = coset_shifts();
+    let omegas: Vec<Fr> = domain.elements().collect();
+    let mut num = vec![Fr::one(); n];
+    let mut den = vec![Fr::one(); n];
+    for i in 0..n {
+        for j in 0..3 {
+            num[i] *= wires[j][i] + beta * shifts[j] * omegas[i] + gamma;
+            den[i] *= wires[j][i] + beta * pk.sigma_evals[j * n + i] + gamma;
+        }
+    }
+    batch_inversion(&mut den);
+    let mut z_evals = Vec::with_capacity(n);
+    let mut acc = Fr::one();
+    for i in 0..n {
+        z_evals.push(acc);
+        acc *= num[i] * den[i];
+    }
+    debug_assert!(acc.is_one(), "wires are read from one assignment, so the copies agree");
+    drop((num, den, wires));
+    let z = blind(&domain, &z_evals, 3, rng);
+    let z_cm = commit(&z);
+    transcript.append(&z_cm);
+    let alpha = transcript.challenge();
+
+    // round 3: the quotient, evaluated on a coset of a domain 8× larger
+    let t = quotient(pk, &domain, public, [&a, &b, &c, &z], [beta, gamma, alpha]);
+    let piece = n + 2;
+    debug_assert!(t[3 * piece..].iter().all(Zero::is_zero), "quotient degree is at most 3n + 5");
+    let mut t_lo = t[..piece].to_vec();
+    let mut t_mid = t[piece..2 * piece].to_vec();
+    let mut t_hi = t[2 * piece..3 * piece].to_vec();
+    let (b10, b11) = (Fr::rand(rng), Fr::rand(rng));
+    t_lo.push(b10);
+    t_mid[0] -= b10;
+    t_mid.push(b11);
+    t_hi[0] -= b11;
+    let (t_lo_cm, t_mid_cm, t_hi_cm) = (commit(&t_lo), commit(&t_mid), commit(&t_hi));
+    transcript.append(&[t_lo_cm, t_mid_cm, t_hi_cm]);
+    let zeta = transcript.challenge();
+
+    // round 4: openings
+    let [q_m, q_l, q_r, q_o, q_c] = &pk.selectors;
+    let [s_1, s_2, s_3] = &pk.sigmas;
+    let at_zeta = [&a, &b, &c, s_1, s_2, s_3, q_m, q_l, q_r, q_o, q_c, &z];
+    let [ea, eb, ec, es1, es2, es3, eqm, eql, eqr, eqo, eqc, ez] = at_zeta.map(|p| evaluate(p, zeta));
+    let evals = Evaluations {
+        a: ea,
+        b: eb,
+        c: ec,
+        s_1: es1,
+        s_2: es2,
+        s_3: es3,
+        q_m: eqm,
+        q_l: eql,
+        q_r: eqr,
+        q_o: eqo,
+        q_c: eqc,
+        z: ez,
+        z_omega: evaluate(&z, zeta * domain.group_gen()),
+    };
+    transcript.append(&evals);
+    let v = transcript.challenge();
+
+    // round 5: one batched opening at ζ, one of z at ζω
+    let zeta_piece = zeta.pow([piece as u64]);
+    let mut batched = t_lo;
+    add_scaled(&mut batched, &t_mid, zeta_piece);
+    add_scaled(&mut batched, &t_hi, zeta_piece.square());
+    let mut power = Fr::one();
+    for p in at_zeta {
+        power *= v;
+        add_scaled(&mut batched, p, power);
+    }
+    let w_zeta = commit(&divide_by_linear(&batched, zeta));
+    let w_zeta_omega = commit(&divide_by_linear(&z, zeta * domain.group_gen()));
+
+    Ok(Proof {
+        a: a_cm,
+        b: b_cm,
+        c: c_cm,
+        z: z_cm,
+        t_lo: t_lo_cm,
+        t_mid: t_mid_cm,
+        t_hi: t_hi_cm,
+        w_zeta,
+        w_zeta_omega,
+        evals,
+    })
+}
+
+/// Interpolate `evals` and add `(r₀ + r₁X + …)·Z_H` with `blinders` random
+/// coefficients, so the commitment and openings reveal nothing about them
+fn blind<R: RngCore>(domain: &Radix2EvaluationDomain<Fr>, evals: &[Fr], blinders: usize, rng: &mut R) -> Vec<Fr> {
+    let n = domain.size();
+    let mut coeffs = domain.ifft(evals);
+    coeffs.resize(n + blinders, Fr::zero());
+    for i in 0..blinders {
+        let r = Fr::rand(rng);
+        coeffs[i] -= r;
+        coeffs[n + i] += r;
+    }
+    coeffs
+}
+
+/// Coefficients of `t = (gate + α·perm + α²·(z − 1)·L₀) / Z_H`
+fn quotient(
+    pk: &ProvingKey,
+    domain: &Radix2EvaluationDomain<Fr>,
+    public: &[Fr],
+    [a, b, c, z]: [&Vec<Fr>; 4],
+    [beta, gamma, alpha]: [Fr; 3],
+) -> Vec<Fr> {
+    let n = domain.size();
+    let m = 8 * n;
+    let coset = Radix2EvaluationDomain::<Fr>::new(m)
+        .and_then(|d| d.get_coset(Fr::GENERATOR))
+        .expect("8n is below the field's two-adicity");
+    let xs: Vec<Fr> = coset.elements().collect();
+    let [ea, eb, ec, ez] = [a, b, c, z].map(|p| coset.fft(p));
+
+    // Z_H(x) = xⁿ − 1 takes 8 values on the coset, by index mod 8
+    let mut zh: Vec<Fr> = xs[..8].iter().map(|x| x.pow([n as u64]) - Fr::one()).collect();
+    let mut l0: Vec<Fr> = xs.iter().map(|x| Fr::from(n as u64) * (*x - Fr::one())).collect();
+    batch_inversion(&mut l0);
+    for (k, l) in l0.iter_mut().enumerate() {
+        *l *= zh[k % 8];
+    }
+    batch_inversion(&mut zh);
+
+    // gate constraint, with PI(X) interpolating −xᵢ on the first rows
+    let [q_m, q_l, q_r, q_o, q_c] = &pk.selectors;
+    let mut t = coset.fft(q_m);
+    for k in 0..m {
+        t[k] *= ea[k] * eb[k];
+    }
+    for (q, wire) in [(q_l, &ea), (q_r, &eb), (q_o, &ec)] {
+        for (k, e) in coset.fft(q).into_iter().enumerate() {
+            t[k] += e * wire[k];
+        }
+    }
+    let mut pi = vec![Fr::zero(); n];
+    for (slot, x) in pi.iter_mut().zip(public) {
+        *slot = -*x;
+    }
+    for e in [coset.fft(q_c), coset.fft(&domain.ifft(&pi))] {
+        for (k, e) in e.into_iter().enumerate() {
+            t[k] += e;
+        }
+    }
+
+    // permutation: z(X)·∏(w + β·kⱼX + γ) = z(ωX)·∏(w + β·sⱼ(X) + γ)
+    let shifts = coset_shifts();
+    let mut sigma_side = vec![Fr::one(); m];
+    for (s, wire) in pk.sigmas.iter().zip([&ea, &eb, &ec]) {
+        for (k, e) in coset.fft(s).into_iter().enumerate() {
+            sigma_side[k] *= wire[k] + beta * e + gamma;
+        }
+    }
+    let alpha_sq = alpha.square();
+    for k in 0..m {
+        let x = xs[k];
+        let id_side = (ea[k] + beta * x + gamma)
+            * (eb[k] + beta * shifts[1] * x + gamma)
+            * (ec[k] + beta * shifts[2] * x + gamma);
+        // ω is η⁸ for the larger domain's generator η, so z(ωx) is 8 slots on
+        let z_omega = ez[(k + 8) % m];
+        t[k] += alpha * (id_side * ez[k] - sigma_side[k] * z_omega) + alpha_sq * (ez[k] - Fr::one()) * l0[k];
+        t[k] *= zh[k % 8];
+    }
+    coset.ifft(&t)
+}
+
+fn add_scaled(acc: &mut Vec<Fr>, p: &[Fr], scale: Fr) {
+    if acc.len() < p.len() {
+        acc.resize(p.len(), Fr::zero());
+    }
+    for (a, c) in acc.iter_mut().zip(p) {
+        *a += scale * c;
+    }
+}
+
+/// `(p(X) − p(x)) / (X − x)`
+fn divide_by_linear(p: &[Fr], x: Fr) -> Vec<Fr> {
+    let mut quotient = vec![Fr::zero(); p.len().saturating_sub(1)];
+    let mut carry = Fr::zero();
+    for i in (1..p.len()).rev() {
+        carry = p[i] + carry * x;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
@@ -0,0 +1,54 @@
+//! Universal KZG reference string: powers of a secret τ in G1, and τ in G2
+
+use ark_bls12_381::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::{scalar_mul::ScalarMul, PrimeGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{CryptoRng, RngCore};
+
+/// Powers `[τ^i]₁` and `[1]₂, [τ]₂` of one secret τ. Any circuit with up
+/// to [`UniversalSrs::max_gates`] gates sets up from the same string, so
+/// changing a circuit needs no new ceremony.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct UniversalSrs {
+    pub powers_g1: Vec<G1Affine>,
+    pub g2: G2Affine,
+    pub tau_g2: G2Affine,
+}
+
+/// Extra powers past the domain size: blinded wire polynomials have degree
+/// `n + 1`, the permutation polynomial `n + 2`
+pub(crate) const BLINDING_DEGREE: usize = 3;
+
+impl UniversalSrs {
+    /// Sample τ and compute the powers for circuits of up to `max_gates`
+    /// gates, rounded up to a power of two. τ is dropped on return; whoever
+    /// runs this could forge proofs for every circuit set up from it, so
+    /// production strings come from a multi-party ceremony.
+    pub fn setup<R: RngCore + CryptoRng>(max_gates: usize, rng: &mut R) -> Self {
+        let size = max_gates.max(2).next_power_of_two() + BLINDING_DEGREE;
+        let tau = Fr::rand(rng);
+        let mut powers = Vec::with_capacity(size);
+        let mut power = Fr::ONE;
+        for _ in 0..size {
+            powers.push(power);
+            power *= tau;
+        }
+        Self {
+            powers_g1: G1Projective::generator().batch_mul(&powers),
+            g2: G2Projective::generator().into(),
+            tau_g2: (G2Projective::generator() * tau).into(),
+        }
+    }
+
+    /// Largest gate count a circuit may compile to
+    pub fn max_gates(&self) -> usize {
+        self.powers_g1.len().saturating_sub(BLINDING_DEGREE)
+    }
+}
+
+/// KZG commitment `[p(τ)]₁` to the polynomial with coefficients `coeffs`
+pub(crate) fn commit(powers: &[G1Affine], coeffs: &[Fr]) -> G1Affine {
+    assert!(coeffs.len() <= powers.len(), "polynomial degree exceeds the reference string");
+    G1Projective::msm_unchecked(&powers[..coeffs.len()], coeffs).into()
+}
@@ -0,0 +1,90 @@
+//! Replay the transcript, recompute t(ζ) from the openings, and check every
+//! opening with one two-pairing KZG batch
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{batch_inversion, Field, One, Zero};
+use ark_poly::EvaluationDomain;
+
+use super::{coset_shifts, Proof, VerifyingKey};
+
+pub(crate) fn verify(vk: &VerifyingKey, public: &[Fr], proof: &Proof) -> bool {
+    if public.len() as u64 != vk.num_public {
+        return false;
+    }
+    let points = [
+        proof.a,
+        proof.b,
+        proof.c,
+        proof.z,
+        proof.t_lo,
+        proof.t_mid,
+        proof.t_hi,
+        proof.w_zeta,
+        proof.w_zeta_omega,
+    ];
+    if !points.iter().all(|p| p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()) {
+        return false;
+    }
+    let Some(domain) = usize::try_from(vk.domain_size).ok().and_then(super::domain) else {
+        return false;
+    };
+    let n = domain.size();
+
+    let mut transcript = super::transcript(vk, public);
+    transcript.append(&[proof.a, proof.b, proof.c]);
+    let beta = transcript.challenge();
+    let gamma = transcript.challenge();
+    transcript.append(&proof.z);
+    let alpha = transcript.challenge();
+    transcript.append(&[proof.t_lo, proof.t_mid, proof.t_hi]);
+    let zeta = transcript.challenge();
+    transcript.append(&proof.evals);
+    let v = transcript.challenge();
+    transcript.append(&[proof.w_zeta, proof.w_zeta_omega]);
+    let u = transcript.challenge();
+
+    let zh = zeta.pow([n as u64]) - Fr::one();
+    let Some(zh_inv) = zh.inverse() else {
+        return false;
+    };
+    // Lᵢ(ζ) = ωⁱ·Z_H(ζ) / (n·(ζ − ωⁱ)) for L₀ and each public input's row
+    let omegas: Vec<Fr> = domain.elements().take(public.len().max(1)).collect();
+    let mut lagrange: Vec<Fr> = omegas.iter().map(|w| Fr::from(n as u64) * (zeta - w)).collect();
+    batch_inversion(&mut lagrange);
+    for (l, w) in lagrange.iter_mut().zip(&omegas) {
+        *l *= *w * zh;
+    }
+    let pi: Fr = public.iter().zip(&lagrange).map(|(x, l)| -*x * l).sum();
+
+    let e = &proof.evals;
+    let shifts = coset_shifts();
+    let gate = e.q_m * e.a * e.b + e.q_l * e.a + e.q_r * e.b + e.q_o * e.c + e.q_c + pi;
+    let perm = (e.a + beta * zeta + gamma)
+        * (e.b + beta * shifts[1] * zeta + gamma)
+        * (e.c + beta * shifts[2] * zeta + gamma)
+        * e.z
+        - (e.a + beta * e.s_1 + gamma) * (e.b + beta * e.s_2 + gamma) * (e.c + beta * e.s_3 + gamma) * e.z_omega;
+    let t = (gate + alpha * perm + alpha.square() * (e.z - Fr::one()) * lagrange[0]) * zh_inv;
+
+    // F = [t] + Σ vⁱ·[pᵢ] and E = t(ζ) + Σ vⁱ·pᵢ(ζ), in the prover's order
+    let zeta_piece = zeta.pow([n as u64 + 2]);
+    let commitments = [
+        proof.a, proof.b, proof.c, vk.s_1, vk.s_2, vk.s_3, vk.q_m, vk.q_l, vk.q_r, vk.q_o, vk.q_c, proof.z,
+    ];
+    let mut f = proof.t_lo.into_group() + proof.t_mid * zeta_piece + proof.t_hi * zeta_piece.square();
+    let mut eval = t;
+    let mut power = Fr::one();
+    for (cm, opening) in commitments.iter().zip(e.at_zeta()) {
+        power *= v;
+        f += *cm * power;
+        eval += power * opening;
+    }
+
+    let zeta_omega = zeta * domain.group_gen();
+    let lhs: G1Projective = proof.w_zeta.into_group() + proof.w_zeta_omega * u;
+    let rhs: G1Projective = proof.w_zeta * zeta + proof.w_zeta_omega * (u * zeta_omega) + f + proof.z * u
+        - vk.g1 * (eval + u * e.z_omega);
+    let (lhs, rhs): (G1Affine, G1Affine) = (lhs.into_affine(), (-rhs).into_affine());
+    Bls12_381::multi_pairing([lhs, rhs], [vk.tau_g2, vk.g2]).is_zero()
+}
@@ -0,0 +1,266 @@
+//! Per-gadget constraint breakdown.
+//!
+//! Synthesizes a circuit in setup mode under an arkworks `ConstraintLayer`,
+//! so every constraint carries the tracing namespaces it was created in.
+//! Constraints are then attributed to the top-level section that produced
+//! them (`ownership`, `merkle_inclusion`, ...) and to a gadget kind:
+//! Poseidon hashes, Merkle level selects, equality checks, or other
+//! (booleanity, range decomposition).
+//!
+//! Compare `profile()` across revisions to catch constraint regressions:
+//!
+//! ```rust
+//! let p = r14_circuit::profile();
+//! assert_eq!(p.merkle_levels.len(), r14_types::MERKLE_DEPTH);
+//! assert_eq!(p.poseidon.constraints + p.merkle_select + p.equality + p.other, p.total);
+//! ```
+
+use ark_bls12_381::Fr;
+use ark_relations::r1cs::{
+    ConstraintLayer, ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode,
+    TracingMode,
+};
+use serde::Serialize;
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::TransferCircuit;
+
+/// Constraint breakdown of one circuit
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct ConstraintProfile {
+    pub total: usize,
+    pub public_inputs: usize,
+    pub witnesses: usize,
+    /// Constraints per top-level namespace, in synthesis order
+    pub sections: Vec<SectionCount>,
+    pub poseidon: GadgetCount,
+    /// Constraints per Merkle level, leaf first (select + hash)
+    pub merkle_levels: Vec<usize>,
+    /// Conditional selects inside Merkle levels
+    pub merkle_select: usize,
+    /// `enforce_equal` checks outside Poseidon
+    pub equality: usize,
+    /// Everything else: booleanity, range checks
+    pub other: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SectionCount {
+    pub name: String,
+    pub constraints: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GadgetCount {
+    pub calls: usize,
+    pub constraints: usize,
+}
+
+/// Profile the transfer circuit
+pub fn profile() -> ConstraintProfile {
+    profile_circuit(TransferCircuit::<Fr>::empty())
+}
+
+/// Profile any circuit built from this crate's gadgets. Constraints created
+/// outside a namespace are reported under the `unscoped` section.
+pub fn profile_circuit<C: ConstraintSynthesizer<Fr>>(circuit: C) -> ConstraintProfile {
+    let subscriber =
+        tracing_subscriber::Registry::default().with(ConstraintLayer::new(TracingMode::OnlyConstraints));
+
+    tracing::subscriber::with_default(subscriber, || {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+        {
+            // root namespace so every constraint has a trace
+            let _ns = ark_relations::ns!(cs, "circuit");
+            circuit
+                .generate_constraints(cs.clone())
+                .expect("constraint generation failed");
+        }
+        let names = cs.constraint_names().expect("constraint tracing requires ark-relations/std");
+
+        let mut p = ConstraintProfile {
+            total: cs.num_constraints(),
+            public_inputs: cs.num_instance_variables() - 1,
+            witnesses: cs.num_witness_variables(),
+            ..Default::default()
+        };
+        let mut prev: Option<Constraint> = None;
+        for name in &names {
+            let c = Constraint::parse(name);
+            p.record(&c, prev.as_ref());
+            prev = Some(c);
+        }
+        p
+    })
+}
+
+/// Trace of one constraint: span names from the root namespace inward
+pub(crate) struct Constraint<'a> {
+    steps: Vec<&'a str>,
+}
+
+impl<'a> Constraint<'a> {
+    /// Steps look like `/circuit/r14_circuit::transfer::ownership/poseidon/...`;
+    /// keep each span's name and drop its module path.
+    pub(crate) fn parse(name: &'a str) -> Self {
+        let steps = name
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.rsplit("::").next().unwrap_or(s))
+            .collect();
+        Self { steps }
+    }
+
+    pub(crate) fn section(&self) -> &'a str {
+        self.steps.get(1).copied().unwrap_or("unscoped")
+    }
+
+    fn within(&self, span: &str) -> bool {
+        self.steps.contains(&span)
+    }
+
+    /// Steps up to and including the first `span`, identifying the call
+    fn scope(&self, span: &str) -> Option<&[&'a str]> {
+        let i = self.steps.iter().position(|s| *s == span)?;
+        Some(&self.steps[..=i])
+    }
+}
+
+impl ConstraintProfile {
+    fn record(&mut self, c: &Constraint, prev: Option<&Constraint>) {
+        match self.sections.last_mut() {
+            Some(s) if s.name == c.section() => s.constraints += 1,
+            _ => self.sections.push(SectionCount {
+                name: c.section().to_string(),
+                constraints: 1,
+            }),
+        }
+
+        let poseidon = c.within("poseidon");
+        let prev_poseidon = prev.is_some_and(|p| p.within("poseidon"));
+        if poseidon {
+            // a new hash starts when the previous constraint wasn't in the same one
+            if !prev_poseidon || prev.and_then(|p| p.scope("poseidon")) != c.scope("poseidon") {
+                self.poseidon.calls += 1;
+            }
+            self.poseidon.constraints += 1;
+        } else if c.within("merkle_level") {
+            self.merkle_select += 1;
+        } else if c.steps.iter().any(|s| s.contains("enforce_equal")) {
+            self.equality += 1;
+        } else {
+            self.other += 1;
+        }
+
+        if c.within("merkle_level") {
+            // each level is its selects followed by its hash
+            let prev_level = prev.is_some_and(|p| p.within("merkle_level"));
+            if !prev_level || (prev_poseidon && !poseidon) {
+                self.merkle_levels.push(0);
+            }
+            if let Some(level) = self.merkle_levels.last_mut() {
+                *level += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use r14_types::MERKLE_DEPTH;
+
+    #[test]
+    fn transfer_breakdown_adds_up() {
+        let p = profile();
+        assert_eq!(p.total, crate::constraint_count());
+        assert_eq!(p.public_inputs, 5);
+        assert_eq!(p.poseidon.constraints + p.merkle_select + p.equality + p.other, p.total);
+        assert_eq!(p.sections.iter().map(|s| s.constraints).sum::<usize>(), p.total);
+    }
+
+    #[test]
+    fn transfer_sections_and_gadgets() {
+        let p = profile();
+        let names: Vec<&str> = p.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "witnesses",
+                "ownership",
+                "consumed_commitment",
+                "merkle_inclusion",
+                "nullifier",
+                "output_commitments",
+                "value_conservation",
+                "app_tag",
+                "timelock",
+                "unaudited",
+            ]
+        );
+
+        // owner, consumed cm, one per level, nullifier, two output cms, plus
+        // the timelock wrap of each of the three note commitments
+        assert_eq!(p.poseidon.calls, MERKLE_DEPTH + 8);
+
+        // every level costs the same: two selects plus one 2-input hash
+        assert_eq!(p.merkle_levels.len(), MERKLE_DEPTH);
+        assert!(p.merkle_levels.iter().all(|&l| l == p.merkle_levels[0]));
+        assert_eq!(p.merkle_select, 2 * MERKLE_DEPTH);
+
+        // owner, root, nullifier, two output cms, two output value
+        // recompositions, value, two app tags, timelock, and the 31-bit app
+        // tag recomposition
+        assert_eq!(p.equality, 12);
+        // index-bit booleanity, output value, timelock and app tag range
+        // bits, and a zero test plus select per commitment
+        assert_eq!(p.other, MERKLE_DEPTH + 2 * 64 + 64 + 31 + 3 * 3);
+    }
+
+    #[test]
+    fn profile_serializes_to_json() {
+        let json = serde_json::to_value(profile()).unwrap();
+        assert!(json["total"].as_u64().unwrap() > 0);
+        assert_eq!(json["merkle_levels"].as_array().unwrap().len(), MERKLE_DEPTH);
+        assert_eq!(json["sections"][1]["name"], "ownership");
+    }
+
+    #[test]
+    fn delegated_circuit_profiles() {
+        let p = profile_circuit(crate::DelegatedTransferCircuit::empty());
+        assert_eq!(p.public_inputs, 5);
+        assert_eq!(p.merkle_levels.len(), MERKLE_DEPTH);
+        assert_eq!(p.poseidon.constraints + p.merkle_select + p.equality + p.other, p.total);
+    }
+
+    #[test]
+    fn audited_circuit_profiles() {
+        let p = profile_circuit(crate::AuditedTransferCircuit::empty());
+        assert_eq!(p.public_inputs, 14);
+        assert_eq!(p.poseidon.constraints + p.merkle_select + p.equality + p.other, p.total);
+        assert_eq!(p.sections.last().unwrap().name, "audit");
+        // the transfer's hashes plus one pad per ciphertext element
+        assert_eq!(p.poseidon.calls, MERKLE_DEPTH + 8 + 4);
+    }
+
+    #[test]
+    fn signed_circuit_profiles() {
+        let p = profile_circuit(crate::SignedTransferCircuit::empty());
+        assert_eq!(p.public_inputs, 5);
+        assert_eq!(p.poseidon.constraints + p.merkle_select + p.equality + p.other, p.total);
+        let names: Vec<&str> = p.sections.iter().map(|s| s.name.as_str()).collect();
+        assert!(!names.contains(&"ownership"));
+        assert_eq!(names.last(), Some(&"spend_auth"));
+    }
+
+    #[test]
+    fn swap_circuit_profiles() {
+        let p = profile_circuit(crate::SwapCircuit::empty());
+        assert_eq!(p.public_inputs, 10);
+        assert_eq!(p.merkle_levels.len(), 2 * MERKLE_DEPTH);
+        assert_eq!(p.poseidon.constraints + p.merkle_select + p.equality + p.other, p.total);
+        assert_eq!(p.sections.last().unwrap().name, "swap");
+    }
+}
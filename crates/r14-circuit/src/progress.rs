@@ -0,0 +1,219 @@
+//! Groth16 proving with progress reports.
+//!
+//! `Groth16::prove` is one opaque call that takes seconds for the transfer
+//! circuit. [`create_proof`] does the same work in its stages — synthesize
+//! the witness, reduce it to the QAP (the FFTs), then the multi-scalar
+//! multiplications — and calls back after each one. The MSMs dominate, so
+//! they are split into chunks of [`MSM_CHUNK`] bases and reported as they
+//! go.
+//!
+//! The proof is the same as `Groth16::prove` would give for the same
+//! randomness. [`create_proof_cancellable`] also checks a [`CancelToken`]
+//! at each of those points and stops with `CircuitError::Cancelled`.
+//!
+//! A callback can feed a channel as easily as a progress bar:
+//!
+//! ```rust,no_run
+//! use std::sync::mpsc;
+//! use r14_circuit::ProveProgress;
+//! # fn example(
+//! #     pk: ark_groth16::ProvingKey<ark_bls12_381::Bls12_381>,
+//! #     sk: ark_bls12_381::Fr,
+//! #     note: r14_types::Note,
+//! #     path: r14_types::MerklePath,
+//! #     outputs: [r14_types::Note; 2],
+//! # ) -> Result<(), r14_circuit::CircuitError> {
+//! let (tx, rx) = mpsc::channel::<ProveProgress>();
+//! let printer = std::thread::spawn(move || {
+//!     for p in rx {
+//!         if let ProveProgress::Msm { done, total } = p {
+//!             println!("{}%", done * 100 / total);
+//!         }
+//!     }
+//! });
+//! let mut rng = r14_sdk::wallet::crypto_rng();
+//! let (proof, pi) = r14_circuit::prove_with_progress(&pk, sk, note, path, outputs, 0, &mut rng, |p| {
+//!     let _ = tx.send(p);
+//! })?;
+//! drop(tx);
+//! printer.join().unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{PrimeField, UniformRand};
+use ark_groth16::r1cs_to_qap::{LibsnarkReduction, R1CSToQAP};
+use ark_groth16::{Proof, ProvingKey};
+use ark_poly::GeneralEvaluationDomain;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal};
+use ark_std::cfg_iter;
+use ark_std::rand::{CryptoRng, RngCore};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::{synthesis_error, CircuitError};
+
+/// Bases per reported MSM step. Smaller chunks report more often but
+/// make Pippenger's bucket method less efficient.
+pub const MSM_CHUNK: usize = 1 << 13;
+
+/// One step of [`create_proof`], in order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProveProgress {
+    /// The proving key is in memory and proving starts
+    KeyLoaded,
+    /// The circuit is synthesized with its witness assigned
+    WitnessBuilt { constraints: usize },
+    /// The witness is reduced to QAP form
+    WitnessMapped,
+    /// `done` of `total` MSM bases multiplied; `done == total` last
+    Msm { done: usize, total: usize },
+    /// The proof is assembled
+    Done,
+}
+
+/// Asks a proof in progress to stop. Clones share the flag, so one can
+/// go to the proving thread and another stay with whoever cancels.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop at the next stage or MSM chunk; there's no undoing it
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn check(&self) -> Result<(), CircuitError> {
+        if self.is_cancelled() {
+            return Err(CircuitError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// Generate a Groth16 proof for `circuit`, reporting each stage to
+/// `on_progress`
+pub fn create_proof<E, C, R>(
+    pk: &ProvingKey<E>,
+    circuit: C,
+    rng: &mut R,
+    on_progress: &mut dyn FnMut(ProveProgress),
+) -> Result<Proof<E>, CircuitError>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: RngCore + CryptoRng,
+{
+    create_proof_cancellable(pk, circuit, rng, &CancelToken::new(), on_progress)
+}
+
+/// [`create_proof`] that gives up with `CircuitError::Cancelled` once
+/// `cancel` is set. Synthesis and the QAP reduction run to completion;
+/// the MSMs stop within a chunk.
+pub fn create_proof_cancellable<E, C, R>(
+    pk: &ProvingKey<E>,
+    circuit: C,
+    rng: &mut R,
+    cancel: &CancelToken,
+    on_progress: &mut dyn FnMut(ProveProgress),
+) -> Result<Proof<E>, CircuitError>
+where
+    E: Pairing,
+    C: ConstraintSynthesizer<E::ScalarField>,
+    R: RngCore + CryptoRng,
+{
+    cancel.check()?;
+    on_progress(ProveProgress::KeyLoaded);
+    let r = E::ScalarField::rand(rng);
+    let s = E::ScalarField::rand(rng);
+
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    circuit.generate_constraints(cs.clone()).map_err(synthesis_error)?;
+    cs.finalize();
+    cancel.check()?;
+    on_progress(ProveProgress::WitnessBuilt { constraints: cs.num_constraints() });
+
+    let h = LibsnarkReduction::witness_map::<E::ScalarField, GeneralEvaluationDomain<E::ScalarField>>(cs.clone())
+        .map_err(synthesis_error)?;
+    cancel.check()?;
+    on_progress(ProveProgress::WitnessMapped);
+
+    let prover = cs.borrow().expect("constraint system is still shared");
+    let bigints = |v: &[E::ScalarField]| cfg_iter!(v).map(|x| x.into_bigint()).collect::<Vec<_>>();
+    let h = bigints(&h);
+    let aux = bigints(&prover.witness_assignment);
+    let assignment = [bigints(&prover.instance_assignment[1..]), aux.clone()].concat();
+    drop(prover);
+
+    let mut msm = Msm {
+        done: 0,
+        total: pk.h_query.len().min(h.len())
+            + pk.l_query.len().min(aux.len())
+            + 2 * (pk.a_query.len() - 1).min(assignment.len())
+            + (pk.b_g2_query.len() - 1).min(assignment.len()),
+        cancel,
+        on_progress,
+    };
+
+    let h_acc = msm.run::<E::G1>(&pk.h_query, &h)?;
+    let l_aux_acc = msm.run::<E::G1>(&pk.l_query, &aux)?;
+
+    // A = alpha + sum(a_i) + r * delta
+    let g_a = pk.delta_g1 * r + pk.a_query[0] + msm.run::<E::G1>(&pk.a_query[1..], &assignment)? + pk.vk.alpha_g1;
+    // B in G1, only needed for C
+    let g1_b =
+        pk.delta_g1 * s + pk.b_g1_query[0] + msm.run::<E::G1>(&pk.b_g1_query[1..], &assignment)? + pk.beta_g1;
+    let g2_b =
+        pk.vk.delta_g2 * s + pk.b_g2_query[0] + msm.run::<E::G2>(&pk.b_g2_query[1..], &assignment)? + pk.vk.beta_g2;
+
+    // C = s*A + r*B + sum(l_i) + h(x)t(x) - r*s*delta
+    let g_c = g_a * s + g1_b * r - pk.delta_g1 * (r * s) + l_aux_acc + h_acc;
+
+    let proof = Proof {
+        a: g_a.into_affine(),
+        b: g2_b.into_affine(),
+        c: g_c.into_affine(),
+    };
+    (msm.on_progress)(ProveProgress::Done);
+    Ok(proof)
+}
+
+/// MSMs run in chunks, counting bases across all of them
+struct Msm<'a> {
+    done: usize,
+    total: usize,
+    cancel: &'a CancelToken,
+    on_progress: &'a mut dyn FnMut(ProveProgress),
+}
+
+impl Msm<'_> {
+    fn run<G: VariableBaseMSM>(
+        &mut self,
+        bases: &[G::MulBase],
+        scalars: &[<G::ScalarField as PrimeField>::BigInt],
+    ) -> Result<G, CircuitError> {
+        let n = bases.len().min(scalars.len());
+        let mut acc = G::zero();
+        for start in (0..n).step_by(MSM_CHUNK) {
+            self.cancel.check()?;
+            let end = (start + MSM_CHUNK).min(n);
+            acc += G::msm_bigint(&bases[start..end], &scalars[start..end]);
+            self.done += end - start;
+            (self.on_progress)(ProveProgress::Msm { done: self.done, total: self.total });
+        }
+        Ok(acc)
+    }
+}
@@ -0,0 +1,38 @@
+use ark_ff::PrimeField;
+use ark_r1cs_std::{
+    alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar, fields::FieldVar,
+};
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+/// Enforce `val` fits in 64 bits by decomposing into Boolean witnesses.
+///
+/// `native_val` supplies the witness; it is `None` during setup.
+pub fn enforce_u64<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    val: &FpVar<F>,
+    native_val: Option<u64>,
+) -> Result<(), SynthesisError> {
+    enforce_bits(cs, val, native_val, 64)
+}
+
+/// Enforce `val` fits in `bits` bits (at most 64)
+pub fn enforce_bits<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    val: &FpVar<F>,
+    native_val: Option<u64>,
+    bits: usize,
+) -> Result<(), SynthesisError> {
+    debug_assert!(bits <= 64);
+    let mut sum = FpVar::zero();
+    let mut coeff = F::ONE;
+    for i in 0..bits {
+        let bit = Boolean::new_witness(cs.clone(), || {
+            let v = native_val.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok((v >> i) & 1 == 1)
+        })?;
+        sum += FpVar::from(bit) * coeff;
+        coeff.double_in_place();
+    }
+    sum.enforce_equal(val)?;
+    Ok(())
+}
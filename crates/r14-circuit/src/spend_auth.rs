@@ -0,0 +1,371 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bls12_381::{constraints::EdwardsVar, EdwardsAffine, Fr as JubjubScalar};
+use ark_ff::UniformRand;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar, fields::FieldVar};
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_poseidon::SPEND_AUTH_DOMAIN;
+use r14_types::{MerklePath, Note, OwnerHash, SecretKey};
+
+use crate::jubjub_gadget::{schnorr_challenge, scalar_bits_witness, verify_schnorr};
+use crate::poseidon_gadget::poseidon_hash_var;
+use crate::transfer::{enforce_unaudited, Ownership, TransferCircuit, TransferVars};
+use crate::{check_merkle_path, check_unaudited, synthesis_error, CircuitError, PublicInputs};
+
+/// Spend-authorization key, a Jubjub scalar. It can stay on a hardware
+/// wallet: the prover only needs its public key and a signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpendAuthKey(pub JubjubScalar);
+
+/// `ask·G` on Jubjub
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpendAuthPublicKey(pub EdwardsAffine);
+
+/// Schnorr signature `(r, s)` over a spend digest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpendAuthSignature {
+    pub r: EdwardsAffine,
+    pub s: JubjubScalar,
+}
+
+impl SpendAuthKey {
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self(JubjubScalar::rand(rng))
+    }
+
+    pub fn public_key(&self) -> SpendAuthPublicKey {
+        SpendAuthPublicKey((EdwardsAffine::generator() * self.0).into_affine())
+    }
+
+    /// Sign a [`spend_digest`]
+    pub fn sign<R: RngCore + CryptoRng>(&self, digest: Fr, rng: &mut R) -> SpendAuthSignature {
+        let k = JubjubScalar::rand(rng);
+        let r = (EdwardsAffine::generator() * k).into_affine();
+        let c = schnorr_challenge(&r, &self.public_key().0, digest);
+        SpendAuthSignature { r, s: k + c * self.0 }
+    }
+}
+
+impl SpendAuthPublicKey {
+    /// Owner hash of notes spent with this key and `nullifier_key`:
+    /// Poseidon(domain, ak.x, ak.y, nullifier_key)
+    pub fn owner(&self, nullifier_key: &SecretKey) -> OwnerHash {
+        r14_poseidon::spend_auth_owner(&self.0.x, &self.0.y, nullifier_key)
+    }
+
+    pub fn verify(&self, digest: Fr, sig: &SpendAuthSignature) -> bool {
+        let c = schnorr_challenge(&sig.r, &self.0, digest);
+        EdwardsAffine::generator() * sig.s == sig.r + self.0 * c
+    }
+}
+
+/// What a spend's signature covers: Poseidon over its five public inputs,
+/// which fix the spent note, both outputs and the ledger
+pub fn spend_digest(public_inputs: &PublicInputs) -> Fr {
+    r14_poseidon::poseidon_hash(&public_inputs.to_vec())
+}
+
+/// Transfer authorized by a signature instead of the owner's secret key.
+///
+/// The note owner is Poseidon(domain, ak, nk) for a spend-authorization key
+/// `ak` and a nullifier key `nk`. The prover knows `nk`, which derives the
+/// nullifier exactly as a secret key does, but not `ak`'s secret: it proves
+/// knowledge of a signature by `ak` over [`spend_digest`] instead. Public
+/// inputs are the transfer's five.
+#[derive(Clone)]
+pub struct SignedTransferCircuit {
+    /// The spend; its `secret_key` holds the nullifier key
+    pub transfer: TransferCircuit,
+    // Private witnesses
+    pub auth_key: Option<SpendAuthPublicKey>,
+    pub signature: Option<SpendAuthSignature>,
+}
+
+impl SignedTransferCircuit {
+    /// Create a circuit with None witnesses (for setup)
+    pub fn empty() -> Self {
+        Self {
+            transfer: TransferCircuit::empty(),
+            auth_key: None,
+            signature: None,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for SignedTransferCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let app_tag = self.transfer.consumed_note.as_ref().map(|n| n.app_tag);
+
+        // === Constraints 2-9: the transfer, minus its secret-key ownership ===
+        let vars = self.transfer.synthesize_with(cs.clone(), Ownership::Deferred)?;
+        enforce_unaudited(cs.clone(), &vars.app_tag, app_tag)?;
+
+        // === Constraint 1: Ownership, by signature over the spend ===
+        let digest = {
+            let _ns = ns!(cs, "spend_digest");
+            poseidon_hash_var(cs.clone(), &vars.public_inputs)?
+        };
+        enforce_spend_auth(cs, &vars, self.auth_key, self.signature, &digest)
+    }
+}
+
+/// Constrain a transfer spent with [`Ownership::Deferred`] to a spend-auth
+/// key: owner == Poseidon(domain, ak, nk), and `ak` signed `digest`. The
+/// transfer's nullifier key is `nk`.
+pub(crate) fn enforce_spend_auth(
+    cs: ConstraintSystemRef<Fr>,
+    vars: &TransferVars<Fr>,
+    auth_key: Option<SpendAuthPublicKey>,
+    signature: Option<SpendAuthSignature>,
+    digest: &FpVar<Fr>,
+) -> Result<(), SynthesisError> {
+    // === Private witnesses ===
+    let witnesses_ns = ns!(cs, "witnesses");
+    let auth_key = EdwardsVar::new_witness(cs.clone(), || {
+        auth_key.map(|k| k.0).ok_or(SynthesisError::AssignmentMissing)
+    })?;
+    let sig_r = EdwardsVar::new_witness(cs.clone(), || {
+        signature.map(|s| s.r).ok_or(SynthesisError::AssignmentMissing)
+    })?;
+    let sig_s = scalar_bits_witness(cs.clone(), signature.map(|s| s.s))?;
+    drop(witnesses_ns);
+
+    let _ns = ns!(cs, "spend_auth");
+    let computed_owner = poseidon_hash_var(
+        cs.clone(),
+        &[
+            FpVar::constant(Fr::from(SPEND_AUTH_DOMAIN)),
+            auth_key.x.clone(),
+            auth_key.y.clone(),
+            vars.nullifier_key.clone(),
+        ],
+    )?;
+    computed_owner.enforce_equal(&vars.consumed_owner)?;
+    verify_schnorr(cs.clone(), &auth_key, digest, &sig_r, &sig_s)
+}
+
+/// Run Groth16 trusted setup for the signed transfer circuit
+pub fn setup_signed<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>), CircuitError> {
+    let circuit = SignedTransferCircuit::empty();
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).map_err(synthesis_error)
+}
+
+/// Public inputs of a signed spend, computed before proving so the
+/// hardware wallet can sign their [`spend_digest`].
+///
+/// Fails like [`crate::prove`].
+pub fn signed_public_inputs(
+    nullifier_key: Fr,
+    consumed_note: &Note,
+    merkle_path: &MerklePath,
+    created_notes: &[Note; 2],
+    current_ledger: u64,
+) -> Result<PublicInputs, CircuitError> {
+    check_merkle_path(merkle_path)?;
+    if consumed_note.unlock_after > current_ledger {
+        return Err(CircuitError::InvalidWitness(format!(
+            "note is locked until ledger {}, proving at {current_ledger}",
+            consumed_note.unlock_after
+        )));
+    }
+    check_unaudited(consumed_note)?;
+    Ok(PublicInputs {
+        old_root: crate::compute_root(r14_poseidon::commitment(consumed_note), merkle_path),
+        nullifier: r14_poseidon::nullifier_hash(nullifier_key, consumed_note.nonce, merkle_path.leaf_index()),
+        out_commitment_0: r14_poseidon::commitment(&created_notes[0]).0,
+        out_commitment_1: r14_poseidon::commitment(&created_notes[1]).0,
+        current_ledger: Fr::from(current_ledger),
+    })
+}
+
+/// Generate a Groth16 proof for a spend signed by `auth_key`. Verify it
+/// with [`crate::verify_offchain`] against the signed circuit's key.
+///
+/// Fails like [`signed_public_inputs`], and with
+/// `CircuitError::InvalidWitness` if `signature` doesn't cover this spend.
+#[allow(clippy::too_many_arguments)]
+pub fn prove_signed<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    auth_key: &SpendAuthPublicKey,
+    nullifier_key: Fr,
+    signature: &SpendAuthSignature,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<Bls12_381>, PublicInputs), CircuitError> {
+    let (circuit, public_inputs) = signed_witness(
+        auth_key,
+        nullifier_key,
+        signature,
+        consumed_note,
+        merkle_path,
+        created_notes,
+        current_ledger,
+    )?;
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).map_err(synthesis_error)?;
+    Ok((proof, public_inputs))
+}
+
+/// Check the witness and signature, and build the signed circuit and its
+/// public inputs
+pub(crate) fn signed_witness(
+    auth_key: &SpendAuthPublicKey,
+    nullifier_key: Fr,
+    signature: &SpendAuthSignature,
+    consumed_note: Note,
+    merkle_path: MerklePath,
+    created_notes: [Note; 2],
+    current_ledger: u64,
+) -> Result<(SignedTransferCircuit, PublicInputs), CircuitError> {
+    let public_inputs =
+        signed_public_inputs(nullifier_key, &consumed_note, &merkle_path, &created_notes, current_ledger)?;
+    if !auth_key.verify(spend_digest(&public_inputs), signature) {
+        return Err(CircuitError::InvalidWitness("signature doesn't cover this spend".into()));
+    }
+
+    let circuit = SignedTransferCircuit {
+        transfer: TransferCircuit {
+            secret_key: Some(nullifier_key),
+            consumed_note: Some(consumed_note),
+            merkle_path: Some(merkle_path),
+            created_notes: Some(created_notes),
+            current_ledger: Some(current_ledger),
+        },
+        auth_key: Some(*auth_key),
+        signature: Some(*signature),
+    };
+
+    Ok((circuit, public_inputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::MERKLE_DEPTH;
+
+    struct Scenario {
+        ask: SpendAuthKey,
+        nk: SecretKey,
+        consumed: Note,
+        path: MerklePath,
+        created: [Note; 2],
+    }
+
+    fn scenario(rng: &mut StdRng) -> Scenario {
+        let ask = SpendAuthKey::random(rng);
+        let nk = SecretKey::random(rng);
+        let owner = ask.public_key().owner(&nk);
+        let siblings: Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect();
+        let indices: Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 3 == 0).collect();
+        Scenario {
+            consumed: Note::new(1000, 1, owner.0, rng),
+            path: MerklePath { siblings, indices },
+            created: [Note::new(650, 1, Fr::rand(rng), rng), Note::new(350, 1, owner.0, rng)],
+            ask,
+            nk,
+        }
+    }
+
+    fn circuit(s: &Scenario, signature: SpendAuthSignature) -> SignedTransferCircuit {
+        SignedTransferCircuit {
+            transfer: TransferCircuit {
+                secret_key: Some(s.nk.0),
+                consumed_note: Some(s.consumed.clone()),
+                merkle_path: Some(s.path.clone()),
+                created_notes: Some(s.created.clone()),
+                current_ledger: Some(0),
+            },
+            auth_key: Some(s.ask.public_key()),
+            signature: Some(signature),
+        }
+    }
+
+    fn digest(s: &Scenario) -> Fr {
+        spend_digest(&signed_public_inputs(s.nk.0, &s.consumed, &s.path, &s.created, 0).unwrap())
+    }
+
+    fn is_satisfied(c: SignedTransferCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        c.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let ask = SpendAuthKey::random(&mut rng);
+        let digest = Fr::rand(&mut rng);
+        let sig = ask.sign(digest, &mut rng);
+        assert!(ask.public_key().verify(digest, &sig));
+        assert!(!ask.public_key().verify(digest + Fr::from(1u64), &sig));
+        assert!(!SpendAuthKey::random(&mut rng).public_key().verify(digest, &sig));
+    }
+
+    #[test]
+    fn test_signed_spend_satisfied() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sig = s.ask.sign(digest(&s), &mut rng);
+        assert!(is_satisfied(circuit(&s, sig)));
+    }
+
+    #[test]
+    fn test_signature_over_other_spend() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sig = s.ask.sign(Fr::rand(&mut rng), &mut rng);
+        assert!(!is_satisfied(circuit(&s, sig)), "should fail: signature covers another digest");
+    }
+
+    #[test]
+    fn test_wrapped_output_value() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sig = s.ask.sign(digest(&s), &mut rng);
+        let failures = crate::transfer::tests::wrapped_output_failures(circuit(&s, sig));
+        assert!(failures.iter().any(|s| s == "value_conservation"), "{failures:?}");
+    }
+
+    #[test]
+    fn test_wrong_nullifier_key() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sig = s.ask.sign(digest(&s), &mut rng);
+        let mut c = circuit(&s, sig);
+        c.transfer.secret_key = Some(Fr::rand(&mut rng));
+        assert!(!is_satisfied(c), "should fail: owner needs the right nullifier key");
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let (pk, vk) = setup_signed(&mut rng).unwrap();
+        let ak = s.ask.public_key();
+
+        let bad = s.ask.sign(Fr::rand(&mut rng), &mut rng);
+        let err = prove_signed(&pk, &ak, s.nk.0, &bad, s.consumed.clone(), s.path.clone(), s.created.clone(), 0, &mut rng)
+            .err()
+            .unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+
+        let sig = s.ask.sign(digest(&s), &mut rng);
+        let (proof, mut pi) = prove_signed(&pk, &ak, s.nk.0, &sig, s.consumed, s.path, s.created, 0, &mut rng).unwrap();
+        assert!(crate::verify_offchain(&vk, &proof, &pi));
+
+        pi.out_commitment_0 = Fr::rand(&mut rng);
+        assert!(!crate::verify_offchain(&vk, &proof, &pi));
+    }
+}
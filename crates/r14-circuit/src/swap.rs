@@ -0,0 +1,361 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_r1cs_std::eq::EqGadget;
+use ark_relations::{
+    ns,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_types::{MerklePath, Note};
+
+use crate::poseidon_gadget::poseidon_hash_var;
+use crate::spend_auth::{enforce_spend_auth, signed_public_inputs, SpendAuthPublicKey, SpendAuthSignature};
+use crate::transfer::{enforce_unaudited, Ownership, TransferCircuit};
+use crate::{synthesis_error, CircuitError, PublicInputs};
+
+/// Two transfers settled atomically, each spending a note of a different
+/// asset (app tag).
+///
+/// Output 0 of each leg pays the other leg's spender, at the owner hash of
+/// the note that leg consumed; output 1 is the leg's change. Public inputs
+/// are leg A's five transfer inputs, then leg B's.
+///
+/// Each leg is spent like a [`crate::SignedTransferCircuit`]: its owner is
+/// Poseidon(domain, ak, nk), and `ak` signs [`swap_digest`]. So each party
+/// authorizes its own leg with its own key, over terms that fix both legs'
+/// outputs. The prover learns both nullifier keys, which link a party's
+/// spends, but can't spend anything without a signature.
+#[derive(Clone)]
+pub struct SwapCircuit {
+    /// Each leg's spend; its `secret_key` holds that party's nullifier key
+    pub legs: [TransferCircuit; 2],
+    // Private witnesses, one per leg
+    pub auth_keys: [Option<SpendAuthPublicKey>; 2],
+    pub signatures: [Option<SpendAuthSignature>; 2],
+}
+
+impl SwapCircuit {
+    /// Create a circuit with None witnesses (for setup)
+    pub fn empty() -> Self {
+        Self {
+            legs: [TransferCircuit::empty(), TransferCircuit::empty()],
+            auth_keys: [None; 2],
+            signatures: [None; 2],
+        }
+    }
+}
+
+/// One party's side of a swap
+#[derive(Clone)]
+pub struct SwapLeg {
+    /// The party's spend-auth public key; its secret signs [`swap_digest`]
+    pub auth_key: SpendAuthPublicKey,
+    pub nullifier_key: Fr,
+    pub consumed_note: Note,
+    pub merkle_path: MerklePath,
+    /// `[payment to the other party, change]`
+    pub created_notes: [Note; 2],
+}
+
+/// Public inputs for a swap proof, one transfer's worth per leg
+pub struct SwapPublicInputs {
+    pub legs: [PublicInputs; 2],
+}
+
+impl SwapPublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        self.legs.iter().flat_map(PublicInputs::to_vec).collect()
+    }
+}
+
+/// What both parties sign: Poseidon over all ten public inputs, so each
+/// signature fixes the other leg's payment as well as its own spend
+pub fn swap_digest(public_inputs: &SwapPublicInputs) -> Fr {
+    r14_poseidon::poseidon_hash(&public_inputs.to_vec())
+}
+
+impl ConstraintSynthesizer<Fr> for SwapCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let [leg_a, leg_b] = self.legs;
+        let tag_a = leg_a.consumed_note.as_ref().map(|n| n.app_tag);
+        let tag_b = leg_b.consumed_note.as_ref().map(|n| n.app_tag);
+
+        // === Constraints 2-9, per leg ===
+        let a = leg_a.synthesize_with(cs.clone(), Ownership::Deferred)?;
+        enforce_unaudited(cs.clone(), &a.app_tag, tag_a)?;
+        let b = leg_b.synthesize_with(cs.clone(), Ownership::Deferred)?;
+        enforce_unaudited(cs.clone(), &b.app_tag, tag_b)?;
+
+        // === Constraint 1: Ownership, each leg signed by its spender ===
+        let digest = {
+            let _ns = ns!(cs, "swap_digest");
+            let inputs: Vec<_> = a.public_inputs.iter().chain(&b.public_inputs).cloned().collect();
+            poseidon_hash_var(cs.clone(), &inputs)?
+        };
+        let [key_a, key_b] = self.auth_keys;
+        let [sig_a, sig_b] = self.signatures;
+        enforce_spend_auth(cs.clone(), &a, key_a, sig_a, &digest)?;
+        enforce_spend_auth(cs.clone(), &b, key_b, sig_b, &digest)?;
+
+        // === Constraint 10: Swap terms ===
+        // each payment goes to the other spender, in the other asset
+        let _ns = ns!(cs, "swap");
+        a.created_owners[0].enforce_equal(&b.consumed_owner)?;
+        b.created_owners[0].enforce_equal(&a.consumed_owner)?;
+        a.app_tag.enforce_not_equal(&b.app_tag)
+    }
+}
+
+/// Run Groth16 trusted setup for the swap circuit
+pub fn setup_swap<R: RngCore + CryptoRng>(
+    rng: &mut R,
+) -> Result<(ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>), CircuitError> {
+    let circuit = SwapCircuit::empty();
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).map_err(synthesis_error)
+}
+
+/// Public inputs of a swap of `legs[0]`'s asset against `legs[1]`'s, both
+/// spent at `current_ledger`, computed before proving so each party can
+/// sign their [`swap_digest`].
+///
+/// Fails with `CircuitError::InvalidWitness` if either leg would fail
+/// [`signed_public_inputs`], both legs spend the same asset, or a leg's
+/// payment isn't owned by the other leg's spender.
+pub fn swap_public_inputs(legs: &[SwapLeg; 2], current_ledger: u64) -> Result<SwapPublicInputs, CircuitError> {
+    let [a, b] = legs;
+    if a.consumed_note.app_tag == b.consumed_note.app_tag {
+        return Err(CircuitError::InvalidWitness(format!(
+            "both legs spend app_tag {}",
+            a.consumed_note.app_tag
+        )));
+    }
+    if a.created_notes[0].owner != b.consumed_note.owner || b.created_notes[0].owner != a.consumed_note.owner {
+        return Err(CircuitError::InvalidWitness("each payment must go to the other leg's spender".into()));
+    }
+    let leg_inputs = |leg: &SwapLeg| {
+        signed_public_inputs(leg.nullifier_key, &leg.consumed_note, &leg.merkle_path, &leg.created_notes, current_ledger)
+    };
+    Ok(SwapPublicInputs {
+        legs: [leg_inputs(a)?, leg_inputs(b)?],
+    })
+}
+
+/// Generate a Groth16 proof for a swap, with `signatures[i]` by
+/// `legs[i].auth_key` over the swap's [`swap_digest`].
+///
+/// Fails like [`swap_public_inputs`], and with
+/// `CircuitError::InvalidWitness` if a signature doesn't cover this swap.
+pub fn prove_swap<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    legs: [SwapLeg; 2],
+    signatures: [SpendAuthSignature; 2],
+    current_ledger: u64,
+    rng: &mut R,
+) -> Result<(ark_groth16::Proof<Bls12_381>, SwapPublicInputs), CircuitError> {
+    let (circuit, public_inputs) = swap_witness(legs, signatures, current_ledger)?;
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).map_err(synthesis_error)?;
+    Ok((proof, public_inputs))
+}
+
+/// Check both legs and signatures, and build the swap circuit and its
+/// public inputs
+pub(crate) fn swap_witness(
+    legs: [SwapLeg; 2],
+    signatures: [SpendAuthSignature; 2],
+    current_ledger: u64,
+) -> Result<(SwapCircuit, SwapPublicInputs), CircuitError> {
+    let public_inputs = swap_public_inputs(&legs, current_ledger)?;
+    let digest = swap_digest(&public_inputs);
+    for (leg, signature) in legs.iter().zip(&signatures) {
+        if !leg.auth_key.verify(digest, signature) {
+            return Err(CircuitError::InvalidWitness("signature doesn't cover this swap".into()));
+        }
+    }
+
+    let circuit = SwapCircuit {
+        auth_keys: legs.each_ref().map(|leg| Some(leg.auth_key)),
+        signatures: signatures.map(Some),
+        legs: legs.map(|leg| TransferCircuit {
+            secret_key: Some(leg.nullifier_key),
+            consumed_note: Some(leg.consumed_note),
+            merkle_path: Some(leg.merkle_path),
+            created_notes: Some(leg.created_notes),
+            current_ledger: Some(current_ledger),
+        }),
+    };
+
+    Ok((circuit, public_inputs))
+}
+
+/// Verify a swap proof off-chain
+pub fn verify_swap_offchain(
+    vk: &VerifyingKey<Bls12_381>,
+    proof: &ark_groth16::Proof<Bls12_381>,
+    public_inputs: &SwapPublicInputs,
+) -> bool {
+    let pvk = PreparedVerifyingKey::from(vk.clone());
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs.to_vec(), proof).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use r14_types::{SecretKey, MERKLE_DEPTH};
+
+    use crate::SpendAuthKey;
+
+    struct Scenario {
+        keys: [SpendAuthKey; 2],
+        legs: [SwapLeg; 2],
+    }
+
+    /// Alice swaps 100 of asset 1 for Bob's 40 of asset 2
+    fn scenario(rng: &mut StdRng) -> Scenario {
+        let keys = [SpendAuthKey::random(rng), SpendAuthKey::random(rng)];
+        let nks = [SecretKey::random(rng), SecretKey::random(rng)];
+        let alice_owner = keys[0].public_key().owner(&nks[0]).0;
+        let bob_owner = keys[1].public_key().owner(&nks[1]).0;
+        let mut path = || MerklePath {
+            siblings: (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect(),
+            indices: (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect(),
+        };
+        let (path_a, path_b) = (path(), path());
+        let legs = [
+            SwapLeg {
+                auth_key: keys[0].public_key(),
+                nullifier_key: nks[0].0,
+                consumed_note: Note::new(150, 1, alice_owner, rng),
+                merkle_path: path_a,
+                created_notes: [Note::new(100, 1, bob_owner, rng), Note::new(50, 1, alice_owner, rng)],
+            },
+            SwapLeg {
+                auth_key: keys[1].public_key(),
+                nullifier_key: nks[1].0,
+                consumed_note: Note::new(40, 2, bob_owner, rng),
+                merkle_path: path_b,
+                created_notes: [Note::new(40, 2, alice_owner, rng), Note::new(0, 2, bob_owner, rng)],
+            },
+        ];
+        Scenario { keys, legs }
+    }
+
+    /// Both parties' signatures over the swap `legs` describe
+    fn sign(s: &Scenario, legs: &[SwapLeg; 2], rng: &mut StdRng) -> [SpendAuthSignature; 2] {
+        let digest = swap_digest(&swap_public_inputs(legs, 0).unwrap());
+        [s.keys[0].sign(digest, rng), s.keys[1].sign(digest, rng)]
+    }
+
+    fn circuit(legs: &[SwapLeg; 2], signatures: [SpendAuthSignature; 2]) -> SwapCircuit {
+        SwapCircuit {
+            auth_keys: legs.each_ref().map(|leg| Some(leg.auth_key)),
+            signatures: signatures.map(Some),
+            legs: legs.clone().map(|leg| TransferCircuit {
+                secret_key: Some(leg.nullifier_key),
+                consumed_note: Some(leg.consumed_note),
+                merkle_path: Some(leg.merkle_path),
+                created_notes: Some(leg.created_notes),
+                current_ledger: Some(0),
+            }),
+        }
+    }
+
+    fn is_satisfied(c: SwapCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        // `enforce_not_equal` can't invert a zero difference and errors out
+        c.generate_constraints(cs.clone()).is_ok() && cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_swap_satisfied() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sigs = sign(&s, &s.legs, &mut rng);
+        assert!(is_satisfied(circuit(&s.legs, sigs)));
+    }
+
+    #[test]
+    fn test_payment_to_third_party() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sigs = sign(&s, &s.legs, &mut rng);
+        let mut legs = s.legs.clone();
+        legs[0].created_notes[0].owner = Fr::rand(&mut rng);
+        assert!(!is_satisfied(circuit(&legs, sigs)), "should fail: Bob doesn't receive asset 1");
+    }
+
+    #[test]
+    fn test_same_asset() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sigs = sign(&s, &s.legs, &mut rng);
+        let mut legs = s.legs.clone();
+        legs[1].consumed_note.app_tag = 1;
+        for note in &mut legs[1].created_notes {
+            note.app_tag = 1;
+        }
+        assert!(!is_satisfied(circuit(&legs, sigs)), "should fail: both legs spend asset 1");
+    }
+
+    #[test]
+    fn test_each_leg_needs_its_own_signature() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let digest = swap_digest(&swap_public_inputs(&s.legs, 0).unwrap());
+        // Alice's key can't authorize Bob's leg
+        let sigs = [s.keys[0].sign(digest, &mut rng), s.keys[0].sign(digest, &mut rng)];
+        let mut c = circuit(&s.legs, sigs);
+        assert!(!is_satisfied(c.clone()), "should fail: leg B isn't signed by Bob");
+        c.auth_keys[1] = Some(s.keys[0].public_key());
+        assert!(!is_satisfied(c), "should fail: leg B isn't owned by Alice's key");
+    }
+
+    #[test]
+    fn test_signature_over_other_terms() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        // Bob signed for a smaller payment to Alice than the legs make
+        let mut smaller = s.legs.clone();
+        let [to_alice, change] = smaller[1].created_notes.clone().map(|n| n.owner);
+        smaller[1].created_notes = [Note::new(10, 2, to_alice, &mut rng), Note::new(30, 2, change, &mut rng)];
+        let [sig_a, _] = sign(&s, &s.legs, &mut rng);
+        let [_, sig_b] = sign(&s, &smaller, &mut rng);
+        assert!(!is_satisfied(circuit(&s.legs, [sig_a, sig_b])), "should fail: Bob signed other terms");
+    }
+
+    #[test]
+    fn test_wrapped_output_value() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let sigs = sign(&s, &s.legs, &mut rng);
+        let failures = crate::transfer::tests::wrapped_output_failures(circuit(&s.legs, sigs));
+        assert!(failures.iter().any(|s| s == "value_conservation"), "{failures:?}");
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let s = scenario(&mut rng);
+        let (pk, vk) = setup_swap(&mut rng).unwrap();
+
+        let mut bad = s.legs.clone();
+        bad[1].created_notes[0].owner = Fr::rand(&mut rng);
+        let sigs = sign(&s, &s.legs, &mut rng);
+        let err = prove_swap(&pk, bad, sigs, 0, &mut rng).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+
+        let forged = [sigs[0], s.keys[0].sign(Fr::rand(&mut rng), &mut rng)];
+        let err = prove_swap(&pk, s.legs.clone(), forged, 0, &mut rng).err().unwrap();
+        assert!(matches!(err, CircuitError::InvalidWitness(_)), "{err}");
+
+        let (proof, mut pi) = prove_swap(&pk, s.legs, sigs, 0, &mut rng).unwrap();
+        assert_eq!(pi.to_vec().len(), 10);
+        assert!(verify_swap_offchain(&vk, &proof, &pi));
+
+        pi.legs.swap(0, 1);
+        assert!(!verify_swap_offchain(&vk, &proof, &pi));
+    }
+}
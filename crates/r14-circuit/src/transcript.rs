@@ -0,0 +1,32 @@
+//! Fiat–Shamir transcript over SHA-256, a hash Soroban contracts can also run
+
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+
+pub(crate) struct Transcript(Sha256);
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        Self(Sha256::new_with_prefix(label))
+    }
+
+    /// Absorb `x` in its compressed encoding
+    pub fn append<T: CanonicalSerialize + ?Sized>(&mut self, x: &T) {
+        let mut bytes = Vec::with_capacity(x.compressed_size());
+        x.serialize_compressed(&mut bytes).expect("writing to a Vec");
+        self.0.update(bytes);
+    }
+
+    /// A challenge bound to everything appended so far. 64 bytes of hash
+    /// output are reduced, so the bias mod r is negligible.
+    pub fn challenge(&mut self) -> Fr {
+        let state = self.0.clone().finalize();
+        let mut wide = [0u8; 64];
+        wide[..32].copy_from_slice(&Sha256::new_with_prefix(state).chain_update([0]).finalize());
+        wide[32..].copy_from_slice(&Sha256::new_with_prefix(state).chain_update([1]).finalize());
+        self.0.update(state);
+        Fr::from_le_bytes_mod_order(&wide)
+    }
+}
@@ -0,0 +1,81 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_serialize::CanonicalSerialize;
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// A fixed-shape circuit with its own keys.
+///
+/// Implementors provide the blank circuit, how a witness assigns it, and
+/// the public input order; setup, proving, verification and constraint
+/// counting come for free. Circuits whose shape is a setup parameter
+/// (`multi_ownership`, `vote`) keep their own functions.
+pub trait R14Circuit: ConstraintSynthesizer<Fr> + Sized {
+    /// Stable name, the key into [`crate::registry::CIRCUITS`]
+    const ID: &'static str;
+    /// Public input names, in verifier order
+    const PUBLIC_INPUTS: &'static [&'static str];
+    /// Depth of the Merkle tree the circuit proves membership in, 0 if none.
+    /// Part of the circuit_id.
+    const DEPTH: usize = 0;
+
+    type Witness;
+    type PublicInputs;
+
+    /// The circuit with no assignment (for setup)
+    fn empty() -> Self;
+
+    /// The circuit for `witness`, and the public inputs it proves
+    fn assign(witness: Self::Witness) -> (Self, Self::PublicInputs);
+
+    /// Public inputs as field elements, in `PUBLIC_INPUTS` order
+    fn public_inputs_to_vec(pi: &Self::PublicInputs) -> Vec<Fr>;
+
+    fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
+        Groth16::<Bls12_381>::circuit_specific_setup(Self::empty(), rng).expect("setup failed")
+    }
+
+    fn prove<R: RngCore + CryptoRng>(
+        pk: &ProvingKey<Bls12_381>,
+        witness: Self::Witness,
+        rng: &mut R,
+    ) -> (ark_groth16::Proof<Bls12_381>, Self::PublicInputs) {
+        let (circuit, pi) = Self::assign(witness);
+        let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
+        (proof, pi)
+    }
+
+    fn verify_offchain(
+        vk: &VerifyingKey<Bls12_381>,
+        proof: &ark_groth16::Proof<Bls12_381>,
+        pi: &Self::PublicInputs,
+    ) -> bool {
+        let pvk = PreparedVerifyingKey::from(vk.clone());
+        Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &Self::public_inputs_to_vec(pi), proof).unwrap_or(false)
+    }
+
+    fn constraint_count() -> usize {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
+        cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
+        Self::empty().generate_constraints(cs.clone()).expect("constraint generation failed");
+        cs.num_constraints()
+    }
+}
+
+/// The circuit_id r14-core assigns to `vk`, registered with Merkle `depth`:
+/// sha256(depth ++ alpha_g1 ++ beta_g2 ++ gamma_g2 ++ delta_g2 ++ ic[0..n]),
+/// depth as 4 big-endian bytes and each point in uncompressed form
+pub fn circuit_id(vk: &VerifyingKey<Bls12_381>, depth: usize) -> [u8; 32] {
+    let mut bytes = u32::try_from(depth).expect("merkle depth fits in u32").to_be_bytes().to_vec();
+    vk.alpha_g1.serialize_uncompressed(&mut bytes).unwrap();
+    vk.beta_g2.serialize_uncompressed(&mut bytes).unwrap();
+    vk.gamma_g2.serialize_uncompressed(&mut bytes).unwrap();
+    vk.delta_g2.serialize_uncompressed(&mut bytes).unwrap();
+    for ic in &vk.gamma_abc_g1 {
+        ic.serialize_uncompressed(&mut bytes).unwrap();
+    }
+    Sha256::digest(&bytes).into()
+}
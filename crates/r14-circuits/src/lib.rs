@@ -1,4 +1,10 @@
+pub mod circuit;
 pub mod preimage;
 pub mod ownership;
+pub mod multi_ownership;
 pub mod membership;
 pub mod range;
+pub mod registry;
+pub mod vote;
+
+pub use circuit::{circuit_id, R14Circuit};
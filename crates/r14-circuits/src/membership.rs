@@ -1,13 +1,14 @@
 use ark_bls12_381::{Bls12_381, Fr};
-use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_groth16::{ProvingKey, VerifyingKey};
 use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar};
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
-use ark_snark::SNARK;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::rand::{CryptoRng, RngCore};
 use r14_circuit::merkle_gadget::verify_merkle_path;
 use r14_circuit::poseidon_gadget::poseidon_hash_var;
 use r14_types::MERKLE_DEPTH;
 
+use crate::circuit::R14Circuit;
+
 /// "I know a leaf + path such that leaf is in the tree with the given root"
 #[derive(Clone)]
 pub struct MembershipCircuit {
@@ -86,9 +87,52 @@ impl PublicInputs {
     }
 }
 
+/// A leaf preimage and its Merkle path
+pub struct Witness {
+    pub leaf_preimage: Fr,
+    pub siblings: Vec<Fr>,
+    pub indices: Vec<bool>,
+}
+
+impl R14Circuit for MembershipCircuit {
+    const ID: &'static str = "membership";
+    const PUBLIC_INPUTS: &'static [&'static str] = &["root", "leaf_commitment"];
+    const DEPTH: usize = MERKLE_DEPTH;
+
+    type Witness = Witness;
+    type PublicInputs = PublicInputs;
+
+    fn empty() -> Self {
+        Self::empty()
+    }
+
+    fn assign(w: Witness) -> (Self, PublicInputs) {
+        let leaf_commitment = r14_poseidon::poseidon_hash(&[w.leaf_preimage]);
+        let mut current = leaf_commitment;
+        for i in 0..w.siblings.len() {
+            if w.indices[i] {
+                current = r14_poseidon::hash2(w.siblings[i], current);
+            } else {
+                current = r14_poseidon::hash2(current, w.siblings[i]);
+            }
+        }
+        let root = current;
+
+        let circuit = Self {
+            leaf_preimage: Some(w.leaf_preimage),
+            siblings: Some(w.siblings),
+            indices: Some(w.indices),
+        };
+        (circuit, PublicInputs { root, leaf_commitment })
+    }
+
+    fn public_inputs_to_vec(pi: &PublicInputs) -> Vec<Fr> {
+        pi.to_vec()
+    }
+}
+
 pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
-    let circuit = MembershipCircuit::empty();
-    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+    MembershipCircuit::setup(rng)
 }
 
 pub fn prove<R: RngCore + CryptoRng>(
@@ -98,24 +142,7 @@ pub fn prove<R: RngCore + CryptoRng>(
     indices: Vec<bool>,
     rng: &mut R,
 ) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
-    let leaf_commitment = r14_poseidon::poseidon_hash(&[leaf_preimage]);
-    let mut current = leaf_commitment;
-    for i in 0..siblings.len() {
-        if indices[i] {
-            current = r14_poseidon::hash2(siblings[i], current);
-        } else {
-            current = r14_poseidon::hash2(current, siblings[i]);
-        }
-    }
-    let root = current;
-
-    let circuit = MembershipCircuit {
-        leaf_preimage: Some(leaf_preimage),
-        siblings: Some(siblings),
-        indices: Some(indices),
-    };
-    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
-    (proof, PublicInputs { root, leaf_commitment })
+    MembershipCircuit::prove(pk, Witness { leaf_preimage, siblings, indices }, rng)
 }
 
 pub fn verify_offchain(
@@ -123,17 +150,11 @@ pub fn verify_offchain(
     proof: &ark_groth16::Proof<Bls12_381>,
     pi: &PublicInputs,
 ) -> bool {
-    let pvk = PreparedVerifyingKey::from(vk.clone());
-    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &pi.to_vec(), proof).unwrap_or(false)
+    MembershipCircuit::verify_offchain(vk, proof, pi)
 }
 
 pub fn constraint_count() -> usize {
-    let cs = ConstraintSystem::<Fr>::new_ref();
-    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
-    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
-    let circuit = MembershipCircuit::empty();
-    circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
-    cs.num_constraints()
+    MembershipCircuit::constraint_count()
 }
 
 #[cfg(test)]
@@ -0,0 +1,227 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_circuit::poseidon_gadget::poseidon_hash_var;
+use r14_poseidon::MULTISIG_DOMAIN;
+
+/// Owner hash of a k-of-n note: Poseidon(domain, k, h_1, ..., h_n), where
+/// each `h_i` is a key hash `Poseidon(sk_i)`
+pub fn owner_hash(threshold: usize, key_hashes: &[Fr]) -> Fr {
+    let mut inputs = vec![Fr::from(MULTISIG_DOMAIN), Fr::from(threshold as u64)];
+    inputs.extend_from_slice(key_hashes);
+    r14_poseidon::poseidon_hash(&inputs)
+}
+
+/// "I know `threshold` of the secret keys behind `owner_hash`"
+///
+/// The owner commits to all n key hashes and the threshold; which keys
+/// signed stays private. `n` and `threshold` are fixed by the setup.
+#[derive(Clone)]
+pub struct MultiOwnershipCircuit {
+    pub n: usize,
+    pub threshold: usize,
+    // Private witnesses
+    pub key_hashes: Option<Vec<Fr>>,
+    /// One entry per key; `Some` for the keys the prover holds
+    pub secret_keys: Option<Vec<Option<Fr>>>,
+}
+
+impl MultiOwnershipCircuit {
+    pub fn empty(n: usize, threshold: usize) -> Self {
+        Self {
+            n,
+            threshold,
+            key_hashes: None,
+            secret_keys: None,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for MultiOwnershipCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let owner_hash_pub = FpVar::new_input(cs.clone(), || {
+            let hashes = self.key_hashes.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(owner_hash(self.threshold, hashes))
+        })?;
+
+        let mut hash_vars = Vec::with_capacity(self.n);
+        let mut signed = FpVar::zero();
+        for i in 0..self.n {
+            let key = || {
+                let keys = self.secret_keys.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+                keys.get(i).copied().ok_or(SynthesisError::AssignmentMissing)
+            };
+            let hash_var = FpVar::new_witness(cs.clone(), || {
+                let hashes = self.key_hashes.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+                hashes.get(i).copied().ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let holds = Boolean::new_witness(cs.clone(), || Ok(key()?.is_some()))?;
+            // unused slots still hash something; 0 is as good as anything
+            let sk_var = FpVar::new_witness(cs.clone(), || Ok(key()?.unwrap_or_default()))?;
+
+            // a held key must match its hash: holds * (Poseidon(sk) - h) == 0
+            let diff = poseidon_hash_var(cs.clone(), &[sk_var])? - &hash_var;
+            diff.mul_equals(&holds.clone().into(), &FpVar::zero())?;
+
+            signed += FpVar::from(holds);
+            hash_vars.push(hash_var);
+        }
+        signed.enforce_equal(&FpVar::constant(Fr::from(self.threshold as u64)))?;
+
+        let mut inputs = vec![
+            FpVar::constant(Fr::from(MULTISIG_DOMAIN)),
+            FpVar::constant(Fr::from(self.threshold as u64)),
+        ];
+        inputs.extend(hash_vars);
+        let computed = poseidon_hash_var(cs, &inputs)?;
+        computed.enforce_equal(&owner_hash_pub)?;
+
+        Ok(())
+    }
+}
+
+pub struct PublicInputs {
+    pub owner_hash: Fr,
+}
+
+impl PublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        vec![self.owner_hash]
+    }
+}
+
+pub fn setup<R: RngCore + CryptoRng>(
+    n: usize,
+    threshold: usize,
+    rng: &mut R,
+) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
+    assert!((1..=n).contains(&threshold), "need 1 <= threshold <= n");
+    let circuit = MultiOwnershipCircuit::empty(n, threshold);
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+}
+
+/// Prove with exactly `threshold` of the keys. `secret_keys[i]` is the key
+/// behind `key_hashes[i]`, or `None` if this prover doesn't hold it.
+pub fn prove<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    threshold: usize,
+    key_hashes: Vec<Fr>,
+    secret_keys: Vec<Option<Fr>>,
+    rng: &mut R,
+) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
+    assert_eq!(key_hashes.len(), secret_keys.len(), "one secret key slot per key hash");
+    assert_eq!(
+        secret_keys.iter().filter(|k| k.is_some()).count(),
+        threshold,
+        "exactly threshold keys must be supplied"
+    );
+    let owner_hash = owner_hash(threshold, &key_hashes);
+    let circuit = MultiOwnershipCircuit {
+        n: key_hashes.len(),
+        threshold,
+        key_hashes: Some(key_hashes),
+        secret_keys: Some(secret_keys),
+    };
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
+    (proof, PublicInputs { owner_hash })
+}
+
+pub fn verify_offchain(
+    vk: &VerifyingKey<Bls12_381>,
+    proof: &ark_groth16::Proof<Bls12_381>,
+    pi: &PublicInputs,
+) -> bool {
+    let pvk = PreparedVerifyingKey::from(vk.clone());
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &pi.to_vec(), proof).unwrap_or(false)
+}
+
+pub fn constraint_count(n: usize, threshold: usize) -> usize {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
+    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
+    let circuit = MultiOwnershipCircuit::empty(n, threshold);
+    circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
+    cs.num_constraints()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    fn test_rng() -> StdRng {
+        StdRng::seed_from_u64(42)
+    }
+
+    fn keys(rng: &mut StdRng, n: usize) -> (Vec<Fr>, Vec<Fr>) {
+        let sks: Vec<Fr> = (0..n).map(|_| Fr::rand(rng)).collect();
+        let hashes = sks.iter().map(|sk| r14_poseidon::poseidon_hash(&[*sk])).collect();
+        (sks, hashes)
+    }
+
+    fn is_satisfied(c: MultiOwnershipCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        c.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_valid_2_of_3() {
+        let mut rng = test_rng();
+        let (sks, hashes) = keys(&mut rng, 3);
+        let (pk, vk) = setup(3, 2, &mut rng);
+        let (proof, pi) = prove(&pk, 2, hashes.clone(), vec![Some(sks[0]), None, Some(sks[2])], &mut rng);
+        assert!(verify_offchain(&vk, &proof, &pi));
+        assert_eq!(pi.owner_hash, owner_hash(2, &hashes));
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let mut rng = test_rng();
+        let (sks, hashes) = keys(&mut rng, 3);
+        let c = MultiOwnershipCircuit {
+            n: 3,
+            threshold: 2,
+            key_hashes: Some(hashes),
+            secret_keys: Some(vec![Some(sks[0]), Some(Fr::rand(&mut rng)), None]),
+        };
+        assert!(!is_satisfied(c), "should fail: key 1 doesn't match its hash");
+    }
+
+    #[test]
+    fn test_below_threshold() {
+        let mut rng = test_rng();
+        let (sks, hashes) = keys(&mut rng, 3);
+        let c = MultiOwnershipCircuit {
+            n: 3,
+            threshold: 2,
+            key_hashes: Some(hashes),
+            secret_keys: Some(vec![None, Some(sks[1]), None]),
+        };
+        assert!(!is_satisfied(c), "should fail: one key of two");
+    }
+
+    #[test]
+    fn test_threshold_is_committed() {
+        let mut rng = test_rng();
+        let (_, hashes) = keys(&mut rng, 3);
+        assert_ne!(owner_hash(1, &hashes), owner_hash(2, &hashes));
+    }
+
+    #[test]
+    fn test_multi_ownership_constraint_count() {
+        let count = constraint_count(3, 2);
+        println!("2-of-3 ownership circuit constraints: {count}");
+        assert!(count > 3 * 100, "too few: {count}");
+        assert!(count < 4 * 1000, "too many: {count}");
+    }
+}
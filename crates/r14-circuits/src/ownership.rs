@@ -1,11 +1,12 @@
 use ark_bls12_381::{Bls12_381, Fr};
-use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_groth16::{ProvingKey, VerifyingKey};
 use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
-use ark_snark::SNARK;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::rand::{CryptoRng, RngCore};
 use r14_circuit::poseidon_gadget::poseidon_hash_var;
 
+use crate::circuit::R14Circuit;
+
 /// "I know `sk` such that `Poseidon(sk) == owner_hash`"
 #[derive(Clone)]
 pub struct OwnershipCircuit {
@@ -46,9 +47,30 @@ impl PublicInputs {
     }
 }
 
+impl R14Circuit for OwnershipCircuit {
+    const ID: &'static str = "ownership";
+    const PUBLIC_INPUTS: &'static [&'static str] = &["owner_hash"];
+
+    /// The secret key
+    type Witness = Fr;
+    type PublicInputs = PublicInputs;
+
+    fn empty() -> Self {
+        Self::empty()
+    }
+
+    fn assign(secret_key: Fr) -> (Self, PublicInputs) {
+        let owner_hash = r14_poseidon::poseidon_hash(&[secret_key]);
+        (Self { secret_key: Some(secret_key) }, PublicInputs { owner_hash })
+    }
+
+    fn public_inputs_to_vec(pi: &PublicInputs) -> Vec<Fr> {
+        pi.to_vec()
+    }
+}
+
 pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
-    let circuit = OwnershipCircuit::empty();
-    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+    OwnershipCircuit::setup(rng)
 }
 
 pub fn prove<R: RngCore + CryptoRng>(
@@ -56,10 +78,7 @@ pub fn prove<R: RngCore + CryptoRng>(
     secret_key: Fr,
     rng: &mut R,
 ) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
-    let owner_hash = r14_poseidon::poseidon_hash(&[secret_key]);
-    let circuit = OwnershipCircuit { secret_key: Some(secret_key) };
-    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
-    (proof, PublicInputs { owner_hash })
+    OwnershipCircuit::prove(pk, secret_key, rng)
 }
 
 pub fn verify_offchain(
@@ -67,17 +86,11 @@ pub fn verify_offchain(
     proof: &ark_groth16::Proof<Bls12_381>,
     pi: &PublicInputs,
 ) -> bool {
-    let pvk = PreparedVerifyingKey::from(vk.clone());
-    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &pi.to_vec(), proof).unwrap_or(false)
+    OwnershipCircuit::verify_offchain(vk, proof, pi)
 }
 
 pub fn constraint_count() -> usize {
-    let cs = ConstraintSystem::<Fr>::new_ref();
-    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
-    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
-    let circuit = OwnershipCircuit::empty();
-    circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
-    cs.num_constraints()
+    OwnershipCircuit::constraint_count()
 }
 
 #[cfg(test)]
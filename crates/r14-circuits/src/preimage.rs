@@ -1,11 +1,12 @@
 use ark_bls12_381::{Bls12_381, Fr};
-use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_groth16::{ProvingKey, VerifyingKey};
 use ark_r1cs_std::{alloc::AllocVar, eq::EqGadget, fields::fp::FpVar};
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
-use ark_snark::SNARK;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::rand::{CryptoRng, RngCore};
 use r14_circuit::poseidon_gadget::poseidon_hash_var;
 
+use crate::circuit::R14Circuit;
+
 /// "I know `x` such that `Poseidon(x) == hash`"
 #[derive(Clone)]
 pub struct PreimageCircuit {
@@ -49,9 +50,30 @@ impl PublicInputs {
     }
 }
 
+impl R14Circuit for PreimageCircuit {
+    const ID: &'static str = "preimage";
+    const PUBLIC_INPUTS: &'static [&'static str] = &["hash"];
+
+    /// The preimage `x`
+    type Witness = Fr;
+    type PublicInputs = PublicInputs;
+
+    fn empty() -> Self {
+        Self::empty()
+    }
+
+    fn assign(preimage: Fr) -> (Self, PublicInputs) {
+        let hash = r14_poseidon::poseidon_hash(&[preimage]);
+        (Self { preimage: Some(preimage) }, PublicInputs { hash })
+    }
+
+    fn public_inputs_to_vec(pi: &PublicInputs) -> Vec<Fr> {
+        pi.to_vec()
+    }
+}
+
 pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
-    let circuit = PreimageCircuit::empty();
-    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+    PreimageCircuit::setup(rng)
 }
 
 pub fn prove<R: RngCore + CryptoRng>(
@@ -59,10 +81,7 @@ pub fn prove<R: RngCore + CryptoRng>(
     preimage: Fr,
     rng: &mut R,
 ) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
-    let hash = r14_poseidon::poseidon_hash(&[preimage]);
-    let circuit = PreimageCircuit { preimage: Some(preimage) };
-    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
-    (proof, PublicInputs { hash })
+    PreimageCircuit::prove(pk, preimage, rng)
 }
 
 pub fn verify_offchain(
@@ -70,17 +89,11 @@ pub fn verify_offchain(
     proof: &ark_groth16::Proof<Bls12_381>,
     pi: &PublicInputs,
 ) -> bool {
-    let pvk = PreparedVerifyingKey::from(vk.clone());
-    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &pi.to_vec(), proof).unwrap_or(false)
+    PreimageCircuit::verify_offchain(vk, proof, pi)
 }
 
 pub fn constraint_count() -> usize {
-    let cs = ConstraintSystem::<Fr>::new_ref();
-    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
-    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
-    let circuit = PreimageCircuit::empty();
-    circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
-    cs.num_constraints()
+    PreimageCircuit::constraint_count()
 }
 
 #[cfg(test)]
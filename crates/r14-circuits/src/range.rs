@@ -1,12 +1,13 @@
 use ark_bls12_381::{Bls12_381, Fr};
 use ark_ff::{AdditiveGroup, PrimeField};
-use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_groth16::{ProvingKey, VerifyingKey};
 use ark_r1cs_std::{alloc::AllocVar, boolean::Boolean, eq::EqGadget, fields::fp::FpVar, fields::FieldVar};
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
-use ark_snark::SNARK;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_std::rand::{CryptoRng, RngCore};
 use r14_circuit::poseidon_gadget::poseidon_hash_var;
 
+use crate::circuit::R14Circuit;
+
 const RANGE_BITS: usize = 64;
 
 /// "I know `x` committed as `cm = Poseidon(x, nonce)` such that `min <= x <= max`"
@@ -130,9 +131,47 @@ impl PublicInputs {
     }
 }
 
+/// A committed value, its nonce, and the bounds it's proven within
+pub struct Witness {
+    pub x: u64,
+    pub nonce: Fr,
+    pub min: u64,
+    pub max: u64,
+}
+
+impl R14Circuit for RangeCircuit {
+    const ID: &'static str = "range";
+    const PUBLIC_INPUTS: &'static [&'static str] = &["min", "max", "commitment"];
+
+    type Witness = Witness;
+    type PublicInputs = PublicInputs;
+
+    fn empty() -> Self {
+        Self::empty()
+    }
+
+    fn assign(w: Witness) -> (Self, PublicInputs) {
+        let x_fr = Fr::from(w.x);
+        let min_fr = Fr::from(w.min);
+        let max_fr = Fr::from(w.max);
+        let commitment = r14_poseidon::poseidon_hash(&[x_fr, w.nonce]);
+
+        let circuit = Self {
+            x: Some(x_fr),
+            nonce: Some(w.nonce),
+            min: Some(min_fr),
+            max: Some(max_fr),
+        };
+        (circuit, PublicInputs { min: min_fr, max: max_fr, commitment })
+    }
+
+    fn public_inputs_to_vec(pi: &PublicInputs) -> Vec<Fr> {
+        pi.to_vec()
+    }
+}
+
 pub fn setup<R: RngCore + CryptoRng>(rng: &mut R) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
-    let circuit = RangeCircuit::empty();
-    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+    RangeCircuit::setup(rng)
 }
 
 pub fn prove<R: RngCore + CryptoRng>(
@@ -143,19 +182,7 @@ pub fn prove<R: RngCore + CryptoRng>(
     max: u64,
     rng: &mut R,
 ) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
-    let x_fr = Fr::from(x);
-    let min_fr = Fr::from(min);
-    let max_fr = Fr::from(max);
-    let commitment = r14_poseidon::poseidon_hash(&[x_fr, nonce]);
-
-    let circuit = RangeCircuit {
-        x: Some(x_fr),
-        nonce: Some(nonce),
-        min: Some(min_fr),
-        max: Some(max_fr),
-    };
-    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
-    (proof, PublicInputs { min: min_fr, max: max_fr, commitment })
+    RangeCircuit::prove(pk, Witness { x, nonce, min, max }, rng)
 }
 
 pub fn verify_offchain(
@@ -163,23 +190,18 @@ pub fn verify_offchain(
     proof: &ark_groth16::Proof<Bls12_381>,
     pi: &PublicInputs,
 ) -> bool {
-    let pvk = PreparedVerifyingKey::from(vk.clone());
-    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &pi.to_vec(), proof).unwrap_or(false)
+    RangeCircuit::verify_offchain(vk, proof, pi)
 }
 
 pub fn constraint_count() -> usize {
-    let cs = ConstraintSystem::<Fr>::new_ref();
-    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
-    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
-    let circuit = RangeCircuit::empty();
-    circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
-    cs.num_constraints()
+    RangeCircuit::constraint_count()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
     use ark_std::rand::{rngs::StdRng, SeedableRng};
 
     fn test_rng() -> StdRng {
@@ -0,0 +1,76 @@
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_std::rand::rngs::StdRng;
+
+use crate::circuit::R14Circuit;
+use crate::membership::MembershipCircuit;
+use crate::ownership::OwnershipCircuit;
+use crate::preimage::PreimageCircuit;
+use crate::range::RangeCircuit;
+
+/// A fixed-shape circuit, by its stable ID.
+///
+/// The ID names the statement; the circuit_id r14-core assigns depends on
+/// the keys and depth, so compute it from the VK with
+/// [`crate::circuit::circuit_id`].
+pub struct CircuitInfo {
+    pub id: &'static str,
+    pub public_inputs: &'static [&'static str],
+    pub depth: usize,
+    pub setup: fn(&mut StdRng) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>),
+    pub constraint_count: fn() -> usize,
+}
+
+const fn info<C: R14Circuit>() -> CircuitInfo {
+    CircuitInfo {
+        id: C::ID,
+        public_inputs: C::PUBLIC_INPUTS,
+        depth: C::DEPTH,
+        setup: C::setup::<StdRng>,
+        constraint_count: C::constraint_count,
+    }
+}
+
+/// Every fixed-shape circuit in this crate. Append only: IDs are stable.
+pub const CIRCUITS: &[CircuitInfo] = &[
+    info::<PreimageCircuit>(),
+    info::<OwnershipCircuit>(),
+    info::<MembershipCircuit>(),
+    info::<RangeCircuit>(),
+];
+
+/// Look up a circuit by ID
+pub fn get(id: &str) -> Option<&'static CircuitInfo> {
+    CIRCUITS.iter().find(|c| c.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::rand::SeedableRng;
+    use r14_sdk::serialize::serialize_vk_for_soroban;
+
+    #[test]
+    fn test_ids_unique() {
+        for (i, c) in CIRCUITS.iter().enumerate() {
+            assert!(CIRCUITS[..i].iter().all(|other| other.id != c.id), "duplicate id {}", c.id);
+            assert_eq!(get(c.id).map(|found| found.id), Some(c.id));
+        }
+        assert!(get("transfer").is_none());
+    }
+
+    #[test]
+    fn test_circuit_id_matches_core() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for id in ["ownership", "membership"] {
+            let info = get(id).unwrap();
+            let (_, vk) = (info.setup)(&mut rng);
+            assert_eq!(vk.gamma_abc_g1.len(), info.public_inputs.len() + 1);
+
+            let expected = r14_sdk::serialize::circuit_id(&serialize_vk_for_soroban(&vk, info.depth));
+            assert_eq!(hex::encode(crate::circuit::circuit_id(&vk, info.depth)), expected);
+        }
+        assert_eq!(get("ownership").unwrap().depth, 0);
+        assert_eq!(get("membership").unwrap().depth, r14_types::MERKLE_DEPTH);
+    }
+}
@@ -0,0 +1,273 @@
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use r14_circuit::merkle_gadget::verify_merkle_path;
+use r14_circuit::poseidon_gadget::poseidon_hash_var;
+use r14_poseidon::VOTE_DOMAIN;
+use r14_types::MERKLE_DEPTH;
+
+/// Nullifier of a vote: Poseidon(domain, sk, proposal_id). One per voter
+/// per proposal, and unlinkable to the voter's transfer nullifiers.
+pub fn nullifier(secret_key: Fr, proposal_id: u64) -> Fr {
+    r14_poseidon::poseidon_hash(&[Fr::from(VOTE_DOMAIN), secret_key, Fr::from(proposal_id)])
+}
+
+/// "I hold a key in the voter tree, and this is my only vote on the proposal"
+///
+/// Voter tree leaves are owner hashes `Poseidon(sk)`. The choice is public
+/// so a contract can count it; which voter cast it stays private.
+/// `num_choices` is fixed by the setup.
+#[derive(Clone)]
+pub struct VoteCircuit {
+    pub num_choices: usize,
+    // Private witnesses
+    pub secret_key: Option<Fr>,
+    pub siblings: Option<Vec<Fr>>,
+    pub indices: Option<Vec<bool>>,
+    // Public
+    pub proposal_id: Option<u64>,
+    pub choice: Option<u64>,
+}
+
+impl VoteCircuit {
+    pub fn empty(num_choices: usize) -> Self {
+        Self {
+            num_choices,
+            secret_key: None,
+            siblings: None,
+            indices: None,
+            proposal_id: None,
+            choice: None,
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for VoteCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        // Public inputs: voter_root, proposal_id, nullifier, choice
+        let root_pub = FpVar::new_input(cs.clone(), || {
+            let sk = self.secret_key.ok_or(SynthesisError::AssignmentMissing)?;
+            let siblings = self.siblings.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+            let indices = self.indices.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(voter_root(r14_poseidon::poseidon_hash(&[sk]), siblings, indices))
+        })?;
+        let proposal_pub = FpVar::new_input(cs.clone(), || {
+            self.proposal_id.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let nullifier_pub = FpVar::new_input(cs.clone(), || {
+            let sk = self.secret_key.ok_or(SynthesisError::AssignmentMissing)?;
+            let proposal_id = self.proposal_id.ok_or(SynthesisError::AssignmentMissing)?;
+            Ok(nullifier(sk, proposal_id))
+        })?;
+        let choice_pub = FpVar::new_input(cs.clone(), || {
+            self.choice.map(Fr::from).ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // Witnesses
+        let sk_var = FpVar::new_witness(cs.clone(), || self.secret_key.ok_or(SynthesisError::AssignmentMissing))?;
+
+        let mut path_vars: Vec<(FpVar<Fr>, Boolean<Fr>)> = Vec::with_capacity(MERKLE_DEPTH);
+        for i in 0..MERKLE_DEPTH {
+            let sibling = FpVar::new_witness(cs.clone(), || {
+                let siblings = self.siblings.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+                siblings.get(i).copied().ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let index_bit = Boolean::new_witness(cs.clone(), || {
+                let indices = self.indices.as_ref().ok_or(SynthesisError::AssignmentMissing)?;
+                indices.get(i).copied().ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            path_vars.push((sibling, index_bit));
+        }
+
+        // Constraint 1: Poseidon(sk) is a leaf of the voter tree
+        let leaf = poseidon_hash_var(cs.clone(), std::slice::from_ref(&sk_var))?;
+        verify_merkle_path(cs.clone(), &leaf, &path_vars, &root_pub)?;
+
+        // Constraint 2: nullifier == Poseidon(domain, sk, proposal_id)
+        let computed_nf = poseidon_hash_var(cs, &[FpVar::constant(Fr::from(VOTE_DOMAIN)), sk_var, proposal_pub])?;
+        computed_nf.enforce_equal(&nullifier_pub)?;
+
+        // Constraint 3: choice is one of 0..num_choices
+        let mut product = FpVar::one();
+        for i in 0..self.num_choices {
+            product *= &choice_pub - Fr::from(i as u64);
+        }
+        product.enforce_equal(&FpVar::zero())?;
+
+        Ok(())
+    }
+}
+
+fn voter_root(leaf: Fr, siblings: &[Fr], indices: &[bool]) -> Fr {
+    let mut current = leaf;
+    for (&sibling, &is_right) in siblings.iter().zip(indices) {
+        if is_right {
+            current = r14_poseidon::hash2(sibling, current);
+        } else {
+            current = r14_poseidon::hash2(current, sibling);
+        }
+    }
+    current
+}
+
+pub struct PublicInputs {
+    pub voter_root: Fr,
+    pub proposal_id: Fr,
+    pub nullifier: Fr,
+    pub choice: Fr,
+}
+
+impl PublicInputs {
+    pub fn to_vec(&self) -> Vec<Fr> {
+        vec![self.voter_root, self.proposal_id, self.nullifier, self.choice]
+    }
+}
+
+pub fn setup<R: RngCore + CryptoRng>(
+    num_choices: usize,
+    rng: &mut R,
+) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
+    assert!(num_choices >= 2, "need at least two choices");
+    let circuit = VoteCircuit::empty(num_choices);
+    Groth16::<Bls12_381>::circuit_specific_setup(circuit, rng).expect("setup failed")
+}
+
+/// Prove a vote for `choice` on `proposal_id` by the voter whose owner hash
+/// `Poseidon(secret_key)` sits at the given path
+#[allow(clippy::too_many_arguments)]
+pub fn prove<R: RngCore + CryptoRng>(
+    pk: &ProvingKey<Bls12_381>,
+    num_choices: usize,
+    secret_key: Fr,
+    siblings: Vec<Fr>,
+    indices: Vec<bool>,
+    proposal_id: u64,
+    choice: u64,
+    rng: &mut R,
+) -> (ark_groth16::Proof<Bls12_381>, PublicInputs) {
+    assert!(choice < num_choices as u64, "choice out of range");
+    let pi = PublicInputs {
+        voter_root: voter_root(r14_poseidon::poseidon_hash(&[secret_key]), &siblings, &indices),
+        proposal_id: Fr::from(proposal_id),
+        nullifier: nullifier(secret_key, proposal_id),
+        choice: Fr::from(choice),
+    };
+    let circuit = VoteCircuit {
+        num_choices,
+        secret_key: Some(secret_key),
+        siblings: Some(siblings),
+        indices: Some(indices),
+        proposal_id: Some(proposal_id),
+        choice: Some(choice),
+    };
+    let proof = Groth16::<Bls12_381>::prove(pk, circuit, rng).expect("proving failed");
+    (proof, pi)
+}
+
+pub fn verify_offchain(
+    vk: &VerifyingKey<Bls12_381>,
+    proof: &ark_groth16::Proof<Bls12_381>,
+    pi: &PublicInputs,
+) -> bool {
+    let pvk = PreparedVerifyingKey::from(vk.clone());
+    Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &pi.to_vec(), proof).unwrap_or(false)
+}
+
+pub fn constraint_count(num_choices: usize) -> usize {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_optimization_goal(ark_relations::r1cs::OptimizationGoal::Constraints);
+    cs.set_mode(ark_relations::r1cs::SynthesisMode::Setup);
+    let circuit = VoteCircuit::empty(num_choices);
+    circuit.generate_constraints(cs.clone()).expect("constraint generation failed");
+    cs.num_constraints()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    fn test_rng() -> StdRng {
+        StdRng::seed_from_u64(42)
+    }
+
+    fn dummy_path(rng: &mut impl RngCore) -> (Vec<Fr>, Vec<bool>) {
+        let siblings: Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect();
+        let indices: Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+        (siblings, indices)
+    }
+
+    fn circuit(rng: &mut StdRng, choice: u64) -> VoteCircuit {
+        let (siblings, indices) = dummy_path(rng);
+        VoteCircuit {
+            num_choices: 3,
+            secret_key: Some(Fr::rand(rng)),
+            siblings: Some(siblings),
+            indices: Some(indices),
+            proposal_id: Some(7),
+            choice: Some(choice),
+        }
+    }
+
+    fn is_satisfied(c: VoteCircuit) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        c.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn test_valid_vote() {
+        let mut rng = test_rng();
+        let sk = Fr::rand(&mut rng);
+        let (siblings, indices) = dummy_path(&mut rng);
+
+        let (pk, vk) = setup(3, &mut rng);
+        let (proof, pi) = prove(&pk, 3, sk, siblings, indices, 7, 2, &mut rng);
+        assert!(verify_offchain(&vk, &proof, &pi));
+        assert_eq!(pi.nullifier, nullifier(sk, 7));
+    }
+
+    #[test]
+    fn test_changed_choice() {
+        let mut rng = test_rng();
+        let sk = Fr::rand(&mut rng);
+        let (siblings, indices) = dummy_path(&mut rng);
+
+        let (pk, vk) = setup(3, &mut rng);
+        let (proof, mut pi) = prove(&pk, 3, sk, siblings, indices, 7, 2, &mut rng);
+        pi.choice = Fr::from(0u64);
+        assert!(!verify_offchain(&vk, &proof, &pi), "should fail: relayer changed the choice");
+    }
+
+    #[test]
+    fn test_choice_out_of_range() {
+        let mut rng = test_rng();
+        assert!(is_satisfied(circuit(&mut rng, 2)));
+        assert!(!is_satisfied(circuit(&mut rng, 3)), "should fail: only choices 0..3");
+    }
+
+    #[test]
+    fn test_nullifier_per_proposal() {
+        let mut rng = test_rng();
+        let sk = Fr::rand(&mut rng);
+        assert_ne!(nullifier(sk, 1), nullifier(sk, 2));
+        assert_ne!(nullifier(sk, 1), r14_poseidon::poseidon_hash(&[sk, Fr::from(1u64)]));
+    }
+
+    #[test]
+    fn test_vote_constraint_count() {
+        let count = constraint_count(3);
+        println!("Vote circuit constraints: {count}");
+        assert!(count > 2000, "too few: {count}");
+        assert!(count < 10000, "too many: {count}");
+    }
+}
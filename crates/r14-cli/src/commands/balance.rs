@@ -1,41 +1,27 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use serde::Deserialize;
 
 use crate::output;
-use r14_sdk::wallet::{load_wallet, save_wallet};
-
-#[derive(Deserialize)]
-struct LeafResponse {
-    index: u64,
-    #[allow(dead_code)]
-    block_height: u64,
-}
+use r14_sdk::wallet::{hex_to_fr, load_wallet, save_wallet};
+use r14_sdk::R14Client;
 
 pub async fn run() -> Result<()> {
     let mut wallet = load_wallet()?;
-    let client = reqwest::Client::new();
+    let client = R14Client::from_wallet(&wallet)?;
 
     let sp = output::spinner("syncing notes with indexer...");
-
-    // sync unspent notes with indexer
-    for note in wallet.notes.iter_mut().filter(|n| !n.spent) {
-        if note.index.is_some() {
-            continue;
-        }
-        let cm_hex = note.commitment.strip_prefix("0x").unwrap_or(&note.commitment);
-        let url = format!("{}/v1/leaf/{}", wallet.indexer_url, cm_hex);
-        match client.get(&url).send().await {
-            Ok(resp) if resp.status().is_success() => {
-                if let Ok(leaf) = resp.json::<LeafResponse>().await {
-                    note.index = Some(leaf.index);
-                }
-            }
-            _ => {} // indexer unreachable or commitment not on-chain yet
-        }
+    let sk = hex_to_fr(&wallet.secret_key)?;
+    let synced: Result<()> = async {
+        // confirm transfers interrupted before their wallet update
+        client.resolve_pending(&mut wallet).await?;
+        client.sync_notes(&mut wallet.notes).await?;
+        // mark notes spent from another device holding the same key
+        client.sync_spent(&mut wallet.notes, &sk).await?;
+        Ok(())
     }
-
+    .await;
     sp.finish_and_clear();
+    synced.with_context(|| format!("failed to sync with the indexer at {}", wallet.indexer_url))?;
     save_wallet(&wallet).context("failed to save wallet after sync")?;
 
     // display
@@ -52,12 +38,17 @@ pub async fn run() -> Result<()> {
                     "commitment": n.commitment,
                     "index": n.index,
                     "status": if n.index.is_some() { "on-chain" } else { "local-only" },
+                    "label": n.meta.label,
+                    "source": n.meta.source,
+                    "created_at": n.meta.created_at,
+                    "counterparty": n.meta.counterparty,
                 })
             })
             .collect();
         output::json_output(serde_json::json!({
             "balance": total,
             "notes": notes_json,
+            "pending_transfers": wallet.pending.len(),
         }));
     } else {
         output::label("balance", &total.to_string());
@@ -68,9 +59,25 @@ pub async fn run() -> Result<()> {
                     Some(idx) => format!("{} (idx={})", "on-chain".green(), idx),
                     None => "local-only".yellow().to_string(),
                 };
-                output::info(&format!("  [{}] value={} app_tag={} {}", i, n.value, n.app_tag, status));
+                let mut line = format!("  [{}] value={} app_tag={} {}", i, n.value, n.app_tag, status);
+                if let Some(source) = n.meta.source {
+                    line += &format!(" {source}");
+                }
+                if let Some(to) = &n.meta.counterparty {
+                    line += &format!(" to={}", &to[..to.len().min(10)]);
+                }
+                if let Some(label) = &n.meta.label {
+                    line += &format!(" \"{}\"", label.cyan());
+                }
+                output::info(&line);
             }
         }
+        if !wallet.pending.is_empty() {
+            output::info(&format!(
+                "\n{} transfer(s) awaiting confirmation, their notes are excluded",
+                wallet.pending.len()
+            ));
+        }
     }
 
     Ok(())
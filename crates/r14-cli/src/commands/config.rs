@@ -1,9 +1,12 @@
 use anyhow::Result;
 
 use crate::output;
-use r14_sdk::wallet::{load_wallet, save_wallet};
+use r14_sdk::network::Network;
+use r14_sdk::wallet::{hex_to_fr, load_wallet, owner_address, save_wallet};
 
 const ALLOWED_KEYS: &[&str] = &[
+    "network",
+    "network_passphrase",
     "rpc_url",
     "indexer_url",
     "core_contract_id",
@@ -21,6 +24,23 @@ pub fn set(key: &str, value: &str) -> Result<()> {
 
     let mut wallet = load_wallet()?;
     match key {
+        // a preset brings its RPC and passphrase; other names keep the
+        // current rpc_url and need `network_passphrase`
+        "network" => {
+            let network = Network::named(value);
+            if let Some(rpc_url) = &network.rpc_url {
+                wallet.rpc_url = rpc_url.clone();
+            }
+            if network.is_preset() {
+                wallet.network_passphrase = None;
+            } else if wallet.network_passphrase.is_none() {
+                output::warn(&format!(
+                    "`{value}` is not a preset; set network_passphrase, or add it with `stellar network add`"
+                ));
+            }
+            wallet.network = value.to_string();
+        }
+        "network_passphrase" => wallet.network_passphrase = Some(value.to_string()).filter(|p| !p.is_empty()),
         "rpc_url" => wallet.rpc_url = value.to_string(),
         "indexer_url" => wallet.indexer_url = value.to_string(),
         "core_contract_id" => wallet.core_contract_id = value.to_string(),
@@ -47,11 +67,15 @@ fn mask(s: &str) -> String {
 
 pub fn show() -> Result<()> {
     let wallet = load_wallet()?;
+    let address = owner_address(&hex_to_fr(&wallet.owner_hash)?);
 
     if output::is_json() {
         output::json_output(serde_json::json!({
             "secret_key": mask(&wallet.secret_key),
             "owner_hash": wallet.owner_hash,
+            "address": address,
+            "network": wallet.network,
+            "network_passphrase": wallet.network_passphrase,
             "stellar_secret": mask(&wallet.stellar_secret),
             "rpc_url": wallet.rpc_url,
             "indexer_url": wallet.indexer_url,
@@ -62,7 +86,12 @@ pub fn show() -> Result<()> {
     } else {
         output::label("secret_key", &mask(&wallet.secret_key));
         output::label("owner_hash", &wallet.owner_hash);
+        output::label("address", &address);
         output::label("stellar_secret", &mask(&wallet.stellar_secret));
+        output::label("network", &wallet.network);
+        if let Some(passphrase) = &wallet.network_passphrase {
+            output::label("network_passphrase", passphrase);
+        }
         output::label("rpc_url", &wallet.rpc_url);
         output::label("indexer_url", &wallet.indexer_url);
         output::label("core_contract_id", &wallet.core_contract_id);
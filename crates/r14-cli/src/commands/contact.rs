@@ -0,0 +1,62 @@
+use anyhow::Result;
+
+use crate::output;
+use r14_sdk::contacts::{normalize_alias, remove_contact, set_contact};
+use r14_sdk::wallet::{load_wallet, owner_address, parse_owner, save_wallet};
+
+pub fn add(alias: &str, owner: &str) -> Result<()> {
+    let mut wallet = load_wallet()?;
+    let previous = set_contact(&mut wallet, alias, owner)?;
+    save_wallet(&wallet)?;
+
+    let alias = normalize_alias(alias)?;
+    if output::is_json() {
+        output::json_output(serde_json::json!({ "alias": alias, "owner": owner, "previous": previous }));
+    } else if previous.is_some() {
+        output::success(&format!("@{alias} updated"));
+    } else {
+        output::success(&format!("@{alias} added"));
+    }
+    Ok(())
+}
+
+pub fn remove(alias: &str) -> Result<()> {
+    let mut wallet = load_wallet()?;
+    let Some(owner) = remove_contact(&mut wallet, alias) else {
+        return Err(output::fail_with_hint(
+            &format!("no contact named `{alias}`"),
+            "`r14 contact list` shows the address book",
+        ));
+    };
+    save_wallet(&wallet)?;
+
+    let alias = normalize_alias(alias)?;
+    if output::is_json() {
+        output::json_output(serde_json::json!({ "alias": alias, "owner": owner }));
+    } else {
+        output::success(&format!("@{alias} removed"));
+    }
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    let wallet = load_wallet()?;
+    // show addresses, whichever form each was saved in
+    let address = |owner: &str| parse_owner(owner).map(|o| owner_address(&o)).unwrap_or_else(|_| owner.to_string());
+
+    if output::is_json() {
+        let contacts: Vec<_> = wallet
+            .contacts
+            .iter()
+            .map(|(alias, owner)| serde_json::json!({ "alias": alias, "owner": owner, "address": address(owner) }))
+            .collect();
+        output::json_output(serde_json::json!({ "contacts": contacts }));
+    } else if wallet.contacts.is_empty() {
+        output::info("no contacts; add one with `r14 contact add <alias> <address>`");
+    } else {
+        for (alias, owner) in &wallet.contacts {
+            output::label(&format!("@{alias}"), &address(owner));
+        }
+    }
+    Ok(())
+}
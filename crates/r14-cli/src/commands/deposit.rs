@@ -1,10 +1,11 @@
 use anyhow::Result;
-use r14_sdk::{commitment, fr_to_raw_hex, Note};
-use r14_sdk::wallet::{crypto_rng, fr_to_hex, hex_to_fr, load_wallet, save_wallet, NoteEntry};
+use r14_sdk::history::HistoryEntry;
+use r14_sdk::{commitment, fr_to_raw_hex, IndexerClient, Network, Note};
+use r14_sdk::wallet::{crypto_rng, fr_to_hex, hex_to_fr, load_wallet, save_wallet, NoteEntry, NoteSource};
 
 use crate::output;
 
-pub async fn run(value: u64, app_tag: u32, local_only: bool) -> Result<()> {
+pub async fn run(value: u64, app_tag: u32, local_only: bool, label: Option<String>) -> Result<()> {
     let mut wallet = load_wallet()?;
     let owner = hex_to_fr(&wallet.owner_hash)?;
 
@@ -12,20 +13,13 @@ pub async fn run(value: u64, app_tag: u32, local_only: bool) -> Result<()> {
     let note = Note::new(value, app_tag, owner, &mut rng);
     let cm = commitment(&note);
 
-    let entry = NoteEntry {
-        value: note.value,
-        app_tag: note.app_tag,
-        owner: fr_to_hex(&note.owner),
-        nonce: fr_to_hex(&note.nonce),
-        commitment: fr_to_hex(&cm),
-        index: None,
-        spent: false,
-    };
+    let mut entry = NoteEntry::new(&note, &cm).with_source(NoteSource::Deposit);
+    entry.meta.label = label;
 
     wallet.notes.push(entry);
     save_wallet(&wallet)?;
 
-    let cm_hex_display = fr_to_hex(&cm);
+    let cm_hex_display = fr_to_hex(&cm.0);
 
     if local_only {
         if output::is_json() {
@@ -45,16 +39,17 @@ pub async fn run(value: u64, app_tag: u32, local_only: bool) -> Result<()> {
         return Ok(());
     }
 
-    let cm_hex = fr_to_raw_hex(&cm);
+    let cm_hex = fr_to_raw_hex(&cm.0);
 
     let sp = output::spinner("computing new merkle root...");
-    let new_root_hex = r14_sdk::merkle::compute_new_root(&wallet.indexer_url, &[cm]).await?;
+    let indexer = IndexerClient::new(&wallet.indexer_url);
+    let new_root_hex = super::new_root(&indexer, &[cm]).await?;
     sp.finish_and_clear();
 
     let sp = output::spinner("submitting deposit on-chain...");
     let result = r14_sdk::soroban::invoke_contract(
         &wallet.transfer_contract_id,
-        "testnet",
+        &Network::from_wallet(&wallet),
         &wallet.stellar_secret,
         "deposit",
         &[("cm", &cm_hex), ("new_root", &new_root_hex)],
@@ -62,6 +57,9 @@ pub async fn run(value: u64, app_tag: u32, local_only: bool) -> Result<()> {
     .await?;
     sp.finish_and_clear();
 
+    wallet.history.push(HistoryEntry::deposit(value, app_tag, cm, result.clone()));
+    save_wallet(&wallet)?;
+
     if output::is_json() {
         output::json_output(serde_json::json!({
             "value": value,
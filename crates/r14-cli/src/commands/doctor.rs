@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::output;
+use r14_sdk::deployment::{Deployment, Finding, Severity, DESCRIPTOR_FILE};
+use r14_sdk::wallet::{load_wallet, wallet_path};
+use r14_sdk::{IndexerClient, R14Error};
+
+pub async fn run(descriptor: Option<PathBuf>, offline: bool) -> Result<()> {
+    let (path, deployment) = match descriptor {
+        Some(p) => {
+            let d = Deployment::load(&p)?;
+            (p, d)
+        }
+        None => match Deployment::discover()? {
+            Some(found) => found,
+            None => {
+                return Err(output::fail_with_hint(
+                    &format!("no {DESCRIPTOR_FILE} found in this directory or its parents"),
+                    "pass --descriptor <path> or set R14_DEPLOYMENT",
+                ))
+            }
+        },
+    };
+
+    let mut findings = deployment.lint();
+
+    let sp = output::spinner("hashing local verification keys...");
+    findings.extend(deployment.check_local_vks());
+    sp.finish_and_clear();
+
+    let wallet = if wallet_path()?.exists() {
+        let w = load_wallet()?;
+        findings.extend(deployment.check_wallet(&w));
+        Some(w)
+    } else {
+        findings.push(Finding::warn(
+            "wallet",
+            "no wallet found",
+            "run `r14 keygen` to create one",
+        ));
+        None
+    };
+
+    if !offline {
+        let sp = output::spinner("checking indexer...");
+        let indexer = IndexerClient::with_timeout(&deployment.indexer_url, Duration::from_secs(5));
+        findings.extend(deployment.check_indexer(&indexer).await);
+        sp.finish_and_clear();
+
+        match wallet.as_ref().map(|w| w.stellar_secret.as_str()) {
+            Some(secret) if secret != "PLACEHOLDER" => {
+                let sp = output::spinner("querying contracts...");
+                findings.extend(deployment.check_contracts(secret).await);
+                sp.finish_and_clear();
+            }
+            _ => findings.push(Finding::warn(
+                "contracts",
+                "skipped: no stellar_secret to simulate with",
+                "run `r14 config set stellar_secret <S...>`",
+            )),
+        }
+    }
+
+    let failures = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Fail)
+        .count();
+
+    if output::is_json() {
+        output::json_output(serde_json::json!({
+            "descriptor": path.display().to_string(),
+            "ok": failures == 0,
+            "findings": findings,
+        }));
+    } else {
+        output::label("descriptor", &path.display().to_string());
+        for f in &findings {
+            let mark = match f.severity {
+                Severity::Ok => "ok".green(),
+                Severity::Warn => "warn".yellow(),
+                Severity::Fail => "FAIL".red(),
+            };
+            output::info(&format!("  [{mark}] {}: {}", f.check.bold(), f.message));
+            if let Some(hint) = &f.hint {
+                output::info(&format!("         {} {hint}", "hint:".bold()));
+            }
+        }
+    }
+
+    if failures > 0 {
+        return Err(R14Error::Config(format!("{failures} deployment check(s) failed")).into());
+    }
+    output::success("deployment consistent with descriptor");
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::output;
+use r14_sdk::history::{query, HistoryKind, HistoryQuery};
+use r14_sdk::wallet::load_wallet;
+
+pub fn run(kind: Option<HistoryKind>, limit: usize, offset: usize) -> Result<()> {
+    let wallet = load_wallet()?;
+    let page = query(&wallet.history, &HistoryQuery { kind, offset, limit: Some(limit) });
+
+    if output::is_json() {
+        output::json_output(serde_json::json!({
+            "entries": page.entries,
+            "total": page.total,
+            "next_offset": page.next_offset,
+        }));
+        return Ok(());
+    }
+
+    if page.total == 0 {
+        output::info("no transactions yet");
+        return Ok(());
+    }
+    for (i, e) in page.entries.iter().enumerate() {
+        let mut line = format!("  [{}] {} value={} app_tag={}", offset + i, e.kind.to_string().bold(), e.value, e.app_tag);
+        if let Some(ts) = e.timestamp {
+            line += &format!(" at={ts}");
+        }
+        if let Some(to) = &e.counterparty {
+            line += &format!(" to={}", &to[..to.len().min(10)]);
+        }
+        if let Some(nf) = &e.nullifier {
+            line += &format!(" nullifier={}", &nf[..nf.len().min(10)]);
+        }
+        output::info(&line);
+    }
+    if let Some(next) = page.next_offset {
+        output::info(&format!("\n{} of {}, more with --offset {next}", page.entries.len(), page.total));
+    }
+    Ok(())
+}
@@ -1,10 +1,12 @@
-use anyhow::Result;
-use ark_std::rand::{rngs::StdRng, SeedableRng};
+use std::path::Path;
+
+use anyhow::{Context, Result};
 
 use crate::output;
 use r14_sdk::wallet::load_wallet;
+use r14_sdk::Network;
 
-pub async fn run() -> Result<()> {
+pub async fn run(vk_file: Option<&Path>) -> Result<()> {
     let wallet = load_wallet()?;
 
     // validation now in main.rs, but keep guard for direct calls
@@ -18,20 +20,14 @@ pub async fn run() -> Result<()> {
         ));
     }
 
-    // Deterministic setup — same seed=42 used everywhere
-    let sp = output::spinner("setting up circuit...");
-    let mut rng = StdRng::seed_from_u64(42);
-    let (_pk, vk) = r14_sdk::prove::setup(&mut rng);
-    sp.finish_and_clear();
-
-    let svk = r14_sdk::prove::serialize_vk_for_soroban(&vk);
-
-    // Build VK JSON matching the Soroban contract's unified IC format
-    let ic_entries: Vec<String> = svk.ic.iter().map(|s| format!("\"{}\"", s)).collect();
-    let vk_json = format!(
-        r#"{{"alpha_g1":"{}","beta_g2":"{}","gamma_g2":"{}","delta_g2":"{}","ic":[{}]}}"#,
-        svk.alpha_g1, svk.beta_g2, svk.gamma_g2, svk.delta_g2, ic_entries.join(",")
-    );
+    let vk_json = match vk_file {
+        // ceremony output is already in the contract's JSON format
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("reading VK {}", path.display()))?
+            .trim()
+            .to_string(),
+        None => local_vk_json()?,
+    };
 
     // Derive caller address from stellar secret
     let caller_address = r14_sdk::soroban::get_public_key(&wallet.stellar_secret).await?;
@@ -40,7 +36,7 @@ pub async fn run() -> Result<()> {
     let sp = output::spinner("registering VK on r14-core...");
     let circuit_id = r14_sdk::soroban::invoke_contract(
         &wallet.core_contract_id,
-        "testnet",
+        &Network::from_wallet(&wallet),
         &wallet.stellar_secret,
         "register",
         &[("caller", &caller_address), ("vk", &vk_json)],
@@ -50,16 +46,18 @@ pub async fn run() -> Result<()> {
 
     output::info(&format!("VK registered, circuit_id: {circuit_id}"));
 
-    // Step 2: Initialize r14-transfer with core address, circuit_id, empty root
+    // Step 2: Initialize r14-transfer with this account as admin, core
+    // address, circuit_id, empty root
     let empty_root_hex = r14_sdk::merkle::empty_root_hex();
 
     let sp = output::spinner("initializing r14-transfer...");
     let result = r14_sdk::soroban::invoke_contract(
         &wallet.transfer_contract_id,
-        "testnet",
+        &Network::from_wallet(&wallet),
         &wallet.stellar_secret,
         "init",
         &[
+            ("admin", &caller_address),
             ("core_contract", &wallet.core_contract_id),
             ("circuit_id", &circuit_id),
             ("empty_root", &empty_root_hex),
@@ -80,3 +78,19 @@ pub async fn run() -> Result<()> {
     }
     Ok(())
 }
+
+/// VK of the installed keys, else of the deterministic setup (seed=42)
+fn local_vk_json() -> Result<String> {
+    let sp = output::spinner("setting up circuit...");
+    let (_pk, vk) = r14_sdk::artifacts::transfer_keys()?;
+    sp.finish_and_clear();
+
+    let svk = r14_sdk::prove::serialize_vk_for_soroban(&vk, r14_sdk::MERKLE_DEPTH);
+
+    // Build VK JSON matching the Soroban contract's unified IC format
+    let ic_entries: Vec<String> = svk.ic.iter().map(|s| format!("\"{}\"", s)).collect();
+    Ok(format!(
+        r#"{{"alpha_g1":"{}","beta_g2":"{}","gamma_g2":"{}","delta_g2":"{}","ic":[{}],"depth":{}}}"#,
+        svk.alpha_g1, svk.beta_g2, svk.gamma_g2, svk.delta_g2, ic_entries.join(","), svk.depth
+    ))
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use r14_sdk::{owner_hash, SecretKey};
-use r14_sdk::wallet::{crypto_rng, fr_to_hex, save_wallet, wallet_path, WalletData};
+use r14_sdk::wallet::{crypto_rng, fr_to_hex, owner_address, save_wallet, wallet_path, WalletData};
 
 use crate::output;
 
@@ -19,8 +19,15 @@ pub fn run() -> Result<()> {
         owner_hash: fr_to_hex(&owner.0),
         stellar_secret: "PLACEHOLDER".into(),
         notes: vec![],
+        sessions: vec![],
+        pending: vec![],
+        history: vec![],
+        policy: None,
+        contacts: Default::default(),
         indexer_url: "http://localhost:3000".into(),
         rpc_url: "https://soroban-testnet.stellar.org:443".into(),
+        network: "testnet".into(),
+        network_passphrase: None,
         core_contract_id: "PLACEHOLDER".into(),
         transfer_contract_id: "PLACEHOLDER".into(),
     };
@@ -31,10 +38,12 @@ pub fn run() -> Result<()> {
         output::json_output(serde_json::json!({
             "wallet_path": path.display().to_string(),
             "owner_hash": wallet.owner_hash,
+            "address": owner_address(&owner.0),
         }));
     } else {
         output::success(&format!("wallet created at {}", path.display()));
         output::label("owner_hash", &wallet.owner_hash);
+        output::label("address", &owner_address(&owner.0));
         output::warn("run `r14 config set stellar_secret <SECRET>` to configure");
     }
     Ok(())
@@ -0,0 +1,43 @@
+use anyhow::Result;
+
+use crate::output;
+use r14_sdk::wallet::{fr_to_raw_hex, load_wallet, save_wallet, strip_0x};
+
+pub fn run(commitment: &str, label: Option<String>) -> Result<()> {
+    let mut wallet = load_wallet()?;
+    let prefix = strip_0x(commitment).to_lowercase();
+    let matches: Vec<usize> = wallet
+        .notes
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| fr_to_raw_hex(&n.commitment.0).starts_with(&prefix))
+        .map(|(i, _)| i)
+        .collect();
+    let idx = match matches[..] {
+        [idx] => idx,
+        [] => return Err(output::fail_with_hint(
+            &format!("no note with commitment {commitment}"),
+            "`r14 balance` lists commitments",
+        )),
+        _ => return Err(output::fail_with_hint(
+            &format!("{} notes match {commitment}", matches.len()),
+            "give more of the commitment",
+        )),
+    };
+
+    let note = &mut wallet.notes[idx];
+    note.meta.label = label;
+    let cm = fr_to_raw_hex(&note.commitment.0);
+    let label = note.meta.label.clone();
+    save_wallet(&wallet)?;
+
+    if output::is_json() {
+        output::json_output(serde_json::json!({ "commitment": format!("0x{cm}"), "label": label }));
+    } else {
+        match label {
+            Some(label) => output::success(&format!("labelled 0x{cm}: {label}")),
+            None => output::success(&format!("label cleared on 0x{cm}")),
+        }
+    }
+    Ok(())
+}
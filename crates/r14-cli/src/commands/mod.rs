@@ -1,7 +1,25 @@
+use anyhow::Result;
+use r14_sdk::leaf_cache::LeafCache;
+use r14_sdk::{Commitment, IndexerClient};
+
 pub mod balance;
 pub mod config;
+pub mod contact;
 pub mod deposit;
+pub mod doctor;
+pub mod history;
 pub mod init_contract;
 pub mod keygen;
+pub mod label;
 pub mod status;
+pub mod submit_proof;
 pub mod transfer;
+
+/// The root after appending `cms`, fetching only the leaves added since
+/// the last command (cached at `~/.r14/leaves.json`)
+pub async fn new_root(indexer: &IndexerClient, cms: &[Commitment]) -> Result<String> {
+    let mut cache = LeafCache::open_default()?;
+    let root = cache.new_root(indexer, cms).await?;
+    cache.save()?;
+    Ok(root)
+}
@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use colored::Colorize;
 
 use crate::output;
-use r14_sdk::wallet::{load_wallet, wallet_path};
+use r14_sdk::indexer::IndexerStats;
+use r14_sdk::wallet::{fr_to_hex, load_wallet, wallet_path};
+use r14_sdk::IndexerClient;
 
 pub async fn run() -> Result<()> {
     let path = wallet_path()?;
@@ -14,6 +18,7 @@ pub async fn run() -> Result<()> {
                 "wallet_loaded": false,
                 "contracts_configured": false,
                 "indexer_reachable": false,
+                "indexer_stats": null,
                 "notes_total": 0,
                 "notes_synced": 0,
             }));
@@ -30,14 +35,10 @@ pub async fn run() -> Result<()> {
         && wallet.core_contract_id != "PLACEHOLDER"
         && wallet.transfer_contract_id != "PLACEHOLDER";
 
-    // ping indexer
-    let indexer_reachable = reqwest::Client::new()
-        .get(format!("{}/v1/root", wallet.indexer_url))
-        .timeout(std::time::Duration::from_secs(3))
-        .send()
-        .await
-        .map(|r| r.status().is_success())
-        .unwrap_or(false);
+    // an indexer that predates /v1/stats still answers /v1/health
+    let indexer = IndexerClient::with_timeout(&wallet.indexer_url, Duration::from_secs(3));
+    let stats = indexer.stats().await.ok();
+    let indexer_reachable = stats.is_some() || indexer.health().await.is_ok();
 
     let unspent: Vec<_> = wallet.notes.iter().filter(|n| !n.spent).collect();
     let notes_total = unspent.len();
@@ -48,6 +49,7 @@ pub async fn run() -> Result<()> {
             "wallet_loaded": true,
             "contracts_configured": contracts_configured,
             "indexer_reachable": indexer_reachable,
+            "indexer_stats": stats.as_ref().map(stats_json),
             "notes_total": notes_total,
             "notes_synced": notes_synced,
         }));
@@ -65,8 +67,41 @@ pub async fn run() -> Result<()> {
             format!("{} ({})", "unreachable".red(), wallet.indexer_url)
         };
         output::label("indexer", &indexer_str);
+        if let Some(stats) = &stats {
+            output::label("tree", &format!("{} leaves, root {}", stats.leaf_count, fr_to_hex(&stats.root.0)));
+            output::label(
+                "indexed",
+                &format!(
+                    "{} deposits, {} transfers, {} nullifiers spent",
+                    stats.deposits, stats.transfers, stats.nullifier_count
+                ),
+            );
+            let ledger = |l: Option<u64>| l.map_or("-".to_string(), |l| l.to_string());
+            output::label(
+                "ledgers",
+                &format!(
+                    "{} to {} (network at {})",
+                    ledger(stats.first_ledger),
+                    ledger(stats.last_ledger),
+                    stats.network_ledger
+                ),
+            );
+        }
         output::label("notes", &format!("{notes_total} total, {notes_synced} synced"));
     }
 
     Ok(())
 }
+
+fn stats_json(stats: &IndexerStats) -> serde_json::Value {
+    serde_json::json!({
+        "leaf_count": stats.leaf_count,
+        "nullifier_count": stats.nullifier_count,
+        "deposits": stats.deposits,
+        "transfers": stats.transfers,
+        "first_ledger": stats.first_ledger,
+        "last_ledger": stats.last_ledger,
+        "network_ledger": stats.network_ledger,
+        "root": fr_to_hex(&stats.root.0),
+    })
+}
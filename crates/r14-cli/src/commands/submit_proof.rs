@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use anyhow::Result;
+use r14_sdk::proof_file::read_proof_file;
+use r14_sdk::wallet::load_wallet;
+use r14_sdk::R14Client;
+
+use crate::output;
+
+pub async fn run(path: &Path) -> Result<()> {
+    let wallet = load_wallet()?;
+    let file = read_proof_file(path)?;
+    if let Some(circuit) = file.metadata.get("circuit").filter(|c| *c != "transfer") {
+        return Err(output::fail_with_hint(
+            &format!("{} holds a {circuit} proof", path.display()),
+            "only transfer proofs can be submitted with `r14 submit-proof`",
+        ));
+    }
+    let proof = file.transfer_proof()?;
+
+    // a proof for another circuit would fail verification after paying fees
+    let client = R14Client::from_wallet(&wallet)?;
+    let onchain = client.transfer_circuit_id().await?;
+    let expected = hex::encode(file.circuit_id);
+    if onchain != expected {
+        return Err(output::fail_with_hint(
+            &format!("proof is for circuit {expected}, the transfer contract verifies {onchain}"),
+            "generate the proof against this deployment's keys",
+        ));
+    }
+
+    let sp = output::spinner("submitting transfer on-chain...");
+    let result = client.submit_transfer(&proof).await;
+    sp.finish_and_clear();
+    let result = result?;
+
+    let nullifier = format!("0x{}", proof.nullifier);
+    if output::is_json() {
+        output::json_output(serde_json::json!({
+            "nullifier": nullifier,
+            "result": result,
+        }));
+    } else {
+        output::success("transfer submitted");
+        output::label("nullifier", &nullifier);
+        output::label("tx", &result);
+    }
+    Ok(())
+}
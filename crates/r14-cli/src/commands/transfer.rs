@@ -1,82 +1,63 @@
-use anyhow::{Context, Result};
-use ark_bls12_381::Fr;
-use r14_sdk::{commitment, MerklePath, Note};
-use r14_sdk::wallet::{crypto_rng, fr_to_hex, hex_to_fr, load_wallet, save_wallet, NoteEntry};
-use serde::Deserialize;
+use std::path::Path;
 
-use ark_std::rand::{rngs::StdRng, SeedableRng};
+use anyhow::{Context, Result};
+use r14_sdk::history::HistoryEntry;
+use r14_sdk::pending::{begin_transfer, confirm_transfer, rollback_transfer};
+use r14_sdk::proof_file::{write_proof_file, ProofFile};
+use r14_sdk::serialize::{hex_to_bytes_n, serialize_proof_bytes};
+use r14_sdk::selection::{select_notes, TRANSFER_INPUTS};
+use r14_sdk::{commitment, CoinSelection, IndexerClient, Network, Note, R14Client, R14Error};
+use r14_sdk::contacts::resolve_recipient;
+use r14_sdk::wallet::{crypto_rng, fr_to_hex, hex_to_fr, load_wallet, save_wallet, NoteEntry, NoteSource};
 
 use r14_sdk::strip_0x;
 
 use crate::output;
 
-#[derive(Deserialize)]
-struct ProofResponse {
-    siblings: Vec<String>,
-    indices: Vec<bool>,
-}
-
-#[derive(Deserialize)]
-struct RootResponse {
-    #[allow(dead_code)]
-    root: String,
-}
-
-pub async fn run(value: u64, recipient_hex: &str, dry_run: bool) -> Result<()> {
+pub async fn run(
+    value: u64,
+    recipient: &str,
+    dry_run: bool,
+    out: Option<&Path>,
+    select: CoinSelection,
+    label: Option<String>,
+) -> Result<()> {
     let mut wallet = load_wallet()?;
     let sk_fr = hex_to_fr(&wallet.secret_key)?;
     let owner_fr = hex_to_fr(&wallet.owner_hash)?;
-    let recipient_fr = hex_to_fr(recipient_hex)?;
+    let recipient_fr = resolve_recipient(&wallet, recipient)?;
+
+    let indexer = IndexerClient::new(&wallet.indexer_url);
+
+    // prove at the indexer's last ledger rather than a note's unlock ledger,
+    // so the public input doesn't reveal which note is spent
+    let current_ledger = indexer
+        .latest_ledger()
+        .await
+        .context("failed to fetch latest ledger")?;
 
-    // find unspent note with sufficient value and on-chain index
-    let note_idx = wallet
-        .notes
-        .iter()
-        .position(|n| !n.spent && n.value >= value && n.index.is_some())
-        .context("no unspent on-chain note with sufficient value")?;
+    // pick an unspent, unlocked on-chain note per the selection strategy
+    let note_idx =
+        select_notes(&wallet.notes, value, select, TRANSFER_INPUTS, current_ledger)?[0];
 
     let entry = &wallet.notes[note_idx];
-    let consumed = Note::with_nonce(
-        entry.value,
-        entry.app_tag,
-        hex_to_fr(&entry.owner)?,
-        hex_to_fr(&entry.nonce)?,
-    );
+    let consumed = entry.note()?;
     let leaf_index = entry.index.unwrap();
     let app_tag = entry.app_tag;
     let consumed_value = entry.value;
 
-    let client = reqwest::Client::new();
-
-    // fetch merkle proof
-    let proof_url = format!("{}/v1/proof/{}", wallet.indexer_url, leaf_index);
-    let proof_resp: ProofResponse = client
-        .get(&proof_url)
-        .send()
-        .await?
-        .json()
-        .await
-        .context("failed to parse merkle proof")?;
-
-    let siblings: Vec<Fr> = proof_resp
-        .siblings
-        .iter()
-        .map(|s| hex_to_fr(s))
-        .collect::<Result<_>>()?;
-    let merkle_path = MerklePath {
-        siblings,
-        indices: proof_resp.indices,
-    };
-
-    // fetch root (for verification context)
-    let root_url = format!("{}/v1/root", wallet.indexer_url);
-    let _root_resp: RootResponse = client
-        .get(&root_url)
-        .send()
-        .await?
-        .json()
+    // fetch merkle proof and check it against the indexer root
+    let root = indexer.root().await.context("failed to fetch root")?;
+    let merkle_path = indexer
+        .merkle_proof(leaf_index)
         .await
-        .context("failed to parse root")?;
+        .context("failed to fetch merkle proof")?;
+    if !r14_sdk::merkle::verify_proof(commitment(&consumed), &merkle_path, root) {
+        return Err(R14Error::StaleRoot(
+            "indexer merkle path does not match its root — retry once it settles".into(),
+        )
+        .into());
+    }
 
     // build output notes
     let mut rng = crypto_rng();
@@ -84,18 +65,19 @@ pub async fn run(value: u64, recipient_hex: &str, dry_run: bool) -> Result<()> {
     let note_0 = Note::new(value, app_tag, recipient_fr, &mut rng);
     let note_1 = Note::new(change, app_tag, owner_fr, &mut rng);
 
-    // prove — deterministic seed for setup so pk matches on-chain vk
-    let sp = output::spinner("generating proof (this may take a few seconds)...");
-    let setup_rng = &mut StdRng::seed_from_u64(42);
-    let (pk, _vk) = r14_sdk::prove::setup(setup_rng);
-    let (proof, pi) = r14_sdk::prove::prove(
+    // prove — installed keys, else the deterministic setup matching the on-chain vk
+    let sp = output::spinner("loading proving key...");
+    let (pk, vk) = r14_sdk::artifacts::transfer_keys()?;
+    let (proof, pi) = r14_sdk::prove::prove_with_progress(
         &pk,
         sk_fr,
         consumed.clone(),
         merkle_path,
         [note_0.clone(), note_1.clone()],
+        current_ledger,
         &mut rng,
-    );
+        |p| sp.set_message(output::prove_message(p)),
+    )?;
     sp.finish_and_clear();
 
     let (serialized_proof, serialized_pi) =
@@ -113,8 +95,8 @@ pub async fn run(value: u64, recipient_hex: &str, dry_run: bool) -> Result<()> {
             },
             "public_inputs": serialized_pi,
             "nullifier": fr_to_hex(&pi.nullifier),
-            "out_commitment_0": fr_to_hex(&cm_0),
-            "out_commitment_1": fr_to_hex(&cm_1),
+            "out_commitment_0": fr_to_hex(&cm_0.0),
+            "out_commitment_1": fr_to_hex(&cm_1.0),
         });
         if output::is_json() {
             output::json_output(dry_output);
@@ -124,30 +106,75 @@ pub async fn run(value: u64, recipient_hex: &str, dry_run: bool) -> Result<()> {
         return Ok(());
     }
 
+    let nullifier = fr_to_hex(&pi.nullifier);
+    let mut payment = NoteEntry::new(&note_0, &cm_0)
+        .with_source(NoteSource::Transfer)
+        .with_counterparty(fr_to_hex(&recipient_fr));
+    payment.meta.label = label;
+    let outputs = vec![payment, NoteEntry::new(&note_1, &cm_1).with_source(NoteSource::Change)];
+
+    if let Some(path) = out {
+        let svk = r14_sdk::prove::serialize_vk_for_soroban(&vk, r14_sdk::MERKLE_DEPTH);
+        let circuit_id = hex_to_bytes_n(&r14_sdk::serialize::circuit_id(&svk))?;
+        let (proof_bytes, pi_bytes) = serialize_proof_bytes(&proof, &pi.to_vec());
+        let file = ProofFile::new(circuit_id, proof_bytes, pi_bytes).with_metadata("circuit", "transfer");
+        write_proof_file(path, &file)?;
+
+        // the note is spent once someone submits the file; park the outputs
+        // until `r14 balance` sees the nullifier on-chain
+        begin_transfer(&mut wallet, note_idx, &nullifier, outputs)?;
+        save_wallet(&wallet)?;
+
+        if output::is_json() {
+            output::json_output(serde_json::json!({
+                "path": path.display().to_string(),
+                "nullifier": nullifier,
+                "out_commitment_0": fr_to_hex(&cm_0.0),
+                "out_commitment_1": fr_to_hex(&cm_1.0),
+            }));
+        } else {
+            output::success("proof written");
+            output::label("path", &path.display().to_string());
+            output::label("nullifier", &nullifier);
+            output::info("submit it with `r14 submit-proof <path>`");
+        }
+        return Ok(());
+    }
+
     // Build proof JSON for Soroban contracttype Proof { a: G1Affine, b: G2Affine, c: G1Affine }
     let proof_json = format!(
         r#"{{"a":"{}","b":"{}","c":"{}"}}"#,
         serialized_proof.a, serialized_proof.b, serialized_proof.c
     );
 
-    // Public inputs: old_root, nullifier, cm_0, cm_1 as hex (no 0x prefix)
+    // Public inputs: old_root, nullifier, cm_0, cm_1 as hex (no 0x prefix);
+    // current_ledger goes as a plain u64
     let old_root_hex = strip_0x(&serialized_pi[0]);
     let nullifier_hex = strip_0x(&serialized_pi[1]);
     let cm_0_hex = strip_0x(&serialized_pi[2]);
     let cm_1_hex = strip_0x(&serialized_pi[3]);
 
+    // the contract only accepts roots still in its history
+    let client = R14Client::from_wallet(&wallet)?;
+    if !client.is_known_root(&old_root_hex).await? {
+        return Err(R14Error::StaleRoot(format!(
+            "root {old_root_hex} is not in the contract's root history — is the indexer behind?"
+        ))
+        .into());
+    }
+
     let sp = output::spinner("computing new merkle root...");
-    let new_root_hex = r14_sdk::merkle::compute_new_root(
-        &wallet.indexer_url,
-        &[cm_0, cm_1],
-    )
-    .await?;
+    let new_root_hex = super::new_root(&indexer, &[cm_0, cm_1]).await?;
     sp.finish_and_clear();
 
+    // phase 1: reserve the note and park the outputs until inclusion
+    begin_transfer(&mut wallet, note_idx, &nullifier, outputs)?;
+    save_wallet(&wallet)?;
+
     let sp = output::spinner("submitting transfer on-chain...");
-    let result = r14_sdk::soroban::invoke_contract(
+    let submitted = r14_sdk::soroban::invoke_contract(
         &wallet.transfer_contract_id,
-        "testnet",
+        &Network::from_wallet(&wallet),
         &wallet.stellar_secret,
         "transfer",
         &[
@@ -156,50 +183,55 @@ pub async fn run(value: u64, recipient_hex: &str, dry_run: bool) -> Result<()> {
             ("nullifier", &nullifier_hex),
             ("cm_0", &cm_0_hex),
             ("cm_1", &cm_1_hex),
+            ("current_ledger", &current_ledger.to_string()),
             ("new_root", &new_root_hex),
         ],
     )
-    .await?;
+    .await;
     sp.finish_and_clear();
 
-    // update wallet: mark consumed as spent, add output notes
-    wallet.notes[note_idx].spent = true;
-
-    wallet.notes.push(NoteEntry {
-        value: note_0.value,
-        app_tag: note_0.app_tag,
-        owner: fr_to_hex(&note_0.owner),
-        nonce: fr_to_hex(&note_0.nonce),
-        commitment: fr_to_hex(&cm_0),
-        index: None,
-        spent: false,
-    });
-
-    wallet.notes.push(NoteEntry {
-        value: note_1.value,
-        app_tag: note_1.app_tag,
-        owner: fr_to_hex(&note_1.owner),
-        nonce: fr_to_hex(&note_1.nonce),
-        commitment: fr_to_hex(&cm_1),
-        index: None,
-        spent: false,
-    });
-
-    save_wallet(&wallet)?;
+    // phase 2: confirm on inclusion, roll back only when it can't have landed
+    let result = match submitted {
+        Ok(result) => {
+            confirm_transfer(&mut wallet, &nullifier)?;
+            wallet.history.push(HistoryEntry::transfer(
+                value,
+                app_tag,
+                nullifier.clone(),
+                [cm_0, cm_1],
+                fr_to_hex(&recipient_fr),
+                result.clone(),
+            ));
+            save_wallet(&wallet)?;
+            result
+        }
+        Err(e) if r14_sdk::soroban::never_landed(&e) => {
+            rollback_transfer(&mut wallet, &nullifier)?;
+            save_wallet(&wallet)?;
+            return Err(e);
+        }
+        Err(e) => {
+            // the note stays reserved; `r14 balance` confirms it once the nullifier lands
+            return Err(output::fail_with_hint(
+                &format!("{e:#}"),
+                "the transfer may still land, so its note stays reserved. Run `r14 balance` later to confirm it",
+            ));
+        }
+    };
 
     if output::is_json() {
         output::json_output(serde_json::json!({
             "value": value,
-            "recipient": recipient_hex,
-            "nullifier": fr_to_hex(&pi.nullifier),
-            "out_commitment_0": fr_to_hex(&cm_0),
-            "out_commitment_1": fr_to_hex(&cm_1),
+            "recipient": recipient,
+            "nullifier": nullifier,
+            "out_commitment_0": fr_to_hex(&cm_0.0),
+            "out_commitment_1": fr_to_hex(&cm_1.0),
             "result": result,
         }));
     } else {
         output::success("transfer submitted");
         output::label("value", &value.to_string());
-        output::label("nullifier", &fr_to_hex(&pi.nullifier));
+        output::label("nullifier", &nullifier);
         output::label("tx", &result);
     }
     Ok(())
@@ -28,21 +28,61 @@ enum Cmd {
         /// Skip on-chain submission, only create local note
         #[arg(long)]
         local_only: bool,
+        /// Label for the new note
+        #[arg(long)]
+        label: Option<String>,
     },
     /// Private transfer with ZK proof
     Transfer {
         /// Amount to send
         value: u64,
-        /// Recipient owner_hash (hex)
+        /// Recipient address (r14o1...), owner_hash (hex), or @alias from the address book
         recipient: String,
         /// Only generate proof, don't submit to Soroban
         #[arg(long)]
         dry_run: bool,
+        /// Write the proof to an .r14proof file for `submit-proof` instead of submitting
+        #[arg(long, conflicts_with = "dry_run")]
+        out: Option<std::path::PathBuf>,
+        /// Note selection: first-fit, largest-first, smallest-sufficient, minimize-change
+        #[arg(long, default_value = "first-fit")]
+        select: r14_sdk::CoinSelection,
+        /// Label for the payment, kept on the recipient's note
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// Set or clear a note's label
+    Label {
+        /// Commitment (hex, or a unique prefix)
+        commitment: String,
+        /// New label; omit to clear
+        label: Option<String>,
+    },
+    /// Submit a transfer proof from an .r14proof file
+    SubmitProof {
+        /// Path written by `r14 transfer --out`
+        path: std::path::PathBuf,
     },
     /// Initialize contract with verification key
-    InitContract,
+    InitContract {
+        /// VK JSON from `r14-ceremony export` (default: deterministic setup)
+        #[arg(long)]
+        vk: Option<std::path::PathBuf>,
+    },
     /// Show balance and sync with indexer
     Balance,
+    /// List past deposits and transfers, newest first
+    History {
+        /// Only deposit, transfer or rotation
+        #[arg(long)]
+        kind: Option<r14_sdk::history::HistoryKind>,
+        /// Entries per page
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Entries to skip
+        #[arg(long, default_value_t = 0)]
+        offset: usize,
+    },
     /// Compute merkle root for given commitments (offline, no indexer)
     ComputeRoot {
         /// Commitment hex values (no 0x prefix)
@@ -50,18 +90,33 @@ enum Cmd {
     },
     /// Show wallet and indexer status
     Status,
+    /// Check wallet, indexer, and contracts against the r14.toml descriptor
+    Doctor {
+        /// Descriptor path (default: $R14_DEPLOYMENT or nearest r14.toml)
+        #[arg(long)]
+        descriptor: Option<std::path::PathBuf>,
+        /// Skip indexer and contract queries
+        #[arg(long)]
+        offline: bool,
+    },
     /// Manage configuration
     Config {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// Manage the address book of named recipients
+    Contact {
+        #[command(subcommand)]
+        action: ContactAction,
+    },
 }
 
 #[derive(Subcommand)]
 enum ConfigAction {
     /// Set a config value
     Set {
-        /// Config key (rpc_url, indexer_url, core_contract_id, transfer_contract_id, stellar_secret)
+        /// Config key (network, network_passphrase, rpc_url, indexer_url, core_contract_id,
+        /// transfer_contract_id, stellar_secret)
         key: String,
         /// New value
         value: String,
@@ -70,6 +125,23 @@ enum ConfigAction {
     Show,
 }
 
+#[derive(Subcommand)]
+enum ContactAction {
+    /// Add a contact, or point an existing alias at a new owner
+    Add {
+        /// Name to pay as `@alias`
+        alias: String,
+        /// Address (r14o1...) or owner_hash (hex)
+        owner: String,
+    },
+    /// Remove a contact
+    Remove {
+        alias: String,
+    },
+    /// List contacts
+    List,
+}
+
 fn validate_config(wallet: &wallet::WalletData) -> anyhow::Result<()> {
     let mut problems = vec![];
     if wallet.stellar_secret == "PLACEHOLDER" {
@@ -90,41 +162,86 @@ fn validate_config(wallet: &wallet::WalletData) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Hold the wallet lock for a command that saves the wallet, so concurrent
+/// `r14` processes queue up instead of overwriting each other's notes
+fn lock_wallet() -> anyhow::Result<wallet::WalletLock> {
+    if let Some(lock) = wallet::WalletLock::try_acquire()? {
+        return Ok(lock);
+    }
+    output::info("waiting for another r14 process to release the wallet...");
+    wallet::WalletLock::acquire()
+}
+
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() {
     let cli = Cli::parse();
     output::set_json_mode(cli.json);
+    // hash with the deployment's family; a broken r14.toml is `r14 doctor`'s to report
+    if let Ok(Some((_, deployment))) = r14_sdk::deployment::Deployment::discover() {
+        deployment.select_hash_family().expect("nothing has hashed yet");
+    }
 
+    if let Err(e) = run(cli).await {
+        let body = r14_errors::describe(&e);
+        let code = body.code;
+        if output::is_json() {
+            output::json_output(serde_json::to_value(body).unwrap());
+        } else {
+            eprintln!("Error: {e:?}");
+        }
+        std::process::exit(code.exit_code());
+    }
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
     match cli.command {
-        Cmd::Keygen => commands::keygen::run()?,
-        Cmd::Deposit { value, app_tag, local_only } => {
+        Cmd::Keygen => {
+            let _lock = lock_wallet()?;
+            commands::keygen::run()?
+        }
+        Cmd::Deposit { value, app_tag, local_only, label } => {
+            let _lock = lock_wallet()?;
             if !local_only {
                 let w = wallet::load_wallet()?;
                 validate_config(&w)?;
             }
-            commands::deposit::run(value, app_tag, local_only).await?
+            commands::deposit::run(value, app_tag, local_only, label).await?
         }
-        Cmd::Transfer { value, recipient, dry_run } => {
-            if !dry_run {
+        Cmd::Transfer { value, recipient, dry_run, out, select, label } => {
+            let _lock = lock_wallet()?;
+            if !dry_run && out.is_none() {
                 let w = wallet::load_wallet()?;
                 validate_config(&w)?;
             }
-            commands::transfer::run(value, &recipient, dry_run).await?
+            commands::transfer::run(value, &recipient, dry_run, out.as_deref(), select, label).await?
         }
-        Cmd::InitContract => {
+        Cmd::Label { commitment, label } => {
+            let _lock = lock_wallet()?;
+            commands::label::run(&commitment, label)?
+        }
+        Cmd::SubmitProof { path } => {
             let w = wallet::load_wallet()?;
             validate_config(&w)?;
-            commands::init_contract::run().await?
+            commands::submit_proof::run(&path).await?
+        }
+        Cmd::InitContract { vk } => {
+            let w = wallet::load_wallet()?;
+            validate_config(&w)?;
+            commands::init_contract::run(vk.as_deref()).await?
+        }
+        Cmd::Balance => {
+            let _lock = lock_wallet()?;
+            commands::balance::run().await?
         }
-        Cmd::Balance => commands::balance::run().await?,
+        Cmd::History { kind, limit, offset } => commands::history::run(kind, limit, offset)?,
         Cmd::ComputeRoot { commitments } => {
             use r14_sdk::merkle;
             let root = if commitments.is_empty() {
                 merkle::empty_root_hex()
             } else {
-                let leaves: Vec<ark_bls12_381::Fr> = commitments
+                let leaves: Vec<r14_sdk::Commitment> = commitments
                     .iter()
-                    .map(|h| wallet::hex_to_fr(h))
+                    .map(|h| wallet::hex_to_fr(h).map(r14_sdk::Commitment))
                     .collect::<anyhow::Result<_>>()?;
                 merkle::compute_root_from_leaves(&leaves)
             };
@@ -135,10 +252,25 @@ async fn main() -> anyhow::Result<()> {
             }
         }
         Cmd::Status => commands::status::run().await?,
+        Cmd::Doctor { descriptor, offline } => commands::doctor::run(descriptor, offline).await?,
         Cmd::Config { action } => match action {
-            ConfigAction::Set { key, value } => commands::config::set(&key, &value)?,
+            ConfigAction::Set { key, value } => {
+                let _lock = lock_wallet()?;
+                commands::config::set(&key, &value)?
+            }
             ConfigAction::Show => commands::config::show()?,
         },
+        Cmd::Contact { action } => match action {
+            ContactAction::Add { alias, owner } => {
+                let _lock = lock_wallet()?;
+                commands::contact::add(&alias, &owner)?
+            }
+            ContactAction::Remove { alias } => {
+                let _lock = lock_wallet()?;
+                commands::contact::remove(&alias)?
+            }
+            ContactAction::List => commands::contact::list()?,
+        },
     }
     Ok(())
 }
@@ -63,6 +63,18 @@ pub fn spinner(msg: &str) -> ProgressBar {
     pb
 }
 
+/// Spinner message for a proving stage
+pub fn prove_message(progress: r14_sdk::prove::ProveProgress) -> String {
+    use r14_sdk::prove::ProveProgress;
+    match progress {
+        ProveProgress::KeyLoaded => "building witness...".into(),
+        ProveProgress::WitnessBuilt { constraints } => format!("reducing {constraints} constraints..."),
+        ProveProgress::WitnessMapped => "generating proof (0%)...".into(),
+        ProveProgress::Msm { done, total } => format!("generating proof ({}%)...", done * 100 / total.max(1)),
+        ProveProgress::Done => "proof generated".into(),
+    }
+}
+
 pub fn fail_with_hint(error: &str, hint: &str) -> anyhow::Error {
     anyhow::anyhow!("{}\n{} {}", error.red(), "hint:".bold(), hint)
 }
@@ -3,8 +3,9 @@
 
 //! R14 Core — general-purpose Groth16 verifier registry
 
+use crate::error::CoreError;
 use crate::types::{Proof, VerificationKey};
-use crate::verifier::verify_groth16;
+use crate::verifier::{verify_groth16, verify_groth16_batch};
 use soroban_sdk::crypto::bls12_381::Fr;
 use soroban_sdk::{contract, contractimpl, contracttype, Address, Bytes, BytesN, Env, Vec};
 
@@ -18,6 +19,8 @@ pub struct VerifyEvent {
 #[derive(Clone)]
 enum DataKey {
     Admin,
+    PendingAdmin,
+    Registrar(Address),
     Circuit(BytesN<32>),
 }
 
@@ -30,33 +33,104 @@ pub struct R14Core;
 #[contractimpl]
 impl R14Core {
     /// Initialize with admin address
-    pub fn init(env: Env, admin: Address) {
+    pub fn init(env: Env, admin: Address) -> Result<(), CoreError> {
         if env.storage().instance().has(&DataKey::Admin) {
-            panic!("already initialized");
+            return Err(CoreError::AlreadyInitialized);
         }
         env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage()
             .instance()
             .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        Ok(())
     }
 
-    /// Register a verification key, returns content-addressed circuit_id
-    pub fn register(env: Env, caller: Address, vk: VerificationKey) -> BytesN<32> {
-        let admin: Address = env
+    /// Current admin address
+    pub fn admin(env: Env) -> Result<Address, CoreError> {
+        Self::admin_addr(&env)
+    }
+
+    /// Propose a new admin (admin only); takes effect once they call `accept_admin`
+    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), CoreError> {
+        Self::admin_addr(&env)?.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingAdmin, &new_admin);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        Ok(())
+    }
+
+    /// Complete a `transfer_admin` handover (pending admin only)
+    pub fn accept_admin(env: Env) -> Result<(), CoreError> {
+        let pending: Address = env
             .storage()
             .instance()
-            .get(&DataKey::Admin)
-            .expect("not initialized");
-        admin.require_auth();
+            .get(&DataKey::PendingAdmin)
+            .ok_or(CoreError::NoPendingAdmin)?;
+        pending.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &pending);
+        env.storage().instance().remove(&DataKey::PendingAdmin);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        Ok(())
+    }
+
+    /// Grant VK registration rights to `registrar` (admin only)
+    pub fn add_registrar(env: Env, registrar: Address) -> Result<(), CoreError> {
+        Self::admin_addr(&env)?.require_auth();
+        let key = DataKey::Registrar(registrar);
+        env.storage().persistent().set(&key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        Ok(())
+    }
+
+    /// Revoke VK registration rights from `registrar` (admin only)
+    pub fn remove_registrar(env: Env, registrar: Address) -> Result<(), CoreError> {
+        Self::admin_addr(&env)?.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::Registrar(registrar));
+        Ok(())
+    }
+
+    /// Check if an address holds the registrar role
+    pub fn is_registrar(env: Env, addr: Address) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Registrar(addr))
+    }
+
+    /// Register a verification key, returns content-addressed circuit_id.
+    /// `caller` must be the admin or a registrar.
+    pub fn register(
+        env: Env,
+        caller: Address,
+        vk: VerificationKey,
+    ) -> Result<BytesN<32>, CoreError> {
+        let admin = Self::admin_addr(&env)?;
+        caller.require_auth();
         if caller != admin {
-            panic!("only admin can register");
+            let registrar = DataKey::Registrar(caller);
+            if !env.storage().persistent().has(&registrar) {
+                return Err(CoreError::Unauthorized);
+            }
+            env.storage()
+                .persistent()
+                .extend_ttl(&registrar, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
         }
 
         let circuit_id = Self::compute_circuit_id(&env, &vk);
         let key = DataKey::Circuit(circuit_id.clone());
 
         if env.storage().persistent().has(&key) {
-            panic!("circuit already registered");
+            return Err(CoreError::AlreadyRegistered);
         }
 
         env.storage().persistent().set(&key, &vk);
@@ -66,42 +140,92 @@ impl R14Core {
         env.storage()
             .instance()
             .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
-        circuit_id
+        Ok(circuit_id)
     }
 
-    /// Verify a proof against a registered circuit
+    /// Verify a proof against a registered circuit.
+    /// Returns `true` on success, `InputLengthMismatch` if the input count
+    /// doesn't match the VK, and `InvalidProof` if the pairing check fails.
     pub fn verify(
         env: Env,
         circuit_id: BytesN<32>,
         proof: Proof,
         public_inputs: Vec<Fr>,
-    ) -> bool {
+    ) -> Result<bool, CoreError> {
+        let key = DataKey::Circuit(circuit_id.clone());
+        let vk: VerificationKey = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(CoreError::CircuitNotFound)?;
+        // ic[0] is the constant term; one coefficient per public input
+        if public_inputs.len() + 1 != vk.ic.len() {
+            return Err(CoreError::InputLengthMismatch);
+        }
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        if !verify_groth16(&env, &vk, &proof, &public_inputs) {
+            return Err(CoreError::InvalidProof);
+        }
+        #[allow(deprecated)]
+        env.events().publish(("verify",), VerifyEvent { circuit_id });
+        Ok(true)
+    }
+
+    /// Verify several proofs against one circuit with a single pairing check.
+    /// All-or-nothing: one bad proof fails the batch with `InvalidProof`.
+    pub fn verify_batch(
+        env: Env,
+        circuit_id: BytesN<32>,
+        proofs: Vec<Proof>,
+        public_inputs: Vec<Vec<Fr>>,
+    ) -> Result<bool, CoreError> {
         let key = DataKey::Circuit(circuit_id.clone());
         let vk: VerificationKey = env
             .storage()
             .persistent()
             .get(&key)
-            .expect("circuit not registered");
+            .ok_or(CoreError::CircuitNotFound)?;
+        if proofs.is_empty() || proofs.len() != public_inputs.len() {
+            return Err(CoreError::InputLengthMismatch);
+        }
+        for inputs in public_inputs.iter() {
+            if inputs.len() + 1 != vk.ic.len() {
+                return Err(CoreError::InputLengthMismatch);
+            }
+        }
         env.storage()
             .persistent()
             .extend_ttl(&key, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
         env.storage()
             .instance()
             .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
-        let result = verify_groth16(&env, &vk, &proof, &public_inputs);
-        if result {
-            env.events().publish(("verify",), VerifyEvent { circuit_id });
+        if !verify_groth16_batch(&env, &vk, &proofs, &public_inputs) {
+            return Err(CoreError::InvalidProof);
+        }
+        for _ in 0..proofs.len() {
+            #[allow(deprecated)]
+            env.events().publish(
+                ("verify",),
+                VerifyEvent {
+                    circuit_id: circuit_id.clone(),
+                },
+            );
         }
-        result
+        Ok(true)
     }
 
     /// Get stored verification key for a circuit
-    pub fn get_vk(env: Env, circuit_id: BytesN<32>) -> VerificationKey {
+    pub fn get_vk(env: Env, circuit_id: BytesN<32>) -> Result<VerificationKey, CoreError> {
         let key = DataKey::Circuit(circuit_id);
         env.storage()
             .persistent()
             .get(&key)
-            .expect("circuit not registered")
+            .ok_or(CoreError::CircuitNotFound)
     }
 
     /// Check if a circuit is registered
@@ -111,9 +235,18 @@ impl R14Core {
             .has(&DataKey::Circuit(circuit_id))
     }
 
-    /// Compute circuit_id = sha256(alpha_g1 ++ beta_g2 ++ gamma_g2 ++ delta_g2 ++ ic[0..n])
+    fn admin_addr(env: &Env) -> Result<Address, CoreError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(CoreError::NotInitialized)
+    }
+
+    /// Compute circuit_id = sha256(depth ++ alpha_g1 ++ beta_g2 ++ gamma_g2 ++ delta_g2 ++ ic[0..n]),
+    /// depth as 4 big-endian bytes
     fn compute_circuit_id(env: &Env, vk: &VerificationKey) -> BytesN<32> {
         let mut buf = Bytes::new(env);
+        buf.extend_from_array(&vk.depth.to_be_bytes());
         buf.extend_from_array(&vk.alpha_g1.to_bytes().to_array());
         buf.extend_from_array(&vk.beta_g2.to_bytes().to_array());
         buf.extend_from_array(&vk.gamma_g2.to_bytes().to_array());
@@ -0,0 +1,21 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Contract error codes returned by R14Core entrypoints
+
+use soroban_sdk::contracterror;
+
+/// Failure causes surfaced to callers as `Error(Contract, #code)`
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum CoreError {
+    NotInitialized = 1,
+    AlreadyInitialized = 2,
+    Unauthorized = 3,
+    AlreadyRegistered = 4,
+    CircuitNotFound = 5,
+    InvalidProof = 6,
+    InputLengthMismatch = 7,
+    NoPendingAdmin = 8,
+}
@@ -6,9 +6,11 @@
 #![no_std]
 
 mod contract;
+mod error;
 mod types;
 mod verifier;
 
 pub use contract::*;
+pub use error::*;
 pub use types::*;
 pub use verifier::*;
@@ -19,6 +19,8 @@ pub struct VerificationKey {
     pub delta_g2: G2Affine,
     /// IC[0..n] in G1 — ic[0] is the constant term, ic[1..] match public inputs
     pub ic: Vec<G1Affine>,
+    /// Depth of the Merkle tree the circuit proves paths in, 0 if none
+    pub depth: u32,
 }
 
 /// Groth16 proof for BLS12-381
@@ -4,8 +4,8 @@
 //! Groth16 verifier using Soroban BLS12-381 host functions
 
 use crate::types::{Proof, VerificationKey};
-use soroban_sdk::crypto::bls12_381::{Fr, G1Affine};
-use soroban_sdk::{BytesN, Env, Vec};
+use soroban_sdk::crypto::bls12_381::{Fr, G1Affine, G2Affine};
+use soroban_sdk::{Bytes, BytesN, Env, Vec};
 
 /// Verify a Groth16 proof using BLS12-381 pairing check
 ///
@@ -62,3 +62,91 @@ pub fn verify_groth16(
 
     bls.pairing_check(g1_points, g2_points)
 }
+
+/// Verify several Groth16 proofs under one VK with a single pairing check
+///
+/// Each proof is weighted by a 128-bit scalar r_i derived from a SHA-256
+/// transcript of all proofs and inputs, then the equations are summed:
+///   Π e(r_i·A_i, B_i) * e(-Σ r_i·L_i, gamma) * e(-Σ r_i·C_i, delta) * e(-(Σ r_i)·alpha, beta) == 1
+///
+/// Costs n + 3 pairings instead of 4n. Callers must check that `proofs` and
+/// `public_inputs` have equal, non-zero length and that every input vector
+/// matches the VK's IC length.
+pub fn verify_groth16_batch(
+    env: &Env,
+    vk: &VerificationKey,
+    proofs: &Vec<Proof>,
+    public_inputs: &Vec<Vec<Fr>>,
+) -> bool {
+    let bls = env.crypto().bls12_381();
+    let zero = Fr::from_bytes(BytesN::from_array(env, &[0u8; 32]));
+
+    // Fiat–Shamir seed over everything the prover controls
+    let mut transcript = Bytes::new(env);
+    for proof in proofs.iter() {
+        transcript.append(&proof.a.to_bytes().into());
+        transcript.append(&proof.b.to_bytes().into());
+        transcript.append(&proof.c.to_bytes().into());
+    }
+    for inputs in public_inputs.iter() {
+        for x in inputs.iter() {
+            transcript.append(&x.to_bytes().into());
+        }
+    }
+    let seed = env.crypto().sha256(&transcript).to_array();
+
+    let mut g1_points: Vec<G1Affine> = Vec::new(env);
+    let mut g2_points: Vec<G2Affine> = Vec::new(env);
+    let mut neg_r: Vec<Fr> = Vec::new(env);
+    let mut c_points: Vec<G1Affine> = Vec::new(env);
+    let mut r_sum = zero.clone();
+    // IC scalars: Σ r_i for IC[0], Σ r_i·x_ij for IC[j]
+    let mut ic_scalars: Vec<Fr> = Vec::new(env);
+    for _ in 0..vk.ic.len() {
+        ic_scalars.push_back(zero.clone());
+    }
+
+    for (i, (proof, inputs)) in proofs.iter().zip(public_inputs.iter()).enumerate() {
+        let mut pre = [0u8; 36];
+        pre[..32].copy_from_slice(&seed);
+        pre[32..].copy_from_slice(&(i as u32).to_be_bytes());
+        let digest = env
+            .crypto()
+            .sha256(&Bytes::from_array(env, &pre))
+            .to_array();
+        let mut r_bytes = [0u8; 32];
+        r_bytes[16..].copy_from_slice(&digest[..16]);
+        let r = Fr::from_bytes(BytesN::from_array(env, &r_bytes));
+
+        g1_points.push_back(bls.g1_mul(&proof.a, &r));
+        g2_points.push_back(proof.b.clone());
+        c_points.push_back(proof.c.clone());
+        neg_r.push_back(bls.fr_sub(&zero, &r));
+
+        r_sum = bls.fr_add(&r_sum, &r);
+        for (j, x) in inputs.iter().enumerate() {
+            let acc = ic_scalars.get_unchecked(j as u32 + 1);
+            ic_scalars.set(j as u32 + 1, bls.fr_add(&acc, &bls.fr_mul(&r, &x)));
+        }
+    }
+    let neg_r_sum = bls.fr_sub(&zero, &r_sum);
+    ic_scalars.set(0, neg_r_sum.clone());
+    for j in 1..ic_scalars.len() {
+        let s = ic_scalars.get_unchecked(j);
+        ic_scalars.set(j, bls.fr_sub(&zero, &s));
+    }
+
+    // -Σ r_i·L_i, -Σ r_i·C_i and -(Σ r_i)·alpha
+    let neg_l = bls.g1_msm(vk.ic.clone(), ic_scalars);
+    let neg_c = bls.g1_msm(c_points, neg_r);
+    let neg_alpha = bls.g1_mul(&vk.alpha_g1, &neg_r_sum);
+
+    g1_points.push_back(neg_l);
+    g2_points.push_back(vk.gamma_g2.clone());
+    g1_points.push_back(neg_c);
+    g2_points.push_back(vk.delta_g2.clone());
+    g1_points.push_back(neg_alpha);
+    g2_points.push_back(vk.beta_g2.clone());
+
+    bls.pairing_check(g1_points, g2_points)
+}
@@ -0,0 +1,112 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! r14-circuits proofs through r14-core: serialize_for_soroban → register → verify
+
+use r14_core::{CoreError, Proof, R14Core, R14CoreClient, VerificationKey};
+use r14_sdk::serialize::{
+    circuit_id, named_public_inputs, serialize_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK,
+};
+use soroban_sdk::crypto::bls12_381::{Fr, G1Affine, G2Affine};
+use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, Vec};
+
+// ── Hex helpers ──
+
+fn hex_to_g1(env: &Env, h: &str) -> G1Affine {
+    let bytes: [u8; 96] = hex::decode(h).unwrap().try_into().unwrap();
+    G1Affine::from_bytes(BytesN::from_array(env, &bytes))
+}
+
+fn hex_to_g2(env: &Env, h: &str) -> G2Affine {
+    let bytes: [u8; 192] = hex::decode(h).unwrap().try_into().unwrap();
+    G2Affine::from_bytes(BytesN::from_array(env, &bytes))
+}
+
+fn hex_to_bytes32(env: &Env, h: &str) -> BytesN<32> {
+    let bytes: [u8; 32] = hex::decode(h).unwrap().try_into().unwrap();
+    BytesN::from_array(env, &bytes)
+}
+
+// ── Build Soroban types from serialized (unified IC) ──
+
+fn build_soroban_vk(env: &Env, svk: &SerializedVK) -> VerificationKey {
+    let mut ic = Vec::new(env);
+    for ic_hex in &svk.ic {
+        ic.push_back(hex_to_g1(env, ic_hex));
+    }
+    VerificationKey {
+        alpha_g1: hex_to_g1(env, &svk.alpha_g1),
+        beta_g2: hex_to_g2(env, &svk.beta_g2),
+        gamma_g2: hex_to_g2(env, &svk.gamma_g2),
+        delta_g2: hex_to_g2(env, &svk.delta_g2),
+        ic,
+        depth: svk.depth,
+    }
+}
+
+fn build_soroban_proof(env: &Env, sp: &SerializedProof) -> Proof {
+    Proof {
+        a: hex_to_g1(env, &sp.a),
+        b: hex_to_g2(env, &sp.b),
+        c: hex_to_g1(env, &sp.c),
+    }
+}
+
+fn build_public_inputs(env: &Env, spi: &[String]) -> Vec<Fr> {
+    let mut pi = Vec::new(env);
+    for h in spi {
+        pi.push_back(Fr::from_bytes(hex_to_bytes32(env, h)));
+    }
+    pi
+}
+
+/// Register `svk` with a fresh r14-core and check its circuit_id
+fn register(env: &Env, svk: &SerializedVK) -> (R14CoreClient<'static>, BytesN<32>) {
+    let admin = Address::generate(env);
+    let client = R14CoreClient::new(env, &env.register(R14Core, ()));
+    client.init(&admin);
+    env.mock_all_auths();
+    let id = client.register(&admin, &build_soroban_vk(env, svk));
+    assert_eq!(id, hex_to_bytes32(env, &circuit_id(svk)));
+    (client, id)
+}
+
+// ── Tests ──
+
+use ark_bls12_381::Fr as ArkFr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_circuits::range::{RangeCircuit, Witness};
+use r14_circuits::R14Circuit;
+
+#[test]
+fn range_proof_verifies_through_core() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let witness = Witness {
+        x: 50,
+        nonce: ArkFr::rand(&mut rng),
+        min: 18,
+        max: 120,
+    };
+    let (pk, vk) = RangeCircuit::setup(&mut rng);
+    let (proof, pi) = RangeCircuit::prove(&pk, witness, &mut rng);
+
+    let named = named_public_inputs::<RangeCircuit>(&pi);
+    let names: std::vec::Vec<_> = named.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["min", "max", "commitment"]);
+
+    let (sp, spi) = serialize_for_soroban::<RangeCircuit>(&proof, &pi);
+    assert_eq!(spi, named.into_iter().map(|(_, h)| h).collect::<std::vec::Vec<_>>());
+
+    let env = Env::default();
+    let (client, id) = register(&env, &serialize_vk_for_soroban(&vk, RangeCircuit::DEPTH));
+    let proof = build_soroban_proof(&env, &sp);
+    assert!(client.verify(&id, &proof, &build_public_inputs(&env, &spi)));
+
+    // min and max swapped: same values, wrong order
+    let swapped = [spi[1].clone(), spi[0].clone(), spi[2].clone()];
+    assert_eq!(
+        client.try_verify(&id, &proof, &build_public_inputs(&env, &swapped)),
+        Err(Ok(CoreError::InvalidProof))
+    );
+}
@@ -1,10 +1,10 @@
 // Copyright 2026 abhirupbanerjee
 // Licensed under the Apache License, Version 2.0
 
-//! Unit tests for r14-core contract: register, verify, get_vk, is_registered
+//! Unit tests for r14-core contract: register, verify, get_vk, is_registered, admin roles
 
-use r14_core::{Proof, R14Core, R14CoreClient, VerificationKey};
-use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_core::{CoreError, Proof, R14Core, R14CoreClient, VerificationKey};
+use r14_sdk::serialize::{circuit_id as circuit_id_hex, serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
 use soroban_sdk::crypto::bls12_381::{Fr, G1Affine, G2Affine};
 use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, Vec};
 
@@ -38,6 +38,7 @@ fn build_soroban_vk(env: &Env, svk: &SerializedVK) -> VerificationKey {
         gamma_g2: hex_to_g2(env, &svk.gamma_g2),
         delta_g2: hex_to_g2(env, &svk.delta_g2),
         ic,
+        depth: svk.depth,
     }
 }
 
@@ -85,12 +86,12 @@ fn setup_and_prove() -> TestScenario {
     let note_0 = Note::new(700, 1, recipient_owner.0, &mut rng);
     let note_1 = Note::new(300, 1, owner.0, &mut rng);
 
-    let (pk, vk) = r14_circuit::setup(&mut rng);
-    let (proof, pi) = r14_circuit::prove(&pk, sk.0, consumed, path, [note_0, note_1], &mut rng);
+    let (pk, vk) = r14_circuit::setup(&mut rng).unwrap();
+    let (proof, pi) = r14_circuit::prove(&pk, sk.0, consumed, path, [note_0, note_1], 0, &mut rng).unwrap();
 
     assert!(r14_circuit::verify_offchain(&vk, &proof, &pi));
 
-    let svk = serialize_vk_for_soroban(&vk);
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
     let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
 
     TestScenario {
@@ -124,6 +125,7 @@ fn register_and_verify() {
             Fr::from_bytes(hex_to_bytes32(&env, &scenario.public_inputs[1])),
             Fr::from_bytes(hex_to_bytes32(&env, &scenario.public_inputs[2])),
             Fr::from_bytes(hex_to_bytes32(&env, &scenario.public_inputs[3])),
+            Fr::from_bytes(hex_to_bytes32(&env, &scenario.public_inputs[4])),
         ],
     );
 
@@ -153,15 +155,94 @@ fn verify_wrong_input() {
             Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32])),
             Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32])),
             Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32])),
+            Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32])),
         ],
     );
 
-    assert!(!client.verify(&circuit_id, &proof, &wrong_inputs));
+    assert_eq!(
+        client.try_verify(&circuit_id, &proof, &wrong_inputs),
+        Err(Ok(CoreError::InvalidProof))
+    );
+}
+
+#[test]
+fn verify_input_length_mismatch() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    let admin = Address::generate(&env);
+
+    let core_id = env.register(R14Core, ());
+    let client = R14CoreClient::new(&env, &core_id);
+    client.init(&admin);
+
+    let vk = build_soroban_vk(&env, &scenario.svk);
+    env.mock_all_auths();
+    let circuit_id = client.register(&admin, &vk);
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let mut inputs: Vec<Fr> = Vec::new(&env);
+    for pi in &scenario.public_inputs[..3] {
+        inputs.push_back(Fr::from_bytes(hex_to_bytes32(&env, pi)));
+    }
+    assert_eq!(
+        client.try_verify(&circuit_id, &proof, &inputs),
+        Err(Ok(CoreError::InputLengthMismatch))
+    );
+
+    // one extra input is rejected the same way
+    for pi in &scenario.public_inputs[3..] {
+        inputs.push_back(Fr::from_bytes(hex_to_bytes32(&env, pi)));
+    }
+    inputs.push_back(Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32])));
+    assert_eq!(
+        client.try_verify(&circuit_id, &proof, &inputs),
+        Err(Ok(CoreError::InputLengthMismatch))
+    );
 }
 
 #[test]
-#[should_panic(expected = "circuit not registered")]
-fn unregistered_circuit_panics() {
+fn verify_batch_all_or_nothing() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    let admin = Address::generate(&env);
+
+    let core_id = env.register(R14Core, ());
+    let client = R14CoreClient::new(&env, &core_id);
+    client.init(&admin);
+
+    let vk = build_soroban_vk(&env, &scenario.svk);
+    env.mock_all_auths();
+    let circuit_id = client.register(&admin, &vk);
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let mut inputs: Vec<Fr> = Vec::new(&env);
+    for pi in &scenario.public_inputs {
+        inputs.push_back(Fr::from_bytes(hex_to_bytes32(&env, pi)));
+    }
+
+    let proofs = Vec::from_array(&env, [proof.clone(), proof.clone()]);
+    let batch = Vec::from_array(&env, [inputs.clone(), inputs.clone()]);
+    assert!(client.verify_batch(&circuit_id, &proofs, &batch));
+
+    // one bad entry fails the whole batch
+    let mut wrong = inputs.clone();
+    wrong.set(0, Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32])));
+    let batch = Vec::from_array(&env, [inputs.clone(), wrong]);
+    assert_eq!(
+        client.try_verify_batch(&circuit_id, &proofs, &batch),
+        Err(Ok(CoreError::InvalidProof))
+    );
+
+    // proofs and input vectors must pair up
+    let batch = Vec::from_array(&env, [inputs]);
+    assert_eq!(
+        client.try_verify_batch(&circuit_id, &proofs, &batch),
+        Err(Ok(CoreError::InputLengthMismatch))
+    );
+}
+
+#[test]
+fn unregistered_circuit_errors() {
     let scenario = setup_and_prove();
     let env = Env::default();
     let admin = Address::generate(&env);
@@ -177,12 +258,18 @@ fn unregistered_circuit_panics() {
         [Fr::from_bytes(BytesN::from_array(&env, &[0u8; 32]))],
     );
 
-    client.verify(&fake_circuit_id, &proof, &inputs);
+    assert_eq!(
+        client.try_verify(&fake_circuit_id, &proof, &inputs),
+        Err(Ok(CoreError::CircuitNotFound))
+    );
+    assert!(matches!(
+        client.try_get_vk(&fake_circuit_id),
+        Err(Ok(CoreError::CircuitNotFound))
+    ));
 }
 
 #[test]
-#[should_panic(expected = "circuit already registered")]
-fn duplicate_register_panics() {
+fn duplicate_register_errors() {
     let scenario = setup_and_prove();
     let env = Env::default();
     let admin = Address::generate(&env);
@@ -193,14 +280,22 @@ fn duplicate_register_panics() {
 
     let vk = build_soroban_vk(&env, &scenario.svk);
     env.mock_all_auths();
-    client.register(&admin, &vk);
-    // Duplicate should panic
-    client.register(&admin, &vk);
+    let circuit_id = client.register(&admin, &vk);
+    assert_eq!(
+        client.try_register(&admin, &vk),
+        Err(Ok(CoreError::AlreadyRegistered))
+    );
+
+    // the same keys at another depth are a different circuit
+    let mut shallow = vk.clone();
+    shallow.depth = 16;
+    let shallow_id = client.register(&admin, &shallow);
+    assert_ne!(shallow_id, circuit_id);
+    assert_eq!(circuit_id, hex_to_bytes32(&env, &circuit_id_hex(&scenario.svk)));
 }
 
 #[test]
-#[should_panic]
-fn non_admin_register_panics() {
+fn non_admin_register_unauthorized() {
     let scenario = setup_and_prove();
     let env = Env::default();
     let admin = Address::generate(&env);
@@ -211,9 +306,31 @@ fn non_admin_register_panics() {
     client.init(&admin);
 
     let vk = build_soroban_vk(&env, &scenario.svk);
-    // Only mock auth for imposter, not admin
     env.mock_all_auths();
-    client.register(&imposter, &vk);
+    assert_eq!(
+        client.try_register(&imposter, &vk),
+        Err(Ok(CoreError::Unauthorized))
+    );
+}
+
+#[test]
+fn init_and_register_state_errors() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    let admin = Address::generate(&env);
+
+    let core_id = env.register(R14Core, ());
+    let client = R14CoreClient::new(&env, &core_id);
+    env.mock_all_auths();
+
+    let vk = build_soroban_vk(&env, &scenario.svk);
+    assert_eq!(
+        client.try_register(&admin, &vk),
+        Err(Ok(CoreError::NotInitialized))
+    );
+
+    client.init(&admin);
+    assert_eq!(client.try_init(&admin), Err(Ok(CoreError::AlreadyInitialized)));
 }
 
 #[test]
@@ -253,4 +370,81 @@ fn get_vk_returns_stored() {
     // Compare alpha_g1 as a spot check
     assert_eq!(stored_vk.alpha_g1.to_bytes(), vk.alpha_g1.to_bytes());
     assert_eq!(stored_vk.ic.len(), vk.ic.len());
+    assert_eq!(stored_vk.depth, MERKLE_DEPTH as u32);
+}
+
+#[test]
+fn admin_handover_rotates_registration_rights() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let new_admin = Address::generate(&env);
+
+    let core_id = env.register(R14Core, ());
+    let client = R14CoreClient::new(&env, &core_id);
+    client.init(&admin);
+
+    env.mock_all_auths();
+    client.transfer_admin(&new_admin);
+    client.accept_admin();
+    assert_eq!(client.admin(), new_admin);
+
+    let vk = build_soroban_vk(&env, &scenario.svk);
+    assert_eq!(
+        client.try_register(&admin, &vk),
+        Err(Ok(CoreError::Unauthorized))
+    );
+    let circuit_id = client.register(&new_admin, &vk);
+    assert!(client.is_registered(&circuit_id));
+}
+
+#[test]
+fn transfer_admin_requires_accept() {
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let multisig = Address::generate(&env);
+
+    let core_id = env.register(R14Core, ());
+    let client = R14CoreClient::new(&env, &core_id);
+    client.init(&admin);
+
+    env.mock_all_auths();
+    client.transfer_admin(&multisig);
+    assert_eq!(client.admin(), admin);
+
+    client.accept_admin();
+    assert_eq!(client.admin(), multisig);
+    assert_eq!(client.try_accept_admin(), Err(Ok(CoreError::NoPendingAdmin)));
+}
+
+#[test]
+fn registrar_role_grants_registration() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    let admin = Address::generate(&env);
+    let registrar = Address::generate(&env);
+
+    let core_id = env.register(R14Core, ());
+    let client = R14CoreClient::new(&env, &core_id);
+    client.init(&admin);
+
+    env.mock_all_auths();
+    let vk = build_soroban_vk(&env, &scenario.svk);
+    assert_eq!(
+        client.try_register(&registrar, &vk),
+        Err(Ok(CoreError::Unauthorized))
+    );
+
+    client.add_registrar(&registrar);
+    assert!(client.is_registrar(&registrar));
+    client.remove_registrar(&registrar);
+    assert!(!client.is_registrar(&registrar));
+    assert_eq!(
+        client.try_register(&registrar, &vk),
+        Err(Ok(CoreError::Unauthorized))
+    );
+
+    client.add_registrar(&registrar);
+    let circuit_id = client.register(&registrar, &vk);
+    assert!(client.is_registered(&circuit_id));
 }
@@ -100,6 +100,9 @@ fn generate_test_vectors() {
 
     println!("=== Test Vectors ===");
     println!("VK_ALPHA_G1: {}", serialize_g1(&vk.alpha_g1));
+    println!("VK_BETA_G2: {}", serialize_g2(&vk.beta_g2));
+    println!("VK_GAMMA_G2: {}", serialize_g2(&vk.gamma_g2));
+    println!("VK_DELTA_G2: {}", serialize_g2(&vk.delta_g2));
     println!("VK_IC length: {}", vk.gamma_abc_g1.len());
     println!("PUBLIC_INPUT: {}", serialize_fr(&y));
 }
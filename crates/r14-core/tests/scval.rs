@@ -0,0 +1,104 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! `r14_sdk::scval` against soroban-sdk's encoding of the contract types
+
+use ark_bls12_381::{Bls12_381, Fr as ArkFr, G1Affine as ArkG1, G2Affine as ArkG2};
+use ark_ec::{AffineRepr, CurveGroup};
+use r14_core::{Proof, VerificationKey};
+use r14_sdk::client::PrebuiltProof;
+use r14_sdk::scval::{self, ScVal};
+use r14_sdk::serialize::{serialize_fr_bytes, serialize_proof_for_soroban, serialize_vk_for_soroban};
+use soroban_sdk::crypto::bls12_381::{Fr, G1Affine, G2Affine};
+use soroban_sdk::{BytesN, Env, TryFromVal, Vec};
+
+fn g1(k: u64) -> ArkG1 {
+    (ArkG1::generator() * ArkFr::from(k)).into_affine()
+}
+
+fn g2(k: u64) -> ArkG2 {
+    (ArkG2::generator() * ArkFr::from(k)).into_affine()
+}
+
+fn bytes<const N: usize>(env: &Env, h: &str) -> BytesN<N> {
+    BytesN::from_array(env, &hex::decode(h).unwrap().try_into().unwrap())
+}
+
+#[test]
+fn proof_and_inputs_match_contract_types() {
+    let env = Env::default();
+    let ark_proof = ark_groth16::Proof::<Bls12_381> { a: g1(2), b: g2(3), c: g1(4) };
+    let inputs: std::vec::Vec<ArkFr> = (1..=5u64).map(|v| ArkFr::from(v) - ArkFr::from(7u64)).collect();
+    let (sp, spi) = serialize_proof_for_soroban(&ark_proof, &inputs);
+
+    let expected = Proof {
+        a: G1Affine::from_bytes(bytes(&env, &sp.a)),
+        b: G2Affine::from_bytes(bytes(&env, &sp.b)),
+        c: G1Affine::from_bytes(bytes(&env, &sp.c)),
+    };
+    let ours = scval::proof(&sp).unwrap();
+    assert_eq!(ours, ScVal::try_from(&expected).unwrap());
+    assert!(Proof::try_from_val(&env, &ours).is_ok());
+
+    let mut frs = Vec::new(&env);
+    for v in &inputs {
+        frs.push_back(Fr::from_bytes(BytesN::from_array(&env, &serialize_fr_bytes(v))));
+    }
+    let ScVal::Vec(Some(ours)) = scval::hex_public_inputs(&spi).unwrap() else { panic!("not a vec") };
+    assert_eq!(ours.len(), 5);
+    for (i, fr) in frs.iter().enumerate() {
+        assert_eq!(ours[i], ScVal::from(&fr), "input {i}");
+    }
+}
+
+#[test]
+fn vk_matches_contract_type() {
+    let env = Env::default();
+    let ark_vk = ark_groth16::VerifyingKey::<Bls12_381> {
+        alpha_g1: g1(5),
+        beta_g2: g2(6),
+        gamma_g2: g2(7),
+        delta_g2: g2(8),
+        gamma_abc_g1: (9..15).map(g1).collect(),
+    };
+    let svk = serialize_vk_for_soroban(&ark_vk, r14_types::MERKLE_DEPTH);
+
+    let mut ic = Vec::new(&env);
+    for p in &svk.ic {
+        ic.push_back(G1Affine::from_bytes(bytes(&env, p)));
+    }
+    let expected = VerificationKey {
+        alpha_g1: G1Affine::from_bytes(bytes(&env, &svk.alpha_g1)),
+        beta_g2: G2Affine::from_bytes(bytes(&env, &svk.beta_g2)),
+        gamma_g2: G2Affine::from_bytes(bytes(&env, &svk.gamma_g2)),
+        delta_g2: G2Affine::from_bytes(bytes(&env, &svk.delta_g2)),
+        ic,
+        depth: svk.depth,
+    };
+    let ours = scval::vk(&svk).unwrap();
+    assert_eq!(ours, ScVal::try_from(&expected).unwrap());
+    assert!(VerificationKey::try_from_val(&env, &ours).is_ok());
+}
+
+#[test]
+fn transfer_args_match_contract_signature() {
+    let env = Env::default();
+    let (sp, _) = serialize_proof_for_soroban(&ark_groth16::Proof::<Bls12_381> { a: g1(2), b: g2(3), c: g1(4) }, &[]);
+    let h = |b: u8| hex::encode([b; 32]);
+    let prebuilt = PrebuiltProof {
+        proof_json: format!(r#"{{"a":"{}","b":"{}","c":"{}"}}"#, sp.a, sp.b, sp.c),
+        old_root: h(1),
+        nullifier: h(2),
+        cm_0: h(3),
+        cm_1: h(4),
+        current_ledger: 77,
+    };
+    let args = scval::transfer_args(&prebuilt, &format!("0x{}", h(5))).unwrap();
+    assert_eq!(args.len(), 7);
+    assert!(Proof::try_from_val(&env, &args[0]).is_ok());
+    for (i, b) in (1..=4u8).enumerate() {
+        assert_eq!(args[i + 1], ScVal::from(&bytes::<32>(&env, &h(b))));
+    }
+    assert_eq!(args[5], ScVal::U64(77));
+    assert_eq!(args[6], ScVal::from(&bytes::<32>(&env, &h(5))));
+}
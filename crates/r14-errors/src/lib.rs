@@ -0,0 +1,501 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Shared error types for Root14 crates.
+//!
+//! Each domain has its own enum ([`R14Error`] for client/SDK operations,
+//! [`IndexerError`] for the indexer service, [`ProverError`] for the prover
+//! daemon, [`CircuitError`] for setup and proving). Every variant maps to a stable [`ErrorCode`], which is what
+//! crosses process boundaries: the services return it in JSON error bodies
+//! ([`ErrorBody`]), and the CLI turns it into an exit code.
+//!
+//! Within a code, each variant also has a stable string id such as
+//! `R14_INDEXER_UNREACHABLE` (`id()`), and some carry structured context
+//! (`context()`), e.g. the amounts of an insufficient balance. Both go
+//! into the [`ErrorBody`], so scripts can branch on them without parsing
+//! messages. Ids and context keys are only ever added, never renamed.
+
+use serde::{Deserialize, Serialize};
+
+/// Stable, machine-readable error category
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InsufficientBalance,
+    NoteNotOnChain,
+    Indexer,
+    Soroban,
+    Config,
+    SessionPolicy,
+    /// The wallet's spending policy forbids the transfer
+    PolicyViolation,
+    StaleRoot,
+    InvalidInput,
+    NotFound,
+    Storage,
+    Unavailable,
+    /// The caller cancelled the operation, e.g. a proof in progress
+    Cancelled,
+    Internal,
+}
+
+impl ErrorCode {
+    /// HTTP status used when this error is returned by a service
+    pub fn http_status(self) -> u16 {
+        match self {
+            Self::InvalidInput | Self::SessionPolicy => 400,
+            Self::PolicyViolation => 403,
+            Self::NotFound | Self::NoteNotOnChain => 404,
+            Self::InsufficientBalance => 422,
+            Self::StaleRoot => 409,
+            Self::Indexer | Self::Soroban => 502,
+            Self::Config | Self::Storage | Self::Internal => 500,
+            Self::Unavailable => 503,
+            // nginx's "client closed request"
+            Self::Cancelled => 499,
+        }
+    }
+
+    /// Process exit code used by the CLI (1 is reserved for unclassified errors)
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Internal => 1,
+            Self::InvalidInput => 2,
+            Self::Config => 3,
+            Self::InsufficientBalance => 4,
+            Self::NoteNotOnChain => 5,
+            Self::SessionPolicy => 6,
+            Self::NotFound => 7,
+            Self::StaleRoot => 8,
+            Self::PolicyViolation => 9,
+            Self::Indexer => 10,
+            Self::Soroban => 11,
+            Self::Storage => 12,
+            Self::Unavailable => 13,
+            // as for SIGINT
+            Self::Cancelled => 130,
+        }
+    }
+}
+
+/// Serializable error representation:
+/// `{ "error": "...", "code": "...", "id": "R14_...", "context": {...} }`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: String,
+    pub code: ErrorCode,
+    /// Stable id of the error variant; `None` from services that predate it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Structured details, for errors that have any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+}
+
+impl ErrorBody {
+    pub fn new(code: ErrorCode, error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            code,
+            id: None,
+            context: None,
+        }
+    }
+
+    pub fn with_id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn with_context(mut self, context: Option<serde_json::Value>) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+/// Errors from client-side operations (wallet, indexer calls, submission)
+#[derive(Debug, thiserror::Error)]
+pub enum R14Error {
+    #[error("insufficient balance: need {needed}, best {best}")]
+    InsufficientBalance { needed: u64, best: u64 },
+
+    #[error("note not on-chain — deposit or sync first")]
+    NoteNotOnChain,
+
+    /// The indexer couldn't be reached or didn't answer in time
+    #[error("indexer unreachable: {0}")]
+    IndexerUnreachable(String),
+
+    /// The indexer answered with an error or a response that isn't valid
+    #[error("indexer: {0}")]
+    Indexer(String),
+
+    #[error("soroban: {0}")]
+    Soroban(String),
+
+    #[error("config: {0}")]
+    Config(String),
+
+    #[error("session policy: {0}")]
+    SessionPolicy(String),
+
+    /// Refused by the wallet's spending policy before proving
+    #[error("spending policy: {0}")]
+    PolicyViolation(String),
+
+    #[error("stale root: {0}")]
+    StaleRoot(String),
+
+    /// The local transfer VK isn't the one the transfer contract verifies
+    /// against, so any proof would be rejected
+    #[error("circuit mismatch: this build proves {local}, the transfer contract verifies {deployed}")]
+    CircuitMismatch { local: String, deployed: String },
+
+    /// Remote proving failed; `code` is the daemon's own, or
+    /// `Unavailable` when it couldn't be reached
+    #[error("prover: {message}")]
+    Prover { code: ErrorCode, message: String },
+
+    /// Relayed submission failed; `code` is the relayer's own, or
+    /// `Unavailable` when it couldn't be reached
+    #[error("relayer: {message}")]
+    Relayer { code: ErrorCode, message: String },
+
+    #[error(transparent)]
+    Circuit(#[from] CircuitError),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl R14Error {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InsufficientBalance { .. } => ErrorCode::InsufficientBalance,
+            Self::NoteNotOnChain => ErrorCode::NoteNotOnChain,
+            Self::IndexerUnreachable(_) | Self::Indexer(_) => ErrorCode::Indexer,
+            Self::Soroban(_) => ErrorCode::Soroban,
+            Self::Config(_) => ErrorCode::Config,
+            Self::SessionPolicy(_) => ErrorCode::SessionPolicy,
+            Self::PolicyViolation(_) => ErrorCode::PolicyViolation,
+            Self::StaleRoot(_) => ErrorCode::StaleRoot,
+            Self::CircuitMismatch { .. } => ErrorCode::Config,
+            Self::Prover { code, .. } | Self::Relayer { code, .. } => *code,
+            Self::Circuit(e) => e.code(),
+            Self::Other(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// Stable id of this variant
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::InsufficientBalance { .. } => "R14_INSUFFICIENT_BALANCE",
+            Self::NoteNotOnChain => "R14_NOTE_NOT_ON_CHAIN",
+            Self::IndexerUnreachable(_) => "R14_INDEXER_UNREACHABLE",
+            Self::Indexer(_) => "R14_INDEXER_ERROR",
+            Self::Soroban(_) => "R14_SOROBAN_ERROR",
+            Self::Config(_) => "R14_CONFIG",
+            Self::SessionPolicy(_) => "R14_SESSION_POLICY",
+            Self::PolicyViolation(_) => "R14_POLICY_VIOLATION",
+            Self::StaleRoot(_) => "R14_STALE_ROOT",
+            Self::CircuitMismatch { .. } => "R14_CIRCUIT_MISMATCH",
+            Self::Prover { code: ErrorCode::Unavailable, .. } => "R14_PROVER_UNAVAILABLE",
+            Self::Prover { .. } => "R14_PROVER_ERROR",
+            Self::Relayer { code: ErrorCode::Unavailable, .. } => "R14_RELAYER_UNAVAILABLE",
+            Self::Relayer { .. } => "R14_RELAYER_REJECTED",
+            Self::Circuit(e) => e.id(),
+            Self::Other(_) => "R14_INTERNAL",
+        }
+    }
+
+    /// Structured details, for variants that have any
+    pub fn context(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::InsufficientBalance { needed, best } => Some(serde_json::json!({ "needed": needed, "best": best })),
+            Self::CircuitMismatch { local, deployed } => Some(serde_json::json!({ "local": local, "deployed": deployed })),
+            Self::Prover { code, .. } | Self::Relayer { code, .. } => Some(serde_json::json!({ "remote_code": code })),
+            _ => None,
+        }
+    }
+
+    pub fn to_body(&self) -> ErrorBody {
+        ErrorBody::new(self.code(), self.to_string())
+            .with_id(self.id())
+            .with_context(self.context())
+    }
+}
+
+/// Indexer errors reported over HTTP. Downstream, the SDK sees
+/// these as [`R14Error::Indexer`].
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error("storage: {0}")]
+    Storage(String),
+}
+
+impl IndexerError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidInput(_) => ErrorCode::InvalidInput,
+            Self::NotFound(_) => ErrorCode::NotFound,
+            Self::Storage(_) => ErrorCode::Storage,
+        }
+    }
+
+    /// Stable id of this variant
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "R14_INVALID_INPUT",
+            Self::NotFound(_) => "R14_NOT_FOUND",
+            Self::Storage(_) => "R14_STORAGE",
+        }
+    }
+
+    pub fn to_body(&self) -> ErrorBody {
+        ErrorBody::new(self.code(), self.to_string()).with_id(self.id())
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl From<rusqlite::Error> for IndexerError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<postgres::Error> for IndexerError {
+    fn from(e: postgres::Error) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
+/// Prover daemon errors reported over HTTP. Downstream, the SDK sees
+/// these as [`R14Error::Prover`], keeping the code.
+#[derive(Debug, thiserror::Error)]
+pub enum ProverError {
+    #[error("{0}")]
+    InvalidInput(String),
+
+    #[error("prover busy: {queued} requests queued")]
+    Busy { queued: usize },
+
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ProverError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidInput(_) => ErrorCode::InvalidInput,
+            Self::Busy { .. } => ErrorCode::Unavailable,
+            Self::Internal(_) => ErrorCode::Internal,
+        }
+    }
+
+    /// Stable id of this variant
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "R14_INVALID_INPUT",
+            Self::Busy { .. } => "R14_PROVER_BUSY",
+            Self::Internal(_) => "R14_INTERNAL",
+        }
+    }
+
+    pub fn context(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::Busy { queued } => Some(serde_json::json!({ "queued": queued })),
+            _ => None,
+        }
+    }
+
+    pub fn to_body(&self) -> ErrorBody {
+        ErrorBody::new(self.code(), self.to_string())
+            .with_id(self.id())
+            .with_context(self.context())
+    }
+}
+
+/// Circuit setup and proving errors (`r14-circuit`)
+#[derive(Debug, thiserror::Error)]
+pub enum CircuitError {
+    /// The witness can't be proven: wrong path length, values that don't
+    /// balance, mismatched app tags
+    #[error("invalid witness: {0}")]
+    InvalidWitness(String),
+
+    /// Constraint synthesis or key generation failed
+    #[error("synthesis: {0}")]
+    Synthesis(String),
+
+    /// Proving was cancelled through a `CancelToken`
+    #[error("proving cancelled")]
+    Cancelled,
+}
+
+impl CircuitError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::InvalidWitness(_) => ErrorCode::InvalidInput,
+            Self::Synthesis(_) => ErrorCode::Internal,
+            Self::Cancelled => ErrorCode::Cancelled,
+        }
+    }
+
+    /// Stable id of this variant
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::InvalidWitness(_) => "R14_INVALID_WITNESS",
+            Self::Synthesis(_) => "R14_SYNTHESIS_FAILED",
+            Self::Cancelled => "R14_CANCELLED",
+        }
+    }
+
+    pub fn to_body(&self) -> ErrorBody {
+        ErrorBody::new(self.code(), self.to_string()).with_id(self.id())
+    }
+}
+
+impl From<CircuitError> for ProverError {
+    fn from(e: CircuitError) -> Self {
+        match e {
+            CircuitError::InvalidWitness(_) => Self::InvalidInput(e.to_string()),
+            CircuitError::Synthesis(_) | CircuitError::Cancelled => Self::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<IndexerError> for R14Error {
+    fn from(e: IndexerError) -> Self {
+        Self::Indexer(e.to_string())
+    }
+}
+
+/// Classify an `anyhow` chain by the first typed error it contains
+pub fn classify(err: &anyhow::Error) -> ErrorCode {
+    describe(err).code
+}
+
+/// [`ErrorBody`] for an `anyhow` chain: the whole chain as the message,
+/// with the code, id and context of the first typed error in it
+/// (`internal` / `R14_INTERNAL` if there is none)
+pub fn describe(err: &anyhow::Error) -> ErrorBody {
+    let typed = err.chain().find_map(|cause| {
+        if let Some(e) = cause.downcast_ref::<R14Error>() {
+            return Some(e.to_body());
+        }
+        if let Some(e) = cause.downcast_ref::<IndexerError>() {
+            return Some(e.to_body());
+        }
+        if let Some(e) = cause.downcast_ref::<ProverError>() {
+            return Some(e.to_body());
+        }
+        cause.downcast_ref::<CircuitError>().map(CircuitError::to_body)
+    });
+    let body = typed.unwrap_or_else(|| ErrorBody::new(ErrorCode::Internal, "").with_id("R14_INTERNAL"));
+    ErrorBody { error: format!("{err:#}"), ..body }
+}
+
+pub type R14Result<T> = Result<T, R14Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn body_serializes_snake_case_code() {
+        let err = R14Error::InsufficientBalance { needed: 10, best: 3 };
+        let json = serde_json::to_value(err.to_body()).unwrap();
+        assert_eq!(json["code"], "insufficient_balance");
+        assert_eq!(json["error"], "insufficient balance: need 10, best 3");
+    }
+
+    #[test]
+    fn classify_finds_typed_error_in_chain() {
+        let err = anyhow::Error::new(R14Error::Config("missing".into())).context("loading");
+        assert_eq!(classify(&err), ErrorCode::Config);
+        assert_eq!(classify(&anyhow::anyhow!("boom")), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn indexer_error_maps_to_sdk_indexer() {
+        let err: R14Error = IndexerError::NotFound("commitment not found".into()).into();
+        assert_eq!(err.code(), ErrorCode::Indexer);
+    }
+
+    #[test]
+    fn prover_errors_keep_the_daemon_code() {
+        let err = R14Error::Prover { code: ErrorCode::Unavailable, message: "prover busy".into() };
+        assert_eq!(err.code().http_status(), 503);
+        assert_eq!(err.to_string(), "prover: prover busy");
+    }
+
+    #[test]
+    fn relayer_errors_keep_the_relayer_code() {
+        let err = R14Error::Relayer { code: ErrorCode::StaleRoot, message: "unknown merkle root".into() };
+        assert_eq!(err.code().exit_code(), 8);
+        assert_eq!(err.to_string(), "relayer: unknown merkle root");
+    }
+
+    #[test]
+    fn stale_root_is_a_conflict() {
+        let err = R14Error::StaleRoot("root 0xab is not in the contract's root history".into());
+        assert_eq!(err.code().http_status(), 409);
+        assert_eq!(err.code().exit_code(), 8);
+        assert_eq!(err.to_body().code, ErrorCode::StaleRoot);
+    }
+
+    #[test]
+    fn circuit_errors_keep_their_code() {
+        let err: R14Error = CircuitError::InvalidWitness("merkle path too short".into()).into();
+        assert_eq!(err.code(), ErrorCode::InvalidInput);
+        assert_eq!(err.to_string(), "invalid witness: merkle path too short");
+
+        let err: ProverError = CircuitError::Synthesis("unsatisfiable".into()).into();
+        assert_eq!(err.code(), ErrorCode::Internal);
+        assert_eq!(classify(&anyhow::Error::new(CircuitError::Synthesis("x".into()))), ErrorCode::Internal);
+    }
+
+    #[test]
+    fn bodies_carry_stable_ids_and_context() {
+        let json = serde_json::to_value(R14Error::InsufficientBalance { needed: 10, best: 3 }.to_body()).unwrap();
+        assert_eq!(json["id"], "R14_INSUFFICIENT_BALANCE");
+        assert_eq!(json["context"], serde_json::json!({ "needed": 10, "best": 3 }));
+
+        let err = R14Error::IndexerUnreachable("/v1/root: connection refused".into());
+        assert_eq!((err.code(), err.id()), (ErrorCode::Indexer, "R14_INDEXER_UNREACHABLE"));
+        assert!(err.to_body().context.is_none());
+
+        let err = anyhow::Error::new(R14Error::Relayer { code: ErrorCode::StaleRoot, message: "old".into() })
+            .context("submitting");
+        let body = describe(&err);
+        assert_eq!(body.error, "submitting: relayer: old");
+        assert_eq!((body.code, body.id.as_deref()), (ErrorCode::StaleRoot, Some("R14_RELAYER_REJECTED")));
+        assert_eq!(body.context.unwrap()["remote_code"], "stale_root");
+        assert_eq!(describe(&anyhow::anyhow!("boom")).id.as_deref(), Some("R14_INTERNAL"));
+
+        // bodies from services without ids still parse
+        let old: ErrorBody = serde_json::from_str(r#"{"error":"x","code":"not_found"}"#).unwrap();
+        assert_eq!(old, ErrorBody::new(ErrorCode::NotFound, "x"));
+    }
+
+    #[test]
+    fn policy_violations_are_forbidden() {
+        let err = R14Error::PolicyViolation("daily limit 1000 reached".into());
+        assert_eq!((err.code().http_status(), err.code().exit_code()), (403, 9));
+        assert_eq!(err.to_body().code, ErrorCode::PolicyViolation);
+    }
+
+    #[test]
+    fn prover_busy_is_unavailable() {
+        let err = ProverError::Busy { queued: 8 };
+        assert_eq!(err.code().http_status(), 503);
+        assert_eq!(err.to_body().error, "prover busy: 8 requests queued");
+    }
+}
@@ -0,0 +1,47 @@
+/* Copyright 2026 abhirupbanerjee
+ * Licensed under the Apache License, Version 2.0
+ *
+ * C ABI for r14-ffi. Arguments and results are UTF-8, NUL-terminated
+ * strings. Every result is a JSON envelope, {"ok": ...} or
+ * {"error": "...", "code": "..."}, owned by the caller: release it with
+ * r14_string_free.
+ */
+
+#ifndef R14_H
+#define R14_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Hash family of the deployment: "poseidon", "poseidon2" or "circom".
+ * Call before anything else hashes. {"ok": "<family>"} */
+char *r14_set_hash_family(const char *family);
+
+/* {"ok": {"secret_key": "0x...", "owner_hash": "0x...", "address": "r14o1..."}} */
+char *r14_keygen(void);
+
+/* New note for owner_hash (hex or r14o1... address), as a wallet entry with its commitment */
+char *r14_note_new(uint64_t value, uint32_t app_tag, const char *owner_hash);
+
+/* Commitment of {"value", "app_tag", "owner", "nonce", "unlock_after"?} */
+char *r14_commitment(const char *note_json);
+
+/* config_json: {"indexer_url", "transfer_contract", "relayer_url"?}
+ * notes_json:  array of wallet entries
+ * {"ok": {"total": n, "notes": [...]}} with leaf indices synced */
+char *r14_balance(const char *config_json, const char *notes_json);
+
+/* proof_json: an r14-prover /v1/prove/transfer response, posted to the
+ * config's relayer_url, which signs and pays. {"ok": "<tx result>"} */
+char *r14_submit_transfer(const char *config_json, const char *proof_json);
+
+void r14_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* R14_H */
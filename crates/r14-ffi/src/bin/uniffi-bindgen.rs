@@ -0,0 +1,8 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Writes the Kotlin and Swift bindings; see the README
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
@@ -0,0 +1,425 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Mobile bindings over `r14-sdk`: a C ABI, and the same calls exported
+//! through UniFFI for Kotlin and Swift.
+//!
+//! The surface is deliberately narrow: keygen, note creation, commitments,
+//! balance sync, and submitting a proof through a relayer. Proving itself
+//! goes to an `r14-prover` daemon, whose response [`r14_submit_transfer`]
+//! accepts as-is, and submission goes to an r14 relayer, which signs and
+//! pays. Nothing here shells out or holds a Stellar key, so all of it runs
+//! on a phone.
+//!
+//! Every call takes and returns UTF-8 strings. Structured arguments are
+//! JSON, and every result is a JSON envelope, either `{"ok": ...}` or the
+//! shared error body `{"error": "...", "code": "..."}`. C results are owned
+//! by the caller and must be released with [`r14_string_free`]; the UniFFI
+//! functions in [`mobile`] return plain strings. Panics are caught and
+//! reported as `internal` errors rather than unwinding into the host.
+//!
+//! A deployment on a hash family other than the default must call
+//! [`r14_set_hash_family`] before anything else; every later call hashes
+//! with it.
+
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use ark_bls12_381::Fr;
+use serde::{Deserialize, Serialize};
+
+use r14_sdk::blocking::R14BlockingClient;
+use r14_sdk::client::DEFAULT_RPC_TIMEOUT;
+use r14_sdk::error::{ErrorCode, R14Error, R14Result};
+use r14_sdk::indexer::IndexerClient;
+use r14_sdk::merkle::compute_new_root;
+use r14_sdk::prover::{NoteJson, ProvedTransfer};
+use r14_sdk::relayer::{RelayRequest, RelayerClient};
+use r14_sdk::wallet::{fr_to_hex, hex_to_fr, os_rng, owner_address, parse_owner, NoteEntry};
+use r14_sdk::{commitment, owner_hash, Commitment, HashFamily, Note, R14Client, R14Contracts, SecretKey};
+
+pub mod mobile;
+
+uniffi::setup_scaffolding!("r14");
+
+/// Connection settings for [`r14_balance`] and [`r14_submit_transfer`]
+#[derive(Deserialize)]
+struct Config {
+    indexer_url: String,
+    transfer_contract: String,
+    /// Relayer that signs and pays for submissions; balance sync never
+    /// uses it
+    relayer_url: Option<String>,
+}
+
+impl Config {
+    /// Client that only reads the indexer; it holds no Stellar key, so it
+    /// can't submit
+    fn client(&self) -> R14Result<R14BlockingClient> {
+        let contracts = R14Contracts {
+            core: String::new(),
+            transfer: self.transfer_contract.clone(),
+        };
+        R14BlockingClient::from_client(R14Client::builder(&self.indexer_url, contracts, "").build()?)
+    }
+
+    fn relayer(&self) -> R14Result<RelayerClient> {
+        match &self.relayer_url {
+            Some(url) => Ok(RelayerClient::new(url)),
+            None => Err(R14Error::Config("relayer_url is required to submit".into())),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Keypair {
+    secret_key: String,
+    owner_hash: String,
+    /// `r14o1...` form of `owner_hash`, for sharing
+    address: String,
+}
+
+#[derive(Serialize)]
+struct Balance {
+    total: u64,
+    /// The input notes with leaf indices filled in; persist these
+    notes: Vec<NoteEntry>,
+}
+
+/// Run `f`, turning its result (or a panic) into a JSON envelope
+fn envelope<T: Serialize>(f: impl FnOnce() -> R14Result<T>) -> String {
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(R14Error::Other(anyhow::anyhow!("panic in r14-ffi"))));
+    let json = match result.and_then(|v| serde_json::to_value(v).map_err(|e| R14Error::Other(e.into()))) {
+        Ok(v) => serde_json::json!({ "ok": v }),
+        Err(e) => serde_json::to_value(e.to_body()).expect("error body serializes"),
+    };
+    json.to_string()
+}
+
+/// [`envelope`] as an owned C string
+fn respond<T: Serialize>(f: impl FnOnce() -> R14Result<T>) -> *mut c_char {
+    // serde_json escapes NUL, so the text never contains one
+    CString::new(envelope(f)).expect("JSON has no NUL").into_raw()
+}
+
+/// Borrow a C string argument
+///
+/// # Safety
+/// `ptr` is null or a NUL-terminated string that outlives the call.
+unsafe fn arg<'a>(name: &str, ptr: *const c_char) -> R14Result<&'a str> {
+    if ptr.is_null() {
+        return Err(R14Error::Other(anyhow::anyhow!("{name} is null")));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| R14Error::Other(anyhow::anyhow!("{name} is not UTF-8")))
+}
+
+/// Parse a JSON argument
+fn json_arg<T: serde::de::DeserializeOwned>(name: &str, text: &str) -> R14Result<T> {
+    serde_json::from_str(text).map_err(|e| R14Error::Other(anyhow::anyhow!("{name}: {e}")))
+}
+
+fn fr_arg(name: &str, hex: &str) -> R14Result<Fr> {
+    hex_to_fr(hex).map_err(|e| R14Error::Other(e.context(format!("{name}: bad field element"))))
+}
+
+// The calls themselves, shared by the C ABI below and by `mobile`
+
+fn set_hash_family(family: &str) -> R14Result<&'static str> {
+    let family: HashFamily = family.parse().map_err(R14Error::Config)?;
+    r14_sdk::set_hash_family(family).map_err(|current| {
+        R14Error::Config(format!("already hashing with {}, can't switch to {}", current.name(), family.name()))
+    })?;
+    Ok(family.name())
+}
+
+fn keygen() -> R14Result<Keypair> {
+    let sk = SecretKey::random(&mut os_rng());
+    let owner = owner_hash(&sk).0;
+    Ok(Keypair {
+        secret_key: fr_to_hex(&sk.0),
+        owner_hash: fr_to_hex(&owner),
+        address: owner_address(&owner),
+    })
+}
+
+fn note_new(value: u64, app_tag: u32, owner_hash: &str) -> R14Result<NoteEntry> {
+    let owner = parse_owner(owner_hash).map_err(R14Error::Other)?;
+    let note = Note::new(value, app_tag, owner, &mut os_rng());
+    Ok(NoteEntry::new(&note, &commitment(&note)))
+}
+
+fn note_commitment(note_json: &str) -> R14Result<Commitment> {
+    let n: NoteJson = json_arg("note_json", note_json)?;
+    let note = Note::with_nonce(n.value, n.app_tag, fr_arg("owner", &n.owner)?, fr_arg("nonce", &n.nonce)?)
+        .locked_until(n.unlock_after);
+    Ok(commitment(&note))
+}
+
+fn balance(config_json: &str, notes_json: &str) -> R14Result<Balance> {
+    let config: Config = json_arg("config_json", config_json)?;
+    let mut notes: Vec<NoteEntry> = json_arg("notes_json", notes_json)?;
+    let total = config.client()?.balance(&mut notes)?.total;
+    Ok(Balance { total, notes })
+}
+
+fn submit_transfer(config_json: &str, proof_json: &str) -> R14Result<String> {
+    let config: Config = json_arg("config_json", config_json)?;
+    let relayer = config.relayer()?;
+    let proved: ProvedTransfer = json_arg("proof_json", proof_json)?;
+    let outputs = [
+        Commitment(fr_arg("cm_0", &proved.public_inputs.cm_0)?),
+        Commitment(fr_arg("cm_1", &proved.public_inputs.cm_1)?),
+    ];
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| R14Error::Other(anyhow::anyhow!("failed to start tokio runtime: {e}")))?;
+    // the relayer's contract call checks the old root, so a stale one
+    // comes back as its error
+    rt.block_on(async {
+        let new_root = compute_new_root(&IndexerClient::new(&config.indexer_url), &outputs).await?;
+        let request = RelayRequest::new(&config.transfer_contract, proved, &new_root);
+        match tokio::time::timeout(DEFAULT_RPC_TIMEOUT, relayer.submit_transfer(&request)).await {
+            Ok(receipt) => Ok(receipt?.tx_result),
+            Err(_) => Err(R14Error::Relayer {
+                code: ErrorCode::Unavailable,
+                message: format!("timed out after {DEFAULT_RPC_TIMEOUT:?}"),
+            }),
+        }
+    })
+}
+
+/// Hash with `family` (`poseidon`, `poseidon2` or `circom`), the
+/// deployment's `hash` from `r14.toml`: `{"ok": "poseidon2"}`. Call it
+/// before anything hashes; once a call has hashed, only the family in use
+/// is accepted.
+///
+/// # Safety
+/// `family` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn r14_set_hash_family(family: *const c_char) -> *mut c_char {
+    respond(|| set_hash_family(arg("family", family)?))
+}
+
+/// New secret key and its owner hash:
+/// `{"ok": {"secret_key": "0x…", "owner_hash": "0x…", "address": "r14o1…"}}`
+#[no_mangle]
+pub extern "C" fn r14_keygen() -> *mut c_char {
+    respond(keygen)
+}
+
+/// New note for `owner_hash` (hex or an `r14o1…` address) with a fresh
+/// nonce, as a wallet entry (including its commitment), ready to deposit
+///
+/// # Safety
+/// `owner_hash` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn r14_note_new(value: u64, app_tag: u32, owner_hash: *const c_char) -> *mut c_char {
+    respond(|| note_new(value, app_tag, arg("owner_hash", owner_hash)?))
+}
+
+/// Commitment of a note given as `{"value", "app_tag", "owner", "nonce"}`
+/// (and optionally `"unlock_after"`); a wallet entry works too
+///
+/// # Safety
+/// `note_json` is null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn r14_commitment(note_json: *const c_char) -> *mut c_char {
+    respond(|| note_commitment(arg("note_json", note_json)?))
+}
+
+/// Sync wallet entries against the indexer:
+/// `{"ok": {"total": …, "notes": [...]}}`
+///
+/// # Safety
+/// Both arguments are null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn r14_balance(config_json: *const c_char, notes_json: *const c_char) -> *mut c_char {
+    respond(|| balance(arg("config_json", config_json)?, arg("notes_json", notes_json)?))
+}
+
+/// Post a transfer proof, in the `r14-prover` response format, to the
+/// configured relayer, which signs and pays for it. Returns the
+/// transaction result.
+///
+/// # Safety
+/// Both arguments are null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn r14_submit_transfer(config_json: *const c_char, proof_json: *const c_char) -> *mut c_char {
+    respond(|| submit_transfer(arg("config_json", config_json)?, arg("proof_json", proof_json)?))
+}
+
+/// Release a string returned by any `r14_*` function
+///
+/// # Safety
+/// `s` is null or came from this library and hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn r14_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(ptr: *mut c_char) -> serde_json::Value {
+        let text = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string();
+        unsafe { r14_string_free(ptr) };
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn note_commitment_matches_entry() {
+        let keys = take(r14_keygen());
+        let owner = CString::new(keys["ok"]["owner_hash"].as_str().unwrap()).unwrap();
+        let entry = take(unsafe { r14_note_new(250, 1, owner.as_ptr()) });
+        assert_eq!(entry["ok"]["value"], 250);
+
+        let address = CString::new(keys["ok"]["address"].as_str().unwrap()).unwrap();
+        let same = take(unsafe { r14_note_new(250, 1, address.as_ptr()) });
+        assert_eq!(same["ok"]["owner"], entry["ok"]["owner"]);
+
+        let note = CString::new(entry["ok"].to_string()).unwrap();
+        let cm = take(unsafe { r14_commitment(note.as_ptr()) });
+        assert_eq!(cm["ok"], entry["ok"]["commitment"]);
+    }
+
+    #[test]
+    fn hash_family_is_fixed_once_set() {
+        let family = CString::new(r14_sdk::hash_family().name()).unwrap();
+        let ok = take(unsafe { r14_set_hash_family(family.as_ptr()) });
+        assert_eq!(ok["ok"], r14_sdk::hash_family().name());
+
+        let other = if r14_sdk::hash_family() == HashFamily::Circom { "poseidon" } else { "circom" };
+        let other = CString::new(other).unwrap();
+        let err = take(unsafe { r14_set_hash_family(other.as_ptr()) });
+        assert_eq!(err["code"], "config");
+        let unknown = CString::new("sha256").unwrap();
+        let err = take(unsafe { r14_set_hash_family(unknown.as_ptr()) });
+        assert!(err["error"].as_str().unwrap().contains("unknown hash family"));
+    }
+
+    #[test]
+    fn bad_arguments_are_error_envelopes() {
+        let null = take(unsafe { r14_note_new(1, 1, std::ptr::null()) });
+        assert_eq!(null["error"], "owner_hash is null");
+        assert_eq!(null["code"], "internal");
+
+        let bad = CString::new("{").unwrap();
+        let err = take(unsafe { r14_commitment(bad.as_ptr()) });
+        assert!(err["error"].as_str().unwrap().starts_with("note_json:"));
+    }
+
+    /// Relayer that answers one request with `{"tx_result":"true"}` and
+    /// returns the body it was sent
+    fn relayer_stub() -> (String, std::thread::JoinHandle<serde_json::Value>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut sock, _) = listener.accept().unwrap();
+            let mut req = Vec::new();
+            let mut buf = [0u8; 4096];
+            let body = loop {
+                let n = sock.read(&mut buf).unwrap();
+                req.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&req).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let len: usize = head
+                        .lines()
+                        .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                        .unwrap();
+                    if body.len() >= len {
+                        break body.to_string();
+                    }
+                }
+            };
+            let resp = r#"{"tx_result":"true"}"#;
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n", resp.len());
+            sock.write_all(format!("{head}{resp}").as_bytes()).unwrap();
+            serde_json::from_str(&body).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn transfers_are_submitted_through_the_relayer() {
+        use r14_sdk::testing::{MockNetwork, MOCK_TRANSFER_CONTRACT};
+        use r14_sdk::wallet::fr_to_raw_hex;
+
+        // the mock indexer keeps serving from this runtime's workers
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let network = rt.block_on(MockNetwork::start()).unwrap();
+        network.insert_leaf(Commitment(Fr::from(5u64)));
+        let (cm_0, cm_1) = (Fr::from(8u64), Fr::from(9u64));
+        let proof = serde_json::json!({
+            "proof": {"a": "aa", "b": "bb", "c": "cc"},
+            "public_inputs": {
+                "old_root": network.root(),
+                "nullifier": fr_to_raw_hex(&Fr::from(1u64)),
+                "cm_0": fr_to_raw_hex(&cm_0),
+                "cm_1": fr_to_raw_hex(&cm_1),
+                "current_ledger": network.ledger(),
+            },
+        });
+        let proof = CString::new(proof.to_string()).unwrap();
+
+        let (url, relayer) = relayer_stub();
+        let config = serde_json::json!({
+            "indexer_url": network.indexer_url(),
+            "transfer_contract": MOCK_TRANSFER_CONTRACT,
+            "relayer_url": url,
+        });
+        let config = CString::new(config.to_string()).unwrap();
+        let ok = take(unsafe { r14_submit_transfer(config.as_ptr(), proof.as_ptr()) });
+        assert_eq!(ok["ok"], "true");
+
+        let body = relayer.join().unwrap();
+        assert_eq!(body["contract_id"], MOCK_TRANSFER_CONTRACT);
+        assert_eq!(body["proof"]["b"], "bb");
+        let expected = r14_sdk::merkle::compute_root_from_leaves(&[
+            Commitment(Fr::from(5u64)),
+            Commitment(cm_0),
+            Commitment(cm_1),
+        ]);
+        assert_eq!(body["new_root"], expected);
+        // nothing went to the contracts directly
+        assert!(network.calls().is_empty());
+    }
+
+    #[test]
+    fn submission_needs_a_relayer() {
+        let config = CString::new(r#"{"indexer_url":"http://127.0.0.1:9","transfer_contract":"C"}"#).unwrap();
+        let proof = CString::new(
+            r#"{"proof":{"a":"","b":"","c":""},
+                "public_inputs":{"old_root":"","nullifier":"","cm_0":"","cm_1":"","current_ledger":0}}"#,
+        )
+        .unwrap();
+        let err = take(unsafe { r14_submit_transfer(config.as_ptr(), proof.as_ptr()) });
+        assert_eq!(err["code"], "config");
+        assert!(err["error"].as_str().unwrap().contains("relayer_url"));
+
+        // balance sync reads only the indexer
+        let notes = CString::new("[]").unwrap();
+        let ok = take(unsafe { r14_balance(config.as_ptr(), notes.as_ptr()) });
+        assert_eq!(ok["ok"]["total"], 0);
+    }
+
+    #[test]
+    fn uniffi_calls_return_the_same_envelopes() {
+        let keys: serde_json::Value = serde_json::from_str(&mobile::keygen()).unwrap();
+        let owner = keys["ok"]["address"].as_str().unwrap().to_string();
+        let entry: serde_json::Value = serde_json::from_str(&mobile::note_new(7, 1, owner)).unwrap();
+        let cm: serde_json::Value = serde_json::from_str(&mobile::commitment(entry["ok"].to_string())).unwrap();
+        assert_eq!(cm["ok"], entry["ok"]["commitment"]);
+
+        let err: serde_json::Value = serde_json::from_str(&mobile::commitment("{".into())).unwrap();
+        assert!(err["error"].as_str().unwrap().starts_with("note_json:"));
+    }
+}
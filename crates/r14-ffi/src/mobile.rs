@@ -0,0 +1,46 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! The C ABI's calls, exported through UniFFI.
+//!
+//! `bindings/kotlin` and `bindings/swift` are generated from these, so a
+//! wallet calls `keygen()` rather than binding `r14_keygen` by hand. Each
+//! function takes and returns the same JSON strings as its `r14_*`
+//! counterpart, errors included, and nothing needs freeing. Regenerate
+//! the bindings after changing this file; the README has the commands.
+
+/// Hash with `family` (`poseidon`, `poseidon2` or `circom`); call first
+#[uniffi::export]
+pub fn set_hash_family(family: String) -> String {
+    crate::envelope(|| crate::set_hash_family(&family))
+}
+
+/// New secret key, owner hash and `r14o1…` address
+#[uniffi::export]
+pub fn keygen() -> String {
+    crate::envelope(crate::keygen)
+}
+
+/// New note for `owner_hash` (hex or address), as a wallet entry
+#[uniffi::export]
+pub fn note_new(value: u64, app_tag: u32, owner_hash: String) -> String {
+    crate::envelope(|| crate::note_new(value, app_tag, &owner_hash))
+}
+
+/// Commitment of a note or wallet entry
+#[uniffi::export]
+pub fn commitment(note_json: String) -> String {
+    crate::envelope(|| crate::note_commitment(&note_json))
+}
+
+/// Sync wallet entries against the indexer: `{"total", "notes"}`
+#[uniffi::export]
+pub fn balance(config_json: String, notes_json: String) -> String {
+    crate::envelope(|| crate::balance(&config_json, &notes_json))
+}
+
+/// Post an `r14-prover` transfer response to the config's relayer
+#[uniffi::export]
+pub fn submit_transfer(config_json: String, proof_json: String) -> String {
+    crate::envelope(|| crate::submit_transfer(&config_json, &proof_json))
+}
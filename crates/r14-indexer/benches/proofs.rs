@@ -0,0 +1,58 @@
+//! Merkle proofs over 100k leaves: to the current root, to an earlier root
+//! from a cached frontier, from the stored nodes alone, and by rehashing
+//! the leaves (the behaviour before `proof_at`).
+//!
+//! cargo bench -p r14-indexer --bench proofs
+
+use std::time::{Duration, Instant};
+
+use ark_bls12_381::Fr;
+use r14_indexer::tree::{verify_proof, SparseMerkleTree};
+use r14_types::{Commitment, LeafIndex};
+
+const LEAVES: usize = 100_000;
+const PROOFS: usize = 1_000;
+
+/// Mean time of `runs` calls to `f`
+fn mean(name: &str, runs: usize, mut f: impl FnMut(usize)) -> Duration {
+    let start = Instant::now();
+    for i in 0..runs {
+        f(i);
+    }
+    let each = start.elapsed() / runs as u32;
+    println!("{name:<22} {each:?} each ({runs} runs)");
+    each
+}
+
+fn main() {
+    let leaves: Vec<Commitment> = (1..=LEAVES as u64).map(|v| Commitment(Fr::from(v))).collect();
+
+    let start = Instant::now();
+    let tree: SparseMerkleTree = SparseMerkleTree::from_leaves(leaves.clone());
+    println!("build {LEAVES} leaves      {:?}", start.elapsed());
+
+    // spread the indices over the tree
+    let index = |i: usize| LeafIndex((i * 7919 % LEAVES) as u64);
+    let earlier = LEAVES / 2 + 1;
+    let earlier_index = |i: usize| LeafIndex((i * 7919 % earlier) as u64);
+    let earlier_root = tree.root_at(earlier);
+
+    mean("current root", PROOFS, |i| {
+        std::hint::black_box(tree.proof(index(i)));
+    });
+    let frontier = tree.frontier(earlier).unwrap();
+    mean("earlier root, cached", PROOFS, |i| {
+        std::hint::black_box(tree.proof_in(&frontier, earlier_index(i)).unwrap());
+    });
+    let stored = mean("earlier root, stored", PROOFS, |i| {
+        std::hint::black_box(tree.proof_at(earlier_index(i), earlier).unwrap());
+    });
+    let rehashed = mean("earlier root, rehashed", 3, |i| {
+        std::hint::black_box(r14_sdk::merkle::path_from_leaves(&leaves[..earlier], earlier_index(i)).unwrap());
+    });
+    println!("speedup: {:.0}x", rehashed.as_secs_f64() / stored.as_secs_f64());
+
+    let i = earlier_index(1);
+    assert!(verify_proof(leaves[i.as_usize()], &tree.proof_at(i, earlier).unwrap(), &earlier_root));
+    assert!(verify_proof(leaves[0], &tree.proof(LeafIndex(0)), &tree.root()));
+}
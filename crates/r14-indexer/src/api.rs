@@ -1,24 +1,312 @@
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
 
 use ark_bls12_381::Fr;
-use ark_ff::{BigInteger, PrimeField};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
-    routing::get,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, IntoResponse, Response,
+    },
+    body::Bytes,
+    routing::{get, post},
     Json, Router,
 };
+use r14_errors::IndexerError;
+use r14_sdk::absence::NullifierAbsence;
+use r14_types::{hex, Commitment, LeafIndex, MerklePath, MerkleRoot, Nullifier};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::CorsLayer;
+use tower_http::trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer};
+use tower_http::LatencyUnit;
+use tracing::Level;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
-use crate::db::Db;
-use crate::tree::SparseMerkleTree;
+use crate::db::{Batch, HistoricalRoot, RawEventFilter, Storage};
+use crate::events::{self, IndexerEvent};
+use crate::reorg::Checkpoint;
+use crate::rpc::RawEvent;
+use crate::signing::AbsenceSigner;
+use crate::tree::{Frontier, SparseMerkleTree};
 
 pub struct AppState {
     pub tree: SparseMerkleTree,
-    pub db: Db,
+    pub db: Box<dyn Storage>,
+    pub events: broadcast::Sender<IndexerEvent>,
+    /// Latest network ledger seen by the poller, 0 before the first poll
+    pub ledger: u64,
+    /// Ledgers a leaf waits before it stops being `pending`; 0 confirms
+    /// leaves as soon as they are indexed
+    pub confirmations: u64,
+    /// Signs `/v1/nullifier/{nullifier}/absence` statements; `None`
+    /// serves none
+    pub signer: Option<AbsenceSigner>,
+    /// About how many of the newest leaves the tree keeps in memory; older
+    /// ones are read back from storage. `None` keeps them all.
+    pub prune_depth: Option<usize>,
+    /// Frontiers of the earlier roots proofs were last asked against,
+    /// newest first, at most [`ROOT_HISTORY_SIZE`]
+    frontiers: Mutex<VecDeque<Frontier>>,
+    /// Writes and events held back by [`Self::begin_batch`]
+    staged: Mutex<Option<(Batch, Vec<IndexerEvent>)>>,
+}
+
+impl AppState {
+    pub fn new(tree: SparseMerkleTree, db: impl Storage + 'static) -> Self {
+        Self::with_storage(tree, Box::new(db))
+    }
+
+    pub fn with_storage(tree: SparseMerkleTree, db: Box<dyn Storage>) -> Self {
+        Self {
+            tree,
+            db,
+            events: events::channel(),
+            ledger: 0,
+            confirmations: 0,
+            signer: None,
+            prune_depth: None,
+            frontiers: Mutex::default(),
+            staged: Mutex::default(),
+        }
+    }
+
+    /// Whether a leaf from `block_height` has yet to see `confirmations`
+    /// ledgers pass
+    pub fn is_pending(&self, block_height: u64) -> bool {
+        self.confirmations > 0 && self.ledger < block_height.saturating_add(self.confirmations)
+    }
+
+    /// Number of leaves that are no longer pending. Leaves are indexed in
+    /// ledger order, so these are always the first ones.
+    pub fn confirmed_leaf_count(&self) -> Result<usize, IndexerError> {
+        if self.confirmations == 0 {
+            return Ok(self.tree.next_index());
+        }
+        match self.ledger.checked_sub(self.confirmations) {
+            // a shared database can hold leaves this tree hasn't caught up to
+            Some(through) => Ok(self.db.count_leaves_through(through)?.min(self.tree.next_index())),
+            None => Ok(0),
+        }
+    }
+
+    /// Append a commitment to the tree, persist it, and notify subscribers.
+    /// The contract takes the same commitment more than once, so every
+    /// call appends a leaf. Polls aren't replayed: their cursor is stored
+    /// in the same transaction as their leaves.
+    pub fn index_leaf(&mut self, commitment: Commitment, block_height: u64) -> LeafIndex {
+        let idx = self.tree.insert(commitment);
+        self.prune();
+        if !self.stage(|batch| batch.leaves.push((idx, commitment, block_height))) {
+            if let Err(e) = self.db.insert_leaf(idx, commitment, block_height) {
+                tracing::error!(index = %idx, block_height, error = %e, "failed to store leaf");
+            }
+        }
+        self.publish(IndexerEvent::leaf(idx, commitment, block_height));
+        idx
+    }
+
+    /// Drop the tree's oldest leaves past [`Self::prune_depth`]. Cheap
+    /// enough to call after every insert.
+    pub fn prune(&mut self) {
+        if let Some(depth) = self.prune_depth {
+            self.tree.prune(depth);
+        }
+    }
+
+    /// Up to `limit` leaves from `from` on, read back from storage if the
+    /// tree pruned them
+    pub fn leaves(&self, from: usize, limit: usize) -> Result<Vec<Commitment>, IndexerError> {
+        if from >= self.tree.pruned() {
+            return Ok(self.tree.leaves_range(from, limit));
+        }
+        // a shared database can hold leaves this tree hasn't caught up to
+        self.db.load_leaves_range(from as u64, limit.min(self.tree.next_index() - from))
+    }
+
+    /// Path for leaf `index` to the current root, or to `frontier`'s;
+    /// `None` if the leaf isn't under it. The leaves around a pruned leaf
+    /// are read back from storage.
+    pub fn proof(&self, index: LeafIndex, frontier: Option<&Frontier>) -> Result<Option<MerklePath>, IndexerError> {
+        let leaf_count = frontier.map_or(self.tree.next_index(), |f| f.leaf_count());
+        if index.as_usize() >= leaf_count {
+            return Ok(None);
+        }
+        if index.as_usize() >= self.tree.pruned() {
+            return Ok(match frontier {
+                Some(frontier) => self.tree.proof_in(frontier, index),
+                None => Some(self.tree.proof(index)),
+            });
+        }
+        let chunk = self.tree.chunk_of(index);
+        let leaves = self.db.load_leaves_range(chunk.start as u64, chunk.len())?;
+        match self.tree.proof_with_chunk(index, &leaves, frontier) {
+            Some(path) => Ok(Some(path)),
+            None => Err(IndexerError::Storage(format!("stored leaves {chunk:?} do not match the tree"))),
+        }
+    }
+
+    /// Persist a spent nullifier and notify subscribers
+    pub fn index_nullifier(&mut self, nullifier: Nullifier, block_height: u64) {
+        if !self.stage(|batch| batch.nullifiers.push((nullifier.clone(), block_height))) {
+            if let Err(e) = self.db.insert_nullifier(&nullifier, block_height) {
+                tracing::error!(block_height, error = %e, "failed to store nullifier");
+            }
+        }
+        self.publish(IndexerEvent::nullifier(nullifier, block_height));
+    }
+
+    /// Hold storage writes and subscriber events back until
+    /// [`Self::commit_batch`] writes them in one transaction
+    pub fn begin_batch(&mut self) {
+        *self.staged.get_mut().expect("staged batch poisoned") = Some(Default::default());
+    }
+
+    /// Write the batch with the poller's cursor and, past a full page, a
+    /// checkpoint, then publish its events. If the write fails the tree is
+    /// rebuilt from storage, dropping the batch's leaves, so the poll can
+    /// be retried.
+    pub fn commit_batch(
+        &mut self,
+        cursor: (u64, Option<String>),
+        checkpoint: Option<Checkpoint>,
+    ) -> Result<(), IndexerError> {
+        let Some((mut batch, events)) = self.staged.get_mut().expect("staged batch poisoned").take() else {
+            return Err(IndexerError::Storage("no batch in progress".into()));
+        };
+        batch.cursor = Some(cursor);
+        batch.checkpoint = checkpoint;
+        if let Err(e) = self.db.write_batch(&batch) {
+            self.tree = SparseMerkleTree::from_leaves(self.db.load_leaves()?);
+            self.prune();
+            let kept = self.tree.next_index();
+            self.frontiers.get_mut().expect("frontier cache poisoned").retain(|f| f.leaf_count() <= kept);
+            return Err(e);
+        }
+        for event in events {
+            self.publish(event);
+        }
+        Ok(())
+    }
+
+    /// Apply `write` to the batch in progress; false if there is none
+    fn stage(&self, write: impl FnOnce(&mut Batch)) -> bool {
+        match self.staged.lock().expect("staged batch poisoned").as_mut() {
+            Some((batch, _)) => {
+                write(batch);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The indexed state as of the end of `ledger`
+    pub fn checkpoint(&self, ledger: u64) -> Checkpoint {
+        Checkpoint {
+            ledger,
+            leaf_count: self.tree.next_index() as u64,
+            root: self.tree.root(),
+        }
+    }
+
+    /// Roll storage and the tree back to `checkpoint` and tell subscribers
+    pub fn rollback_to(&mut self, checkpoint: &Checkpoint) -> Result<(), IndexerError> {
+        self.db.rollback_to(checkpoint)?;
+        let tree = SparseMerkleTree::from_leaves(self.db.load_leaves()?);
+        if tree.root() != checkpoint.root {
+            return Err(IndexerError::Storage(format!(
+                "leaves kept at ledger {} hash to {:?}, the checkpoint says {:?}",
+                checkpoint.ledger,
+                tree.root(),
+                checkpoint.root
+            )));
+        }
+        self.tree = tree;
+        self.prune();
+        self.ledger = checkpoint.ledger;
+        let kept = self.tree.next_index();
+        self.frontiers.get_mut().expect("frontier cache poisoned").retain(|f| f.leaf_count() <= kept);
+        self.publish(IndexerEvent::rollback(checkpoint.ledger, self.tree.next_index(), self.tree.root()));
+        Ok(())
+    }
+
+    /// Persist the current root as the one a call in `ledger` left. The
+    /// contract keeps one root per call, so call this once per call, after
+    /// all of its leaves.
+    pub fn record_root(&self, ledger: u64) {
+        let root = HistoricalRoot {
+            root: self.tree.root(),
+            leaf_count: self.tree.next_index() as u64,
+            ledger,
+        };
+        if !self.stage(|batch| batch.roots.push(root.clone())) {
+            if let Err(e) = self.db.insert_root(&root) {
+                tracing::error!(leaf_count = root.leaf_count, ledger, error = %e, "failed to store root");
+            }
+        }
+    }
+
+    /// Persist a contract event the poller is about to index
+    pub fn record_event(&self, event: &RawEvent) {
+        if !self.stage(|batch| batch.raw_events.push(event.clone())) {
+            if let Err(e) = self.db.insert_raw_event(event) {
+                tracing::error!(id = %event.id, ledger = event.ledger, error = %e, "failed to store contract event");
+            }
+        }
+    }
+
+    /// Frontier of recorded root `root`, to prove against it with stored
+    /// nodes. Built and checked against the root on first use, then cached.
+    pub fn frontier(&self, root: &MerkleRoot) -> Result<Frontier, IndexerError> {
+        let mut cache = self.frontiers.lock().expect("frontier cache poisoned");
+        if let Some(i) = cache.iter().position(|f| f.root() == root) {
+            let frontier = cache.remove(i).expect("position is in bounds");
+            cache.push_front(frontier.clone());
+            return Ok(frontier);
+        }
+        let recorded = self
+            .db
+            .find_root(root)?
+            .ok_or_else(|| IndexerError::NotFound("root not recorded".into()))?;
+        let leaf_count = usize::try_from(recorded.leaf_count).unwrap_or(usize::MAX);
+        let frontier = self.tree.frontier(leaf_count).ok_or_else(|| {
+            IndexerError::NotFound(if leaf_count < self.tree.pruned() {
+                "root is older than the leaves this indexer keeps in memory".into()
+            } else {
+                "root not in the tree yet".into()
+            })
+        })?;
+        if frontier.root() != root {
+            return Err(IndexerError::Storage(format!("recorded root {root:?} does not match the leaves")));
+        }
+        cache.push_front(frontier.clone());
+        cache.truncate(ROOT_HISTORY_SIZE);
+        Ok(frontier)
+    }
+
+    /// Notify subscribers of the current root (call once per indexed batch)
+    pub fn publish_root(&self) {
+        self.publish(IndexerEvent::root(self.tree.root(), self.tree.next_index()));
+    }
+
+    /// Log `event` and push it to subscribers, or hold it back with the
+    /// batch in progress
+    pub fn publish(&self, event: IndexerEvent) {
+        if let Some((_, events)) = self.staged.lock().expect("staged batch poisoned").as_mut() {
+            events.push(event);
+            return;
+        }
+        if let Err(e) = self.db.append_event(&event) {
+            tracing::error!(kind = event.kind(), error = %e, "failed to log event");
+        }
+        // Err only means there are no subscribers right now
+        let _ = self.events.send(event);
+    }
 }
 
 pub type SharedState = Arc<RwLock<AppState>>;
@@ -26,77 +314,780 @@ pub type SharedState = Arc<RwLock<AppState>>;
 pub fn router(state: SharedState) -> Router {
     Router::new()
         .route("/v1/health", get(health))
+        .route("/v1/stats", get(get_stats))
         .route("/v1/root", get(get_root))
+        .route("/v1/roots", get(get_roots))
+        .route("/v1/root/at/{leaf_count}", get(get_root_at))
         .route("/v1/proof/{index}", get(get_proof))
+        .route("/v1/proof/by-commitment/{commitment}", get(get_proof_by_commitment))
+        .route("/v1/proofs", post(post_proofs))
         .route("/v1/leaf/{commitment}", get(get_leaf))
         .route("/v1/leaves", get(get_leaves))
+        .route("/v1/nullifier/{nullifier}", get(get_nullifier))
+        .route("/v1/nullifier/{nullifier}/absence", get(get_nullifier_absence))
+        .route("/v1/events", get(get_events))
+        .route("/v1/subscribe", get(subscribe))
+        .route("/v1/openapi.json", get(openapi))
+        .route("/v1/docs", get(docs))
+        // gzip or brotli, as the client accepts; SSE streams are left alone
+        .layer(CompressionLayer::new())
         .layer(CorsLayer::permissive())
+        // one access log line per request: method, path, status, latency
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                .on_response(DefaultOnResponse::new().level(Level::INFO).latency_unit(LatencyUnit::Millis)),
+        )
         .with_state(state)
 }
 
-async fn health() -> impl IntoResponse {
-    Json(json!({ "status": "ok" }))
+/// The HTTP API as an OpenAPI 3.1 document, served at `/v1/openapi.json`.
+/// Every route but the spec and docs pages themselves is in it.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "r14-indexer", description = "Merkle tree, nullifier and contract event API of the Root14 indexer"),
+    paths(
+        health,
+        get_stats,
+        get_root,
+        get_roots,
+        get_root_at,
+        get_proof,
+        get_proof_by_commitment,
+        post_proofs,
+        get_leaf,
+        get_leaves,
+        get_nullifier,
+        get_nullifier_absence,
+        get_events,
+        subscribe,
+    ),
+    components(schemas(IndexerEvent))
+)]
+pub struct ApiDoc;
+
+// The schema-only types below describe JSON of types from other crates
+
+/// A BLS12-381 scalar as `0x`-prefixed big-endian hex. Requests also
+/// accept it without the prefix.
+#[derive(ToSchema)]
+#[schema(example = "0x0000000000000000000000000000000000000000000000000000000000000001")]
+#[allow(dead_code)]
+pub(crate) struct FieldElement(String);
+
+/// Path from a leaf to the root
+#[derive(ToSchema)]
+#[schema(as = MerklePath)]
+#[allow(dead_code)]
+struct MerklePathSchema {
+    /// Sibling hashes, from the leaf's level up
+    siblings: Vec<FieldElement>,
+    /// Whether the node at each level is a right child, from the leaf up
+    indices: Vec<bool>,
+}
+
+/// Body of every error response
+#[derive(ToSchema)]
+#[schema(as = ErrorBody)]
+#[allow(dead_code)]
+struct ErrorBodySchema {
+    error: String,
+    /// Error category, e.g. `invalid_input` or `not_found`
+    code: String,
+    /// Stable id of the error, e.g. `R14_INDEXER_NOT_FOUND`
+    id: Option<String>,
+    /// Structured details, for errors that have any
+    context: Option<serde_json::Value>,
+}
+
+async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Swagger UI over `/v1/openapi.json`
+async fn docs() -> Html<&'static str> {
+    Html(include_str!("docs.html"))
+}
+
+#[derive(Serialize, ToSchema)]
+struct HealthResponse {
+    /// Always `ok`
+    status: &'static str,
+}
+
+#[utoipa::path(get, path = "/v1/health", responses((status = 200, body = HealthResponse)))]
+async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse { status: "ok" })
+}
+
+#[derive(Serialize, ToSchema)]
+struct StatsResponse {
+    /// Leaves in the tree
+    leaf_count: usize,
+    /// Spent nullifiers
+    nullifier_count: u64,
+    /// Deposit and transfer events indexed. History imported from a
+    /// snapshot carries no events, so isn't counted.
+    deposits: u64,
+    transfers: u64,
+    /// Ledger of the first leaf; null while there are none
+    first_ledger: Option<u64>,
+    /// Ledger indexing has reached; null before the first poll
+    last_ledger: Option<u64>,
+    /// Latest network ledger the poller has seen, 0 before its first poll
+    network_ledger: u64,
+    #[schema(value_type = FieldElement)]
+    root: MerkleRoot,
+}
+
+/// Totals over the indexed state, for dashboards
+#[utoipa::path(get, path = "/v1/stats", responses((status = 200, body = StatsResponse)))]
+async fn get_stats(State(state): State<SharedState>) -> Result<Json<StatsResponse>, ApiError> {
+    let s = state.read().await;
+    let stats = s.db.stats()?;
+    Ok(Json(StatsResponse {
+        leaf_count: s.tree.next_index(),
+        nullifier_count: stats.nullifiers,
+        deposits: stats.deposits,
+        transfers: stats.transfers,
+        first_ledger: stats.first_ledger,
+        last_ledger: stats.last_ledger,
+        network_ledger: s.ledger,
+        root: s.tree.root(),
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+struct RootResponse {
+    #[schema(value_type = FieldElement)]
+    root: MerkleRoot,
+    /// Latest network ledger the poller has seen, 0 before its first poll
+    ledger: u64,
 }
 
-async fn get_root(State(state): State<SharedState>) -> impl IntoResponse {
+#[utoipa::path(get, path = "/v1/root", responses((status = 200, body = RootResponse)))]
+async fn get_root(State(state): State<SharedState>) -> Json<RootResponse> {
     let s = state.read().await;
-    let root = s.tree.root();
-    let hex = fr_to_hex(&root.0);
-    Json(json!({ "root": hex }))
+    Json(RootResponse { root: s.tree.root(), ledger: s.ledger })
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct RootsQuery {
+    /// How many roots, 1 to 1000; 100 by default
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct RootsResponse {
+    /// Newest first
+    roots: Vec<HistoricalRoot>,
 }
 
+/// The transfer contract remembers this many roots; older ones can't be
+/// proven against
+pub const ROOT_HISTORY_SIZE: usize = 100;
+
+/// Most roots one `/v1/roots` request returns
+pub const MAX_ROOTS_LIMIT: usize = 1000;
+
+/// The last `?limit=` roots (default `ROOT_HISTORY_SIZE`), newest first.
+/// 304 if `If-None-Match` names the newest one.
+#[utoipa::path(
+    get,
+    path = "/v1/roots",
+    params(RootsQuery, ("If-None-Match" = Option<String>, Header)),
+    responses(
+        (status = 200, body = RootsResponse, headers(("ETag" = String))),
+        (status = 304, description = "The roots haven't changed since that ETag"),
+        (status = 400, body = ErrorBodySchema),
+    ),
+)]
+async fn get_roots(
+    State(state): State<SharedState>,
+    Query(query): Query<RootsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let limit = query.limit.unwrap_or(ROOT_HISTORY_SIZE);
+    if limit == 0 || limit > MAX_ROOTS_LIMIT {
+        return Err(IndexerError::InvalidInput(format!("limit must be between 1 and {MAX_ROOTS_LIMIT}")).into());
+    }
+    let roots = state.read().await.db.recent_roots(limit)?;
+    let Some(newest) = roots.first() else {
+        return Ok(Json(RootsResponse { roots }).into_response());
+    };
+    let etag = etag(&newest.root, &[newest.leaf_count, newest.ledger, roots.len() as u64]);
+    Ok(conditional(&headers, etag, || Json(RootsResponse { roots })))
+}
+
+/// Weak ETag for a response read from the tree at `root`. A root commits to
+/// every leaf under it, so with the counts it pins down the body without
+/// serializing it. Weak, since compression changes the bytes.
+fn etag(root: &MerkleRoot, counts: &[u64]) -> HeaderValue {
+    let counts: Vec<_> = counts.iter().map(u64::to_string).collect();
+    let tag = format!("W/\"{}-{}\"", hex::encode(&root.0), counts.join("-"));
+    HeaderValue::from_str(&tag).expect("ETag is ASCII")
+}
+
+/// 304 if the request's `If-None-Match` names `etag`, else `body()`; either
+/// way with the ETag. Tags are compared weakly, ignoring any `W/`.
+fn conditional<B: IntoResponse>(headers: &HeaderMap, etag: HeaderValue, body: impl FnOnce() -> B) -> Response {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let ours = opaque(etag.to_str().expect("ETag is ASCII"));
+    let matched = headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|tag| tag.trim() == "*" || opaque(tag) == ours);
+    if matched {
+        (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response()
+    } else {
+        ([(header::ETAG, etag)], body()).into_response()
+    }
+}
+
+/// The root the tree had at `leaf_count` leaves. Only counts a contract
+/// call ended at have one.
+#[utoipa::path(
+    get,
+    path = "/v1/root/at/{leaf_count}",
+    params(("leaf_count" = u64, Path)),
+    responses((status = 200, body = HistoricalRoot), (status = 404, body = ErrorBodySchema)),
+)]
+async fn get_root_at(
+    State(state): State<SharedState>,
+    Path(leaf_count): Path<u64>,
+) -> Result<Json<HistoricalRoot>, ApiError> {
+    let s = state.read().await;
+    let root = s
+        .db
+        .root_at(leaf_count)?
+        .ok_or_else(|| IndexerError::NotFound(format!("no root recorded at {leaf_count} leaves")))?;
+    Ok(Json(root))
+}
+
+/// `IndexerError` rendered as an HTTP status plus `ErrorBody` JSON
+pub struct ApiError(IndexerError);
+
+impl From<IndexerError> for ApiError {
+    fn from(e: IndexerError) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        let code = self.0.code();
+        let status = StatusCode::from_u16(code.http_status())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(self.0.to_body())).into_response()
+    }
+}
+
+/// Merkle path of leaf `index` to the current root
+#[utoipa::path(
+    get,
+    path = "/v1/proof/{index}",
+    params(("index" = u64, Path)),
+    responses((status = 200, body = MerklePathSchema), (status = 404, body = ErrorBodySchema)),
+)]
 async fn get_proof(
     State(state): State<SharedState>,
-    Path(index): Path<usize>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    Path(index): Path<u64>,
+) -> Result<Json<MerklePath>, ApiError> {
+    let index = LeafIndex(index);
+    let s = state.read().await;
+    if index.as_usize() >= s.tree.next_index() {
+        return Err(IndexerError::NotFound("index out of bounds".into()).into());
+    }
+    Ok(Json(s.proof(index, None)?.expect("index is in bounds")))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct ProofQuery {
+    /// A recorded root to prove against instead of the current one
+    #[param(value_type = Option<FieldElement>)]
+    root: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct CommitmentProof {
+    #[schema(value_type = u64)]
+    index: LeafIndex,
+    /// Ledger the leaf was indexed in
+    block_height: u64,
+    /// Whether the leaf has yet to see the configured confirmations
+    pending: bool,
+    /// The root `path` leads to
+    #[schema(value_type = FieldElement)]
+    root: MerkleRoot,
+    /// Leaves under `root`
+    leaf_count: usize,
+    #[schema(value_type = MerklePathSchema)]
+    path: MerklePath,
+}
+
+/// Where `commitment` sits and its Merkle path, in one read. The path is
+/// to the current root, or to `?root=` if that's a recorded root whose
+/// tree holds the leaf.
+#[utoipa::path(
+    get,
+    path = "/v1/proof/by-commitment/{commitment}",
+    params(("commitment" = FieldElement, Path), ProofQuery),
+    responses(
+        (status = 200, body = CommitmentProof),
+        (status = 400, body = ErrorBodySchema),
+        (status = 404, body = ErrorBodySchema),
+    ),
+)]
+async fn get_proof_by_commitment(
+    State(state): State<SharedState>,
+    Path(commitment): Path<String>,
+    Query(query): Query<ProofQuery>,
+) -> Result<Json<CommitmentProof>, ApiError> {
+    let commitment = Commitment(parse_fr(&commitment)?);
+    let root = query.root.as_deref().map(parse_fr).transpose()?.map(MerkleRoot);
     let s = state.read().await;
-    if index >= s.tree.next_index() {
-        return Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "index out of bounds" })),
-        ));
+    let (idx, height) = s
+        .db
+        .get_leaf_by_commitment(commitment)?
+        .ok_or_else(|| IndexerError::NotFound("commitment not found".into()))?;
+    // a shared database can hold leaves this tree hasn't caught up to
+    if idx.as_usize() >= s.tree.next_index() {
+        return Err(IndexerError::NotFound("commitment not in the tree yet".into()).into());
     }
-    let proof = s.tree.proof(index);
-    let siblings: Vec<String> = proof.siblings.iter().map(fr_to_hex).collect();
-    let indices: Vec<bool> = proof.indices;
-    Ok(Json(json!({ "siblings": siblings, "indices": indices })))
+    let (root, leaf_count, path) = match root {
+        None => (s.tree.root(), s.tree.next_index(), s.proof(idx, None)?.expect("index is in bounds")),
+        Some(root) if root == s.tree.root() => {
+            (root, s.tree.next_index(), s.proof(idx, None)?.expect("index is in bounds"))
+        }
+        Some(root) => {
+            let frontier = s.frontier(&root)?;
+            let path = s
+                .proof(idx, Some(&frontier))?
+                .ok_or_else(|| IndexerError::InvalidInput(format!("leaf {idx} is not under that root")))?;
+            (root, frontier.leaf_count(), path)
+        }
+    };
+    Ok(Json(CommitmentProof {
+        index: idx,
+        block_height: height,
+        pending: s.is_pending(height),
+        root,
+        leaf_count,
+        path,
+    }))
 }
 
+/// Most paths one `/v1/proofs` request asks for
+pub const MAX_BATCH_PROOFS: usize = 256;
+
+/// `/v1/proofs` body: leaf indices or commitments, not both
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+struct ProofsRequest {
+    #[serde(default)]
+    indices: Vec<u64>,
+    #[serde(default)]
+    #[schema(value_type = Vec<FieldElement>)]
+    commitments: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ProofsResponse {
+    /// The root every path leads to
+    #[schema(value_type = FieldElement)]
+    root: MerkleRoot,
+    leaf_count: usize,
+    /// In the order asked for
+    proofs: Vec<LeafProof>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct LeafProof {
+    #[schema(value_type = u64)]
+    index: LeafIndex,
+    #[schema(value_type = MerklePathSchema)]
+    path: MerklePath,
+}
+
+/// Merkle paths for several leaves, all to the root they're returned with,
+/// in the order asked for. Any unknown leaf fails the whole request.
+#[utoipa::path(
+    post,
+    path = "/v1/proofs",
+    request_body = ProofsRequest,
+    responses(
+        (status = 200, body = ProofsResponse),
+        (status = 400, body = ErrorBodySchema),
+        (status = 404, body = ErrorBodySchema),
+    ),
+)]
+async fn post_proofs(State(state): State<SharedState>, body: Bytes) -> Result<Json<ProofsResponse>, ApiError> {
+    let request: ProofsRequest =
+        serde_json::from_slice(&body).map_err(|e| IndexerError::InvalidInput(format!("bad request body: {e}")))?;
+    let count = request.indices.len() + request.commitments.len();
+    if request.indices.is_empty() == request.commitments.is_empty() {
+        return Err(IndexerError::InvalidInput("send either `indices` or `commitments`".into()).into());
+    }
+    if count > MAX_BATCH_PROOFS {
+        return Err(IndexerError::InvalidInput(format!("at most {MAX_BATCH_PROOFS} proofs per request")).into());
+    }
+    let commitments = request
+        .commitments
+        .iter()
+        .map(|cm| parse_fr(cm).map(Commitment))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let s = state.read().await;
+    let mut indices: Vec<LeafIndex> = request.indices.into_iter().map(LeafIndex).collect();
+    for commitment in commitments {
+        let (idx, _) = s
+            .db
+            .get_leaf_by_commitment(commitment)?
+            .ok_or_else(|| IndexerError::NotFound(format!("commitment {} not found", hex::encode(&commitment.0))))?;
+        indices.push(idx);
+    }
+    let mut proofs = Vec::with_capacity(indices.len());
+    for index in indices {
+        if index.as_usize() >= s.tree.next_index() {
+            return Err(IndexerError::NotFound(format!("index {index} out of bounds")).into());
+        }
+        proofs.push(LeafProof { index, path: s.proof(index, None)?.expect("index is in bounds") });
+    }
+    Ok(Json(ProofsResponse {
+        root: s.tree.root(),
+        leaf_count: s.tree.next_index(),
+        proofs,
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+struct LeafResponse {
+    #[schema(value_type = u64)]
+    index: LeafIndex,
+    block_height: u64,
+    pending: bool,
+}
+
+/// Where `commitment` sits in the tree
+#[utoipa::path(
+    get,
+    path = "/v1/leaf/{commitment}",
+    params(("commitment" = FieldElement, Path)),
+    responses(
+        (status = 200, body = LeafResponse),
+        (status = 400, body = ErrorBodySchema),
+        (status = 404, body = ErrorBodySchema),
+    ),
+)]
 async fn get_leaf(
     State(state): State<SharedState>,
     Path(commitment): Path<String>,
-) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let bytes = hex::decode(commitment.strip_prefix("0x").unwrap_or(&commitment))
-        .map_err(|_| {
-            (
-                StatusCode::BAD_REQUEST,
-                Json(json!({ "error": "invalid hex" })),
-            )
-        })?;
-    let fr = Fr::from_be_bytes_mod_order(&bytes);
+) -> Result<Json<LeafResponse>, ApiError> {
+    let commitment = Commitment(parse_fr(&commitment)?);
+    let s = state.read().await;
+    let (idx, height) = s
+        .db
+        .get_leaf_by_commitment(commitment)?
+        .ok_or_else(|| IndexerError::NotFound("commitment not found".into()))?;
+    Ok(Json(LeafResponse {
+        index: idx,
+        block_height: height,
+        pending: s.is_pending(height),
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+struct NullifierResponse {
+    #[schema(value_type = FieldElement)]
+    nullifier: Nullifier,
+    /// Ledger it was spent in
+    block_height: u64,
+}
+
+/// Whether `nullifier` is spent; 404 if it isn't
+#[utoipa::path(
+    get,
+    path = "/v1/nullifier/{nullifier}",
+    params(("nullifier" = FieldElement, Path)),
+    responses(
+        (status = 200, body = NullifierResponse),
+        (status = 400, body = ErrorBodySchema),
+        (status = 404, body = ErrorBodySchema),
+    ),
+)]
+async fn get_nullifier(
+    State(state): State<SharedState>,
+    Path(nullifier): Path<String>,
+) -> Result<Json<NullifierResponse>, ApiError> {
+    let nullifier = Nullifier(parse_fr(&nullifier)?);
     let s = state.read().await;
-    match s.db.get_leaf_by_commitment(fr) {
-        Ok(Some((idx, height))) => Ok(Json(json!({
-            "index": idx,
-            "block_height": height,
-        }))),
-        Ok(None) => Err((
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "commitment not found" })),
-        )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        )),
-    }
-}
-
-async fn get_leaves(State(state): State<SharedState>) -> impl IntoResponse {
+    let height = s
+        .db
+        .get_nullifier(&nullifier)?
+        .ok_or_else(|| IndexerError::NotFound("nullifier not spent".into()))?;
+    Ok(Json(NullifierResponse {
+        nullifier,
+        block_height: height,
+    }))
+}
+
+/// Signed statement that a nullifier was unspent
+#[derive(ToSchema)]
+#[schema(as = NullifierAbsence)]
+#[allow(dead_code)]
+struct NullifierAbsenceSchema {
+    nullifier: FieldElement,
+    /// Transfer contract the indexer indexes
+    contract_id: String,
+    /// Root of the tree when the indexer checked
+    root: FieldElement,
+    /// Leaves under `root`
+    leaf_count: u64,
+    /// Latest network ledger the indexer had seen
+    ledger: u64,
+    /// Ed25519 key that signed, as 64 hex digits
+    public_key: String,
+    /// Ed25519 signature, as 128 hex digits, over the message described
+    /// in `r14_sdk::absence::NullifierAbsence::message`
+    signature: String,
+}
+
+/// A statement, signed with the indexer's key, that `nullifier` is unspent
+/// at the current root and ledger, for relayers and light clients to check
+/// a transfer against before submitting it. 404 if the nullifier is spent
+/// or the indexer has no signing key.
+#[utoipa::path(
+    get,
+    path = "/v1/nullifier/{nullifier}/absence",
+    params(("nullifier" = FieldElement, Path)),
+    responses(
+        (status = 200, body = NullifierAbsenceSchema),
+        (status = 400, body = ErrorBodySchema),
+        (status = 404, body = ErrorBodySchema),
+    ),
+)]
+async fn get_nullifier_absence(
+    State(state): State<SharedState>,
+    Path(nullifier): Path<String>,
+) -> Result<Json<NullifierAbsence>, ApiError> {
+    let nullifier = Nullifier(parse_fr(&nullifier)?);
+    let s = state.read().await;
+    let signer = s
+        .signer
+        .as_ref()
+        .ok_or_else(|| IndexerError::NotFound("this indexer has no signing key".into()))?;
+    if let Some(height) = s.db.get_nullifier(&nullifier)? {
+        return Err(IndexerError::NotFound(format!("nullifier spent in ledger {height}")).into());
+    }
+    Ok(Json(signer.sign(nullifier, s.tree.root(), s.tree.next_index() as u64, s.ledger)))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct LeavesQuery {
+    /// First leaf index to return; 0 by default
+    from_index: Option<u64>,
+    /// Older name for `from_index`
+    from: Option<u64>,
+    /// How many leaves, 1 to 10000; every one by default
+    limit: Option<usize>,
+    /// Skip the leaves indexed before this ledger
+    since_ledger: Option<u64>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct LeavesResponse {
+    /// Index of `leaves[0]`
+    from: u64,
+    #[schema(value_type = Vec<FieldElement>)]
+    leaves: Vec<Commitment>,
+    /// Root of the whole tree, not just of `leaves`
+    #[schema(value_type = FieldElement)]
+    root: MerkleRoot,
+    /// Leaves before this index are confirmed
+    confirmed: usize,
+    /// `?from_index=` for the next page; null once `leaves` reaches the
+    /// end of the tree
+    next: Option<u64>,
+}
+
+/// Most leaves one `/v1/leaves` request returns when it sets `?limit=`
+pub const MAX_LEAVES_LIMIT: usize = 10_000;
+
+/// Up to `?limit=` leaves from `?from_index=` on (default 0), skipping
+/// those indexed before `?since_ledger=`, with the root of the whole tree
+/// read under the same lock, so a client holding the earlier leaves can
+/// check its copy. Leaves at index `confirmed` and later are pending.
+/// 304 if `If-None-Match` names the current tree.
+#[utoipa::path(
+    get,
+    path = "/v1/leaves",
+    params(LeavesQuery, ("If-None-Match" = Option<String>, Header)),
+    responses(
+        (status = 200, body = LeavesResponse, headers(("ETag" = String))),
+        (status = 304, description = "The tree hasn't changed since that ETag"),
+    ),
+)]
+async fn get_leaves(
+    State(state): State<SharedState>,
+    Query(query): Query<LeavesQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if query.limit.is_some_and(|limit| limit == 0 || limit > MAX_LEAVES_LIMIT) {
+        return Err(IndexerError::InvalidInput(format!("limit must be between 1 and {MAX_LEAVES_LIMIT}")).into());
+    }
     let s = state.read().await;
-    let leaves: Vec<String> = s.tree.leaves().iter().map(fr_to_hex).collect();
-    Json(json!({ "leaves": leaves }))
+    let mut from = query.from_index.or(query.from).unwrap_or(0);
+    if let Some(ledger) = query.since_ledger.and_then(|l| l.checked_sub(1)) {
+        // leaves are appended in ledger order, so the earlier ones come first
+        from = from.max(s.db.count_leaves_through(ledger)? as u64);
+    }
+    let confirmed = s.confirmed_leaf_count()?;
+    let etag = etag(&s.tree.root(), &[s.tree.next_index() as u64, confirmed as u64]);
+    Ok(conditional(&headers, etag, || -> Result<_, ApiError> {
+        let start = usize::try_from(from).unwrap_or(usize::MAX);
+        let leaves = s.leaves(start, query.limit.unwrap_or(usize::MAX))?;
+        let end = from + leaves.len() as u64;
+        Ok(Json(LeavesResponse {
+            from,
+            leaves,
+            root: s.tree.root(),
+            confirmed,
+            next: Some(end).filter(|&end| end < s.tree.next_index() as u64),
+        }))
+    }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct EventsQuery {
+    /// Only events after this id, the `next` of the previous page
+    after: Option<String>,
+    /// `deposit` or `transfer`
+    topic: Option<String>,
+    tx_hash: Option<String>,
+    from_ledger: Option<u64>,
+    to_ledger: Option<u64>,
+    /// Page size, 1 to 1000; 100 by default
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+struct EventsResponse {
+    events: Vec<RawEvent>,
+    /// `?after=` for the next page; null on a page that isn't full
+    next: Option<String>,
+}
+
+/// Most contract events one `/v1/events` request returns
+pub const MAX_EVENTS_LIMIT: usize = 1000;
+
+/// Contract events the leaves and nullifiers came from, in chain order,
+/// filtered by `?topic=`, `?tx_hash=` and `?from_ledger=`/`?to_ledger=`.
+/// A full page of `?limit=` (default 100) carries `next`, the `?after=`
+/// for the page after it.
+#[utoipa::path(
+    get,
+    path = "/v1/events",
+    params(EventsQuery),
+    responses((status = 200, body = EventsResponse), (status = 400, body = ErrorBodySchema)),
+)]
+async fn get_events(
+    State(state): State<SharedState>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventsResponse>, ApiError> {
+    let limit = query.limit.unwrap_or(100);
+    if limit == 0 || limit > MAX_EVENTS_LIMIT {
+        return Err(IndexerError::InvalidInput(format!("limit must be between 1 and {MAX_EVENTS_LIMIT}")).into());
+    }
+    if let Some(topic) = query.topic.as_deref().filter(|&t| t != "deposit" && t != "transfer") {
+        return Err(IndexerError::InvalidInput(format!("topic must be deposit or transfer, got `{topic}`")).into());
+    }
+    let filter = RawEventFilter {
+        after: query.after,
+        topic: query.topic,
+        tx_hash: query.tx_hash,
+        from_ledger: query.from_ledger,
+        to_ledger: query.to_ledger,
+    };
+    let events = state.read().await.db.raw_events(&filter, limit)?;
+    let next = events.last().filter(|_| events.len() == limit).map(|ev| ev.id.clone());
+    Ok(Json(EventsResponse { events, next }))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SubscribeQuery {
+    /// Comma-separated event types to send, e.g. `nullifier`; all of them
+    /// by default. `rollback` and `lagged` are always sent.
+    types: Option<String>,
+}
+
+/// Event types `?types=` can name
+const EVENT_TYPES: [&str; 4] = ["leaf", "root", "nullifier", "rollback"];
+
+/// Server-sent events stream of `leaf`, `root`, `nullifier` and `rollback`
+/// updates, or just the `?types=` asked for. A wallet watching for spends
+/// of its notes asks for `nullifier` and matches them locally, so the
+/// indexer never learns which nullifiers are its. Slow clients that fall
+/// more than `EVENT_CAPACITY` events behind skip ahead, told by a `lagged`
+/// event with the number skipped, and should resync via `/v1/leaves`.
+#[utoipa::path(
+    get,
+    path = "/v1/subscribe",
+    params(SubscribeQuery),
+    responses(
+        (
+            status = 200,
+            description = "One SSE message per update, named by its `type`",
+            content_type = "text/event-stream",
+            body = IndexerEvent,
+        ),
+        (status = 400, body = ErrorBodySchema),
+    ),
+)]
+async fn subscribe(
+    State(state): State<SharedState>,
+    Query(query): Query<SubscribeQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let wanted = match query.types.as_deref() {
+        None => EVENT_TYPES.to_vec(),
+        Some(types) => types
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                EVENT_TYPES.into_iter().find(|&known| known == t).ok_or_else(|| {
+                    IndexerError::InvalidInput(format!("unknown event type `{t}`, expected one of {EVENT_TYPES:?}"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+    let rx = state.read().await.events.subscribe();
+    let stream = stream::unfold((rx, wanted), |(mut rx, wanted)| async move {
+        loop {
+            let event = match rx.recv().await {
+                // a rollback undoes events of every type, so always goes out
+                Ok(ev) if ev.kind() != "rollback" && !wanted.contains(&ev.kind()) => continue,
+                Ok(ev) => Event::default().event(ev.kind()).json_data(&ev),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(skipped = n, "subscriber lagged");
+                    Event::default().event("lagged").json_data(json!({ "type": "lagged", "skipped": n }))
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            return Some((Ok(event.expect("event serializes")), (rx, wanted)));
+        }
+    });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
 }
 
-fn fr_to_hex(fr: &Fr) -> String {
-    format!("0x{}", hex::encode(fr.into_bigint().to_bytes_be()))
+/// Path parameter as a field element; non-canonical encodings are rejected
+/// rather than reduced
+fn parse_fr(s: &str) -> Result<Fr, ApiError> {
+    hex::decode(s).map_err(|e| IndexerError::InvalidInput(e.to_string()).into())
 }
@@ -0,0 +1,389 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, ensure, Context, Result};
+use clap::{Parser, Subcommand};
+use r14_sdk::deployment::Deployment;
+use r14_sdk::merkle::PRUNED_LEVELS;
+use r14_sdk::HashFamily;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_RPC_URL: &str = "https://soroban-testnet.stellar.org:443";
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 5;
+pub const DEFAULT_DB_URL: &str = "r14-indexer.db";
+pub const DEFAULT_LISTEN_ADDR: &str = "0.0.0.0:3000";
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+pub const DEFAULT_DRAIN_TIMEOUT_SECS: u64 = 10;
+
+/// Command line. Each setting can also come from its environment variable,
+/// then the `--config` file, then `r14.toml`, then the default.
+#[derive(Parser, Debug, Default)]
+#[command(name = "r14-indexer", about = "Event indexer and Merkle tree service for Root14", version)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// TOML config file
+    #[arg(long, env = "R14_INDEXER_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Soroban RPC endpoint
+    #[arg(long, env = "R14_RPC_URL")]
+    pub rpc_url: Option<String>,
+    /// RPC endpoint to fail over to, in order; repeat the flag or separate
+    /// them with commas
+    #[arg(long = "rpc-fallback", env = "R14_RPC_FALLBACKS", value_delimiter = ',')]
+    pub rpc_fallbacks: Vec<String>,
+    /// Transfer contract to index
+    #[arg(long, env = "R14_CONTRACT_ID")]
+    pub contract_id: Option<String>,
+    /// Hash family of the deployment: poseidon, poseidon2 or circom
+    #[arg(long, env = "R14_HASH")]
+    pub hash: Option<HashFamily>,
+    /// Seconds between polls
+    #[arg(long, env = "R14_POLL_INTERVAL")]
+    pub poll_interval_secs: Option<u64>,
+    /// SQLite path, `sqlite://` URL or `postgres://` URL
+    #[arg(long, env = "R14_DB_URL")]
+    pub db_url: Option<String>,
+    /// Older name for a SQLite `--db-url`
+    #[arg(long, env = "R14_DB_PATH", hide = true)]
+    pub db_path: Option<String>,
+    /// HTTP listen address
+    #[arg(long, env = "R14_LISTEN_ADDR")]
+    pub listen_addr: Option<String>,
+    /// On a first run, index from this ledger instead of the latest
+    #[arg(long, env = "R14_START_LEDGER")]
+    pub backfill_from: Option<u64>,
+    /// Ledgers a leaf stays pending for
+    #[arg(long, env = "R14_CONFIRMATIONS")]
+    pub confirmations: Option<u64>,
+    /// Log filter, e.g. `info` or `debug,tower_http=warn`
+    #[arg(long, env = "RUST_LOG")]
+    pub log_level: Option<String>,
+    /// Seconds to wait on shutdown for requests and the current poll to finish
+    #[arg(long, env = "R14_DRAIN_TIMEOUT")]
+    pub drain_timeout_secs: Option<u64>,
+    /// File holding the Ed25519 key to sign nullifier absence statements
+    /// with, as 64 hex digits; without one, none are served
+    #[arg(long, env = "R14_SIGNING_KEY_FILE")]
+    pub signing_key_file: Option<PathBuf>,
+    /// Keep only about this many of the newest leaves in memory, reading
+    /// older ones back from the database when asked for
+    #[arg(long, env = "R14_PRUNE_DEPTH")]
+    pub prune_depth: Option<u64>,
+    /// Keep every leaf in memory, whatever `prune_depth` says
+    #[arg(long, env = "R14_ARCHIVE")]
+    pub archive: bool,
+    /// Roll back to the newest checkpoint at or before this ledger on startup
+    #[arg(long, env = "R14_ROLLBACK_TO")]
+    pub rollback_to: Option<u64>,
+    /// Print the resolved configuration as TOML and exit
+    #[arg(long)]
+    pub print_config: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Rebuild the tree from the stored leaves, check it against the roots
+    /// recorded while indexing and the contract's latest root, and exit
+    /// non-zero on a divergence
+    Verify,
+    /// Move the indexed state between databases as one file
+    #[command(subcommand)]
+    Snapshot(SnapshotCommand),
+    /// Print the HTTP API's OpenAPI document, as `/v1/openapi.json` serves
+    /// it, and exit
+    Openapi,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum SnapshotCommand {
+    /// Write the leaves, nullifiers, recorded roots and cursor to a file
+    Export { path: PathBuf },
+    /// Load a snapshot into a database that holds no leaves yet, checking
+    /// its root and contract first
+    Import { path: PathBuf },
+}
+
+/// The `--config` file; every field is optional
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    pub rpc_url: Option<String>,
+    pub rpc_fallbacks: Option<Vec<String>>,
+    pub contract_id: Option<String>,
+    pub hash: Option<HashFamily>,
+    pub poll_interval_secs: Option<u64>,
+    pub db_url: Option<String>,
+    pub listen_addr: Option<String>,
+    pub backfill_from: Option<u64>,
+    pub confirmations: Option<u64>,
+    pub log_level: Option<String>,
+    pub drain_timeout_secs: Option<u64>,
+    pub signing_key_file: Option<PathBuf>,
+    pub prune_depth: Option<u64>,
+    pub archive: Option<bool>,
+}
+
+impl FileConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+        toml::from_str(&data).with_context(|| format!("in {}", path.display()))
+    }
+}
+
+/// Settings the indexer runs with, after precedence and validation
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Config {
+    pub rpc_url: String,
+    /// Endpoints to fail over to when `rpc_url` errors or times out
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rpc_fallbacks: Vec<String>,
+    pub contract_id: String,
+    pub hash: HashFamily,
+    pub poll_interval_secs: u64,
+    pub db_url: String,
+    pub listen_addr: SocketAddr,
+    /// First ledger to index when there is no cursor; `None` starts at the
+    /// latest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backfill_from: Option<u64>,
+    pub confirmations: u64,
+    pub log_level: String,
+    pub drain_timeout_secs: u64,
+    /// Key for `/v1/nullifier/{nullifier}/absence` statements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key_file: Option<PathBuf>,
+    /// Newest leaves the tree keeps in memory; `None` keeps them all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prune_depth: Option<u64>,
+}
+
+impl Config {
+    /// Merge `args` over `file` over the `r14.toml` `descriptor`, fill in
+    /// defaults, and validate. A contract or hash family that contradicts
+    /// the descriptor is an error.
+    pub fn resolve(args: &Args, file: FileConfig, descriptor: Option<(&Path, &Deployment)>) -> Result<Self> {
+        let contract_id = args.contract_id.clone().or(file.contract_id);
+        if let (Some(id), Some((path, d))) = (&contract_id, descriptor) {
+            ensure!(
+                *id == d.contracts.transfer,
+                "contract {id} but {} pins transfer contract {}",
+                path.display(),
+                d.contracts.transfer
+            );
+        }
+        let Some(contract_id) = contract_id.or_else(|| descriptor.map(|(_, d)| d.contracts.transfer.clone())) else {
+            bail!("no transfer contract: pass --contract-id, set R14_CONTRACT_ID, or add an r14.toml");
+        };
+
+        let hash = args.hash.or(file.hash);
+        if let (Some(hash), Some((path, d))) = (hash, descriptor) {
+            ensure!(
+                hash == d.hash,
+                "hash family {} but {} pins {}",
+                hash.name(),
+                path.display(),
+                d.hash.name()
+            );
+        }
+        let hash = hash.or_else(|| descriptor.map(|(_, d)| d.hash)).unwrap_or_default();
+
+        let rpc_url = args
+            .rpc_url
+            .clone()
+            .or(file.rpc_url)
+            .or_else(|| descriptor.map(|(_, d)| d.rpc_url.clone()))
+            .unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
+        let rpc_fallbacks = match &args.rpc_fallbacks[..] {
+            [] => file.rpc_fallbacks.unwrap_or_default(),
+            urls => urls.to_vec(),
+        };
+        for (i, url) in std::iter::once(&rpc_url).chain(&rpc_fallbacks).enumerate() {
+            ensure!(
+                url.starts_with("http://") || url.starts_with("https://"),
+                "rpc_url must be an http(s) URL, got `{url}`"
+            );
+            ensure!(
+                !std::iter::once(&rpc_url).chain(&rpc_fallbacks).take(i).any(|earlier| earlier == url),
+                "RPC endpoint `{url}` is listed twice"
+            );
+        }
+
+        let poll_interval_secs = args.poll_interval_secs.or(file.poll_interval_secs).unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+        ensure!(poll_interval_secs > 0, "poll_interval_secs must be at least 1");
+
+        let listen_addr = args.listen_addr.clone().or(file.listen_addr).unwrap_or_else(|| DEFAULT_LISTEN_ADDR.to_string());
+        let listen_addr = listen_addr
+            .parse()
+            .with_context(|| format!("listen_addr `{listen_addr}` is not an ip:port address"))?;
+
+        let backfill_from = args.backfill_from.or(file.backfill_from);
+        ensure!(backfill_from != Some(0), "backfill_from must be a ledger, at least 1");
+
+        let log_level = args.log_level.clone().or(file.log_level).unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+        tracing_subscriber::EnvFilter::try_new(&log_level)
+            .with_context(|| format!("log_level `{log_level}` is not a valid filter"))?;
+
+        let archive = args.archive || file.archive == Some(true);
+        let prune_depth = args.prune_depth.or(file.prune_depth).filter(|_| !archive);
+        ensure!(
+            prune_depth.is_none_or(|depth| depth >= 1 << PRUNED_LEVELS),
+            "prune_depth must be at least {} leaves",
+            1 << PRUNED_LEVELS
+        );
+
+        Ok(Self {
+            rpc_url,
+            rpc_fallbacks,
+            contract_id,
+            hash,
+            poll_interval_secs,
+            db_url: args
+                .db_url
+                .clone()
+                .or_else(|| args.db_path.clone())
+                .or(file.db_url)
+                .unwrap_or_else(|| DEFAULT_DB_URL.to_string()),
+            listen_addr,
+            backfill_from,
+            confirmations: args.confirmations.or(file.confirmations).unwrap_or(0),
+            log_level,
+            drain_timeout_secs: args
+                .drain_timeout_secs
+                .or(file.drain_timeout_secs)
+                .unwrap_or(DEFAULT_DRAIN_TIMEOUT_SECS),
+            signing_key_file: args.signing_key_file.clone().or(file.signing_key_file),
+            prune_depth,
+        })
+    }
+
+    /// `rpc_url`, then the fallbacks
+    pub fn rpc_urls(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::once(&self.rpc_url).chain(&self.rpc_fallbacks).cloned()
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_secs)
+    }
+
+    /// The config as a file `--config` accepts
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("config serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = "CDEPLOYEDTRANSFER";
+
+    fn descriptor() -> Deployment {
+        Deployment::from_toml_str(&format!(
+            r#"
+network = "testnet"
+rpc_url = "https://rpc.example.org"
+indexer_url = "http://localhost:3000"
+
+[contracts]
+core = "CCORE"
+transfer = "{CONTRACT}"
+
+[circuits.transfer]
+circuit_id = "00"
+"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn flags_beat_the_file_which_beats_the_descriptor() {
+        let d = descriptor();
+        let file = FileConfig {
+            rpc_url: Some("https://file.example.org".into()),
+            rpc_fallbacks: Some(vec!["https://fallback.example.org".into()]),
+            poll_interval_secs: Some(9),
+            db_url: Some("postgres://db/r14".into()),
+            backfill_from: Some(51_200),
+            confirmations: Some(4),
+            signing_key_file: Some("keys/indexer.key".into()),
+            prune_depth: Some(100_000),
+            ..FileConfig::default()
+        };
+        let args = Args { poll_interval_secs: Some(2), ..Args::default() };
+        let config = Config::resolve(&args, file, Some((Path::new("r14.toml"), &d))).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                rpc_url: "https://file.example.org".into(),
+                rpc_fallbacks: vec!["https://fallback.example.org".into()],
+                contract_id: CONTRACT.into(),
+                hash: HashFamily::Poseidon,
+                poll_interval_secs: 2,
+                db_url: "postgres://db/r14".into(),
+                listen_addr: DEFAULT_LISTEN_ADDR.parse().unwrap(),
+                backfill_from: Some(51_200),
+                confirmations: 4,
+                log_level: DEFAULT_LOG_LEVEL.into(),
+                drain_timeout_secs: DEFAULT_DRAIN_TIMEOUT_SECS,
+                signing_key_file: Some("keys/indexer.key".into()),
+                prune_depth: Some(100_000),
+            }
+        );
+
+        // what --print-config writes loads back as the same config
+        let printed: FileConfig = toml::from_str(&config.to_toml()).unwrap();
+        assert_eq!(Config::resolve(&Args::default(), printed, None).unwrap(), config);
+
+        let args = Args::try_parse_from(["r14-indexer", "--rpc-fallback", "https://a.example.org,https://b.example.org"]);
+        assert_eq!(args.unwrap().rpc_fallbacks.len(), 2);
+
+        // archive mode keeps everything, even over a file that prunes
+        let file = FileConfig { contract_id: Some(CONTRACT.into()), prune_depth: Some(100_000), ..FileConfig::default() };
+        let args = Args { archive: true, ..Args::default() };
+        assert_eq!(Config::resolve(&args, file, None).unwrap().prune_depth, None);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let d = descriptor();
+        let with = |args: Args| Config::resolve(&args, FileConfig::default(), None).unwrap_err().to_string();
+        let contract = || Some(CONTRACT.to_string());
+
+        assert!(with(Args::default()).contains("no transfer contract"));
+        let err = Config::resolve(
+            &Args { contract_id: Some("COTHER".into()), ..Args::default() },
+            FileConfig::default(),
+            Some((Path::new("r14.toml"), &d)),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("pins transfer contract"), "{err}");
+        let err = Config::resolve(
+            &Args { hash: Some(HashFamily::Poseidon2), ..Args::default() },
+            FileConfig::default(),
+            Some((Path::new("r14.toml"), &d)),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("pins poseidon"), "{err}");
+        assert!(with(Args { contract_id: contract(), rpc_url: Some("rpc.example.org".into()), ..Args::default() })
+            .contains("http(s) URL"));
+        let fallbacks = |urls: &[&str]| urls.iter().map(|url| url.to_string()).collect();
+        assert!(with(Args { contract_id: contract(), rpc_fallbacks: fallbacks(&["ws://rpc"]), ..Args::default() })
+            .contains("http(s) URL"));
+        let twice = fallbacks(&["https://b.example.org", "https://b.example.org"]);
+        assert!(with(Args { contract_id: contract(), rpc_fallbacks: twice, ..Args::default() }).contains("listed twice"));
+        assert!(with(Args { contract_id: contract(), poll_interval_secs: Some(0), ..Args::default() }).contains("at least 1"));
+        assert!(with(Args { contract_id: contract(), backfill_from: Some(0), ..Args::default() }).contains("a ledger"));
+        assert!(with(Args { contract_id: contract(), listen_addr: Some("localhost".into()), ..Args::default() })
+            .contains("ip:port"));
+        assert!(with(Args { contract_id: contract(), log_level: Some("info,=bogus=".into()), ..Args::default() })
+            .contains("not a valid filter"));
+        assert!(with(Args { contract_id: contract(), prune_depth: Some(100), ..Args::default() }).contains("at least 1024"));
+        assert!(toml::from_str::<FileConfig>("rpc = \"typo\"").is_err());
+    }
+}
@@ -1,9 +1,186 @@
 use ark_bls12_381::Fr;
 use ark_ff::{BigInteger, PrimeField};
-use rusqlite::{params, Connection};
+use r14_errors::IndexerError;
+use r14_types::{Commitment, LeafIndex, MerkleRoot, Nullifier};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use utoipa::ToSchema;
 use std::path::Path;
 use std::sync::Mutex;
 
+use crate::events::IndexerEvent;
+use crate::reorg::{Checkpoint, CHECKPOINT_WINDOW};
+use crate::rpc::RawEvent;
+use crate::snapshot::Snapshot;
+
+pub type StorageResult<T> = Result<T, IndexerError>;
+
+/// A root the tree reached: `root` over the first `leaf_count` leaves,
+/// set by a call in `ledger`
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct HistoricalRoot {
+    #[schema(value_type = crate::api::FieldElement)]
+    pub root: MerkleRoot,
+    pub leaf_count: u64,
+    pub ledger: u64,
+}
+
+/// What [`Storage::stats`] counts
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StorageStats {
+    pub nullifiers: u64,
+    /// Logged `deposit` events. State imported from a snapshot has no
+    /// logged events, so isn't counted.
+    pub deposits: u64,
+    /// Logged `transfer` events
+    pub transfers: u64,
+    /// Ledger of the first leaf; leaves are stored in ledger order
+    pub first_ledger: Option<u64>,
+    /// Ledger the cursor has reached
+    pub last_ledger: Option<u64>,
+}
+
+/// [`StorageStats`] in one row, in SQL both backends accept
+pub(crate) const STATS_QUERY: &str = "SELECT (SELECT COUNT(*) FROM nullifiers),
+     (SELECT COUNT(*) FROM chain_events WHERE topic = 'deposit'),
+     (SELECT COUNT(*) FROM chain_events WHERE topic = 'transfer'),
+     (SELECT block_height FROM leaves ORDER BY idx LIMIT 1),
+     (SELECT last_ledger FROM sync_cursor WHERE id = 1)";
+
+/// Which contract events [`Storage::raw_events`] returns. Unset fields
+/// match everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RawEventFilter {
+    /// Only events after this id, to page through the log
+    pub after: Option<String>,
+    pub topic: Option<String>,
+    pub tx_hash: Option<String>,
+    pub from_ledger: Option<u64>,
+    pub to_ledger: Option<u64>,
+}
+
+/// Everything one poll indexed, for [`Storage::write_batch`]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Batch {
+    pub leaves: Vec<(LeafIndex, Commitment, u64)>,
+    pub nullifiers: Vec<(Nullifier, u64)>,
+    pub roots: Vec<HistoricalRoot>,
+    pub raw_events: Vec<RawEvent>,
+    /// Ledger and paging cursor the poll reached
+    pub cursor: Option<(u64, Option<String>)>,
+    pub checkpoint: Option<Checkpoint>,
+}
+
+/// Persistent indexer state: the leaves in tree order, spent nullifiers,
+/// the roots the tree passed through, the contract events they came from,
+/// the poller's sync cursor, checkpoints to roll back to, and the log of
+/// events published to subscribers.
+///
+/// Leaf, nullifier, root and contract event writes ignore a row that is
+/// already there, so
+/// several indexers following the same contract can share one database.
+/// Leaves are keyed by index alone: a commitment deposited twice is two
+/// leaves.
+pub trait Storage: Send + Sync {
+    fn insert_leaf(&self, idx: LeafIndex, commitment: Commitment, block_height: u64) -> StorageResult<()>;
+
+    fn load_leaves(&self) -> StorageResult<Vec<Commitment>>;
+
+    /// Up to `limit` leaves from index `from` on, in order
+    fn load_leaves_range(&self, from: u64, limit: usize) -> StorageResult<Vec<Commitment>>;
+
+    fn get_leaf_by_commitment(&self, commitment: Commitment) -> StorageResult<Option<(LeafIndex, u64)>>;
+
+    /// Number of leaves from `block_height` or earlier
+    fn count_leaves_through(&self, block_height: u64) -> StorageResult<usize>;
+
+    /// Record a spent nullifier; re-inserting one keeps the first block height
+    fn insert_nullifier(&self, nullifier: &Nullifier, block_height: u64) -> StorageResult<()>;
+
+    /// Block height at which `nullifier` was spent, if it has been
+    fn get_nullifier(&self, nullifier: &Nullifier) -> StorageResult<Option<u64>>;
+
+    /// Record a root transition; re-recording a leaf count keeps the first
+    fn insert_root(&self, root: &HistoricalRoot) -> StorageResult<()>;
+
+    /// Up to `limit` recorded roots, newest first
+    fn recent_roots(&self, limit: usize) -> StorageResult<Vec<HistoricalRoot>>;
+
+    /// Every recorded root, oldest first
+    fn all_roots(&self) -> StorageResult<Vec<HistoricalRoot>>;
+
+    /// The root recorded when the tree had `leaf_count` leaves
+    fn root_at(&self, leaf_count: u64) -> StorageResult<Option<HistoricalRoot>>;
+
+    /// The recorded transition to `root`
+    fn find_root(&self, root: &MerkleRoot) -> StorageResult<Option<HistoricalRoot>>;
+
+    /// Record a contract event as the RPC returned it; re-recording an id
+    /// keeps the first
+    fn insert_raw_event(&self, event: &RawEvent) -> StorageResult<()>;
+
+    /// Up to `limit` recorded contract events matching `filter`, in chain order
+    fn raw_events(&self, filter: &RawEventFilter, limit: usize) -> StorageResult<Vec<RawEvent>>;
+
+    fn save_cursor(&self, last_ledger: u64, cursor: Option<&str>) -> StorageResult<()>;
+
+    fn load_cursor(&self) -> StorageResult<Option<(u64, Option<String>)>>;
+
+    /// Totals over the stored nullifiers and contract events, and the
+    /// ledgers indexing started and has reached
+    fn stats(&self) -> StorageResult<StorageStats>;
+
+    /// Append `event` to the event log and return its sequence number.
+    /// Indexers sharing a database each append what they publish.
+    fn append_event(&self, event: &IndexerEvent) -> StorageResult<u64>;
+
+    /// Up to `limit` logged events after sequence number `after`, oldest first
+    fn events_after(&self, after: u64, limit: usize) -> StorageResult<Vec<(u64, IndexerEvent)>>;
+
+    /// Leaves, nullifiers, recorded roots and the cursor, read together,
+    /// as a snapshot of `contract_id`'s state. `None` before the first
+    /// cursor is saved.
+    fn export_snapshot(&self, contract_id: &str) -> StorageResult<Option<Snapshot>>;
+
+    /// Load `snapshot` into this storage, which must hold no leaves yet,
+    /// with a checkpoint at its ledger to roll back to
+    fn import_snapshot(&self, snapshot: &Snapshot) -> StorageResult<()>;
+
+    /// Record `checkpoint`, dropping those more than `CHECKPOINT_WINDOW`
+    /// ledgers older
+    fn save_checkpoint(&self, checkpoint: &Checkpoint) -> StorageResult<()>;
+
+    /// Write `batch` in one transaction, so a crash keeps all of a poll's
+    /// leaves, nullifiers and cursor or none of them
+    fn write_batch(&self, batch: &Batch) -> StorageResult<()>;
+
+    /// Latest checkpoint at or before `ledger`
+    fn checkpoint_at_or_before(&self, ledger: u64) -> StorageResult<Option<Checkpoint>>;
+
+    /// Forget everything indexed after `checkpoint`: later leaves,
+    /// nullifiers, roots, contract events and checkpoints go, and the
+    /// cursor restarts at its ledger.
+    /// The event log is kept.
+    fn rollback_to(&self, checkpoint: &Checkpoint) -> StorageResult<()>;
+}
+
+/// Open the storage `url` names: `postgres://` or `postgresql://` for
+/// Postgres (needs the `postgres` feature), otherwise a SQLite file path,
+/// optionally prefixed with `sqlite://`
+pub fn open(url: &str) -> StorageResult<Box<dyn Storage>> {
+    if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        #[cfg(feature = "postgres")]
+        return Ok(Box::new(crate::pg::PgDb::connect(url)?));
+        #[cfg(not(feature = "postgres"))]
+        return Err(IndexerError::Storage(
+            "Postgres storage needs r14-indexer built with `--features postgres`".into(),
+        ));
+    }
+    let path = url.strip_prefix("sqlite://").unwrap_or(url);
+    Ok(Box::new(Db::open(Path::new(path))?))
+}
+
+/// SQLite storage
 pub struct Db {
     conn: Mutex<Connection>,
 }
@@ -11,55 +188,99 @@ pub struct Db {
 impl Db {
     pub fn open(path: &Path) -> rusqlite::Result<Self> {
         let conn = Connection::open(path)?;
+        // readers such as `snapshot export` don't block the poller's writes;
+        // `NORMAL` still syncs at every checkpoint of the log, which is
+        // durable enough in WAL mode
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS leaves (
                 idx INTEGER PRIMARY KEY,
                 commitment BLOB NOT NULL,
                 block_height INTEGER NOT NULL
             );
+            CREATE INDEX IF NOT EXISTS leaves_by_commitment ON leaves (commitment);
+            CREATE TABLE IF NOT EXISTS nullifiers (
+                nullifier BLOB PRIMARY KEY,
+                block_height INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS roots (
+                leaf_count INTEGER PRIMARY KEY,
+                root BLOB NOT NULL,
+                ledger INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS roots_by_root ON roots (root);
+            CREATE TABLE IF NOT EXISTS chain_events (
+                id TEXT PRIMARY KEY,
+                topic TEXT NOT NULL,
+                tx_hash TEXT NOT NULL,
+                ledger INTEGER NOT NULL,
+                closed_at TEXT NOT NULL,
+                value_xdr TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS chain_events_by_tx ON chain_events (tx_hash);
             CREATE TABLE IF NOT EXISTS sync_cursor (
                 id INTEGER PRIMARY KEY CHECK (id = 1),
                 last_ledger INTEGER NOT NULL,
                 last_cursor TEXT
+            );
+            CREATE TABLE IF NOT EXISTS events (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                event TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS checkpoints (
+                ledger INTEGER PRIMARY KEY,
+                leaf_count INTEGER NOT NULL,
+                root BLOB NOT NULL
             );",
         )?;
         Ok(Self {
             conn: Mutex::new(conn),
         })
     }
+}
 
-    pub fn insert_leaf(&self, idx: usize, commitment: Fr, block_height: u64) -> rusqlite::Result<()> {
-        let bytes = fr_to_bytes(&commitment);
-        let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO leaves (idx, commitment, block_height) VALUES (?1, ?2, ?3)",
-            params![idx as i64, bytes, block_height as i64],
-        )?;
+impl Storage for Db {
+    fn insert_leaf(&self, idx: LeafIndex, commitment: Commitment, block_height: u64) -> StorageResult<()> {
+        put_leaf(&self.conn.lock().unwrap(), idx, &commitment, block_height)?;
         Ok(())
     }
 
-    pub fn load_leaves(&self) -> rusqlite::Result<Vec<Fr>> {
+    fn load_leaves(&self) -> StorageResult<Vec<Commitment>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare("SELECT commitment FROM leaves ORDER BY idx")?;
         let leaves = stmt
             .query_map([], |row| {
                 let bytes: Vec<u8> = row.get(0)?;
-                Ok(fr_from_bytes(&bytes))
+                Ok(Commitment(fr_from_bytes(&bytes)))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(leaves)
+    }
+
+    fn load_leaves_range(&self, from: u64, limit: usize) -> StorageResult<Vec<Commitment>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT commitment FROM leaves WHERE idx >= ?1 ORDER BY idx LIMIT ?2")?;
+        let leaves = stmt
+            .query_map(params![from as i64, limit.min(i64::MAX as usize) as i64], |row| {
+                let bytes: Vec<u8> = row.get(0)?;
+                Ok(Commitment(fr_from_bytes(&bytes)))
             })?
-            .collect::<rusqlite::Result<Vec<Fr>>>()?;
+            .collect::<rusqlite::Result<Vec<_>>>()?;
         Ok(leaves)
     }
 
-    pub fn get_leaf_by_commitment(&self, commitment: Fr) -> rusqlite::Result<Option<(usize, u64)>> {
-        let bytes = fr_to_bytes(&commitment);
+    fn get_leaf_by_commitment(&self, commitment: Commitment) -> StorageResult<Option<(LeafIndex, u64)>> {
+        let bytes = fr_to_bytes(&commitment.0);
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT idx, block_height FROM leaves WHERE commitment = ?1",
+            "SELECT idx, block_height FROM leaves WHERE commitment = ?1 ORDER BY idx LIMIT 1",
         )?;
         let mut rows = stmt.query_map(params![bytes], |row| {
             let idx: i64 = row.get(0)?;
             let height: i64 = row.get(1)?;
-            Ok((idx as usize, height as u64))
+            Ok((LeafIndex(idx as u64), height as u64))
         })?;
         match rows.next() {
             Some(row) => Ok(Some(row?)),
@@ -67,18 +288,123 @@ impl Db {
         }
     }
 
-    pub fn save_cursor(&self, last_ledger: u64, cursor: Option<&str>) -> rusqlite::Result<()> {
+    fn count_leaves_through(&self, block_height: u64) -> StorageResult<usize> {
         let conn = self.conn.lock().unwrap();
-        conn.execute(
-            "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
-             VALUES (1, ?1, ?2)
-             ON CONFLICT(id) DO UPDATE SET last_ledger = ?1, last_cursor = ?2",
-            params![last_ledger as i64, cursor],
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM leaves WHERE block_height <= ?1",
+            params![block_height as i64],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    fn insert_nullifier(&self, nullifier: &Nullifier, block_height: u64) -> StorageResult<()> {
+        put_nullifier(&self.conn.lock().unwrap(), nullifier, block_height)?;
+        Ok(())
+    }
+
+    fn get_nullifier(&self, nullifier: &Nullifier) -> StorageResult<Option<u64>> {
+        let bytes = fr_to_bytes(&nullifier.0);
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT block_height FROM nullifiers WHERE nullifier = ?1")?;
+        let mut rows = stmt.query_map(params![bytes], |row| {
+            let height: i64 = row.get(0)?;
+            Ok(height as u64)
+        })?;
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+
+    fn insert_root(&self, root: &HistoricalRoot) -> StorageResult<()> {
+        put_root(&self.conn.lock().unwrap(), root)?;
+        Ok(())
+    }
+
+    fn recent_roots(&self, limit: usize) -> StorageResult<Vec<HistoricalRoot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT leaf_count, root, ledger FROM roots ORDER BY leaf_count DESC LIMIT ?1")?;
+        let roots = stmt
+            .query_map(params![limit as i64], root_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(roots)
+    }
+
+    fn all_roots(&self) -> StorageResult<Vec<HistoricalRoot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT leaf_count, root, ledger FROM roots ORDER BY leaf_count")?;
+        let roots = stmt.query_map([], root_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(roots)
+    }
+
+    fn root_at(&self, leaf_count: u64) -> StorageResult<Option<HistoricalRoot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT leaf_count, root, ledger FROM roots WHERE leaf_count = ?1")?;
+        let mut rows = stmt.query_map(params![leaf_count as i64], root_from_row)?;
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+
+    fn find_root(&self, root: &MerkleRoot) -> StorageResult<Option<HistoricalRoot>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT leaf_count, root, ledger FROM roots WHERE root = ?1 ORDER BY leaf_count LIMIT 1")?;
+        let mut rows = stmt.query_map(params![fr_to_bytes(&root.0)], root_from_row)?;
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+
+    fn insert_raw_event(&self, event: &RawEvent) -> StorageResult<()> {
+        put_raw_event(&self.conn.lock().unwrap(), event)?;
+        Ok(())
+    }
+
+    fn raw_events(&self, filter: &RawEventFilter, limit: usize) -> StorageResult<Vec<RawEvent>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT id, topic, tx_hash, ledger, closed_at, value_xdr FROM chain_events
+             WHERE (?1 IS NULL OR id > ?1) AND (?2 IS NULL OR topic = ?2) AND (?3 IS NULL OR tx_hash = ?3)
+               AND (?4 IS NULL OR ledger >= ?4) AND (?5 IS NULL OR ledger <= ?5)
+             ORDER BY id LIMIT ?6",
         )?;
+        let events = stmt
+            .query_map(
+                params![
+                    filter.after,
+                    filter.topic,
+                    filter.tx_hash,
+                    filter.from_ledger.map(|l| l as i64),
+                    filter.to_ledger.map(|l| l as i64),
+                    limit as i64
+                ],
+                |row| {
+                    let ledger: i64 = row.get(3)?;
+                    Ok(RawEvent {
+                        id: row.get(0)?,
+                        topic: row.get(1)?,
+                        tx_hash: row.get(2)?,
+                        ledger: ledger as u64,
+                        closed_at: row.get(4)?,
+                        value_xdr: row.get(5)?,
+                    })
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(events)
+    }
+
+    fn save_cursor(&self, last_ledger: u64, cursor: Option<&str>) -> StorageResult<()> {
+        put_cursor(&self.conn.lock().unwrap(), last_ledger, cursor)?;
         Ok(())
     }
 
-    pub fn load_cursor(&self) -> rusqlite::Result<Option<(u64, Option<String>)>> {
+    fn load_cursor(&self) -> StorageResult<Option<(u64, Option<String>)>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
             "SELECT last_ledger, last_cursor FROM sync_cursor WHERE id = 1",
@@ -93,12 +419,249 @@ impl Db {
             None => Ok(None),
         }
     }
+
+    fn stats(&self) -> StorageResult<StorageStats> {
+        let conn = self.conn.lock().unwrap();
+        let stats = conn.query_row(STATS_QUERY, [], |row| {
+            Ok(StorageStats {
+                nullifiers: row.get::<_, i64>(0)? as u64,
+                deposits: row.get::<_, i64>(1)? as u64,
+                transfers: row.get::<_, i64>(2)? as u64,
+                first_ledger: row.get::<_, Option<i64>>(3)?.map(|l| l as u64),
+                last_ledger: row.get::<_, Option<i64>>(4)?.map(|l| l as u64),
+            })
+        })?;
+        Ok(stats)
+    }
+
+    fn append_event(&self, event: &IndexerEvent) -> StorageResult<u64> {
+        let json = encode_event(event)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute("INSERT INTO events (event) VALUES (?1)", params![json])?;
+        Ok(conn.last_insert_rowid() as u64)
+    }
+
+    fn events_after(&self, after: u64, limit: usize) -> StorageResult<Vec<(u64, IndexerEvent)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT seq, event FROM events WHERE seq > ?1 ORDER BY seq LIMIT ?2")?;
+        let rows = stmt
+            .query_map(params![after as i64, limit as i64], |row| {
+                let seq: i64 = row.get(0)?;
+                let json: String = row.get(1)?;
+                Ok((seq as u64, json))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.into_iter().map(|(seq, json)| Ok((seq, decode_event(&json)?))).collect()
+    }
+
+    fn export_snapshot(&self, contract_id: &str) -> StorageResult<Option<Snapshot>> {
+        let conn = self.conn.lock().unwrap();
+        // one read transaction, so a running indexer's writes land wholly
+        // before or after it
+        let tx = conn.unchecked_transaction()?;
+        let cursor = tx
+            .query_row("SELECT last_ledger, last_cursor FROM sync_cursor WHERE id = 1", [], |row| {
+                Ok((row.get::<_, i64>(0)? as u64, row.get::<_, Option<String>>(1)?))
+            })
+            .optional()?;
+        let Some(cursor) = cursor else {
+            return Ok(None);
+        };
+        let leaves = tx
+            .prepare("SELECT commitment, block_height FROM leaves ORDER BY idx")?
+            .query_map([], |row| {
+                Ok((Commitment(fr_from_bytes(&row.get::<_, Vec<u8>>(0)?)), row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let nullifiers = tx
+            .prepare("SELECT nullifier, block_height FROM nullifiers ORDER BY block_height, nullifier")?
+            .query_map([], |row| {
+                Ok((Nullifier(fr_from_bytes(&row.get::<_, Vec<u8>>(0)?)), row.get::<_, i64>(1)? as u64))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let roots = tx
+            .prepare("SELECT leaf_count, root, ledger FROM roots ORDER BY leaf_count")?
+            .query_map([], root_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(Some(Snapshot::new(contract_id, cursor, leaves, nullifiers, roots)))
+    }
+
+    fn import_snapshot(&self, snapshot: &Snapshot) -> StorageResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        let leaves: i64 = tx.query_row("SELECT COUNT(*) FROM leaves", [], |row| row.get(0))?;
+        if leaves > 0 {
+            return Err(IndexerError::Storage(format!("database already holds {leaves} leaves")));
+        }
+        {
+            let mut insert = tx.prepare("INSERT INTO leaves (idx, commitment, block_height) VALUES (?1, ?2, ?3)")?;
+            for (idx, (leaf, height)) in snapshot.leaves.iter().enumerate() {
+                insert.execute(params![idx as i64, fr_to_bytes(&leaf.0), *height as i64])?;
+            }
+            let mut insert =
+                tx.prepare("INSERT OR IGNORE INTO nullifiers (nullifier, block_height) VALUES (?1, ?2)")?;
+            for (nullifier, height) in &snapshot.nullifiers {
+                insert.execute(params![fr_to_bytes(&nullifier.0), *height as i64])?;
+            }
+            let mut insert = tx.prepare("INSERT OR IGNORE INTO roots (leaf_count, root, ledger) VALUES (?1, ?2, ?3)")?;
+            for root in &snapshot.roots {
+                insert.execute(params![root.leaf_count as i64, fr_to_bytes(&root.root.0), root.ledger as i64])?;
+            }
+        }
+        tx.execute(
+            "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
+             VALUES (1, ?1, ?2)
+             ON CONFLICT(id) DO UPDATE SET last_ledger = ?1, last_cursor = ?2",
+            params![snapshot.ledger as i64, snapshot.cursor],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO checkpoints (ledger, leaf_count, root) VALUES (?1, ?2, ?3)",
+            params![snapshot.ledger as i64, snapshot.leaves.len() as i64, fr_to_bytes(&snapshot.root.0)],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn save_checkpoint(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
+        put_checkpoint(&self.conn.lock().unwrap(), checkpoint)?;
+        Ok(())
+    }
+
+    fn write_batch(&self, batch: &Batch) -> StorageResult<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (idx, commitment, block_height) in &batch.leaves {
+            put_leaf(&tx, *idx, commitment, *block_height)?;
+        }
+        for (nullifier, block_height) in &batch.nullifiers {
+            put_nullifier(&tx, nullifier, *block_height)?;
+        }
+        for root in &batch.roots {
+            put_root(&tx, root)?;
+        }
+        for event in &batch.raw_events {
+            put_raw_event(&tx, event)?;
+        }
+        if let Some((ledger, cursor)) = &batch.cursor {
+            put_cursor(&tx, *ledger, cursor.as_deref())?;
+        }
+        if let Some(checkpoint) = &batch.checkpoint {
+            put_checkpoint(&tx, checkpoint)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn checkpoint_at_or_before(&self, ledger: u64) -> StorageResult<Option<Checkpoint>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT ledger, leaf_count, root FROM checkpoints WHERE ledger <= ?1 ORDER BY ledger DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map(params![ledger as i64], |row| {
+            let ledger: i64 = row.get(0)?;
+            let leaf_count: i64 = row.get(1)?;
+            let root: Vec<u8> = row.get(2)?;
+            Ok(Checkpoint {
+                ledger: ledger as u64,
+                leaf_count: leaf_count as u64,
+                root: MerkleRoot(fr_from_bytes(&root)),
+            })
+        })?;
+        match rows.next() {
+            Some(row) => Ok(Some(row?)),
+            None => Ok(None),
+        }
+    }
+
+    fn rollback_to(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
+        let conn = self.conn.lock().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM leaves WHERE idx >= ?1", params![checkpoint.leaf_count as i64])?;
+        tx.execute("DELETE FROM nullifiers WHERE block_height > ?1", params![checkpoint.ledger as i64])?;
+        tx.execute("DELETE FROM roots WHERE leaf_count > ?1", params![checkpoint.leaf_count as i64])?;
+        tx.execute("DELETE FROM chain_events WHERE ledger > ?1", params![checkpoint.ledger as i64])?;
+        tx.execute("DELETE FROM checkpoints WHERE ledger > ?1", params![checkpoint.ledger as i64])?;
+        tx.execute(
+            "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
+             VALUES (1, ?1, NULL)
+             ON CONFLICT(id) DO UPDATE SET last_ledger = ?1, last_cursor = NULL",
+            params![checkpoint.ledger as i64],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn put_leaf(conn: &Connection, idx: LeafIndex, commitment: &Commitment, block_height: u64) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT OR IGNORE INTO leaves (idx, commitment, block_height) VALUES (?1, ?2, ?3)",
+    )?
+    .execute(params![idx.0 as i64, fr_to_bytes(&commitment.0), block_height as i64])?;
+    Ok(())
+}
+
+fn put_nullifier(conn: &Connection, nullifier: &Nullifier, block_height: u64) -> rusqlite::Result<()> {
+    conn.prepare_cached("INSERT OR IGNORE INTO nullifiers (nullifier, block_height) VALUES (?1, ?2)")?
+        .execute(params![fr_to_bytes(&nullifier.0), block_height as i64])?;
+    Ok(())
+}
+
+fn put_root(conn: &Connection, root: &HistoricalRoot) -> rusqlite::Result<()> {
+    conn.prepare_cached("INSERT OR IGNORE INTO roots (leaf_count, root, ledger) VALUES (?1, ?2, ?3)")?
+        .execute(params![root.leaf_count as i64, fr_to_bytes(&root.root.0), root.ledger as i64])?;
+    Ok(())
+}
+
+fn put_raw_event(conn: &Connection, event: &RawEvent) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT OR IGNORE INTO chain_events (id, topic, tx_hash, ledger, closed_at, value_xdr)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?
+    .execute(params![event.id, event.topic, event.tx_hash, event.ledger as i64, event.closed_at, event.value_xdr])?;
+    Ok(())
+}
+
+fn put_cursor(conn: &Connection, last_ledger: u64, cursor: Option<&str>) -> rusqlite::Result<()> {
+    conn.prepare_cached(
+        "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
+         VALUES (1, ?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET last_ledger = ?1, last_cursor = ?2",
+    )?
+    .execute(params![last_ledger as i64, cursor])?;
+    Ok(())
 }
 
-fn fr_to_bytes(fr: &Fr) -> Vec<u8> {
+fn put_checkpoint(conn: &Connection, checkpoint: &Checkpoint) -> rusqlite::Result<()> {
+    conn.prepare_cached("INSERT OR REPLACE INTO checkpoints (ledger, leaf_count, root) VALUES (?1, ?2, ?3)")?
+        .execute(params![checkpoint.ledger as i64, checkpoint.leaf_count as i64, fr_to_bytes(&checkpoint.root.0)])?;
+    conn.prepare_cached("DELETE FROM checkpoints WHERE ledger < ?1")?
+        .execute(params![checkpoint.ledger.saturating_sub(CHECKPOINT_WINDOW) as i64])?;
+    Ok(())
+}
+
+fn root_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoricalRoot> {
+    let leaf_count: i64 = row.get(0)?;
+    let root: Vec<u8> = row.get(1)?;
+    let ledger: i64 = row.get(2)?;
+    Ok(HistoricalRoot {
+        root: MerkleRoot(fr_from_bytes(&root)),
+        leaf_count: leaf_count as u64,
+        ledger: ledger as u64,
+    })
+}
+
+pub(crate) fn fr_to_bytes(fr: &Fr) -> Vec<u8> {
     fr.into_bigint().to_bytes_be()
 }
 
-fn fr_from_bytes(bytes: &[u8]) -> Fr {
+pub(crate) fn fr_from_bytes(bytes: &[u8]) -> Fr {
     Fr::from_be_bytes_mod_order(bytes)
 }
+
+pub(crate) fn encode_event(event: &IndexerEvent) -> StorageResult<String> {
+    serde_json::to_string(event).map_err(|e| IndexerError::Storage(format!("encoding event: {e}")))
+}
+
+pub(crate) fn decode_event(json: &str) -> StorageResult<IndexerEvent> {
+    serde_json::from_str(json).map_err(|e| IndexerError::Storage(format!("decoding event: {e}")))
+}
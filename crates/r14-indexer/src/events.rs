@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use r14_types::{Commitment, LeafIndex, MerkleRoot, Nullifier};
+
+/// Buffered events per subscriber before it starts lagging
+pub const EVENT_CAPACITY: usize = 1024;
+
+/// Update pushed to `/v1/subscribe` clients as the poller indexes events
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndexerEvent {
+    Leaf {
+        #[schema(value_type = u64)]
+        index: LeafIndex,
+        #[schema(value_type = crate::api::FieldElement)]
+        commitment: Commitment,
+        block_height: u64,
+    },
+    Root {
+        #[schema(value_type = crate::api::FieldElement)]
+        root: MerkleRoot,
+        leaf_count: usize,
+    },
+    Nullifier {
+        #[schema(value_type = crate::api::FieldElement)]
+        nullifier: Nullifier,
+        block_height: u64,
+    },
+    /// Everything after `ledger` was dropped; the tree is back to
+    /// `leaf_count` leaves and `root`. Clients should resync.
+    Rollback {
+        ledger: u64,
+        leaf_count: usize,
+        #[schema(value_type = crate::api::FieldElement)]
+        root: MerkleRoot,
+    },
+}
+
+impl IndexerEvent {
+    pub fn leaf(index: LeafIndex, commitment: Commitment, block_height: u64) -> Self {
+        Self::Leaf {
+            index,
+            commitment,
+            block_height,
+        }
+    }
+
+    pub fn root(root: MerkleRoot, leaf_count: usize) -> Self {
+        Self::Root { root, leaf_count }
+    }
+
+    pub fn nullifier(nullifier: Nullifier, block_height: u64) -> Self {
+        Self::Nullifier {
+            nullifier,
+            block_height,
+        }
+    }
+
+    pub fn rollback(ledger: u64, leaf_count: usize, root: MerkleRoot) -> Self {
+        Self::Rollback {
+            ledger,
+            leaf_count,
+            root,
+        }
+    }
+
+    /// SSE event name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Leaf { .. } => "leaf",
+            Self::Root { .. } => "root",
+            Self::Nullifier { .. } => "nullifier",
+            Self::Rollback { .. } => "rollback",
+        }
+    }
+}
+
+pub fn channel() -> broadcast::Sender<IndexerEvent> {
+    broadcast::channel(EVENT_CAPACITY).0
+}
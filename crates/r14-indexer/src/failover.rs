@@ -0,0 +1,185 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// How long one RPC request may take before it counts as a failure
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long an endpoint is skipped after a failure; doubles with each
+/// consecutive one
+pub const BASE_BACKOFF: Duration = Duration::from_secs(1);
+pub const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Soroban RPC endpoints, tried in order. An endpoint that fails is skipped
+/// until its backoff ends and the request moves on to the next, so the
+/// first endpoint is preferred again as soon as it recovers.
+pub struct RpcPool {
+    client: Client,
+    endpoints: Vec<Endpoint>,
+    /// Index of the endpoint that answered last
+    last_used: AtomicUsize,
+}
+
+struct Endpoint {
+    url: String,
+    breaker: Mutex<Breaker>,
+}
+
+/// Consecutive failures of one endpoint, and until when it is skipped
+#[derive(Debug, Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl Breaker {
+    fn is_open(&self, now: Instant) -> bool {
+        self.open_until.is_some_and(|until| now < until)
+    }
+
+    fn succeeded(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Record a failure and open the breaker; how long for
+    fn failed(&mut self, now: Instant) -> Duration {
+        self.failures = self.failures.saturating_add(1);
+        let backoff = BASE_BACKOFF.saturating_mul(1 << (self.failures - 1).min(16)).min(MAX_BACKOFF);
+        self.open_until = Some(now + backoff);
+        backoff
+    }
+}
+
+impl RpcPool {
+    /// A pool over `urls`, most preferred first
+    pub fn new(urls: impl IntoIterator<Item = String>) -> Self {
+        let client = Client::builder().timeout(REQUEST_TIMEOUT).build().expect("failed to build HTTP client");
+        let endpoints: Vec<_> = urls
+            .into_iter()
+            .map(|url| Endpoint { url, breaker: Mutex::default() })
+            .collect();
+        assert!(!endpoints.is_empty(), "an RPC pool needs an endpoint");
+        Self { client, endpoints, last_used: AtomicUsize::new(0) }
+    }
+
+    /// URL of the endpoint that answered last
+    pub fn last_used(&self) -> &str {
+        &self.endpoints[self.last_used.load(Ordering::Relaxed)].url
+    }
+
+    /// POST `body` to the first endpoint that isn't backing off. A transport
+    /// error, timeout, error status or unreadable body moves on to the next.
+    /// JSON-RPC errors are the caller's to handle, since every endpoint
+    /// would give the same one for a bad request.
+    pub async fn post<T: DeserializeOwned>(&self, body: &impl Serialize) -> anyhow::Result<T> {
+        let mut last_error = None;
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            if endpoint.breaker.lock().unwrap().is_open(Instant::now()) {
+                continue;
+            }
+            match self.post_to(&endpoint.url, body).await {
+                Ok(resp) => {
+                    endpoint.breaker.lock().unwrap().succeeded();
+                    let previous = self.last_used.swap(i, Ordering::Relaxed);
+                    if previous != i {
+                        tracing::info!(from = %self.endpoints[previous].url, to = %endpoint.url, "switched RPC endpoint");
+                    }
+                    return Ok(resp);
+                }
+                Err(e) => {
+                    let backoff = endpoint.breaker.lock().unwrap().failed(Instant::now());
+                    tracing::warn!(
+                        url = %endpoint.url,
+                        error = %e,
+                        backoff_secs = backoff.as_secs(),
+                        "RPC endpoint failed"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(match last_error {
+            Some(e) => e.context("every RPC endpoint failed"),
+            None => anyhow::anyhow!("every RPC endpoint is backing off; retry in {:?}", self.next_retry()),
+        })
+    }
+
+    async fn post_to<T: DeserializeOwned>(&self, url: &str, body: &impl Serialize) -> anyhow::Result<T> {
+        Ok(self.client.post(url).json(body).send().await?.error_for_status()?.json().await?)
+    }
+
+    /// Time until the first breaker closes
+    fn next_retry(&self) -> Duration {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .filter_map(|e| e.breaker.lock().unwrap().open_until)
+            .map(|until| until.saturating_duration_since(now))
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let now = Instant::now();
+        let mut breaker = Breaker::default();
+        assert!(!breaker.is_open(now));
+        let backoffs: Vec<_> = (0..8).map(|_| breaker.failed(now).as_secs()).collect();
+        assert_eq!(backoffs, [1, 2, 4, 8, 16, 32, 60, 60]);
+        assert!(breaker.is_open(now) && !breaker.is_open(now + MAX_BACKOFF));
+
+        breaker.succeeded();
+        assert!(!breaker.is_open(now));
+        assert_eq!(breaker.failed(now), BASE_BACKOFF);
+    }
+
+    /// An endpoint answering every POST with `status`, counting requests
+    async fn serve(status: u16) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::post(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    let status = axum::http::StatusCode::from_u16(status).unwrap();
+                    (status, axum::Json(serde_json::json!({ "result": { "sequence": 42 } })))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn failed_endpoints_are_skipped_until_their_backoff_ends() {
+        let (down, down_hits) = serve(503).await;
+        let (up, up_hits) = serve(200).await;
+        let pool = RpcPool::new([down.clone(), up.clone()]);
+
+        let resp: serde_json::Value = pool.post(&serde_json::json!({})).await.unwrap();
+        assert_eq!(resp["result"]["sequence"], 42);
+        assert_eq!(pool.last_used(), up);
+        // the failed endpoint is backing off, so the next request skips it
+        let _: serde_json::Value = pool.post(&serde_json::json!({})).await.unwrap();
+        assert_eq!((down_hits.load(Ordering::Relaxed), up_hits.load(Ordering::Relaxed)), (1, 2));
+
+        let pool = RpcPool::new([down]);
+        let err = pool.post::<serde_json::Value>(&serde_json::json!({})).await.unwrap_err();
+        assert!(format!("{err:#}").contains("503"), "{err:#}");
+        let err = pool.post::<serde_json::Value>(&serde_json::json!({})).await.unwrap_err();
+        assert!(err.to_string().contains("backing off"), "{err}");
+        assert_eq!(down_hits.load(Ordering::Relaxed), 2);
+    }
+}
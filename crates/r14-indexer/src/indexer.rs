@@ -0,0 +1,332 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use axum::Router;
+use r14_errors::IndexerError;
+use r14_types::{Commitment, Nullifier};
+use tokio::sync::{watch, RwLock};
+use tracing::{debug, error, info, warn};
+
+use crate::api::{self, AppState, SharedState};
+use crate::config::Config;
+use crate::db::Storage;
+use crate::failover::RpcPool;
+use crate::reorg::{self, Checkpoint, Divergence};
+use crate::rpc::{operation_id, ContractEvent, RpcClient};
+use crate::signing::AbsenceSigner;
+use crate::tree::SparseMerkleTree;
+
+/// The poller and the state it indexes into, for the `r14-indexer` binary
+/// or a service embedding it. Serve [`Self::router`] and drive
+/// [`Self::run`].
+pub struct Indexer<R = RpcPool> {
+    state: SharedState,
+    rpc: R,
+    config: Config,
+}
+
+impl<R: RpcClient> Indexer<R> {
+    /// Rebuild the tree from `storage`, to index `config`'s contract
+    /// through `rpc`. Fails if the leaves or the signing key can't be read.
+    pub fn new(config: Config, storage: Box<dyn Storage>, rpc: R) -> Result<Self, IndexerError> {
+        let leaves = storage.load_leaves()?;
+        let leaf_count = leaves.len();
+        let tree = SparseMerkleTree::from_leaves(leaves);
+        info!(leaves = leaf_count, root = ?tree.root(), "rebuilt tree");
+
+        let mut app = AppState::with_storage(tree, storage);
+        app.confirmations = config.confirmations;
+        app.prune_depth = config.prune_depth.map(|depth| usize::try_from(depth).unwrap_or(usize::MAX));
+        app.prune();
+        if app.tree.pruned() > 0 {
+            info!(pruned = app.tree.pruned(), "pruned leaves from memory");
+        }
+        if let Some(path) = &config.signing_key_file {
+            let signer = AbsenceSigner::from_file(path, &config.contract_id)?;
+            info!(public_key = %hex::encode(signer.public_key()), "signing nullifier absence statements");
+            app.signer = Some(signer);
+        }
+        Ok(Self { state: Arc::new(RwLock::new(app)), rpc, config })
+    }
+
+    pub fn state(&self) -> SharedState {
+        self.state.clone()
+    }
+
+    /// The HTTP API over this indexer's state
+    pub fn router(&self) -> Router {
+        api::router(self.state())
+    }
+
+    /// Roll back to the newest checkpoint at or before `ledger`
+    pub async fn rollback_to(&self, ledger: u64) -> Result<Checkpoint, IndexerError> {
+        let mut s = self.state.write().await;
+        let checkpoint = s
+            .db
+            .checkpoint_at_or_before(ledger)?
+            .ok_or_else(|| IndexerError::NotFound(format!("no checkpoint at or before ledger {ledger}")))?;
+        s.rollback_to(&checkpoint)?;
+        info!(
+            ledger = checkpoint.ledger,
+            leaves = checkpoint.leaf_count,
+            root = ?checkpoint.root,
+            "rolled back to checkpoint"
+        );
+        Ok(checkpoint)
+    }
+
+    /// Poll the contract's events into the state until `shutdown` is set,
+    /// then save the cursor. Fails before the first poll if the cursor
+    /// can't be read or `backfill_from` is outside the RPC's retention.
+    pub async fn run(self, shutdown: watch::Receiver<bool>) -> anyhow::Result<()> {
+        let cursor = self.state.read().await.db.load_cursor()?;
+        self.check_backfill(cursor.as_ref()).await?;
+        poll(&self.state, &self.rpc, cursor, &self.config, shutdown).await;
+        Ok(())
+    }
+
+    /// A backfill only works while the RPC still has its start ledger
+    async fn check_backfill(&self, cursor: Option<&(u64, Option<String>)>) -> anyhow::Result<()> {
+        let Some(from) = self.config.backfill_from else {
+            return Ok(());
+        };
+        if let Some((ledger, _)) = cursor {
+            info!(
+                ledger,
+                backfill_from = from,
+                "resuming from the saved cursor; backfill_from only applies to an empty database"
+            );
+            return Ok(());
+        }
+        match self.rpc.health().await {
+            Ok(health) if from < health.oldest_ledger => anyhow::bail!(
+                "backfill_from {from} is before the RPC's oldest ledger {}; import a snapshot or use an RPC \
+                 with longer retention",
+                health.oldest_ledger
+            ),
+            Ok(health) if from > health.latest_ledger => {
+                anyhow::bail!("backfill_from {from} is past the latest ledger {}", health.latest_ledger)
+            }
+            Ok(health) => info!(
+                backfill_from = from,
+                oldest_ledger = health.oldest_ledger,
+                ledgers = health.latest_ledger - from + 1,
+                "backfilling"
+            ),
+            Err(e) => warn!(error = %e, "could not check the RPC's retention window, backfilling anyway"),
+        }
+        Ok(())
+    }
+}
+
+/// The poller: page through the contract's events from `initial_cursor`,
+/// or from `backfill_from` or the latest ledger without one, rolling back
+/// when the chain diverges
+async fn poll<R: RpcClient>(
+    state: &SharedState,
+    rpc: &R,
+    initial_cursor: Option<(u64, Option<String>)>,
+    config: &Config,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let contract_id = config.contract_id.as_str();
+
+    let resumed = initial_cursor.is_some();
+    let (mut start_ledger, mut cursor) = match (initial_cursor, config.backfill_from) {
+        (Some((ledger, c)), _) => (ledger, c),
+        (None, Some(from)) => {
+            info!(ledger = from, "no cursor, backfilling from the configured ledger");
+            (from, None)
+        }
+        (None, None) => loop {
+            // First run: get latest ledger as starting point
+            match rpc.latest_ledger().await {
+                Ok(seq) => {
+                    info!(ledger = seq, "no cursor, starting from the latest ledger");
+                    break (seq, None);
+                }
+                Err(e) => {
+                    warn!(error = %e, "failed to get latest ledger, retrying");
+                    tokio::select! {
+                        _ = tokio::time::sleep(config.poll_interval()) => {}
+                        _ = shutdown.wait_for(|&stop| stop) => return,
+                    }
+                }
+            }
+        },
+    };
+    // with a cursor, `start_ledger` has been indexed; on a first run it hasn't
+    let mut last = {
+        let s = state.read().await;
+        match s.db.checkpoint_at_or_before(start_ledger) {
+            Ok(Some(checkpoint)) if checkpoint.ledger == start_ledger => checkpoint,
+            _ if resumed => s.checkpoint(start_ledger),
+            _ => s.checkpoint(start_ledger.saturating_sub(1)),
+        }
+    };
+
+    // while pages come back full, fetch the next one without waiting
+    let mut catching_up = false;
+    // endpoint the last poll was served by; a fallback may lag it
+    let mut indexed_by: Option<String> = None;
+    loop {
+        if !catching_up {
+            tokio::select! {
+                _ = tokio::time::sleep(config.poll_interval()) => {}
+                _ = shutdown.wait_for(|&stop| stop) => break,
+            }
+        } else if *shutdown.borrow() {
+            break;
+        }
+
+        let started = Instant::now();
+        let result = match rpc.poll_events(contract_id, start_ledger, cursor.as_deref()).await {
+            Ok(r) => r,
+            Err(e) => {
+                warn!(ledger = start_ledger, error = %e, "event poll failed");
+                catching_up = false;
+                continue;
+            }
+        };
+        debug!(
+            ledger = start_ledger,
+            latest_ledger = result.latest_ledger,
+            events = result.events.len(),
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "polled contract events"
+        );
+
+        let ids = result.events.iter().map(|ev| (ev.raw().id.as_str(), ev.raw().ledger));
+        if let Some(divergence) = reorg::detect(&last, cursor.as_deref(), result.latest_ledger, result.oldest_ledger, ids) {
+            let switched = indexed_by.as_deref().is_some_and(|url| url != rpc.endpoint());
+            if switched && matches!(divergence, Divergence::Rewound { .. }) {
+                warn!(%divergence, url = rpc.endpoint(), "RPC endpoint is behind the indexed ledger, waiting for it");
+                catching_up = false;
+                continue;
+            }
+            if let Some(checkpoint) = roll_back(state, &divergence).await {
+                (start_ledger, cursor) = (checkpoint.ledger, None);
+                last = checkpoint;
+            }
+            catching_up = false;
+            continue;
+        }
+
+        indexed_by = Some(rpc.endpoint().to_string());
+
+        // an unpaged poll re-reads the last indexed ledger
+        let paged = cursor.is_some();
+        let events: Vec<_> =
+            result.events.iter().filter(|ev| paged || reorg::is_new(&last, ev.raw().ledger)).collect();
+        // the poll's writes and its cursor are committed together
+        let mut s = state.write().await;
+        s.begin_batch();
+        if !events.is_empty() {
+            let (mut transfers, mut leaves) = (0, 0);
+            for (i, ev) in events.iter().enumerate() {
+                s.record_event(ev.raw());
+                match ev {
+                    ContractEvent::Transfer(ev) => {
+                        s.index_nullifier(Nullifier(Fr::from_be_bytes_mod_order(&ev.nullifier)), ev.ledger);
+                        s.index_leaf(Commitment(Fr::from_be_bytes_mod_order(&ev.cm_0)), ev.ledger);
+                        s.index_leaf(Commitment(Fr::from_be_bytes_mod_order(&ev.cm_1)), ev.ledger);
+                        transfers += 1;
+                        leaves += 2;
+                    }
+                    ContractEvent::Deposit(ev) => {
+                        s.index_leaf(Commitment(Fr::from_be_bytes_mod_order(&ev.cm)), ev.ledger);
+                        leaves += 1;
+                    }
+                }
+                // a batch deposit emits one event per leaf but sets one root
+                if ends_call(&events, i, |ev| &ev.raw().id) {
+                    s.record_root(ev.raw().ledger);
+                }
+            }
+            s.publish_root();
+            info!(
+                transfers,
+                deposits = events.len() - transfers,
+                leaves,
+                root = ?s.tree.root(),
+                "indexed contract events"
+            );
+        }
+
+        // a full page may end partway through a ledger, so only the ones
+        // before it are done; the cursor picks up the rest. The tree may
+        // then hold part of the next ledger, which a checkpoint at this
+        // one mustn't roll back to.
+        let checkpoint = s.checkpoint(result.read_through);
+        let saved = (!result.more).then(|| checkpoint.clone());
+        if let Err(e) = s.commit_batch((result.read_through, result.cursor.clone()), saved) {
+            error!(ledger = start_ledger, error = %e, "failed to store the poll, polling it again");
+            catching_up = false;
+            continue;
+        }
+        s.ledger = result.read_through;
+        drop(s);
+
+        (start_ledger, cursor, last) = (result.read_through, result.cursor, checkpoint);
+        if result.more {
+            info!(
+                ledger = start_ledger,
+                latest_ledger = result.latest_ledger,
+                remaining = result.latest_ledger.saturating_sub(start_ledger),
+                "catching up"
+            );
+        } else if catching_up {
+            info!(ledger = start_ledger, "caught up");
+        }
+        catching_up = result.more;
+    }
+
+    // stopped between polls, so everything up to the cursor is indexed
+    let s = state.read().await;
+    match s.db.save_cursor(start_ledger, cursor.as_deref()) {
+        Ok(()) => info!(ledger = start_ledger, "poller stopped, cursor saved"),
+        Err(e) => error!(ledger = start_ledger, error = %e, "failed to save cursor on shutdown"),
+    }
+}
+
+/// Whether `events[i]` is the last event its contract call emitted
+fn ends_call<T>(events: &[T], i: usize, id: impl Fn(&T) -> &str) -> bool {
+    events
+        .get(i + 1)
+        .is_none_or(|next| operation_id(id(next)) != operation_id(id(&events[i])))
+}
+
+/// Roll back to the newest checkpoint `divergence` leaves trusted.
+/// `None` leaves the state as it was, for an operator to resolve.
+async fn roll_back(state: &SharedState, divergence: &Divergence) -> Option<Checkpoint> {
+    warn!(%divergence, "chain diverged");
+    let Some(ledger) = divergence.trusted_ledger() else {
+        error!("cannot roll back past a gap; re-index into a fresh database");
+        return None;
+    };
+    let mut s = state.write().await;
+    let checkpoint = match s.db.checkpoint_at_or_before(ledger) {
+        Ok(Some(checkpoint)) => checkpoint,
+        Ok(None) => {
+            error!(ledger, "no checkpoint at or before the ledger; re-index into a fresh database");
+            return None;
+        }
+        Err(e) => {
+            error!(ledger, error = %e, "failed to load checkpoint");
+            return None;
+        }
+    };
+    if let Err(e) = s.rollback_to(&checkpoint) {
+        error!(ledger = checkpoint.ledger, error = %e, "rollback failed");
+        return None;
+    }
+    info!(
+        ledger = checkpoint.ledger,
+        leaves = checkpoint.leaf_count,
+        root = ?checkpoint.root,
+        "rolled back to checkpoint"
+    );
+    Some(checkpoint)
+}
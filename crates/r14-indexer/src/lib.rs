@@ -1,4 +1,14 @@
 pub mod api;
+pub mod config;
 pub mod db;
+pub mod events;
+pub mod failover;
+pub mod indexer;
+#[cfg(feature = "postgres")]
+pub mod pg;
+pub mod reorg;
 pub mod rpc;
+pub mod signing;
+pub mod snapshot;
 pub mod tree;
+pub mod verify;
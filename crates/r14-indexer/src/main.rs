@@ -1,185 +1,293 @@
-mod api;
-mod db;
-mod rpc;
-mod tree;
+use r14_indexer::{api, config, db, failover, indexer, rpc, snapshot, verify};
 
-use std::sync::Arc;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
+use anyhow::Context;
 use ark_bls12_381::Fr;
 use ark_ff::PrimeField;
-use tokio::sync::RwLock;
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+use utoipa::OpenApi;
 
-use api::{AppState, SharedState};
-use db::Db;
-use tree::SparseMerkleTree;
+use clap::Parser;
+use api::ApiDoc;
+use config::{Args, Command, Config, FileConfig, SnapshotCommand};
+use failover::RpcPool;
+use indexer::Indexer;
+use r14_sdk::deployment::Deployment;
+use r14_types::{hex, MerkleRoot};
 
 // ── Config ───────────────────────────────────────────────────────────
-const POLL_INTERVAL: Duration = Duration::from_secs(5);
 
-fn env_or(key: &str, default: &str) -> String {
-    std::env::var(key).unwrap_or_else(|_| default.into())
+/// Log to stderr, filtered by the resolved `log_level`
+fn init_tracing(filter: &str) {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::new(filter))
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// Resolve settings from flags, env, the `--config` file and the `r14.toml`
+/// descriptor. Any error aborts before the indexer touches the database.
+fn load_config(args: &Args) -> anyhow::Result<(Config, Option<PathBuf>)> {
+    let file = match &args.config {
+        Some(path) => FileConfig::load(path)?,
+        None => FileConfig::default(),
+    };
+    let deployment = Deployment::discover()?;
+    let descriptor = deployment.as_ref().map(|(path, d)| (path.as_path(), d));
+    let config = Config::resolve(args, file, descriptor)?;
+    Ok((config, deployment.map(|(path, _)| path)))
 }
 
 #[tokio::main]
 async fn main() {
-    let rpc_url = env_or("R14_RPC_URL", "https://soroban-testnet.stellar.org:443");
-    let contract_id = env_or("R14_CONTRACT_ID", "PLACEHOLDER_CONTRACT_ID");
-    let db_path = env_or("R14_DB_PATH", "r14-indexer.db");
-    let listen_addr = env_or("R14_LISTEN_ADDR", "0.0.0.0:3000");
-
-    eprintln!("r14-indexer starting...");
-    eprintln!("  contract: {contract_id}");
-    eprintln!("  rpc:      {rpc_url}");
-
-    // 1. Open DB + create tables
-    let db = Db::open(std::path::Path::new(&db_path)).expect("failed to open db");
-
-    // 2. Rebuild tree from persisted leaves
-    let mut tree = SparseMerkleTree::new();
-    let leaves = db.load_leaves().expect("failed to load leaves");
-    let leaf_count = leaves.len();
-    for leaf in leaves {
-        tree.insert(leaf);
+    let args = Args::parse();
+    // needs no configuration, so it works before any is set up
+    if args.command == Some(Command::Openapi) {
+        println!("{}", ApiDoc::openapi().to_pretty_json().expect("OpenAPI document serializes"));
+        return;
+    }
+    let (config, descriptor) = match load_config(&args) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            std::process::exit(1);
+        }
+    };
+    if args.print_config {
+        print!("{}", config.to_toml());
+        return;
+    }
+    // before the tree or a snapshot hashes anything
+    r14_sdk::set_hash_family(config.hash).expect("nothing has hashed yet");
+    match &args.command {
+        Some(Command::Verify) => {
+            let consistent = verify_consistency(&config).await;
+            std::process::exit(if consistent { 0 } else { 1 });
+        }
+        Some(Command::Snapshot(command)) => {
+            let result = match command {
+                SnapshotCommand::Export { path } => export_snapshot(&config, path),
+                SnapshotCommand::Import { path } => import_snapshot(&config, path),
+            };
+            if let Err(e) = result {
+                eprintln!("error: {e:#}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Openapi) | None => {}
     }
-    eprintln!("rebuilt tree with {leaf_count} leaves, root={:?}", tree.root());
 
-    // 3. Load sync cursor
-    let cursor_state = db.load_cursor().expect("failed to load cursor");
+    init_tracing(&config.log_level);
+    if let Some(path) = descriptor {
+        info!(descriptor = %path.display(), "using deployment descriptor");
+    }
+    info!(
+        contract = %config.contract_id,
+        rpc = %config.rpc_url,
+        rpc_fallbacks = config.rpc_fallbacks.len(),
+        confirmations = config.confirmations,
+        "r14-indexer starting"
+    );
 
-    let state: SharedState = Arc::new(RwLock::new(AppState { tree, db }));
+    // 1. Open DB, rebuild the tree and optionally roll back to a checkpoint
+    let db = db::open(&config.db_url).expect("failed to open db");
+    let indexer = Indexer::new(config.clone(), db, RpcPool::new(config.rpc_urls())).expect("failed to load leaves");
+    if let Some(ledger) = args.rollback_to {
+        if let Err(e) = indexer.rollback_to(ledger).await {
+            error!(ledger, error = %e, "failed to roll back to the --rollback-to ledger");
+            std::process::exit(1);
+        }
+    }
 
-    // 4. Spawn poller
-    let poller_state = state.clone();
-    let poller_rpc = rpc_url.clone();
-    let poller_contract = contract_id.clone();
-    tokio::spawn(async move {
-        poller_loop(poller_state, cursor_state, &poller_rpc, &poller_contract).await;
-    });
+    // 2. Spawn poller
+    let router = indexer.router();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut poller = tokio::spawn(indexer.run(shutdown_rx.clone()));
 
-    // 5. Start HTTP server
-    let router = api::router(state);
-    let listener = tokio::net::TcpListener::bind(&listen_addr)
+    // 3. Start HTTP server
+    let listener = tokio::net::TcpListener::bind(config.listen_addr)
         .await
         .expect("failed to bind");
-    eprintln!("listening on {listen_addr}");
-    axum::serve(listener, router).await.expect("server error");
-}
+    info!(addr = %config.listen_addr, "listening");
+    let mut server_shutdown = shutdown_rx;
+    let mut server = tokio::spawn(async move {
+        axum::serve(listener, router)
+            .with_graceful_shutdown(async move {
+                let _ = server_shutdown.wait_for(|&stop| stop).await;
+            })
+            .await
+    });
 
-async fn poller_loop(state: SharedState, initial_cursor: Option<(u64, Option<String>)>, rpc_url: &str, contract_id: &str) {
-    let client = reqwest::Client::new();
-
-    let mut deposit_cursor: Option<String> = None;
-    let (mut start_ledger, mut cursor) = match initial_cursor {
-        Some((ledger, c)) => (ledger, c),
-        None => {
-            // First run: get latest ledger as starting point
-            match rpc::get_latest_ledger(&client, rpc_url).await {
-                Ok(seq) => {
-                    eprintln!("no cursor, starting from ledger {seq}");
-                    (seq, None)
-                }
-                Err(e) => {
-                    eprintln!("failed to get latest ledger: {e}, retrying...");
-                    tokio::time::sleep(POLL_INTERVAL).await;
-                    return;
-                }
-            }
+    // 4. On SIGINT/SIGTERM, stop polling and drain connections
+    tokio::select! {
+        _ = shutdown_signal() => {}
+        result = &mut server => {
+            error!(result = ?result, "server stopped");
+            std::process::exit(1);
         }
-    };
-
-    loop {
-        tokio::time::sleep(POLL_INTERVAL).await;
+        result = &mut poller => {
+            error!(result = ?result, "poller stopped");
+            std::process::exit(1);
+        }
+    }
+    info!(timeout_secs = config.drain_timeout_secs, "shutting down");
+    let _ = shutdown_tx.send(true);
+    let deadline = tokio::time::Instant::now() + config.drain_timeout();
+    // the poller stops between polls, so its inserts and cursor land together
+    if tokio::time::timeout_at(deadline, &mut poller).await.is_err() {
+        warn!("poll still running at the drain timeout, abandoning it");
+        poller.abort();
+    }
+    // subscribers never hang up on their own, so the timeout ends them
+    if tokio::time::timeout_at(deadline, &mut server).await.is_err() {
+        warn!("connections still open at the drain timeout, closing them");
+        server.abort();
+    }
+    info!("shut down");
+}
 
-        let result = match rpc::poll_events(
-            &client,
-            rpc_url,
-            contract_id,
-            start_ledger,
-            cursor.as_deref(),
-        )
-        .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("poll error: {e}");
-                continue;
-            }
-        };
+/// `r14-indexer snapshot export`
+fn export_snapshot(config: &Config, path: &Path) -> anyhow::Result<()> {
+    let db = db::open(&config.db_url)?;
+    let Some(snapshot) = db.export_snapshot(&config.contract_id)? else {
+        anyhow::bail!("{} has no sync cursor yet; nothing has been indexed", config.db_url);
+    };
+    let file = std::fs::File::create(path).with_context(|| format!("failed to create {}", path.display()))?;
+    snapshot
+        .write_to(std::io::BufWriter::new(file))
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    println!(
+        "exported {} leaves, {} nullifiers and {} roots through ledger {} to {}",
+        snapshot.leaves.len(),
+        snapshot.nullifiers.len(),
+        snapshot.roots.len(),
+        snapshot.ledger,
+        path.display()
+    );
+    Ok(())
+}
 
-        if !result.events.is_empty() {
-            let mut s = state.write().await;
-            for ev in &result.events {
-                let cm_0 = Fr::from_be_bytes_mod_order(&ev.cm_0);
-                let cm_1 = Fr::from_be_bytes_mod_order(&ev.cm_1);
+/// `r14-indexer snapshot import`
+fn import_snapshot(config: &Config, path: &Path) -> anyhow::Result<()> {
+    let file = std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let snapshot = snapshot::Snapshot::read_from(std::io::BufReader::new(file))
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    anyhow::ensure!(
+        snapshot.contract_id == config.contract_id,
+        "{} is a snapshot of contract {}, not {}",
+        path.display(),
+        snapshot.contract_id,
+        config.contract_id
+    );
+    snapshot.tree()?;
+    db::open(&config.db_url)?.import_snapshot(&snapshot)?;
+    println!(
+        "imported {} leaves through ledger {} into {}, root {}",
+        snapshot.leaves.len(),
+        snapshot.ledger,
+        config.db_url,
+        hex::encode(&snapshot.root.0)
+    );
+    Ok(())
+}
 
-                let idx0 = s.tree.insert(cm_0);
-                if let Err(e) = s.db.insert_leaf(idx0, cm_0, ev.ledger) {
-                    eprintln!("db insert cm_0 error: {e}");
-                }
+/// `r14-indexer verify`: replay the stored leaves and report where they
+/// stop agreeing with the recorded roots and with the contract. Whether
+/// everything agreed.
+async fn verify_consistency(config: &Config) -> bool {
+    let db = match db::open(&config.db_url) {
+        Ok(db) => db,
+        Err(e) => {
+            eprintln!("error: failed to open {}: {e}", config.db_url);
+            return false;
+        }
+    };
+    let (leaves, recorded) = match db.load_leaves().and_then(|leaves| Ok((leaves, db.all_roots()?))) {
+        Ok(stored) => stored,
+        Err(e) => {
+            eprintln!("error: failed to read {}: {e}", config.db_url);
+            return false;
+        }
+    };
 
-                let idx1 = s.tree.insert(cm_1);
-                if let Err(e) = s.db.insert_leaf(idx1, cm_1, ev.ledger) {
-                    eprintln!("db insert cm_1 error: {e}");
-                }
-            }
-            eprintln!(
-                "indexed {} transfer events, {} new leaves, root={:?}",
-                result.events.len(),
-                result.events.len() * 2,
-                s.tree.root()
+    let started = Instant::now();
+    let replay = verify::replay(leaves, &recorded);
+    let leaf_count = replay.tree.next_index();
+    println!(
+        "replayed {leaf_count} leaves in {:.1?}: root {}",
+        started.elapsed(),
+        hex::encode(&replay.tree.root().0)
+    );
+    let mut consistent = true;
+    match &replay.mismatch {
+        None => println!("all {} recorded roots match", replay.matched),
+        Some(mismatch) => {
+            consistent = false;
+            let recorded = &mismatch.recorded;
+            let replayed = mismatch.replayed.as_ref().map_or("nothing, too few leaves".into(), |r| hex::encode(&r.0));
+            println!(
+                "recorded root at {} leaves (ledger {}) is {}, the leaves give {replayed}",
+                recorded.leaf_count,
+                recorded.ledger,
+                hex::encode(&recorded.root.0)
             );
+            if recorded.leaf_count == mismatch.agreed_through + 1 {
+                println!("first divergent leaf: {}", mismatch.agreed_through);
+            } else {
+                println!(
+                    "first divergent leaf: one of {}..{} ({} roots matched before it)",
+                    mismatch.agreed_through, recorded.leaf_count, replay.matched
+                );
+            }
         }
+    }
 
-        // Poll deposit events
-        let dep_result = match rpc::poll_deposit_events(
-            &client,
-            rpc_url,
-            contract_id,
-            start_ledger,
-            deposit_cursor.as_deref(),
-        )
-        .await
-        {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("deposit poll error: {e}");
-                // still update transfer cursor below
-                start_ledger = result.latest_ledger;
-                cursor = result.cursor.clone();
-                let s = state.read().await;
-                if let Err(e) = s.db.save_cursor(start_ledger, cursor.as_deref()) {
-                    eprintln!("save cursor error: {e}");
+    let rpc = RpcPool::new(config.rpc_urls());
+    match rpc::get_latest_root(&rpc, &config.contract_id).await {
+        Ok(bytes) => {
+            let root = MerkleRoot(Fr::from_be_bytes_mod_order(&bytes));
+            let hex = hex::encode(&root.0);
+            match replay.leaf_count_at(&root) {
+                Some(n) if n == leaf_count => println!("contract root {hex} matches"),
+                Some(n) => {
+                    consistent = false;
+                    println!("contract root {hex} is the tree's at {n} leaves; leaves {n}..{leaf_count} are not on chain");
                 }
-                continue;
-            }
-        };
-
-        if !dep_result.events.is_empty() {
-            let mut s = state.write().await;
-            for ev in &dep_result.events {
-                let cm = Fr::from_be_bytes_mod_order(&ev.cm);
-                let idx = s.tree.insert(cm);
-                if let Err(e) = s.db.insert_leaf(idx, cm, ev.ledger) {
-                    eprintln!("db insert deposit cm error: {e}");
+                None => {
+                    consistent = false;
+                    println!("contract root {hex} is not the root of any prefix of the stored leaves");
+                    println!("the indexer is behind the contract, or diverged from it");
                 }
             }
-            eprintln!(
-                "indexed {} deposit events, root={:?}",
-                dep_result.events.len(),
-                s.tree.root()
-            );
         }
-
-        deposit_cursor = dep_result.cursor;
-
-        start_ledger = result.latest_ledger;
-        cursor = result.cursor.clone();
-
-        // Persist cursor
-        let s = state.read().await;
-        if let Err(e) = s.db.save_cursor(start_ledger, cursor.as_deref()) {
-            eprintln!("save cursor error: {e}");
+        Err(e) => {
+            consistent = false;
+            println!("could not read the contract's latest root: {e:#}");
         }
     }
+    consistent
+}
+
+/// Resolves on Ctrl-C, or SIGTERM where there is one
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to listen for Ctrl-C");
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
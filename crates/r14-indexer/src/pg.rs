@@ -0,0 +1,457 @@
+use std::sync::Mutex;
+
+use postgres::{Client, GenericClient, IsolationLevel, NoTls};
+use r14_errors::IndexerError;
+use r14_types::{Commitment, LeafIndex, MerkleRoot, Nullifier};
+
+use crate::db::{
+    decode_event, encode_event, fr_from_bytes, fr_to_bytes, Batch, HistoricalRoot, RawEventFilter, Storage,
+    StorageResult, StorageStats, STATS_QUERY,
+};
+use crate::events::IndexerEvent;
+use crate::reorg::{Checkpoint, CHECKPOINT_WINDOW};
+use crate::rpc::RawEvent;
+use crate::snapshot::Snapshot;
+
+/// Postgres storage, for indexers that share a database
+pub struct PgDb {
+    client: Mutex<Client>,
+}
+
+impl PgDb {
+    pub fn connect(url: &str) -> StorageResult<Self> {
+        blocking(|| {
+            let mut client = Client::connect(url, NoTls)?;
+            client.batch_execute(
+                "CREATE TABLE IF NOT EXISTS leaves (
+                    idx BIGINT PRIMARY KEY,
+                    commitment BYTEA NOT NULL,
+                    block_height BIGINT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS leaves_by_commitment ON leaves (commitment);
+                CREATE TABLE IF NOT EXISTS nullifiers (
+                    nullifier BYTEA PRIMARY KEY,
+                    block_height BIGINT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS roots (
+                    leaf_count BIGINT PRIMARY KEY,
+                    root BYTEA NOT NULL,
+                    ledger BIGINT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS roots_by_root ON roots (root);
+                CREATE TABLE IF NOT EXISTS chain_events (
+                    id TEXT PRIMARY KEY,
+                    topic TEXT NOT NULL,
+                    tx_hash TEXT NOT NULL,
+                    ledger BIGINT NOT NULL,
+                    closed_at TEXT NOT NULL,
+                    value_xdr TEXT NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS chain_events_by_tx ON chain_events (tx_hash);
+                CREATE TABLE IF NOT EXISTS sync_cursor (
+                    id INTEGER PRIMARY KEY CHECK (id = 1),
+                    last_ledger BIGINT NOT NULL,
+                    last_cursor TEXT
+                );
+                CREATE TABLE IF NOT EXISTS events (
+                    seq BIGSERIAL PRIMARY KEY,
+                    event TEXT NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS checkpoints (
+                    ledger BIGINT PRIMARY KEY,
+                    leaf_count BIGINT NOT NULL,
+                    root BYTEA NOT NULL
+                );",
+            )?;
+            Ok(Self {
+                client: Mutex::new(client),
+            })
+        })
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Client) -> Result<T, postgres::Error>) -> StorageResult<T> {
+        let mut client = self.client.lock().unwrap();
+        Ok(blocking(|| f(&mut client))?)
+    }
+}
+
+impl Storage for PgDb {
+    fn insert_leaf(&self, idx: LeafIndex, commitment: Commitment, block_height: u64) -> StorageResult<()> {
+        self.with(|c| put_leaf(c, idx, &commitment, block_height))
+    }
+
+    fn load_leaves(&self) -> StorageResult<Vec<Commitment>> {
+        let rows = self.with(|c| c.query("SELECT commitment FROM leaves ORDER BY idx", &[]))?;
+        Ok(rows
+            .iter()
+            .map(|row| Commitment(fr_from_bytes(row.get::<_, &[u8]>(0))))
+            .collect())
+    }
+
+    fn load_leaves_range(&self, from: u64, limit: usize) -> StorageResult<Vec<Commitment>> {
+        let (from, limit) = (from as i64, limit.min(i64::MAX as usize) as i64);
+        let rows = self.with(|c| {
+            c.query("SELECT commitment FROM leaves WHERE idx >= $1 ORDER BY idx LIMIT $2", &[&from, &limit])
+        })?;
+        Ok(rows
+            .iter()
+            .map(|row| Commitment(fr_from_bytes(row.get::<_, &[u8]>(0))))
+            .collect())
+    }
+
+    fn get_leaf_by_commitment(&self, commitment: Commitment) -> StorageResult<Option<(LeafIndex, u64)>> {
+        let bytes = fr_to_bytes(&commitment.0);
+        let row = self.with(|c| {
+            c.query_opt("SELECT idx, block_height FROM leaves WHERE commitment = $1 ORDER BY idx LIMIT 1", &[&bytes])
+        })?;
+        Ok(row.map(|row| (LeafIndex(row.get::<_, i64>(0) as u64), row.get::<_, i64>(1) as u64)))
+    }
+
+    fn count_leaves_through(&self, block_height: u64) -> StorageResult<usize> {
+        let height = block_height as i64;
+        let row = self.with(|c| c.query_one("SELECT COUNT(*) FROM leaves WHERE block_height <= $1", &[&height]))?;
+        Ok(row.get::<_, i64>(0) as usize)
+    }
+
+    fn insert_nullifier(&self, nullifier: &Nullifier, block_height: u64) -> StorageResult<()> {
+        self.with(|c| put_nullifier(c, nullifier, block_height))
+    }
+
+    fn get_nullifier(&self, nullifier: &Nullifier) -> StorageResult<Option<u64>> {
+        let bytes = fr_to_bytes(&nullifier.0);
+        let row = self.with(|c| c.query_opt("SELECT block_height FROM nullifiers WHERE nullifier = $1", &[&bytes]))?;
+        Ok(row.map(|row| row.get::<_, i64>(0) as u64))
+    }
+
+    fn insert_root(&self, root: &HistoricalRoot) -> StorageResult<()> {
+        self.with(|c| put_root(c, root))
+    }
+
+    fn recent_roots(&self, limit: usize) -> StorageResult<Vec<HistoricalRoot>> {
+        let rows = self.with(|c| {
+            c.query(
+                "SELECT leaf_count, root, ledger FROM roots ORDER BY leaf_count DESC LIMIT $1",
+                &[&(limit as i64)],
+            )
+        })?;
+        Ok(rows.iter().map(root_from_row).collect())
+    }
+
+    fn all_roots(&self) -> StorageResult<Vec<HistoricalRoot>> {
+        let rows = self.with(|c| c.query("SELECT leaf_count, root, ledger FROM roots ORDER BY leaf_count", &[]))?;
+        Ok(rows.iter().map(root_from_row).collect())
+    }
+
+    fn root_at(&self, leaf_count: u64) -> StorageResult<Option<HistoricalRoot>> {
+        let row = self.with(|c| {
+            c.query_opt(
+                "SELECT leaf_count, root, ledger FROM roots WHERE leaf_count = $1",
+                &[&(leaf_count as i64)],
+            )
+        })?;
+        Ok(row.as_ref().map(root_from_row))
+    }
+
+    fn find_root(&self, root: &MerkleRoot) -> StorageResult<Option<HistoricalRoot>> {
+        let bytes = fr_to_bytes(&root.0);
+        let row = self.with(|c| {
+            c.query_opt(
+                "SELECT leaf_count, root, ledger FROM roots WHERE root = $1 ORDER BY leaf_count LIMIT 1",
+                &[&bytes],
+            )
+        })?;
+        Ok(row.as_ref().map(root_from_row))
+    }
+
+    fn insert_raw_event(&self, event: &RawEvent) -> StorageResult<()> {
+        self.with(|c| put_raw_event(c, event))
+    }
+
+    fn raw_events(&self, filter: &RawEventFilter, limit: usize) -> StorageResult<Vec<RawEvent>> {
+        let rows = self.with(|c| {
+            c.query(
+                "SELECT id, topic, tx_hash, ledger, closed_at, value_xdr FROM chain_events
+                 WHERE ($1::TEXT IS NULL OR id > $1) AND ($2::TEXT IS NULL OR topic = $2)
+                   AND ($3::TEXT IS NULL OR tx_hash = $3)
+                   AND ($4::BIGINT IS NULL OR ledger >= $4) AND ($5::BIGINT IS NULL OR ledger <= $5)
+                 ORDER BY id LIMIT $6",
+                &[
+                    &filter.after,
+                    &filter.topic,
+                    &filter.tx_hash,
+                    &filter.from_ledger.map(|l| l as i64),
+                    &filter.to_ledger.map(|l| l as i64),
+                    &(limit as i64),
+                ],
+            )
+        })?;
+        Ok(rows
+            .iter()
+            .map(|row| RawEvent {
+                id: row.get(0),
+                topic: row.get(1),
+                tx_hash: row.get(2),
+                ledger: row.get::<_, i64>(3) as u64,
+                closed_at: row.get(4),
+                value_xdr: row.get(5),
+            })
+            .collect())
+    }
+
+    fn save_cursor(&self, last_ledger: u64, cursor: Option<&str>) -> StorageResult<()> {
+        self.with(|c| put_cursor(c, last_ledger, cursor))
+    }
+
+    fn load_cursor(&self) -> StorageResult<Option<(u64, Option<String>)>> {
+        let row = self.with(|c| c.query_opt("SELECT last_ledger, last_cursor FROM sync_cursor WHERE id = 1", &[]))?;
+        Ok(row.map(|row| (row.get::<_, i64>(0) as u64, row.get(1))))
+    }
+
+    fn stats(&self) -> StorageResult<StorageStats> {
+        let row = self.with(|c| c.query_one(STATS_QUERY, &[]))?;
+        Ok(StorageStats {
+            nullifiers: row.get::<_, i64>(0) as u64,
+            deposits: row.get::<_, i64>(1) as u64,
+            transfers: row.get::<_, i64>(2) as u64,
+            first_ledger: row.get::<_, Option<i64>>(3).map(|l| l as u64),
+            last_ledger: row.get::<_, Option<i64>>(4).map(|l| l as u64),
+        })
+    }
+
+    fn append_event(&self, event: &IndexerEvent) -> StorageResult<u64> {
+        let json = encode_event(event)?;
+        let row = self.with(|c| c.query_one("INSERT INTO events (event) VALUES ($1) RETURNING seq", &[&json]))?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+
+    fn events_after(&self, after: u64, limit: usize) -> StorageResult<Vec<(u64, IndexerEvent)>> {
+        let rows = self.with(|c| {
+            c.query(
+                "SELECT seq, event FROM events WHERE seq > $1 ORDER BY seq LIMIT $2",
+                &[&(after as i64), &(limit as i64)],
+            )
+        })?;
+        rows.iter()
+            .map(|row| Ok((row.get::<_, i64>(0) as u64, decode_event(row.get(1))?)))
+            .collect()
+    }
+
+    fn export_snapshot(&self, contract_id: &str) -> StorageResult<Option<Snapshot>> {
+        let read = self.with(|c| {
+            // one snapshot of the tables, whatever a running indexer writes
+            let mut tx = c
+                .build_transaction()
+                .isolation_level(IsolationLevel::RepeatableRead)
+                .read_only(true)
+                .start()?;
+            let Some(cursor) = tx.query_opt("SELECT last_ledger, last_cursor FROM sync_cursor WHERE id = 1", &[])? else {
+                return Ok(None);
+            };
+            let leaves = tx.query("SELECT commitment, block_height FROM leaves ORDER BY idx", &[])?;
+            let nullifiers =
+                tx.query("SELECT nullifier, block_height FROM nullifiers ORDER BY block_height, nullifier", &[])?;
+            let roots = tx.query("SELECT leaf_count, root, ledger FROM roots ORDER BY leaf_count", &[])?;
+            tx.commit()?;
+            Ok(Some((cursor, leaves, nullifiers, roots)))
+        })?;
+        let Some((cursor, leaves, nullifiers, roots)) = read else {
+            return Ok(None);
+        };
+        let pair = |row: &postgres::Row| (fr_from_bytes(row.get::<_, &[u8]>(0)), row.get::<_, i64>(1) as u64);
+        Ok(Some(Snapshot::new(
+            contract_id,
+            (cursor.get::<_, i64>(0) as u64, cursor.get(1)),
+            leaves.iter().map(pair).map(|(leaf, height)| (Commitment(leaf), height)).collect(),
+            nullifiers.iter().map(pair).map(|(nullifier, height)| (Nullifier(nullifier), height)).collect(),
+            roots.iter().map(root_from_row).collect(),
+        )))
+    }
+
+    fn import_snapshot(&self, snapshot: &Snapshot) -> StorageResult<()> {
+        let held = self.with(|c| {
+            let mut tx = c.transaction()?;
+            let held: i64 = tx.query_one("SELECT COUNT(*) FROM leaves", &[])?.get(0);
+            if held > 0 {
+                return Ok(held);
+            }
+            let insert = tx.prepare("INSERT INTO leaves (idx, commitment, block_height) VALUES ($1, $2, $3)")?;
+            for (idx, (leaf, height)) in snapshot.leaves.iter().enumerate() {
+                tx.execute(&insert, &[&(idx as i64), &fr_to_bytes(&leaf.0), &(*height as i64)])?;
+            }
+            let insert = tx.prepare(
+                "INSERT INTO nullifiers (nullifier, block_height) VALUES ($1, $2) ON CONFLICT (nullifier) DO NOTHING",
+            )?;
+            for (nullifier, height) in &snapshot.nullifiers {
+                tx.execute(&insert, &[&fr_to_bytes(&nullifier.0), &(*height as i64)])?;
+            }
+            let insert = tx.prepare(
+                "INSERT INTO roots (leaf_count, root, ledger) VALUES ($1, $2, $3) ON CONFLICT (leaf_count) DO NOTHING",
+            )?;
+            for root in &snapshot.roots {
+                tx.execute(&insert, &[&(root.leaf_count as i64), &fr_to_bytes(&root.root.0), &(root.ledger as i64)])?;
+            }
+            tx.execute(
+                "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
+                 VALUES (1, $1, $2)
+                 ON CONFLICT (id) DO UPDATE SET last_ledger = $1, last_cursor = $2",
+                &[&(snapshot.ledger as i64), &snapshot.cursor],
+            )?;
+            tx.execute(
+                "INSERT INTO checkpoints (ledger, leaf_count, root) VALUES ($1, $2, $3)
+                 ON CONFLICT (ledger) DO UPDATE SET leaf_count = $2, root = $3",
+                &[&(snapshot.ledger as i64), &(snapshot.leaves.len() as i64), &fr_to_bytes(&snapshot.root.0)],
+            )?;
+            tx.commit()?;
+            Ok(0)
+        })?;
+        if held > 0 {
+            return Err(IndexerError::Storage(format!("database already holds {held} leaves")));
+        }
+        Ok(())
+    }
+
+    fn save_checkpoint(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
+        self.with(|c| put_checkpoint(c, checkpoint))
+    }
+
+    fn write_batch(&self, batch: &Batch) -> StorageResult<()> {
+        self.with(|c| {
+            let mut tx = c.transaction()?;
+            for (idx, commitment, block_height) in &batch.leaves {
+                put_leaf(&mut tx, *idx, commitment, *block_height)?;
+            }
+            for (nullifier, block_height) in &batch.nullifiers {
+                put_nullifier(&mut tx, nullifier, *block_height)?;
+            }
+            for root in &batch.roots {
+                put_root(&mut tx, root)?;
+            }
+            for event in &batch.raw_events {
+                put_raw_event(&mut tx, event)?;
+            }
+            if let Some((ledger, cursor)) = &batch.cursor {
+                put_cursor(&mut tx, *ledger, cursor.as_deref())?;
+            }
+            if let Some(checkpoint) = &batch.checkpoint {
+                put_checkpoint(&mut tx, checkpoint)?;
+            }
+            tx.commit()
+        })
+    }
+
+    fn checkpoint_at_or_before(&self, ledger: u64) -> StorageResult<Option<Checkpoint>> {
+        let row = self.with(|c| {
+            c.query_opt(
+                "SELECT ledger, leaf_count, root FROM checkpoints WHERE ledger <= $1 ORDER BY ledger DESC LIMIT 1",
+                &[&(ledger as i64)],
+            )
+        })?;
+        Ok(row.map(|row| Checkpoint {
+            ledger: row.get::<_, i64>(0) as u64,
+            leaf_count: row.get::<_, i64>(1) as u64,
+            root: MerkleRoot(fr_from_bytes(row.get::<_, &[u8]>(2))),
+        }))
+    }
+
+    fn rollback_to(&self, checkpoint: &Checkpoint) -> StorageResult<()> {
+        let (ledger, leaf_count) = (checkpoint.ledger as i64, checkpoint.leaf_count as i64);
+        self.with(|c| {
+            let mut tx = c.transaction()?;
+            tx.execute("DELETE FROM leaves WHERE idx >= $1", &[&leaf_count])?;
+            tx.execute("DELETE FROM nullifiers WHERE block_height > $1", &[&ledger])?;
+            tx.execute("DELETE FROM roots WHERE leaf_count > $1", &[&leaf_count])?;
+            tx.execute("DELETE FROM chain_events WHERE ledger > $1", &[&ledger])?;
+            tx.execute("DELETE FROM checkpoints WHERE ledger > $1", &[&ledger])?;
+            tx.execute(
+                "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
+                 VALUES (1, $1, NULL)
+                 ON CONFLICT (id) DO UPDATE SET last_ledger = $1, last_cursor = NULL",
+                &[&ledger],
+            )?;
+            tx.commit()
+        })
+    }
+}
+
+fn put_leaf(
+    c: &mut impl GenericClient,
+    idx: LeafIndex,
+    commitment: &Commitment,
+    block_height: u64,
+) -> Result<(), postgres::Error> {
+    c.execute(
+        "INSERT INTO leaves (idx, commitment, block_height) VALUES ($1, $2, $3) ON CONFLICT (idx) DO NOTHING",
+        &[&(idx.0 as i64), &fr_to_bytes(&commitment.0), &(block_height as i64)],
+    )?;
+    Ok(())
+}
+
+fn put_nullifier(c: &mut impl GenericClient, nullifier: &Nullifier, block_height: u64) -> Result<(), postgres::Error> {
+    c.execute(
+        "INSERT INTO nullifiers (nullifier, block_height) VALUES ($1, $2)
+         ON CONFLICT (nullifier) DO NOTHING",
+        &[&fr_to_bytes(&nullifier.0), &(block_height as i64)],
+    )?;
+    Ok(())
+}
+
+fn put_root(c: &mut impl GenericClient, root: &HistoricalRoot) -> Result<(), postgres::Error> {
+    c.execute(
+        "INSERT INTO roots (leaf_count, root, ledger) VALUES ($1, $2, $3)
+         ON CONFLICT (leaf_count) DO NOTHING",
+        &[&(root.leaf_count as i64), &fr_to_bytes(&root.root.0), &(root.ledger as i64)],
+    )?;
+    Ok(())
+}
+
+fn put_raw_event(c: &mut impl GenericClient, event: &RawEvent) -> Result<(), postgres::Error> {
+    c.execute(
+        "INSERT INTO chain_events (id, topic, tx_hash, ledger, closed_at, value_xdr)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (id) DO NOTHING",
+        &[&event.id, &event.topic, &event.tx_hash, &(event.ledger as i64), &event.closed_at, &event.value_xdr],
+    )?;
+    Ok(())
+}
+
+fn put_cursor(c: &mut impl GenericClient, last_ledger: u64, cursor: Option<&str>) -> Result<(), postgres::Error> {
+    c.execute(
+        "INSERT INTO sync_cursor (id, last_ledger, last_cursor)
+         VALUES (1, $1, $2)
+         ON CONFLICT (id) DO UPDATE SET last_ledger = $1, last_cursor = $2",
+        &[&(last_ledger as i64), &cursor],
+    )?;
+    Ok(())
+}
+
+fn put_checkpoint(c: &mut impl GenericClient, checkpoint: &Checkpoint) -> Result<(), postgres::Error> {
+    c.execute(
+        "INSERT INTO checkpoints (ledger, leaf_count, root) VALUES ($1, $2, $3)
+         ON CONFLICT (ledger) DO UPDATE SET leaf_count = $2, root = $3",
+        &[&(checkpoint.ledger as i64), &(checkpoint.leaf_count as i64), &fr_to_bytes(&checkpoint.root.0)],
+    )?;
+    c.execute(
+        "DELETE FROM checkpoints WHERE ledger < $1",
+        &[&(checkpoint.ledger.saturating_sub(CHECKPOINT_WINDOW) as i64)],
+    )?;
+    Ok(())
+}
+
+fn root_from_row(row: &postgres::Row) -> HistoricalRoot {
+    HistoricalRoot {
+        root: MerkleRoot(fr_from_bytes(row.get::<_, &[u8]>(1))),
+        leaf_count: row.get::<_, i64>(0) as u64,
+        ledger: row.get::<_, i64>(2) as u64,
+    }
+}
+
+/// `postgres` drives its own runtime, which can't start inside the
+/// server's, so step out of it for the call. Needs the multi-threaded
+/// runtime `#[tokio::main]` starts.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        tokio::task::block_in_place(f)
+    } else {
+        f()
+    }
+}
@@ -0,0 +1,118 @@
+use r14_types::MerkleRoot;
+
+/// Ledgers of checkpoints kept for rollback, about an hour at 5s ledgers
+pub const CHECKPOINT_WINDOW: u64 = 720;
+
+/// Indexed state as of the end of a ledger: the tree had `leaf_count`
+/// leaves with root `root`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    pub ledger: u64,
+    pub leaf_count: u64,
+    pub root: MerkleRoot,
+}
+
+/// Evidence that ledgers the indexer already applied are no longer the
+/// chain's
+#[derive(Clone, Debug, PartialEq)]
+pub enum Divergence {
+    /// The RPC's latest ledger is behind one already indexed
+    Rewound { latest_ledger: u64 },
+    /// A paged poll returned an event the cursor had already passed
+    Replayed { id: String, ledger: u64 },
+    /// The RPC dropped ledgers the indexer never saw
+    Gap { oldest_ledger: u64 },
+}
+
+impl Divergence {
+    /// Latest ledger still trusted, to roll back to a checkpoint at or
+    /// before. `None` for a gap, which no rollback can repair.
+    pub fn trusted_ledger(&self) -> Option<u64> {
+        match self {
+            Self::Rewound { latest_ledger } => Some(*latest_ledger),
+            Self::Replayed { ledger, .. } => Some(ledger.saturating_sub(1)),
+            Self::Gap { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rewound { latest_ledger } => write!(f, "RPC rewound to ledger {latest_ledger}"),
+            Self::Replayed { id, ledger } => write!(f, "event {id} in ledger {ledger} replayed past the cursor"),
+            Self::Gap { oldest_ledger } => write!(f, "RPC no longer has ledgers before {oldest_ledger}"),
+        }
+    }
+}
+
+/// Check one `getEvents` page against the last checkpoint.
+///
+/// `cursor` is the cursor the page was requested with. A paged poll only
+/// returns events after it, so one at or before the checkpoint's ledger, or
+/// whose id doesn't sort after the cursor, means the chain changed under
+/// the indexer. Event ids are zero-padded, so they sort as strings. An
+/// unpaged poll starts at the checkpoint's ledger and re-reads it; drop
+/// those events with [`is_new`] instead.
+pub fn detect<'a>(
+    last: &Checkpoint,
+    cursor: Option<&str>,
+    latest_ledger: u64,
+    oldest_ledger: Option<u64>,
+    events: impl IntoIterator<Item = (&'a str, u64)>,
+) -> Option<Divergence> {
+    if latest_ledger < last.ledger {
+        return Some(Divergence::Rewound { latest_ledger });
+    }
+    if let Some(oldest_ledger) = oldest_ledger.filter(|&oldest| oldest > last.ledger + 1) {
+        return Some(Divergence::Gap { oldest_ledger });
+    }
+    let cursor = cursor?;
+    events
+        .into_iter()
+        .find(|&(id, ledger)| ledger <= last.ledger || id <= cursor)
+        .map(|(id, ledger)| Divergence::Replayed { id: id.to_string(), ledger })
+}
+
+/// Whether an event in `ledger` is past the checkpoint
+pub fn is_new(last: &Checkpoint, ledger: u64) -> bool {
+    ledger > last.ledger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn checkpoint(ledger: u64) -> Checkpoint {
+        Checkpoint { ledger, leaf_count: 4, root: MerkleRoot(Fr::from(9u64)) }
+    }
+
+    #[test]
+    fn quiet_chain_has_no_divergence() {
+        let last = checkpoint(100);
+        let events = [("0000000101-0000000001", 101), ("0000000102-0000000001", 102)];
+        assert_eq!(detect(&last, Some("0000000100-0000000003"), 102, Some(50), events), None);
+        // an unpaged poll re-reads the checkpoint's ledger; that's expected
+        assert_eq!(detect(&last, None, 102, None, [("0000000100-0000000001", 100)]), None);
+        assert!(!is_new(&last, 100) && is_new(&last, 101));
+    }
+
+    #[test]
+    fn rewinds_replays_and_gaps_are_caught() {
+        let last = checkpoint(100);
+        let none = std::iter::empty();
+
+        let rewound = detect(&last, None, 97, None, none.clone()).unwrap();
+        assert_eq!(rewound, Divergence::Rewound { latest_ledger: 97 });
+        assert_eq!(rewound.trusted_ledger(), Some(97));
+
+        let replayed = detect(&last, Some("0000000100-0000000003"), 101, None, [("0000000099-0000000001", 99)]).unwrap();
+        assert_eq!(replayed.trusted_ledger(), Some(98));
+        let stale_id = detect(&last, Some("0000000101-0000000003"), 101, None, [("0000000101-0000000002", 101)]);
+        assert!(matches!(stale_id, Some(Divergence::Replayed { ledger: 101, .. })));
+
+        let gap = detect(&last, None, 300, Some(150), none).unwrap();
+        assert_eq!((gap.clone(), gap.trusted_ledger()), (Divergence::Gap { oldest_ledger: 150 }, None));
+    }
+}
@@ -1,7 +1,15 @@
+use std::future::Future;
+
 use base64::{engine::general_purpose::STANDARD as B64, Engine};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use stellar_xdr::curr::{Limits, ReadXdr, ScMapEntry, ScVal};
+use stellar_xdr::curr::{
+    HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Limits, Memo, MuxedAccount, Operation, OperationBody,
+    Preconditions, ReadXdr, ScAddress, ScSymbol, ScVal, SequenceNumber, Transaction, TransactionEnvelope, TransactionExt,
+    TransactionV1Envelope, Uint256, WriteXdr,
+};
+use utoipa::ToSchema;
+
+use crate::failover::RpcPool;
 
 #[derive(Debug)]
 pub struct TransferEvent {
@@ -9,12 +17,59 @@ pub struct TransferEvent {
     pub cm_0: [u8; 32],
     pub cm_1: [u8; 32],
     pub ledger: u64,
+    /// RPC event id, which is also the paging cursor
+    pub id: String,
+    pub raw: RawEvent,
 }
 
 #[derive(Debug)]
 pub struct DepositEvent {
     pub cm: [u8; 32],
     pub ledger: u64,
+    /// RPC event id, which is also the paging cursor
+    pub id: String,
+    pub raw: RawEvent,
+}
+
+/// A transfer or deposit, in the order the contract emitted them
+#[derive(Debug)]
+pub enum ContractEvent {
+    Transfer(TransferEvent),
+    Deposit(DepositEvent),
+}
+
+impl ContractEvent {
+    pub fn raw(&self) -> &RawEvent {
+        match self {
+            Self::Transfer(ev) => &ev.raw,
+            Self::Deposit(ev) => &ev.raw,
+        }
+    }
+}
+
+/// A contract event as `getEvents` returned it, kept so the tree can be
+/// reconciled against the chain
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema)]
+pub struct RawEvent {
+    /// RPC event id; ids sort in chain order
+    pub id: String,
+    /// `deposit` or `transfer`
+    pub topic: String,
+    pub tx_hash: String,
+    pub ledger: u64,
+    /// Ledger close time as the RPC reported it, RFC 3339
+    pub closed_at: String,
+    /// The event value, base64 XDR
+    pub value_xdr: String,
+}
+
+/// Events one `getEvents` request asks for
+pub const PAGE_LIMIT: usize = 100;
+
+/// The part of an RPC event id naming the operation that emitted it.
+/// Events from one contract call share it.
+pub fn operation_id(event_id: &str) -> &str {
+    event_id.split_once('-').map_or(event_id, |(op, _)| op)
 }
 
 #[derive(Serialize)]
@@ -36,6 +91,9 @@ struct GetEventsResult {
     events: Vec<RpcEvent>,
     #[serde(rename = "latestLedger")]
     latest_ledger: u64,
+    /// Oldest ledger the RPC still keeps events for (newer RPC versions)
+    #[serde(rename = "oldestLedger")]
+    oldest_ledger: Option<u64>,
 }
 
 #[derive(Deserialize)]
@@ -43,6 +101,25 @@ struct RpcEvent {
     ledger: u64,
     value: String,
     id: Option<String>,
+    #[serde(default)]
+    topic: Vec<String>,
+    #[serde(rename = "txHash", default)]
+    tx_hash: String,
+    #[serde(rename = "ledgerClosedAt", default)]
+    ledger_closed_at: String,
+}
+
+impl RpcEvent {
+    fn raw(&self, topic: &str) -> RawEvent {
+        RawEvent {
+            id: self.id.clone().unwrap_or_default(),
+            topic: topic.to_string(),
+            tx_hash: self.tx_hash.clone(),
+            ledger: self.ledger,
+            closed_at: self.ledger_closed_at.clone(),
+            value_xdr: self.value.clone(),
+        }
+    }
 }
 
 #[derive(Deserialize)]
@@ -50,66 +127,204 @@ struct GetLatestLedgerResult {
     sequence: u64,
 }
 
-pub struct PollResult {
-    pub events: Vec<TransferEvent>,
+/// `getHealth`: the ledgers the RPC has events for
+#[derive(Deserialize, Debug)]
+pub struct Health {
+    #[serde(rename = "latestLedger")]
     pub latest_ledger: u64,
-    pub cursor: Option<String>,
+    #[serde(rename = "oldestLedger")]
+    pub oldest_ledger: u64,
+}
+
+#[derive(Deserialize)]
+struct SimulateTransactionResult {
+    #[serde(default)]
+    results: Vec<SimulateHostFunctionResult>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SimulateHostFunctionResult {
+    xdr: String,
 }
 
-pub struct DepositPollResult {
-    pub events: Vec<DepositEvent>,
+pub struct PollResult {
+    pub events: Vec<ContractEvent>,
     pub latest_ledger: u64,
+    pub oldest_ledger: Option<u64>,
     pub cursor: Option<String>,
+    /// Every event up to this ledger has been returned
+    pub read_through: u64,
+    /// The page was full, so more events may be waiting
+    pub more: bool,
+}
+
+/// What the poller needs from Soroban RPC. [`RpcPool`] is the real one;
+/// tests and embedding services can serve events from elsewhere.
+pub trait RpcClient: Send + Sync + 'static {
+    fn latest_ledger(&self) -> impl Future<Output = anyhow::Result<u64>> + Send;
+
+    fn health(&self) -> impl Future<Output = anyhow::Result<Health>> + Send;
+
+    /// One page of the contract's transfer and deposit events, from
+    /// `cursor` or else `start_ledger`
+    fn poll_events(
+        &self,
+        contract_id: &str,
+        start_ledger: u64,
+        cursor: Option<&str>,
+    ) -> impl Future<Output = anyhow::Result<PollResult>> + Send;
+
+    /// Endpoint that answered last, so a fallback that lags isn't taken
+    /// for a rewound chain
+    fn endpoint(&self) -> &str;
+}
+
+impl RpcClient for RpcPool {
+    async fn latest_ledger(&self) -> anyhow::Result<u64> {
+        get_latest_ledger(self).await
+    }
+
+    async fn health(&self) -> anyhow::Result<Health> {
+        get_health(self).await
+    }
+
+    async fn poll_events(
+        &self,
+        contract_id: &str,
+        start_ledger: u64,
+        cursor: Option<&str>,
+    ) -> anyhow::Result<PollResult> {
+        poll_events(self, contract_id, start_ledger, cursor).await
+    }
+
+    fn endpoint(&self) -> &str {
+        self.last_used()
+    }
+}
+
+pub async fn get_health(rpc: &RpcPool) -> anyhow::Result<Health> {
+    let req = JsonRpcRequest {
+        jsonrpc: "2.0",
+        id: 4,
+        method: "getHealth",
+        params: serde_json::json!({}),
+    };
+    let resp: JsonRpcResponse<Health> = rpc.post(&req).await?;
+    match resp.result {
+        Some(r) => Ok(r),
+        None => Err(anyhow::anyhow!("getHealth error: {:?}", resp.error)),
+    }
 }
 
-pub async fn get_latest_ledger(client: &Client, rpc_url: &str) -> anyhow::Result<u64> {
+pub async fn get_latest_ledger(rpc: &RpcPool) -> anyhow::Result<u64> {
     let req = JsonRpcRequest {
         jsonrpc: "2.0",
         id: 1,
         method: "getLatestLedger",
         params: serde_json::json!({}),
     };
-    let resp: JsonRpcResponse<GetLatestLedgerResult> =
-        client.post(rpc_url).json(&req).send().await?.json().await?;
+    let resp: JsonRpcResponse<GetLatestLedgerResult> = rpc.post(&req).await?;
     match resp.result {
         Some(r) => Ok(r.sequence),
         None => Err(anyhow::anyhow!("getLatestLedger error: {:?}", resp.error)),
     }
 }
 
-fn build_topic_filter(contract_id: &str, topic_name: &str) -> serde_json::Value {
+/// The transfer contract's `latest_root`, read by simulating the call
+pub async fn get_latest_root(rpc: &RpcPool, contract_id: &str) -> anyhow::Result<[u8; 32]> {
+    let req = JsonRpcRequest {
+        jsonrpc: "2.0",
+        id: 3,
+        method: "simulateTransaction",
+        params: serde_json::json!({ "transaction": read_only_call(contract_id, "latest_root")? }),
+    };
+    let resp: JsonRpcResponse<SimulateTransactionResult> = rpc.post(&req).await?;
+    let result = match resp.result {
+        Some(r) => r,
+        None => return Err(anyhow::anyhow!("simulateTransaction error: {:?}", resp.error)),
+    };
+    if let Some(e) = result.error {
+        return Err(anyhow::anyhow!("latest_root failed in simulation: {e}"));
+    }
+    let Some(ret) = result.results.first() else {
+        return Err(anyhow::anyhow!("simulateTransaction returned no result"));
+    };
+    let sc_val = ScVal::from_xdr(B64.decode(&ret.xdr)?, Limits::none())?;
+    extract_bytes32(&sc_val, "latest_root")
+}
+
+/// Base64 envelope calling `function` on `contract_id` with no arguments,
+/// for `simulateTransaction`. Simulation checks neither the source account
+/// nor signatures, so the zero account stands in.
+fn read_only_call(contract_id: &str, function: &str) -> anyhow::Result<String> {
+    let contract_address: ScAddress = contract_id
+        .parse()
+        .map_err(|e| anyhow::anyhow!("contract id {contract_id}: {e}"))?;
+    let invoke = InvokeHostFunctionOp {
+        host_function: HostFunction::InvokeContract(InvokeContractArgs {
+            contract_address,
+            function_name: ScSymbol(function.try_into()?),
+            args: Default::default(),
+        }),
+        auth: Default::default(),
+    };
+    let tx = Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256([0; 32])),
+        fee: 100,
+        seq_num: SequenceNumber(0),
+        cond: Preconditions::None,
+        memo: Memo::None,
+        operations: vec![Operation {
+            source_account: None,
+            body: OperationBody::InvokeHostFunction(invoke),
+        }]
+        .try_into()?,
+        ext: TransactionExt::V0,
+    };
+    let envelope = TransactionEnvelope::Tx(TransactionV1Envelope { tx, signatures: Default::default() });
+    Ok(B64.encode(envelope.to_xdr(Limits::none())?))
+}
+
+/// A symbol topic as base64 XDR, the way the chain emits it
+fn topic_symbol(name: &str) -> String {
     // Build XDR manually: Soroban runtime uses SCV_SYMBOL = tag 14 (0x0e)
     // but stellar-xdr 25.0.0 encodes Symbol as tag 13. Hardcode the correct
     // wire format to match what the chain actually emits.
-    let name_bytes = topic_name.as_bytes();
+    let name_bytes = name.as_bytes();
     let mut buf = Vec::new();
     buf.extend_from_slice(&14u32.to_be_bytes()); // SCV_SYMBOL tag on chain
     buf.extend_from_slice(&(name_bytes.len() as u32).to_be_bytes());
     buf.extend_from_slice(name_bytes);
     // XDR strings are padded to 4-byte boundary
     let pad = (4 - (name_bytes.len() % 4)) % 4;
-    buf.extend(std::iter::repeat(0u8).take(pad));
-    let topic_b64 = B64.encode(&buf);
+    buf.extend(std::iter::repeat_n(0u8, pad));
+    B64.encode(&buf)
+}
 
+fn build_topic_filter(contract_id: &str, topic_names: &[&str]) -> serde_json::Value {
+    let topics: Vec<_> = topic_names.iter().map(|name| [topic_symbol(name)]).collect();
     serde_json::json!([{
         "type": "contract",
         "contractIds": [contract_id],
-        "topics": [[topic_b64]]
+        "topics": topics
     }])
 }
 
+/// One page of transfer and deposit events. Both come from one request so
+/// leaves are inserted in the order the chain emitted them, whatever the
+/// page size.
 pub async fn poll_events(
-    client: &Client,
-    rpc_url: &str,
+    rpc: &RpcPool,
     contract_id: &str,
     start_ledger: u64,
     cursor: Option<&str>,
 ) -> anyhow::Result<PollResult> {
-    let filters = build_topic_filter(contract_id, "transfer");
+    let filters = build_topic_filter(contract_id, &["transfer", "deposit"]);
 
     let mut params = serde_json::json!({
         "filters": filters,
-        "pagination": { "limit": 100 }
+        "pagination": { "limit": PAGE_LIMIT }
     });
 
     if let Some(c) = cursor {
@@ -125,34 +340,53 @@ pub async fn poll_events(
         params,
     };
 
-    let resp: JsonRpcResponse<GetEventsResult> =
-        client.post(rpc_url).json(&req).send().await?.json().await?;
+    let resp: JsonRpcResponse<GetEventsResult> = rpc.post(&req).await?;
 
     let result = match resp.result {
         Some(r) => r,
         None => return Err(anyhow::anyhow!("getEvents error: {:?}", resp.error)),
     };
 
+    let (transfer, deposit) = (topic_symbol("transfer"), topic_symbol("deposit"));
     let mut events = Vec::new();
     let mut last_cursor = None;
 
     for ev in &result.events {
         last_cursor = ev.id.clone();
-        match parse_transfer_value(&ev.value, ev.ledger) {
-            Ok(te) => events.push(te),
-            Err(e) => eprintln!("skip event parse: {e}"),
+        let parsed = match ev.topic.first() {
+            Some(t) if *t == transfer => parse_transfer_value(ev.raw("transfer")).map(ContractEvent::Transfer),
+            Some(t) if *t == deposit => parse_deposit_value(ev.raw("deposit")).map(ContractEvent::Deposit),
+            _ => Err(anyhow::anyhow!("unexpected topic {:?}", ev.topic)),
+        };
+        match parsed {
+            Ok(event) => events.push(event),
+            Err(e) => tracing::warn!(ledger = ev.ledger, error = %e, "skipping unparseable contract event"),
         }
     }
 
+    let (read_through, more) = read_through(&result);
     Ok(PollResult {
         events,
         latest_ledger: result.latest_ledger,
+        oldest_ledger: result.oldest_ledger,
         cursor: last_cursor,
+        read_through,
+        more,
     })
 }
 
-fn parse_transfer_value(value_b64: &str, ledger: u64) -> anyhow::Result<TransferEvent> {
-    let xdr_bytes = B64.decode(value_b64)?;
+/// How far a page covers, and whether it was full. A full page can stop
+/// partway through its last event's ledger, so only the ledgers before
+/// that one are complete.
+fn read_through(result: &GetEventsResult) -> (u64, bool) {
+    match result.events.last() {
+        Some(last) if result.events.len() >= PAGE_LIMIT => (last.ledger.saturating_sub(1), true),
+        _ => (result.latest_ledger, false),
+    }
+}
+
+fn parse_transfer_value(raw: RawEvent) -> anyhow::Result<TransferEvent> {
+    let xdr_bytes = B64.decode(&raw.value_xdr)?;
     let sc_val = ScVal::from_xdr(&xdr_bytes, Limits::none())?;
 
     match sc_val {
@@ -164,7 +398,9 @@ fn parse_transfer_value(value_b64: &str, ledger: u64) -> anyhow::Result<Transfer
                 nullifier,
                 cm_0,
                 cm_1,
-                ledger,
+                ledger: raw.ledger,
+                id: raw.id.clone(),
+                raw,
             })
         }
         _ => Err(anyhow::anyhow!("unexpected event value shape: {sc_val:?}")),
@@ -194,69 +430,79 @@ fn extract_bytes32(val: &ScVal, name: &str) -> anyhow::Result<[u8; 32]> {
     }
 }
 
-pub async fn poll_deposit_events(
-    client: &Client,
-    rpc_url: &str,
-    contract_id: &str,
-    start_ledger: u64,
-    cursor: Option<&str>,
-) -> anyhow::Result<DepositPollResult> {
-    let filters = build_topic_filter(contract_id, "deposit");
-
-    let mut params = serde_json::json!({
-        "filters": filters,
-        "pagination": { "limit": 100 }
-    });
-
-    if let Some(c) = cursor {
-        params["pagination"]["cursor"] = serde_json::json!(c);
-    } else {
-        params["startLedger"] = serde_json::json!(start_ledger);
-    }
-
-    let req = JsonRpcRequest {
-        jsonrpc: "2.0",
-        id: 2,
-        method: "getEvents",
-        params,
-    };
-
-    let resp: JsonRpcResponse<GetEventsResult> =
-        client.post(rpc_url).json(&req).send().await?.json().await?;
-
-    let result = match resp.result {
-        Some(r) => r,
-        None => return Err(anyhow::anyhow!("getEvents(deposit) error: {:?}", resp.error)),
-    };
-
-    let mut events = Vec::new();
-    let mut last_cursor = None;
-
-    for ev in &result.events {
-        last_cursor = ev.id.clone();
-        match parse_deposit_value(&ev.value, ev.ledger) {
-            Ok(de) => events.push(de),
-            Err(e) => eprintln!("skip deposit event parse: {e}"),
-        }
-    }
-
-    Ok(DepositPollResult {
-        events,
-        latest_ledger: result.latest_ledger,
-        cursor: last_cursor,
-    })
-}
-
-fn parse_deposit_value(value_b64: &str, ledger: u64) -> anyhow::Result<DepositEvent> {
-    let xdr_bytes = B64.decode(value_b64)?;
+fn parse_deposit_value(raw: RawEvent) -> anyhow::Result<DepositEvent> {
+    let xdr_bytes = B64.decode(&raw.value_xdr)?;
     let sc_val = ScVal::from_xdr(&xdr_bytes, Limits::none())?;
 
     // deposit event value is a #[contracttype] struct encoded as ScMap
     match sc_val {
         ScVal::Map(Some(map)) => {
             let cm = extract_bytes32_from_map(&map, "cm")?;
-            Ok(DepositEvent { cm, ledger })
+            Ok(DepositEvent { cm, ledger: raw.ledger, id: raw.id.clone(), raw })
         }
         _ => Err(anyhow::anyhow!("unexpected deposit event value shape: {sc_val:?}")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_call_invokes_the_contract() {
+        // any well-formed contract strkey
+        let contract = stellar_xdr::curr::ContractId(stellar_xdr::curr::Hash([7; 32])).to_string();
+        let b64 = read_only_call(&contract, "latest_root").unwrap();
+        let envelope = TransactionEnvelope::from_xdr(B64.decode(b64).unwrap(), Limits::none()).unwrap();
+        let TransactionEnvelope::Tx(envelope) = envelope else {
+            panic!("not a v1 envelope");
+        };
+        let [op] = envelope.tx.operations.as_slice() else { panic!("expected one operation") };
+        let OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(call),
+            ..
+        }) = &op.body
+        else {
+            panic!("not a contract call");
+        };
+        assert_eq!(call.contract_address.to_string(), contract);
+        assert_eq!(call.function_name.to_utf8_string_lossy(), "latest_root");
+        assert!(call.args.is_empty());
+
+        assert!(read_only_call("GNOTACONTRACT", "latest_root").is_err());
+    }
+
+    #[test]
+    fn one_filter_asks_for_both_topics() {
+        let filter = build_topic_filter("CTRANSFER", &["transfer", "deposit"]);
+        let topics = &filter[0]["topics"];
+        assert_eq!(topics.as_array().unwrap().len(), 2);
+        assert_eq!(topics[1][0], topic_symbol("deposit"));
+        // tag 14, length 7, "deposit" padded to 8 bytes
+        let deposit = B64.decode(topic_symbol("deposit")).unwrap();
+        assert_eq!(deposit, [&[0, 0, 0, 14, 0, 0, 0, 7][..], b"deposit\0"].concat());
+    }
+
+    #[test]
+    fn full_pages_cover_only_the_ledgers_before_their_last() {
+        let page = |ledgers: &[u64]| GetEventsResult {
+            events: ledgers
+                .iter()
+                .map(|&ledger| RpcEvent {
+                    ledger,
+                    value: String::new(),
+                    id: None,
+                    topic: Vec::new(),
+                    tx_hash: String::new(),
+                    ledger_closed_at: String::new(),
+                })
+                .collect(),
+            latest_ledger: 900,
+            oldest_ledger: None,
+        };
+        assert_eq!(read_through(&page(&[])), (900, false));
+        assert_eq!(read_through(&page(&[500, 501])), (900, false));
+        let full: Vec<u64> = (0..PAGE_LIMIT as u64).map(|i| 500 + i / 10).collect();
+        assert_eq!(read_through(&page(&full)), (508, true));
+    }
+}
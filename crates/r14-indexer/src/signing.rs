@@ -0,0 +1,60 @@
+use std::path::Path;
+
+use r14_errors::IndexerError;
+use r14_sdk::absence::{self, NullifierAbsence};
+use r14_types::{MerkleRoot, Nullifier};
+use zeroize::Zeroizing;
+
+/// Key the indexer signs nullifier absence statements with, and the
+/// contract they are about
+pub struct AbsenceSigner {
+    secret_key: Zeroizing<[u8; 32]>,
+    contract_id: String,
+}
+
+impl AbsenceSigner {
+    pub fn new(secret_key: [u8; 32], contract_id: &str) -> Self {
+        Self { secret_key: Zeroizing::new(secret_key), contract_id: contract_id.to_string() }
+    }
+
+    /// The Ed25519 secret key in `path`, 32 bytes as 64 hex digits, e.g.
+    /// written by `openssl rand -hex 32`
+    pub fn from_file(path: &Path, contract_id: &str) -> Result<Self, IndexerError> {
+        let invalid = |msg: String| IndexerError::InvalidInput(format!("signing key {}: {msg}", path.display()));
+        let data = Zeroizing::new(std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?);
+        let mut secret_key = Zeroizing::new([0; 32]);
+        hex::decode_to_slice(r14_sdk::strip_0x(data.trim()), secret_key.as_mut())
+            .map_err(|_| invalid("expected 64 hex digits".into()))?;
+        Ok(Self::new(*secret_key, contract_id))
+    }
+
+    /// The key clients check statements against
+    pub fn public_key(&self) -> [u8; 32] {
+        absence::public_key(&self.secret_key)
+    }
+
+    pub fn sign(&self, nullifier: Nullifier, root: MerkleRoot, leaf_count: u64, ledger: u64) -> NullifierAbsence {
+        NullifierAbsence::sign(&self.secret_key, &self.contract_id, nullifier, root, leaf_count, ledger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_read_from_hex_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("indexer.key");
+        std::fs::write(&path, format!("{}\n", "07".repeat(32))).unwrap();
+        let signer = AbsenceSigner::from_file(&path, "CTRANSFER").unwrap();
+        assert_eq!(signer.public_key(), absence::public_key(&[7; 32]));
+
+        for bad in ["07".repeat(31), "zz".repeat(32)] {
+            std::fs::write(&path, bad).unwrap();
+            let Err(err) = AbsenceSigner::from_file(&path, "CTRANSFER") else { panic!("bad key accepted") };
+            assert!(err.to_string().contains("64 hex digits"), "{err}");
+        }
+        assert!(AbsenceSigner::from_file(&dir.path().join("missing"), "CTRANSFER").is_err());
+    }
+}
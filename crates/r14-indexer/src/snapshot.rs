@@ -0,0 +1,219 @@
+use std::io::{self, Read, Write};
+
+use r14_errors::IndexerError;
+use r14_types::{Commitment, MerkleRoot, Nullifier};
+
+use crate::db::{fr_from_bytes, fr_to_bytes, HistoricalRoot};
+use crate::tree::SparseMerkleTree;
+
+const MAGIC: &[u8; 8] = b"R14SNAP1";
+
+/// Indexed state in a file one indexer exports and a fresh one imports,
+/// instead of replaying the contract's events from the start.
+///
+/// The file is `R14SNAP1`, then the contract id, the cursor's ledger and
+/// paging cursor, and the root, then the leaves, nullifiers and recorded
+/// roots, each list prefixed with its length. Integers are big-endian,
+/// strings are a `u32` length and UTF-8, and field elements 32 bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// Transfer contract the state was indexed from
+    pub contract_id: String,
+    /// Last indexed ledger
+    pub ledger: u64,
+    /// Paging cursor within it, if the poller had one
+    pub cursor: Option<String>,
+    /// Root of the tree over `leaves`
+    pub root: MerkleRoot,
+    /// Commitments in tree order, each with the ledger it was indexed in
+    pub leaves: Vec<(Commitment, u64)>,
+    /// Spent nullifiers, each with the ledger it was spent in
+    pub nullifiers: Vec<(Nullifier, u64)>,
+    /// Recorded roots, oldest first
+    pub roots: Vec<HistoricalRoot>,
+}
+
+impl Snapshot {
+    /// A snapshot of `leaves` and the rest, with the root they hash to
+    pub fn new(
+        contract_id: &str,
+        (ledger, cursor): (u64, Option<String>),
+        leaves: Vec<(Commitment, u64)>,
+        nullifiers: Vec<(Nullifier, u64)>,
+        roots: Vec<HistoricalRoot>,
+    ) -> Self {
+        let tree: SparseMerkleTree = SparseMerkleTree::from_leaves(leaves.iter().map(|&(leaf, _)| leaf).collect());
+        Self {
+            contract_id: contract_id.to_string(),
+            ledger,
+            cursor,
+            root: tree.root(),
+            leaves,
+            nullifiers,
+            roots,
+        }
+    }
+
+    /// The tree over the snapshot's leaves, checked against its root
+    pub fn tree(&self) -> Result<SparseMerkleTree, IndexerError> {
+        let tree: SparseMerkleTree = SparseMerkleTree::from_leaves(self.leaves.iter().map(|&(leaf, _)| leaf).collect());
+        if tree.root() != self.root {
+            return Err(IndexerError::InvalidInput(format!(
+                "snapshot leaves hash to {:?}, its header says {:?}",
+                tree.root(),
+                self.root
+            )));
+        }
+        Ok(tree)
+    }
+
+    pub fn write_to(&self, mut w: impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        write_str(&mut w, &self.contract_id)?;
+        w.write_all(&self.ledger.to_be_bytes())?;
+        write_str(&mut w, self.cursor.as_deref().unwrap_or_default())?;
+        write_fr(&mut w, &self.root.0)?;
+        w.write_all(&(self.leaves.len() as u64).to_be_bytes())?;
+        for (leaf, ledger) in &self.leaves {
+            write_fr(&mut w, &leaf.0)?;
+            w.write_all(&ledger.to_be_bytes())?;
+        }
+        w.write_all(&(self.nullifiers.len() as u64).to_be_bytes())?;
+        for (nullifier, ledger) in &self.nullifiers {
+            write_fr(&mut w, &nullifier.0)?;
+            w.write_all(&ledger.to_be_bytes())?;
+        }
+        w.write_all(&(self.roots.len() as u64).to_be_bytes())?;
+        for root in &self.roots {
+            write_fr(&mut w, &root.root.0)?;
+            w.write_all(&root.leaf_count.to_be_bytes())?;
+            w.write_all(&root.ledger.to_be_bytes())?;
+        }
+        w.flush()
+    }
+
+    /// Parse a snapshot. Doesn't check the root; [`Self::tree`] does.
+    pub fn read_from(mut r: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not an r14-indexer snapshot"));
+        }
+        let contract_id = read_str(&mut r)?;
+        let ledger = read_u64(&mut r)?;
+        let cursor = Some(read_str(&mut r)?).filter(|c| !c.is_empty());
+        let root = MerkleRoot(read_fr(&mut r)?);
+        let leaves = read_list(&mut r, |r| Ok((Commitment(read_fr(r)?), read_u64(r)?)))?;
+        let nullifiers = read_list(&mut r, |r| Ok((Nullifier(read_fr(r)?), read_u64(r)?)))?;
+        let roots = read_list(&mut r, |r| {
+            Ok(HistoricalRoot {
+                root: MerkleRoot(read_fr(r)?),
+                leaf_count: read_u64(r)?,
+                ledger: read_u64(r)?,
+            })
+        })?;
+        if r.read(&mut [0])? != 0 {
+            return Err(invalid("trailing bytes after the snapshot"));
+        }
+        Ok(Self { contract_id, ledger, cursor, root, leaves, nullifiers, roots })
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn write_str(w: &mut impl Write, s: &str) -> io::Result<()> {
+    let len = u32::try_from(s.len()).map_err(|_| invalid("string too long"))?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn write_fr(w: &mut impl Write, fr: &ark_bls12_381::Fr) -> io::Result<()> {
+    let bytes = fr_to_bytes(fr);
+    // big-endian, so pad on the left
+    w.write_all(&[0; 32][bytes.len()..])?;
+    w.write_all(&bytes)
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_be_bytes(buf))
+}
+
+fn read_str(r: &mut impl Read) -> io::Result<String> {
+    let mut len = [0; 4];
+    r.read_exact(&mut len)?;
+    let mut buf = Vec::new();
+    r.take(u32::from_be_bytes(len).into()).read_to_end(&mut buf)?;
+    if buf.len() != u32::from_be_bytes(len) as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(buf).map_err(|_| invalid("string is not UTF-8"))
+}
+
+fn read_fr(r: &mut impl Read) -> io::Result<ark_bls12_381::Fr> {
+    let mut buf = [0; 32];
+    r.read_exact(&mut buf)?;
+    Ok(fr_from_bytes(&buf))
+}
+
+/// A length-prefixed list. The length isn't trusted for the allocation,
+/// so a corrupt one fails at the end of the file instead.
+fn read_list<R: Read, T>(r: &mut R, mut item: impl FnMut(&mut R) -> io::Result<T>) -> io::Result<Vec<T>> {
+    let len = read_u64(r)?;
+    let mut items = Vec::with_capacity(len.min(1 << 16) as usize);
+    for _ in 0..len {
+        items.push(item(r)?);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn snapshot() -> Snapshot {
+        let leaves: Vec<_> = (1..=5u64).map(|v| (Commitment(Fr::from(v)), 100 + v / 2)).collect();
+        let tree: SparseMerkleTree = SparseMerkleTree::from_leaves(leaves.iter().map(|&(c, _)| c).collect());
+        let roots = vec![HistoricalRoot { root: tree.root(), leaf_count: 5, ledger: 102 }];
+        let cursor = (103, Some("0000000103-0000000001".into()));
+        Snapshot::new("CTRANSFER", cursor, leaves, vec![(Nullifier(Fr::from(77u64)), 102)], roots)
+    }
+
+    #[test]
+    fn snapshots_round_trip() {
+        let snapshot = snapshot();
+        let mut file = Vec::new();
+        snapshot.write_to(&mut file).unwrap();
+        // 8 + 13 + 8 + 25 + 32 + (8 + 5 * 40) + (8 + 40) + (8 + 48)
+        assert_eq!(file.len(), 398);
+        let read = Snapshot::read_from(file.as_slice()).unwrap();
+        assert_eq!(read, snapshot);
+        assert_eq!(read.tree().unwrap().next_index(), 5);
+
+        let no_cursor = Snapshot { cursor: None, ..snapshot };
+        file.clear();
+        no_cursor.write_to(&mut file).unwrap();
+        assert_eq!(Snapshot::read_from(file.as_slice()).unwrap(), no_cursor);
+    }
+
+    #[test]
+    fn damaged_snapshots_are_rejected() {
+        let mut file = Vec::new();
+        snapshot().write_to(&mut file).unwrap();
+
+        assert!(Snapshot::read_from(&file[..file.len() - 1]).is_err());
+        assert!(Snapshot::read_from([file.as_slice(), &[0]].concat().as_slice()).is_err());
+        let mut wrong_magic = file.clone();
+        wrong_magic[0] = b'X';
+        assert!(Snapshot::read_from(wrong_magic.as_slice()).is_err());
+
+        let mut tampered = snapshot();
+        tampered.leaves[2].0 = Commitment(Fr::from(99u64));
+        let Err(err) = tampered.tree() else { panic!("tampered leaves accepted") };
+        assert!(err.to_string().contains("hash to"), "{err}");
+    }
+}
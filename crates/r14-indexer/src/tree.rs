@@ -1,107 +1,11 @@
-use ark_bls12_381::Fr;
-use ark_ff::AdditiveGroup;
 use r14_poseidon::hash2;
-use r14_types::{MerklePath, MerkleRoot, MERKLE_DEPTH};
+use r14_types::{Commitment, MerklePath, MerkleRoot};
 
-pub struct SparseMerkleTree {
-    leaves: Vec<Fr>,
-    zeros: Vec<Fr>,
-}
-
-impl SparseMerkleTree {
-    pub fn new() -> Self {
-        let mut zeros = vec![Fr::ZERO; MERKLE_DEPTH + 1];
-        for i in 1..=MERKLE_DEPTH {
-            zeros[i] = hash2(zeros[i - 1], zeros[i - 1]);
-        }
-        Self {
-            leaves: Vec::new(),
-            zeros,
-        }
-    }
-
-    pub fn insert(&mut self, leaf: Fr) -> usize {
-        let idx = self.leaves.len();
-        self.leaves.push(leaf);
-        idx
-    }
-
-    pub fn next_index(&self) -> usize {
-        self.leaves.len()
-    }
-
-    pub fn leaves(&self) -> &[Fr] {
-        &self.leaves
-    }
-
-    pub fn root(&self) -> MerkleRoot {
-        if self.leaves.is_empty() {
-            return MerkleRoot(self.zeros[MERKLE_DEPTH]);
-        }
-        let mut layer: Vec<Fr> = self.leaves.clone();
-        for level in 0..MERKLE_DEPTH {
-            let mut next = Vec::with_capacity((layer.len() + 1) / 2);
-            let zero = self.zeros[level];
-            let mut i = 0;
-            while i < layer.len() {
-                let left = layer[i];
-                let right = if i + 1 < layer.len() {
-                    layer[i + 1]
-                } else {
-                    zero
-                };
-                next.push(hash2(left, right));
-                i += 2;
-            }
-            layer = next;
-        }
-        MerkleRoot(layer[0])
-    }
-
-    pub fn proof(&self, index: usize) -> MerklePath {
-        assert!(index < self.leaves.len(), "index out of bounds");
-        let mut siblings = Vec::with_capacity(MERKLE_DEPTH);
-        let mut indices = Vec::with_capacity(MERKLE_DEPTH);
-        let mut layer: Vec<Fr> = self.leaves.clone();
-        let mut idx = index;
-
-        for level in 0..MERKLE_DEPTH {
-            let zero = self.zeros[level];
-            let is_right = idx & 1 == 1;
-            indices.push(is_right);
-
-            let sibling_idx = if is_right { idx - 1 } else { idx + 1 };
-            let sibling = if sibling_idx < layer.len() {
-                layer[sibling_idx]
-            } else {
-                zero
-            };
-            siblings.push(sibling);
-
-            // build next layer
-            let mut next = Vec::with_capacity((layer.len() + 1) / 2);
-            let mut i = 0;
-            while i < layer.len() {
-                let left = layer[i];
-                let right = if i + 1 < layer.len() {
-                    layer[i + 1]
-                } else {
-                    zero
-                };
-                next.push(hash2(left, right));
-                i += 2;
-            }
-            layer = next;
-            idx /= 2;
-        }
-
-        MerklePath { siblings, indices }
-    }
-}
+pub use r14_sdk::merkle::{Frontier, SparseMerkleTree};
 
 /// Verify a Merkle proof against a root (used in tests + API consumers)
-pub fn verify_proof(leaf: Fr, path: &MerklePath, root: &MerkleRoot) -> bool {
-    let mut current = leaf;
+pub fn verify_proof(leaf: Commitment, path: &MerklePath, root: &MerkleRoot) -> bool {
+    let mut current = leaf.0;
     for i in 0..path.siblings.len() {
         if path.indices[i] {
             current = hash2(path.siblings[i], current);
@@ -115,34 +19,38 @@ pub fn verify_proof(leaf: Fr, path: &MerklePath, root: &MerkleRoot) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_ff::UniformRand;
+    use ark_bls12_381::Fr;
+    use ark_ff::{AdditiveGroup, UniformRand};
+    use r14_types::{LeafIndex, MERKLE_DEPTH};
+
+    type Tree = SparseMerkleTree;
 
     #[test]
     fn empty_root_deterministic() {
-        let t1 = SparseMerkleTree::new();
-        let t2 = SparseMerkleTree::new();
+        let t1 = Tree::new();
+        let t2 = Tree::new();
         assert_eq!(t1.root().0, t2.root().0);
     }
 
     #[test]
     fn single_insert_changes_root() {
-        let mut tree = SparseMerkleTree::new();
+        let mut tree = Tree::new();
         let empty_root = tree.root();
         let mut rng = ark_std::test_rng();
-        tree.insert(Fr::rand(&mut rng));
+        tree.insert(Commitment(Fr::rand(&mut rng)));
         assert_ne!(tree.root().0, empty_root.0);
     }
 
     #[test]
     fn proof_verifies() {
-        let mut tree = SparseMerkleTree::new();
+        let mut tree = Tree::new();
         let mut rng = ark_std::test_rng();
-        let leaf = Fr::rand(&mut rng);
+        let leaf = Commitment(Fr::rand(&mut rng));
         tree.insert(leaf);
-        tree.insert(Fr::rand(&mut rng));
-        tree.insert(Fr::rand(&mut rng));
+        tree.insert(Commitment(Fr::rand(&mut rng)));
+        tree.insert(Commitment(Fr::rand(&mut rng)));
 
-        let proof = tree.proof(0);
+        let proof = tree.proof(LeafIndex(0));
         let root = tree.root();
         assert!(verify_proof(leaf, &proof, &root));
     }
@@ -150,14 +58,14 @@ mod tests {
     #[test]
     fn rebuild_consistency() {
         let mut rng = ark_std::test_rng();
-        let leaves: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let leaves: Vec<Commitment> = (0..5).map(|_| Commitment(Fr::rand(&mut rng))).collect();
 
-        let mut t1 = SparseMerkleTree::new();
+        let mut t1 = Tree::new();
         for l in &leaves {
             t1.insert(*l);
         }
 
-        let mut t2 = SparseMerkleTree::new();
+        let mut t2 = Tree::new();
         for l in &leaves {
             t2.insert(*l);
         }
@@ -165,17 +73,94 @@ mod tests {
         assert_eq!(t1.root().0, t2.root().0);
     }
 
+    /// Full layer-by-layer rebuild, the pre-incremental algorithm
+    fn naive_root(leaves: &[Commitment]) -> Fr {
+        let mut zeros = vec![Fr::ZERO; MERKLE_DEPTH + 1];
+        for i in 1..=MERKLE_DEPTH {
+            zeros[i] = hash2(zeros[i - 1], zeros[i - 1]);
+        }
+        let mut layer: Vec<Fr> = leaves.iter().map(|c| c.0).collect();
+        for level in 0..MERKLE_DEPTH {
+            if layer.is_empty() {
+                return zeros[MERKLE_DEPTH];
+            }
+            layer = layer
+                .chunks(2)
+                .map(|c| hash2(c[0], c.get(1).copied().unwrap_or(zeros[level])))
+                .collect();
+        }
+        layer[0]
+    }
+
+    #[test]
+    fn incremental_matches_rebuild() {
+        let mut tree = Tree::new();
+        let mut rng = ark_std::test_rng();
+        let mut leaves = Vec::new();
+        assert_eq!(tree.root().0, naive_root(&leaves));
+        for _ in 0..9 {
+            let leaf = Commitment(Fr::rand(&mut rng));
+            leaves.push(leaf);
+            tree.insert(leaf);
+            assert_eq!(tree.root().0, naive_root(&leaves), "mismatch at {} leaves", leaves.len());
+        }
+    }
+
+    #[test]
+    fn from_leaves_matches_inserts() {
+        let mut rng = ark_std::test_rng();
+        let leaves: Vec<Commitment> = (0..7).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let mut tree = Tree::new();
+        for l in &leaves {
+            tree.insert(*l);
+        }
+        let mut bulk = Tree::from_leaves(leaves);
+        assert_eq!(bulk.root().0, tree.root().0);
+        assert_eq!(bulk.proof(LeafIndex(6)).siblings, tree.proof(LeafIndex(6)).siblings);
+
+        let leaf = Commitment(Fr::rand(&mut rng));
+        tree.insert(leaf);
+        assert_eq!(bulk.insert(leaf), LeafIndex(7));
+        assert_eq!(bulk.root().0, tree.root().0);
+        assert_eq!(Tree::from_leaves(Vec::new()).root().0, Tree::new().root().0);
+    }
+
+    #[test]
+    fn shallow_tree() {
+        let mut tree = SparseMerkleTree::<4>::new();
+        let mut rng = ark_std::test_rng();
+        let leaves: Vec<Commitment> = (0..16).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        for l in &leaves {
+            tree.insert(*l);
+        }
+        let root = tree.root();
+        let proof = tree.proof(LeafIndex(15));
+        assert_eq!(proof.siblings.len(), 4);
+        assert!(verify_proof(leaves[15], &proof, &root));
+        assert_eq!(root.0, SparseMerkleTree::<4>::from_leaves(leaves.clone()).root().0);
+        assert_ne!(root.0, Tree::from_leaves(leaves).root().0);
+    }
+
+    #[test]
+    #[should_panic(expected = "tree is full")]
+    fn insert_past_capacity() {
+        let mut tree = SparseMerkleTree::<2>::new();
+        for i in 0..5u64 {
+            tree.insert(Commitment(Fr::from(i)));
+        }
+    }
+
     #[test]
     fn all_proofs_verify() {
-        let mut tree = SparseMerkleTree::new();
+        let mut tree = Tree::new();
         let mut rng = ark_std::test_rng();
-        let leaves: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let leaves: Vec<Commitment> = (0..8).map(|_| Commitment(Fr::rand(&mut rng))).collect();
         for l in &leaves {
             tree.insert(*l);
         }
         let root = tree.root();
         for (i, leaf) in leaves.iter().enumerate() {
-            let proof = tree.proof(i);
+            let proof = tree.proof(i.into());
             assert!(verify_proof(*leaf, &proof, &root), "proof failed for index {i}");
         }
     }
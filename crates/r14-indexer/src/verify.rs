@@ -0,0 +1,119 @@
+use r14_types::{Commitment, MerkleRoot};
+
+use crate::db::HistoricalRoot;
+use crate::tree::SparseMerkleTree;
+
+/// The stored leaves rebuilt into a tree one at a time, checked against
+/// the roots recorded while indexing them
+pub struct Replay {
+    pub tree: SparseMerkleTree,
+    /// Recorded roots the rebuilt tree reached
+    pub matched: usize,
+    /// The first recorded root it didn't
+    pub mismatch: Option<Mismatch>,
+    /// Root after each prefix of the leaves, `roots[n]` over the first `n`
+    roots: Vec<MerkleRoot>,
+}
+
+/// A recorded root the stored leaves don't hash to
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    /// Leaf count of the last recorded root that matched, 0 if none did.
+    /// The first divergent leaf is between this index and the recorded
+    /// root's leaf count; exactly here when they are one leaf apart.
+    pub agreed_through: u64,
+    pub recorded: HistoricalRoot,
+    /// What the stored leaves give at that count; `None` if there are fewer
+    pub replayed: Option<MerkleRoot>,
+}
+
+impl Replay {
+    /// How many leaves the tree had when it reached `root`
+    pub fn leaf_count_at(&self, root: &MerkleRoot) -> Option<usize> {
+        self.roots.iter().rposition(|r| r == root)
+    }
+}
+
+/// Insert `leaves` into an empty tree one by one, and compare each root in
+/// `recorded` (oldest first) against the tree at its leaf count. Stops at
+/// the first that differs.
+pub fn replay(leaves: Vec<Commitment>, recorded: &[HistoricalRoot]) -> Replay {
+    let mut tree = SparseMerkleTree::new();
+    let mut roots = Vec::with_capacity(leaves.len() + 1);
+    roots.push(tree.root());
+    for leaf in leaves {
+        tree.insert(leaf);
+        roots.push(tree.root());
+    }
+
+    let (mut matched, mut agreed_through) = (0, 0);
+    let mut mismatch = None;
+    for root in recorded {
+        let replayed = usize::try_from(root.leaf_count).ok().and_then(|n| roots.get(n)).cloned();
+        if replayed.as_ref() == Some(&root.root) {
+            matched += 1;
+            agreed_through = root.leaf_count;
+        } else {
+            mismatch = Some(Mismatch { agreed_through, recorded: root.clone(), replayed });
+            break;
+        }
+    }
+    Replay { tree, matched, mismatch, roots }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn leaves(n: u64) -> Vec<Commitment> {
+        (1..=n).map(|v| Commitment(Fr::from(v))).collect()
+    }
+
+    fn root_of(leaves: &[Commitment]) -> MerkleRoot {
+        let tree: SparseMerkleTree = SparseMerkleTree::from_leaves(leaves.to_vec());
+        tree.root()
+    }
+
+    fn recorded(leaves: &[Commitment], counts: &[usize]) -> Vec<HistoricalRoot> {
+        counts
+            .iter()
+            .map(|&n| HistoricalRoot {
+                root: root_of(&leaves[..n]),
+                leaf_count: n as u64,
+                ledger: 100 + n as u64,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consistent_leaves_match_every_recorded_root() {
+        let leaves = leaves(6);
+        let replay = replay(leaves.clone(), &recorded(&leaves, &[1, 3, 6]));
+        assert_eq!((replay.matched, &replay.mismatch), (3, &None));
+        assert_eq!(replay.tree.root(), root_of(&leaves));
+
+        let at_three = root_of(&leaves[..3]);
+        assert_eq!(replay.leaf_count_at(&at_three), Some(3));
+        assert_eq!(replay.leaf_count_at(&MerkleRoot(Fr::from(5u64))), None);
+    }
+
+    #[test]
+    fn the_first_bad_leaf_is_bracketed_by_recorded_roots() {
+        let chain = leaves(6);
+        let roots = recorded(&chain, &[1, 3, 4, 6]);
+        let mut stored = chain.clone();
+        stored[3] = Commitment(Fr::from(99u64));
+
+        let mismatch = replay(stored.clone(), &roots).mismatch.unwrap();
+        assert_eq!(mismatch.agreed_through, 3);
+        assert_eq!(mismatch.recorded, roots[2]);
+        assert_eq!(mismatch.replayed, Some(root_of(&stored[..4])));
+
+        // leaves lost from storage
+        let replay = replay(chain[..4].to_vec(), &roots);
+        assert_eq!(replay.matched, 3);
+        let mismatch = replay.mismatch.unwrap();
+        assert_eq!((mismatch.agreed_through, mismatch.replayed), (4, None));
+    }
+}
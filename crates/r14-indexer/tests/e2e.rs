@@ -1,23 +1,21 @@
 use std::sync::Arc;
 
 use ark_bls12_381::Fr;
-use ark_ff::{BigInteger, PrimeField, UniformRand};
+use ark_ff::UniformRand;
 use axum::body::Body;
 use http_body_util::BodyExt;
 use tokio::sync::RwLock;
 use tower::ServiceExt;
 
 use r14_indexer::api::{AppState, SharedState};
-use r14_indexer::db::Db;
+use r14_indexer::db::{Db, Storage};
 use r14_indexer::tree::{verify_proof, SparseMerkleTree};
-
-fn fr_to_hex(fr: &Fr) -> String {
-    format!("0x{}", hex::encode(fr.into_bigint().to_bytes_be()))
-}
+use r14_types::hex::encode as fr_to_hex;
+use r14_types::{Commitment, LeafIndex};
 
 /// Build shared state from a temp DB path
 fn make_state(db: Db, tree: SparseMerkleTree) -> SharedState {
-    Arc::new(RwLock::new(AppState { tree, db }))
+    Arc::new(RwLock::new(AppState::new(tree, db)))
 }
 
 #[tokio::test]
@@ -27,14 +25,14 @@ async fn e2e_full_flow() {
 
     // ── 1. Setup: insert 5 random leaves ───────────────────────────────
     let mut rng = ark_std::test_rng();
-    let leaves: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+    let leaves: Vec<Commitment> = (0..5).map(|_| Commitment(Fr::rand(&mut rng))).collect();
 
     let db = Db::open(&db_path).unwrap();
     let mut tree = SparseMerkleTree::new();
 
     for (i, leaf) in leaves.iter().enumerate() {
         let idx = tree.insert(*leaf);
-        assert_eq!(idx, i);
+        assert_eq!(idx, LeafIndex::from(i));
         db.insert_leaf(idx, *leaf, 100 + i as u64).unwrap();
     }
 
@@ -42,7 +40,7 @@ async fn e2e_full_flow() {
 
     // ── 2. Proof verification ──────────────────────────────────────────
     for (i, leaf) in leaves.iter().enumerate() {
-        let proof = tree.proof(i);
+        let proof = tree.proof(i.into());
         assert!(
             verify_proof(*leaf, &proof, &root_after_insert),
             "proof failed for index {i}"
@@ -85,6 +83,7 @@ async fn e2e_full_flow() {
     let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
     let expected_root = fr_to_hex(&root_after_insert.0);
     assert_eq!(json["root"], expected_root);
+    assert_eq!(json["ledger"], 0);
 
     // /v1/proof/0 → 200, has siblings + indices
     let resp = app
@@ -115,9 +114,13 @@ async fn e2e_full_flow() {
         .await
         .unwrap();
     assert_eq!(resp.status(), 404);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["code"], "not_found");
+    assert_eq!(json["error"], "index out of bounds");
 
     // /v1/leaf/{hex} → 200, correct index + block_height
-    let leaf_hex = fr_to_hex(&leaves[2]);
+    let leaf_hex = fr_to_hex(&leaves[2].0);
     let resp = app
         .clone()
         .oneshot(
@@ -139,7 +142,7 @@ async fn e2e_full_flow() {
         .clone()
         .oneshot(
             axum::http::Request::builder()
-                .uri("/v1/leaf/0xdeadbeef00000000000000000000000000000000000000000000000000000000")
+                .uri("/v1/leaf/0x0deadbeef0000000000000000000000000000000000000000000000000000000")
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -147,13 +150,26 @@ async fn e2e_full_flow() {
         .unwrap();
     assert_eq!(resp.status(), 404);
 
+    // /v1/leaf/{not a field element} → 400
+    let resp = app
+        .clone()
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/v1/leaf/0xdeadbeef00000000000000000000000000000000000000000000000000000000")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
     // ── 4. Persistence: reopen DB, rebuild tree, same root ─────────────
     drop(state); // drop to release DB lock
     let db2 = Db::open(&db_path).unwrap();
     let loaded = db2.load_leaves().unwrap();
     assert_eq!(loaded.len(), 5);
 
-    let mut tree2 = SparseMerkleTree::new();
+    let mut tree2: SparseMerkleTree = SparseMerkleTree::new();
     for leaf in &loaded {
         tree2.insert(*leaf);
     }
@@ -169,3 +185,1191 @@ async fn e2e_full_flow() {
     let cursor = db2.load_cursor().unwrap();
     assert_eq!(cursor, Some((99, None)));
 }
+
+#[tokio::test]
+async fn subscribe_streams_indexed_leaves_and_roots() {
+    let tmp = tempfile::tempdir().unwrap();
+    let db = Db::open(&tmp.path().join("test.db")).unwrap();
+    let state = make_state(db, SparseMerkleTree::new());
+    let app = r14_indexer::api::router(state.clone());
+
+    let resp = app
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/v1/subscribe")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/event-stream");
+
+    let leaf = Commitment(Fr::from(7u64));
+    let root = {
+        let mut s = state.write().await;
+        s.index_leaf(leaf, 500);
+        s.publish_root();
+        s.tree.root()
+    };
+
+    let mut body = resp.into_body();
+    let mut text = String::new();
+    while !text.contains("event: root") {
+        let frame = body.frame().await.unwrap().unwrap();
+        if let Some(data) = frame.data_ref() {
+            text.push_str(std::str::from_utf8(data).unwrap());
+        }
+    }
+
+    let events: Vec<serde_json::Value> = text
+        .lines()
+        .filter_map(|l| l.strip_prefix("data: "))
+        .map(|d| serde_json::from_str(d).unwrap())
+        .collect();
+    assert_eq!(events[0]["type"], "leaf");
+    assert_eq!(events[0]["index"], 0);
+    assert_eq!(events[0]["commitment"], fr_to_hex(&leaf.0));
+    assert_eq!(events[0]["block_height"], 500);
+    assert_eq!(events[1]["type"], "root");
+    assert_eq!(events[1]["root"], fr_to_hex(&root.0));
+    assert_eq!(events[1]["leaf_count"], 1);
+}
+
+#[tokio::test]
+async fn subscribers_get_only_the_event_types_they_ask_for() {
+    use r14_indexer::events::IndexerEvent;
+    use r14_sdk::indexer::{EventType, IndexerEvent as SdkEvent};
+    use r14_types::Nullifier;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let db = Db::open(&tmp.path().join("test.db")).unwrap();
+    let state = make_state(db, SparseMerkleTree::new());
+    let app = r14_indexer::api::router(state.clone());
+
+    let resp = app
+        .clone()
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/v1/subscribe?types=nullifier,receipt")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+    let mut nullifiers = client.subscribe(&[EventType::Nullifier]).await.unwrap();
+    let mut everything = client.subscribe(&[]).await.unwrap();
+
+    let spent = Nullifier(Fr::from(5u64));
+    let root = {
+        let mut s = state.write().await;
+        s.index_leaf(Commitment(Fr::from(7u64)), 500);
+        s.index_nullifier(spent.clone(), 501);
+        s.publish_root();
+        let root = s.tree.root();
+        s.publish(IndexerEvent::rollback(499, 0, root.clone()));
+        root
+    };
+
+    let expected_nullifier = SdkEvent::Nullifier { nullifier: spent, block_height: 501 };
+    let expected_rollback = SdkEvent::Rollback { ledger: 499, leaf_count: 0, root };
+    // a rollback goes to every subscriber, whatever it asked for
+    assert_eq!(nullifiers.next().await.unwrap(), Some(expected_nullifier.clone()));
+    assert_eq!(nullifiers.next().await.unwrap(), Some(expected_rollback.clone()));
+
+    let mut events = Vec::new();
+    for _ in 0..4 {
+        events.push(everything.next().await.unwrap().unwrap());
+    }
+    assert!(matches!(events[0], SdkEvent::Leaf { block_height: 500, .. }));
+    assert_eq!(events[1], expected_nullifier);
+    assert!(matches!(events[2], SdkEvent::Root { leaf_count: 1, .. }));
+    assert_eq!(events[3], expected_rollback);
+}
+
+#[tokio::test]
+async fn sdk_indexer_client_reads_live_server() {
+    let tmp = tempfile::tempdir().unwrap();
+    let db = Db::open(&tmp.path().join("test.db")).unwrap();
+    let mut tree = SparseMerkleTree::new();
+    let leaves = [11u64, 22, 33].map(|v| Commitment(Fr::from(v)));
+    for (i, leaf) in leaves.iter().enumerate() {
+        let idx = tree.insert(*leaf);
+        db.insert_leaf(idx, *leaf, 700 + i as u64).unwrap();
+    }
+    let root = tree.root();
+    let app = r14_indexer::api::router(make_state(db, tree));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = r14_sdk::IndexerClient::new(&url);
+    client.health().await.unwrap();
+    assert_eq!(client.root().await.unwrap(), root.0);
+    assert_eq!(client.leaves().await.unwrap(), leaves);
+
+    let leaf = client.leaf(&leaves[1]).await.unwrap().unwrap();
+    assert_eq!(leaf.index, LeafIndex(1));
+    assert_eq!(leaf.block_height, 701);
+    assert!(client.leaf(&Commitment(Fr::from(99u64))).await.unwrap().is_none());
+
+    let path = client.merkle_proof(LeafIndex(2)).await.unwrap();
+    assert!(verify_proof(leaves[2], &path, &root));
+    // the SDK's local check agrees with the indexer's tree
+    assert!(r14_sdk::merkle::verify_proof(leaves[2], &path, root.0));
+    assert!(!r14_sdk::merkle::verify_proof(leaves[1], &path, root.0));
+
+    // indexer error bodies surface as R14Error::Indexer with their message
+    let err = client.merkle_proof(LeafIndex(3)).await.unwrap_err();
+    assert_eq!(err.code(), r14_sdk::error::ErrorCode::Indexer);
+    assert!(err.to_string().contains("index out of bounds"), "{err}");
+}
+
+#[tokio::test]
+async fn spent_nullifiers_are_served_and_synced() {
+    use r14_sdk::client::{R14Client, R14Contracts};
+    use r14_sdk::wallet::NoteEntry;
+    use r14_sdk::{nullifier, owner_hash, Note, SecretKey};
+
+    let tmp = tempfile::tempdir().unwrap();
+    let db_path = tmp.path().join("test.db");
+    let mut rng = ark_std::test_rng();
+    let sk = SecretKey::random(&mut rng);
+    let owner = owner_hash(&sk).0;
+    let notes: Vec<Note> = (0..3).map(|i| Note::new(100 * (i + 1), 1, owner, &mut rng)).collect();
+    let entries: Vec<NoteEntry> = notes
+        .iter()
+        .enumerate()
+        .map(|(i, n)| NoteEntry {
+            index: Some(i.into()),
+            ..NoteEntry::new(n, &r14_sdk::commitment(n))
+        })
+        .collect();
+
+    // notes 0 and 2 were spent from another device
+    let state = make_state(Db::open(&db_path).unwrap(), SparseMerkleTree::new());
+    let spent_0 = nullifier(&sk, &notes[0].nonce, LeafIndex(0));
+    let spent_2 = nullifier(&sk, &notes[2].nonce, LeafIndex(2));
+    {
+        let mut s = state.write().await;
+        s.index_nullifier(spent_0.clone(), 900);
+        s.index_nullifier(spent_2.clone(), 901);
+        // replays keep the first block height
+        s.index_nullifier(spent_0.clone(), 950);
+    }
+    let app = r14_indexer::api::router(state.clone());
+
+    let resp = app
+        .clone()
+        .oneshot(
+            axum::http::Request::builder()
+                .uri(format!("/v1/nullifier/{}", fr_to_hex(&spent_0.0)))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["nullifier"], fr_to_hex(&spent_0.0));
+    assert_eq!(json["block_height"], 900);
+
+    let resp = app
+        .clone()
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/v1/nullifier/zz")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let indexer = r14_sdk::IndexerClient::new(&url);
+    let unspent = nullifier(&sk, &notes[1].nonce, LeafIndex(1));
+    assert!(indexer.nullifier(&unspent).await.unwrap().is_none());
+
+    let client = R14Client::new(
+        &url,
+        R14Contracts {
+            core: "PLACEHOLDER".into(),
+            transfer: "PLACEHOLDER".into(),
+        },
+        "S_SECRET",
+        "testnet",
+    )
+    .unwrap();
+    let mut wallet_notes = entries.clone();
+    assert_eq!(client.sync_spent(&mut wallet_notes, &sk.0).await.unwrap(), 2);
+    let spent: Vec<bool> = wallet_notes.iter().map(|n| n.spent).collect();
+    assert_eq!(spent, [true, false, true]);
+    assert_eq!(client.sync_spent(&mut wallet_notes, &sk.0).await.unwrap(), 0);
+
+    // the nullifier binds the position: the same note at another index, or
+    // with no index yet, isn't marked
+    let mut moved = entries.clone();
+    moved[0].index = Some(LeafIndex(5));
+    moved[2].index = None;
+    assert_eq!(client.sync_spent(&mut moved, &sk.0).await.unwrap(), 0);
+
+    // a different key derives different nullifiers
+    let other = SecretKey::random(&mut rng);
+    let mut untouched = entries.clone();
+    assert_eq!(client.sync_spent(&mut untouched, &other.0).await.unwrap(), 0);
+    assert!(untouched.iter().all(|n| !n.spent));
+
+    // the same nullifiers, for matching against /v1/subscribe instead
+    let watched = R14Client::watched_nullifiers(&entries, &sk.0).unwrap();
+    assert_eq!((watched.len(), watched[&spent_0], watched[&spent_2]), (3, 0, 2));
+    assert_eq!(R14Client::watched_nullifiers(&wallet_notes, &sk.0).unwrap().len(), 1);
+    assert!(R14Client::watched_nullifiers(&entries, &other.0).unwrap().is_empty());
+
+    // a pending transfer whose nullifier landed is confirmed, others stay
+    let mut wallet = r14_sdk::wallet::WalletData::for_test(&sk);
+    wallet.notes = entries[..2].to_vec();
+    wallet.indexer_url = url.clone();
+    let change = entries[2].clone();
+    r14_sdk::pending::begin_transfer(&mut wallet, 0, &fr_to_hex(&spent_0.0), vec![change]).unwrap();
+    r14_sdk::pending::begin_transfer(&mut wallet, 1, &fr_to_hex(&unspent.0), vec![]).unwrap();
+    assert_eq!(client.resolve_pending(&mut wallet).await.unwrap(), 1);
+    assert_eq!(wallet.pending.len(), 1);
+    assert_eq!(wallet.pending[0].nullifier, fr_to_hex(&unspent.0));
+    assert_eq!(wallet.notes.len(), 3);
+
+    // nullifiers survive a restart
+    drop(state);
+    let db = Db::open(&db_path).unwrap();
+    assert_eq!(db.get_nullifier(&spent_2).unwrap(), Some(901));
+    assert_eq!(db.get_nullifier(&unspent).unwrap(), None);
+}
+
+#[tokio::test]
+async fn leaf_cache_fetches_only_new_leaves() {
+    use r14_sdk::leaf_cache::LeafCache;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let app = r14_indexer::api::router(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+    let index = |vs: &[u64]| {
+        let state = state.clone();
+        let cms: Vec<Commitment> = vs.iter().map(|&v| Commitment(Fr::from(v))).collect();
+        async move {
+            let mut s = state.write().await;
+            for (i, cm) in cms.into_iter().enumerate() {
+                s.index_leaf(cm, i as u64);
+            }
+        }
+    };
+
+    index(&[1, 2, 3]).await;
+    let page = client.leaves_from(LeafIndex(2)).await.unwrap();
+    assert_eq!((page.from, page.leaves.len()), (LeafIndex(2), 1));
+    assert_eq!(page.root, Some(state.read().await.tree.root()));
+    assert!(client.leaves_from(LeafIndex(9)).await.unwrap().leaves.is_empty());
+
+    let path = tmp.path().join("leaves.json");
+    let mut cache = LeafCache::open(&path).unwrap();
+    assert_eq!(cache.sync(&client).await.unwrap(), 3);
+    cache.save().unwrap();
+
+    index(&[4]).await;
+    let mut cache = LeafCache::open(&path).unwrap();
+    let new = [Commitment(Fr::from(5u64))];
+    let expected = r14_sdk::merkle::compute_new_root(&client, &new).await.unwrap();
+    assert_eq!(cache.new_root(&client, &new).await.unwrap(), expected);
+    assert_eq!(cache.len(), 4);
+    assert_eq!(cache.sync(&client).await.unwrap(), 0);
+
+    // an indexer rebuilt with different leaves: the cache starts over
+    state.write().await.tree = SparseMerkleTree::new();
+    index(&[7, 8]).await;
+    assert_eq!(cache.sync(&client).await.unwrap(), 2);
+    assert_eq!(cache.leaves(), client.leaves().await.unwrap());
+    assert_eq!(cache.root(), state.read().await.tree.root());
+}
+
+#[tokio::test]
+async fn absence_statements_are_signed_for_unspent_nullifiers() {
+    use r14_indexer::signing::AbsenceSigner;
+    use r14_types::Nullifier;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let (spent, unspent) = (Nullifier(Fr::from(5u64)), Nullifier(Fr::from(6u64)));
+    let root = {
+        let mut s = state.write().await;
+        s.index_leaf(Commitment(Fr::from(7u64)), 300);
+        s.index_nullifier(spent.clone(), 301);
+        s.ledger = 305;
+        s.tree.root()
+    };
+    let app = r14_indexer::api::router(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+    let key = r14_sdk::absence::public_key(&[9; 32]);
+
+    let err = client.nullifier_absence(&unspent, &key).await.unwrap_err();
+    assert!(err.to_string().contains("no signing key"), "{err}");
+
+    state.write().await.signer = Some(AbsenceSigner::new([9; 32], "CTRANSFER"));
+    let statement = client.nullifier_absence(&unspent, &key).await.unwrap();
+    assert_eq!(statement.nullifier, unspent);
+    assert_eq!(statement.contract_id, "CTRANSFER");
+    assert_eq!((statement.root, statement.leaf_count, statement.ledger), (root, 1, 305));
+
+    let err = client.nullifier_absence(&spent, &key).await.unwrap_err();
+    assert!(err.to_string().contains("spent in ledger 301"), "{err}");
+    // a statement from a key the client doesn't trust is refused
+    let other = r14_sdk::absence::public_key(&[8; 32]);
+    let err = client.nullifier_absence(&unspent, &other).await.unwrap_err();
+    assert!(err.to_string().contains("not the trusted key"), "{err}");
+}
+
+#[tokio::test]
+async fn leaves_are_paged_and_filtered_by_ledger() {
+    use r14_sdk::indexer::LeavesQuery;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let leaves: Vec<Commitment> = (1..=5u64).map(|v| Commitment(Fr::from(v))).collect();
+    {
+        let mut s = state.write().await;
+        for (cm, ledger) in leaves.iter().zip([10, 10, 11, 13, 13]) {
+            s.index_leaf(*cm, ledger);
+        }
+    }
+    let app = r14_indexer::api::router(state);
+    for bad in ["limit=0", "limit=10001", "from_index=x"] {
+        let resp = app
+            .clone()
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri(format!("/v1/leaves?{bad}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 400, "{bad}");
+    }
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+
+    let page = |from, limit, since_ledger| {
+        client.leaves_page(LeavesQuery { from: LeafIndex(from), limit, since_ledger })
+    };
+    let first = page(0, Some(2), None).await.unwrap();
+    assert_eq!((first.from, &first.leaves[..], first.next), (LeafIndex(0), &leaves[..2], Some(LeafIndex(2))));
+    let last = page(4, Some(2), None).await.unwrap();
+    assert_eq!((&last.leaves[..], last.next), (&leaves[4..], None));
+    assert_eq!(page(0, None, None).await.unwrap().leaves, leaves);
+
+    // leaves from ledger 11 on, starting at whichever is later
+    let since = page(0, Some(2), Some(11)).await.unwrap();
+    assert_eq!((since.from, &since.leaves[..], since.next), (LeafIndex(2), &leaves[2..4], Some(LeafIndex(4))));
+    assert_eq!(page(0, None, Some(12)).await.unwrap().from, LeafIndex(3));
+    assert_eq!(page(4, None, Some(11)).await.unwrap().from, LeafIndex(4));
+    let none = page(0, None, Some(14)).await.unwrap();
+    assert_eq!((none.from, none.leaves.len(), none.next), (LeafIndex(5), 0, None));
+
+    // `from` still works for older clients
+    assert_eq!(client.leaves_from(LeafIndex(3)).await.unwrap().leaves, leaves[3..]);
+}
+
+#[tokio::test]
+async fn storage_url_picks_the_backend_and_logs_events() {
+    use r14_indexer::events::IndexerEvent;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let url = format!("sqlite://{}", tmp.path().join("test.db").display());
+    let state = Arc::new(RwLock::new(AppState::with_storage(
+        SparseMerkleTree::new(),
+        r14_indexer::db::open(&url).unwrap(),
+    )));
+    {
+        let mut s = state.write().await;
+        s.index_leaf(Commitment(Fr::from(3u64)), 40);
+        // a second indexer sharing the database writes the same leaf again
+        s.db.insert_leaf(LeafIndex(0), Commitment(Fr::from(3u64)), 40).unwrap();
+        s.publish_root();
+    }
+    drop(state);
+
+    let db = r14_indexer::db::open(&url).unwrap();
+    assert_eq!(db.load_leaves().unwrap(), [Commitment(Fr::from(3u64))]);
+    let events = db.events_after(0, 10).unwrap();
+    assert_eq!(events.iter().map(|(seq, _)| *seq).collect::<Vec<_>>(), [1, 2]);
+    assert!(matches!(events[0].1, IndexerEvent::Leaf { index: LeafIndex(0), block_height: 40, .. }));
+    assert!(matches!(events[1].1, IndexerEvent::Root { leaf_count: 1, .. }));
+    assert_eq!(db.events_after(1, 10).unwrap().len(), 1);
+
+    #[cfg(not(feature = "postgres"))]
+    assert!(r14_indexer::db::open("postgres://localhost/r14").is_err());
+}
+
+#[tokio::test]
+async fn rollback_restores_a_checkpoint() {
+    use r14_indexer::events::IndexerEvent;
+    use r14_types::Nullifier;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let mut s = state.write().await;
+
+    s.index_leaf(Commitment(Fr::from(1u64)), 100);
+    s.index_leaf(Commitment(Fr::from(2u64)), 100);
+    s.index_nullifier(Nullifier(Fr::from(10u64)), 100);
+    let kept = s.checkpoint(100);
+    s.db.save_checkpoint(&kept).unwrap();
+
+    // ledgers 101-102 are about to be orphaned
+    s.index_leaf(Commitment(Fr::from(3u64)), 101);
+    s.index_nullifier(Nullifier(Fr::from(11u64)), 101);
+    s.db.save_checkpoint(&s.checkpoint(101)).unwrap();
+    s.index_leaf(Commitment(Fr::from(4u64)), 102);
+    s.db.save_cursor(102, Some("0000000102-0000000001")).unwrap();
+    s.db.save_checkpoint(&s.checkpoint(102)).unwrap();
+
+    let checkpoint = s.db.checkpoint_at_or_before(101).unwrap().unwrap();
+    assert_eq!(checkpoint.ledger, 101);
+    let checkpoint = s.db.checkpoint_at_or_before(100).unwrap().unwrap();
+    assert_eq!(checkpoint, kept);
+    let mut rx = s.events.subscribe();
+    s.rollback_to(&checkpoint).unwrap();
+
+    assert_eq!(s.tree.root(), kept.root);
+    assert_eq!((s.tree.next_index(), s.ledger), (2, 100));
+    assert_eq!(s.db.load_leaves().unwrap(), [Commitment(Fr::from(1u64)), Commitment(Fr::from(2u64))]);
+    assert_eq!(s.db.get_nullifier(&Nullifier(Fr::from(10u64))).unwrap(), Some(100));
+    assert_eq!(s.db.get_nullifier(&Nullifier(Fr::from(11u64))).unwrap(), None);
+    assert_eq!(s.db.load_cursor().unwrap(), Some((100, None)));
+    assert_eq!(s.db.checkpoint_at_or_before(102).unwrap(), Some(kept));
+    assert!(matches!(rx.try_recv().unwrap(), IndexerEvent::Rollback { ledger: 100, leaf_count: 2, .. }));
+
+    // re-indexing the new chain reuses the freed indices
+    s.index_leaf(Commitment(Fr::from(5u64)), 101);
+    assert_eq!(s.db.get_leaf_by_commitment(Commitment(Fr::from(5u64))).unwrap(), Some((LeafIndex(2), 101)));
+}
+
+#[tokio::test]
+async fn leaves_stay_pending_until_confirmed() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let leaves = [11u64, 22, 33].map(|v| Commitment(Fr::from(v)));
+    {
+        let mut s = state.write().await;
+        s.confirmations = 3;
+        s.index_leaf(leaves[0], 100);
+        s.index_leaf(leaves[1], 101);
+        s.index_leaf(leaves[2], 103);
+        s.ledger = 104;
+    }
+    let app = r14_indexer::api::router(state.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = r14_sdk::IndexerClient::new(&url);
+    assert!(!client.leaf(&leaves[1]).await.unwrap().unwrap().pending);
+    assert!(client.leaf(&leaves[2]).await.unwrap().unwrap().pending);
+    let page = client.leaves_from(LeafIndex(1)).await.unwrap();
+    assert_eq!((page.leaves.len(), page.confirmed), (2, Some(2)));
+
+    state.write().await.ledger = 106;
+    assert!(!client.leaf(&leaves[2]).await.unwrap().unwrap().pending);
+    assert_eq!(client.leaves_from(LeafIndex(0)).await.unwrap().confirmed, Some(3));
+
+    // with no confirmation depth nothing is ever pending
+    state.write().await.confirmations = 0;
+    state.write().await.ledger = 0;
+    assert!(!client.leaf(&leaves[2]).await.unwrap().unwrap().pending);
+    assert_eq!(client.leaves_from(LeafIndex(0)).await.unwrap().confirmed, Some(3));
+}
+
+#[tokio::test]
+async fn historical_roots_are_served_and_rolled_back() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let mut roots = Vec::new();
+    {
+        let mut s = state.write().await;
+        // a two-leaf transfer, then a deposit
+        s.index_leaf(Commitment(Fr::from(1u64)), 100);
+        s.index_leaf(Commitment(Fr::from(2u64)), 100);
+        s.record_root(100);
+        roots.push(s.tree.root());
+        s.db.save_checkpoint(&s.checkpoint(100)).unwrap();
+        s.index_leaf(Commitment(Fr::from(3u64)), 101);
+        s.record_root(101);
+        roots.push(s.tree.root());
+    }
+    let app = r14_indexer::api::router(state.clone());
+
+    let resp = app
+        .clone()
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/v1/roots?limit=0")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = r14_sdk::IndexerClient::new(&url);
+    let history = client.roots(100).await.unwrap();
+    let summary: Vec<_> = history.iter().map(|r| (r.root.0, r.leaf_count, r.ledger)).collect();
+    assert_eq!(summary, [(roots[1].0, 3, 101), (roots[0].0, 2, 100)]);
+    assert_eq!(client.roots(1).await.unwrap(), history[..1]);
+    assert_eq!(client.root_at(2).await.unwrap(), Some(history[1].clone()));
+    // no call ended between the transfer's two leaves
+    assert_eq!(client.root_at(1).await.unwrap(), None);
+
+    // a rollback forgets the roots after the checkpoint
+    {
+        let mut s = state.write().await;
+        let checkpoint = s.db.checkpoint_at_or_before(100).unwrap().unwrap();
+        s.rollback_to(&checkpoint).unwrap();
+    }
+    assert_eq!(client.root_at(3).await.unwrap(), None);
+    assert_eq!(client.roots(100).await.unwrap(), history[1..]);
+}
+
+#[tokio::test]
+async fn proof_by_commitment_uses_one_read() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let leaves = [11u64, 22, 33].map(|v| Commitment(Fr::from(v)));
+    let old_root = {
+        let mut s = state.write().await;
+        s.index_leaf(leaves[0], 100);
+        s.index_leaf(leaves[1], 100);
+        s.record_root(100);
+        let old_root = s.tree.root();
+        s.index_leaf(leaves[2], 101);
+        s.record_root(101);
+        old_root
+    };
+    let app = r14_indexer::api::router(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+
+    let proof = client.proof_by_commitment(&leaves[1], None).await.unwrap().unwrap();
+    assert_eq!((proof.index, proof.block_height, proof.leaf_count), (LeafIndex(1), 100, 3));
+    assert_eq!(proof.root, state.read().await.tree.root());
+    assert!(verify_proof(leaves[1], &proof.path, &proof.root));
+
+    // against the root before the last deposit
+    let proof = client.proof_by_commitment(&leaves[0], Some(&old_root)).await.unwrap().unwrap();
+    assert_eq!((proof.root.clone(), proof.leaf_count), (old_root.clone(), 2));
+    assert!(verify_proof(leaves[0], &proof.path, &old_root));
+    let err = client.proof_by_commitment(&leaves[2], Some(&old_root)).await.unwrap_err();
+    assert!(err.to_string().contains("not under that root"), "{err}");
+    // the next proof against it comes from the cached frontier
+    let proof = client.proof_by_commitment(&leaves[1], Some(&old_root)).await.unwrap().unwrap();
+    assert!(verify_proof(leaves[1], &proof.path, &old_root));
+
+    let unknown = r14_types::MerkleRoot(Fr::from(5u64));
+    assert!(client.proof_by_commitment(&leaves[0], Some(&unknown)).await.unwrap().is_none());
+    assert!(client.proof_by_commitment(&Commitment(Fr::from(99u64)), None).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn batch_proofs_are_read_under_one_root() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let leaves = [11u64, 22, 33].map(|v| Commitment(Fr::from(v)));
+    {
+        let mut s = state.write().await;
+        for leaf in leaves {
+            s.index_leaf(leaf, 100);
+        }
+    }
+    let app = r14_indexer::api::router(state.clone());
+    let post = |body: &str| {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/v1/proofs")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    };
+    for bad in [r#"{}"#, r#"{"indices":[0],"commitments":["0x01"]}"#, r#"{"leaves":[0]}"#, "not json"] {
+        let resp = app.clone().oneshot(post(bad)).await.unwrap();
+        assert_eq!(resp.status(), 400, "{bad}");
+    }
+    let too_many = format!(r#"{{"indices":{:?}}}"#, vec![0; r14_indexer::api::MAX_BATCH_PROOFS + 1]);
+    assert_eq!(app.clone().oneshot(post(&too_many)).await.unwrap().status(), 400);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+
+    let root = state.read().await.tree.root();
+    let batch = client.merkle_proofs(&[LeafIndex(2), LeafIndex(0)]).await.unwrap().unwrap();
+    assert_eq!((batch.root.clone(), batch.leaf_count), (root.clone(), 3));
+    assert_eq!(batch.proofs[0].index, LeafIndex(2));
+    assert!(verify_proof(leaves[2], &batch.proofs[0].path, &root));
+    assert!(verify_proof(leaves[0], &batch.proofs[1].path, &root));
+
+    let batch = client.merkle_proofs_by_commitment(&[leaves[1], leaves[0]]).await.unwrap().unwrap();
+    assert_eq!(batch.proofs.iter().map(|p| p.index).collect::<Vec<_>>(), [LeafIndex(1), LeafIndex(0)]);
+    assert!(verify_proof(leaves[1], &batch.proofs[0].path, &root));
+
+    // one unknown leaf fails the whole batch
+    let err = client.merkle_proofs(&[LeafIndex(0), LeafIndex(3)]).await.unwrap_err();
+    assert!(err.to_string().contains("out of bounds"), "{err}");
+    let err = client.merkle_proofs_by_commitment(&[Commitment(Fr::from(99u64))]).await.unwrap_err();
+    assert!(err.to_string().contains("not found"), "{err}");
+}
+
+#[tokio::test]
+async fn contract_events_are_logged_filtered_and_rolled_back() {
+    use r14_indexer::rpc::RawEvent;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let event = |id: &str, topic: &str, tx: &str, ledger: u64| RawEvent {
+        id: id.into(),
+        topic: topic.into(),
+        tx_hash: tx.into(),
+        ledger,
+        closed_at: "2026-10-16T12:00:00Z".into(),
+        value_xdr: "AAAAEQAAAAE=".into(),
+    };
+    let events = [
+        event("0000000100-0000000001", "deposit", "aa", 100),
+        event("0000000100-0000000002", "deposit", "aa", 100),
+        event("0000000101-0000000001", "transfer", "bb", 101),
+        event("0000000102-0000000001", "deposit", "cc", 102),
+    ];
+    {
+        let s = state.write().await;
+        for ev in &events {
+            s.record_event(ev);
+        }
+        // the poller re-reads a ledger after a restart
+        s.record_event(&events[0]);
+        s.db.save_checkpoint(&s.checkpoint(101)).unwrap();
+    }
+    let app = r14_indexer::api::router(state.clone());
+    let get = |uri: &str| {
+        let app = app.clone();
+        let req = axum::http::Request::builder().uri(uri).body(Body::empty()).unwrap();
+        async move {
+            let resp = app.oneshot(req).await.unwrap();
+            let status = resp.status();
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            (status, serde_json::from_slice::<serde_json::Value>(&body).unwrap())
+        }
+    };
+    let ids = |json: &serde_json::Value| {
+        json["events"].as_array().unwrap().iter().map(|ev| ev["id"].as_str().unwrap().to_string()).collect::<Vec<_>>()
+    };
+
+    let (status, json) = get("/v1/events").await;
+    assert_eq!(status, 200);
+    assert_eq!(ids(&json), events.iter().map(|ev| ev.id.clone()).collect::<Vec<_>>());
+    assert_eq!(json["events"][2], serde_json::to_value(&events[2]).unwrap());
+    assert_eq!(json["next"], serde_json::Value::Null);
+
+    // paging
+    let (_, page) = get("/v1/events?limit=3").await;
+    assert_eq!(page["next"], events[2].id);
+    let (_, rest) = get(&format!("/v1/events?limit=3&after={}", events[2].id)).await;
+    assert_eq!((ids(&rest), &rest["next"]), (vec![events[3].id.clone()], &serde_json::Value::Null));
+
+    // filters
+    assert_eq!(ids(&get("/v1/events?topic=transfer").await.1), [events[2].id.clone()]);
+    assert_eq!(ids(&get("/v1/events?tx_hash=aa").await.1).len(), 2);
+    assert_eq!(ids(&get("/v1/events?from_ledger=101&to_ledger=101").await.1), [events[2].id.clone()]);
+    assert_eq!(ids(&get("/v1/events?topic=deposit&from_ledger=101").await.1), [events[3].id.clone()]);
+    for bad in ["/v1/events?limit=0", "/v1/events?limit=1001", "/v1/events?topic=mint"] {
+        assert_eq!(get(bad).await.0, 400, "{bad}");
+    }
+
+    // a rollback drops the events of the ledgers it undoes
+    {
+        let mut s = state.write().await;
+        let checkpoint = s.db.checkpoint_at_or_before(101).unwrap().unwrap();
+        s.rollback_to(&checkpoint).unwrap();
+    }
+    assert_eq!(ids(&get("/v1/events").await.1).len(), 3);
+}
+
+#[tokio::test]
+async fn snapshots_bootstrap_a_fresh_database() {
+    use r14_indexer::snapshot::Snapshot;
+    use r14_types::Nullifier;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("source.db")).unwrap(), SparseMerkleTree::new());
+    {
+        let mut s = state.write().await;
+        assert_eq!(s.db.export_snapshot("CTRANSFER").unwrap(), None);
+        for v in 1..=4u64 {
+            s.index_leaf(Commitment(Fr::from(v)), 100 + v);
+            s.record_root(100 + v);
+        }
+        s.index_nullifier(Nullifier(Fr::from(42u64)), 103);
+        s.db.save_cursor(104, Some("0000000104-0000000002")).unwrap();
+    }
+
+    let source = state.read().await;
+    let snapshot = source.db.export_snapshot("CTRANSFER").unwrap().unwrap();
+    assert_eq!((snapshot.ledger, snapshot.root.clone()), (104, source.tree.root()));
+    let mut file = Vec::new();
+    snapshot.write_to(&mut file).unwrap();
+    let snapshot = Snapshot::read_from(file.as_slice()).unwrap();
+
+    let fresh = Db::open(&tmp.path().join("fresh.db")).unwrap();
+    fresh.import_snapshot(&snapshot).unwrap();
+    assert_eq!(fresh.load_leaves().unwrap(), source.db.load_leaves().unwrap());
+    assert_eq!(fresh.get_leaf_by_commitment(Commitment(Fr::from(3u64))).unwrap(), Some((LeafIndex(2), 103)));
+    assert_eq!(fresh.get_nullifier(&Nullifier(Fr::from(42u64))).unwrap(), Some(103));
+    assert_eq!(fresh.all_roots().unwrap(), source.db.all_roots().unwrap());
+    assert_eq!(fresh.load_cursor().unwrap(), Some((104, Some("0000000104-0000000002".into()))));
+    let checkpoint = fresh.checkpoint_at_or_before(104).unwrap().unwrap();
+    assert_eq!((checkpoint.leaf_count, checkpoint.root), (4, source.tree.root()));
+
+    // only into an empty database
+    let err = fresh.import_snapshot(&snapshot).unwrap_err();
+    assert!(err.to_string().contains("already holds 4 leaves"), "{err}");
+}
+
+#[tokio::test]
+async fn polls_are_stored_in_one_transaction() {
+    use r14_indexer::events::IndexerEvent;
+    use r14_types::Nullifier;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let path = tmp.path().join("test.db");
+    let state = make_state(Db::open(&path).unwrap(), SparseMerkleTree::new());
+    let mut s = state.write().await;
+    let mut rx = s.events.subscribe();
+
+    // nothing is written or published until the commit
+    s.begin_batch();
+    s.index_leaf(Commitment(Fr::from(1u64)), 100);
+    s.index_leaf(Commitment(Fr::from(2u64)), 100);
+    s.index_nullifier(Nullifier(Fr::from(10u64)), 100);
+    s.record_root(100);
+    s.publish_root();
+    assert!(s.db.load_leaves().unwrap().is_empty());
+    assert!(rx.try_recv().is_err());
+    let checkpoint = s.checkpoint(100);
+    s.commit_batch((100, Some("0000000100-0000000002".into())), Some(checkpoint.clone())).unwrap();
+    assert_eq!(s.db.load_leaves().unwrap().len(), 2);
+    assert_eq!(s.db.get_nullifier(&Nullifier(Fr::from(10u64))).unwrap(), Some(100));
+    assert_eq!(s.db.root_at(2).unwrap().map(|root| root.ledger), Some(100));
+    assert_eq!(s.db.load_cursor().unwrap(), Some((100, Some("0000000100-0000000002".into()))));
+    assert_eq!(s.db.checkpoint_at_or_before(100).unwrap(), Some(checkpoint));
+    assert!(matches!(rx.try_recv().unwrap(), IndexerEvent::Leaf { index: LeafIndex(0), .. }));
+
+    // a poll cut off before its commit leaves storage as it was
+    s.begin_batch();
+    s.index_leaf(Commitment(Fr::from(3u64)), 101);
+    drop(s);
+    drop(state);
+    let db = Db::open(&path).unwrap();
+    assert_eq!(db.load_leaves().unwrap().len(), 2);
+    assert_eq!(db.load_cursor().unwrap().map(|(ledger, _)| ledger), Some(100));
+
+    // the restart resumes from the cursor with the leaves it was stored with
+    let tree = SparseMerkleTree::from_leaves(db.load_leaves().unwrap());
+    let state = make_state(db, tree);
+    let mut s = state.write().await;
+    assert_eq!(s.index_leaf(Commitment(Fr::from(3u64)), 101), LeafIndex(2));
+    // an index another indexer on the same database already wrote is kept
+    s.db.insert_leaf(LeafIndex(2), Commitment(Fr::from(9u64)), 101).unwrap();
+    assert_eq!(s.db.load_leaves().unwrap()[2], Commitment(Fr::from(3u64)));
+
+    let conn = rusqlite::Connection::open(&path).unwrap();
+    let mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+    assert_eq!(mode, "wal");
+}
+
+#[tokio::test]
+async fn a_commitment_deposited_twice_is_two_leaves() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let mut s = state.write().await;
+    let (cm, other) = (Commitment(Fr::from(7u64)), Commitment(Fr::from(8u64)));
+
+    // once in one poll, then twice in the same staged batch
+    assert_eq!(s.index_leaf(cm, 100), LeafIndex(0));
+    s.begin_batch();
+    assert_eq!(s.index_leaf(other, 101), LeafIndex(1));
+    assert_eq!(s.index_leaf(cm, 101), LeafIndex(2));
+    assert_eq!(s.index_leaf(cm, 101), LeafIndex(3));
+    s.commit_batch((101, None), None).unwrap();
+
+    let leaves = vec![cm, other, cm, cm];
+    let expected: SparseMerkleTree = SparseMerkleTree::from_leaves(leaves.clone());
+    assert_eq!(s.tree.root(), expected.root());
+    assert_eq!(s.db.load_leaves().unwrap(), leaves);
+    // lookups by commitment find its first position
+    assert_eq!(s.db.get_leaf_by_commitment(cm).unwrap(), Some((LeafIndex(0), 100)));
+    assert!(verify_proof(cm, &s.tree.proof(LeafIndex(3)), &s.tree.root()));
+}
+
+/// Soroban RPC stand-in serving a fixed list of pages, then empty ones
+struct ScriptedRpc {
+    pages: std::sync::Mutex<std::collections::VecDeque<r14_indexer::rpc::PollResult>>,
+    /// `(start_ledger, cursor)` of each request
+    requests: std::sync::Mutex<Vec<(u64, Option<String>)>>,
+    latest_ledger: u64,
+}
+
+impl r14_indexer::rpc::RpcClient for ScriptedRpc {
+    async fn latest_ledger(&self) -> anyhow::Result<u64> {
+        Ok(self.latest_ledger)
+    }
+
+    async fn health(&self) -> anyhow::Result<r14_indexer::rpc::Health> {
+        Ok(r14_indexer::rpc::Health { latest_ledger: self.latest_ledger, oldest_ledger: 50 })
+    }
+
+    async fn poll_events(
+        &self,
+        _contract_id: &str,
+        start_ledger: u64,
+        cursor: Option<&str>,
+    ) -> anyhow::Result<r14_indexer::rpc::PollResult> {
+        self.requests.lock().unwrap().push((start_ledger, cursor.map(str::to_string)));
+        let next = self.pages.lock().unwrap().pop_front();
+        Ok(next.unwrap_or(r14_indexer::rpc::PollResult {
+            events: Vec::new(),
+            latest_ledger: self.latest_ledger,
+            oldest_ledger: None,
+            cursor: cursor.map(str::to_string),
+            read_through: self.latest_ledger,
+            more: false,
+        }))
+    }
+
+    fn endpoint(&self) -> &str {
+        "scripted"
+    }
+}
+
+#[tokio::test]
+async fn embedded_indexer_backfills_from_an_injected_rpc() {
+    use r14_indexer::config::{Args, Config, FileConfig};
+    use r14_indexer::indexer::Indexer;
+    use r14_indexer::rpc::{ContractEvent, DepositEvent, PollResult, RawEvent, TransferEvent};
+    use r14_types::Nullifier;
+
+    let bytes = |v: u8| {
+        let mut b = [0; 32];
+        b[31] = v;
+        b
+    };
+    let raw = |ledger: u64, topic: &str| RawEvent {
+        id: format!("{ledger:010}-0000000001"),
+        topic: topic.into(),
+        tx_hash: format!("{ledger:x}"),
+        ledger,
+        closed_at: String::new(),
+        value_xdr: String::new(),
+    };
+    let deposit = |ledger: u64, cm: u8| {
+        let raw = raw(ledger, "deposit");
+        ContractEvent::Deposit(DepositEvent { cm: bytes(cm), ledger, id: raw.id.clone(), raw })
+    };
+    let transfer = raw(102, "transfer");
+    let transfer = ContractEvent::Transfer(TransferEvent {
+        nullifier: bytes(9),
+        cm_0: bytes(3),
+        cm_1: bytes(4),
+        ledger: 102,
+        id: transfer.id.clone(),
+        raw: transfer,
+    });
+    // a full page through ledger 102, then the rest
+    let pages = [
+        PollResult {
+            events: vec![deposit(100, 1), deposit(101, 2), transfer],
+            latest_ledger: 200,
+            oldest_ledger: Some(50),
+            cursor: Some("0000000102-0000000001".into()),
+            read_through: 101,
+            more: true,
+        },
+        PollResult {
+            events: vec![deposit(150, 5)],
+            latest_ledger: 200,
+            oldest_ledger: Some(50),
+            cursor: Some("0000000150-0000000001".into()),
+            read_through: 200,
+            more: false,
+        },
+    ];
+    let rpc = ScriptedRpc {
+        pages: std::sync::Mutex::new(pages.into()),
+        requests: Default::default(),
+        latest_ledger: 200,
+    };
+
+    let tmp = tempfile::tempdir().unwrap();
+    let args = Args {
+        contract_id: Some("CTRANSFER".into()),
+        db_url: Some(tmp.path().join("test.db").display().to_string()),
+        poll_interval_secs: Some(1),
+        backfill_from: Some(100),
+        ..Args::default()
+    };
+    let config = Config::resolve(&args, FileConfig::default(), None).unwrap();
+    let indexer = Indexer::new(config.clone(), r14_indexer::db::open(&config.db_url).unwrap(), rpc).unwrap();
+    let state = indexer.state();
+    let (stop, shutdown) = tokio::sync::watch::channel(false);
+    let poller = tokio::spawn(indexer.run(shutdown));
+
+    tokio::time::timeout(std::time::Duration::from_secs(10), async {
+        while state.read().await.db.checkpoint_at_or_before(200).unwrap().is_none() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("the indexer never caught up");
+    stop.send(true).unwrap();
+    poller.await.unwrap().unwrap();
+
+    let s = state.read().await;
+    let leaves: Vec<_> = [1u64, 2, 3, 4, 5].map(|v| Commitment(Fr::from(v))).into();
+    assert_eq!(s.db.load_leaves().unwrap(), leaves);
+    let replayed: SparseMerkleTree = SparseMerkleTree::from_leaves(leaves);
+    assert_eq!(s.tree.root(), replayed.root());
+    assert_eq!(s.db.get_nullifier(&Nullifier(Fr::from(9u64))).unwrap(), Some(102));
+    assert_eq!(s.db.load_cursor().unwrap(), Some((200, Some("0000000150-0000000001".into()))));
+    // no checkpoint partway through the full page
+    assert_eq!(s.db.checkpoint_at_or_before(199).unwrap(), None);
+}
+
+#[tokio::test]
+async fn openapi_document_matches_the_checked_in_spec_and_responses() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let commitment = Commitment(Fr::from(7u64));
+    {
+        let mut s = state.write().await;
+        s.index_leaf(commitment, 100);
+        s.record_root(100);
+    }
+    let app = r14_indexer::api::router(state);
+    let get = |uri: String| {
+        let app = app.clone();
+        async move {
+            let resp = app
+                .oneshot(axum::http::Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), 200);
+            resp.into_body().collect().await.unwrap().to_bytes()
+        }
+    };
+
+    // regenerate with `r14-indexer openapi > crates/r14-indexer/openapi.json`
+    let spec: serde_json::Value = serde_json::from_slice(&get("/v1/openapi.json".into()).await).unwrap();
+    let checked_in: serde_json::Value = serde_json::from_str(include_str!("../openapi.json")).unwrap();
+    assert!(spec == checked_in, "openapi.json is out of date with the API");
+    let docs = get("/v1/docs".into()).await;
+    assert!(std::str::from_utf8(&docs).unwrap().contains("openapi.json"));
+
+    // every field a response carries is in its schema, and every required one is there
+    let cm = fr_to_hex(&commitment.0);
+    for (uri, schema) in [
+        ("/v1/root".to_string(), "RootResponse"),
+        ("/v1/roots".into(), "RootsResponse"),
+        ("/v1/leaves".into(), "LeavesResponse"),
+        (format!("/v1/leaf/{cm}"), "LeafResponse"),
+        (format!("/v1/proof/by-commitment/{cm}"), "CommitmentProof"),
+        ("/v1/proof/0".into(), "MerklePath"),
+        ("/v1/events".into(), "EventsResponse"),
+    ] {
+        let body: serde_json::Value = serde_json::from_slice(&get(uri.clone()).await).unwrap();
+        let schema = &spec["components"]["schemas"][schema];
+        let mut fields: Vec<_> = body.as_object().unwrap().keys().cloned().collect();
+        let mut properties: Vec<_> = schema["properties"].as_object().unwrap().keys().cloned().collect();
+        fields.sort();
+        properties.sort();
+        assert_eq!(fields, properties, "{uri}");
+        for required in schema["required"].as_array().unwrap() {
+            assert!(body.get(required.as_str().unwrap()).is_some(), "{uri} lacks {required}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn leaves_and_roots_are_compressed_and_revalidated() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    {
+        let mut s = state.write().await;
+        for v in 1..=50u64 {
+            s.index_leaf(Commitment(Fr::from(v)), 100);
+        }
+        s.record_root(100);
+    }
+    let app = r14_indexer::api::router(state.clone());
+    let get = |uri: &'static str, if_none_match: Option<axum::http::HeaderValue>| {
+        let app = app.clone();
+        async move {
+            let mut req = axum::http::Request::builder().uri(uri).header("accept-encoding", "gzip");
+            if let Some(tag) = if_none_match {
+                req = req.header("if-none-match", tag);
+            }
+            app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+        }
+    };
+
+    for uri in ["/v1/leaves", "/v1/roots"] {
+        let resp = get(uri, None).await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["content-encoding"], "gzip", "{uri}");
+        let etag = resp.headers()["etag"].clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""), "{etag:?}");
+
+        let resp = get(uri, Some(etag.clone())).await;
+        assert_eq!(resp.status(), 304, "{uri}");
+        assert_eq!(resp.headers()["etag"], etag);
+        assert!(resp.into_body().collect().await.unwrap().to_bytes().is_empty());
+        let other = axum::http::HeaderValue::from_static("\"stale\", W/\"older\"");
+        assert_eq!(get(uri, Some(other)).await.status(), 200);
+    }
+
+    // a new leaf changes the tree, and a new recorded root the list
+    let leaves_tag = get("/v1/leaves", None).await.headers()["etag"].clone();
+    let roots_tag = get("/v1/roots", None).await.headers()["etag"].clone();
+    {
+        let mut s = state.write().await;
+        s.index_leaf(Commitment(Fr::from(51u64)), 101);
+    }
+    assert_eq!(get("/v1/leaves", Some(leaves_tag)).await.status(), 200);
+    assert_eq!(get("/v1/roots", Some(roots_tag.clone())).await.status(), 304);
+    state.write().await.record_root(101);
+    assert_eq!(get("/v1/roots", Some(roots_tag)).await.status(), 200);
+}
+
+#[tokio::test]
+async fn stats_count_what_was_indexed() {
+    use r14_indexer::rpc::RawEvent;
+    use r14_types::Nullifier;
+
+    let tmp = tempfile::tempdir().unwrap();
+    let state = make_state(Db::open(&tmp.path().join("test.db")).unwrap(), SparseMerkleTree::new());
+    let app = r14_indexer::api::router(state.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+
+    let empty = client.stats().await.unwrap();
+    assert_eq!((empty.leaf_count, empty.nullifier_count, empty.deposits, empty.transfers), (0, 0, 0, 0));
+    assert_eq!((empty.first_ledger, empty.last_ledger), (None, None));
+
+    let event = |id: &str, topic: &str, ledger: u64| RawEvent {
+        id: id.into(),
+        topic: topic.into(),
+        tx_hash: "aa".into(),
+        ledger,
+        closed_at: "2026-10-16T12:00:00Z".into(),
+        value_xdr: "AAAAEQAAAAE=".into(),
+    };
+    {
+        let mut s = state.write().await;
+        s.ledger = 110;
+        s.index_leaf(Commitment(Fr::from(1u64)), 100);
+        s.record_event(&event("0000000100-0000000001", "deposit", 100));
+        s.index_leaf(Commitment(Fr::from(2u64)), 104);
+        s.index_leaf(Commitment(Fr::from(3u64)), 104);
+        s.index_nullifier(Nullifier(Fr::from(9u64)), 104);
+        s.record_event(&event("0000000104-0000000001", "transfer", 104));
+        s.db.save_cursor(108, None).unwrap();
+    }
+    let stats = client.stats().await.unwrap();
+    assert_eq!((stats.leaf_count, stats.nullifier_count, stats.deposits, stats.transfers), (3, 1, 1, 1));
+    assert_eq!((stats.first_ledger, stats.last_ledger, stats.network_ledger), (Some(100), Some(108), 110));
+    assert_eq!(stats.root, state.read().await.tree.root());
+}
+
+#[tokio::test]
+async fn pruned_leaves_are_read_back_from_storage() {
+    use r14_indexer::db::{Batch, HistoricalRoot};
+
+    let tmp = tempfile::tempdir().unwrap();
+    let db = Db::open(&tmp.path().join("test.db")).unwrap();
+    let leaves: Vec<Commitment> = (1..=2100u64).map(|v| Commitment(Fr::from(v))).collect();
+    let full = SparseMerkleTree::from_leaves(leaves.clone());
+    let recorded = |leaf_count: usize| HistoricalRoot {
+        root: full.root_at(leaf_count),
+        leaf_count: leaf_count as u64,
+        ledger: 10,
+    };
+    db.write_batch(&Batch {
+        leaves: leaves.iter().enumerate().map(|(i, cm)| (LeafIndex::from(i), *cm, 10)).collect(),
+        roots: vec![recorded(1000), recorded(2048)],
+        ..Batch::default()
+    })
+    .unwrap();
+
+    let mut app = AppState::new(full.clone(), db);
+    app.prune_depth = Some(1024);
+    app.prune();
+    assert_eq!(app.tree.pruned(), 1024);
+    let state = Arc::new(RwLock::new(app));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let router = r14_indexer::api::router(state.clone());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    let client = r14_sdk::IndexerClient::new(&url);
+
+    // leaves and paths on either side of the pruned ones
+    let page = client
+        .leaves_page(r14_sdk::indexer::LeavesQuery { from: LeafIndex(1000), limit: Some(100), ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(page.leaves, leaves[1000..1100]);
+    assert_eq!(client.leaves().await.unwrap(), leaves);
+    for index in [LeafIndex(5), LeafIndex(2050)] {
+        let path = client.merkle_proof(index).await.unwrap();
+        assert_eq!(path.siblings, full.proof(index).siblings);
+    }
+    let batch = client.merkle_proofs(&[LeafIndex(0), LeafIndex(2099)]).await.unwrap().unwrap();
+    assert!(batch.proofs.iter().all(|p| verify_proof(leaves[p.index.as_usize()], &p.path, &full.root())));
+
+    // an earlier root past the pruned leaves still proves them; one inside doesn't
+    let proof = client.proof_by_commitment(&leaves[5], Some(&full.root_at(2048))).await.unwrap().unwrap();
+    assert!(verify_proof(leaves[5], &proof.path, &full.root_at(2048)));
+    assert!(client.proof_by_commitment(&leaves[5], Some(&full.root_at(1000))).await.unwrap().is_none());
+}
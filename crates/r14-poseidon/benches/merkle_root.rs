@@ -0,0 +1,81 @@
+//! Merkle root over a few thousand leaves, with the shared Poseidon config
+//! against deriving it per hash (the behaviour before it was cached), and
+//! with the Poseidon2 permutation.
+//!
+//! cargo bench -p r14-poseidon --bench merkle_root
+
+use std::time::{Duration, Instant};
+
+use ark_bls12_381::Fr;
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge};
+use ark_crypto_primitives::sponge::{CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_ff::{AdditiveGroup, PrimeField};
+use r14_types::MERKLE_DEPTH;
+
+const LEAVES: usize = 4096;
+
+fn sponge_hash2(config: &PoseidonConfig<Fr>, a: Fr, b: Fr) -> Fr {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&[a, b].as_slice());
+    sponge.squeeze_native_field_elements(1)[0]
+}
+
+/// Poseidon hash2 with the shared config, whatever the process's family
+fn hash2_cached(a: Fr, b: Fr) -> Fr {
+    sponge_hash2(r14_poseidon::poseidon_config(), a, b)
+}
+
+/// hash2 with the config derived on every call
+fn hash2_uncached(a: Fr, b: Fr) -> Fr {
+    let config = r14_poseidon::poseidon_config::<Fr>();
+    let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+        Fr::MODULUS_BIT_SIZE as u64,
+        config.rate,
+        config.full_rounds as u64,
+        config.partial_rounds as u64,
+        0,
+    );
+    let config = PoseidonConfig::new(config.full_rounds, config.partial_rounds, config.alpha, mds, ark, config.rate, 1);
+    sponge_hash2(&config, a, b)
+}
+
+fn hash2_poseidon2(a: Fr, b: Fr) -> Fr {
+    r14_poseidon::poseidon2::hash(&[a, b])
+}
+
+/// Same layering as the indexer and `r14_sdk::merkle`
+fn root(leaves: &[Fr], hash2: fn(Fr, Fr) -> Fr) -> Fr {
+    let mut zero = Fr::ZERO;
+    let mut layer = leaves.to_vec();
+    for _ in 0..MERKLE_DEPTH {
+        layer = layer
+            .chunks(2)
+            .map(|c| hash2(c[0], c.get(1).copied().unwrap_or(zero)))
+            .collect();
+        zero = hash2(zero, zero);
+    }
+    layer[0]
+}
+
+fn time(name: &str, leaves: &[Fr], hash2: fn(Fr, Fr) -> Fr) -> (Fr, Duration) {
+    let start = Instant::now();
+    let r = root(leaves, hash2);
+    let elapsed = start.elapsed();
+    println!("{name:<10} {LEAVES} leaves: {elapsed:?}");
+    (r, elapsed)
+}
+
+fn main() {
+    let leaves: Vec<Fr> = (0..LEAVES as u64).map(Fr::from).collect();
+    // warm the cache so the cached run measures hashing only
+    hash2_cached(Fr::ZERO, Fr::ZERO);
+    hash2_poseidon2(Fr::ZERO, Fr::ZERO);
+
+    let (cached, fast) = time("cached", &leaves, hash2_cached);
+    let (uncached, slow) = time("uncached", &leaves, hash2_uncached);
+    assert_eq!(cached, uncached);
+    println!("speedup: {:.1}x", slow.as_secs_f64() / fast.as_secs_f64());
+
+    let (_, p2) = time("poseidon2", &leaves, hash2_poseidon2);
+    println!("poseidon2 vs cached: {:.1}x", fast.as_secs_f64() / p2.as_secs_f64());
+}
@@ -0,0 +1,113 @@
+//! circomlib-compatible Poseidon.
+//!
+//! circomlib's `Poseidon(n)` permutes the width `n + 1` state
+//! `[0, in_1, .., in_n]` once and outputs the first element. Each width
+//! has its own parameters: 8 full rounds, the partial rounds in
+//! [`PARTIAL_ROUNDS`], alpha 5, and round constants and MDS matrix from
+//! the reference Grain LFSR (the same generator as [`crate::poseidon_config`]).
+//!
+//! On BN254 this hashes to exactly the values of circomlib and circomlibjs
+//! (see [`crate::vectors::CIRCOM_BN254`]). Other fields get the same
+//! construction with constants derived for their modulus.
+
+use std::sync::OnceLock;
+
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig};
+use ark_ff::PrimeField;
+
+/// circomlib accepts 1 to 16 inputs
+pub const MAX_INPUTS: usize = 16;
+pub const FULL_ROUNDS: usize = 8;
+/// Partial rounds for 1..=16 inputs (widths 2..=17), from circomlib
+pub const PARTIAL_ROUNDS: [usize; MAX_INPUTS] = [56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68];
+pub const ALPHA: u64 = 5;
+
+/// Per-width parameters for `F`, each derived on first use
+pub struct CircomParams<F: PrimeField> {
+    configs: [OnceLock<PoseidonConfig<F>>; MAX_INPUTS],
+}
+
+impl<F: PrimeField> CircomParams<F> {
+    /// Parameters for hashing `n_inputs` elements (state width `n_inputs + 1`)
+    pub fn config(&self, n_inputs: usize) -> &PoseidonConfig<F> {
+        assert!(
+            (1..=MAX_INPUTS).contains(&n_inputs),
+            "circom Poseidon takes 1 to {MAX_INPUTS} inputs, got {n_inputs}"
+        );
+        self.configs[n_inputs - 1].get_or_init(|| {
+            let partial_rounds = PARTIAL_ROUNDS[n_inputs - 1];
+            let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+                F::MODULUS_BIT_SIZE as u64,
+                n_inputs,
+                FULL_ROUNDS as u64,
+                partial_rounds as u64,
+                0,
+            );
+            PoseidonConfig::new(FULL_ROUNDS, partial_rounds, ALPHA, mds, ark, n_inputs, 1)
+        })
+    }
+}
+
+pub(crate) fn derive_params<F: PrimeField>() -> CircomParams<F> {
+    CircomParams {
+        configs: std::array::from_fn(|_| OnceLock::new()),
+    }
+}
+
+pub fn permute<F: PrimeField>(config: &PoseidonConfig<F>, state: &mut [F]) {
+    let half = config.full_rounds / 2;
+    for (round, rc) in config.ark.iter().enumerate() {
+        for (s, c) in state.iter_mut().zip(rc) {
+            *s += c;
+        }
+        if round < half || round >= half + config.partial_rounds {
+            for s in state.iter_mut() {
+                *s = s.pow([config.alpha]);
+            }
+        } else {
+            state[0] = state[0].pow([config.alpha]);
+        }
+        let mixed: Vec<F> = config
+            .mds
+            .iter()
+            .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum())
+            .collect();
+        state.copy_from_slice(&mixed);
+    }
+}
+
+pub(crate) fn hash_with<F: PrimeField>(params: &CircomParams<F>, inputs: &[F]) -> F {
+    let config = params.config(inputs.len());
+    let mut state = Vec::with_capacity(inputs.len() + 1);
+    state.push(F::zero());
+    state.extend_from_slice(inputs);
+    permute(config, &mut state);
+    state[0]
+}
+
+/// circomlib `Poseidon(inputs.len())`, regardless of the process's hash family
+pub fn hash<F: PrimeField>(inputs: &[F]) -> F {
+    hash_with(crate::circom_params(), inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_widths_have_their_own_rounds() {
+        let params = crate::circom_params::<Fr>();
+        for n in [1, 2, 4, MAX_INPUTS] {
+            let config = params.config(n);
+            assert_eq!(config.mds.len(), n + 1);
+            assert_eq!(config.ark.len(), FULL_ROUNDS + PARTIAL_ROUNDS[n - 1]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "1 to 16 inputs")]
+    fn test_too_many_inputs() {
+        hash(&[Fr::from(1u64); MAX_INPUTS + 1]);
+    }
+}
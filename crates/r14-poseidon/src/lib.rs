@@ -1,20 +1,170 @@
+//! Poseidon hash for Root14 ZK circuits.
+//!
+//! The hash, `hash2` and `commitment` are generic over the scalar field so
+//! the same parameters (rate 2, 8 full / 31 partial rounds, alpha 17) can be
+//! instantiated on BN254 as well as BLS12-381. Round constants and the MDS
+//! matrix are derived from the field modulus, so the two curves hash to
+//! unrelated values. Key, nullifier and session helpers stay on BLS12-381.
+//!
+//! [`set_hash_family`] switches every hash here (and so every commitment,
+//! nullifier and Merkle root) to the [`poseidon2`] permutation or the
+//! circomlib-compatible parameters in [`circom`]. The family is fixed per
+//! process; see [`hash_family`]. Known-answer vectors for each family are
+//! in [`vectors`].
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
 use ark_bls12_381::Fr;
 use ark_crypto_primitives::sponge::{
     poseidon::{PoseidonConfig, PoseidonSponge},
-    CryptographicSponge, FieldBasedCryptographicSponge,
+    Absorb, CryptographicSponge, FieldBasedCryptographicSponge,
 };
 use ark_ff::PrimeField;
-use r14_types::{Note, Nullifier, OwnerHash, SecretKey};
+use r14_types::{Commitment, LeafIndex, Note, Nullifier, OwnerHash, SecretKey, SessionPolicy};
+
+pub mod circom;
+pub mod poseidon2;
+pub mod vectors;
+
+pub use circom::CircomParams;
+pub use poseidon2::Poseidon2Params;
 
 const RATE: usize = 2;
 const FULL_ROUNDS: usize = 8;
 const PARTIAL_ROUNDS: usize = 31;
 const ALPHA: u64 = 17;
 
-pub fn poseidon_config() -> PoseidonConfig<Fr> {
+/// Domain separator for session key derivation ("r14_sess")
+const SESSION_KEY_DOMAIN: u64 = 0x7231_345f_7365_7373;
+
+/// Domain separator for timelocked note commitments ("r14_lock")
+pub const TIMELOCK_DOMAIN: u64 = 0x7231_345f_6c6f_636b;
+
+/// Domain separator for auditor encryption pads ("r14_audt")
+pub const AUDIT_DOMAIN: u64 = 0x7231_345f_6175_6474;
+
+/// Domain separator for owners authorized by a spend-auth key ("r14_auth")
+pub const SPEND_AUTH_DOMAIN: u64 = 0x7231_345f_6175_7468;
+
+/// Domain separator for spend-auth signature challenges ("r14_sign")
+pub const SIGNATURE_DOMAIN: u64 = 0x7231_345f_7369_676e;
+
+/// Domain separator for k-of-n owner hashes ("r14_msig")
+pub const MULTISIG_DOMAIN: u64 = 0x7231_345f_6d73_6967;
+
+/// Domain separator for vote nullifiers ("r14_vote")
+pub const VOTE_DOMAIN: u64 = 0x7231_345f_766f_7465;
+
+/// The permutation behind [`poseidon_hash`]. Circuits hash with the same
+/// family, so it is baked into every verifying key and circuit_id.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum HashFamily {
+    #[default]
+    Poseidon,
+    Poseidon2,
+    Circom,
+}
+
+impl HashFamily {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Poseidon => "poseidon",
+            Self::Poseidon2 => "poseidon2",
+            Self::Circom => "circom",
+        }
+    }
+
+    /// Hash `inputs` with this family, whatever the process's own
+    pub fn hash<F: PrimeField + Absorb>(self, inputs: &[F]) -> F {
+        match self {
+            Self::Poseidon => hash_with(poseidon_config(), inputs),
+            Self::Poseidon2 => poseidon2::hash(inputs),
+            Self::Circom => circom::hash(inputs),
+        }
+    }
+}
+
+impl std::str::FromStr for HashFamily {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Poseidon, Self::Poseidon2, Self::Circom]
+            .into_iter()
+            .find(|f| f.name() == s)
+            .ok_or_else(|| format!("unknown hash family `{s}`; expected poseidon, poseidon2 or circom"))
+    }
+}
+
+static HASH_FAMILY: OnceLock<HashFamily> = OnceLock::new();
+
+/// Select the hash family for this process. Call it before anything
+/// hashes: the first hash fixes the family, Poseidon unless one was set.
+/// Setting the family already in use is a no-op; a different one returns
+/// `Err` with the family in use.
+pub fn set_hash_family(family: HashFamily) -> Result<(), HashFamily> {
+    match *HASH_FAMILY.get_or_init(|| family) {
+        current if current == family => Ok(()),
+        current => Err(current),
+    }
+}
+
+/// Hash family of this process; see [`set_hash_family`]
+pub fn hash_family() -> HashFamily {
+    *HASH_FAMILY.get_or_init(HashFamily::default)
+}
+
+/// Poseidon parameters for `F`, derived once per field and shared.
+///
+/// Deriving the round constants and MDS matrix runs the Grain LFSR, which
+/// dominated native hashing and circuit synthesis when it ran per call.
+pub fn poseidon_config<F: PrimeField>() -> &'static PoseidonConfig<F> {
+    cached(derive_config::<F>)
+}
+
+/// Poseidon2 round constants for `F`, derived once per field and shared
+pub fn poseidon2_params<F: PrimeField>() -> &'static Poseidon2Params<F> {
+    cached(poseidon2::derive_params::<F>)
+}
+
+/// circomlib Poseidon parameters for `F`, shared; each width is derived on
+/// first use
+pub fn circom_params<F: PrimeField>() -> &'static CircomParams<F> {
+    cached(circom::derive_params::<F>)
+}
+
+type Cache = HashMap<TypeId, &'static (dyn Any + Send + Sync)>;
+
+/// One `T` per process, derived on first use and never freed. Each thread
+/// keeps its own index of them, so only a thread's first lookup of a type
+/// takes the global lock; hashing after that is lock-free.
+fn cached<T: Any + Send + Sync>(derive: fn() -> T) -> &'static T {
+    static GLOBAL: OnceLock<Mutex<Cache>> = OnceLock::new();
+    thread_local! {
+        static LOCAL: RefCell<Cache> = RefCell::default();
+    }
+
+    let id = TypeId::of::<T>();
+    let value = LOCAL.with(|local| {
+        if let Some(value) = local.borrow().get(&id) {
+            return *value;
+        }
+        let mut global = GLOBAL.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+        let value = *global.entry(id).or_insert_with(|| Box::leak(Box::new(derive())));
+        local.borrow_mut().insert(id, value);
+        value
+    });
+    value.downcast_ref().expect("value cached under its own TypeId")
+}
+
+fn derive_config<F: PrimeField>() -> PoseidonConfig<F> {
     let (ark, mds) =
-        ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds::<Fr>(
-            Fr::MODULUS_BIT_SIZE as u64,
+        ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds::<F>(
+            F::MODULUS_BIT_SIZE as u64,
             RATE,
             FULL_ROUNDS as u64,
             PARTIAL_ROUNDS as u64,
@@ -23,34 +173,132 @@ pub fn poseidon_config() -> PoseidonConfig<Fr> {
     PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, RATE, 1)
 }
 
-pub fn poseidon_hash(inputs: &[Fr]) -> Fr {
-    let config = poseidon_config();
-    let mut sponge = PoseidonSponge::new(&config);
+pub fn poseidon_hash<F: PrimeField + Absorb>(inputs: &[F]) -> F {
+    hash_family().hash(inputs)
+}
+
+fn hash_with<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, inputs: &[F]) -> F {
+    let mut sponge = PoseidonSponge::new(config);
     sponge.absorb(&inputs);
     sponge.squeeze_native_field_elements(1)[0]
 }
 
-pub fn hash2(a: Fr, b: Fr) -> Fr {
+pub fn hash2<F: PrimeField + Absorb>(a: F, b: F) -> F {
     poseidon_hash(&[a, b])
 }
 
-pub fn commitment(note: &Note) -> Fr {
-    poseidon_hash(&[
-        Fr::from(note.value),
-        Fr::from(note.app_tag as u64),
+/// `hash2` of each pair, in order. Runs on the rayon pool with the
+/// `parallel` feature.
+pub fn poseidon_hash_batch<F: PrimeField + Absorb>(pairs: &[[F; 2]]) -> Vec<F> {
+    let hash = pair_hasher::<F>();
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        pairs.par_iter().map(&hash).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        pairs.iter().map(&hash).collect()
+    }
+}
+
+type PairHasher<F> = Box<dyn Fn(&[F; 2]) -> F + Send + Sync>;
+
+/// `hash2` with the parameters looked up once, for batch use
+fn pair_hasher<F: PrimeField + Absorb>() -> PairHasher<F> {
+    match hash_family() {
+        HashFamily::Poseidon => {
+            let config = poseidon_config::<F>();
+            Box::new(move |pair| hash_with(config, pair))
+        }
+        HashFamily::Poseidon2 => {
+            let params = poseidon2_params::<F>();
+            Box::new(move |pair| poseidon2::hash_with(params, pair))
+        }
+        HashFamily::Circom => {
+            let params = circom_params::<F>();
+            Box::new(move |pair| circom::hash_with(params, pair))
+        }
+    }
+}
+
+/// The Merkle layer above `layer`: adjacent nodes hashed pairwise, with
+/// `zero` (the empty subtree at this height) as the last right child when
+/// the layer has odd length
+pub fn hash_layer<F: PrimeField + Absorb>(layer: &[F], zero: F) -> Vec<F> {
+    let pairs: Vec<[F; 2]> = layer
+        .chunks(2)
+        .map(|c| [c[0], c.get(1).copied().unwrap_or(zero)])
+        .collect();
+    poseidon_hash_batch(&pairs)
+}
+
+/// Note commitment: Poseidon(value, app_tag, owner, nonce).
+///
+/// A timelocked note wraps that as Poseidon(domain, base, unlock_after), so
+/// notes without a timelock keep the commitment they always had.
+pub fn commitment<F: PrimeField + Absorb>(note: &Note<F>) -> Commitment<F> {
+    let base = poseidon_hash(&[
+        F::from(note.value),
+        F::from(note.app_tag as u64),
         note.owner,
         note.nonce,
-    ])
+    ]);
+    if note.is_locked() {
+        Commitment(poseidon_hash(&[F::from(TIMELOCK_DOMAIN), base, F::from(note.unlock_after)]))
+    } else {
+        Commitment(base)
+    }
+}
+
+/// Nullifier of the note with `nonce` at tree position `leaf_index`,
+/// spent with key `sk`: Poseidon(sk, nonce, leaf_index).
+///
+/// Binding the position gives two notes with the same key and nonce
+/// (a copied note, inserted twice) distinct nullifiers, so spending one
+/// doesn't burn the other.
+pub fn nullifier(sk: &SecretKey, nonce: &Fr, leaf_index: LeafIndex) -> Nullifier {
+    Nullifier::from_fr(nullifier_hash(sk.0, *nonce, leaf_index))
 }
 
-pub fn nullifier(sk: &SecretKey, nonce: &Fr) -> Nullifier {
-    Nullifier::from_fr(hash2(sk.0, *nonce))
+/// [`nullifier`] over any field, for circuits: `key` is whichever secret
+/// the circuit derives nullifiers from (spending, session or nullifier key)
+pub fn nullifier_hash<F: PrimeField + Absorb>(key: F, nonce: F, leaf_index: LeafIndex) -> F {
+    poseidon_hash(&[key, nonce, F::from(leaf_index.0)])
 }
 
 pub fn owner_hash(sk: &SecretKey) -> OwnerHash {
     OwnerHash(poseidon_hash(&[sk.0]))
 }
 
+/// Owner hash of notes spent by signature: Poseidon(domain, ak.x, ak.y, nk)
+/// for spend-auth public key `ak` and nullifier key `nk`
+pub fn spend_auth_owner(ak_x: &Fr, ak_y: &Fr, nullifier_key: &SecretKey) -> OwnerHash {
+    OwnerHash(poseidon_hash(&[Fr::from(SPEND_AUTH_DOMAIN), *ak_x, *ak_y, nullifier_key.0]))
+}
+
+/// Derive the `index`-th session key: Poseidon(domain, sk, index)
+pub fn session_key(sk: &SecretKey, index: u64) -> SecretKey {
+    SecretKey(poseidon_hash(&[
+        Fr::from(SESSION_KEY_DOMAIN),
+        sk.0,
+        Fr::from(index),
+    ]))
+}
+
+/// Owner hash of notes spendable by a session key under `policy`.
+///
+/// Poseidon(Poseidon(session_sk), max_amount, app_tag, expiry, refund_owner)
+pub fn session_owner(session_sk: &SecretKey, policy: &SessionPolicy, refund_owner: &OwnerHash) -> OwnerHash {
+    OwnerHash(poseidon_hash(&[
+        owner_hash(session_sk).0,
+        Fr::from(policy.max_amount),
+        Fr::from(policy.app_tag as u64),
+        Fr::from(policy.expiry),
+        refund_owner.0,
+    ]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,12 +330,22 @@ mod tests {
         assert_eq!(commitment(&note), commitment(&note));
     }
 
+    #[test]
+    fn test_timelock_changes_commitment() {
+        let mut rng = test_rng();
+        let note = Note::new(1000, 1, Fr::rand(&mut rng), &mut rng);
+        let locked = note.clone().locked_until(500);
+        assert_ne!(commitment(&note), commitment(&locked));
+        assert_ne!(commitment(&locked), commitment(&note.clone().locked_until(501)));
+        assert_eq!(commitment(&note.locked_until(0)), commitment(&locked.locked_until(0)));
+    }
+
     #[test]
     fn test_nullifier_deterministic() {
         let mut rng = test_rng();
         let sk = SecretKey::random(&mut rng);
         let nonce = Fr::rand(&mut rng);
-        assert_eq!(nullifier(&sk, &nonce), nullifier(&sk, &nonce));
+        assert_eq!(nullifier(&sk, &nonce, LeafIndex(3)), nullifier(&sk, &nonce, LeafIndex(3)));
     }
 
     #[test]
@@ -96,7 +354,93 @@ mod tests {
         let sk = SecretKey::random(&mut rng);
         let n1 = Fr::rand(&mut rng);
         let n2 = Fr::rand(&mut rng);
-        assert_ne!(nullifier(&sk, &n1), nullifier(&sk, &n2));
+        assert_ne!(nullifier(&sk, &n1, LeafIndex(0)), nullifier(&sk, &n2, LeafIndex(0)));
+    }
+
+    #[test]
+    fn test_same_note_at_two_positions() {
+        let mut rng = test_rng();
+        let sk = SecretKey::random(&mut rng);
+        let nonce = Fr::rand(&mut rng);
+        assert_ne!(nullifier(&sk, &nonce, LeafIndex(0)), nullifier(&sk, &nonce, LeafIndex(1)));
+        assert_ne!(nullifier(&sk, &nonce, LeafIndex(0)).0, hash2(sk.0, nonce));
+    }
+
+    #[test]
+    fn test_session_key_per_index() {
+        let mut rng = test_rng();
+        let sk = SecretKey::random(&mut rng);
+        assert_eq!(session_key(&sk, 0).0, session_key(&sk, 0).0);
+        assert_ne!(session_key(&sk, 0).0, session_key(&sk, 1).0);
+        assert_ne!(session_key(&sk, 0).0, sk.0);
+    }
+
+    #[test]
+    fn test_session_owner_binds_policy() {
+        let mut rng = test_rng();
+        let sk = SecretKey::random(&mut rng);
+        let ssk = session_key(&sk, 0);
+        let refund = owner_hash(&sk);
+        let p1 = SessionPolicy::new(100, 1, 500);
+        let p2 = SessionPolicy::new(101, 1, 500);
+        assert_ne!(session_owner(&ssk, &p1, &refund), session_owner(&ssk, &p2, &refund));
+        assert_ne!(session_owner(&ssk, &p1, &refund), owner_hash(&ssk));
+    }
+
+    #[test]
+    fn test_spend_auth_owner_binds_key() {
+        let mut rng = test_rng();
+        let nk = SecretKey::random(&mut rng);
+        let (x, y) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        assert_ne!(spend_auth_owner(&x, &y, &nk), spend_auth_owner(&y, &x, &nk));
+        assert_ne!(spend_auth_owner(&x, &y, &nk), owner_hash(&nk));
+    }
+
+    #[test]
+    fn test_bn254_instantiation() {
+        type Bn = ark_bn254::Fr;
+        let mut rng = test_rng();
+        let (owner, nonce) = (u64::rand(&mut rng), u64::rand(&mut rng));
+        let bn = Note::with_nonce(1000, 1, Bn::from(owner), Bn::from(nonce));
+        let bls = Note::with_nonce(1000, 1, Fr::from(owner), Fr::from(nonce));
+        assert_ne!(commitment(&bn).0.into_bigint(), commitment(&bls).0.into_bigint());
+        assert_ne!(
+            hash2(Bn::from(1u64), Bn::from(2u64)).into_bigint(),
+            hash2(Fr::from(1u64), Fr::from(2u64)).into_bigint()
+        );
+        assert_ne!(hash2(Bn::from(1u64), Bn::from(2u64)), hash2(Bn::from(2u64), Bn::from(1u64)));
+        assert_eq!(poseidon_config::<Bn>().ark.len(), FULL_ROUNDS + PARTIAL_ROUNDS);
+    }
+
+    #[test]
+    fn test_hash_family_fixed_once_used() {
+        let family = hash_family();
+        assert_eq!(set_hash_family(family), Ok(()));
+        let other = if family == HashFamily::Circom { HashFamily::Poseidon } else { HashFamily::Circom };
+        assert_eq!(set_hash_family(other), Err(family));
+        assert_eq!("poseidon2".parse(), Ok(HashFamily::Poseidon2));
+        assert!("sha256".parse::<HashFamily>().is_err());
+    }
+
+    #[test]
+    fn test_config_derived_once_per_field() {
+        assert!(std::ptr::eq(poseidon_config::<Fr>(), poseidon_config::<Fr>()));
+        let elsewhere = std::thread::spawn(|| poseidon_config::<Fr>() as *const _ as usize).join().unwrap();
+        assert_eq!(poseidon_config::<Fr>() as *const _ as usize, elsewhere);
+        assert_eq!(poseidon_config::<ark_bn254::Fr>().ark, derive_config::<ark_bn254::Fr>().ark);
+    }
+
+    #[test]
+    fn test_hash_batch_matches_hash2() {
+        let mut rng = test_rng();
+        let pairs: Vec<[Fr; 2]> = (0..9).map(|_| [Fr::rand(&mut rng), Fr::rand(&mut rng)]).collect();
+        let expected: Vec<Fr> = pairs.iter().map(|[a, b]| hash2(*a, *b)).collect();
+        assert_eq!(poseidon_hash_batch(&pairs), expected);
+
+        let zero = Fr::from(0u64);
+        let layer: Vec<Fr> = pairs.iter().flatten().copied().take(5).collect();
+        let next = hash_layer(&layer, zero);
+        assert_eq!(next, vec![hash2(layer[0], layer[1]), hash2(layer[2], layer[3]), hash2(layer[4], zero)]);
     }
 
     #[test]
@@ -0,0 +1,142 @@
+//! Poseidon2 permutation (width 3, x^5 S-box).
+//!
+//! Poseidon2 swaps Poseidon's dense MDS matrix for cheap structured ones
+//! and needs only a low-degree S-box, so a permutation costs fewer field
+//! multiplications natively and fewer R1CS constraints in-circuit.
+//!
+//! Parameters: rate 2 / capacity 1, 8 full + 56 partial rounds, alpha 5.
+//! The external matrix is circ(2, 1, 1) and the internal one is
+//! `1 + diag(1, 1, 2)`. Round constants come from the same Grain LFSR as
+//! the Poseidon config, so they are field-specific. This is not checked
+//! against the reference implementation's test vectors and hashes to
+//! different values; never mix the two families in one deployment.
+//!
+//! Hashing puts the input length in the capacity element, then absorbs two
+//! elements per permutation.
+
+use ark_ff::PrimeField;
+
+pub const WIDTH: usize = 3;
+pub const FULL_ROUNDS: usize = 8;
+pub const PARTIAL_ROUNDS: usize = 56;
+pub const ALPHA: u64 = 5;
+
+/// Round constants for `F`: one row per full round, one element per
+/// partial round (added to the first state element only)
+pub struct Poseidon2Params<F> {
+    pub full: Vec<[F; WIDTH]>,
+    pub partial: Vec<F>,
+}
+
+pub(crate) fn derive_params<F: PrimeField>() -> Poseidon2Params<F> {
+    let (ark, _) = ark_crypto_primitives::sponge::poseidon::find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        WIDTH - 1,
+        FULL_ROUNDS as u64,
+        PARTIAL_ROUNDS as u64,
+        0,
+    );
+    let row = |r: &Vec<F>| [r[0], r[1], r[2]];
+    let half = FULL_ROUNDS / 2;
+    let full = ark[..half].iter().chain(&ark[half + PARTIAL_ROUNDS..]).map(row).collect();
+    let partial = ark[half..half + PARTIAL_ROUNDS].iter().map(|r| r[0]).collect();
+    Poseidon2Params { full, partial }
+}
+
+fn sbox<F: PrimeField>(x: F) -> F {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// circ(2, 1, 1): each element plus the sum of all three
+fn external_linear<F: PrimeField>(state: &mut [F; WIDTH]) {
+    let sum = state[0] + state[1] + state[2];
+    for s in state.iter_mut() {
+        *s += sum;
+    }
+}
+
+/// 1 + diag(1, 1, 2)
+fn internal_linear<F: PrimeField>(state: &mut [F; WIDTH]) {
+    let sum = state[0] + state[1] + state[2];
+    state[0] += sum;
+    state[1] += sum;
+    state[2] = state[2].double() + sum;
+}
+
+pub fn permute<F: PrimeField>(params: &Poseidon2Params<F>, state: &mut [F; WIDTH]) {
+    let half = FULL_ROUNDS / 2;
+    external_linear(state);
+    for rc in &params.full[..half] {
+        for (s, c) in state.iter_mut().zip(rc) {
+            *s = sbox(*s + c);
+        }
+        external_linear(state);
+    }
+    for c in &params.partial {
+        state[0] = sbox(state[0] + c);
+        internal_linear(state);
+    }
+    for rc in &params.full[half..] {
+        for (s, c) in state.iter_mut().zip(rc) {
+            *s = sbox(*s + c);
+        }
+        external_linear(state);
+    }
+}
+
+pub(crate) fn hash_with<F: PrimeField>(params: &Poseidon2Params<F>, inputs: &[F]) -> F {
+    let mut state = [F::from(inputs.len() as u64), F::zero(), F::zero()];
+    if inputs.is_empty() {
+        permute(params, &mut state);
+    }
+    for chunk in inputs.chunks(WIDTH - 1) {
+        for (s, x) in state[1..].iter_mut().zip(chunk) {
+            *s += x;
+        }
+        permute(params, &mut state);
+    }
+    state[1]
+}
+
+/// Poseidon2 hash of `inputs`, regardless of the process's hash family
+pub fn hash<F: PrimeField>(inputs: &[F]) -> F {
+    hash_with(crate::poseidon2_params(), inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_ff::{BigInteger, UniformRand};
+    use ark_std::test_rng;
+
+    fn modulus_rem<F: PrimeField>(m: u64) -> u64 {
+        F::MODULUS.to_bytes_be().iter().fold(0, |r, &b| (r * 256 + b as u64) % m)
+    }
+
+    #[test]
+    fn test_alpha_is_a_permutation() {
+        // x^5 is a bijection iff gcd(5, p - 1) == 1
+        assert_ne!(modulus_rem::<Fr>(ALPHA), 1);
+        assert_ne!(modulus_rem::<ark_bn254::Fr>(ALPHA), 1);
+    }
+
+    #[test]
+    fn test_round_constants() {
+        let params = derive_params::<Fr>();
+        assert_eq!(params.full.len(), FULL_ROUNDS);
+        assert_eq!(params.partial.len(), PARTIAL_ROUNDS);
+    }
+
+    #[test]
+    fn test_hash_separates_inputs() {
+        let mut rng = test_rng();
+        let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        assert_eq!(hash(&[a, b]), hash(&[a, b]));
+        assert_ne!(hash(&[a, b]), hash(&[b, a]));
+        // length is absorbed, so zero-padding changes the hash
+        assert_ne!(hash(&[a]), hash(&[a, Fr::from(0u64)]));
+        assert_ne!(hash(&[a, b]), crate::hash_with(crate::poseidon_config(), &[a, b]));
+    }
+}
@@ -0,0 +1,109 @@
+//! Known-answer vectors for each hash family.
+//!
+//! Inputs are small integers and outputs are decimal field elements (the
+//! format circomlibjs prints). Use them to check another implementation,
+//! or a rebuild of this one, against the parameters here.
+//! [`CIRCOM_BN254`] is taken from circomlib's own tests. The others pin
+//! this crate's outputs.
+
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+
+use crate::HashFamily;
+
+pub struct TestVector {
+    pub inputs: &'static [u64],
+    pub output: &'static str,
+}
+
+impl TestVector {
+    /// Whether `family` hashes `inputs` to `output` over `F`
+    pub fn check<F: PrimeField + Absorb>(&self, family: HashFamily) -> bool {
+        let inputs: Vec<F> = self.inputs.iter().map(|&x| F::from(x)).collect();
+        let expected: F = self.output.parse().unwrap_or_else(|_| panic!("bad vector output {}", self.output));
+        family.hash(&inputs) == expected
+    }
+}
+
+pub const POSEIDON_BLS12_381: &[TestVector] = &[
+    TestVector {
+        inputs: &[1],
+        output: "26511395754353438153956014645716883342078847001336301618301653090674576206984",
+    },
+    TestVector {
+        inputs: &[1, 2],
+        output: "15097507876956563474224915811700428590665428051852057322491674301805944149214",
+    },
+    TestVector {
+        inputs: &[1, 2, 3, 4],
+        output: "44545278154036072400341986415573606433514036075881104926843131623212222979236",
+    },
+];
+
+pub const POSEIDON2_BLS12_381: &[TestVector] = &[
+    TestVector {
+        inputs: &[1],
+        output: "12870270530055163607293280044273225082695188623784787846496743351429405215470",
+    },
+    TestVector {
+        inputs: &[1, 2],
+        output: "27635798175252956918256730245399823091256918474580779786373759701898812284226",
+    },
+    TestVector {
+        inputs: &[1, 2, 3, 4],
+        output: "8618507541176182591459487578084028772073176823577763704502329485326740910595",
+    },
+];
+
+pub const CIRCOM_BN254: &[TestVector] = &[
+    TestVector {
+        inputs: &[1],
+        output: "18586133768512220936620570745912940619677854269274689475585506675881198879027",
+    },
+    TestVector {
+        inputs: &[1, 2],
+        output: "7853200120776062878684798364095072458815029376092732009249414926327459813530",
+    },
+    TestVector {
+        inputs: &[1, 2, 3, 4],
+        output: "18821383157269793795438455681495246036402687001665670618754263018637548127333",
+    },
+];
+
+pub const CIRCOM_BLS12_381: &[TestVector] = &[
+    TestVector {
+        inputs: &[1],
+        output: "33312903538086167554741214005086116725441315171650202128840830167854170336490",
+    },
+    TestVector {
+        inputs: &[1, 2],
+        output: "18456658763349757341014058622209659766100673761449600566550821987295786346378",
+    },
+    TestVector {
+        inputs: &[1, 2, 3, 4],
+        output: "19254296030192702347705476604674913000958357963414387321395030558732596555288",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn test_vectors() {
+        let tables = [
+            (HashFamily::Poseidon, POSEIDON_BLS12_381),
+            (HashFamily::Poseidon2, POSEIDON2_BLS12_381),
+            (HashFamily::Circom, CIRCOM_BLS12_381),
+        ];
+        for (family, vectors) in tables {
+            for v in vectors {
+                assert!(v.check::<Fr>(family), "{} {:?}", family.name(), v.inputs);
+            }
+        }
+        for v in CIRCOM_BN254 {
+            assert!(v.check::<ark_bn254::Fr>(HashFamily::Circom), "circomlib {:?}", v.inputs);
+        }
+    }
+}
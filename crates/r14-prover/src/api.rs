@@ -0,0 +1,69 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! HTTP routes for the prover daemon
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{rejection::JsonRejection, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use r14_errors::ProverError;
+use serde_json::json;
+use tower_http::cors::CorsLayer;
+
+use crate::pool::ProverPool;
+use crate::witness::TransferWitness;
+
+pub type SharedPool = Arc<ProverPool>;
+
+pub fn router(pool: SharedPool) -> Router {
+    Router::new()
+        .route("/v1/health", get(health))
+        .route("/v1/prove/transfer", post(prove_transfer))
+        .with_state(pool)
+}
+
+/// CORS for browser wallets served from `origins`, layered on [`router`]
+/// only when configured. Requests carry spending keys, so by default the
+/// daemon sends no CORS headers and other sites' scripts can't call it.
+pub fn cors(origins: Vec<HeaderValue>) -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::CONTENT_TYPE])
+}
+
+async fn health(State(pool): State<SharedPool>) -> impl IntoResponse {
+    Json(json!({ "status": "ok", "pool": pool.stats() }))
+}
+
+/// `ProverError` rendered as an HTTP status plus `ErrorBody` JSON
+pub struct ApiError(ProverError);
+
+impl From<ProverError> for ApiError {
+    fn from(e: ProverError) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> axum::response::Response {
+        let code = self.0.code();
+        let status = StatusCode::from_u16(code.http_status())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        (status, Json(self.0.to_body())).into_response()
+    }
+}
+
+async fn prove_transfer(
+    State(pool): State<SharedPool>,
+    body: Result<Json<TransferWitness>, JsonRejection>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Json(witness) = body.map_err(|e| ProverError::InvalidInput(e.body_text()))?;
+    Ok(Json(pool.prove_transfer(witness).await?))
+}
@@ -0,0 +1,141 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Proving-key warm cache.
+//!
+//! A deployment with published or ceremony keys installs them in an
+//! [`r14_sdk::artifacts::KeyCache`] directory, the same place wallets prove
+//! from; the daemon proves with those when present. Otherwise it derives the
+//! transfer proving key with a full Groth16 setup from the public seed, once
+//! at startup, and keeps it in memory; with a cache path it also persists the
+//! key so restarts only pay a deserialize. The cache file name carries the
+//! circuit digest, so upgrading the circuit never loads a key for the old one.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ark_bls12_381::Bls12_381;
+use ark_groth16::ProvingKey;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_sdk::artifacts::KeyCache;
+use r14_sdk::client::TRANSFER_SETUP_SEED;
+
+/// Where the daemon's proving key came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// `transfer.pk` installed in a key directory, checked against its
+    /// `transfer.vk.json`
+    Installed(PathBuf),
+    /// The daemon's own earlier setup output
+    Cache(PathBuf),
+    /// Setup from the public seed, just now
+    Setup,
+}
+
+impl std::fmt::Display for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Installed(path) => write!(f, "installed key {}", path.display()),
+            Self::Cache(path) => write!(f, "cached setup {}", path.display()),
+            Self::Setup => f.write_str("deterministic setup"),
+        }
+    }
+}
+
+/// Transfer circuit proving key, the same one wallets prove with: the key
+/// installed in `installed` when there is one, else the deterministic setup,
+/// loaded from `cache_file(cache)` when present and written there otherwise.
+pub fn load_or_setup_transfer(
+    installed: &KeyCache,
+    cache: Option<&Path>,
+) -> Result<(ProvingKey<Bls12_381>, KeySource)> {
+    // keys may be copied in by hand, so run the subgroup checks
+    if let Some((pk, _vk)) = installed.load_transfer_checked()? {
+        return Ok((pk, KeySource::Installed(installed.transfer_pk_path())));
+    }
+
+    let cache = cache.map(cache_file);
+    let cache = cache.as_deref();
+    if let Some(path) = cache.filter(|p| p.exists()) {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("reading proving key {}", path.display()))?;
+        // the file is our own setup output, so skip the subgroup checks
+        let pk = ProvingKey::deserialize_uncompressed_unchecked(bytes.as_slice())
+            .with_context(|| format!("decoding proving key {}", path.display()))?;
+        return Ok((pk, KeySource::Cache(path.to_path_buf())));
+    }
+
+    let pk = setup_transfer()?;
+    if let Some(path) = cache {
+        let mut bytes = Vec::new();
+        pk.serialize_uncompressed(&mut bytes)
+            .context("encoding proving key")?;
+        // write-then-rename so a crash never leaves a truncated cache
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, &bytes)
+            .with_context(|| format!("writing proving key {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("writing proving key {}", path.display()))?;
+    }
+    Ok((pk, KeySource::Setup))
+}
+
+/// `cache` with the first 8 bytes of [`r14_sdk::prove::transfer_digest`] spliced
+/// in before the extension: `r14-prover-transfer.pk` becomes
+/// `r14-prover-transfer.<digest>.pk`.
+pub fn cache_file(cache: &Path) -> PathBuf {
+    let digest: String = r14_sdk::prove::transfer_digest()[..8].iter().map(|b| format!("{b:02x}")).collect();
+    let stem = cache.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match cache.extension() {
+        Some(ext) => format!("{stem}.{digest}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{digest}"),
+    };
+    cache.with_file_name(name)
+}
+
+/// Deterministic setup — same seed as `R14Client`, so proofs verify on-chain
+pub fn setup_transfer() -> Result<ProvingKey<Bls12_381>> {
+    let (pk, _vk) = r14_sdk::prove::setup(&mut StdRng::seed_from_u64(TRANSFER_SETUP_SEED))?;
+    Ok(pk)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_file_is_keyed_by_circuit() {
+        let file = cache_file(Path::new("/var/lib/r14/r14-prover-transfer.pk"));
+        assert_eq!(file.parent(), Some(Path::new("/var/lib/r14")));
+        assert_eq!(file.extension().unwrap(), "pk");
+        let name = file.file_name().unwrap().to_string_lossy().into_owned();
+        let digest = name.strip_prefix("r14-prover-transfer.").unwrap().strip_suffix(".pk").unwrap();
+        assert_eq!(digest.len(), 16);
+        assert_eq!(cache_file(Path::new("key")).file_name().unwrap().to_string_lossy(), format!("key.{digest}"));
+    }
+
+    #[test]
+    fn installed_key_wins_over_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let installed = KeyCache::open(dir.path().join("keys"));
+        std::fs::create_dir(installed.dir()).unwrap();
+        // a ceremony key: any setup other than the public seed
+        let (pk, vk) = r14_sdk::prove::setup(&mut StdRng::seed_from_u64(7)).unwrap();
+        let mut bytes = Vec::new();
+        pk.serialize_uncompressed(&mut bytes).unwrap();
+        std::fs::write(installed.transfer_pk_path(), bytes).unwrap();
+        let svk = r14_sdk::prove::serialize_vk_for_soroban(&vk, r14_sdk::MERKLE_DEPTH);
+        let json = serde_json::json!({
+            "alpha_g1": svk.alpha_g1, "beta_g2": svk.beta_g2, "gamma_g2": svk.gamma_g2,
+            "delta_g2": svk.delta_g2, "ic": svk.ic, "depth": svk.depth,
+        });
+        std::fs::write(installed.transfer_vk_path(), json.to_string()).unwrap();
+
+        let cache = dir.path().join("r14-prover-transfer.pk");
+        std::fs::write(cache_file(&cache), b"not a key").unwrap();
+        let (loaded, source) = load_or_setup_transfer(&installed, Some(&cache)).unwrap();
+        assert_eq!(source, KeySource::Installed(installed.transfer_pk_path()));
+        assert_eq!(loaded.vk, vk);
+    }
+}
@@ -0,0 +1,11 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! r14-prover: proof generation service for clients too constrained to prove
+//! locally (browsers, phones). Wraps `r14_sdk::prove` behind a REST API with a
+//! bounded worker pool and a warm proving-key cache.
+
+pub mod api;
+pub mod keys;
+pub mod pool;
+pub mod witness;
@@ -0,0 +1,105 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::http::HeaderValue;
+use r14_prover::{api, keys, pool::ProverPool};
+use r14_sdk::artifacts::KeyCache;
+
+// ── Config ───────────────────────────────────────────────────────────
+const DEFAULT_QUEUE_DEPTH: &str = "16";
+
+fn env_or(key: &str, default: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| default.into())
+}
+
+fn env_usize(key: &str, default: &str) -> usize {
+    let raw = env_or(key, default);
+    raw.parse().unwrap_or_else(|_| {
+        eprintln!("error: {key}={raw} is not a number");
+        std::process::exit(1);
+    })
+}
+
+#[tokio::main]
+async fn main() {
+    // requests carry spending keys, so stay off the network unless asked
+    let listen_addr = env_or("R14_PROVER_LISTEN_ADDR", "127.0.0.1:3001");
+    let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let workers = env_usize("R14_PROVER_WORKERS", &cpus.to_string());
+    let queue_depth = env_usize("R14_PROVER_QUEUE_DEPTH", DEFAULT_QUEUE_DEPTH);
+    // empty disables the on-disk cache
+    let key_cache = env_or("R14_PROVER_KEY_CACHE", "r14-prover-transfer.pk");
+    let key_cache = (!key_cache.is_empty()).then(|| PathBuf::from(key_cache));
+    // installed keys, as `KeyCache::install` or `r14-ceremony export` leave them
+    let keys_dir = match std::env::var("R14_PROVER_KEYS_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => r14_sdk::artifacts::default_dir().unwrap_or_else(|e| {
+            eprintln!("error: {e:#}; set R14_PROVER_KEYS_DIR");
+            std::process::exit(1);
+        }),
+    };
+    let installed = KeyCache::open(keys_dir);
+    // browser wallets allowed to call the daemon cross-origin; none by default
+    let cors_origins: Vec<HeaderValue> = env_or("R14_PROVER_CORS_ORIGINS", "")
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(|origin| {
+            origin.parse().unwrap_or_else(|_| {
+                eprintln!("error: R14_PROVER_CORS_ORIGINS: `{origin}` is not a valid origin");
+                std::process::exit(1);
+            })
+        })
+        .collect();
+    // R14_HASH, else r14.toml's, so witnesses hash like the deployment's commitments
+    let hash = std::env::var("R14_HASH").ok().map(|raw| {
+        raw.parse().unwrap_or_else(|e| {
+            eprintln!("error: R14_HASH: {e}");
+            std::process::exit(1);
+        })
+    });
+    let hash = r14_sdk::deployment::resolve_hash_family(hash).unwrap_or_else(|e| {
+        eprintln!("error: {e:#}");
+        std::process::exit(1);
+    });
+    // before the key setup or any witness hashes
+    r14_sdk::set_hash_family(hash).expect("nothing has hashed yet");
+
+    eprintln!("r14-prover starting...");
+    eprintln!("  workers: {workers}, queue depth: {queue_depth}");
+    eprintln!("  hash family: {}", hash.name());
+    eprintln!("  installed keys: {}", installed.dir().display());
+    if !cors_origins.is_empty() {
+        eprintln!("  CORS origins: {}", env_or("R14_PROVER_CORS_ORIGINS", ""));
+    }
+    if let Some(path) = &key_cache {
+        eprintln!("  key cache: {}", keys::cache_file(path).display());
+    }
+
+    // 1. Warm the proving key before accepting requests
+    let started = Instant::now();
+    let (pk, source) = match keys::load_or_setup_transfer(&installed, key_cache.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("error: {e:#}");
+            std::process::exit(1);
+        }
+    };
+    eprintln!("transfer proving key ready in {:.1?} ({source})", started.elapsed());
+
+    // 2. Start HTTP server
+    let pool = Arc::new(ProverPool::new(pk, workers, queue_depth));
+    let mut router = api::router(pool);
+    if !cors_origins.is_empty() {
+        router = router.layer(api::cors(cors_origins));
+    }
+    let listener = tokio::net::TcpListener::bind(&listen_addr)
+        .await
+        .expect("failed to bind");
+    eprintln!("listening on {listen_addr}");
+    axum::serve(listener, router).await.expect("server error");
+}
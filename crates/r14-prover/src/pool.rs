@@ -0,0 +1,120 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Bounded proving pool.
+//!
+//! At most `workers` proofs run at once on blocking threads; up to
+//! `queue_depth` more wait for a worker. Anything beyond that is rejected
+//! with `ProverError::Busy` instead of piling up unbounded latency.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use ark_bls12_381::Bls12_381;
+use ark_groth16::ProvingKey;
+use r14_errors::ProverError;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+use crate::witness::{self, ProvedTransfer, TransferWitness};
+
+pub struct ProverPool {
+    pk: Arc<ProvingKey<Bls12_381>>,
+    workers: Arc<Semaphore>,
+    worker_count: usize,
+    /// Requests admitted (running or waiting for a worker)
+    pending: Arc<AtomicUsize>,
+    capacity: usize,
+}
+
+/// Admission slot held for the lifetime of one request
+pub struct Ticket {
+    pending: Arc<AtomicUsize>,
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Snapshot reported by `/v1/health`
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct PoolStats {
+    pub workers: usize,
+    pub pending: usize,
+    pub capacity: usize,
+}
+
+impl ProverPool {
+    pub fn new(pk: ProvingKey<Bls12_381>, workers: usize, queue_depth: usize) -> Self {
+        let workers = workers.max(1);
+        Self {
+            pk: Arc::new(pk),
+            workers: Arc::new(Semaphore::new(workers)),
+            worker_count: workers,
+            pending: Arc::new(AtomicUsize::new(0)),
+            capacity: workers + queue_depth,
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            workers: self.worker_count,
+            pending: self.pending.load(Ordering::Acquire),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Claim an admission slot, or `Busy` when workers and queue are full
+    pub fn reserve(&self) -> Result<Ticket, ProverError> {
+        self.pending
+            .try_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < self.capacity).then_some(n + 1)
+            })
+            .map_err(|queued| ProverError::Busy { queued })?;
+        Ok(Ticket {
+            pending: self.pending.clone(),
+        })
+    }
+
+    /// Validate the witness, wait for a worker, and prove on a blocking thread
+    pub async fn prove_transfer(
+        &self,
+        witness: TransferWitness,
+    ) -> Result<ProvedTransfer, ProverError> {
+        let _ticket = self.reserve()?;
+        let inputs = witness::parse(&witness)?;
+
+        let _permit = self
+            .workers
+            .acquire()
+            .await
+            .map_err(|_| ProverError::Internal("worker pool closed".into()))?;
+
+        let pk = self.pk.clone();
+        tokio::task::spawn_blocking(move || {
+            // the proof's blinding factors: a guessable seed would leak the witness
+            let mut rng = r14_sdk::wallet::os_rng();
+            let (proof, pi) = r14_sdk::prove::prove(
+                &pk,
+                inputs.secret_key,
+                inputs.consumed_note,
+                inputs.merkle_path,
+                inputs.created_notes,
+                inputs.current_ledger,
+                &mut rng,
+            )?;
+            // parse() catches the common mistakes; this catches the rest
+            if !r14_sdk::prove::verify_offchain(&pk.vk, &proof, &pi) {
+                return Err(ProverError::InvalidInput(
+                    "witness does not satisfy the transfer circuit".into(),
+                ));
+            }
+            let (sp, spi) = r14_sdk::prove::serialize_proof_for_soroban(&proof, &pi.to_vec());
+            Ok(witness::proved_transfer(sp, &spi, inputs.current_ledger))
+        })
+        .await
+        .map_err(|e| ProverError::Internal(format!("prover task failed: {e}")))?
+    }
+}
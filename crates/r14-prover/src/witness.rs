@@ -0,0 +1,126 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Request and response bodies for `POST /v1/prove/transfer`.
+//!
+//! Field elements travel as hex strings (with or without `0x`), matching
+//! `r14_sdk::wallet::hex_to_fr`. Proof and public inputs come back in the
+//! same raw-hex form `R14Client::transfer_with_proof` submits.
+
+use ark_bls12_381::Fr;
+use r14_errors::ProverError;
+use r14_sdk::wallet::{hex_to_fr, strip_0x};
+use r14_sdk::{owner_hash, MerklePath, Note, SecretKey, MERKLE_DEPTH};
+
+pub use r14_sdk::prover::{
+    MerklePathJson, NoteJson, ProofJson, ProvedTransfer, PublicInputsJson, TransferWitness,
+};
+
+/// Parsed witness, ready for `r14_sdk::prove::prove`
+pub struct TransferInputs {
+    pub secret_key: Fr,
+    pub consumed_note: Note,
+    pub merkle_path: MerklePath,
+    pub created_notes: [Note; 2],
+    pub current_ledger: u64,
+}
+
+fn field(name: &str, hex: &str) -> Result<Fr, ProverError> {
+    hex_to_fr(hex).map_err(|e| ProverError::InvalidInput(format!("{name}: {e}")))
+}
+
+fn note(name: &str, json: &NoteJson) -> Result<Note, ProverError> {
+    Ok(Note::with_nonce(
+        json.value,
+        json.app_tag,
+        field(&format!("{name}.owner"), &json.owner)?,
+        field(&format!("{name}.nonce"), &json.nonce)?,
+    )
+    .locked_until(json.unlock_after))
+}
+
+/// Decode hex fields and reject witnesses the circuit can't satisfy, so
+/// clients get a 400 instead of a proof that fails verification
+pub fn parse(witness: &TransferWitness) -> Result<TransferInputs, ProverError> {
+    let secret_key = field("secret_key", &witness.secret_key)?;
+    let consumed_note = note("consumed_note", &witness.consumed_note)?;
+    let created_notes = [
+        note("created_notes[0]", &witness.created_notes[0])?,
+        note("created_notes[1]", &witness.created_notes[1])?,
+    ];
+
+    let path = &witness.merkle_path;
+    if path.siblings.len() != MERKLE_DEPTH || path.indices.len() != MERKLE_DEPTH {
+        return Err(ProverError::InvalidInput(format!(
+            "merkle_path: expected {MERKLE_DEPTH} siblings and indices, got {} and {}",
+            path.siblings.len(),
+            path.indices.len()
+        )));
+    }
+    let siblings = path
+        .siblings
+        .iter()
+        .enumerate()
+        .map(|(i, s)| field(&format!("merkle_path.siblings[{i}]"), s))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if owner_hash(&SecretKey(secret_key)).0 != consumed_note.owner {
+        return Err(ProverError::InvalidInput(
+            "secret_key does not own consumed_note".into(),
+        ));
+    }
+    let out_value = created_notes[0]
+        .value
+        .checked_add(created_notes[1].value)
+        .ok_or_else(|| ProverError::InvalidInput("created_notes: value overflow".into()))?;
+    if out_value != consumed_note.value {
+        return Err(ProverError::InvalidInput(format!(
+            "created_notes: values sum to {out_value}, consumed_note holds {}",
+            consumed_note.value
+        )));
+    }
+    if created_notes.iter().any(|n| n.app_tag != consumed_note.app_tag) {
+        return Err(ProverError::InvalidInput(
+            "created_notes: app_tag must match consumed_note".into(),
+        ));
+    }
+    if consumed_note.unlock_after > witness.current_ledger {
+        return Err(ProverError::InvalidInput(format!(
+            "consumed_note: locked until ledger {}, current_ledger is {}",
+            consumed_note.unlock_after, witness.current_ledger
+        )));
+    }
+
+    Ok(TransferInputs {
+        secret_key,
+        consumed_note,
+        merkle_path: MerklePath {
+            siblings,
+            indices: path.indices.clone(),
+        },
+        created_notes,
+        current_ledger: witness.current_ledger,
+    })
+}
+
+/// Response body for a serialized proof and its public inputs
+pub fn proved_transfer(
+    proof: r14_sdk::prove::SerializedProof,
+    public_inputs: &[String],
+    current_ledger: u64,
+) -> ProvedTransfer {
+    ProvedTransfer {
+        proof: ProofJson {
+            a: proof.a,
+            b: proof.b,
+            c: proof.c,
+        },
+        public_inputs: PublicInputsJson {
+            old_root: strip_0x(&public_inputs[0]),
+            nullifier: strip_0x(&public_inputs[1]),
+            cm_0: strip_0x(&public_inputs[2]),
+            cm_1: strip_0x(&public_inputs[3]),
+            current_ledger,
+        },
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::{Arc, OnceLock};
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::UniformRand;
+use ark_groth16::ProvingKey;
+use axum::body::Body;
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+use r14_prover::pool::ProverPool;
+use r14_prover::witness::{MerklePathJson, NoteJson, TransferWitness};
+use r14_sdk::wallet::{fr_to_hex, fr_to_raw_hex};
+use r14_sdk::{Note, SecretKey, MERKLE_DEPTH};
+
+/// One setup per test binary — it dominates the runtime
+fn proving_key() -> ProvingKey<Bls12_381> {
+    static PK: OnceLock<ProvingKey<Bls12_381>> = OnceLock::new();
+    PK.get_or_init(|| r14_prover::keys::setup_transfer().unwrap()).clone()
+}
+
+fn note_json(note: &Note) -> NoteJson {
+    NoteJson {
+        value: note.value,
+        app_tag: note.app_tag,
+        owner: fr_to_hex(&note.owner),
+        nonce: fr_to_hex(&note.nonce),
+        unlock_after: note.unlock_after,
+    }
+}
+
+/// Valid witness for a 1000 → 600 + 400 transfer, plus its output notes
+fn witness() -> (TransferWitness, SecretKey, [Note; 2]) {
+    let mut rng = ark_std::test_rng();
+    let sk = SecretKey::random(&mut rng);
+    let owner = r14_poseidon::owner_hash(&sk);
+    let consumed = Note::new(1000, 1, owner.0, &mut rng);
+    let recipient = r14_poseidon::owner_hash(&SecretKey::random(&mut rng));
+    let created = [
+        Note::new(600, 1, recipient.0, &mut rng),
+        Note::new(400, 1, owner.0, &mut rng),
+    ];
+    let witness = TransferWitness {
+        secret_key: fr_to_hex(&sk.0),
+        consumed_note: note_json(&consumed),
+        merkle_path: MerklePathJson {
+            siblings: (0..MERKLE_DEPTH).map(|_| fr_to_hex(&Fr::rand(&mut rng))).collect(),
+            indices: (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect(),
+        },
+        created_notes: [note_json(&created[0]), note_json(&created[1])],
+        current_ledger: 0,
+    };
+    (witness, sk, created)
+}
+
+async fn post(app: axum::Router, body: String) -> (u16, serde_json::Value) {
+    let resp = app
+        .oneshot(
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/v1/prove/transfer")
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = resp.status().as_u16();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn prove_transfer_returns_soroban_proof() {
+    let pool = Arc::new(ProverPool::new(proving_key(), 1, 1));
+    let app = r14_prover::api::router(pool);
+    let (witness, sk, created) = witness();
+
+    let (status, json) = post(app, serde_json::to_string(&witness).unwrap()).await;
+    assert_eq!(status, 200, "{json}");
+
+    let nonce = r14_sdk::wallet::hex_to_fr(&witness.consumed_note.nonce).unwrap();
+    let index = r14_sdk::MerklePath::<Fr> { siblings: vec![], indices: witness.merkle_path.indices.clone() }.leaf_index();
+    let pi = &json["public_inputs"];
+    assert_eq!(pi["nullifier"], fr_to_raw_hex(&r14_poseidon::nullifier(&sk, &nonce, index).0));
+    assert_eq!(pi["cm_0"], fr_to_raw_hex(&r14_poseidon::commitment(&created[0]).0));
+    assert_eq!(pi["cm_1"], fr_to_raw_hex(&r14_poseidon::commitment(&created[1]).0));
+    assert_eq!(pi["current_ledger"], 0);
+    assert_eq!(json["proof"]["a"].as_str().unwrap().len(), 192);
+    assert_eq!(json["proof"]["b"].as_str().unwrap().len(), 384);
+}
+
+#[tokio::test]
+async fn invalid_witness_is_rejected_before_proving() {
+    let pool = Arc::new(ProverPool::new(proving_key(), 1, 1));
+    let app = r14_prover::api::router(pool);
+
+    let (mut unbalanced, _, _) = witness();
+    unbalanced.created_notes[0].value += 1;
+    let (status, json) = post(app.clone(), serde_json::to_string(&unbalanced).unwrap()).await;
+    assert_eq!(status, 400);
+    assert_eq!(json["code"], "invalid_input");
+
+    let (mut short_path, _, _) = witness();
+    short_path.merkle_path.siblings.pop();
+    let (status, _) = post(app.clone(), serde_json::to_string(&short_path).unwrap()).await;
+    assert_eq!(status, 400);
+
+    let (mut locked, _, _) = witness();
+    locked.consumed_note.unlock_after = 500;
+    locked.current_ledger = 499;
+    let (status, json) = post(app.clone(), serde_json::to_string(&locked).unwrap()).await;
+    assert_eq!(status, 400);
+    assert!(json["error"].as_str().unwrap().contains("locked"), "{json}");
+
+    let (status, json) = post(app, "{\"secret_key\":".into()).await;
+    assert_eq!(status, 400);
+    assert_eq!(json["code"], "invalid_input");
+}
+
+#[tokio::test]
+async fn full_pool_returns_busy() {
+    let pool = Arc::new(ProverPool::new(proving_key(), 1, 1));
+    let app = r14_prover::api::router(pool.clone());
+
+    // one running + one queued fills capacity
+    let _running = pool.reserve().unwrap();
+    let queued = pool.reserve().unwrap();
+    assert_eq!(pool.stats().pending, 2);
+
+    let (witness, _, _) = witness();
+    let (status, json) = post(app, serde_json::to_string(&witness).unwrap()).await;
+    assert_eq!(status, 503);
+    assert_eq!(json["code"], "unavailable");
+
+    drop(queued);
+    assert!(pool.reserve().is_ok());
+}
+
+#[tokio::test]
+async fn cors_is_opt_in() {
+    async fn allowed_origin(app: axum::Router, origin: &str) -> Option<String> {
+        let resp = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/v1/health")
+                    .header("origin", origin)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let allowed = resp.headers().get("access-control-allow-origin");
+        allowed.map(|v| v.to_str().unwrap().to_string())
+    }
+
+    let pool = Arc::new(ProverPool::new(proving_key(), 1, 1));
+    let app = r14_prover::api::router(pool);
+    assert_eq!(allowed_origin(app.clone(), "https://evil.example").await, None);
+
+    let wallet = "https://wallet.example";
+    let app = app.layer(r14_prover::api::cors(vec![wallet.parse().unwrap()]));
+    assert_eq!(allowed_origin(app.clone(), wallet).await.as_deref(), Some(wallet));
+    assert_eq!(allowed_origin(app, "https://evil.example").await, None);
+}
@@ -0,0 +1,55 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Watch the local wallet's balance, syncing with the indexer on an interval.
+//!
+//! Reads `~/.r14/wallet.json` (create one with `r14 keygen`) and prints
+//! whenever the synced balance changes. Stop with Ctrl-C.
+//!
+//! ```sh
+//! R14_POLL_SECS=5 cargo run -p r14-sdk --example balance_watcher
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use r14_sdk::wallet::{load_wallet, save_wallet};
+use r14_sdk::R14Client;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let poll = Duration::from_secs(common::env_or("R14_POLL_SECS", "5").parse()?);
+    let mut wallet = load_wallet()?;
+    let client = R14Client::from_wallet(&wallet)?;
+
+    let mut last: Option<(u64, usize)> = None;
+    let mut ticker = tokio::time::interval(poll);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        let balance = match client.balance(&mut wallet.notes).await {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("sync failed: {e}");
+                continue;
+            }
+        };
+        let synced = balance.notes.iter().filter(|n| n.on_chain).count();
+        if last != Some((balance.total, synced)) {
+            println!(
+                "balance {} ({} notes, {} on-chain)",
+                balance.total,
+                balance.notes.len(),
+                synced
+            );
+            save_wallet(&wallet)?;
+            last = Some((balance.total, synced));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Shared devnet configuration for the r14-sdk examples.
+//!
+//! Every example reads the same environment variables so they can be
+//! pointed at a local devnet harness or testnet without code changes:
+//!
+//! | Variable | Default |
+//! |---|---|
+//! | `R14_INDEXER_URL` | `http://localhost:3000` |
+//! | `R14_NETWORK` | `testnet` |
+//! | `R14_CORE_CONTRACT` | *required* |
+//! | `R14_TRANSFER_CONTRACT` | *required* |
+//! | `R14_STELLAR_SECRET` | *required* |
+
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use r14_sdk::wallet::NoteEntry;
+use r14_sdk::{R14Client, R14Contracts};
+
+pub fn env_or(key: &str, default: &str) -> String {
+    std::env::var(key).unwrap_or_else(|_| default.into())
+}
+
+pub fn env_required(key: &str) -> Result<String> {
+    std::env::var(key).with_context(|| format!("{key} must be set"))
+}
+
+/// Build a client from the `R14_*` environment, using `secret_var` for the signer
+pub fn client_from_env(secret_var: &str) -> Result<R14Client> {
+    let contracts = R14Contracts {
+        core: env_required("R14_CORE_CONTRACT")?,
+        transfer: env_required("R14_TRANSFER_CONTRACT")?,
+    };
+    Ok(R14Client::new(
+        &env_or("R14_INDEXER_URL", "http://localhost:3000"),
+        contracts,
+        &env_required(secret_var)?,
+        &env_or("R14_NETWORK", "testnet"),
+    )?)
+}
+
+/// Poll the indexer until every note has an on-chain leaf index
+pub async fn wait_for_index(client: &R14Client, notes: &mut [NoteEntry], timeout: Duration) -> Result<()> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        client.sync_notes(notes).await?;
+        if notes.iter().all(|n| n.spent || n.index.is_some()) {
+            return Ok(());
+        }
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!("indexer did not pick up notes within {timeout:?}");
+        }
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Full pipeline: keygen → deposit → wait for indexer → private transfer → balance.
+//!
+//! ```sh
+//! R14_CORE_CONTRACT=C... R14_TRANSFER_CONTRACT=C... R14_STELLAR_SECRET=S... \
+//!     cargo run -p r14-sdk --example deposit_and_transfer --features prove
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use r14_sdk::{owner_hash, CoinSelection, SecretKey};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let client = common::client_from_env("R14_STELLAR_SECRET")?;
+    let mut rng = r14_sdk::wallet::crypto_rng();
+
+    // 1. Keygen — sender and recipient live only in memory
+    let alice = SecretKey::random(&mut rng);
+    let alice_owner = owner_hash(&alice);
+    let bob_owner = owner_hash(&SecretKey::random(&mut rng));
+    println!("alice: {}", r14_sdk::wallet::fr_to_hex(&alice_owner.0));
+    println!("bob:   {}", r14_sdk::wallet::fr_to_hex(&bob_owner.0));
+
+    // 2. Deposit
+    let deposit = client.deposit(1_000, 1, &alice_owner.0).await?;
+    println!("deposited {} → {}", deposit.value, r14_sdk::wallet::fr_to_hex(&deposit.commitment.0));
+    let mut notes = vec![deposit.note_entry];
+
+    // 3. Wait until the indexer has the leaf
+    common::wait_for_index(&client, &mut notes, Duration::from_secs(60)).await?;
+    println!("deposit indexed at leaf {}", notes[0].index.unwrap());
+
+    // 4. Private transfer: 700 to bob, 300 change to alice. The wallet
+    //    only tracks notes here; nothing is saved.
+    let mut wallet = r14_sdk::wallet::WalletData::for_test(&alice);
+    wallet.notes = notes;
+    let transfer = client
+        .transfer(&mut wallet, &alice.0, &alice_owner.0, &bob_owner.0, 700, CoinSelection::default())
+        .await?;
+    println!("nullifier: {}", transfer.nullifier);
+
+    // 5. Balance after the change note is indexed. The payment to bob
+    //    stays in the wallet as a record, so count alice's notes only.
+    let alice_hex = r14_sdk::wallet::fr_to_hex(&alice_owner.0);
+    let mut notes: Vec<_> = wallet.notes.iter().filter(|n| n.owner == alice_hex).cloned().collect();
+    common::wait_for_index(&client, &mut notes, Duration::from_secs(60)).await?;
+    let balance = client.balance(&mut notes).await?;
+    println!("alice balance: {}", balance.total);
+    assert_eq!(balance.total, 300);
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Split proving from submission: the user proves locally and hands a
+//! [`PrebuiltProof`] to a relayer, which pays the fee with its own Stellar key.
+//!
+//! ```sh
+//! R14_CORE_CONTRACT=C... R14_TRANSFER_CONTRACT=C... \
+//! R14_STELLAR_SECRET=S_USER... R14_RELAYER_SECRET=S_RELAYER... \
+//!     cargo run -p r14-sdk --example relayer_submit --features prove
+//! ```
+
+mod common;
+
+use std::time::Duration;
+
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_sdk::wallet::{hex_to_fr, strip_0x, NoteEntry};
+use r14_sdk::{commitment, owner_hash, Note, PrebuiltProof, SecretKey};
+
+fn entry(note: &Note) -> NoteEntry {
+    NoteEntry::new(note, &commitment(note))
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let user = common::client_from_env("R14_STELLAR_SECRET")?;
+    let relayer = common::client_from_env("R14_RELAYER_SECRET")?;
+    let mut rng = r14_sdk::wallet::crypto_rng();
+
+    let sk = SecretKey::random(&mut rng);
+    let owner = owner_hash(&sk);
+    let recipient = owner_hash(&SecretKey::random(&mut rng));
+
+    // User funds a note and waits for it to land
+    let deposit = user.deposit(500, 1, &owner.0).await?;
+    let mut notes = vec![deposit.note_entry];
+    common::wait_for_index(&user, &mut notes, Duration::from_secs(60)).await?;
+
+    // User side: build and prove the transfer offline
+    let consumed = Note::with_nonce(500, 1, owner.0, hex_to_fr(&notes[0].nonce)?);
+    let path = user.indexer().merkle_proof(notes[0].index.unwrap()).await?;
+    let out_0 = Note::new(200, 1, recipient.0, &mut rng);
+    let out_1 = Note::new(300, 1, owner.0, &mut rng);
+
+    let ledger = user.indexer().latest_ledger().await?;
+
+    let (pk, _vk) = r14_sdk::prove::setup(&mut StdRng::seed_from_u64(42))?;
+    let (proof, pi) =
+        r14_sdk::prove::prove(&pk, sk.0, consumed, path, [out_0.clone(), out_1.clone()], ledger, &mut rng)?;
+    let (sp, spi) = r14_sdk::prove::serialize_proof_for_soroban(&proof, &pi.to_vec());
+
+    let prebuilt = PrebuiltProof {
+        proof_json: format!(r#"{{"a":"{}","b":"{}","c":"{}"}}"#, sp.a, sp.b, sp.c),
+        old_root: strip_0x(&spi[0]),
+        nullifier: strip_0x(&spi[1]),
+        cm_0: strip_0x(&spi[2]),
+        cm_1: strip_0x(&spi[3]),
+        current_ledger: ledger,
+    };
+
+    // Relayer side: submit without ever seeing the secret key or note values
+    let result = relayer
+        .transfer_with_proof(&prebuilt, entry(&out_0), entry(&out_1), 0)
+        .await?;
+    println!("relayed transfer, nullifier {}", result.nullifier);
+    println!("tx: {}", result.tx_result);
+
+    Ok(())
+}
@@ -0,0 +1,176 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Indexer-signed statements that a nullifier is unspent.
+//!
+//! An indexer started with a signing key answers
+//! `/v1/nullifier/{nullifier}/absence` with a [`NullifierAbsence`]: the
+//! nullifier, the transfer contract, the root and ledger the indexer had
+//! reached, and an Ed25519 signature over them. A relayer or light client
+//! that trusts the indexer's key can turn away a transfer whose nullifier
+//! is already spent before simulating it, and keep the statement as a
+//! record of what the indexer said.
+//!
+//! A statement covers only what the indexer had seen by `ledger`. Check
+//! that `ledger` is recent and `root` one the contract still accepts
+//! before relying on it.
+//!
+//! ```rust
+//! use r14_sdk::absence::{public_key, NullifierAbsence};
+//! # use ark_bls12_381::Fr;
+//! # use r14_sdk::{MerkleRoot, Nullifier};
+//! # let (nullifier, root) = (Nullifier(Fr::from(5u64)), MerkleRoot(Fr::from(9u64)));
+//!
+//! let indexer_key = [7u8; 32];
+//! let statement = NullifierAbsence::sign(&indexer_key, "CTRANSFER", nullifier, root, 3, 1200);
+//! assert!(statement.verify(&public_key(&indexer_key)).is_ok());
+//! assert!(statement.verify(&[0; 32]).is_err());
+//! ```
+
+use ark_ff::{BigInteger, PrimeField};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{R14Error, R14Result};
+use crate::{MerkleRoot, Nullifier};
+
+/// Start of every signed message, so the signature can't be passed off
+/// as one over anything else
+pub const DOMAIN: &[u8] = b"r14-indexer/nullifier-absence/v1";
+
+/// `/v1/nullifier/{nullifier}/absence` — the indexer's signed word that
+/// `nullifier` was unspent when its tree had `leaf_count` leaves and root
+/// `root`, as of `ledger`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NullifierAbsence {
+    pub nullifier: Nullifier,
+    /// Transfer contract the indexer indexes
+    pub contract_id: String,
+    pub root: MerkleRoot,
+    pub leaf_count: u64,
+    /// Latest network ledger the indexer had seen
+    pub ledger: u64,
+    /// Ed25519 key that signed, as hex
+    #[serde(with = "hex_bytes")]
+    pub public_key: [u8; 32],
+    /// Over [`Self::message`], as hex
+    #[serde(with = "hex_bytes")]
+    pub signature: [u8; 64],
+}
+
+/// Ed25519 public key of the 32-byte `secret_key`, the one clients pin
+pub fn public_key(secret_key: &[u8; 32]) -> [u8; 32] {
+    SigningKey::from_bytes(secret_key).verifying_key().to_bytes()
+}
+
+impl NullifierAbsence {
+    /// The statement about `nullifier`, signed with `secret_key`
+    pub fn sign(
+        secret_key: &[u8; 32],
+        contract_id: &str,
+        nullifier: Nullifier,
+        root: MerkleRoot,
+        leaf_count: u64,
+        ledger: u64,
+    ) -> Self {
+        let key = SigningKey::from_bytes(secret_key);
+        let mut statement = Self {
+            nullifier,
+            contract_id: contract_id.to_string(),
+            root,
+            leaf_count,
+            ledger,
+            public_key: key.verifying_key().to_bytes(),
+            signature: [0; 64],
+        };
+        statement.signature = key.sign(&statement.message()).to_bytes();
+        statement
+    }
+
+    /// Bytes the signature covers: [`DOMAIN`], the contract id's length
+    /// as a `u32` and the id, the nullifier and root as 32 bytes each,
+    /// then `leaf_count` and `ledger`, all big-endian
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = DOMAIN.to_vec();
+        message.extend((self.contract_id.len() as u32).to_be_bytes());
+        message.extend(self.contract_id.as_bytes());
+        for fr in [&self.nullifier.0, &self.root.0] {
+            let bytes = fr.into_bigint().to_bytes_be();
+            message.extend(std::iter::repeat_n(0, 32 - bytes.len()));
+            message.extend(bytes);
+        }
+        message.extend(self.leaf_count.to_be_bytes());
+        message.extend(self.ledger.to_be_bytes());
+        message
+    }
+
+    /// Check the statement was signed with `trusted_key`. The key it
+    /// carries only has to match; it's never trusted on its own.
+    pub fn verify(&self, trusted_key: &[u8; 32]) -> R14Result<()> {
+        if self.public_key != *trusted_key {
+            return Err(R14Error::Indexer(format!(
+                "absence statement signed by {}, not the trusted key",
+                hex::encode(self.public_key)
+            )));
+        }
+        let key = VerifyingKey::from_bytes(trusted_key)
+            .map_err(|e| R14Error::Indexer(format!("bad indexer key: {e}")))?;
+        key.verify(&self.message(), &Signature::from_bytes(&self.signature))
+            .map_err(|_| R14Error::Indexer("absence statement has a bad signature".into()))
+    }
+}
+
+/// Fixed-size byte arrays as hex, with or without `0x`
+mod hex_bytes {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(d: D) -> Result<[u8; N], D::Error> {
+        let s = String::deserialize(d)?;
+        let mut bytes = [0; N];
+        hex::decode_to_slice(crate::strip_0x(&s), &mut bytes).map_err(D::Error::custom)?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn statement() -> NullifierAbsence {
+        let (nullifier, root) = (Nullifier(Fr::from(5u64)), MerkleRoot(Fr::from(9u64)));
+        NullifierAbsence::sign(&[7; 32], "CTRANSFER", nullifier, root, 3, 1200)
+    }
+
+    #[test]
+    fn statements_round_trip_as_json() {
+        let statement = statement();
+        let json = serde_json::to_value(&statement).unwrap();
+        assert_eq!(json["nullifier"], format!("0x{}", "0".repeat(63) + "5"));
+        assert_eq!(json["signature"].as_str().unwrap().len(), 128);
+        let parsed: NullifierAbsence = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, statement);
+        parsed.verify(&public_key(&[7; 32])).unwrap();
+    }
+
+    #[test]
+    fn altered_statements_fail() {
+        let key = public_key(&[7; 32]);
+        let altered = [
+            NullifierAbsence { ledger: 1201, ..statement() },
+            NullifierAbsence { contract_id: "CTRANSFES".into(), ..statement() },
+            NullifierAbsence { nullifier: Nullifier(Fr::from(6u64)), ..statement() },
+        ];
+        for statement in altered {
+            let err = statement.verify(&key).unwrap_err();
+            assert!(err.to_string().contains("bad signature"), "{err}");
+        }
+        let resigned = NullifierAbsence::sign(&[8; 32], "CTRANSFER", statement().nullifier, statement().root, 3, 1200);
+        assert!(resigned.verify(&key).unwrap_err().to_string().contains("not the trusted key"));
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Published proving keys, downloaded and pinned by SHA-256.
+//!
+//! Without installed keys, every proof starts with a full Groth16 setup
+//! from the public seed. A deployment can instead publish its transfer
+//! keys (the `r14-ceremony export` output) and pin their hashes in
+//! `r14.toml`:
+//!
+//! ```toml
+//! [artifacts.transfer]
+//! proving_key = { url = "https://example.org/r14/transfer.pk", sha256 = "5d2c..." }
+//! verifying_key = { url = "https://example.org/r14/transfer.vk.json", sha256 = "0e41..." }
+//! ```
+//!
+//! [`KeyCache::install`] downloads both, refuses anything that doesn't
+//! hash to its pin, and writes them to `~/.r14/keys/`. From then on
+//! `R14Client`, `offline::prove_and_sign`, `r14 transfer` and
+//! `r14 init-contract` use the installed keys instead of running setup.
+//!
+//! ```rust,no_run
+//! use r14_sdk::artifacts::KeyCache;
+//! use r14_sdk::deployment::Deployment;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let (_, deployment) = Deployment::discover()?.expect("no r14.toml");
+//! let artifacts = deployment.artifacts.expect("no [artifacts] in r14.toml");
+//! let cache = KeyCache::open_default()?;
+//! let downloaded = cache.install(&artifacts.transfer, &reqwest::Client::new()).await?;
+//! println!("{downloaded} file(s) downloaded to {}", cache.dir().display());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Proving key file, in the format `r14-ceremony export` writes
+pub const TRANSFER_PK_FILE: &str = "transfer.pk";
+/// VK JSON for `r14 init-contract --vk`
+pub const TRANSFER_VK_FILE: &str = "transfer.vk.json";
+
+/// One published file and the hash it must have
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Artifact {
+    pub url: String,
+    /// SHA-256 of the file, 64-char hex
+    pub sha256: String,
+}
+
+/// A circuit's proving and verifying key
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyArtifacts {
+    pub proving_key: Artifact,
+    pub verifying_key: Artifact,
+}
+
+/// `[artifacts]` in `r14.toml`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PublishedArtifacts {
+    pub transfer: KeyArtifacts,
+}
+
+/// Default key cache directory, next to the wallet
+pub fn default_dir() -> Result<PathBuf> {
+    Ok(crate::wallet::wallet_path()?.with_file_name("keys"))
+}
+
+/// Directory holding installed keys
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    pub fn open(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Ok(Self::open(default_dir()?))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn transfer_pk_path(&self) -> PathBuf {
+        self.dir.join(TRANSFER_PK_FILE)
+    }
+
+    pub fn transfer_vk_path(&self) -> PathBuf {
+        self.dir.join(TRANSFER_VK_FILE)
+    }
+
+    /// Download whichever of `keys` isn't installed at its pinned hash and
+    /// return how many files were downloaded.
+    ///
+    /// Both files are fetched and checked before either is written, and the
+    /// proving key is written last, since its presence is what switches
+    /// proving over.
+    pub async fn install(&self, keys: &KeyArtifacts, http: &reqwest::Client) -> Result<usize> {
+        let targets = [(&keys.verifying_key, self.transfer_vk_path()), (&keys.proving_key, self.transfer_pk_path())];
+        let mut fetched = Vec::new();
+        for (artifact, path) in targets {
+            if !is_installed(artifact, &path)? {
+                fetched.push((fetch(artifact, http).await?, path));
+            }
+        }
+
+        std::fs::create_dir_all(&self.dir).with_context(|| format!("creating {}", self.dir.display()))?;
+        for (bytes, path) in &fetched {
+            // write-then-rename so a crash never leaves a truncated key
+            let tmp = path.with_extension("tmp");
+            std::fs::write(&tmp, bytes).with_context(|| format!("writing {}", tmp.display()))?;
+            std::fs::rename(&tmp, path).with_context(|| format!("writing {}", path.display()))?;
+        }
+        Ok(fetched.len())
+    }
+
+    /// Installed transfer keys, if any. The proving key must carry the
+    /// installed verifying key, or its proofs wouldn't verify against the
+    /// VK registered from that file.
+    #[cfg(feature = "prove")]
+    pub fn load_transfer(&self) -> Result<Option<TransferKeys>> {
+        // the file matched its pin when installed, so skip the subgroup checks
+        self.load_transfer_with(ark_serialize::Validate::No)
+    }
+
+    /// [`Self::load_transfer`] with the subgroup checks, for a directory
+    /// keys may have been copied into by hand rather than installed against
+    /// a pin. Slower; meant for services that load the key once.
+    #[cfg(feature = "prove")]
+    pub fn load_transfer_checked(&self) -> Result<Option<TransferKeys>> {
+        self.load_transfer_with(ark_serialize::Validate::Yes)
+    }
+
+    #[cfg(feature = "prove")]
+    fn load_transfer_with(&self, validate: ark_serialize::Validate) -> Result<Option<TransferKeys>> {
+        use ark_serialize::{CanonicalDeserialize, Compress};
+        use crate::serialize::{circuit_id, serialize_vk_for_soroban};
+
+        let path = self.transfer_pk_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(&path).with_context(|| format!("reading proving key {}", path.display()))?;
+        let pk = ark_groth16::ProvingKey::deserialize_with_mode(bytes.as_slice(), Compress::No, validate)
+            .with_context(|| format!("decoding proving key {}", path.display()))?;
+
+        let vk_path = self.transfer_vk_path();
+        let json = std::fs::read_to_string(&vk_path)
+            .with_context(|| format!("reading verifying key {} for {}", vk_path.display(), path.display()))?;
+        let installed: VkJson =
+            serde_json::from_str(&json).with_context(|| format!("decoding verifying key {}", vk_path.display()))?;
+        let installed = circuit_id(&installed.into());
+        let carried = circuit_id(&serialize_vk_for_soroban(&pk.vk, crate::MERKLE_DEPTH));
+        ensure!(
+            installed == carried,
+            "{} is for circuit_id {carried} but {} is {installed}; install both from the same `r14-ceremony export`",
+            path.display(),
+            vk_path.display()
+        );
+        let vk = pk.vk.clone();
+        Ok(Some((pk, vk)))
+    }
+}
+
+/// `transfer.vk.json`, as `r14 init-contract --vk` reads it
+#[cfg(feature = "prove")]
+#[derive(Deserialize)]
+struct VkJson {
+    alpha_g1: String,
+    beta_g2: String,
+    gamma_g2: String,
+    delta_g2: String,
+    ic: Vec<String>,
+    depth: u32,
+}
+
+#[cfg(feature = "prove")]
+impl From<VkJson> for crate::serialize::SerializedVK {
+    fn from(vk: VkJson) -> Self {
+        Self {
+            alpha_g1: vk.alpha_g1,
+            beta_g2: vk.beta_g2,
+            gamma_g2: vk.gamma_g2,
+            delta_g2: vk.delta_g2,
+            ic: vk.ic,
+            depth: vk.depth,
+        }
+    }
+}
+
+#[cfg(feature = "prove")]
+pub type TransferKeys = (
+    ark_groth16::ProvingKey<ark_bls12_381::Bls12_381>,
+    ark_groth16::VerifyingKey<ark_bls12_381::Bls12_381>,
+);
+
+/// Transfer keys to prove with: the installed ones when the default cache
+/// has them, otherwise the deterministic setup
+#[cfg(all(feature = "prove", not(target_arch = "wasm32")))]
+pub fn transfer_keys() -> Result<TransferKeys> {
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    if let Some(keys) = KeyCache::open_default()?.load_transfer()? {
+        return Ok(keys);
+    }
+    Ok(crate::prove::setup(&mut StdRng::seed_from_u64(crate::client::TRANSFER_SETUP_SEED))?)
+}
+
+/// Download `artifact` and check it against its pin
+pub async fn fetch(artifact: &Artifact, http: &reqwest::Client) -> Result<Vec<u8>> {
+    let resp = http.get(&artifact.url).send().await.with_context(|| format!("fetching {}", artifact.url))?;
+    let status = resp.status();
+    if !status.is_success() {
+        bail!("fetching {}: HTTP {status}", artifact.url);
+    }
+    let bytes = resp.bytes().await.with_context(|| format!("fetching {}", artifact.url))?.to_vec();
+    let actual = sha256_hex(&bytes);
+    ensure!(
+        actual == pinned(artifact),
+        "{}: SHA-256 is {actual}, expected {}; refusing to install",
+        artifact.url,
+        artifact.sha256
+    );
+    Ok(bytes)
+}
+
+/// Whether `path` already holds `artifact`
+fn is_installed(artifact: &Artifact, path: &Path) -> Result<bool> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(sha256_hex(&bytes) == pinned(artifact)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("reading {}", path.display())),
+    }
+}
+
+fn pinned(artifact: &Artifact) -> String {
+    crate::wallet::strip_0x(&artifact.sha256).to_ascii_lowercase()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Server answering every request with `body`; returns its base URL
+    async fn file_server(body: &'static [u8]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut sock, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = sock.read(&mut buf).await;
+                let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n", body.len());
+                let _ = sock.write_all(head.as_bytes()).await;
+                let _ = sock.write_all(body).await;
+            }
+        });
+        url
+    }
+
+    fn keys(url: &str, pk_hash: String, vk_hash: String) -> KeyArtifacts {
+        KeyArtifacts {
+            proving_key: Artifact { url: format!("{url}/transfer.pk"), sha256: pk_hash },
+            verifying_key: Artifact { url: format!("{url}/transfer.vk.json"), sha256: vk_hash },
+        }
+    }
+
+    #[tokio::test]
+    async fn installs_pinned_files_once() {
+        let url = file_server(b"published key").await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = KeyCache::open(dir.path().join("keys"));
+        let hash = sha256_hex(b"published key");
+        let keys = keys(&url, hash.to_uppercase(), format!("0x{hash}"));
+        let http = reqwest::Client::new();
+
+        assert_eq!(cache.install(&keys, &http).await.unwrap(), 2);
+        assert_eq!(std::fs::read(cache.transfer_pk_path()).unwrap(), b"published key");
+        assert_eq!(std::fs::read(cache.transfer_vk_path()).unwrap(), b"published key");
+        assert_eq!(cache.install(&keys, &http).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn refuses_a_hash_mismatch() {
+        let url = file_server(b"tampered key").await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = KeyCache::open(dir.path());
+        let hash = sha256_hex(b"published key");
+        let keys = keys(&url, hash.clone(), hash);
+
+        let err = cache.install(&keys, &reqwest::Client::new()).await.unwrap_err();
+        assert!(err.to_string().contains("refusing to install"), "{err:#}");
+        assert!(!cache.transfer_pk_path().exists());
+        assert!(!cache.transfer_vk_path().exists());
+    }
+
+    #[cfg(feature = "prove")]
+    #[test]
+    fn proving_key_must_match_the_installed_vk() {
+        use ark_serialize::CanonicalSerialize;
+        use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = KeyCache::open(dir.path());
+        let (pk, vk) = crate::prove::setup(&mut StdRng::seed_from_u64(7)).unwrap();
+        let mut bytes = Vec::new();
+        pk.serialize_uncompressed(&mut bytes).unwrap();
+        std::fs::write(cache.transfer_pk_path(), bytes).unwrap();
+
+        // a proving key without its VK is refused
+        let err = cache.load_transfer().unwrap_err();
+        assert!(err.to_string().contains("reading verifying key"), "{err:#}");
+
+        let mut svk = crate::serialize::serialize_vk_for_soroban(&vk, crate::MERKLE_DEPTH);
+        std::fs::write(cache.transfer_vk_path(), crate::client::vk_to_json(&svk)).unwrap();
+        assert!(cache.load_transfer().unwrap().is_some());
+        assert!(cache.load_transfer_checked().unwrap().is_some());
+
+        // the VK from another ceremony: only δ differs
+        svk.delta_g2 = svk.gamma_g2.clone();
+        std::fs::write(cache.transfer_vk_path(), crate::client::vk_to_json(&svk)).unwrap();
+        let err = cache.load_transfer().unwrap_err();
+        assert!(err.to_string().contains("install both from the same"), "{err:#}");
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Association sets for privacy-pools style compliance proofs.
+//!
+//! An association set provider publishes a list of deposit commitments it
+//! has approved. A spender proves with `AssociationCircuit` (`prove`
+//! feature) that the note they spend is in that list, without revealing
+//! which one. Integrators then accept funds only from sets they trust.
+//!
+//! The set is an ordinary Merkle tree over the approved commitments, in
+//! the provider's published order, with the same depth and hashing as the
+//! commitment tree.
+//!
+//! ```rust
+//! use r14_sdk::association::AssociationSet;
+//! # use ark_bls12_381::Fr;
+//! # use r14_sdk::Commitment;
+//! # let (cm_a, cm_b) = (Commitment(Fr::from(1u64)), Commitment(Fr::from(2u64)));
+//!
+//! let set = AssociationSet::new(vec![cm_a, cm_b]);
+//! let path = set.path(&cm_b).expect("approved");
+//! assert!(r14_sdk::merkle::verify_proof(cm_b, &path, set.root()));
+//! // prove with r14_sdk::prove::prove_association(.., path, ..)
+//! ```
+
+use anyhow::Result;
+use ark_bls12_381::Fr;
+use r14_types::{Commitment, MerklePath};
+
+use crate::merkle::{compute_root, path_from_leaves};
+use crate::wallet::{fr_to_raw_hex, hex_to_fr};
+
+/// Approved deposit commitments, in the provider's order
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AssociationSet {
+    commitments: Vec<Commitment>,
+}
+
+impl AssociationSet {
+    pub fn new(commitments: Vec<Commitment>) -> Self {
+        Self { commitments }
+    }
+
+    /// Parse a published list of hex commitments
+    pub fn from_hex<S: AsRef<str>>(commitments: &[S]) -> Result<Self> {
+        let commitments = commitments
+            .iter()
+            .map(|c| hex_to_fr(c.as_ref()).map(Commitment))
+            .collect::<Result<_>>()?;
+        Ok(Self { commitments })
+    }
+
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    pub fn contains(&self, commitment: &Commitment) -> bool {
+        self.commitments.contains(commitment)
+    }
+
+    /// Root the association proof's sixth public input must equal
+    pub fn root(&self) -> Fr {
+        compute_root(&self.commitments)
+    }
+
+    /// Root as raw hex (no 0x prefix)
+    pub fn root_hex(&self) -> String {
+        fr_to_raw_hex(&self.root())
+    }
+
+    /// Path from `commitment` to [`AssociationSet::root`]; `None` if the
+    /// commitment isn't approved
+    pub fn path(&self, commitment: &Commitment) -> Option<MerklePath> {
+        let index = self.commitments.iter().position(|c| c == commitment)?;
+        path_from_leaves(&self.commitments, index.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::verify_proof;
+    use ark_ff::UniformRand;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn paths_lead_to_root() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let cms: Vec<Commitment> = (0..7).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let set = AssociationSet::new(cms.clone());
+        for cm in &cms {
+            assert!(verify_proof(*cm, &set.path(cm).unwrap(), set.root()));
+        }
+
+        let outsider = Commitment(Fr::rand(&mut rng));
+        assert!(!set.contains(&outsider));
+        assert!(set.path(&outsider).is_none());
+    }
+
+    #[test]
+    fn hex_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let cms: Vec<Commitment> = (0..3).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let hex: Vec<String> = cms.iter().map(|c| crate::wallet::fr_to_hex(&c.0)).collect();
+        let set = AssociationSet::from_hex(&hex).unwrap();
+        assert_eq!(set, AssociationSet::new(cms));
+        assert_eq!(set.root_hex().len(), 64);
+        assert!(AssociationSet::from_hex(&["zz"]).is_err());
+    }
+}
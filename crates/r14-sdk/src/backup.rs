@@ -0,0 +1,421 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Encrypted wallet backups for moving notes between machines.
+//!
+//! [`export_notes`] writes the wallet's keys, notes and session records to
+//! a passphrase-encrypted archive; [`import_notes`] merges one back into a
+//! wallet of the same key and [`restore_notes`] into a fresh one. History,
+//! pending transfers and endpoints stay behind: they belong to the
+//! machine, and a fresh wallet's config is kept.
+//!
+//! The archive is JSON with a plaintext header and one sealed payload:
+//!
+//! ```json
+//! { "format": "r14-backup", "version": 1,
+//!   "kdf": { "algorithm": "argon2id", "salt": "…", "m_cost": 19456, "t_cost": 2, "p_cost": 1 },
+//!   "nonce": "…", "ciphertext": "…" }
+//! ```
+//!
+//! The key is Argon2id of the passphrase; the payload is
+//! ChaCha20-Poly1305 with the header as associated data, so a changed
+//! header fails to decrypt just like a wrong passphrase.
+//!
+//! Importing skips notes the wallet already has (same commitment), only
+//! taking a spent flag or leaf index the wallet's copy lacks. A backup of
+//! another key is refused by [`import_notes`]; [`restore_notes`] takes it
+//! into a wallet without notes, which then takes on the backup's keys,
+//! e.g. after `r14 keygen` on a new machine.
+//!
+//! ```rust,no_run
+//! use r14_sdk::wallet;
+//!
+//! # fn example() -> anyhow::Result<()> {
+//! let w = wallet::load_wallet()?;
+//! wallet::export_notes(&w, "r14-backup.json", "correct horse battery staple")?;
+//!
+//! // on the other machine
+//! let mut w = wallet::load_wallet()?;
+//! let summary = wallet::restore_notes(&mut w, "r14-backup.json", "correct horse battery staple")?;
+//! println!("{} notes added, {} already here", summary.added, summary.duplicates);
+//! wallet::save_wallet(&w)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::session::SessionEntry;
+use crate::wallet::{hex_to_fr, NoteEntry, WalletData};
+
+/// `format` of every archive
+pub const BACKUP_FORMAT: &str = "r14-backup";
+
+/// Archive version this SDK writes; it reads this and older ones
+pub const BACKUP_VERSION: u32 = 1;
+
+/// Argon2id memory (KiB), passes and lanes for new archives
+const KDF_COSTS: (u32, u32, u32) = (19 * 1024, 2, 1);
+
+/// Largest Argon2id memory (KiB), passes and lanes an archive may ask
+/// for. The header is only authenticated after the key is derived, so an
+/// edited file must not get to pick an arbitrary cost.
+const KDF_MAX_COSTS: (u32, u32, u32) = (256 * 1024, 16, 8);
+
+const SALT_LEN: usize = 16;
+
+/// What [`import_notes`] changed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Notes the wallet didn't have
+    pub added: usize,
+    /// Notes already present that took the backup's spent flag or leaf index
+    pub updated: usize,
+    /// Notes already present with nothing to take
+    pub duplicates: usize,
+    /// Session records the wallet didn't have
+    pub sessions_added: usize,
+    /// The wallet had no notes and took the backup's keys
+    pub adopted_keys: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    format: String,
+    version: u32,
+    kdf: Kdf,
+    /// Hex
+    nonce: String,
+    /// Hex
+    ciphertext: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Kdf {
+    algorithm: String,
+    /// Hex
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+/// The sealed payload. Secrets are wiped on drop.
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    secret_key: String,
+    owner_hash: String,
+    stellar_secret: String,
+    notes: Vec<NoteEntry>,
+    #[serde(default)]
+    sessions: Vec<SessionEntry>,
+}
+
+impl Drop for Contents {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+        self.stellar_secret.zeroize();
+    }
+}
+
+/// Write `wallet`'s keys, notes and session records to `path`, encrypted
+/// under `passphrase`
+pub fn export_notes(wallet: &WalletData, path: impl AsRef<Path>, passphrase: &str) -> Result<()> {
+    let path = path.as_ref();
+    let contents = Contents {
+        secret_key: wallet.secret_key.clone(),
+        owner_hash: wallet.owner_hash.clone(),
+        stellar_secret: wallet.stellar_secret.clone(),
+        notes: wallet.notes.clone(),
+        sessions: wallet.sessions.clone(),
+    };
+    let archive = seal(&contents, passphrase, KDF_COSTS)?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    crate::wallet::write_atomic(path, serde_json::to_string_pretty(&archive)?.as_bytes())
+}
+
+/// Decrypt the backup at `path` and merge it into `wallet`, which must
+/// hold the same key. Save the wallet afterwards.
+pub fn import_notes(wallet: &mut WalletData, path: impl AsRef<Path>, passphrase: &str) -> Result<ImportSummary> {
+    let contents = read(path.as_ref(), passphrase)?;
+    merge(wallet, &contents, false)
+}
+
+/// Like [`import_notes`], but a wallet of another key with no notes and
+/// no pending transfers takes on the backup's keys, dropping its own.
+/// Save the wallet afterwards.
+pub fn restore_notes(wallet: &mut WalletData, path: impl AsRef<Path>, passphrase: &str) -> Result<ImportSummary> {
+    let contents = read(path.as_ref(), passphrase)?;
+    merge(wallet, &contents, true)
+}
+
+fn read(path: &Path, passphrase: &str) -> Result<Contents> {
+    let data = fs::read_to_string(path).with_context(|| format!("cannot read backup at {}", path.display()))?;
+    let archive: Archive = serde_json::from_str(&data).context("not an r14 backup")?;
+    open(&archive, passphrase)
+}
+
+fn seal(contents: &Contents, passphrase: &str, (m_cost, t_cost, p_cost): (u32, u32, u32)) -> Result<Archive> {
+    if passphrase.is_empty() {
+        bail!("backup passphrase is empty");
+    }
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let kdf = Kdf {
+        algorithm: "argon2id".into(),
+        salt: hex::encode(salt),
+        m_cost,
+        t_cost,
+        p_cost,
+    };
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = Zeroizing::new(serde_json::to_vec(contents)?);
+    let cipher = ChaCha20Poly1305::new((&*derive_key(passphrase, &kdf)?).into());
+    let aad = header(BACKUP_FORMAT, BACKUP_VERSION, &kdf)?;
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: &plaintext, aad: &aad })
+        .map_err(|_| anyhow::anyhow!("cannot encrypt backup"))?;
+    Ok(Archive {
+        format: BACKUP_FORMAT.into(),
+        version: BACKUP_VERSION,
+        kdf,
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn open(archive: &Archive, passphrase: &str) -> Result<Contents> {
+    if archive.format != BACKUP_FORMAT {
+        bail!("not an r14 backup (format `{}`)", archive.format);
+    }
+    if archive.version == 0 || archive.version > BACKUP_VERSION {
+        bail!("backup version {} is not supported (this SDK reads up to {BACKUP_VERSION})", archive.version);
+    }
+    if archive.kdf.algorithm != "argon2id" {
+        bail!("unsupported backup key derivation `{}`", archive.kdf.algorithm);
+    }
+    let nonce: [u8; 12] = hex::decode(&archive.nonce)
+        .context("bad backup nonce")?
+        .try_into()
+        .map_err(|v: Vec<u8>| anyhow::anyhow!("bad backup nonce length {}", v.len()))?;
+    let ciphertext = hex::decode(&archive.ciphertext).context("bad backup ciphertext")?;
+    let cipher = ChaCha20Poly1305::new((&*derive_key(passphrase, &archive.kdf)?).into());
+    let aad = header(&archive.format, archive.version, &archive.kdf)?;
+    let plaintext = Zeroizing::new(
+        cipher
+            .decrypt(&Nonce::from(nonce), Payload { msg: &ciphertext, aad: &aad })
+            .map_err(|_| anyhow::anyhow!("wrong passphrase or corrupted backup"))?,
+    );
+    serde_json::from_slice(&plaintext).context("backup payload is not valid")
+}
+
+/// Associated data binding the plaintext header to the payload
+fn header(format: &str, version: u32, kdf: &Kdf) -> Result<Vec<u8>> {
+    Ok(serde_json::to_vec(&(format, version, kdf))?)
+}
+
+/// The archive key, wiped when dropped like the plaintext it protects
+fn derive_key(passphrase: &str, kdf: &Kdf) -> Result<Zeroizing<[u8; 32]>> {
+    let (m_max, t_max, p_max) = KDF_MAX_COSTS;
+    if kdf.m_cost > m_max || kdf.t_cost > t_max || kdf.p_cost > p_max {
+        bail!(
+            "backup key derivation costs m={} t={} p={} exceed the limit m={m_max} t={t_max} p={p_max}",
+            kdf.m_cost,
+            kdf.t_cost,
+            kdf.p_cost
+        );
+    }
+    let salt = hex::decode(&kdf.salt).context("bad backup salt")?;
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| anyhow::anyhow!("bad backup key derivation parameters: {e}"))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut *key)
+        .map_err(|e| anyhow::anyhow!("cannot derive backup key: {e}"))?;
+    Ok(key)
+}
+
+fn merge(wallet: &mut WalletData, backup: &Contents, adopt_keys: bool) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let backup_owner = hex_to_fr(&backup.owner_hash).context("bad owner hash in backup")?;
+    if hex_to_fr(&wallet.owner_hash).ok() != Some(backup_owner) {
+        if !adopt_keys {
+            bail!(
+                "backup belongs to owner {} but this wallet is {}; use restore_notes to take on its keys",
+                backup.owner_hash,
+                wallet.owner_hash
+            );
+        }
+        if !wallet.notes.is_empty() || !wallet.pending.is_empty() {
+            bail!(
+                "backup belongs to owner {} but this wallet is {} and holds notes; import into a fresh wallet",
+                backup.owner_hash,
+                wallet.owner_hash
+            );
+        }
+        wallet.secret_key.zeroize();
+        wallet.secret_key = backup.secret_key.clone();
+        wallet.owner_hash = backup.owner_hash.clone();
+        wallet.stellar_secret.zeroize();
+        wallet.stellar_secret = backup.stellar_secret.clone();
+        wallet.sessions.clear();
+        summary.adopted_keys = true;
+    }
+
+    for note in &backup.notes {
+        match wallet.notes.iter_mut().find(|n| n.commitment == note.commitment) {
+            Some(existing) => {
+                let spent = note.spent && !existing.spent;
+                let index = note.index.is_some() && existing.index.is_none();
+                if spent {
+                    existing.spent = true;
+                }
+                if index {
+                    existing.index = note.index;
+                }
+                if spent || index {
+                    summary.updated += 1;
+                } else {
+                    summary.duplicates += 1;
+                }
+            }
+            None => {
+                wallet.notes.push(note.clone());
+                summary.added += 1;
+            }
+        }
+    }
+    for session in &backup.sessions {
+        if !wallet.sessions.iter().any(|s| s.index == session.index) {
+            wallet.sessions.push(session.clone());
+            summary.sessions_added += 1;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commitment, owner_hash, Note, SecretKey};
+
+    /// Cheap KDF so tests stay fast
+    const TEST_COSTS: (u32, u32, u32) = (64, 1, 1);
+
+    fn wallet(sk: &SecretKey, notes: Vec<NoteEntry>) -> WalletData {
+        let mut w = WalletData::for_test(sk);
+        w.notes = notes;
+        w
+    }
+
+    fn note(value: u64, sk: &SecretKey) -> NoteEntry {
+        let note = Note::new(value, 1, owner_hash(sk).0, &mut crate::wallet::crypto_rng());
+        NoteEntry::new(&note, &commitment(&note))
+    }
+
+    fn contents(w: &WalletData) -> Contents {
+        Contents {
+            secret_key: w.secret_key.clone(),
+            owner_hash: w.owner_hash.clone(),
+            stellar_secret: w.stellar_secret.clone(),
+            notes: w.notes.clone(),
+            sessions: w.sessions.clone(),
+        }
+    }
+
+    #[test]
+    fn round_trip_and_wrong_passphrase() {
+        let sk = SecretKey::random(&mut crate::wallet::crypto_rng());
+        let w = wallet(&sk, vec![note(10, &sk), note(20, &sk)]);
+        let archive = seal(&contents(&w), "hunter2", TEST_COSTS).unwrap();
+        let json = serde_json::to_string(&archive).unwrap();
+        assert!(!json.contains(&w.secret_key[2..]) && json.contains(r#""version":1"#));
+
+        let back = open(&archive, "hunter2").unwrap();
+        assert_eq!((back.secret_key.as_str(), back.notes.len()), (w.secret_key.as_str(), 2));
+        let err = open(&archive, "hunter3").err().unwrap();
+        assert_eq!(err.to_string(), "wrong passphrase or corrupted backup");
+
+        // the header is authenticated too
+        let mut tampered: Archive = serde_json::from_str(&json).unwrap();
+        tampered.kdf.t_cost = 2;
+        assert!(open(&tampered, "hunter2").is_err());
+        tampered.version = BACKUP_VERSION + 1;
+        assert!(open(&tampered, "hunter2").err().unwrap().to_string().contains("not supported"));
+    }
+
+    #[test]
+    fn oversized_kdf_costs_are_refused() {
+        let sk = SecretKey::random(&mut crate::wallet::crypto_rng());
+        let mut archive = seal(&contents(&wallet(&sk, vec![])), "hunter2", TEST_COSTS).unwrap();
+        archive.kdf.m_cost = u32::MAX;
+        assert!(open(&archive, "hunter2").err().unwrap().to_string().contains("exceed the limit"));
+        archive.kdf.m_cost = TEST_COSTS.0;
+        archive.kdf.t_cost = KDF_MAX_COSTS.1 + 1;
+        assert!(open(&archive, "hunter2").err().unwrap().to_string().contains("exceed the limit"));
+    }
+
+    #[test]
+    fn merge_skips_duplicates_and_takes_spent_flags() {
+        let sk = SecretKey::random(&mut crate::wallet::crypto_rng());
+        let (a, b, c) = (note(10, &sk), note(20, &sk), note(30, &sk));
+        let mut spent_b = b.clone();
+        spent_b.spent = true;
+        let backup = contents(&wallet(&sk, vec![a.clone(), spent_b, c]));
+
+        let mut w = wallet(&sk, vec![a, b]);
+        let summary = merge(&mut w, &backup, false).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary { added: 1, updated: 1, duplicates: 1, ..Default::default() }
+        );
+        assert_eq!(w.notes.iter().map(|n| (n.value, n.spent)).collect::<Vec<_>>(), [(10, false), (20, true), (30, false)]);
+        assert_eq!(merge(&mut w, &backup, false).unwrap().duplicates, 3);
+    }
+
+    #[test]
+    fn other_keys_only_go_into_an_empty_wallet_on_restore() {
+        let mut rng = crate::wallet::crypto_rng();
+        let (old, fresh) = (SecretKey::random(&mut rng), SecretKey::random(&mut rng));
+        let backup = contents(&wallet(&old, vec![note(10, &old)]));
+
+        let mut busy = wallet(&fresh, vec![note(5, &fresh)]);
+        assert!(merge(&mut busy, &backup, true).is_err());
+        assert_eq!(busy.notes.len(), 1);
+
+        let mut empty = wallet(&fresh, vec![]);
+        let err = merge(&mut empty, &backup, false).err().unwrap();
+        assert!(err.to_string().contains("restore_notes"));
+        assert_eq!(empty.secret_key, wallet(&fresh, vec![]).secret_key);
+
+        let summary = merge(&mut empty, &backup, true).unwrap();
+        assert!(summary.adopted_keys && summary.added == 1);
+        assert_eq!(empty.secret_key, backup.secret_key);
+        assert_eq!(empty.indexer_url, "http://localhost:3000");
+    }
+
+    #[test]
+    fn export_then_import_through_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.json");
+        let sk = SecretKey::random(&mut crate::wallet::crypto_rng());
+        let w = wallet(&sk, vec![note(10, &sk)]);
+        export_notes(&w, &path, "pw").unwrap();
+
+        let mut other = wallet(&sk, vec![]);
+        assert_eq!(import_notes(&mut other, &path, "pw").unwrap().added, 1);
+        assert!(import_notes(&mut other, dir.path().join("missing.json"), "pw").is_err());
+        assert!(export_notes(&w, &path, "").is_err());
+    }
+}
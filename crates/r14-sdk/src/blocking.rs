@@ -0,0 +1,257 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Synchronous wrapper around [`R14Client`] (requires the `blocking` feature).
+//!
+//! [`R14BlockingClient`] owns a single-threaded tokio runtime and blocks on
+//! each call, for scripts and applications that don't run one themselves.
+//! Like `reqwest::blocking`, it must not be created, used, or dropped
+//! inside an async context; the runtime panics if it is.
+//!
+//! ```rust,no_run
+//! use r14_sdk::blocking::R14BlockingClient;
+//! use r14_sdk::client::R14Contracts;
+//!
+//! # fn example() -> r14_sdk::error::R14Result<()> {
+//! let client = R14BlockingClient::new(
+//!     "http://localhost:3000",
+//!     R14Contracts { core: "C_CORE...".into(), transfer: "C_XFER...".into() },
+//!     "S_SECRET...",
+//!     "testnet",
+//! )?;
+//! let mut w = r14_sdk::wallet::load_wallet()?;
+//! let balance = client.balance(&mut w.notes)?;
+//! println!("{}", balance.total);
+//! # Ok(())
+//! # }
+//! ```
+
+use ark_bls12_381::Fr;
+use tokio::runtime::Runtime;
+
+use crate::client::{
+    BalanceResult, DepositResult, PrebuiltProof, R14Client, R14Contracts, RescanReport, ResumedTransfer,
+    RootConsistency, TransferResult,
+};
+#[cfg(feature = "prove")]
+use crate::client::{InitResult, RotationResult};
+use crate::error::{R14Error, R14Result};
+use crate::offline::{SignedTransfer, TransferBundle};
+use crate::wallet::{NoteEntry, WalletData};
+use crate::CoinSelection;
+
+pub struct R14BlockingClient {
+    inner: R14Client,
+    rt: Runtime,
+}
+
+impl R14BlockingClient {
+    pub fn new(indexer_url: &str, contracts: R14Contracts, stellar_secret: &str, network: &str) -> R14Result<Self> {
+        Self::from_client(R14Client::new(indexer_url, contracts, stellar_secret, network)?)
+    }
+
+    pub fn from_wallet(wallet: &WalletData) -> R14Result<Self> {
+        Self::from_client(R14Client::from_wallet(wallet)?)
+    }
+
+    /// Wrap a configured client, e.g. from [`R14Client::builder`]
+    pub fn from_client(inner: R14Client) -> R14Result<Self> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| R14Error::Other(anyhow::anyhow!("failed to start tokio runtime: {e}")))?;
+        Ok(Self { inner, rt })
+    }
+
+    /// The async client, for calls not wrapped here
+    pub fn inner(&self) -> &R14Client {
+        &self.inner
+    }
+
+    pub fn deposit(&self, value: u64, app_tag: u32, owner: &Fr) -> R14Result<DepositResult> {
+        self.rt.block_on(self.inner.deposit(value, app_tag, owner))
+    }
+
+    pub fn deposit_many(&self, notes: &[(u64, u32)], owner: &Fr) -> R14Result<Vec<DepositResult>> {
+        self.rt.block_on(self.inner.deposit_many(notes, owner))
+    }
+
+    pub fn deposit_split(
+        &self,
+        value: u64,
+        app_tag: u32,
+        owner: &Fr,
+        denominations: &crate::denomination::DenominationConfig,
+    ) -> R14Result<Vec<DepositResult>> {
+        self.rt.block_on(self.inner.deposit_split(value, app_tag, owner, denominations))
+    }
+
+    pub fn sync_notes(&self, notes: &mut [NoteEntry]) -> R14Result<()> {
+        self.rt.block_on(self.inner.sync_notes(notes))
+    }
+
+    pub fn sync_spent(&self, notes: &mut [NoteEntry], sk: &Fr) -> R14Result<usize> {
+        self.rt.block_on(self.inner.sync_spent(notes, sk))
+    }
+
+    pub fn rescan(&self, notes: &mut Vec<NoteEntry>, sk: &Fr, from_ledger: u64) -> R14Result<RescanReport> {
+        self.rt.block_on(self.inner.rescan(notes, sk, from_ledger))
+    }
+
+    pub fn resolve_pending(&self, wallet: &mut WalletData) -> R14Result<usize> {
+        self.rt.block_on(self.inner.resolve_pending(wallet))
+    }
+
+    pub fn save_pending_proof(
+        &self,
+        wallet: &mut WalletData,
+        consumed_idx: usize,
+        proof: &PrebuiltProof,
+        recipient_note: NoteEntry,
+        change_note: NoteEntry,
+    ) -> R14Result<()> {
+        self.inner
+            .save_pending_proof(wallet, consumed_idx, proof, recipient_note, change_note)
+    }
+
+    pub fn resume_pending(&self, wallet: &mut WalletData) -> R14Result<Vec<ResumedTransfer>> {
+        self.rt.block_on(self.inner.resume_pending(wallet))
+    }
+
+    pub fn balance(&self, notes: &mut [NoteEntry]) -> R14Result<BalanceResult> {
+        self.rt.block_on(self.inner.balance(notes))
+    }
+
+    pub fn is_spent(&self, nullifier: &str) -> R14Result<bool> {
+        self.rt.block_on(self.inner.is_spent(nullifier))
+    }
+
+    pub fn is_known_root(&self, root: &str) -> R14Result<bool> {
+        self.rt.block_on(self.inner.is_known_root(root))
+    }
+
+    pub fn latest_root(&self) -> R14Result<String> {
+        self.rt.block_on(self.inner.latest_root())
+    }
+
+    pub fn verify_root_consistency(&self) -> R14Result<RootConsistency> {
+        self.rt.block_on(self.inner.verify_root_consistency())
+    }
+
+    pub fn transfer_circuit_id(&self) -> R14Result<String> {
+        self.rt.block_on(self.inner.transfer_circuit_id())
+    }
+
+    pub fn transfer_with_proof(
+        &self,
+        proof: &PrebuiltProof,
+        recipient_note: NoteEntry,
+        change_note: NoteEntry,
+        consumed_idx: usize,
+    ) -> R14Result<TransferResult> {
+        self.rt
+            .block_on(self.inner.transfer_with_proof(proof, recipient_note, change_note, consumed_idx))
+    }
+
+    pub fn submit_transfer(&self, proof: &PrebuiltProof) -> R14Result<String> {
+        self.rt.block_on(self.inner.submit_transfer(proof))
+    }
+
+    pub fn prepare_transfer(
+        &self,
+        notes: &[NoteEntry],
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferBundle> {
+        self.rt.block_on(self.inner.prepare_transfer(notes, owner, recipient, value, selection))
+    }
+
+    pub fn submit_signed(&self, wallet: &mut WalletData, signed: &SignedTransfer) -> R14Result<TransferResult> {
+        self.rt.block_on(self.inner.submit_signed(wallet, signed))
+    }
+
+    pub fn submit_via_relayer(&self, relayer_url: &str, proof: &PrebuiltProof) -> R14Result<String> {
+        self.rt.block_on(self.inner.submit_via_relayer(relayer_url, proof))
+    }
+
+    #[cfg(feature = "prove")]
+    pub fn transfer(
+        &self,
+        wallet: &mut WalletData,
+        sk: &Fr,
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferResult> {
+        self.rt
+            .block_on(self.inner.transfer(wallet, sk, owner, recipient, value, selection))
+    }
+
+    #[cfg(feature = "prove")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_via_relayer(
+        &self,
+        relayer_url: &str,
+        wallet: &mut WalletData,
+        sk: &Fr,
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferResult> {
+        self.rt.block_on(
+            self.inner
+                .transfer_via_relayer(relayer_url, wallet, sk, owner, recipient, value, selection),
+        )
+    }
+
+    #[cfg(feature = "prove")]
+    pub fn rotate_key(&self, wallet: &mut WalletData, new_sk: &Fr) -> R14Result<RotationResult> {
+        self.rt.block_on(self.inner.rotate_key(wallet, new_sk))
+    }
+
+    #[cfg(feature = "prove")]
+    pub fn transfer_with_session(
+        &self,
+        notes: &mut [NoteEntry],
+        session: &crate::session::SessionKey,
+        recipient: &Fr,
+        value: u64,
+        valid_until: u64,
+    ) -> R14Result<TransferResult> {
+        self.rt
+            .block_on(self.inner.transfer_with_session(notes, session, recipient, value, valid_until))
+    }
+
+    #[cfg(feature = "prove")]
+    pub fn init_delegated(&self) -> R14Result<InitResult> {
+        self.rt.block_on(self.inner.init_delegated())
+    }
+
+    #[cfg(feature = "prove")]
+    pub fn init_contracts(&self) -> R14Result<InitResult> {
+        self.rt.block_on(self.inner.init_contracts())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_run_without_an_outer_runtime() {
+        let contracts = R14Contracts { core: "PLACEHOLDER".into(), transfer: "PLACEHOLDER".into() };
+        let client = R14BlockingClient::new("http://127.0.0.1:9", contracts, "S", "testnet").unwrap();
+        assert!(matches!(client.is_spent("00"), Err(R14Error::Config(_))));
+        // nothing to sync: no request is made
+        let balance = client.balance(&mut []).unwrap();
+        assert_eq!(balance.total, 0);
+        // port 9 refuses, surfacing as an unreachable indexer rather than a panic
+        let note = crate::Note::new(5, 1, Fr::from(1u64), &mut ark_std::test_rng());
+        let mut note = NoteEntry::new(&note, &crate::commitment(&note));
+        assert!(matches!(client.sync_notes(std::slice::from_mut(&mut note)), Err(R14Error::IndexerUnreachable(_))));
+    }
+}
@@ -19,24 +19,200 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! [`R14Client::builder`] sets timeouts, retry, and the HTTP client:
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use r14_sdk::client::{R14Client, R14Contracts};
+//! use r14_sdk::retry::RetryPolicy;
+//!
+//! # fn example() -> r14_sdk::error::R14Result<()> {
+//! let client = R14Client::builder(
+//!     "http://localhost:3000",
+//!     R14Contracts { core: "C_CORE...".into(), transfer: "C_XFER...".into() },
+//!     "S_SECRET...",
+//! )
+//! .network("testnet")
+//! .indexer_timeout(Duration::from_secs(3))
+//! .retry(RetryPolicy::default())
+//! .user_agent("my-wallet/1.0")
+//! .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
 use ark_bls12_381::Fr;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 use crate::error::{R14Error, R14Result};
-use crate::wallet::NoteEntry;
-use crate::{commitment, Note};
+use crate::history::{HistoryEntry, HistoryPage, HistoryQuery};
+use crate::indexer::IndexerClient;
+use crate::leaf_cache::LeafCache;
+use crate::network::Network;
+use crate::policy::{PolicyState, SpendingPolicy};
+use crate::relayer::{RelayRequest, RelayerClient};
+use crate::retry::{retry, RetryPolicy};
+use crate::offline::{SignedTransfer, TransferBundle};
+use crate::selection::{select_notes, CoinSelection, TRANSFER_INPUTS};
+use crate::soroban::{ContractInvoker, StellarCli};
+use crate::wallet::{NoteEntry, NoteMeta, NoteSource};
+use crate::{commitment, Commitment, Note, Nullifier};
 
 // ---------------------------------------------------------------------------
 // Structs
 // ---------------------------------------------------------------------------
 
 pub struct R14Client {
+    indexer: IndexerClient,
+    contracts: R14Contracts,
+    stellar_secret: String,
+    network: Network,
+    invoker: Arc<dyn ContractInvoker>,
+    leaf_cache: Option<tokio::sync::Mutex<LeafCache>>,
+    rpc_timeout: Duration,
+    retry: RetryPolicy,
+    policy: Option<std::sync::Mutex<PolicyState>>,
+    confirmed_only: bool,
+}
+
+/// Contract calls give up after this long by default
+pub const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Configures an [`R14Client`]; start with [`R14Client::builder`].
+///
+/// Unset options keep the defaults of [`R14Client::new`]: testnet, the
+/// indexer's [`crate::indexer::DEFAULT_TIMEOUT`], [`DEFAULT_RPC_TIMEOUT`],
+/// and no retry.
+pub struct R14ClientBuilder {
     indexer_url: String,
     contracts: R14Contracts,
     stellar_secret: String,
-    network: String,
-    http: reqwest::Client,
+    network: Network,
+    invoker: Option<Arc<dyn ContractInvoker>>,
+    leaf_cache: Option<LeafCache>,
+    indexer_timeout: Duration,
+    rpc_timeout: Duration,
+    retry: RetryPolicy,
+    http: Option<reqwest::Client>,
+    user_agent: Option<String>,
+    policy: Option<PolicyState>,
+    confirmed_only: bool,
+}
+
+impl Drop for R14ClientBuilder {
+    fn drop(&mut self) {
+        self.stellar_secret.zeroize();
+    }
+}
+
+impl R14ClientBuilder {
+    /// A preset or stellar CLI network name, or a [`Network`] with its
+    /// own RPC and passphrase
+    pub fn network(mut self, network: impl Into<Network>) -> Self {
+        self.network = network.into();
+        self
+    }
+
+    /// Run contract calls through `invoker` instead of the `stellar` CLI
+    pub fn invoker(mut self, invoker: Arc<dyn ContractInvoker>) -> Self {
+        self.invoker = Some(invoker);
+        self
+    }
+
+    /// Compute new roots from `cache`, fetching only the leaves it lacks,
+    /// instead of downloading every leaf per deposit and transfer. The
+    /// cache is saved after each sync.
+    pub fn leaf_cache(mut self, cache: LeafCache) -> Self {
+        self.leaf_cache = Some(cache);
+        self
+    }
+
+    /// Per-request timeout for indexer calls
+    pub fn indexer_timeout(mut self, timeout: Duration) -> Self {
+        self.indexer_timeout = timeout;
+        self
+    }
+
+    /// Per-call timeout for contract invocations
+    pub fn rpc_timeout(mut self, timeout: Duration) -> Self {
+        self.rpc_timeout = timeout;
+        self
+    }
+
+    /// Retry indexer requests and read-only contract calls; submissions
+    /// are never retried (see [`crate::retry`])
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Send indexer requests through `http` instead of a fresh client
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// `User-Agent` for indexer requests (default `r14-sdk/<version>`)
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Enforce `policy` on transfers, counting `spent_today` as already
+    /// sent today (see [`crate::policy::spent_on`])
+    pub fn spending_policy(mut self, policy: SpendingPolicy, spent_today: u64) -> Self {
+        self.policy = Some(PolicyState::new(policy, spent_today));
+        self
+    }
+
+    /// Spend only notes the indexer reports as confirmed, proving against
+    /// the root of its confirmed leaves rather than its latest root, so a
+    /// reorg of the newest ledgers can't strand the proof. Needs an indexer
+    /// with a confirmation depth (`R14_CONFIRMATIONS`).
+    pub fn confirmed_only(mut self) -> Self {
+        self.confirmed_only = true;
+        self
+    }
+
+    pub fn build(mut self) -> R14Result<R14Client> {
+        if self.retry.max_attempts == 0 {
+            return Err(R14Error::Config("retry policy needs at least one attempt".into()));
+        }
+        let http = self.http.take().unwrap_or_default();
+        let mut indexer = IndexerClient::with_http_client(&self.indexer_url, http)
+            .timeout(self.indexer_timeout)
+            .retry(self.retry);
+        if let Some(user_agent) = &self.user_agent {
+            indexer = indexer.user_agent(user_agent);
+        }
+        Ok(R14Client {
+            indexer,
+            contracts: R14Contracts {
+                core: std::mem::take(&mut self.contracts.core),
+                transfer: std::mem::take(&mut self.contracts.transfer),
+            },
+            stellar_secret: std::mem::take(&mut self.stellar_secret),
+            network: std::mem::take(&mut self.network),
+            invoker: self.invoker.take().unwrap_or_else(|| Arc::new(StellarCli)),
+            leaf_cache: self.leaf_cache.take().map(tokio::sync::Mutex::new),
+            rpc_timeout: self.rpc_timeout,
+            retry: self.retry,
+            policy: self.policy.take().map(std::sync::Mutex::new),
+            confirmed_only: self.confirmed_only,
+        })
+    }
+}
+
+impl Drop for R14Client {
+    fn drop(&mut self) {
+        self.stellar_secret.zeroize();
+    }
 }
 
 pub struct R14Contracts {
@@ -45,7 +221,7 @@ pub struct R14Contracts {
 }
 
 pub struct DepositResult {
-    pub commitment: String,
+    pub commitment: Commitment,
     pub value: u64,
     pub app_tag: u32,
     pub tx_result: String,
@@ -54,8 +230,8 @@ pub struct DepositResult {
 
 pub struct TransferResult {
     pub nullifier: String,
-    pub out_commitment_0: String,
-    pub out_commitment_1: String,
+    pub out_commitment_0: Commitment,
+    pub out_commitment_1: Commitment,
     pub tx_result: String,
     pub recipient_note: NoteEntry,
     pub change_note: NoteEntry,
@@ -70,8 +246,9 @@ pub struct BalanceResult {
 pub struct NoteStatus {
     pub value: u64,
     pub app_tag: u32,
-    pub commitment: String,
+    pub commitment: Commitment,
     pub on_chain: bool,
+    pub meta: NoteMeta,
 }
 
 pub struct InitResult {
@@ -79,30 +256,101 @@ pub struct InitResult {
     pub tx_result: String,
 }
 
+/// A transfer proof with its public inputs, ready to submit. Serializes
+/// as plain JSON, so it can be kept and submitted later.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PrebuiltProof {
     pub proof_json: String,
     pub old_root: String,
     pub nullifier: String,
     pub cm_0: String,
     pub cm_1: String,
+    /// Ledger the proof was built for (its last public input)
+    pub current_ledger: u64,
 }
 
-// ---------------------------------------------------------------------------
-// Indexer response types
-// ---------------------------------------------------------------------------
+/// How the indexer's root relates to the transfer contract's
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RootStatus {
+    /// The indexer's root is the contract's latest root
+    InSync,
+    /// The indexer's root is an older one still in the contract's root
+    /// history; proofs against it are accepted until it ages out
+    Behind,
+    /// The contract doesn't know the indexer's root: the indexer is on
+    /// another pool, or so far behind its root has aged out. Proofs
+    /// against it will be rejected.
+    Diverged,
+}
+
+/// [`R14Client::verify_root_consistency`]'s report
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RootConsistency {
+    /// Raw 64-char hex
+    pub indexer_root: String,
+    /// The contract's latest root, raw 64-char hex
+    pub contract_root: String,
+    pub status: RootStatus,
+}
+
+impl RootConsistency {
+    /// `StaleRoot` if proofs against the indexer's root would be rejected
+    pub fn ensure_usable(&self) -> R14Result<()> {
+        if self.status == RootStatus::Diverged {
+            return Err(R14Error::StaleRoot(format!(
+                "indexer root {} is not in the contract's root history (latest {})",
+                self.indexer_root, self.contract_root
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// What [`R14Client::resume_pending`] did with one pending transfer
+#[derive(Debug)]
+pub enum ResumeOutcome {
+    /// The nullifier was already on-chain; outputs added to the wallet
+    Landed,
+    /// The saved proof was submitted now; outputs added to the wallet
+    Submitted(String),
+    /// The proof's root has left the contract's history, so it can never
+    /// land; the consumed note is spendable again
+    Expired,
+    /// Submission failed and the transfer stays pending, e.g. while the
+    /// network is still down
+    Failed(R14Error),
+}
 
-#[derive(Deserialize)]
-struct LeafResponse {
-    index: u64,
-    #[allow(dead_code)]
-    block_height: u64,
+pub struct ResumedTransfer {
+    /// As in the pending record (`0x` hex)
+    pub nullifier: String,
+    pub outcome: ResumeOutcome,
 }
 
-#[derive(Deserialize)]
-#[cfg_attr(not(feature = "prove"), allow(dead_code))]
-struct ProofResponse {
-    siblings: Vec<String>,
-    indices: Vec<bool>,
+/// What [`R14Client::rescan`] found
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RescanReport {
+    /// Owned notes checked against the chain
+    pub checked: usize,
+    /// Notes whose leaf index was filled in
+    pub located: usize,
+    /// Notes newly marked spent because their nullifier is on-chain
+    pub spent: usize,
+    /// Owned, unspent notes whose commitment isn't on-chain
+    pub missing: usize,
+    /// Repeated entries of the same commitment that were dropped
+    pub duplicates: usize,
+}
+
+/// What [`R14Client::rotate_key`] did
+pub struct RotationResult {
+    /// `0x` hex, as in the wallet's `owner_hash` before and after
+    pub old_owner: String,
+    pub new_owner: String,
+    /// One self-transfer per swept note, in wallet order
+    pub transfers: Vec<TransferResult>,
+    /// Total value moved to the new key
+    pub swept: u64,
 }
 
 // ---------------------------------------------------------------------------
@@ -116,78 +364,94 @@ impl R14Client {
         stellar_secret: &str,
         network: &str,
     ) -> R14Result<Self> {
-        Ok(Self {
+        Self::builder(indexer_url, contracts, stellar_secret)
+            .network(network)
+            .build()
+    }
+
+    pub fn builder(indexer_url: &str, contracts: R14Contracts, stellar_secret: &str) -> R14ClientBuilder {
+        R14ClientBuilder {
             indexer_url: indexer_url.to_string(),
             contracts,
             stellar_secret: stellar_secret.to_string(),
-            network: network.to_string(),
-            http: reqwest::Client::new(),
-        })
+            network: Network::default(),
+            invoker: None,
+            leaf_cache: None,
+            indexer_timeout: crate::indexer::DEFAULT_TIMEOUT,
+            rpc_timeout: DEFAULT_RPC_TIMEOUT,
+            retry: RetryPolicy::none(),
+            http: None,
+            user_agent: None,
+            policy: None,
+            confirmed_only: false,
+        }
     }
 
+    /// Client for `wallet`'s indexer, contracts and network, enforcing its
+    /// spending policy if it has one
     pub fn from_wallet(wallet: &crate::wallet::WalletData) -> R14Result<Self> {
-        Ok(Self {
-            indexer_url: wallet.indexer_url.clone(),
-            contracts: R14Contracts {
-                core: wallet.core_contract_id.clone(),
-                transfer: wallet.transfer_contract_id.clone(),
-            },
-            stellar_secret: wallet.stellar_secret.clone(),
-            network: "testnet".to_string(),
-            http: reqwest::Client::new(),
-        })
+        let contracts = R14Contracts {
+            core: wallet.core_contract_id.clone(),
+            transfer: wallet.transfer_contract_id.clone(),
+        };
+        let mut builder = Self::builder(&wallet.indexer_url, contracts, &wallet.stellar_secret)
+            .network(Network::from_wallet(wallet));
+        if let Some(policy) = &wallet.policy {
+            let owner = crate::wallet::hex_to_fr(&wallet.owner_hash).map_err(R14Error::Other)?;
+            let spent = crate::policy::spent_on(&wallet.history, &owner, crate::policy::today());
+            builder = builder.spending_policy(policy.clone(), spent);
+        }
+        builder.build()
     }
 
-    // -----------------------------------------------------------------------
-    // Internal helpers
-    // -----------------------------------------------------------------------
+    /// Build a client from an `r14.toml` descriptor, selecting its hash
+    /// family for the process
+    pub fn from_deployment(
+        deployment: &crate::deployment::Deployment,
+        stellar_secret: &str,
+    ) -> R14Result<Self> {
+        deployment.select_hash_family().map_err(|e| R14Error::Config(e.to_string()))?;
+        let contracts = R14Contracts {
+            core: deployment.contracts.core.clone(),
+            transfer: deployment.contracts.transfer.clone(),
+        };
+        Self::builder(&deployment.indexer_url, contracts, stellar_secret)
+            .network(deployment.network_config())
+            .build()
+    }
 
-    fn fr_to_raw_hex(fr: &Fr) -> String {
-        crate::wallet::fr_to_raw_hex(fr)
+    /// Replace the indexer client, e.g. to change its request timeout
+    pub fn with_indexer(mut self, indexer: IndexerClient) -> Self {
+        self.indexer = indexer;
+        self
     }
 
-    async fn fetch_leaf_index(&self, cm_hex: &str) -> R14Result<Option<u64>> {
-        let cm = cm_hex.strip_prefix("0x").unwrap_or(cm_hex);
-        let url = format!("{}/v1/leaf/{}", self.indexer_url, cm);
-        let resp = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| R14Error::Indexer(e.to_string()))?;
+    pub fn indexer(&self) -> &IndexerClient {
+        &self.indexer
+    }
 
-        if !resp.status().is_success() {
-            return Ok(None);
-        }
-        match resp.json::<LeafResponse>().await {
-            Ok(leaf) => Ok(Some(leaf.index)),
-            Err(_) => Ok(None),
-        }
+    pub fn network(&self) -> &Network {
+        &self.network
     }
 
-    #[cfg_attr(not(feature = "prove"), allow(dead_code))]
-    async fn fetch_merkle_proof(
-        &self,
-        leaf_index: u64,
-    ) -> R14Result<(Vec<Fr>, Vec<bool>)> {
-        let url = format!("{}/v1/proof/{}", self.indexer_url, leaf_index);
-        let resp: ProofResponse = self
-            .http
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| R14Error::Indexer(e.to_string()))?
-            .json()
-            .await
-            .map_err(|e| R14Error::Indexer(format!("parse proof: {e}")))?;
+    // -----------------------------------------------------------------------
+    // Internal helpers
+    // -----------------------------------------------------------------------
 
-        let siblings: Vec<Fr> = resp
-            .siblings
-            .iter()
-            .map(|s| crate::wallet::hex_to_fr(s).map_err(R14Error::Other))
-            .collect::<R14Result<_>>()?;
+    fn fr_to_raw_hex(fr: &Fr) -> String {
+        crate::wallet::fr_to_raw_hex(fr)
+    }
 
-        Ok((siblings, resp.indices))
+    /// Root after appending `cms`, through the leaf cache if one is set
+    async fn new_root(&self, cms: &[Commitment]) -> R14Result<String> {
+        let Some(cache) = &self.leaf_cache else {
+            return crate::merkle::compute_new_root(&self.indexer, cms).await;
+        };
+        let mut cache = cache.lock().await;
+        let root = cache.new_root(&self.indexer, cms).await?;
+        // an unsaved cache only costs a refetch in the next process
+        let _ = cache.save();
+        Ok(root)
     }
 
     async fn invoke(
@@ -196,15 +460,30 @@ impl R14Client {
         function: &str,
         args: &[(&str, &str)],
     ) -> R14Result<String> {
-        crate::soroban::invoke_contract(
+        let call = self.invoker.invoke(
             contract_id,
             &self.network,
             &self.stellar_secret,
             function,
             args,
-        )
-        .await
-        .map_err(|e| R14Error::Soroban(e.to_string()))
+        );
+        match tokio::time::timeout(self.rpc_timeout, call).await {
+            Ok(result) => result.map_err(|e| R14Error::Soroban(e.to_string())),
+            Err(_) => Err(R14Error::Soroban(format!(
+                "{function}: timed out after {:?}",
+                self.rpc_timeout
+            ))),
+        }
+    }
+
+    /// [`Self::invoke`] for read-only functions, retried per the policy
+    async fn query(
+        &self,
+        contract_id: &str,
+        function: &str,
+        args: &[(&str, &str)],
+    ) -> R14Result<String> {
+        retry(&self.retry, || self.invoke(contract_id, function, args)).await
     }
 
     #[cfg_attr(not(feature = "prove"), allow(dead_code))]
@@ -217,6 +496,96 @@ impl R14Client {
         Ok(())
     }
 
+    /// Fetch the Merkle path for leaf `index` and check it against the
+    /// indexer's current root, so a stale or inconsistent indexer fails
+    /// here instead of producing a proof that reverts on-chain.
+    async fn verified_merkle_path(&self, leaf: Commitment, index: crate::LeafIndex) -> R14Result<crate::MerklePath> {
+        // the path and the root it leads to come from one read
+        if let Some(proof) = self.indexer.proof_by_commitment(&leaf, None).await? {
+            if proof.index == index && crate::merkle::verify_proof(leaf, &proof.path, proof.root.0) {
+                return Ok(proof.path);
+            }
+            return Err(R14Error::StaleRoot(format!(
+                "indexer merkle path for leaf {index} does not match its root"
+            )));
+        }
+        // an indexer without the endpoint: the tree can grow between the
+        // two reads, so retry a few times
+        for _ in 0..MERKLE_READ_ATTEMPTS {
+            let root = self.indexer.root().await?;
+            let path = self.indexer.merkle_proof(index).await?;
+            if crate::merkle::verify_proof(leaf, &path, root) {
+                return Ok(path);
+            }
+        }
+        Err(R14Error::StaleRoot(format!(
+            "indexer merkle path for leaf {index} does not match its root"
+        )))
+    }
+
+    /// [`Self::verified_merkle_path`] for several leaves in one request,
+    /// all checked against the root the indexer sent with them
+    #[cfg(feature = "prove")]
+    async fn verified_merkle_paths(
+        &self,
+        leaves: &[(Commitment, crate::LeafIndex)],
+    ) -> R14Result<Vec<crate::MerklePath>> {
+        let indices: Vec<_> = leaves.iter().map(|&(_, index)| index).collect();
+        let Some(batch) = self.indexer.merkle_proofs(&indices).await? else {
+            // an indexer without the endpoint
+            let mut paths = Vec::with_capacity(leaves.len());
+            for &(leaf, index) in leaves {
+                paths.push(self.verified_merkle_path(leaf, index).await?);
+            }
+            return Ok(paths);
+        };
+        if batch.proofs.len() != leaves.len() {
+            return Err(R14Error::Indexer(format!(
+                "/v1/proofs: asked for {} paths, got {}",
+                leaves.len(),
+                batch.proofs.len()
+            )));
+        }
+        leaves
+            .iter()
+            .zip(batch.proofs)
+            .map(|(&(leaf, index), proof)| {
+                if proof.index == index && crate::merkle::verify_proof(leaf, &proof.path, batch.root.0) {
+                    Ok(proof.path)
+                } else {
+                    Err(R14Error::StaleRoot(format!("indexer merkle path for leaf {index} does not match its root")))
+                }
+            })
+            .collect()
+    }
+
+    /// Reject `old_root` unless the transfer contract still accepts it
+    async fn require_known_root(&self, old_root: &str) -> R14Result<()> {
+        if !self.is_known_root(old_root).await? {
+            return Err(R14Error::StaleRoot(format!(
+                "root {} is not in the contract's root history — is the indexer behind?",
+                crate::wallet::strip_0x(old_root)
+            )));
+        }
+        Ok(())
+    }
+
+    /// `PolicyViolation` if the spending policy forbids sending `value`
+    /// from `owner` to `recipient`
+    fn check_policy(&self, owner: &Fr, recipient: &Fr, value: u64) -> R14Result<()> {
+        match &self.policy {
+            Some(policy) => policy.lock().unwrap_or_else(|e| e.into_inner()).check(owner, recipient, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Add a submitted transfer to the policy's daily total
+    fn record_spend(&self, owner: &Fr, recipient: &Fr, value: u64) {
+        if let Some(policy) = &self.policy {
+            policy.lock().unwrap_or_else(|e| e.into_inner()).record(owner, recipient, value);
+        }
+    }
+
     fn require_transfer_contract(&self) -> R14Result<()> {
         if self.contracts.transfer == "PLACEHOLDER" {
             return Err(R14Error::Config(
@@ -243,10 +612,8 @@ impl R14Client {
         let note = Note::new(value, app_tag, *owner, &mut rng);
         let cm = commitment(&note);
 
-        let cm_hex = Self::fr_to_raw_hex(&cm);
-        let new_root = crate::merkle::compute_new_root(&self.indexer_url, &[cm])
-            .await
-            .map_err(R14Error::Other)?;
+        let cm_hex = Self::fr_to_raw_hex(&cm.0);
+        let new_root = self.new_root(&[cm]).await?;
 
         let tx_result = self
             .invoke(
@@ -256,18 +623,10 @@ impl R14Client {
             )
             .await?;
 
-        let note_entry = NoteEntry {
-            value: note.value,
-            app_tag: note.app_tag,
-            owner: crate::wallet::fr_to_hex(&note.owner),
-            nonce: crate::wallet::fr_to_hex(&note.nonce),
-            commitment: crate::wallet::fr_to_hex(&cm),
-            index: None,
-            spent: false,
-        };
+        let note_entry = NoteEntry::new(&note, &cm).with_source(NoteSource::Deposit);
 
         Ok(DepositResult {
-            commitment: crate::wallet::fr_to_hex(&cm),
+            commitment: cm,
             value,
             app_tag,
             tx_result,
@@ -275,39 +634,360 @@ impl R14Client {
         })
     }
 
+    /// Create one note per `(value, app_tag)` for `owner` and submit them
+    /// in a single `deposit_batch` call, under one new root.
+    ///
+    /// Cheaper than a [`Self::deposit`] per note when funding a wallet
+    /// with several denominations. The results come back in input order
+    /// and share the one `tx_result`.
+    pub async fn deposit_many(&self, notes: &[(u64, u32)], owner: &Fr) -> R14Result<Vec<DepositResult>> {
+        self.require_transfer_contract()?;
+        if notes.is_empty() {
+            return Err(R14Error::Config("deposit_many: no notes to deposit".to_string()));
+        }
+
+        let mut rng = crate::wallet::crypto_rng();
+        let notes: Vec<Note> = notes
+            .iter()
+            .map(|&(value, app_tag)| Note::new(value, app_tag, *owner, &mut rng))
+            .collect();
+        let cms: Vec<Commitment> = notes.iter().map(commitment).collect();
+
+        let cms_json = serde_json::to_string(&cms.iter().map(|cm| Self::fr_to_raw_hex(&cm.0)).collect::<Vec<_>>())
+            .map_err(|e| R14Error::Other(e.into()))?;
+        let new_root = self.new_root(&cms).await?;
+
+        let tx_result = self
+            .invoke(
+                &self.contracts.transfer,
+                "deposit_batch",
+                &[("cms", &cms_json), ("new_root", &new_root)],
+            )
+            .await?;
+
+        Ok(notes
+            .iter()
+            .zip(cms)
+            .map(|(note, cm)| DepositResult {
+                commitment: cm,
+                value: note.value,
+                app_tag: note.app_tag,
+                tx_result: tx_result.clone(),
+                note_entry: NoteEntry::new(note, &cm).with_source(NoteSource::Deposit),
+            })
+            .collect())
+    }
+
+    /// Deposit `value` as standard denominations for `app_tag`, split per
+    /// `denominations` and submitted through [`Self::deposit_many`].
+    pub async fn deposit_split(
+        &self,
+        value: u64,
+        app_tag: u32,
+        owner: &Fr,
+        denominations: &crate::denomination::DenominationConfig,
+    ) -> R14Result<Vec<DepositResult>> {
+        self.deposit_many(&denominations.split(value, app_tag)?, owner).await
+    }
+
     /// Sync note on-chain indices from the indexer.
     pub async fn sync_notes(&self, notes: &mut [NoteEntry]) -> R14Result<()> {
         for note in notes.iter_mut().filter(|n| !n.spent && n.index.is_none()) {
-            if let Some(idx) = self.fetch_leaf_index(&note.commitment).await? {
-                note.index = Some(idx);
+            if let Some(leaf) = self.indexer.leaf(&note.commitment).await? {
+                note.index = Some(leaf.index);
             }
         }
         Ok(())
     }
 
+    /// Mark notes spent whose nullifier the indexer has seen on-chain.
+    ///
+    /// Catches notes consumed from another device holding the same key.
+    /// Only notes owned by `sk` with a known leaf index are checked (the
+    /// nullifier binds the position; run [`Self::sync_notes`] first).
+    /// Returns how many notes were newly marked spent.
+    pub async fn sync_spent(&self, notes: &mut [NoteEntry], sk: &Fr) -> R14Result<usize> {
+        let mut marked = 0;
+        for (nf, i) in Self::watched_nullifiers(notes, sk)? {
+            if self.indexer.nullifier(&nf).await?.is_some() {
+                notes[i].spent = true;
+                marked += 1;
+            }
+        }
+        Ok(marked)
+    }
+
+    /// Nullifiers of the unspent notes in `notes` that [`Self::sync_spent`]
+    /// would check, each with its note's position in `notes`.
+    ///
+    /// A wallet with many notes derives these once and matches the
+    /// `nullifier` events of [`IndexerClient::subscribe`] against them, so
+    /// it learns of spends from another device as they land instead of
+    /// asking about each note.
+    pub fn watched_nullifiers(notes: &[NoteEntry], sk: &Fr) -> R14Result<HashMap<crate::Nullifier, usize>> {
+        let sk = crate::SecretKey(*sk);
+        let owner = crate::owner_hash(&sk).0;
+        let mut watched = HashMap::new();
+        for (i, note) in notes.iter().enumerate().filter(|(_, n)| !n.spent) {
+            if crate::wallet::hex_to_fr(&note.owner).ok() != Some(owner) {
+                continue;
+            }
+            let Some(index) = note.index else { continue };
+            let nonce = crate::wallet::hex_to_fr(&note.nonce).map_err(R14Error::Other)?;
+            watched.insert(crate::nullifier(&sk, &nonce, index), i);
+        }
+        Ok(watched)
+    }
+
+    /// Rebuild the notes `sk` owns in `notes` from the chain: drop repeated
+    /// commitments, fill in leaf indices, and mark spent every note whose
+    /// nullifier has landed.
+    ///
+    /// The indexer only publishes commitments, so a note is recognised
+    /// from its preimage: this repairs a note list that's out of date (a
+    /// restored backup, a wallet last used on another device) but can't
+    /// find notes it has never seen. Notes whose leaf landed before
+    /// `from_ledger` are left as recorded; pass 0 to check everything.
+    /// Notes are never marked unspent, since a pending transfer may hold
+    /// them.
+    pub async fn rescan(&self, notes: &mut Vec<NoteEntry>, sk: &Fr, from_ledger: u64) -> R14Result<RescanReport> {
+        let sk = crate::SecretKey(*sk);
+        let owner = crate::owner_hash(&sk).0;
+        let owned = |n: &NoteEntry| crate::wallet::hex_to_fr(&n.owner).ok() == Some(owner);
+
+        let mut report = RescanReport::default();
+        let mut seen = std::collections::HashSet::new();
+        let before = notes.len();
+        notes.retain(|n| !owned(n) || seen.insert(n.commitment));
+        report.duplicates = before - notes.len();
+
+        for note in notes.iter_mut().filter(|n| owned(n)) {
+            report.checked += 1;
+            let Some(leaf) = self.indexer.leaf(&note.commitment).await? else {
+                note.index = None;
+                if !note.spent {
+                    report.missing += 1;
+                }
+                continue;
+            };
+            if leaf.block_height < from_ledger {
+                continue;
+            }
+            if note.index != Some(leaf.index) {
+                note.index = Some(leaf.index);
+                report.located += 1;
+            }
+            if !note.spent {
+                let nonce = crate::wallet::hex_to_fr(&note.nonce).map_err(R14Error::Other)?;
+                if self.indexer.nullifier(&crate::nullifier(&sk, &nonce, leaf.index)).await?.is_some() {
+                    note.spent = true;
+                    report.spent += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Confirm pending transfers whose nullifier the indexer has seen,
+    /// recording each in the wallet's history.
+    ///
+    /// Transfers still unseen stay pending; roll them back with
+    /// [`crate::pending::rollback_transfer`] once you know they were
+    /// dropped. Returns how many were confirmed.
+    pub async fn resolve_pending(&self, wallet: &mut crate::wallet::WalletData) -> R14Result<usize> {
+        let mut landed = Vec::new();
+        for p in &wallet.pending {
+            let nf = Nullifier(crate::wallet::hex_to_fr(&p.nullifier).map_err(R14Error::Other)?);
+            if self.indexer.nullifier(&nf).await?.is_some() {
+                landed.push(p.nullifier.clone());
+            }
+        }
+        for nullifier in &landed {
+            let entry = wallet
+                .pending
+                .iter()
+                .find(|p| &p.nullifier == nullifier)
+                .map(HistoryEntry::from_pending);
+            crate::pending::confirm_transfer(wallet, nullifier).map_err(R14Error::Other)?;
+            wallet.history.extend(entry);
+        }
+        Ok(landed.len())
+    }
+
+    /// Park `proof` in the wallet instead of submitting it: the consumed
+    /// note is reserved and the proof kept in its pending record, so
+    /// [`Self::resume_pending`] can submit it later without proving again.
+    /// Save the wallet afterwards.
+    pub fn save_pending_proof(
+        &self,
+        wallet: &mut crate::wallet::WalletData,
+        consumed_idx: usize,
+        proof: &PrebuiltProof,
+        recipient_note: NoteEntry,
+        change_note: NoteEntry,
+    ) -> R14Result<()> {
+        crate::pending::begin_transfer_with_proof(wallet, consumed_idx, proof.try_into()?, vec![
+            recipient_note,
+            change_note,
+        ])
+        .map_err(R14Error::Other)
+    }
+
+    /// Settle every pending transfer the chain can decide: confirm those
+    /// whose nullifier has landed, and submit the ones saved with a proof
+    /// (see [`Self::save_pending_proof`]). A proof whose root has aged out
+    /// is rolled back; other failures leave it pending for the next call.
+    ///
+    /// Pending transfers without a proof that haven't landed are skipped.
+    pub async fn resume_pending(
+        &self,
+        wallet: &mut crate::wallet::WalletData,
+    ) -> R14Result<Vec<ResumedTransfer>> {
+        let parked: Vec<(String, Option<PrebuiltProof>)> = wallet
+            .pending
+            .iter()
+            .map(|p| (p.nullifier.clone(), p.proof.clone().map(PrebuiltProof::from)))
+            .collect();
+        let mut resumed = Vec::new();
+        for (nullifier, proof) in parked {
+            let nf = Nullifier(crate::wallet::hex_to_fr(&nullifier).map_err(R14Error::Other)?);
+            let outcome = if self.indexer.nullifier(&nf).await?.is_some() {
+                ResumeOutcome::Landed
+            } else if let Some(proof) = proof {
+                match self.submit_transfer(&proof).await {
+                    Ok(tx_result) => ResumeOutcome::Submitted(tx_result),
+                    Err(R14Error::StaleRoot(_)) => ResumeOutcome::Expired,
+                    Err(e) => ResumeOutcome::Failed(e),
+                }
+            } else {
+                continue;
+            };
+
+            match &outcome {
+                ResumeOutcome::Landed | ResumeOutcome::Submitted(_) => {
+                    let mut entry = wallet
+                        .pending
+                        .iter()
+                        .find(|p| p.nullifier == nullifier)
+                        .map(HistoryEntry::from_pending);
+                    if let (Some(entry), ResumeOutcome::Submitted(tx_result)) = (&mut entry, &outcome) {
+                        entry.tx_result = Some(tx_result.clone());
+                    }
+                    crate::pending::confirm_transfer(wallet, &nullifier).map_err(R14Error::Other)?;
+                    wallet.history.extend(entry);
+                }
+                ResumeOutcome::Expired => {
+                    crate::pending::rollback_transfer(wallet, &nullifier).map_err(R14Error::Other)?;
+                }
+                ResumeOutcome::Failed(_) => {}
+            }
+            resumed.push(ResumedTransfer { nullifier, outcome });
+        }
+        Ok(resumed)
+    }
+
     /// Sync notes and return balance summary.
     pub async fn balance(&self, notes: &mut [NoteEntry]) -> R14Result<BalanceResult> {
         self.sync_notes(notes).await?;
+        Ok(summarize_balance(notes))
+    }
 
-        let mut total = 0u64;
-        let mut statuses = Vec::new();
-        for note in notes.iter().filter(|n| !n.spent) {
-            total += note.value;
-            statuses.push(NoteStatus {
-                value: note.value,
-                app_tag: note.app_tag,
-                commitment: note.commitment.clone(),
-                on_chain: note.index.is_some(),
-            });
-        }
+    /// One page of `wallet`'s transaction history, newest first.
+    ///
+    /// Transfers made through a wallet ([`Self::transfer`] and friends)
+    /// are recorded for you. Record the rest yourself:
+    /// `wallet.history.push((&result).into())` for a [`DepositResult`] or
+    /// a [`TransferResult`] from [`Self::transfer_with_proof`].
+    pub fn history<'a>(&self, wallet: &'a crate::wallet::WalletData, query: &HistoryQuery) -> HistoryPage<'a> {
+        crate::history::query(&wallet.history, query)
+    }
 
-        Ok(BalanceResult {
-            total,
-            notes: statuses,
+    /// Check whether a nullifier (hex, optional `0x`) is already spent on-chain.
+    pub async fn is_spent(&self, nullifier: &str) -> R14Result<bool> {
+        self.require_transfer_contract()?;
+        let out = self
+            .query(
+                &self.contracts.transfer,
+                "is_spent",
+                &[("nullifier", &crate::wallet::strip_0x(nullifier))],
+            )
+            .await?;
+        Ok(out == "true")
+    }
+
+    /// Check whether a root (hex, optional `0x`) is in the contract's root history.
+    pub async fn is_known_root(&self, root: &str) -> R14Result<bool> {
+        self.require_transfer_contract()?;
+        let out = self
+            .query(
+                &self.contracts.transfer,
+                "is_known_root",
+                &[("root", &crate::wallet::strip_0x(root))],
+            )
+            .await?;
+        Ok(out == "true")
+    }
+
+    /// Most recent root committed on-chain (raw 64-char hex).
+    pub async fn latest_root(&self) -> R14Result<String> {
+        self.require_transfer_contract()?;
+        let out = self
+            .query(&self.contracts.transfer, "latest_root", &[])
+            .await?;
+        Ok(out.trim_matches('"').to_string())
+    }
+
+    /// Compare the indexer's root with the contract's latest root.
+    ///
+    /// Call before proving and refuse on [`RootStatus::Diverged`] (see
+    /// [`RootConsistency::ensure_usable`]): a proof against a root the
+    /// contract doesn't know fails on submission after the proving work.
+    pub async fn verify_root_consistency(&self) -> R14Result<RootConsistency> {
+        let indexer_root = Self::fr_to_raw_hex(&self.indexer.root().await?);
+        let contract_root = self.latest_root().await?;
+        let status = if indexer_root.eq_ignore_ascii_case(&contract_root) {
+            RootStatus::InSync
+        } else if self.is_known_root(&indexer_root).await? {
+            RootStatus::Behind
+        } else {
+            RootStatus::Diverged
+        };
+        Ok(RootConsistency {
+            indexer_root,
+            contract_root,
+            status,
         })
     }
 
+    /// circuit_id the transfer contract verifies against (raw 64-char hex).
+    pub async fn transfer_circuit_id(&self) -> R14Result<String> {
+        self.require_transfer_contract()?;
+        let out = self
+            .query(&self.contracts.transfer, "circuit_id", &[])
+            .await?;
+        Ok(out.trim_matches('"').to_string())
+    }
+
+    /// Fail with `CircuitMismatch` unless the transfer contract verifies
+    /// proofs made against `vk`, so a prover and verifier that have
+    /// diverged are caught before proving rather than at submission.
+    #[cfg(feature = "prove")]
+    pub async fn check_transfer_circuit(
+        &self,
+        vk: &ark_groth16::VerifyingKey<ark_bls12_381::Bls12_381>,
+    ) -> R14Result<()> {
+        let local = crate::serialize::circuit_id(&crate::prove::serialize_vk_for_soroban(vk, crate::MERKLE_DEPTH));
+        let deployed = self.transfer_circuit_id().await?;
+        if !crate::wallet::strip_0x(&deployed).eq_ignore_ascii_case(&local) {
+            return Err(R14Error::CircuitMismatch { local, deployed });
+        }
+        Ok(())
+    }
+
     /// Submit a pre-built proof on-chain (no ZK generation needed).
+    ///
+    /// Fails with `StaleRoot` before submitting if `old_root` has left the
+    /// contract's root history.
     pub async fn transfer_with_proof(
         &self,
         proof: &PrebuiltProof,
@@ -315,19 +995,26 @@ impl R14Client {
         change_note: NoteEntry,
         consumed_idx: usize,
     ) -> R14Result<TransferResult> {
+        let tx_result = self.submit_transfer(proof).await?;
+        Ok(transfer_result(proof, recipient_note, change_note, consumed_idx, tx_result))
+    }
+
+    /// Submit a pre-built proof without its notes, e.g. one read from a
+    /// [`crate::proof_file`] made elsewhere. Returns the transaction result.
+    ///
+    /// Fails with `StaleRoot` before submitting if `old_root` has left the
+    /// contract's root history.
+    pub async fn submit_transfer(&self, proof: &PrebuiltProof) -> R14Result<String> {
         self.require_transfer_contract()?;
 
-        let cm_0_fr =
-            crate::wallet::hex_to_fr(&recipient_note.commitment).map_err(R14Error::Other)?;
-        let cm_1_fr =
-            crate::wallet::hex_to_fr(&change_note.commitment).map_err(R14Error::Other)?;
+        self.require_known_root(&proof.old_root).await?;
 
-        let new_root =
-            crate::merkle::compute_new_root(&self.indexer_url, &[cm_0_fr, cm_1_fr])
-                .await
-                .map_err(R14Error::Other)?;
+        let cm_0 = Commitment(crate::wallet::hex_to_fr(&proof.cm_0).map_err(R14Error::Other)?);
+        let cm_1 = Commitment(crate::wallet::hex_to_fr(&proof.cm_1).map_err(R14Error::Other)?);
+        let new_root = self.new_root(&[cm_0, cm_1]).await?;
 
-        let tx_result = self
+        let current_ledger = proof.current_ledger.to_string();
+        self
             .invoke(
                 &self.contracts.transfer,
                 "transfer",
@@ -337,48 +1024,404 @@ impl R14Client {
                     ("nullifier", &proof.nullifier),
                     ("cm_0", &proof.cm_0),
                     ("cm_1", &proof.cm_1),
+                    ("current_ledger", &current_ledger),
                     ("new_root", &new_root),
                 ],
             )
-            .await?;
+            .await
+    }
 
-        Ok(TransferResult {
-            nullifier: format!("0x{}", &proof.nullifier),
-            out_commitment_0: recipient_note.commitment.clone(),
-            out_commitment_1: change_note.commitment.clone(),
-            tx_result,
-            recipient_note,
-            change_note,
-            consumed_note_index: consumed_idx,
-        })
+    /// [`Self::submit_transfer`] through an r14 relayer: the same root
+    /// check and new root, but the relayer signs and pays for the call.
+    /// Returns the relayer's `tx_result`.
+    pub async fn submit_via_relayer(&self, relayer_url: &str, proof: &PrebuiltProof) -> R14Result<String> {
+        self.require_transfer_contract()?;
+
+        self.require_known_root(&proof.old_root).await?;
+
+        let cm_0 = Commitment(crate::wallet::hex_to_fr(&proof.cm_0).map_err(R14Error::Other)?);
+        let cm_1 = Commitment(crate::wallet::hex_to_fr(&proof.cm_1).map_err(R14Error::Other)?);
+        let new_root = self.new_root(&[cm_0, cm_1]).await?;
+
+        let request = RelayRequest::new(&self.contracts.transfer, proof.try_into()?, &new_root);
+        let relayer = RelayerClient::new(relayer_url);
+        match tokio::time::timeout(self.rpc_timeout, relayer.submit_transfer(&request)).await {
+            Ok(receipt) => Ok(receipt?.tx_result),
+            Err(_) => Err(R14Error::Relayer {
+                code: crate::error::ErrorCode::Unavailable,
+                message: format!("timed out after {:?}", self.rpc_timeout),
+            }),
+        }
     }
 
     // -----------------------------------------------------------------------
     // Public API — prove-gated
     // -----------------------------------------------------------------------
 
-    /// Auto-select note, generate proof, submit transfer on-chain.
+    /// Select a note of `wallet` with `selection`, generate proof, submit
+    /// transfer on-chain.
+    ///
+    /// A client with a spending policy (see [`crate::policy`]) checks it
+    /// first and fails with `PolicyViolation` without proving. The
+    /// consumed note is reserved in a [`crate::pending`] record before
+    /// submission. On success its outputs join `wallet.notes` and the
+    /// transfer is recorded in `wallet.history`; on a failure that
+    /// certainly didn't land the note is released. Any other failure
+    /// leaves the record pending for [`Self::resolve_pending`]. Save the
+    /// wallet afterwards either way.
     #[cfg(feature = "prove")]
     pub async fn transfer(
         &self,
-        notes: &mut [NoteEntry],
+        wallet: &mut crate::wallet::WalletData,
+        sk: &Fr,
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferResult> {
+        let (note_idx, proven) = self.prove_for(&wallet.notes, sk, owner, recipient, value, selection).await?;
+
+        let outputs = vec![proven.recipient.clone(), proven.change.clone()];
+        let submit = self.submit_transfer(&proven.prebuilt);
+        let tx_result = submit_reserved(wallet, note_idx, &proven.prebuilt, outputs, submit).await?;
+        self.record_spend(owner, recipient, value);
+        let result = transfer_result(&proven.prebuilt, proven.recipient, proven.change, note_idx, tx_result);
+        wallet.history.push((&result).into());
+        Ok(result)
+    }
+
+    /// [`Self::transfer`], submitted through an r14 relayer that pays the
+    /// fee, so the Stellar account needs no XLM. The proof is still built
+    /// here; the relayer only sees public data.
+    #[cfg(feature = "prove")]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn transfer_via_relayer(
+        &self,
+        relayer_url: &str,
+        wallet: &mut crate::wallet::WalletData,
         sk: &Fr,
         owner: &Fr,
         recipient: &Fr,
         value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferResult> {
+        let (note_idx, proven) = self.prove_for(&wallet.notes, sk, owner, recipient, value, selection).await?;
+
+        let outputs = vec![proven.recipient.clone(), proven.change.clone()];
+        let submit = self.submit_via_relayer(relayer_url, &proven.prebuilt);
+        let tx_result = submit_reserved(wallet, note_idx, &proven.prebuilt, outputs, submit).await?;
+        self.record_spend(owner, recipient, value);
+        let result = transfer_result(&proven.prebuilt, proven.recipient, proven.change, note_idx, tx_result);
+        wallet.history.push((&result).into());
+        Ok(result)
+    }
+
+    /// Move `wallet` to `new_sk`, e.g. after the old key may have leaked.
+    ///
+    /// Every unspent note the old key owns is swept to the new owner hash
+    /// with a self-transfer of its full value, then the wallet's
+    /// `secret_key` and `owner_hash` switch to the new key. Each sweep is
+    /// recorded as a transfer and the whole rotation as one
+    /// [`HistoryKind::Rotation`](crate::history::HistoryKind::Rotation)
+    /// entry. Save the wallet afterwards.
+    ///
+    /// Nothing is submitted unless every note can be swept now: the wallet
+    /// must have no pending transfers, and every note must be on-chain
+    /// and unlocked at the indexer's ledger. If a sweep fails part way,
+    /// the wallet keeps the old key and the notes already swept belong to
+    /// `new_sk`; call again with the same key to finish. A sweep that may
+    /// still have landed stays pending, so settle it with
+    /// [`Self::resolve_pending`] before calling again. Session keys
+    /// delegated from the old key keep refunding to the old owner hash.
+    #[cfg(feature = "prove")]
+    pub async fn rotate_key(
+        &self,
+        wallet: &mut crate::wallet::WalletData,
+        new_sk: &Fr,
+    ) -> R14Result<RotationResult> {
+        use crate::wallet::{fr_to_hex, hex_to_fr};
+
+        self.require_transfer_contract()?;
+        if !wallet.pending.is_empty() {
+            return Err(R14Error::Config(format!(
+                "{} pending transfer(s); resolve them before rotating the key",
+                wallet.pending.len()
+            )));
+        }
+
+        let old_sk = hex_to_fr(&wallet.secret_key).map_err(R14Error::Other)?;
+        let old_owner = crate::owner_hash(&crate::SecretKey(old_sk)).0;
+        let new_owner = crate::owner_hash(&crate::SecretKey(*new_sk)).0;
+
+        self.sync_notes(&mut wallet.notes).await?;
+        let current_ledger = self.indexer.latest_ledger().await?;
+        let to_sweep: Vec<usize> = wallet
+            .notes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.spent && n.value > 0 && hex_to_fr(&n.owner).ok() == Some(old_owner))
+            .map(|(i, _)| i)
+            .collect();
+        for &i in &to_sweep {
+            let note = &wallet.notes[i];
+            if note.index.is_none() {
+                return Err(R14Error::NoteNotOnChain);
+            }
+            if note.unlock_after > current_ledger {
+                return Err(R14Error::Config(format!(
+                    "note {i} is locked until ledger {} (now {current_ledger}); rotate after it unlocks",
+                    note.unlock_after
+                )));
+            }
+        }
+
+        let (pk, vk) = crate::artifacts::transfer_keys()?;
+        self.check_transfer_circuit(&vk).await?;
+        let new_owner_hex = fr_to_hex(&new_owner);
+        let mut transfers = Vec::new();
+        let mut swept_notes = Vec::new();
+        let mut paths = std::collections::VecDeque::new();
+        for (n, &i) in to_sweep.iter().enumerate() {
+            if paths.is_empty() {
+                let leaves = to_sweep[n..]
+                    .iter()
+                    .take(SWEEP_PATH_BATCH)
+                    .map(|&j| Ok((wallet.notes[j].commitment, wallet.notes[j].index.ok_or(R14Error::NoteNotOnChain)?)))
+                    .collect::<R14Result<Vec<_>>>()?;
+                paths.extend(self.verified_merkle_paths(&leaves).await?);
+            }
+            let entry = &wallet.notes[i];
+            let merkle_path = paths.pop_front().expect("a path was fetched for every note");
+            let proven = prove_transfer(
+                &pk,
+                entry,
+                merkle_path,
+                &old_sk,
+                &new_owner,
+                &new_owner,
+                entry.value,
+                current_ledger,
+            )?;
+
+            // the change is empty, so only the swept note is kept
+            let recipient = proven.recipient.with_source(NoteSource::Change);
+            let submit = self.submit_transfer(&proven.prebuilt);
+            let tx_result = submit_reserved(wallet, i, &proven.prebuilt, vec![recipient.clone()], submit).await?;
+            let result = transfer_result(&proven.prebuilt, recipient, proven.change, i, tx_result);
+            wallet.history.push((&result).into());
+            swept_notes.push(result.recipient_note.commitment);
+            transfers.push(result);
+        }
+
+        let swept = transfers.iter().map(|t| t.recipient_note.value).sum();
+        let old_owner_hex = std::mem::replace(&mut wallet.owner_hash, new_owner_hex.clone());
+        wallet.secret_key.zeroize();
+        wallet.secret_key = fr_to_hex(new_sk);
+        wallet
+            .history
+            .push(HistoryEntry::rotation(swept, swept_notes, new_owner_hex.clone()));
+        Ok(RotationResult {
+            old_owner: old_owner_hex,
+            new_owner: new_owner_hex,
+            transfers,
+            swept,
+        })
+    }
+
+    /// Select a note for sending `value` and fetch its Merkle path at the
+    /// indexer's ledger; returns the note's index, the path and the ledger
+    async fn spend_inputs(
+        &self,
+        notes: &[NoteEntry],
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<(usize, crate::MerklePath, u64)> {
+        self.require_transfer_contract()?;
+        self.check_policy(owner, recipient, value)?;
+
+        // prove at the indexer's view of the ledger: never ahead of the
+        // chain, and it doesn't reveal the consumed note's unlock_after
+        let current_ledger = self.indexer.latest_ledger().await?;
+        if self.confirmed_only {
+            return self.confirmed_spend_inputs(notes, value, selection, current_ledger).await;
+        }
+        let note_idx = select_notes(notes, value, selection, TRANSFER_INPUTS, current_ledger)?[0];
+
+        let entry = &notes[note_idx];
+        let leaf_index = entry.index.ok_or(R14Error::NoteNotOnChain)?;
+
+        // fetch merkle proof, checked against the indexer root
+        let merkle_path = self.verified_merkle_path(entry.commitment, leaf_index).await?;
+        Ok((note_idx, merkle_path, current_ledger))
+    }
+
+    /// [`Self::spend_inputs`] for a `confirmed_only` client: pending notes
+    /// aren't selected, and the path leads to the root of the confirmed
+    /// leaves, checked against the contract's root history
+    async fn confirmed_spend_inputs(
+        &self,
+        notes: &[NoteEntry],
+        value: u64,
+        selection: CoinSelection,
+        current_ledger: u64,
+    ) -> R14Result<(usize, crate::MerklePath, u64)> {
+        let page = self.indexer.leaves_from(crate::LeafIndex(0)).await?;
+        let confirmed = page
+            .confirmed
+            .ok_or_else(|| R14Error::Indexer("/v1/leaves: the indexer doesn't report confirmed leaves".into()))?;
+        let leaves = &page.leaves[..page.leaves.len().min(confirmed as usize)];
+
+        // pending notes look unindexed to selection
+        let view: Vec<NoteEntry> = notes
+            .iter()
+            .map(|n| NoteEntry {
+                index: n.index.filter(|i| i.as_usize() < leaves.len()),
+                ..n.clone()
+            })
+            .collect();
+        let note_idx = select_notes(&view, value, selection, TRANSFER_INPUTS, current_ledger)?[0];
+
+        let entry = &notes[note_idx];
+        let leaf_index = view[note_idx].index.ok_or(R14Error::NoteNotOnChain)?;
+        if leaves[leaf_index.as_usize()] != entry.commitment {
+            return Err(R14Error::StaleRoot(format!("indexer leaf {leaf_index} is not the note's commitment")));
+        }
+        let merkle_path = crate::merkle::path_from_leaves(leaves, leaf_index).ok_or(R14Error::NoteNotOnChain)?;
+        self.require_known_root(&crate::merkle::compute_root_from_leaves(leaves)).await?;
+        Ok((note_idx, merkle_path, current_ledger))
+    }
+
+    /// Select a note and prove sending `value` from it at the indexer's
+    /// ledger; returns the note's index and the proof
+    #[cfg(feature = "prove")]
+    async fn prove_for(
+        &self,
+        notes: &[NoteEntry],
+        sk: &Fr,
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<(usize, ProvenTransfer)> {
+        let (note_idx, merkle_path, current_ledger) = self.spend_inputs(notes, owner, recipient, value, selection).await?;
+
+        // installed keys, else the deterministic setup matching the on-chain VK
+        let (pk, vk) = crate::artifacts::transfer_keys()?;
+        self.check_transfer_circuit(&vk).await?;
+        let proven = prove_transfer(&pk, &notes[note_idx], merkle_path, sk, owner, recipient, value, current_ledger)?;
+        Ok((note_idx, proven))
+    }
+
+    /// The online half of an air-gapped transfer: select a note for
+    /// sending `value` to `recipient` and gather everything proving needs
+    /// into a [`TransferBundle`] for [`crate::offline::prove_and_sign`].
+    ///
+    /// Needs no key and no `prove` feature; the bundle holds nothing
+    /// secret. The spending policy is checked here. Nothing is reserved,
+    /// so don't prepare twice from the same notes before submitting.
+    pub async fn prepare_transfer(
+        &self,
+        notes: &[NoteEntry],
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferBundle> {
+        let (note_idx, merkle_path, current_ledger) = self.spend_inputs(notes, owner, recipient, value, selection).await?;
+        Ok(TransferBundle::new(
+            self.transfer_circuit_id().await?,
+            notes[note_idx].clone(),
+            merkle_path,
+            recipient,
+            value,
+            current_ledger,
+        ))
+    }
+
+    /// Submit a transfer proven offline, for the note of `wallet` it spends.
+    ///
+    /// Like [`Self::transfer`], the note is reserved in a pending record
+    /// before submission, released only if submission certainly didn't
+    /// land, and the outputs join the wallet on success.
+    pub async fn submit_signed(
+        &self,
+        wallet: &mut crate::wallet::WalletData,
+        signed: &SignedTransfer,
+    ) -> R14Result<TransferResult> {
+        let note_idx = wallet
+            .notes
+            .iter()
+            .position(|n| n.commitment == signed.consumed)
+            .ok_or_else(|| R14Error::Config("the signed transfer spends a note that isn't in the wallet".to_string()))?;
+        if wallet.notes[note_idx].spent {
+            return Err(R14Error::Config("the signed transfer spends a note already marked spent".to_string()));
+        }
+        let owner = crate::wallet::hex_to_fr(&wallet.notes[note_idx].owner).map_err(R14Error::Other)?;
+        let recipient = crate::wallet::hex_to_fr(&signed.recipient_note.owner).map_err(R14Error::Other)?;
+
+        let outputs = vec![signed.recipient_note.clone(), signed.change_note.clone()];
+        let submit = self.submit_transfer(&signed.proof);
+        let tx_result = submit_reserved(wallet, note_idx, &signed.proof, outputs, submit).await?;
+        self.record_spend(&owner, &recipient, signed.recipient_note.value);
+        let result = transfer_result(
+            &signed.proof,
+            signed.recipient_note.clone(),
+            signed.change_note.clone(),
+            note_idx,
+            tx_result,
+        );
+        wallet.history.push((&result).into());
+        Ok(result)
+    }
+
+    /// Spend a session-owned note with a delegated proof.
+    ///
+    /// Pays `value` to `recipient` and keeps the change under the session.
+    /// Paying the session's refund owner sweeps without policy limits.
+    /// `valid_until` is the last ledger at which the contract accepts the proof.
+    #[cfg(feature = "prove")]
+    pub async fn transfer_with_session(
+        &self,
+        notes: &mut [NoteEntry],
+        session: &crate::session::SessionKey,
+        recipient: &Fr,
+        value: u64,
+        valid_until: u64,
     ) -> R14Result<TransferResult> {
         use ark_std::rand::{rngs::StdRng, SeedableRng};
 
         self.require_transfer_contract()?;
 
-        // find first unspent on-chain note with sufficient value
+        let policy = session.policy();
+        let session_sk = session.session_secret().map_err(R14Error::Other)?;
+        let refund_owner = session.refund_owner_hash().map_err(R14Error::Other)?;
+        let session_owner = crate::wallet::hex_to_fr(&session.owner).map_err(R14Error::Other)?;
+
+        let is_refund = *recipient == refund_owner.0;
+        if !is_refund && !policy.allows(value, policy.app_tag, valid_until) {
+            return Err(R14Error::SessionPolicy(format!(
+                "transfer of {value} valid until ledger {valid_until} exceeds max {} / expiry {}",
+                policy.max_amount, policy.expiry
+            )));
+        }
+
+        // the delegated circuit only handles unlocked notes
+        let owned_by_session = |n: &NoteEntry| {
+            !n.spent
+                && n.index.is_some()
+                && n.unlock_after == 0
+                && n.app_tag == policy.app_tag
+                && crate::wallet::hex_to_fr(&n.owner).ok() == Some(session_owner)
+        };
         let note_idx = notes
             .iter()
-            .position(|n| !n.spent && n.value >= value && n.index.is_some())
+            .position(|n| owned_by_session(n) && n.value >= value)
             .ok_or_else(|| {
                 let best = notes
                     .iter()
-                    .filter(|n| !n.spent && n.index.is_some())
+                    .filter(|n| owned_by_session(n))
                     .map(|n| n.value)
                     .max()
                     .unwrap_or(0);
@@ -389,100 +1432,120 @@ impl R14Client {
         let consumed = Note::with_nonce(
             entry.value,
             entry.app_tag,
-            crate::wallet::hex_to_fr(&entry.owner).map_err(R14Error::Other)?,
+            session_owner,
             crate::wallet::hex_to_fr(&entry.nonce).map_err(R14Error::Other)?,
         );
         let leaf_index = entry.index.ok_or(R14Error::NoteNotOnChain)?;
-        let app_tag = entry.app_tag;
-        let consumed_value = entry.value;
 
-        // fetch merkle proof
-        let (siblings, indices) = self.fetch_merkle_proof(leaf_index).await?;
-        let merkle_path = crate::MerklePath { siblings, indices };
+        let merkle_path = self.verified_merkle_path(commitment(&consumed), leaf_index).await?;
 
-        // build output notes
         let mut rng = crate::wallet::crypto_rng();
-        let change = consumed_value - value;
-        let note_0 = Note::new(value, app_tag, *recipient, &mut rng);
-        let note_1 = Note::new(change, app_tag, *owner, &mut rng);
-
-        // Deterministic setup — same seed=42 reproduces VK matching on-chain
-        let setup_rng = &mut StdRng::seed_from_u64(42);
-        let (pk, _vk) = crate::prove::setup(setup_rng);
-        let (proof, pi) = crate::prove::prove(
+        let note_0 = Note::new(value, policy.app_tag, *recipient, &mut rng);
+        let note_1 = Note::new(consumed.value - value, policy.app_tag, session_owner, &mut rng);
+
+        let setup_rng = &mut StdRng::seed_from_u64(DELEGATED_SETUP_SEED);
+        let (pk, _vk) = crate::prove::setup_delegated(setup_rng)?;
+        let (proof, pi) = crate::prove::prove_delegated(
             &pk,
-            *sk,
+            &session_sk,
+            &policy,
+            &refund_owner,
             consumed,
             merkle_path,
             [note_0.clone(), note_1.clone()],
+            valid_until,
             &mut rng,
-        );
+        )?;
 
         let (serialized_proof, serialized_pi) =
             crate::prove::serialize_proof_for_soroban(&proof, &pi.to_vec());
-
-        let cm_0 = commitment(&note_0);
-        let cm_1 = commitment(&note_1);
-
         let proof_json = format!(
             r#"{{"a":"{}","b":"{}","c":"{}"}}"#,
             serialized_proof.a, serialized_proof.b, serialized_proof.c
         );
 
-        let prebuilt = PrebuiltProof {
-            proof_json,
-            old_root: crate::wallet::strip_0x(&serialized_pi[0]),
-            nullifier: crate::wallet::strip_0x(&serialized_pi[1]),
-            cm_0: crate::wallet::strip_0x(&serialized_pi[2]),
-            cm_1: crate::wallet::strip_0x(&serialized_pi[3]),
-        };
-
-        let recipient_entry = NoteEntry {
-            value: note_0.value,
-            app_tag: note_0.app_tag,
-            owner: crate::wallet::fr_to_hex(&note_0.owner),
-            nonce: crate::wallet::fr_to_hex(&note_0.nonce),
-            commitment: crate::wallet::fr_to_hex(&cm_0),
-            index: None,
-            spent: false,
-        };
+        self.require_known_root(&serialized_pi[0]).await?;
 
-        let change_entry = NoteEntry {
-            value: note_1.value,
-            app_tag: note_1.app_tag,
-            owner: crate::wallet::fr_to_hex(&note_1.owner),
-            nonce: crate::wallet::fr_to_hex(&note_1.nonce),
-            commitment: crate::wallet::fr_to_hex(&cm_1),
-            index: None,
-            spent: false,
-        };
+        let cm_0 = commitment(&note_0);
+        let cm_1 = commitment(&note_1);
+        let new_root = self.new_root(&[cm_0, cm_1]).await?;
 
-        let result = self
-            .transfer_with_proof(&prebuilt, recipient_entry, change_entry, note_idx)
+        let valid_until_arg = valid_until.to_string();
+        let tx_result = self
+            .invoke(
+                &self.contracts.transfer,
+                "transfer_delegated",
+                &[
+                    ("proof", &proof_json),
+                    ("old_root", &serialized_pi[0]),
+                    ("nullifier", &serialized_pi[1]),
+                    ("cm_0", &serialized_pi[2]),
+                    ("cm_1", &serialized_pi[3]),
+                    ("valid_until", &valid_until_arg),
+                    ("new_root", &new_root),
+                ],
+            )
             .await?;
 
-        // mark consumed note spent
         notes[note_idx].spent = true;
 
-        Ok(result)
+        Ok(TransferResult {
+            nullifier: format!("0x{}", serialized_pi[1]),
+            out_commitment_0: cm_0,
+            out_commitment_1: cm_1,
+            tx_result,
+            recipient_note: NoteEntry::new(&note_0, &cm_0)
+                .with_source(NoteSource::Transfer)
+                .with_counterparty(crate::wallet::fr_to_hex(recipient)),
+            change_note: NoteEntry::new(&note_1, &cm_1).with_source(NoteSource::Change),
+            consumed_note_index: note_idx,
+        })
     }
 
-    /// Register VK on core contract and initialize transfer contract.
+    /// Register the delegated-transfer VK and enable session spends.
     #[cfg(feature = "prove")]
-    pub async fn init_contracts(&self) -> R14Result<InitResult> {
+    pub async fn init_delegated(&self) -> R14Result<InitResult> {
         use ark_std::rand::{rngs::StdRng, SeedableRng};
 
         self.require_contracts()?;
 
-        let mut rng = StdRng::seed_from_u64(42);
-        let (_pk, vk) = crate::prove::setup(&mut rng);
-        let svk = crate::prove::serialize_vk_for_soroban(&vk);
+        let mut rng = StdRng::seed_from_u64(DELEGATED_SETUP_SEED);
+        let (_pk, vk) = crate::prove::setup_delegated(&mut rng)?;
+        let vk_json = vk_to_json(&crate::prove::serialize_vk_for_soroban(&vk, crate::MERKLE_DEPTH));
 
-        let ic_entries: Vec<String> = svk.ic.iter().map(|s| format!("\"{}\"", s)).collect();
-        let vk_json = format!(
-            r#"{{"alpha_g1":"{}","beta_g2":"{}","gamma_g2":"{}","delta_g2":"{}","ic":[{}]}}"#,
-            svk.alpha_g1, svk.beta_g2, svk.gamma_g2, svk.delta_g2, ic_entries.join(",")
-        );
+        let caller = crate::soroban::get_public_key(&self.stellar_secret)
+            .await
+            .map_err(|e| R14Error::Soroban(e.to_string()))?;
+
+        let circuit_id = self
+            .invoke(
+                &self.contracts.core,
+                "register",
+                &[("caller", &caller), ("vk", &vk_json)],
+            )
+            .await?;
+
+        let tx_result = self
+            .invoke(
+                &self.contracts.transfer,
+                "init_delegated",
+                &[("circuit_id", &circuit_id)],
+            )
+            .await?;
+
+        Ok(InitResult {
+            circuit_id,
+            tx_result,
+        })
+    }
+
+    /// Register VK on core contract and initialize transfer contract.
+    #[cfg(feature = "prove")]
+    pub async fn init_contracts(&self) -> R14Result<InitResult> {
+        self.require_contracts()?;
+
+        let (_pk, vk) = crate::artifacts::transfer_keys()?;
+        let vk_json = vk_to_json(&crate::prove::serialize_vk_for_soroban(&vk, crate::MERKLE_DEPTH));
 
         let caller = crate::soroban::get_public_key(&self.stellar_secret)
             .await
@@ -503,6 +1566,7 @@ impl R14Client {
                 &self.contracts.transfer,
                 "init",
                 &[
+                    ("admin", &caller),
                     ("core_contract", &self.contracts.core),
                     ("circuit_id", &circuit_id),
                     ("empty_root", &empty_root),
@@ -517,23 +1581,212 @@ impl R14Client {
     }
 }
 
+/// Unspent total and per-note status of already-synced `notes`
+pub(crate) fn summarize_balance(notes: &[NoteEntry]) -> BalanceResult {
+    let mut total = 0u64;
+    let mut statuses = Vec::new();
+    for note in notes.iter().filter(|n| !n.spent) {
+        total += note.value;
+        statuses.push(NoteStatus {
+            value: note.value,
+            app_tag: note.app_tag,
+            commitment: note.commitment,
+            on_chain: note.index.is_some(),
+            meta: note.meta.clone(),
+        });
+    }
+    BalanceResult {
+        total,
+        notes: statuses,
+    }
+}
+
+/// A transfer proof and its output notes, not yet submitted
+#[cfg(feature = "prove")]
+pub(crate) struct ProvenTransfer {
+    pub(crate) prebuilt: PrebuiltProof,
+    /// For verifying locally, as the simulator does
+    #[cfg_attr(not(feature = "testing"), allow(dead_code))]
+    pub(crate) proof: ark_groth16::Proof<ark_bls12_381::Bls12_381>,
+    #[cfg_attr(not(feature = "testing"), allow(dead_code))]
+    pub(crate) public_inputs: crate::prove::PublicInputs,
+    pub(crate) recipient: NoteEntry,
+    pub(crate) change: NoteEntry,
+}
+
+fn transfer_result(
+    proof: &PrebuiltProof,
+    recipient_note: NoteEntry,
+    change_note: NoteEntry,
+    consumed_idx: usize,
+    tx_result: String,
+) -> TransferResult {
+    TransferResult {
+        nullifier: format!("0x{}", proof.nullifier),
+        out_commitment_0: recipient_note.commitment,
+        out_commitment_1: change_note.commitment,
+        tx_result,
+        recipient_note,
+        change_note,
+        consumed_note_index: consumed_idx,
+    }
+}
+
+/// Run `submit` for `proof` with `wallet.notes[consumed_idx]` reserved in
+/// a [`crate::pending`] record parking `outputs`. Confirms the record on
+/// success and rolls it back only when the failure certainly didn't land;
+/// otherwise the note stays reserved until the nullifier shows up.
+async fn submit_reserved(
+    wallet: &mut crate::wallet::WalletData,
+    consumed_idx: usize,
+    proof: &PrebuiltProof,
+    outputs: Vec<NoteEntry>,
+    submit: impl std::future::Future<Output = R14Result<String>>,
+) -> R14Result<String> {
+    let nullifier = format!("0x{}", proof.nullifier);
+    crate::pending::begin_transfer(wallet, consumed_idx, &nullifier, outputs).map_err(R14Error::Other)?;
+    match submit.await {
+        Ok(tx_result) => {
+            crate::pending::confirm_transfer(wallet, &nullifier).map_err(R14Error::Other)?;
+            Ok(tx_result)
+        }
+        Err(e) => {
+            if never_landed(&e) {
+                crate::pending::rollback_transfer(wallet, &nullifier).map_err(R14Error::Other)?;
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Whether a failed submission certainly didn't land. Failures before the
+/// contract call (a stale root, an unreachable indexer) never do; a
+/// contract call only when it was rejected (see
+/// [`crate::soroban::never_landed`]). A relayer that timed out or failed
+/// internally may have submitted anyway.
+fn never_landed(err: &R14Error) -> bool {
+    use crate::error::ErrorCode;
+
+    match err {
+        R14Error::Soroban(message) | R14Error::Relayer { code: ErrorCode::Soroban, message } => {
+            crate::soroban::never_landed(&anyhow::anyhow!("{message}"))
+        }
+        R14Error::Relayer { code, .. } => !matches!(code, ErrorCode::Unavailable | ErrorCode::Internal),
+        _ => true,
+    }
+}
+
+/// Prove spending `entry` at `merkle_path`: `value` to `recipient` and
+/// the change back to `owner`, in the consumed note's asset
+#[cfg(feature = "prove")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prove_transfer(
+    pk: &ark_groth16::ProvingKey<ark_bls12_381::Bls12_381>,
+    entry: &NoteEntry,
+    merkle_path: crate::MerklePath,
+    sk: &Fr,
+    owner: &Fr,
+    recipient: &Fr,
+    value: u64,
+    current_ledger: u64,
+) -> R14Result<ProvenTransfer> {
+    let consumed = entry.note().map_err(R14Error::Other)?;
+
+    // build output notes
+    let mut rng = crate::wallet::crypto_rng();
+    let change = entry.value - value;
+    let note_0 = Note::new(value, entry.app_tag, *recipient, &mut rng);
+    let note_1 = Note::new(change, entry.app_tag, *owner, &mut rng);
+
+    let (proof, pi) = crate::prove::prove(
+        pk,
+        *sk,
+        consumed,
+        merkle_path,
+        [note_0.clone(), note_1.clone()],
+        current_ledger,
+        &mut rng,
+    )?;
+
+    let (serialized_proof, serialized_pi) =
+        crate::prove::serialize_proof_for_soroban(&proof, &pi.to_vec());
+
+    let cm_0 = commitment(&note_0);
+    let cm_1 = commitment(&note_1);
+
+    let proof_json = format!(
+        r#"{{"a":"{}","b":"{}","c":"{}"}}"#,
+        serialized_proof.a, serialized_proof.b, serialized_proof.c
+    );
+
+    let prebuilt = PrebuiltProof {
+        proof_json,
+        old_root: crate::wallet::strip_0x(&serialized_pi[0]),
+        nullifier: crate::wallet::strip_0x(&serialized_pi[1]),
+        cm_0: crate::wallet::strip_0x(&serialized_pi[2]),
+        cm_1: crate::wallet::strip_0x(&serialized_pi[3]),
+        current_ledger,
+    };
+
+    Ok(ProvenTransfer {
+        prebuilt,
+        proof,
+        public_inputs: pi,
+        recipient: NoteEntry::new(&note_0, &cm_0)
+            .with_source(NoteSource::Transfer)
+            .with_counterparty(crate::wallet::fr_to_hex(recipient)),
+        change: NoteEntry::new(&note_1, &cm_1).with_source(NoteSource::Change),
+    })
+}
+
+/// Root/path reads before a mismatch is reported as a stale indexer
+const MERKLE_READ_ATTEMPTS: usize = 3;
+
+/// Notes a key rotation fetches Merkle paths for at once. Each sweep adds
+/// a root, so a batch has to be spent well inside the contract's 100-root
+/// history.
+#[cfg(feature = "prove")]
+const SWEEP_PATH_BATCH: usize = 16;
+
+/// Deterministic setup seed for the transfer circuit
+#[cfg(feature = "prove")]
+pub const TRANSFER_SETUP_SEED: u64 = 42;
+
+/// Deterministic setup seed for the delegated (session-key) circuit
+#[cfg(feature = "prove")]
+pub(crate) const DELEGATED_SETUP_SEED: u64 = 43;
+
+/// VK JSON matching the Soroban contract's unified IC format
+#[cfg(feature = "prove")]
+pub(crate) fn vk_to_json(svk: &crate::serialize::SerializedVK) -> String {
+    let ic_entries: Vec<String> = svk.ic.iter().map(|s| format!("\"{}\"", s)).collect();
+    format!(
+        r#"{{"alpha_g1":"{}","beta_g2":"{}","gamma_g2":"{}","delta_g2":"{}","ic":[{}],"depth":{}}}"#,
+        svk.alpha_g1, svk.beta_g2, svk.gamma_g2, svk.delta_g2, ic_entries.join(","), svk.depth
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::wallet::WalletData;
 
+    #[test]
+    fn builder_rejects_zero_attempts() {
+        let contracts = || R14Contracts { core: "C".into(), transfer: "C".into() };
+        let retry = RetryPolicy { max_attempts: 0, ..RetryPolicy::default() };
+        let err = R14Client::builder("http://localhost:3000", contracts(), "S").retry(retry).build();
+        assert!(matches!(err, Err(R14Error::Config(_))));
+        let client = R14Client::builder("http://localhost:3000/", contracts(), "S")
+            .retry(RetryPolicy::default())
+            .build()
+            .unwrap();
+        assert_eq!(client.indexer().base_url(), "http://localhost:3000");
+    }
+
     #[test]
     fn from_wallet_accepts_placeholder() {
-        let wallet = WalletData {
-            secret_key: "0x01".to_string(),
-            owner_hash: "0x02".to_string(),
-            stellar_secret: "PLACEHOLDER".to_string(),
-            notes: vec![],
-            indexer_url: "http://localhost:3000".to_string(),
-            rpc_url: "https://soroban-testnet.stellar.org:443".to_string(),
-            core_contract_id: "PLACEHOLDER".to_string(),
-            transfer_contract_id: "PLACEHOLDER".to_string(),
-        };
+        let wallet = WalletData::for_test(&crate::SecretKey(Fr::from(1u64)));
         let client = R14Client::from_wallet(&wallet);
         assert!(client.is_ok());
     }
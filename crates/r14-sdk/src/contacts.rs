@@ -0,0 +1,113 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Address book: named recipients kept in the wallet.
+//!
+//! [`WalletData::contacts`](crate::wallet::WalletData::contacts) maps an
+//! alias to an owner, given as an `r14o1` address or owner hash hex.
+//! Aliases are case-insensitive and stored lowercase. Wherever a recipient
+//! is typed, [`resolve_recipient`] takes `@alias` as well as an address.
+//!
+//! ```rust
+//! use ark_bls12_381::Fr;
+//! use r14_sdk::contacts::{remove_contact, resolve_recipient, set_contact};
+//! use r14_sdk::wallet::owner_address;
+//! # fn example(wallet: &mut r14_sdk::wallet::WalletData) -> anyhow::Result<()> {
+//! let alice = Fr::from(7u64);
+//! set_contact(wallet, "Alice", &owner_address(&alice))?;
+//! assert_eq!(resolve_recipient(wallet, "@alice")?, alice);
+//! assert!(remove_contact(wallet, "alice").is_some());
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{bail, Context, Result};
+use ark_bls12_381::Fr;
+
+use crate::wallet::{parse_owner, WalletData};
+
+/// Aliases longer than this are refused
+pub const MAX_ALIAS_LEN: usize = 64;
+
+/// `alias` lowercased, or an error unless it's letters, digits, `-`,
+/// `_` and `.`
+pub fn normalize_alias(alias: &str) -> Result<String> {
+    let alias = alias.strip_prefix('@').unwrap_or(alias);
+    if alias.is_empty() || alias.len() > MAX_ALIAS_LEN {
+        bail!("alias must be 1 to {MAX_ALIAS_LEN} characters");
+    }
+    if !alias.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        bail!("alias `{alias}` may only contain letters, digits, `-`, `_` and `.`");
+    }
+    Ok(alias.to_ascii_lowercase())
+}
+
+/// Add `alias` for `owner`, or repoint it. Returns the owner it had.
+pub fn set_contact(wallet: &mut WalletData, alias: &str, owner: &str) -> Result<Option<String>> {
+    let alias = normalize_alias(alias)?;
+    parse_owner(owner)?;
+    Ok(wallet.contacts.insert(alias, owner.to_string()))
+}
+
+/// Owner `alias` stands for, as it was stored
+pub fn contact<'a>(wallet: &'a WalletData, alias: &str) -> Option<&'a str> {
+    let alias = normalize_alias(alias).ok()?;
+    wallet.contacts.get(&alias).map(String::as_str)
+}
+
+/// Drop `alias`, returning the owner it stood for
+pub fn remove_contact(wallet: &mut WalletData, alias: &str) -> Option<String> {
+    wallet.contacts.remove(&normalize_alias(alias).ok()?)
+}
+
+/// Owner hash of `recipient`: `@alias` from the address book, otherwise
+/// an `r14o1` address or hex
+pub fn resolve_recipient(wallet: &WalletData, recipient: &str) -> Result<Fr> {
+    let Some(alias) = recipient.strip_prefix('@') else {
+        return parse_owner(recipient);
+    };
+    let owner = contact(wallet, alias).with_context(|| format!("no contact named `{alias}`"))?;
+    parse_owner(owner).with_context(|| format!("contact `{alias}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{fr_to_hex, owner_address};
+
+    fn wallet() -> WalletData {
+        serde_json::from_str(
+            r#"{"secret_key":"0x1","owner_hash":"0x2","stellar_secret":"S","notes":[],
+                "indexer_url":"","rpc_url":"","core_contract_id":"","transfer_contract_id":""}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn aliases_resolve_case_insensitively() {
+        let mut w = wallet();
+        let (alice, bob) = (Fr::from(7u64), Fr::from(8u64));
+        assert_eq!(set_contact(&mut w, "Alice", &owner_address(&alice)).unwrap(), None);
+        set_contact(&mut w, "@bob.b", &fr_to_hex(&bob)).unwrap();
+
+        assert_eq!(resolve_recipient(&w, "@ALICE").unwrap(), alice);
+        assert_eq!(resolve_recipient(&w, "@bob.b").unwrap(), bob);
+        assert_eq!(resolve_recipient(&w, &owner_address(&bob)).unwrap(), bob);
+        assert!(resolve_recipient(&w, "@carol").is_err());
+
+        // repointing returns the old owner; removing forgets it
+        assert_eq!(set_contact(&mut w, "alice", &fr_to_hex(&bob)).unwrap(), Some(owner_address(&alice)));
+        assert_eq!(remove_contact(&mut w, "ALICE"), Some(fr_to_hex(&bob)));
+        assert_eq!(contact(&w, "alice"), None);
+    }
+
+    #[test]
+    fn rejects_bad_aliases_and_owners() {
+        let mut w = wallet();
+        assert!(set_contact(&mut w, "", "0x1").is_err());
+        assert!(set_contact(&mut w, "a b", "0x1").is_err());
+        assert!(set_contact(&mut w, &"a".repeat(MAX_ALIAS_LEN + 1), "0x1").is_err());
+        assert!(set_contact(&mut w, "alice", "not-an-owner").is_err());
+        assert!(w.contacts.is_empty());
+    }
+}
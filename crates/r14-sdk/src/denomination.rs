@@ -0,0 +1,162 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Splitting deposits into standard denominations.
+//!
+//! Note values are hidden, but a note of exactly 1 337 is spent with its
+//! own change pattern and stands out once values leak through amounts
+//! paid in or out. Depositing round values instead — powers of 10, or a
+//! fixed coin-like set — puts each note among many others of the same
+//! value.
+//!
+//! A [`DenominationConfig`] picks the [`Denominations`] per `app_tag`,
+//! falling back to a default. Its [`split`](DenominationConfig::split)
+//! output feeds straight into `R14Client::deposit_many`, and
+//! `R14Client::deposit_split` does both.
+//!
+//! ```rust
+//! use r14_sdk::denomination::{DenominationConfig, Denominations};
+//!
+//! let mut config = DenominationConfig::default(); // powers of 10
+//! config.per_tag.insert(2, Denominations::Fixed(vec![1, 5, 25]));
+//!
+//! let notes = config.split(1_230, 1).unwrap();
+//! assert_eq!(notes, [(1_000, 1), (100, 1), (100, 1), (10, 1), (10, 1), (10, 1)]);
+//! assert_eq!(config.split(31, 2).unwrap(), [(25, 2), (5, 2), (1, 2)]);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{R14Error, R14Result};
+
+/// Most notes one split may produce; more would make the batch deposit
+/// too large for a transaction
+pub const MAX_SPLIT_NOTES: usize = 64;
+
+/// The note values a deposit may be split into
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Denominations {
+    /// 1, base, base², … (base at least 2)
+    PowersOf(u64),
+    /// An explicit set of values. Include 1 to split every amount.
+    Fixed(Vec<u64>),
+}
+
+impl Default for Denominations {
+    fn default() -> Self {
+        Self::PowersOf(10)
+    }
+}
+
+impl Denominations {
+    /// Values up to `max`, largest first
+    fn values(&self, max: u64) -> R14Result<Vec<u64>> {
+        let mut values = match self {
+            Self::PowersOf(base) => {
+                if *base < 2 {
+                    return Err(R14Error::Config(format!("denomination base must be at least 2, got {base}")));
+                }
+                let mut values = vec![1];
+                let mut d = 1u64;
+                while let Some(next) = d.checked_mul(*base).filter(|&n| n <= max) {
+                    values.push(next);
+                    d = next;
+                }
+                values
+            }
+            Self::Fixed(values) => {
+                if values.contains(&0) {
+                    return Err(R14Error::Config("denominations must be non-zero".to_string()));
+                }
+                values.iter().copied().filter(|&v| v <= max).collect()
+            }
+        };
+        values.sort_unstable_by(|a, b| b.cmp(a));
+        values.dedup();
+        Ok(values)
+    }
+
+    /// Split `value` into denominations, largest first, taking as many of
+    /// each as fit. Errors when a fixed set can't make the amount exactly
+    /// this way, or the split would take more than [`MAX_SPLIT_NOTES`].
+    pub fn split(&self, value: u64) -> R14Result<Vec<u64>> {
+        let mut notes = Vec::new();
+        let mut left = value;
+        for d in self.values(value)? {
+            let count = left / d;
+            if notes.len() as u64 + count > MAX_SPLIT_NOTES as u64 {
+                return Err(R14Error::Config(format!(
+                    "splitting {value} takes more than {MAX_SPLIT_NOTES} notes"
+                )));
+            }
+            notes.extend(std::iter::repeat_n(d, count as usize));
+            left %= d;
+        }
+        if left != 0 {
+            return Err(R14Error::Config(format!(
+                "{value} can't be made from the denominations ({left} left over)"
+            )));
+        }
+        Ok(notes)
+    }
+}
+
+/// Denominations per `app_tag`, with a default for tags not listed
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenominationConfig {
+    #[serde(default)]
+    pub default: Denominations,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub per_tag: BTreeMap<u32, Denominations>,
+}
+
+impl DenominationConfig {
+    pub fn for_tag(&self, app_tag: u32) -> &Denominations {
+        self.per_tag.get(&app_tag).unwrap_or(&self.default)
+    }
+
+    /// Split `value` with `app_tag`'s denominations, as `(value, app_tag)`
+    /// pairs for `R14Client::deposit_many`
+    pub fn split(&self, value: u64, app_tag: u32) -> R14Result<Vec<(u64, u32)>> {
+        Ok(self.for_tag(app_tag).split(value)?.into_iter().map(|v| (v, app_tag)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn powers_split_digit_by_digit() {
+        let split = Denominations::PowersOf(10).split(4_021).unwrap();
+        assert_eq!(split, [1_000, 1_000, 1_000, 1_000, 10, 10, 1]);
+        assert_eq!(Denominations::PowersOf(2).split(13).unwrap(), [8, 4, 1]);
+        assert!(Denominations::PowersOf(10).split(0).unwrap().is_empty());
+        // the next power would overflow
+        assert_eq!(Denominations::PowersOf(10).split(10u64.pow(19)).unwrap(), [10u64.pow(19)]);
+        assert!(Denominations::PowersOf(1).split(5).is_err());
+    }
+
+    #[test]
+    fn fixed_sets_must_make_the_amount() {
+        let coins = Denominations::Fixed(vec![25, 5, 5, 1]);
+        assert_eq!(coins.split(57).unwrap(), [25, 25, 5, 1, 1]);
+        assert!(Denominations::Fixed(vec![5, 10]).split(12).is_err());
+        assert!(Denominations::Fixed(vec![0, 1]).split(1).is_err());
+        // too many notes
+        assert!(Denominations::Fixed(vec![1]).split(MAX_SPLIT_NOTES as u64 + 1).is_err());
+    }
+
+    #[test]
+    fn config_picks_per_tag_and_round_trips() {
+        let json = r#"{"default":{"powers_of":2},"per_tag":{"7":{"fixed":[50]}}}"#;
+        let config: DenominationConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.split(100, 7).unwrap(), [(50, 7), (50, 7)]);
+        assert_eq!(config.split(3, 1).unwrap(), [(2, 1), (1, 1)]);
+        assert_eq!(serde_json::to_string(&config).unwrap(), json);
+        assert_eq!(serde_json::from_str::<DenominationConfig>("{}").unwrap(), DenominationConfig::default());
+    }
+}
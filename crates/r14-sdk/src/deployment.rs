@@ -0,0 +1,570 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! `r14.toml` deployment descriptor.
+//!
+//! A descriptor pins everything the wallet, CLI, and indexer must agree on
+//! for one environment: network, endpoints, contract IDs, and the
+//! circuit_ids registered on r14-core. Check it into the repo next to the
+//! deploy scripts; `r14 doctor` compares it against live state.
+//!
+//! ```toml
+//! network = "testnet"
+//! rpc_url = "https://soroban-testnet.stellar.org:443"
+//! # optional, for a network that isn't a preset (testnet, futurenet, mainnet)
+//! # network_passphrase = "Standalone Network ; February 2017"
+//! indexer_url = "http://localhost:3000"
+//! # optional, "poseidon" (default), "poseidon2" or "circom"
+//! hash = "poseidon"
+//!
+//! [contracts]
+//! core = "CCORE..."
+//! transfer = "CXFER..."
+//!
+//! [circuits.transfer]
+//! circuit_id = "9f3c..."
+//!
+//! # optional, only if session keys are enabled
+//! [circuits.delegated]
+//! circuit_id = "51ab..."
+//!
+//! # optional, published keys so wallets skip setup (see `artifacts`)
+//! [artifacts.transfer]
+//! proving_key = { url = "https://example.org/r14/transfer.pk", sha256 = "5d2c..." }
+//! verifying_key = { url = "https://example.org/r14/transfer.vk.json", sha256 = "0e41..." }
+//! ```
+//!
+//! Each check returns [`Finding`]s rather than failing fast, so a single
+//! run reports every mismatch.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use r14_poseidon::{hash_family, HashFamily};
+use serde::{Deserialize, Serialize};
+
+pub use crate::artifacts::PublishedArtifacts;
+use crate::indexer::IndexerClient;
+use crate::network::Network;
+use crate::wallet::WalletData;
+
+pub const DESCRIPTOR_FILE: &str = "r14.toml";
+
+/// Env var overriding descriptor discovery
+pub const DESCRIPTOR_ENV: &str = "R14_DEPLOYMENT";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Deployment {
+    pub network: String,
+    pub rpc_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_passphrase: Option<String>,
+    pub indexer_url: String,
+    /// Hash family the circuits and commitments use. Part of every
+    /// circuit_id, so it is fixed for the life of a deployment.
+    #[serde(default, skip_serializing_if = "is_default_hash")]
+    pub hash: HashFamily,
+    pub contracts: DeployedContracts,
+    pub circuits: DeployedCircuits,
+    /// Published keys for [`crate::artifacts::KeyCache::install`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifacts: Option<PublishedArtifacts>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeployedContracts {
+    pub core: String,
+    pub transfer: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeployedCircuits {
+    pub transfer: CircuitEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delegated: Option<CircuitEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CircuitEntry {
+    /// Raw 64-char hex, as returned by r14-core `register`
+    pub circuit_id: String,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// Outcome of a single descriptor check
+#[derive(Serialize, Clone, Debug)]
+pub struct Finding {
+    pub check: String,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl Finding {
+    pub fn ok(check: &str, message: impl Into<String>) -> Self {
+        Self {
+            check: check.into(),
+            severity: Severity::Ok,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub fn warn(check: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            check: check.into(),
+            severity: Severity::Warn,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    pub fn fail(check: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            check: check.into(),
+            severity: Severity::Fail,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+impl Deployment {
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        toml::from_str(s).context("invalid r14.toml")
+    }
+
+    pub fn to_toml_string(&self) -> Result<String> {
+        toml::to_string_pretty(self).context("failed to serialize deployment")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_toml_str(&data).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// Locate the descriptor: `$R14_DEPLOYMENT`, else the nearest `r14.toml`
+    /// in the current directory or its ancestors.
+    pub fn discover() -> Result<Option<(PathBuf, Self)>> {
+        if let Ok(path) = std::env::var(DESCRIPTOR_ENV) {
+            let path = PathBuf::from(path);
+            let d = Self::load(&path)?;
+            return Ok(Some((path, d)));
+        }
+        let cwd = std::env::current_dir().context("failed to read current dir")?;
+        for dir in cwd.ancestors() {
+            let path = dir.join(DESCRIPTOR_FILE);
+            if path.is_file() {
+                let d = Self::load(&path)?;
+                return Ok(Some((path, d)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The network contract calls go to: `network` through `rpc_url`
+    pub fn network_config(&self) -> Network {
+        let network = Network::named(&self.network).with_rpc_url(&self.rpc_url);
+        match &self.network_passphrase {
+            Some(passphrase) => network.with_passphrase(passphrase),
+            None => network,
+        }
+    }
+
+    /// Make `hash` this process's hash family. Call it before anything
+    /// hashes; see [`r14_poseidon::set_hash_family`].
+    pub fn select_hash_family(&self) -> Result<()> {
+        r14_poseidon::set_hash_family(self.hash).map_err(|current| {
+            anyhow::anyhow!("descriptor uses {}, but this process already hashes with {}", self.hash.name(), current.name())
+        })
+    }
+
+    /// Static checks that need no network access
+    pub fn lint(&self) -> Vec<Finding> {
+        let mut out = vec![];
+
+        for (name, id) in [("core", &self.contracts.core), ("transfer", &self.contracts.transfer)] {
+            let check = format!("contracts.{name}");
+            if is_contract_id(id) {
+                out.push(Finding::ok(&check, id.clone()));
+            } else {
+                out.push(Finding::fail(
+                    &check,
+                    format!("`{id}` is not a contract ID"),
+                    "expected a 56-char strkey starting with C",
+                ));
+            }
+        }
+
+        let circuits = [("transfer", Some(&self.circuits.transfer)), ("delegated", self.circuits.delegated.as_ref())];
+        for (name, entry) in circuits {
+            let Some(entry) = entry else { continue };
+            let check = format!("circuits.{name}");
+            if is_circuit_id(&entry.circuit_id) {
+                out.push(Finding::ok(&check, entry.circuit_id.clone()));
+            } else {
+                out.push(Finding::fail(
+                    &check,
+                    format!("`{}` is not a circuit_id", entry.circuit_id),
+                    "expected 64 hex chars (no 0x) as returned by r14-core `register`",
+                ));
+            }
+        }
+
+        if self.hash == hash_family() {
+            out.push(Finding::ok("hash", self.hash.name()));
+        } else {
+            out.push(Finding::fail(
+                "hash",
+                format!("descriptor uses {}, this process hashes with {}", self.hash.name(), hash_family().name()),
+                "call `Deployment::select_hash_family` before anything hashes",
+            ));
+        }
+
+        for (name, url) in [("rpc_url", &self.rpc_url), ("indexer_url", &self.indexer_url)] {
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                out.push(Finding::fail(
+                    name,
+                    format!("`{url}` is not an http(s) URL"),
+                    "use a full URL, e.g. http://localhost:3000",
+                ));
+            }
+        }
+
+        out
+    }
+
+    /// Compare a wallet's configuration against the descriptor
+    pub fn check_wallet(&self, wallet: &WalletData) -> Vec<Finding> {
+        let pairs = [
+            ("core_contract_id", &wallet.core_contract_id, &self.contracts.core),
+            ("transfer_contract_id", &wallet.transfer_contract_id, &self.contracts.transfer),
+            ("indexer_url", &wallet.indexer_url, &self.indexer_url),
+            ("rpc_url", &wallet.rpc_url, &self.rpc_url),
+            ("network", &wallet.network, &self.network),
+        ];
+        pairs
+            .into_iter()
+            .map(|(key, have, want)| {
+                let check = format!("wallet.{key}");
+                if have == want {
+                    Finding::ok(&check, "matches descriptor")
+                } else {
+                    Finding::fail(
+                        &check,
+                        format!("wallet has `{have}`, descriptor has `{want}`"),
+                        format!("run `r14 config set {key} {want}`"),
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Recompute the indexer's root from its leaves and compare
+    pub async fn check_indexer(&self, indexer: &IndexerClient) -> Vec<Finding> {
+        const CHECK: &str = "indexer";
+
+        let root = match indexer.root().await {
+            Ok(r) => crate::wallet::fr_to_raw_hex(&r),
+            Err(e) => {
+                return vec![Finding::fail(
+                    CHECK,
+                    format!("unreachable at {}: {e}", indexer.base_url()),
+                    "start r14-indexer or fix indexer_url",
+                )]
+            }
+        };
+        let leaves = match indexer.leaves().await {
+            Ok(l) => l,
+            Err(e) => {
+                return vec![Finding::fail(
+                    CHECK,
+                    format!("bad /v1/leaves response: {e}"),
+                    "check the indexer logs",
+                )]
+            }
+        };
+
+        let expected = crate::merkle::compute_root_from_leaves(&leaves);
+        if root == expected {
+            vec![Finding::ok(CHECK, format!("{} leaves, root consistent", leaves.len()))]
+        } else {
+            vec![Finding::fail(
+                CHECK,
+                format!("root {root} does not match its {} leaves", leaves.len()),
+                "the indexer DB is corrupt; stop it, delete its DB, and resync",
+            )]
+        }
+    }
+
+    /// Query r14-core and r14-transfer for the registered circuits.
+    ///
+    /// Uses `stellar contract invoke` in simulation, so `source_secret`
+    /// only needs to be a funded account on the network.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn check_contracts(&self, source_secret: &str) -> Vec<Finding> {
+        let mut out = vec![];
+        let network = self.network_config();
+
+        let mut circuits = vec![("transfer", &self.circuits.transfer)];
+        if let Some(d) = &self.circuits.delegated {
+            circuits.push(("delegated", d));
+        }
+        for (name, entry) in circuits {
+            let check = format!("core.{name}_registered");
+            let res = crate::soroban::invoke_contract(
+                &self.contracts.core,
+                &network,
+                source_secret,
+                "is_registered",
+                &[("circuit_id", &entry.circuit_id)],
+            )
+            .await;
+            out.push(match res.as_deref() {
+                Ok("true") => Finding::ok(&check, "registered"),
+                Ok(other) => Finding::fail(
+                    &check,
+                    format!("circuit {} not registered ({other})", entry.circuit_id),
+                    match name {
+                        "delegated" => "run init_delegated, then update circuits.delegated",
+                        _ => "run `r14 init-contract`, then update circuits.transfer",
+                    },
+                ),
+                Err(e) => Finding::fail(&check, format!("{e:#}"), "check contracts.core and network"),
+            });
+        }
+
+        let check = "transfer.initialized";
+        let res = crate::soroban::invoke_contract(
+            &self.contracts.transfer,
+            &network,
+            source_secret,
+            "circuit_id",
+            &[],
+        )
+        .await;
+        out.push(match res {
+            Ok(raw) => {
+                let onchain = raw.trim_matches('"');
+                if onchain == self.circuits.transfer.circuit_id {
+                    Finding::ok(check, "bound to circuits.transfer")
+                } else {
+                    Finding::fail(
+                        check,
+                        format!("transfer contract uses circuit {onchain}"),
+                        "update circuits.transfer or redeploy the transfer contract",
+                    )
+                }
+            }
+            Err(e) => Finding::fail(
+                check,
+                format!("{e:#}"),
+                "run `r14 init-contract` if the contract was never initialized",
+            ),
+        });
+
+        out
+    }
+
+    /// Recompute circuit_ids from the keys this machine proves with (the
+    /// installed ones, else the deterministic setup) and compare, so a
+    /// descriptor pinned to an older circuit version is caught locally.
+    #[cfg(all(feature = "prove", not(target_arch = "wasm32")))]
+    pub fn check_local_vks(&self) -> Vec<Finding> {
+        use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+        let setup = crate::artifacts::transfer_keys();
+        let mut out = vec![compare_local_vk("vk.transfer", &self.circuits.transfer, setup)];
+        if let Some(entry) = &self.circuits.delegated {
+            let setup = crate::prove::setup_delegated(&mut StdRng::seed_from_u64(
+                crate::client::DELEGATED_SETUP_SEED,
+            ));
+            out.push(compare_local_vk("vk.delegated", entry, setup));
+        }
+        out
+    }
+}
+
+#[cfg(all(feature = "prove", not(target_arch = "wasm32")))]
+fn compare_local_vk<PK, E: std::fmt::Display>(
+    check: &str,
+    entry: &CircuitEntry,
+    setup: Result<(PK, ark_groth16::VerifyingKey<ark_bls12_381::Bls12_381>), E>,
+) -> Finding {
+    match setup {
+        Ok((_, vk)) => compare_vk(
+            check,
+            entry,
+            &crate::serialize::circuit_id(&crate::prove::serialize_vk_for_soroban(&vk, crate::MERKLE_DEPTH)),
+        ),
+        Err(e) => Finding::fail(check, format!("local setup failed: {e:#}"), "rebuild r14 from a clean checkout"),
+    }
+}
+
+#[cfg(all(feature = "prove", not(target_arch = "wasm32")))]
+fn compare_vk(check: &str, entry: &CircuitEntry, local: &str) -> Finding {
+    if entry.circuit_id == local {
+        Finding::ok(check, "local VK hash matches")
+    } else {
+        Finding::fail(
+            check,
+            format!("local VK hashes to {local}, descriptor pins {}", entry.circuit_id),
+            "this build's circuit differs from the deployed one; upgrade or re-register",
+        )
+    }
+}
+
+fn is_default_hash(h: &HashFamily) -> bool {
+    *h == HashFamily::default()
+}
+
+fn is_contract_id(s: &str) -> bool {
+    s.len() == 56 && s.starts_with('C') && s.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+fn is_circuit_id(s: &str) -> bool {
+    s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Hash family for a tool configured with `explicit` (its flag or
+/// `R14_HASH`), else the discovered descriptor's, else the default. An
+/// explicit family that contradicts the descriptor is an error.
+pub fn resolve_hash_family(explicit: Option<HashFamily>) -> Result<HashFamily> {
+    let descriptor = Deployment::discover()?;
+    pick_hash_family(explicit, descriptor.as_ref().map(|(path, d)| (path.as_path(), d)))
+}
+
+fn pick_hash_family(explicit: Option<HashFamily>, descriptor: Option<(&Path, &Deployment)>) -> Result<HashFamily> {
+    match (explicit, descriptor) {
+        (Some(hash), Some((path, d))) => {
+            anyhow::ensure!(
+                hash == d.hash,
+                "hash family {} but {} pins {}",
+                hash.name(),
+                path.display(),
+                d.hash.name()
+            );
+            Ok(hash)
+        }
+        (Some(hash), None) => Ok(hash),
+        (None, descriptor) => Ok(descriptor.map(|(_, d)| d.hash).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORE: &str = "CAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+    const XFER: &str = "CBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBB";
+
+    fn sample() -> String {
+        format!(
+            r#"
+network = "testnet"
+rpc_url = "https://soroban-testnet.stellar.org:443"
+indexer_url = "http://localhost:3000"
+hash = "{}"
+
+[contracts]
+core = "{CORE}"
+transfer = "{XFER}"
+
+[circuits.transfer]
+circuit_id = "{}"
+"#,
+            hash_family().name(),
+            "ab".repeat(32)
+        )
+    }
+
+    #[test]
+    fn parse_and_roundtrip() {
+        let d = Deployment::from_toml_str(&sample()).unwrap();
+        assert_eq!(d.contracts.core, CORE);
+        assert!(d.circuits.delegated.is_none());
+        let again = Deployment::from_toml_str(&d.to_toml_string().unwrap()).unwrap();
+        assert_eq!(d, again);
+    }
+
+    #[test]
+    fn explicit_hash_family_must_match_the_descriptor() {
+        let mut d = Deployment::from_toml_str(&sample()).unwrap();
+        d.hash = HashFamily::Poseidon2;
+        let descriptor = Some((Path::new("r14.toml"), &d));
+        assert_eq!(pick_hash_family(None, descriptor).unwrap(), HashFamily::Poseidon2);
+        assert_eq!(pick_hash_family(Some(HashFamily::Poseidon2), descriptor).unwrap(), HashFamily::Poseidon2);
+        let err = pick_hash_family(Some(HashFamily::Circom), descriptor).unwrap_err();
+        assert!(err.to_string().contains("r14.toml pins poseidon2"), "{err}");
+        assert_eq!(pick_hash_family(Some(HashFamily::Circom), None).unwrap(), HashFamily::Circom);
+        assert_eq!(pick_hash_family(None, None).unwrap(), HashFamily::default());
+    }
+
+    #[test]
+    fn parses_published_artifacts() {
+        assert!(Deployment::from_toml_str(&sample()).unwrap().artifacts.is_none());
+
+        let toml = format!(
+            r#"{}
+[artifacts.transfer]
+proving_key = {{ url = "https://example.org/transfer.pk", sha256 = "{}" }}
+verifying_key = {{ url = "https://example.org/transfer.vk.json", sha256 = "{}" }}
+"#,
+            sample(),
+            "11".repeat(32),
+            "22".repeat(32)
+        );
+        let d = Deployment::from_toml_str(&toml).unwrap();
+        let keys = &d.artifacts.as_ref().unwrap().transfer;
+        assert_eq!(keys.proving_key.url, "https://example.org/transfer.pk");
+        assert_eq!(keys.verifying_key.sha256, "22".repeat(32));
+        assert_eq!(Deployment::from_toml_str(&d.to_toml_string().unwrap()).unwrap(), d);
+    }
+
+    #[test]
+    fn lint_flags_bad_ids() {
+        let mut d = Deployment::from_toml_str(&sample()).unwrap();
+        assert!(d.lint().iter().all(|f| f.severity == Severity::Ok));
+
+        d.contracts.transfer = "PLACEHOLDER".into();
+        d.circuits.transfer.circuit_id = "0xabc".into();
+        let failed: Vec<_> = d
+            .lint()
+            .into_iter()
+            .filter(|f| f.severity == Severity::Fail)
+            .map(|f| f.check)
+            .collect();
+        assert_eq!(failed, ["contracts.transfer", "circuits.transfer"]);
+    }
+
+    #[test]
+    fn lint_flags_other_hash_family() {
+        let hash_line = format!("hash = \"{}\"\n", hash_family().name());
+        let d = Deployment::from_toml_str(&sample().replace(&hash_line, "")).unwrap();
+        assert_eq!(d.hash, HashFamily::Poseidon);
+
+        let other = match hash_family() {
+            HashFamily::Poseidon => HashFamily::Circom,
+            HashFamily::Poseidon2 | HashFamily::Circom => HashFamily::Poseidon,
+        };
+        let toml = sample().replace(&hash_line, &format!("hash = \"{}\"\n", other.name()));
+        let d = Deployment::from_toml_str(&toml).unwrap();
+        assert_eq!(d.hash, other);
+        let hash = d.lint().into_iter().find(|f| f.check == "hash").unwrap();
+        assert_eq!(hash.severity, Severity::Fail);
+    }
+
+    #[test]
+    fn missing_section_is_an_error() {
+        assert!(Deployment::from_toml_str("network = \"testnet\"").is_err());
+    }
+}
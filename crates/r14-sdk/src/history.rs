@@ -0,0 +1,262 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Transaction history kept in the wallet.
+//!
+//! Notes say what the wallet holds now; the history says how it got there.
+//! Each submitted deposit or transfer appends a [`HistoryEntry`] to
+//! [`WalletData::history`](crate::wallet::WalletData::history) with its
+//! time, the submission's result, the nullifier it published, and the
+//! commitments of the notes it created. Entries are only appended once a
+//! submission succeeds, so a rolled-back transfer leaves none. A pending
+//! transfer confirmed later by `R14Client::resolve_pending` gets an entry
+//! then, without a submission result. A key rotation records each sweep
+//! as a transfer, then one [`HistoryKind::Rotation`] entry.
+//!
+//! [`query`] pages through it newest first:
+//!
+//! ```rust
+//! use r14_sdk::history::{query, HistoryEntry, HistoryKind, HistoryQuery};
+//! use r14_sdk::Commitment;
+//! use ark_bls12_381::Fr;
+//!
+//! let history: Vec<HistoryEntry> = (1..=5)
+//!     .map(|v| HistoryEntry::deposit(v, 1, Commitment(Fr::from(v)), "ok"))
+//!     .collect();
+//! let page = query(&history, &HistoryQuery { limit: Some(2), ..Default::default() });
+//! assert_eq!(page.entries.iter().map(|e| e.value).collect::<Vec<_>>(), [5, 4]);
+//! assert_eq!((page.total, page.next_offset), (5, Some(2)));
+//!
+//! let transfers = HistoryQuery { kind: Some(HistoryKind::Transfer), ..Default::default() };
+//! assert_eq!(query(&history, &transfers).total, 0);
+//! ```
+
+use r14_types::Commitment;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryKind {
+    Deposit,
+    Transfer,
+    /// The wallet moved to a new key; see `R14Client::rotate_key`
+    Rotation,
+}
+
+impl std::fmt::Display for HistoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Deposit => "deposit",
+            Self::Transfer => "transfer",
+            Self::Rotation => "rotation",
+        })
+    }
+}
+
+impl std::str::FromStr for HistoryKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "deposit" => Ok(Self::Deposit),
+            "transfer" => Ok(Self::Transfer),
+            "rotation" => Ok(Self::Rotation),
+            other => Err(format!("unknown transaction kind `{other}` (deposit, transfer, rotation)")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub kind: HistoryKind,
+    /// Unix seconds when the submission succeeded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Amount deposited, or sent to the recipient (change excluded); for
+    /// a rotation, everything swept to the new key
+    pub value: u64,
+    pub app_tag: u32,
+    /// What the submission returned; `None` for transfers recovered from
+    /// the pending list and for rotations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_result: Option<String>,
+    /// Nullifier the transaction published (transfers only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nullifier: Option<String>,
+    /// Notes the transaction created: the deposit, or recipient then change
+    pub commitments: Vec<Commitment>,
+    /// Recipient's owner hash (transfers), or the new owner hash
+    /// (rotations)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn deposit(value: u64, app_tag: u32, commitment: Commitment, tx_result: impl Into<String>) -> Self {
+        Self {
+            kind: HistoryKind::Deposit,
+            timestamp: crate::wallet::now_unix(),
+            value,
+            app_tag,
+            tx_result: Some(tx_result.into()),
+            nullifier: None,
+            commitments: vec![commitment],
+            counterparty: None,
+        }
+    }
+
+    /// `outputs` are the recipient's note then the change
+    pub fn transfer(
+        value: u64,
+        app_tag: u32,
+        nullifier: impl Into<String>,
+        outputs: [Commitment; 2],
+        counterparty: impl Into<String>,
+        tx_result: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind: HistoryKind::Transfer,
+            timestamp: crate::wallet::now_unix(),
+            value,
+            app_tag,
+            tx_result: Some(tx_result.into()),
+            nullifier: Some(nullifier.into()),
+            commitments: outputs.to_vec(),
+            counterparty: Some(counterparty.into()),
+        }
+    }
+
+    /// A key rotation to `new_owner`, after its sweeps (each recorded as
+    /// a transfer) landed. `commitments` are the swept notes; the
+    /// app tag is 0 since they can span assets.
+    pub fn rotation(value: u64, commitments: Vec<Commitment>, new_owner: impl Into<String>) -> Self {
+        Self {
+            kind: HistoryKind::Rotation,
+            timestamp: crate::wallet::now_unix(),
+            value,
+            app_tag: 0,
+            tx_result: None,
+            nullifier: None,
+            commitments,
+            counterparty: Some(new_owner.into()),
+        }
+    }
+
+    /// Entry for a pending transfer found to have landed
+    pub fn from_pending(pending: &crate::pending::PendingTransfer) -> Self {
+        let recipient = pending.outputs.first();
+        Self {
+            kind: HistoryKind::Transfer,
+            timestamp: crate::wallet::now_unix(),
+            value: recipient.map_or(0, |n| n.value),
+            app_tag: recipient.map_or(0, |n| n.app_tag),
+            tx_result: None,
+            nullifier: Some(pending.nullifier.clone()),
+            commitments: pending.outputs.iter().map(|n| n.commitment).collect(),
+            counterparty: recipient.and_then(|n| n.meta.counterparty.clone()),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<&crate::client::DepositResult> for HistoryEntry {
+    fn from(r: &crate::client::DepositResult) -> Self {
+        Self::deposit(r.value, r.app_tag, r.commitment, r.tx_result.clone())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<&crate::client::TransferResult> for HistoryEntry {
+    fn from(r: &crate::client::TransferResult) -> Self {
+        Self::transfer(
+            r.recipient_note.value,
+            r.recipient_note.app_tag,
+            r.nullifier.clone(),
+            [r.out_commitment_0, r.out_commitment_1],
+            r.recipient_note.meta.counterparty.clone().unwrap_or_default(),
+            r.tx_result.clone(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HistoryQuery {
+    /// Only this kind; `None` for all
+    pub kind: Option<HistoryKind>,
+    /// Matching entries to skip, newest first
+    pub offset: usize,
+    /// Page size; `None` for everything after `offset`
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct HistoryPage<'a> {
+    /// Newest first
+    pub entries: Vec<&'a HistoryEntry>,
+    /// Entries matching the query across all pages
+    pub total: usize,
+    /// Offset of the next page, if there is one
+    pub next_offset: Option<usize>,
+}
+
+/// One page of `history` (oldest first, as stored) matching `q`
+pub fn query<'a>(history: &'a [HistoryEntry], q: &HistoryQuery) -> HistoryPage<'a> {
+    let matching: Vec<&HistoryEntry> = history
+        .iter()
+        .rev()
+        .filter(|e| q.kind.is_none_or(|k| e.kind == k))
+        .collect();
+    let total = matching.len();
+    let entries: Vec<_> = matching
+        .into_iter()
+        .skip(q.offset)
+        .take(q.limit.unwrap_or(usize::MAX))
+        .collect();
+    let end = q.offset.saturating_add(entries.len());
+    HistoryPage {
+        entries,
+        total,
+        next_offset: (end < total).then_some(end),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn cm(v: u64) -> Commitment {
+        Commitment(Fr::from(v))
+    }
+
+    #[test]
+    fn pages_newest_first_by_kind() {
+        let history = vec![
+            HistoryEntry::deposit(100, 1, cm(1), "a"),
+            HistoryEntry::transfer(30, 1, "0x01", [cm(2), cm(3)], "0xbob", "b"),
+            HistoryEntry::deposit(50, 1, cm(4), "c"),
+            HistoryEntry::transfer(20, 1, "0x02", [cm(5), cm(6)], "0xbob", "d"),
+        ];
+        let page = |kind, offset, limit| {
+            let p = query(&history, &HistoryQuery { kind, offset, limit });
+            (p.entries.iter().map(|e| e.tx_result.as_deref().unwrap()).collect::<Vec<_>>(), p.total, p.next_offset)
+        };
+        assert_eq!(page(None, 0, Some(3)), (vec!["d", "c", "b"], 4, Some(3)));
+        assert_eq!(page(None, 3, Some(3)), (vec!["a"], 4, None));
+        assert_eq!(page(Some(HistoryKind::Deposit), 0, None), (vec!["c", "a"], 2, None));
+        assert_eq!(page(Some(HistoryKind::Transfer), 1, Some(1)), (vec!["b"], 2, None));
+        assert_eq!(page(None, 9, Some(3)), (vec![], 4, None));
+    }
+
+    #[test]
+    fn serde_omits_empty_fields() {
+        let json = serde_json::to_value(HistoryEntry::deposit(5, 1, cm(7), "ok")).unwrap();
+        assert_eq!(json["kind"], "deposit");
+        assert!(json.get("nullifier").is_none());
+        assert_eq!(json["commitments"][0], r14_types::hex::encode(&Fr::from(7u64)));
+
+        let back: HistoryEntry =
+            serde_json::from_str(r#"{"kind":"transfer","value":1,"app_tag":1,"commitments":[]}"#).unwrap();
+        assert_eq!((back.kind, back.timestamp, back.tx_result), (HistoryKind::Transfer, None, None));
+    }
+}
@@ -0,0 +1,679 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Typed client for the `r14-indexer` REST API.
+//!
+//! Every method maps transport failures, timeouts, non-2xx statuses, and
+//! malformed bodies to [`R14Error::Indexer`], carrying the indexer's own
+//! error message when it returned an [`ErrorBody`]. With a
+//! [`RetryPolicy`], transport failures, timeouts and 5xx responses are
+//! retried first.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use r14_sdk::indexer::IndexerClient;
+//!
+//! # async fn example() -> r14_sdk::error::R14Result<()> {
+//! let indexer = IndexerClient::with_timeout("http://localhost:3000", Duration::from_secs(3));
+//! let root = indexer.root().await?;
+//! # let commitment = r14_sdk::Commitment(ark_bls12_381::Fr::from(1u64));
+//! if let Some(leaf) = indexer.leaf(&commitment).await? {
+//!     let path = indexer.merkle_proof(leaf.index).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use ark_bls12_381::Fr;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::absence::NullifierAbsence;
+use crate::error::{ErrorBody, R14Error, R14Result};
+use crate::retry::RetryPolicy;
+use crate::wallet::fr_to_raw_hex;
+use crate::{Commitment, LeafIndex, MerklePath, MerkleRoot, Nullifier};
+
+/// Per-request timeout used by [`IndexerClient::new`]
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Most leaves one [`IndexerClient::merkle_proofs`] call can ask for
+pub const MAX_BATCH_PROOFS: usize = 256;
+
+/// `User-Agent` sent unless the caller sets one
+pub const DEFAULT_USER_AGENT: &str = concat!("r14-sdk/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Clone, Debug)]
+pub struct IndexerClient {
+    base_url: String,
+    http: reqwest::Client,
+    timeout: Duration,
+    retry: RetryPolicy,
+    user_agent: String,
+}
+
+/// `/v1/leaf/{commitment}` — where a commitment sits in the tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct Leaf {
+    pub index: LeafIndex,
+    pub block_height: u64,
+    /// Still waiting out the indexer's confirmation depth; `false` from an
+    /// indexer that predates the field
+    #[serde(default)]
+    pub pending: bool,
+}
+
+/// `/v1/nullifier/{nullifier}` — when a nullifier was spent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct SpentNullifier {
+    pub block_height: u64,
+}
+
+/// `/v1/leaves?from=N` — the leaves at position `from` and after
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct LeavesPage {
+    /// Position of `leaves[0]`; 0 from an indexer that ignores `from` and
+    /// sends every leaf
+    #[serde(default)]
+    pub from: LeafIndex,
+    pub leaves: Vec<Commitment>,
+    /// Root of the indexer's whole tree when it sent the page; `None` from
+    /// an indexer that predates the field
+    #[serde(default)]
+    pub root: Option<MerkleRoot>,
+    /// Leaves before this index (counting the whole tree) are confirmed,
+    /// the rest pending; `None` from an indexer that predates the field
+    #[serde(default)]
+    pub confirmed: Option<u64>,
+    /// Where the next page starts when a limit cut this one short; `None`
+    /// once `leaves` reaches the end of the tree
+    #[serde(default)]
+    pub next: Option<LeafIndex>,
+}
+
+/// Which leaves [`IndexerClient::leaves_page`] asks for; every leaf by
+/// default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LeavesQuery {
+    /// First leaf to send
+    pub from: LeafIndex,
+    /// Most leaves to send, 1 to 10000
+    pub limit: Option<usize>,
+    /// Skip the leaves indexed before this ledger
+    pub since_ledger: Option<u64>,
+}
+
+/// `/v1/stats` — totals over what the indexer has indexed
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct IndexerStats {
+    pub leaf_count: u64,
+    pub nullifier_count: u64,
+    /// Deposit and transfer events indexed; history the indexer imported
+    /// from a snapshot isn't counted
+    pub deposits: u64,
+    pub transfers: u64,
+    /// Ledger of the first leaf; `None` while there are none
+    pub first_ledger: Option<u64>,
+    /// Ledger indexing has reached; `None` before the first poll
+    pub last_ledger: Option<u64>,
+    /// Latest network ledger the indexer has seen
+    pub network_ledger: u64,
+    pub root: MerkleRoot,
+}
+
+/// `/v1/roots` and `/v1/root/at/{leaf_count}` — a root the indexer's tree
+/// passed through
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct HistoricalRoot {
+    pub root: MerkleRoot,
+    /// Leaves the tree held at this root
+    pub leaf_count: u64,
+    /// Ledger of the contract call that set it
+    pub ledger: u64,
+}
+
+/// `/v1/proof/by-commitment/{commitment}` — a leaf and its Merkle path,
+/// read together
+#[derive(Clone, Debug, Deserialize)]
+pub struct CommitmentProof {
+    pub index: LeafIndex,
+    pub block_height: u64,
+    #[serde(default)]
+    pub pending: bool,
+    /// Root `path` leads to
+    pub root: MerkleRoot,
+    /// Leaves in the tree `root` is the root of
+    pub leaf_count: u64,
+    pub path: MerklePath,
+}
+
+/// `POST /v1/proofs` — Merkle paths for several leaves, all to `root`
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProofBatch {
+    pub root: MerkleRoot,
+    /// Leaves in the tree `root` is the root of
+    pub leaf_count: u64,
+    /// In the order the leaves were asked for
+    pub proofs: Vec<BatchProof>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BatchProof {
+    pub index: LeafIndex,
+    pub path: MerklePath,
+}
+
+/// Event types [`IndexerClient::subscribe`] can ask for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventType {
+    Leaf,
+    Root,
+    Nullifier,
+    Rollback,
+}
+
+impl EventType {
+    /// Name in `/v1/subscribe?types=`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Leaf => "leaf",
+            Self::Root => "root",
+            Self::Nullifier => "nullifier",
+            Self::Rollback => "rollback",
+        }
+    }
+}
+
+/// `/v1/subscribe` — one update from the indexer
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndexerEvent {
+    Leaf {
+        index: LeafIndex,
+        commitment: Commitment,
+        block_height: u64,
+    },
+    /// Sent once per indexed batch
+    Root { root: MerkleRoot, leaf_count: u64 },
+    Nullifier { nullifier: Nullifier, block_height: u64 },
+    /// Everything after `ledger` was dropped; the tree is back to
+    /// `leaf_count` leaves and `root`
+    Rollback { ledger: u64, leaf_count: u64, root: MerkleRoot },
+    /// The subscription fell behind and `skipped` events were dropped;
+    /// resync the way a reconnecting client would
+    Lagged { skipped: u64 },
+    /// A type from a newer indexer
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+struct RootsResponse {
+    roots: Vec<HistoricalRoot>,
+}
+
+#[derive(Deserialize)]
+struct RootResponse {
+    root: MerkleRoot,
+    #[serde(default)]
+    ledger: u64,
+}
+
+#[derive(Deserialize)]
+struct LeavesResponse {
+    leaves: Vec<Commitment>,
+}
+
+impl IndexerClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_timeout(base_url, DEFAULT_TIMEOUT)
+    }
+
+    /// Client whose requests fail with `R14Error::Indexer` after `timeout`
+    pub fn with_timeout(base_url: &str, timeout: Duration) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new()).timeout(timeout)
+    }
+
+    /// Send requests through `http` (proxies, TLS roots, connection pool).
+    /// The per-request timeout and user agent set here still apply.
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+            timeout: DEFAULT_TIMEOUT,
+            retry: RetryPolicy::none(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+
+    /// Fail each attempt with `R14Error::Indexer` after `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Retry transport failures, timeouts, and 5xx responses
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `/v1/health` — `Ok` when the indexer is up
+    pub async fn health(&self) -> R14Result<()> {
+        self.get::<serde_json::Value>("/v1/health").await.map(|_| ())
+    }
+
+    /// `/v1/stats` — leaf and nullifier counts, deposits and transfers
+    /// indexed, and the ledgers indexing spans
+    pub async fn stats(&self) -> R14Result<IndexerStats> {
+        self.get("/v1/stats").await
+    }
+
+    /// `/v1/root` — current root of the indexer's tree
+    pub async fn root(&self) -> R14Result<Fr> {
+        let resp: RootResponse = self.get("/v1/root").await?;
+        Ok(resp.root.0)
+    }
+
+    /// `/v1/root` — latest network ledger the indexer has polled; 0 until
+    /// its first poll, or from an indexer that predates the field
+    pub async fn latest_ledger(&self) -> R14Result<u64> {
+        let resp: RootResponse = self.get("/v1/root").await?;
+        Ok(resp.ledger)
+    }
+
+    /// `/v1/roots?limit=N` — the last `limit` roots, newest first. The
+    /// transfer contract accepts proofs against its last 100.
+    pub async fn roots(&self, limit: usize) -> R14Result<Vec<HistoricalRoot>> {
+        let resp: RootsResponse = self.get(&format!("/v1/roots?limit={limit}")).await?;
+        Ok(resp.roots)
+    }
+
+    /// `/v1/root/at/{leaf_count}` — the root the tree had at `leaf_count`
+    /// leaves; `None` if no contract call ended there
+    pub async fn root_at(&self, leaf_count: u64) -> R14Result<Option<HistoricalRoot>> {
+        let path = format!("/v1/root/at/{leaf_count}");
+        match self.send(&path).await? {
+            resp if resp.status() == StatusCode::NOT_FOUND => Ok(None),
+            resp => decode(&path, resp).await.map(Some),
+        }
+    }
+
+    /// `/v1/leaf/{commitment}` — `None` if the commitment isn't indexed yet
+    pub async fn leaf(&self, commitment: &Commitment) -> R14Result<Option<Leaf>> {
+        let path = format!("/v1/leaf/{}", fr_to_raw_hex(&commitment.0));
+        match self.send(&path).await? {
+            resp if resp.status() == StatusCode::NOT_FOUND => Ok(None),
+            resp => decode(&path, resp).await.map(Some),
+        }
+    }
+
+    /// `/v1/nullifier/{nullifier}` — `None` if the nullifier hasn't been spent
+    pub async fn nullifier(&self, nullifier: &Nullifier) -> R14Result<Option<SpentNullifier>> {
+        let path = format!("/v1/nullifier/{}", fr_to_raw_hex(&nullifier.0));
+        match self.send(&path).await? {
+            resp if resp.status() == StatusCode::NOT_FOUND => Ok(None),
+            resp => decode(&path, resp).await.map(Some),
+        }
+    }
+
+    /// `/v1/nullifier/{nullifier}/absence` — the indexer's signed statement
+    /// that `nullifier` is unspent, checked to be about `nullifier` and
+    /// signed with `trusted_key`. Fails if the nullifier is spent or the
+    /// indexer has no signing key.
+    pub async fn nullifier_absence(
+        &self,
+        nullifier: &Nullifier,
+        trusted_key: &[u8; 32],
+    ) -> R14Result<NullifierAbsence> {
+        let path = format!("/v1/nullifier/{}/absence", fr_to_raw_hex(&nullifier.0));
+        let statement: NullifierAbsence = self.get(&path).await?;
+        if statement.nullifier != *nullifier {
+            return Err(R14Error::Indexer(format!("{path}: statement is about another nullifier")));
+        }
+        statement.verify(trusted_key)?;
+        Ok(statement)
+    }
+
+    /// `/v1/proof/{index}` — Merkle path from leaf `index` to the current root
+    pub async fn merkle_proof(&self, index: LeafIndex) -> R14Result<MerklePath> {
+        self.get(&format!("/v1/proof/{index}")).await
+    }
+
+    /// `/v1/proof/by-commitment/{commitment}` — the leaf and its path in
+    /// one request, to the current root or to `root` if given. `None` if
+    /// the commitment isn't indexed yet or `root` isn't one the indexer
+    /// recorded.
+    pub async fn proof_by_commitment(
+        &self,
+        commitment: &Commitment,
+        root: Option<&MerkleRoot>,
+    ) -> R14Result<Option<CommitmentProof>> {
+        let mut path = format!("/v1/proof/by-commitment/{}", fr_to_raw_hex(&commitment.0));
+        if let Some(root) = root {
+            path.push_str(&format!("?root={}", fr_to_raw_hex(&root.0)));
+        }
+        match self.send(&path).await? {
+            resp if resp.status() == StatusCode::NOT_FOUND => Ok(None),
+            resp => decode(&path, resp).await.map(Some),
+        }
+    }
+
+    /// `POST /v1/proofs` — paths for up to [`MAX_BATCH_PROOFS`] leaves in
+    /// one request, all to the same root. `None` from an indexer that
+    /// predates the endpoint.
+    pub async fn merkle_proofs(&self, indices: &[LeafIndex]) -> R14Result<Option<ProofBatch>> {
+        self.post_proofs(serde_json::json!({ "indices": indices })).await
+    }
+
+    /// [`Self::merkle_proofs`] for leaves known by commitment
+    pub async fn merkle_proofs_by_commitment(&self, commitments: &[Commitment]) -> R14Result<Option<ProofBatch>> {
+        let commitments: Vec<String> = commitments.iter().map(|cm| fr_to_raw_hex(&cm.0)).collect();
+        self.post_proofs(serde_json::json!({ "commitments": commitments })).await
+    }
+
+    async fn post_proofs(&self, body: serde_json::Value) -> R14Result<Option<ProofBatch>> {
+        let path = "/v1/proofs";
+        let resp = self.send_with(path, Some(&body)).await?;
+        if !matches!(resp.status(), StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED) {
+            return decode(path, resp).await.map(Some);
+        }
+        // an unknown leaf comes with an error body; a missing route doesn't
+        let status = resp.status();
+        let bytes = resp.bytes().await.unwrap_or_default();
+        match serde_json::from_slice::<ErrorBody>(&bytes) {
+            Ok(_) => Err(status_error(path, status, &bytes)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// `/v1/leaves` — every indexed commitment in insertion order
+    pub async fn leaves(&self) -> R14Result<Vec<Commitment>> {
+        let resp: LeavesResponse = self.get("/v1/leaves").await?;
+        Ok(resp.leaves)
+    }
+
+    /// `/v1/leaves?from=N` — only the leaves a caller holding `from` of
+    /// them is missing, plus the root to check the result against
+    pub async fn leaves_from(&self, from: LeafIndex) -> R14Result<LeavesPage> {
+        self.get(&format!("/v1/leaves?from={from}")).await
+    }
+
+    /// `/v1/leaves?from_index=N&limit=L&since_ledger=S` — one page of the
+    /// leaves `query` picks. `page.from` is where it really starts: an
+    /// indexer that predates these parameters sends every leaf.
+    pub async fn leaves_page(&self, query: LeavesQuery) -> R14Result<LeavesPage> {
+        let mut path = format!("/v1/leaves?from_index={}", query.from);
+        if let Some(limit) = query.limit {
+            path = format!("{path}&limit={limit}");
+        }
+        if let Some(ledger) = query.since_ledger {
+            path = format!("{path}&since_ledger={ledger}");
+        }
+        self.get(&path).await
+    }
+
+    /// `/v1/subscribe?types=...` — updates as the indexer makes them, only
+    /// of `types` (every type if empty). Rollbacks and [`IndexerEvent::Lagged`]
+    /// always come through. The timeout covers opening the stream, which
+    /// then stays open until the indexer closes it.
+    ///
+    /// To learn of spends from another device without a request per note,
+    /// subscribe to [`EventType::Nullifier`] and look each one up in
+    /// [`crate::client::R14Client::watched_nullifiers`]. Every subscriber
+    /// gets every nullifier, so the indexer can't tell which are yours.
+    /// Native only: in a browser, use `EventSource`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn subscribe(&self, types: &[EventType]) -> R14Result<Subscription> {
+        let mut path = "/v1/subscribe".to_string();
+        if !types.is_empty() {
+            let names: Vec<_> = types.iter().map(|t| t.as_str()).collect();
+            path = format!("{path}?types={}", names.join(","));
+        }
+        let request = self
+            .http
+            .get(format!("{}{path}", self.base_url))
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .header(reqwest::header::USER_AGENT, &self.user_agent);
+        let response = match tokio::time::timeout(self.timeout, request.send()).await {
+            Ok(result) => result.map_err(|e| R14Error::IndexerUnreachable(format!("{path}: {e}")))?,
+            Err(_) => return Err(R14Error::IndexerUnreachable(format!("{path}: timed out"))),
+        };
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.bytes().await.unwrap_or_default();
+            return Err(status_error(&path, status, &body));
+        }
+        Ok(Subscription { response, buf: Vec::new() })
+    }
+
+    async fn send(&self, path: &str) -> R14Result<reqwest::Response> {
+        self.send_with(path, None).await
+    }
+
+    /// GET `path`, or POST `body` to it. Only read-only requests come
+    /// through here, so either is safe to retry.
+    async fn send_with(&self, path: &str, body: Option<&serde_json::Value>) -> R14Result<reqwest::Response> {
+        let url = format!("{}{path}", self.base_url);
+        let mut attempt = 1;
+        loop {
+            let request = match body {
+                Some(body) => self.http.post(&url).json(body),
+                None => self.http.get(&url),
+            };
+            let request = request.timeout(self.timeout);
+            // browsers set their own, and a custom one forces a CORS preflight
+            #[cfg(not(target_arch = "wasm32"))]
+            let request = request.header(reqwest::header::USER_AGENT, &self.user_agent);
+            let result = request.send().await;
+            // a 5xx may still carry an ErrorBody, so the last one is decoded
+            let transient = match &result {
+                Ok(resp) => resp.status().is_server_error(),
+                Err(_) => true,
+            };
+            if !transient || attempt >= self.retry.max_attempts {
+                return result.map_err(|e| R14Error::IndexerUnreachable(format!("{path}: {e}")));
+            }
+            crate::retry::sleep(self.retry.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> R14Result<T> {
+        let resp = self.send(path).await?;
+        decode(path, resp).await
+    }
+}
+
+/// Decode a 2xx body as `T`, or turn the error status into `R14Error::Indexer`
+async fn decode<T: DeserializeOwned>(path: &str, resp: reqwest::Response) -> R14Result<T> {
+    let status = resp.status();
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| R14Error::IndexerUnreachable(format!("{path}: {e}")))?;
+    if !status.is_success() {
+        return Err(status_error(path, status, &bytes));
+    }
+    serde_json::from_slice(&bytes)
+        .map_err(|e| R14Error::Indexer(format!("{path}: bad response: {e}")))
+}
+
+fn status_error(path: &str, status: StatusCode, body: &[u8]) -> R14Error {
+    match serde_json::from_slice::<ErrorBody>(body) {
+        Ok(body) => R14Error::Indexer(format!("{path}: {}", body.error)),
+        Err(_) => R14Error::Indexer(format!("{path}: HTTP {status}")),
+    }
+}
+
+/// Open `/v1/subscribe` stream from [`IndexerClient::subscribe`]
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Subscription {
+    response: reqwest::Response,
+    /// Received bytes not yet parsed into a whole message
+    buf: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Subscription {
+    /// Wait for the next event; `None` once the indexer closes the stream
+    pub async fn next(&mut self) -> R14Result<Option<IndexerEvent>> {
+        loop {
+            // messages end with a blank line
+            if let Some(end) = self.buf.windows(2).position(|w| w == b"\n\n") {
+                let message: Vec<u8> = self.buf.drain(..end + 2).collect();
+                match parse_message(&message)? {
+                    Some(event) => return Ok(Some(event)),
+                    None => continue,
+                }
+            }
+            let chunk = self
+                .response
+                .chunk()
+                .await
+                .map_err(|e| R14Error::IndexerUnreachable(format!("/v1/subscribe: {e}")))?;
+            match chunk {
+                Some(chunk) => self.buf.extend(chunk.iter().filter(|&&b| b != b'\r')),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+/// The event in one SSE message, or `None` for a keep-alive comment
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn parse_message(message: &[u8]) -> R14Result<Option<IndexerEvent>> {
+    let message = std::str::from_utf8(message)
+        .map_err(|_| R14Error::Indexer("/v1/subscribe: message is not UTF-8".into()))?;
+    let data: Vec<_> = message
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|d| d.strip_prefix(' ').unwrap_or(d))
+        .collect();
+    if data.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(&data.join("\n"))
+        .map(Some)
+        .map_err(|e| R14Error::Indexer(format!("/v1/subscribe: bad event: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    #[test]
+    fn trailing_slash_trimmed() {
+        let c = IndexerClient::new("http://localhost:3000/");
+        assert_eq!(c.base_url(), "http://localhost:3000");
+    }
+
+    #[test]
+    fn status_error_uses_indexer_message() {
+        let body = serde_json::to_vec(&ErrorBody::new(ErrorCode::InvalidInput, "invalid hex")).unwrap();
+        let err = status_error("/v1/leaf/zz", StatusCode::BAD_REQUEST, &body);
+        assert_eq!(err.code(), ErrorCode::Indexer);
+        assert_eq!(err.to_string(), "indexer: /v1/leaf/zz: invalid hex");
+    }
+
+    #[test]
+    fn status_error_without_body() {
+        let err = status_error("/v1/root", StatusCode::BAD_GATEWAY, b"<html>");
+        assert_eq!(err.to_string(), "indexer: /v1/root: HTTP 502 Bad Gateway");
+    }
+
+    #[test]
+    fn bad_field_elements_are_rejected() {
+        assert!(serde_json::from_str::<RootResponse>(r#"{"root":"0xzz"}"#).is_err());
+        let too_big = format!(r#"{{"leaves":["0x{}"]}}"#, "ff".repeat(32));
+        assert!(serde_json::from_str::<LeavesResponse>(&too_big).is_err());
+    }
+
+    #[test]
+    fn subscription_messages() {
+        assert_eq!(parse_message(b":\n\n").unwrap(), None);
+        let message = b"event: nullifier\ndata: {\"type\":\"nullifier\",\"nullifier\":\"0x2a\",\"block_height\":9}\n\n";
+        let expected = IndexerEvent::Nullifier { nullifier: Nullifier(Fr::from(42u64)), block_height: 9 };
+        assert_eq!(parse_message(message).unwrap(), Some(expected));
+        let lagged = b"event: lagged\ndata: {\"type\":\"lagged\",\"skipped\":3}\n\n";
+        assert_eq!(parse_message(lagged).unwrap(), Some(IndexerEvent::Lagged { skipped: 3 }));
+        let newer = b"data: {\"type\":\"receipt\",\"id\":1}\n\n";
+        assert_eq!(parse_message(newer).unwrap(), Some(IndexerEvent::Other));
+        assert!(parse_message(b"data: {\"type\":\"leaf\"}\n\n").is_err());
+    }
+
+    #[test]
+    fn leaves_page_from_older_indexer() {
+        let page: LeavesPage = serde_json::from_str(r#"{"leaves":["0x01"]}"#).unwrap();
+        assert_eq!((page.from, page.leaves.len(), page.root, page.next), (LeafIndex(0), 1, None, None));
+    }
+
+    /// Answers `statuses` in order, one connection each, and returns the
+    /// request heads it saw
+    async fn stub(statuses: &'static [u16]) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut heads = Vec::new();
+            for &status in statuses {
+                let (mut sock, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let n = sock.read(&mut buf).await.unwrap();
+                heads.push(String::from_utf8_lossy(&buf[..n]).to_lowercase());
+                let body = r#"{"status":"ok"}"#;
+                let resp = format!(
+                    "HTTP/1.1 {status} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                sock.write_all(resp.as_bytes()).await.unwrap();
+            }
+            heads
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_retried() {
+        let (url, server) = stub(&[503, 502, 200]).await;
+        let retry = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        };
+        let client = IndexerClient::new(&url).retry(retry).user_agent("test-agent/1");
+        client.health().await.unwrap();
+        let heads = server.await.unwrap();
+        assert_eq!(heads.len(), 3);
+        assert!(heads.iter().all(|h| h.contains("user-agent: test-agent/1")));
+    }
+
+    #[tokio::test]
+    async fn last_server_error_is_reported() {
+        let (url, server) = stub(&[503]).await;
+        let err = IndexerClient::new(&url).health().await.unwrap_err();
+        assert!(err.to_string().contains("503"), "{err}");
+        assert_eq!(server.await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn batch_proofs_from_older_indexer() {
+        let (url, server) = stub(&[404]).await;
+        assert!(IndexerClient::new(&url).merkle_proofs(&[LeafIndex(0)]).await.unwrap().is_none());
+        assert!(server.await.unwrap()[0].starts_with("post /v1/proofs"));
+    }
+}
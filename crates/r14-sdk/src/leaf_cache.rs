@@ -0,0 +1,247 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Persistent copy of the indexer's leaves.
+//!
+//! [`compute_new_root`](crate::merkle::compute_new_root) downloads every
+//! leaf on each deposit and transfer, so its cost grows with the pool. A
+//! [`LeafCache`] keeps the leaves it has seen, by position, together with
+//! the root they hash to, and asks the indexer only for the ones after
+//! (`/v1/leaves?from_index=N`), in pages of up to 10000.
+//!
+//! The indexer sends its root with each page. When the cached leaves plus
+//! the new ones don't hash to it (the indexer was rebuilt, or the cache
+//! file belongs to another pool), the cache refetches every leaf. A
+//! missing or unreadable cache file just means starting empty.
+//!
+//! ```rust,no_run
+//! use r14_sdk::leaf_cache::LeafCache;
+//! use r14_sdk::IndexerClient;
+//!
+//! # async fn example(cm: r14_sdk::Commitment) -> anyhow::Result<()> {
+//! let indexer = IndexerClient::new("http://localhost:3000");
+//! let mut cache = LeafCache::open_default()?;
+//! let new_root = cache.new_root(&indexer, &[cm]).await?;
+//! cache.save()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{R14Error, R14Result};
+use crate::indexer::{IndexerClient, LeavesQuery};
+use crate::merkle::SparseMerkleTree;
+use crate::wallet::fr_to_raw_hex;
+use crate::{Commitment, LeafIndex, MerkleRoot, MERKLE_DEPTH};
+
+/// Leaves per `/v1/leaves` request while syncing, the most the indexer sends
+const SYNC_PAGE_SIZE: usize = 10_000;
+
+/// `~/.r14/leaves.json`, next to the wallet
+pub fn default_path() -> Result<PathBuf> {
+    Ok(crate::wallet::wallet_path()?.with_file_name("leaves.json"))
+}
+
+/// The indexer's leaves as of the last [`LeafCache::sync`]
+#[derive(Default)]
+pub struct LeafCache {
+    tree: SparseMerkleTree,
+    path: Option<PathBuf>,
+}
+
+/// On-disk form: leaves in position order and the root they hash to
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    root: MerkleRoot,
+    leaves: Vec<Commitment>,
+}
+
+impl LeafCache {
+    /// Empty cache that [`save`](Self::save) doesn't write anywhere
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache kept at `path`. Starts empty if the file is missing, isn't a
+    /// cache, or its leaves don't hash to its root; fails only if the
+    /// file exists and can't be read.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let tree = match fs::read_to_string(&path) {
+            Ok(data) => load(&data).unwrap_or_default(),
+            Err(e) if e.kind() == ErrorKind::NotFound => SparseMerkleTree::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("cannot read leaf cache at {}", path.display()))
+            }
+        };
+        Ok(Self { tree, path: Some(path) })
+    }
+
+    /// [`open`](Self::open) at [`default_path`]
+    pub fn open_default() -> Result<Self> {
+        Self::open(default_path()?)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Number of cached leaves, i.e. the next position to fetch
+    pub fn len(&self) -> usize {
+        self.tree.next_index()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn root(&self) -> MerkleRoot {
+        self.tree.root()
+    }
+
+    pub fn leaves(&self) -> Vec<Commitment> {
+        self.tree.leaves()
+    }
+
+    /// The cached tree, e.g. for Merkle paths without asking the indexer
+    pub fn tree(&self) -> &SparseMerkleTree {
+        &self.tree
+    }
+
+    /// Fetch the leaves added since the last sync and return how many
+    /// leaves were downloaded (all of them when the cache had to be
+    /// rebuilt)
+    pub async fn sync(&mut self, indexer: &IndexerClient) -> R14Result<usize> {
+        let (mut fetched, root) = self.fetch(indexer).await?;
+        if root.is_none_or(|root| root == self.tree.root()) {
+            return Ok(fetched);
+        }
+
+        self.tree = SparseMerkleTree::new();
+        let (refetched, root) = self.fetch(indexer).await?;
+        fetched += refetched;
+        match root {
+            Some(root) if root != self.tree.root() => Err(R14Error::Indexer(
+                "/v1/leaves: leaves don't hash to the indexer's root (different hash family?)".into(),
+            )),
+            _ => Ok(fetched),
+        }
+    }
+
+    /// Append the indexer's leaves past the cached ones, a page at a time;
+    /// how many were downloaded and the root sent with the last page
+    async fn fetch(&mut self, indexer: &IndexerClient) -> R14Result<(usize, Option<MerkleRoot>)> {
+        let mut fetched = 0;
+        loop {
+            let query = LeavesQuery {
+                from: LeafIndex::from(self.len()),
+                limit: Some(SYNC_PAGE_SIZE),
+                since_ledger: None,
+            };
+            let page = indexer.leaves_page(query).await?;
+            fetched += page.leaves.len();
+            let done = page.next.is_none() || page.leaves.is_empty();
+            if page.from.as_usize() == self.len() {
+                for cm in page.leaves {
+                    self.tree.insert(cm);
+                }
+            } else {
+                // the indexer ignored `from_index` and sent every leaf
+                self.tree = SparseMerkleTree::from_leaves(page.leaves);
+            }
+            // the root covers the whole tree, so only the last page's can
+            // be checked
+            if done {
+                return Ok((fetched, page.root));
+            }
+        }
+    }
+
+    /// [`sync`](Self::sync), then the root after appending
+    /// `new_commitments`, as raw hex. Like
+    /// [`compute_new_root`](crate::merkle::compute_new_root), the new
+    /// commitments are not added to the cache.
+    pub async fn new_root(
+        &mut self,
+        indexer: &IndexerClient,
+        new_commitments: &[Commitment],
+    ) -> R14Result<String> {
+        self.sync(indexer).await?;
+        let mut tree = self.tree.clone();
+        for cm in new_commitments {
+            tree.insert(*cm);
+        }
+        Ok(fr_to_raw_hex(&tree.root().0))
+    }
+
+    /// Write the cache to its file; does nothing for [`LeafCache::new`]
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = CacheFile {
+            root: self.tree.root(),
+            leaves: self.tree.leaves(),
+        };
+        crate::wallet::write_atomic(path, serde_json::to_string(&file)?.as_bytes())
+    }
+}
+
+/// The tree in a cache file, if the file is one and is consistent
+fn load(data: &str) -> Option<SparseMerkleTree> {
+    let file: CacheFile = serde_json::from_str(data).ok()?;
+    if file.leaves.len() > 1 << MERKLE_DEPTH {
+        return None;
+    }
+    let tree = SparseMerkleTree::from_leaves(file.leaves);
+    (tree.root() == file.root).then_some(tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn save_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("r14").join("leaves.json");
+        let mut cache = LeafCache::open(&path).unwrap();
+        assert!(cache.is_empty());
+        for v in 1..=3u64 {
+            cache.tree.insert(Commitment(Fr::from(v)));
+        }
+        cache.save().unwrap();
+
+        let reopened = LeafCache::open(&path).unwrap();
+        assert_eq!(reopened.leaves(), cache.leaves());
+        assert_eq!(reopened.root(), cache.root());
+    }
+
+    #[test]
+    fn bad_files_start_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("leaves.json");
+        fs::write(&path, "not json").unwrap();
+        assert!(LeafCache::open(&path).unwrap().is_empty());
+
+        // leaves that don't hash to the recorded root
+        let file = CacheFile {
+            root: MerkleRoot(crate::merkle::empty_root()),
+            leaves: vec![Commitment(Fr::from(1u64))],
+        };
+        fs::write(&path, serde_json::to_string(&file).unwrap()).unwrap();
+        assert!(LeafCache::open(&path).unwrap().is_empty());
+
+        LeafCache::new().save().unwrap();
+    }
+}
@@ -16,10 +16,37 @@
 //! |---|---|
 //! | *crate root* | Re-exports core types (`SecretKey`, `Note`, `commitment`, …) |
 //! | [`wallet`] | Key/note persistence, hex ↔ `Fr` conversion |
+//! | [`backup`] | Passphrase-encrypted export and merging import of notes and keys |
 //! | [`merkle`] | Offline and indexer-backed Merkle root computation |
+//! | [`leaf_cache`] | Persistent leaf cache that fetches only new leaves |
+//! | [`association`] | Association sets of approved deposits (compliance proofs) |
+//! | [`indexer`] | Typed client for the r14-indexer REST API |
+//! | [`prover`] | Client for the r14-prover daemon (remote proving) |
+//! | [`relayer`] | Client for r14 relayers, which submit transfers and pay the fee |
+//! | [`retry`] | Backoff policy for indexer and contract reads |
+//! | [`network`] | Network presets (testnet, futurenet, mainnet) and custom RPC/passphrase |
 //! | [`soroban`] | Stellar CLI wrapper for on-chain contract invocation |
-//! | [`serialize`] | Arkworks → hex serialization for Soroban contracts |
+//! | [`serialize`] | Arkworks → hex / byte serialization for Soroban contracts |
+//! | [`scval`] | Proofs, VKs and public inputs as `stellar_xdr` `ScVal`s |
+//! | [`pending`] | Two-phase wallet updates for submitted transfers |
+//! | [`history`] | Transaction history kept in the wallet, with paged queries |
+//! | [`contacts`] | Address book of named recipients kept in the wallet |
+//! | [`policy`] | Wallet spending limits enforced before transfers are proved |
+//! | `offline` | Air-gapped transfers: prepare online, prove and sign offline |
+//! | [`proof_file`] | `.r14proof` files for proving and submitting on different machines |
+//! | [`artifacts`] | Published proving keys, downloaded and checked against pinned SHA-256 |
+//! | [`deployment`] | `r14.toml` deployment descriptor and consistency checks |
+//! | [`session`] | Scoped session keys for dapps (mint / revoke) |
+//! | [`selection`] | Coin selection strategies for choosing notes to spend |
+//! | [`denomination`] | Splitting deposits into standard denominations per `app_tag` |
+//! | `testing` | Mock indexer and contracts for dapp tests (requires `testing` feature) |
+//! | `simulator` | In-memory tree, nullifiers and real proofs, no HTTP (requires `testing` and `prove`) |
 //! | `prove` | ZK proof generation (feature-gated, requires `prove` feature) |
+//! | `blocking` | Synchronous client wrapper (requires `blocking` feature) |
+//!
+//! `client` and `soroban` shell out to the `stellar` CLI, so they are left
+//! out on `wasm32-unknown-unknown`. The rest builds for the browser: notes,
+//! commitments, roots, indexer queries, and proving via [`prover`].
 //!
 //! ## Quick start
 //!
@@ -35,7 +62,7 @@
 //!
 //! ```rust,no_run
 //! use r14_sdk::{SecretKey, Note, owner_hash, commitment, nullifier};
-//! use r14_sdk::wallet::{self, fr_to_hex, hex_to_fr};
+//! use r14_sdk::wallet;
 //!
 //! # fn example() -> anyhow::Result<()> {
 //! // 1. Keygen
@@ -49,50 +76,80 @@
 //!
 //! // 3. Persist to wallet
 //! let mut w = wallet::load_wallet()?;
-//! w.notes.push(wallet::NoteEntry {
-//!     value: note.value,
-//!     app_tag: note.app_tag,
-//!     owner: fr_to_hex(&note.owner),
-//!     nonce: fr_to_hex(&note.nonce),
-//!     commitment: fr_to_hex(&cm),
-//!     index: None,
-//!     spent: false,
-//! });
+//! w.notes.push(wallet::NoteEntry::new(&note, &cm));
 //! wallet::save_wallet(&w)?;
 //!
 //! // 4. Compute Merkle root (offline or via indexer)
 //! let root = r14_sdk::merkle::compute_root_from_leaves(&[cm]);
 //!
 //! // 5. Generate proof (enable `prove` feature)
-//! //    let (proof, pi) = r14_sdk::prove::prove(&pk, sk, note, path, outputs, &mut rng);
+//! //    let (proof, pi) = r14_sdk::prove::prove(&pk, sk, note, path, outputs, &mut rng)?;
 //!
 //! // 6. Serialize for Soroban
 //! //    let (sp, spi) = r14_sdk::serialize::serialize_proof_for_soroban(&proof, &pi_vec);
 //!
 //! // 7. Submit on-chain
-//! //    r14_sdk::soroban::invoke_contract(&contract_id, "testnet", &secret, "deposit", &args).await?;
+//! //    r14_sdk::soroban::invoke_contract(&contract_id, &Network::testnet(), &secret, "deposit", &args).await?;
 //! # Ok(())
 //! # }
 //! ```
 
 // Re-exports from r14-types
-pub use r14_types::{MerklePath, MerkleRoot, Note, Nullifier, SecretKey, MERKLE_DEPTH};
+pub use r14_types::{
+    Commitment, LeafIndex, MerklePath, MerkleRoot, Note, Nullifier, SecretKey, SessionPolicy, MERKLE_DEPTH,
+};
 
 // Re-exports from r14-poseidon
-pub use r14_poseidon::{commitment, hash2, nullifier, owner_hash};
+pub use r14_poseidon::{
+    commitment, hash2, hash_family, nullifier, owner_hash, session_key, session_owner, set_hash_family, HashFamily,
+};
 
+pub mod absence;
+pub mod artifacts;
+pub mod association;
+pub mod backup;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod client;
+pub mod contacts;
+pub mod denomination;
+pub mod deployment;
 pub mod error;
+pub mod history;
+pub mod indexer;
+pub mod leaf_cache;
 pub mod merkle;
+pub mod network;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod offline;
+pub mod pending;
+pub mod policy;
+pub mod proof_file;
+pub mod prover;
+pub mod relayer;
+pub mod retry;
 #[cfg(feature = "prove")]
 pub mod prove;
+pub mod scval;
+pub mod selection;
 pub mod serialize;
+pub mod session;
+#[cfg(all(feature = "testing", feature = "prove", not(target_arch = "wasm32")))]
+pub mod simulator;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod soroban;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
 pub mod wallet;
 
+#[cfg(not(target_arch = "wasm32"))]
 pub use client::{
-    R14Client, R14Contracts, BalanceResult, DepositResult, InitResult, NoteStatus, PrebuiltProof,
-    TransferResult,
+    R14Client, R14ClientBuilder, R14Contracts, BalanceResult, DepositResult, InitResult, NoteStatus, PrebuiltProof,
+    RescanReport, ResumeOutcome, ResumedTransfer, RootConsistency, RotationResult, RootStatus, TransferResult,
 };
 pub use error::{R14Error, R14Result};
+pub use indexer::IndexerClient;
+pub use network::Network;
+pub use selection::CoinSelection;
 pub use wallet::{fr_to_raw_hex, strip_0x};
@@ -5,7 +5,8 @@
 //!
 //! Provides both offline root computation from a leaf list and
 //! indexer-backed root computation that fetches existing leaves
-//! over HTTP before appending new commitments.
+//! over HTTP before appending new commitments. [`SparseMerkleTree`] is
+//! the incremental tree the indexer keeps, for holding one in memory.
 //!
 //! The tree uses Poseidon `hash2` with depth [`MERKLE_DEPTH`]
 //! and zero-valued empty leaves.
@@ -21,16 +22,17 @@
 //!
 //! // with leaves
 //! # use ark_bls12_381::Fr;
-//! let root = compute_root_from_leaves(&[Fr::from(1u64), Fr::from(2u64)]);
+//! # use r14_sdk::Commitment;
+//! let root = compute_root_from_leaves(&[Commitment(Fr::from(1u64)), Commitment(Fr::from(2u64))]);
 //! ```
 
-use anyhow::{Context, Result};
 use ark_bls12_381::Fr;
 use ark_ff::AdditiveGroup;
-use r14_poseidon::hash2;
-use r14_types::MERKLE_DEPTH;
+use r14_poseidon::{hash2, hash_layer};
+use r14_types::{Commitment, LeafIndex, MerklePath, MerkleRoot, MERKLE_DEPTH};
 
-use crate::wallet::hex_to_fr;
+use crate::error::R14Result;
+use crate::indexer::IndexerClient;
 
 /// Compute the empty Merkle root: hash2(0,0) iterated MERKLE_DEPTH times
 pub fn empty_root() -> Fr {
@@ -41,8 +43,8 @@ pub fn empty_root() -> Fr {
     h
 }
 
-/// Compute the Merkle root from a list of leaves (mirrors indexer's SparseMerkleTree::root)
-fn compute_root(leaves: &[Fr]) -> Fr {
+/// Compute the Merkle root from a list of leaves (mirrors [`SparseMerkleTree::root`])
+pub(crate) fn compute_root(leaves: &[Commitment]) -> Fr {
     if leaves.is_empty() {
         return empty_root();
     }
@@ -53,54 +55,357 @@ fn compute_root(leaves: &[Fr]) -> Fr {
         zeros[i] = hash2(zeros[i - 1], zeros[i - 1]);
     }
 
-    let mut layer: Vec<Fr> = leaves.to_vec();
-    for level in 0..MERKLE_DEPTH {
-        let mut next = Vec::with_capacity((layer.len() + 1) / 2);
-        let zero = zeros[level];
-        let mut i = 0;
-        while i < layer.len() {
-            let left = layer[i];
-            let right = if i + 1 < layer.len() {
-                layer[i + 1]
-            } else {
-                zero
-            };
-            next.push(hash2(left, right));
-            i += 2;
-        }
-        layer = next;
+    let mut layer: Vec<Fr> = leaves.iter().map(|c| c.0).collect();
+    for &zero in zeros.iter().take(MERKLE_DEPTH) {
+        layer = hash_layer(&layer, zero);
     }
     layer[0]
 }
 
-/// Fetch leaves from indexer, append new commitments, return the new root as raw hex
-pub async fn compute_new_root(
-    indexer_url: &str,
-    new_commitments: &[Fr],
-) -> Result<String> {
-    let client = reqwest::Client::new();
-    let url = format!("{}/v1/leaves", indexer_url);
+/// Check that `path` leads from `leaf` to `root`
+pub fn verify_proof(leaf: Commitment, path: &MerklePath, root: Fr) -> bool {
+    if path.siblings.len() != MERKLE_DEPTH || path.indices.len() != MERKLE_DEPTH {
+        return false;
+    }
+    let mut current = leaf.0;
+    for (sibling, &is_right) in path.siblings.iter().zip(&path.indices) {
+        current = if is_right {
+            hash2(*sibling, current)
+        } else {
+            hash2(current, *sibling)
+        };
+    }
+    current == root
+}
+
+/// Path for `leaves[index]`, built level by level like the indexer does.
+/// `None` if `index` is out of range.
+pub fn path_from_leaves(leaves: &[Commitment], index: LeafIndex) -> Option<MerklePath> {
+    if index.as_usize() >= leaves.len() {
+        return None;
+    }
+    let mut zero = Fr::ZERO;
+    let mut layer: Vec<Fr> = leaves.iter().map(|c| c.0).collect();
+    let mut idx = index.as_usize();
+    let mut siblings = Vec::with_capacity(MERKLE_DEPTH);
+    let mut indices = Vec::with_capacity(MERKLE_DEPTH);
+    for _ in 0..MERKLE_DEPTH {
+        siblings.push(layer.get(idx ^ 1).copied().unwrap_or(zero));
+        indices.push(idx % 2 == 1);
+        layer = hash_layer(&layer, zero);
+        zero = hash2(zero, zero);
+        idx /= 2;
+    }
+    Some(MerklePath { siblings, indices })
+}
+
+/// Levels [`SparseMerkleTree::prune`] drops: leaves go in aligned chunks
+/// of `2^PRUNED_LEVELS`, and only each chunk's root stays stored
+pub const PRUNED_LEVELS: usize = 10;
+
+/// Append-only Poseidon Merkle tree with cached internal nodes.
+///
+/// `nodes[0]` holds the leaves and `nodes[level]` the filled nodes at that
+/// height; a missing right child is the empty-subtree hash `zeros[level]`.
+/// Insertion rehashes one path (O(depth)) and proofs read stored nodes.
+///
+/// [`Self::prune`] drops the leaves before [`Self::pruned`] and the nodes
+/// below [`PRUNED_LEVELS`] above them, so `nodes[level]` for those levels
+/// starts at `pruned >> level`. Paths for those leaves need their chunk's
+/// leaves back, see [`Self::proof_with_chunk`].
+///
+/// `DEPTH` fixes the capacity (2^DEPTH leaves) and the proof length; it has
+/// to match the depth the transfer circuit was set up for.
+#[derive(Clone)]
+pub struct SparseMerkleTree<const DEPTH: usize = MERKLE_DEPTH> {
+    nodes: Vec<Vec<Fr>>,
+    zeros: Vec<Fr>,
+    pruned: usize,
+}
+
+/// Where a tree's first `leaf_count` leaves end: per level, the one node
+/// that is partly filled. With the tree's stored nodes this is enough to
+/// read that smaller tree's root and paths without hashing. Build one with
+/// [`SparseMerkleTree::frontier`].
+#[derive(Clone, Debug)]
+pub struct Frontier {
+    leaf_count: usize,
+    edges: Vec<Fr>,
+    root: MerkleRoot,
+}
+
+impl Frontier {
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Root of the tree with only the first `leaf_count` leaves
+    pub fn root(&self) -> &MerkleRoot {
+        &self.root
+    }
+}
+
+impl<const DEPTH: usize> Default for SparseMerkleTree<DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const DEPTH: usize> SparseMerkleTree<DEPTH> {
+    pub fn new() -> Self {
+        let mut zeros = vec![Fr::ZERO; DEPTH + 1];
+        for i in 1..=DEPTH {
+            zeros[i] = hash2(zeros[i - 1], zeros[i - 1]);
+        }
+        Self {
+            nodes: vec![Vec::new(); DEPTH + 1],
+            zeros,
+            pruned: 0,
+        }
+    }
+
+    /// Build the tree over `leaves` a layer at a time, hashing each layer
+    /// as one batch (faster than inserting them one by one on startup)
+    pub fn from_leaves(leaves: Vec<Commitment>) -> Self {
+        let mut tree = Self::new();
+        assert!(leaves.len() <= tree.capacity(), "tree is full ({} leaves)", tree.capacity());
+        tree.nodes[0] = leaves.into_iter().map(|c| c.0).collect();
+        for level in 0..DEPTH {
+            tree.nodes[level + 1] = hash_layer(&tree.nodes[level], tree.zeros[level]);
+        }
+        tree
+    }
+
+    /// Number of leaves the tree holds, 2^DEPTH
+    pub fn capacity(&self) -> usize {
+        1usize.checked_shl(DEPTH as u32).unwrap_or(usize::MAX)
+    }
+
+    pub fn insert(&mut self, leaf: Commitment) -> LeafIndex {
+        let idx = self.next_index();
+        assert!(idx < self.capacity(), "tree is full ({} leaves)", self.capacity());
+        self.nodes[0].push(leaf.0);
+
+        // new leaves are past anything pruned, and so is every node they touch
+        let mut pos = idx;
+        for level in 0..DEPTH {
+            let left_pos = pos & !1;
+            let left = self.node(level, left_pos).expect("new leaves are never pruned");
+            let right = self.node_or_zero(level, left_pos + 1);
+            let parent = hash2(left, right);
+
+            pos /= 2;
+            let slot = pos - self.offset(level + 1);
+            let above = &mut self.nodes[level + 1];
+            if slot < above.len() {
+                above[slot] = parent;
+            } else {
+                above.push(parent);
+            }
+        }
+        LeafIndex::from(idx)
+    }
+
+    pub fn next_index(&self) -> usize {
+        self.pruned + self.nodes[0].len()
+    }
+
+    /// Leaves before this one were dropped by [`Self::prune`]; 0 if none were
+    pub fn pruned(&self) -> usize {
+        self.pruned
+    }
+
+    /// Drop the leaves, and the nodes below [`PRUNED_LEVELS`] over them,
+    /// for all but about the newest `keep`. Only whole chunks go, and only
+    /// once `keep` more leaves have piled up behind the ones kept, so
+    /// calling this after every insert copies each leaf O(1) times and
+    /// about twice `keep` stay stored. The root, new leaves and
+    /// paths for the kept leaves work as before.
+    pub fn prune(&mut self, keep: usize) {
+        let chunk = 1 << Self::pruned_levels();
+        let before = self.next_index().saturating_sub(keep) / chunk * chunk;
+        if before <= self.pruned || before - self.pruned < keep.max(chunk) {
+            return;
+        }
+        for level in 0..Self::pruned_levels() {
+            let dropped = (before - self.pruned) >> level;
+            self.nodes[level].drain(..dropped);
+            self.nodes[level].shrink_to_fit();
+        }
+        self.pruned = before;
+    }
+
+    /// The aligned leaves [`Self::proof_with_chunk`] needs back to prove
+    /// leaf `index`
+    pub fn chunk_of(&self, index: LeafIndex) -> std::ops::Range<usize> {
+        let start = index.as_usize() >> Self::pruned_levels() << Self::pruned_levels();
+        start..start + (1 << Self::pruned_levels())
+    }
+
+    pub fn leaves(&self) -> Vec<Commitment> {
+        self.leaves_from(0)
+    }
+
+    /// Leaves at position `from` and after; empty past the end
+    pub fn leaves_from(&self, from: usize) -> Vec<Commitment> {
+        self.leaves_range(from, usize::MAX)
+    }
+
+    /// Up to `limit` leaves at position `from` and after. Panics if `from`
+    /// is before [`Self::pruned`].
+    pub fn leaves_range(&self, from: usize, limit: usize) -> Vec<Commitment> {
+        assert!(from >= self.pruned, "leaves before {} were pruned", self.pruned);
+        let tail = self.nodes[0].get(from - self.pruned..).unwrap_or_default();
+        tail.iter().take(limit).copied().map(Commitment).collect()
+    }
 
-    let resp: serde_json::Value = client
-        .get(&url)
-        .send()
-        .await?
-        .json()
-        .await
-        .context("failed to fetch leaves from indexer")?;
+    pub fn root(&self) -> MerkleRoot {
+        MerkleRoot(self.node_or_zero(DEPTH, 0))
+    }
+
+    /// Panics if `index` is past the end or before [`Self::pruned`]
+    pub fn proof(&self, index: LeafIndex) -> MerklePath {
+        assert!(index.as_usize() < self.next_index(), "index out of bounds");
+        self.path(index, None, &[]).expect("leaf was pruned")
+    }
+
+    /// Path for a leaf before [`Self::pruned`], to the current root or to
+    /// `frontier`'s, given the leaves [`Self::chunk_of`] it. `None` if
+    /// they don't hash to the chunk root the tree kept, or the leaf isn't
+    /// under `frontier`.
+    pub fn proof_with_chunk(
+        &self,
+        index: LeafIndex,
+        chunk: &[Commitment],
+        frontier: Option<&Frontier>,
+    ) -> Option<MerklePath> {
+        let limit = frontier.map_or(self.next_index(), |f| f.leaf_count);
+        if index.as_usize() >= limit || chunk.len() != self.chunk_of(index).len() {
+            return None;
+        }
+        self.path(index, frontier, chunk)
+    }
+
+    /// Root the tree had when it held its first `leaf_count` leaves
+    pub fn root_at(&self, leaf_count: usize) -> MerkleRoot {
+        self.frontier(leaf_count).expect("leaf count out of bounds").root
+    }
+
+    /// Path for leaf `index` to [`Self::root_at`]`(leaf_count)`, read from
+    /// stored nodes with O(depth) hashing. `None` unless
+    /// `index < leaf_count <= next_index()`.
+    pub fn proof_at(&self, index: LeafIndex, leaf_count: usize) -> Option<MerklePath> {
+        self.proof_in(&self.frontier(leaf_count)?, index)
+    }
+
+    /// The [`Frontier`] of the first `leaf_count` leaves, `None` past
+    /// `next_index()` or if the nodes it needs were pruned. Costs O(depth) hashes; keep it to prove against that
+    /// root again without hashing.
+    pub fn frontier(&self, leaf_count: usize) -> Option<Frontier> {
+        if leaf_count > self.next_index() {
+            return None;
+        }
+        // per level, the node the end of the leaves falls in, hashed as if
+        // the leaves after them were empty. Nodes left of it cover only
+        // earlier leaves, so the stored ones are still right.
+        let mut edges = Vec::with_capacity(DEPTH + 1);
+        edges.push(self.zeros[0]);
+        for level in 0..DEPTH {
+            let pos = leaf_count >> level;
+            let edge = edges[level];
+            edges.push(if pos & 1 == 1 {
+                hash2(self.node(level, pos - 1)?, edge)
+            } else if edge == self.zeros[level] {
+                self.zeros[level + 1]
+            } else {
+                hash2(edge, self.zeros[level])
+            });
+        }
+        let mut frontier = Frontier { leaf_count, edges, root: MerkleRoot(Fr::ZERO) };
+        frontier.root = MerkleRoot(self.node_in(&frontier, DEPTH, 0)?);
+        Some(frontier)
+    }
+
+    /// Path for leaf `index` to `frontier`'s root, from stored nodes only.
+    /// `frontier` has to come from this tree, or one with the same first
+    /// `frontier.leaf_count()` leaves. `None` unless the leaf is under it.
+    pub fn proof_in(&self, frontier: &Frontier, index: LeafIndex) -> Option<MerklePath> {
+        if index.as_usize() >= frontier.leaf_count || frontier.leaf_count > self.next_index() {
+            return None;
+        }
+        self.path(index, Some(frontier), &[])
+    }
+
+    /// Path for leaf `index` to the current root or `frontier`'s. The
+    /// levels below [`PRUNED_LEVELS`] come from `chunk` if it isn't empty;
+    /// a pruned chunk lies wholly before any frontier that covers its leaf.
+    fn path(&self, index: LeafIndex, frontier: Option<&Frontier>, chunk: &[Commitment]) -> Option<MerklePath> {
+        let start = self.chunk_of(index).start;
+        let mut layer: Vec<Fr> = chunk.iter().map(|c| c.0).collect();
+        let mut siblings = Vec::with_capacity(DEPTH);
+        let mut indices = Vec::with_capacity(DEPTH);
+        let mut pos = index.as_usize();
+        for level in 0..DEPTH {
+            let from_chunk = !chunk.is_empty() && level < Self::pruned_levels();
+            indices.push(pos & 1 == 1);
+            siblings.push(match frontier {
+                _ if from_chunk => layer[(pos ^ 1) - (start >> level)],
+                Some(frontier) => self.node_in(frontier, level, pos ^ 1)?,
+                None => self.node(level, pos ^ 1)?,
+            });
+            pos /= 2;
+            if from_chunk {
+                layer = hash_layer(&layer, self.zeros[level]);
+                if level + 1 == Self::pruned_levels() && Some(layer[0]) != self.node(level + 1, pos) {
+                    return None;
+                }
+            }
+        }
+        Some(MerklePath { siblings, indices })
+    }
 
-    let leaf_hexes = resp["leaves"]
-        .as_array()
-        .context("invalid leaves response")?;
+    /// Node `pos` at `level` as of `frontier`'s leaves, `None` if pruned
+    fn node_in(&self, frontier: &Frontier, level: usize, pos: usize) -> Option<Fr> {
+        match pos.cmp(&(frontier.leaf_count >> level)) {
+            std::cmp::Ordering::Less => self.node(level, pos),
+            std::cmp::Ordering::Equal => Some(frontier.edges[level]),
+            std::cmp::Ordering::Greater => Some(self.zeros[level]),
+        }
+    }
+
+    /// Stored node `pos` at `level`, the empty-subtree hash past the
+    /// filled ones, `None` if pruned
+    fn node(&self, level: usize, pos: usize) -> Option<Fr> {
+        let slot = pos.checked_sub(self.offset(level))?;
+        Some(self.nodes[level].get(slot).copied().unwrap_or(self.zeros[level]))
+    }
 
-    let mut leaves: Vec<Fr> = leaf_hexes
-        .iter()
-        .map(|v| hex_to_fr(v.as_str().unwrap_or("")))
-        .collect::<Result<_>>()?;
+    /// Panics on pruned nodes; the ones insertion and the root read never are
+    fn node_or_zero(&self, level: usize, pos: usize) -> Fr {
+        self.node(level, pos).expect("node was pruned")
+    }
+
+    /// Position of `nodes[level][0]`
+    fn offset(&self, level: usize) -> usize {
+        if level < Self::pruned_levels() {
+            self.pruned >> level
+        } else {
+            0
+        }
+    }
 
-    for cm in new_commitments {
-        leaves.push(*cm);
+    fn pruned_levels() -> usize {
+        PRUNED_LEVELS.min(DEPTH)
     }
+}
+
+/// Fetch leaves from indexer, append new commitments, return the new root as raw hex
+pub async fn compute_new_root(
+    indexer: &IndexerClient,
+    new_commitments: &[Commitment],
+) -> R14Result<String> {
+    let mut leaves = indexer.leaves().await?;
+    leaves.extend_from_slice(new_commitments);
 
     let root = compute_root(&leaves);
     Ok(fr_to_raw_hex(&root))
@@ -111,7 +416,7 @@ fn fr_to_raw_hex(fr: &Fr) -> String {
 }
 
 /// Compute root from leaves and return as raw hex (no 0x prefix)
-pub fn compute_root_from_leaves(leaves: &[Fr]) -> String {
+pub fn compute_root_from_leaves(leaves: &[Commitment]) -> String {
     fr_to_raw_hex(&compute_root(leaves))
 }
 
@@ -157,7 +462,7 @@ mod tests {
     #[test]
     fn single_leaf_root() {
         let mut rng = StdRng::seed_from_u64(77);
-        let leaf = Fr::rand(&mut rng);
+        let leaf = Commitment(Fr::rand(&mut rng));
         let root = compute_root_from_leaves(&[leaf]);
         // should differ from empty root
         assert_ne!(root, empty_root_hex());
@@ -168,21 +473,124 @@ mod tests {
     #[test]
     fn two_leaves_root() {
         let mut rng = StdRng::seed_from_u64(88);
-        let a = Fr::rand(&mut rng);
-        let b = Fr::rand(&mut rng);
+        let a = Commitment(Fr::rand(&mut rng));
+        let b = Commitment(Fr::rand(&mut rng));
         let root_ab = compute_root_from_leaves(&[a, b]);
         let root_ba = compute_root_from_leaves(&[b, a]);
         // order matters
         assert_ne!(root_ab, root_ba);
     }
 
+    #[test]
+    fn verify_proof_accepts_current_root_only() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let leaves: Vec<Commitment> = (0..5).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let root = compute_root(&leaves);
+        for i in 0..leaves.len() {
+            assert!(verify_proof(leaves[i], &path_from_leaves(&leaves, i.into()).unwrap(), root), "leaf {i}");
+        }
+
+        // a path into an older tree doesn't match the grown tree's root
+        let stale = path_from_leaves(&leaves[..3], LeafIndex(1)).unwrap();
+        assert!(!verify_proof(leaves[1], &stale, root));
+        assert!(!verify_proof(leaves[0], &path_from_leaves(&leaves, LeafIndex(1)).unwrap(), root));
+
+        let mut short = path_from_leaves(&leaves, LeafIndex(0)).unwrap();
+        short.siblings.pop();
+        assert!(!verify_proof(leaves[0], &short, root));
+
+        assert!(path_from_leaves(&leaves, LeafIndex(5)).is_none());
+        assert!(path_from_leaves(&[], LeafIndex(0)).is_none());
+    }
+
     #[test]
     fn root_changes_with_extra_leaf() {
         let mut rng = StdRng::seed_from_u64(99);
-        let a = Fr::rand(&mut rng);
-        let b = Fr::rand(&mut rng);
+        let a = Commitment(Fr::rand(&mut rng));
+        let b = Commitment(Fr::rand(&mut rng));
         let root_1 = compute_root_from_leaves(&[a]);
         let root_2 = compute_root_from_leaves(&[a, b]);
         assert_ne!(root_1, root_2);
     }
+
+    #[test]
+    fn tree_matches_leaf_list() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let leaves: Vec<Commitment> = (0..6).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let mut tree = SparseMerkleTree::<MERKLE_DEPTH>::new();
+        for (i, leaf) in leaves.iter().enumerate() {
+            assert_eq!(tree.insert(*leaf), LeafIndex::from(i));
+            assert_eq!(tree.root().0, compute_root(&leaves[..=i]));
+        }
+        let path = tree.proof(LeafIndex(3));
+        assert_eq!(path.siblings, path_from_leaves(&leaves, LeafIndex(3)).unwrap().siblings);
+        assert!(verify_proof(leaves[3], &path, tree.root().0));
+        assert_eq!(tree.leaves(), leaves);
+        assert_eq!(tree.leaves_range(2, 3), leaves[2..5]);
+        assert!(tree.leaves_range(6, 3).is_empty());
+    }
+
+    #[test]
+    fn proofs_at_earlier_sizes_match_the_smaller_tree() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let leaves: Vec<Commitment> = (0..9).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let tree = SparseMerkleTree::<MERKLE_DEPTH>::from_leaves(leaves.clone());
+        for count in 0..=leaves.len() {
+            assert_eq!(tree.root_at(count).0, compute_root(&leaves[..count]), "{count} leaves");
+            for i in 0..count {
+                let path = tree.proof_at(LeafIndex::from(i), count).unwrap();
+                assert_eq!(path.siblings, path_from_leaves(&leaves[..count], LeafIndex::from(i)).unwrap().siblings);
+            }
+            assert!(tree.proof_at(LeafIndex::from(count), count).is_none());
+        }
+        assert!(tree.proof_at(LeafIndex(0), leaves.len() + 1).is_none());
+        assert!(tree.frontier(leaves.len() + 1).is_none());
+
+        // a frontier keeps proving against its root after the tree grows
+        let frontier = tree.frontier(5).unwrap();
+        let mut grown = tree.clone();
+        grown.insert(Commitment(Fr::from(77u64)));
+        assert_eq!(frontier.root().0, compute_root(&leaves[..5]));
+        let path = grown.proof_in(&frontier, LeafIndex(4)).unwrap();
+        assert_eq!(path.siblings, path_from_leaves(&leaves[..5], LeafIndex(4)).unwrap().siblings);
+
+        // a full tree
+        let full = SparseMerkleTree::<2>::from_leaves(leaves[..4].to_vec());
+        assert_eq!(full.root_at(4), full.root());
+        assert_eq!(full.proof_at(LeafIndex(3), 4).unwrap().siblings, full.proof(LeafIndex(3)).siblings);
+    }
+
+    #[test]
+    fn pruned_leaves_prove_from_their_chunk() {
+        let mut rng = StdRng::seed_from_u64(14);
+        let leaves: Vec<Commitment> = (0..3000).map(|_| Commitment(Fr::rand(&mut rng))).collect();
+        let mut full = SparseMerkleTree::<MERKLE_DEPTH>::from_leaves(leaves.clone());
+        let mut tree = full.clone();
+        tree.prune(500);
+        assert_eq!(tree.pruned(), 2048);
+        assert_eq!(tree.next_index(), 3000);
+        assert_eq!(tree.root(), full.root());
+        assert_eq!(tree.leaves_range(2048, 3), leaves[2048..2051]);
+        assert_eq!(tree.proof(LeafIndex(2500)).siblings, full.proof(LeafIndex(2500)).siblings);
+
+        let chunk = &leaves[tree.chunk_of(LeafIndex(5))];
+        let path = tree.proof_with_chunk(LeafIndex(5), chunk, None).unwrap();
+        assert_eq!(path.siblings, full.proof(LeafIndex(5)).siblings);
+        assert!(tree.proof_with_chunk(LeafIndex(5), &leaves[1024..2048], None).is_none());
+
+        // earlier roots: aligned or past the pruned leaves still have a frontier
+        assert!(tree.frontier(1500).is_none());
+        assert_eq!(tree.frontier(1024).unwrap().root(), &full.root_at(1024));
+        let frontier = tree.frontier(2100).unwrap();
+        assert!(tree.proof_in(&frontier, LeafIndex(100)).is_none());
+        let path = tree.proof_with_chunk(LeafIndex(100), &leaves[..1024], Some(&frontier)).unwrap();
+        assert_eq!(path.siblings, full.proof_at(LeafIndex(100), 2100).unwrap().siblings);
+
+        // not enough new leaves yet for another step
+        let leaf = Commitment(Fr::from(77u64));
+        assert_eq!(tree.insert(leaf), full.insert(leaf));
+        assert_eq!(tree.root(), full.root());
+        tree.prune(500);
+        assert_eq!(tree.pruned(), 2048);
+    }
 }
@@ -0,0 +1,179 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Stellar networks: presets, custom RPC endpoints and passphrases.
+//!
+//! A [`Network`] with an RPC URL and passphrase is passed to the `stellar`
+//! CLI explicitly (`--rpc-url`, `--network-passphrase`), so it needs no
+//! `stellar network add`. One with only a name falls back to
+//! `--network <name>`, a network from the CLI's own config.
+//!
+//! | Preset | RPC | Passphrase |
+//! |---|---|---|
+//! | `testnet` | `https://soroban-testnet.stellar.org:443` | [`TESTNET_PASSPHRASE`] |
+//! | `futurenet` | `https://rpc-futurenet.stellar.org:443` | [`FUTURENET_PASSPHRASE`] |
+//! | `mainnet` | none, bring your own | [`MAINNET_PASSPHRASE`] |
+//!
+//! ```rust
+//! use r14_sdk::network::{Network, MAINNET_PASSPHRASE};
+//!
+//! let testnet = Network::named("testnet");
+//! assert_eq!(testnet.invoke_args()[0], "--rpc-url");
+//!
+//! let mainnet = Network::named("mainnet").with_rpc_url("https://rpc.example.org");
+//! assert_eq!(mainnet.passphrase.as_deref(), Some(MAINNET_PASSPHRASE));
+//!
+//! let local = Network::custom("local", "http://localhost:8000/rpc", "Standalone Network ; February 2017");
+//! assert_eq!(local.invoke_args()[3], "Standalone Network ; February 2017");
+//!
+//! // not a preset: the stellar CLI resolves it from its config
+//! assert_eq!(Network::named("staging").invoke_args(), ["--network", "staging"]);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+pub const FUTURENET_PASSPHRASE: &str = "Test SDF Future Network ; October 2022";
+pub const MAINNET_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+
+/// Preset names, for help text and validation
+pub const PRESETS: [&str; 3] = ["testnet", "futurenet", "mainnet"];
+
+pub const DEFAULT_NETWORK: &str = "testnet";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Network {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase: Option<String>,
+}
+
+impl Network {
+    /// A preset, with its RPC and passphrase filled in; any other name is
+    /// left to the `stellar` CLI's config
+    pub fn named(name: &str) -> Self {
+        let (rpc_url, passphrase) = match name {
+            "testnet" => (Some("https://soroban-testnet.stellar.org:443"), Some(TESTNET_PASSPHRASE)),
+            "futurenet" => (Some("https://rpc-futurenet.stellar.org:443"), Some(FUTURENET_PASSPHRASE)),
+            "mainnet" => (None, Some(MAINNET_PASSPHRASE)),
+            _ => (None, None),
+        };
+        Self {
+            name: name.to_string(),
+            rpc_url: rpc_url.map(String::from),
+            passphrase: passphrase.map(String::from),
+        }
+    }
+
+    /// Any network by endpoint and passphrase; `name` is only a label
+    pub fn custom(name: &str, rpc_url: &str, passphrase: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rpc_url: Some(rpc_url.to_string()),
+            passphrase: Some(passphrase.to_string()),
+        }
+    }
+
+    pub fn testnet() -> Self {
+        Self::named("testnet")
+    }
+
+    pub fn futurenet() -> Self {
+        Self::named("futurenet")
+    }
+
+    /// Mainnet through `rpc_url`; SDF runs no public mainnet RPC
+    pub fn mainnet(rpc_url: &str) -> Self {
+        Self::named("mainnet").with_rpc_url(rpc_url)
+    }
+
+    pub fn with_rpc_url(mut self, rpc_url: &str) -> Self {
+        self.rpc_url = Some(rpc_url.to_string());
+        self
+    }
+
+    pub fn with_passphrase(mut self, passphrase: &str) -> Self {
+        self.passphrase = Some(passphrase.to_string());
+        self
+    }
+
+    /// The network a wallet is configured for, through its `rpc_url`
+    pub fn from_wallet(wallet: &crate::wallet::WalletData) -> Self {
+        let mut network = Self::named(&wallet.network).with_rpc_url(&wallet.rpc_url);
+        if let Some(passphrase) = &wallet.network_passphrase {
+            network.passphrase = Some(passphrase.clone());
+        }
+        network
+    }
+
+    pub fn is_preset(&self) -> bool {
+        PRESETS.contains(&self.name.as_str())
+    }
+
+    /// `stellar` CLI flags selecting this network
+    pub fn invoke_args(&self) -> Vec<&str> {
+        match (&self.rpc_url, &self.passphrase) {
+            (Some(rpc_url), Some(passphrase)) => vec!["--rpc-url", rpc_url, "--network-passphrase", passphrase],
+            _ => vec!["--network", &self.name],
+        }
+    }
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Self::named(DEFAULT_NETWORK)
+    }
+}
+
+impl From<&str> for Network {
+    fn from(name: &str) -> Self {
+        Self::named(name)
+    }
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.rpc_url {
+            Some(rpc_url) => write!(f, "{} ({rpc_url})", self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_explicit() {
+        assert_eq!(
+            Network::testnet().invoke_args(),
+            ["--rpc-url", "https://soroban-testnet.stellar.org:443", "--network-passphrase", TESTNET_PASSPHRASE]
+        );
+        assert_eq!(Network::futurenet().passphrase.as_deref(), Some(FUTURENET_PASSPHRASE));
+        // mainnet has a passphrase but no default RPC
+        assert_eq!(Network::named("mainnet").invoke_args(), ["--network", "mainnet"]);
+        assert_eq!(Network::mainnet("https://rpc.example.org").invoke_args()[1], "https://rpc.example.org");
+        assert!(Network::default().is_preset() && !Network::named("local").is_preset());
+    }
+
+    #[test]
+    fn wallet_overrides_rpc_and_passphrase() {
+        let mut w: crate::wallet::WalletData = serde_json::from_value(serde_json::json!({
+            "secret_key": "0x01", "owner_hash": "0x02", "stellar_secret": "S", "notes": [],
+            "indexer_url": "http://localhost:3000", "rpc_url": "http://localhost:8000/rpc",
+            "core_contract_id": "C", "transfer_contract_id": "C",
+        }))
+        .unwrap();
+        // wallets from before the network fields are testnet
+        assert_eq!(w.network, "testnet");
+        let n = Network::from_wallet(&w);
+        assert_eq!((n.rpc_url.as_deref(), n.passphrase.as_deref()), (Some("http://localhost:8000/rpc"), Some(TESTNET_PASSPHRASE)));
+
+        w.network = "local".into();
+        w.network_passphrase = Some("Standalone Network ; February 2017".into());
+        assert_eq!(Network::from_wallet(&w).invoke_args()[3], "Standalone Network ; February 2017");
+    }
+}
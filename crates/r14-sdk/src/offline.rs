@@ -0,0 +1,171 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Air-gapped transfers: prepare online, prove offline, submit online.
+//!
+//! A custodian can keep the spending key on a machine that never touches
+//! the network. The online machine holds the notes (which aren't secret
+//! enough to need the air gap), picks one with
+//! `R14Client::prepare_transfer`, and writes a [`TransferBundle`]: the
+//! note, its Merkle path, the recipient, the ledger to prove at, and the
+//! circuit_id the contract verifies against. The bundle is carried across
+//! as JSON. `prove_and_sign` checks it against the key, proves, and
+//! returns a [`SignedTransfer`] to carry back; `R14Client::submit_signed`
+//! submits it and adds the new notes to the wallet.
+//!
+//! ```rust,no_run
+//! use r14_sdk::offline::{SignedTransfer, TransferBundle};
+//! use r14_sdk::CoinSelection;
+//!
+//! # async fn example(
+//! #     client: r14_sdk::R14Client,
+//! #     wallet: &mut r14_sdk::wallet::WalletData,
+//! #     owner: ark_bls12_381::Fr,
+//! #     sk: ark_bls12_381::Fr,
+//! #     bob: ark_bls12_381::Fr,
+//! # ) -> anyhow::Result<()> {
+//! // online
+//! let bundle = client.prepare_transfer(&wallet.notes, &owner, &bob, 500, CoinSelection::default()).await?;
+//! std::fs::write("transfer.bundle.json", bundle.to_json()?)?;
+//!
+//! // offline, with the key (needs the `prove` feature)
+//! # #[cfg(feature = "prove")] {
+//! let bundle = TransferBundle::from_json(&std::fs::read_to_string("transfer.bundle.json")?)?;
+//! let signed = r14_sdk::offline::prove_and_sign(&bundle, &sk)?;
+//! std::fs::write("transfer.signed.json", serde_json::to_string(&signed)?)?;
+//! # }
+//!
+//! // online again
+//! let signed: SignedTransfer = serde_json::from_str(&std::fs::read_to_string("transfer.signed.json")?)?;
+//! let result = client.submit_signed(wallet, &signed).await?;
+//! println!("{}", result.nullifier);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The proof is the spend authorization; nothing else is signed. A bundle
+//! goes stale once its Merkle root leaves the contract's root history, and
+//! submission then fails with `StaleRoot`; prepare a new one.
+
+use anyhow::{ensure, Context, Result};
+use ark_bls12_381::Fr;
+use serde::{Deserialize, Serialize};
+
+use crate::client::PrebuiltProof;
+use crate::wallet::NoteEntry;
+use crate::{Commitment, MerklePath};
+
+pub const BUNDLE_FORMAT: &str = "r14-transfer-bundle";
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Everything proving a transfer needs except the key
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TransferBundle {
+    pub format: String,
+    pub version: u32,
+    /// circuit_id the transfer contract verifies against (raw hex)
+    pub circuit_id: String,
+    /// The note to spend
+    pub note: NoteEntry,
+    pub merkle_path: MerklePath,
+    /// Owner hash receiving `value` (hex)
+    pub recipient: String,
+    pub value: u64,
+    /// Ledger to prove at
+    pub current_ledger: u64,
+}
+
+impl TransferBundle {
+    pub fn new(
+        circuit_id: String,
+        note: NoteEntry,
+        merkle_path: MerklePath,
+        recipient: &Fr,
+        value: u64,
+        current_ledger: u64,
+    ) -> Self {
+        Self {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            circuit_id,
+            note,
+            merkle_path,
+            recipient: crate::wallet::fr_to_hex(recipient),
+            value,
+            current_ledger,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).context("failed to serialize transfer bundle")
+    }
+
+    /// Parse a bundle, refusing other formats and versions
+    pub fn from_json(json: &str) -> Result<Self> {
+        let bundle: Self = serde_json::from_str(json).context("not a transfer bundle")?;
+        ensure!(bundle.format == BUNDLE_FORMAT, "not a transfer bundle (format `{}`)", bundle.format);
+        ensure!(
+            bundle.version == BUNDLE_VERSION,
+            "unsupported transfer bundle version {} (this build reads {BUNDLE_VERSION})",
+            bundle.version
+        );
+        Ok(bundle)
+    }
+}
+
+/// A transfer proven offline, with the notes it creates
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignedTransfer {
+    /// Commitment of the note it spends
+    pub consumed: Commitment,
+    pub proof: PrebuiltProof,
+    pub recipient_note: NoteEntry,
+    pub change_note: NoteEntry,
+}
+
+/// The offline half: check `bundle` against `sk` and this build's circuit,
+/// then prove it.
+///
+/// Fails before proving if `sk` doesn't own the note, the path isn't the
+/// note's, the note can't cover the value, or this build's transfer
+/// circuit isn't the one the contract verifies.
+#[cfg(feature = "prove")]
+pub fn prove_and_sign(bundle: &TransferBundle, sk: &Fr) -> crate::R14Result<SignedTransfer> {
+    use crate::error::R14Error;
+
+    let owner = crate::owner_hash(&crate::SecretKey(*sk)).0;
+    let note = &bundle.note;
+    if crate::wallet::hex_to_fr(&note.owner).map_err(R14Error::Other)? != owner {
+        return Err(R14Error::Config("the bundle's note is not owned by this key".to_string()));
+    }
+    if note.index != Some(bundle.merkle_path.leaf_index()) {
+        return Err(R14Error::Config("the bundle's merkle path is not for its note".to_string()));
+    }
+    if note.value < bundle.value {
+        return Err(R14Error::InsufficientBalance { needed: bundle.value, best: note.value });
+    }
+    let recipient = crate::wallet::hex_to_fr(&bundle.recipient).map_err(R14Error::Other)?;
+
+    let (pk, vk) = crate::artifacts::transfer_keys()?;
+    let circuit_id = crate::serialize::circuit_id(&crate::prove::serialize_vk_for_soroban(&vk, crate::MERKLE_DEPTH));
+    if !crate::wallet::strip_0x(&bundle.circuit_id).eq_ignore_ascii_case(&circuit_id) {
+        return Err(R14Error::CircuitMismatch { local: circuit_id, deployed: bundle.circuit_id.clone() });
+    }
+
+    let proven = crate::client::prove_transfer(
+        &pk,
+        note,
+        bundle.merkle_path.clone(),
+        sk,
+        &owner,
+        &recipient,
+        bundle.value,
+        bundle.current_ledger,
+    )?;
+    Ok(SignedTransfer {
+        consumed: note.commitment,
+        proof: proven.prebuilt,
+        recipient_note: proven.recipient,
+        change_note: proven.change,
+    })
+}
@@ -0,0 +1,247 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Two-phase wallet updates for transfers.
+//!
+//! Submitting a transfer can fail after the wallet has been touched, or
+//! succeed without the caller ever hearing back. To keep the wallet
+//! consistent, a transfer goes through three steps:
+//!
+//! 1. [`begin_transfer`] marks the consumed note spent and parks the output
+//!    notes in a [`PendingTransfer`]. Save the wallet before submitting.
+//! 2. On inclusion, [`confirm_transfer`] moves the outputs into the wallet.
+//! 3. On a failure that can't have landed (see
+//!    [`crate::soroban::never_landed`]), [`rollback_transfer`] clears the
+//!    spent flag and drops the outputs. Any other failure leaves the record
+//!    pending.
+//!
+//! If the process dies between steps, the record survives in the wallet
+//! file. `R14Client::resolve_pending` confirms it once the indexer has
+//! seen the nullifier.
+//!
+//! A proof that can't be submitted yet (the network is down, say) can be
+//! parked with [`begin_transfer_with_proof`] instead: the record keeps the
+//! proof, and `R14Client::resume_pending` submits it later without
+//! proving again.
+//!
+//! ```rust,no_run
+//! use r14_sdk::pending::{begin_transfer, confirm_transfer, rollback_transfer};
+//! # use r14_sdk::wallet::NoteEntry;
+//!
+//! # async fn submit() -> anyhow::Result<String> { Ok(String::new()) }
+//! # async fn example(outputs: Vec<NoteEntry>, nullifier: String) -> anyhow::Result<()> {
+//! let mut w = r14_sdk::wallet::load_wallet()?;
+//! begin_transfer(&mut w, 0, &nullifier, outputs)?;
+//! r14_sdk::wallet::save_wallet(&w)?;
+//! match submit().await {
+//!     Ok(_) => confirm_transfer(&mut w, &nullifier)?,
+//!     Err(e) if r14_sdk::soroban::never_landed(&e) => rollback_transfer(&mut w, &nullifier)?,
+//!     // may still land: resolve_pending confirms it later
+//!     Err(_) => {}
+//! }
+//! r14_sdk::wallet::save_wallet(&w)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::prover::ProvedTransfer;
+use crate::wallet::{fr_to_hex, strip_0x, NoteEntry, WalletData};
+use crate::Commitment;
+
+/// A submitted transfer not yet confirmed on-chain
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PendingTransfer {
+    /// Nullifier of the consumed note (`0x` hex), identifies the transfer
+    pub nullifier: String,
+    /// Commitment of the consumed note
+    pub consumed: Commitment,
+    /// Output notes, added to the wallet on confirmation
+    pub outputs: Vec<NoteEntry>,
+    /// The proof, when it was parked for later submission rather than
+    /// submitted (`R14Client::resume_pending` submits it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<ProvedTransfer>,
+}
+
+/// Mark `wallet.notes[consumed_idx]` spent and record the transfer as pending.
+pub fn begin_transfer(
+    wallet: &mut WalletData,
+    consumed_idx: usize,
+    nullifier: &str,
+    outputs: Vec<NoteEntry>,
+) -> Result<()> {
+    if wallet.pending.iter().any(|p| p.nullifier == nullifier) {
+        bail!("transfer {nullifier} is already pending");
+    }
+    let note = wallet
+        .notes
+        .get_mut(consumed_idx)
+        .with_context(|| format!("no note at index {consumed_idx}"))?;
+    if note.spent {
+        bail!("note {} is already spent", fr_to_hex(&note.commitment.0));
+    }
+    note.spent = true;
+    wallet.pending.push(PendingTransfer {
+        nullifier: nullifier.to_string(),
+        consumed: note.commitment,
+        outputs,
+        proof: None,
+    });
+    Ok(())
+}
+
+/// [`begin_transfer`] for a proof that isn't submitted yet: the record
+/// keeps the proof, keyed by its nullifier, so it can be submitted later
+/// without proving again.
+pub fn begin_transfer_with_proof(
+    wallet: &mut WalletData,
+    consumed_idx: usize,
+    proof: ProvedTransfer,
+    outputs: Vec<NoteEntry>,
+) -> Result<()> {
+    let nullifier = format!("0x{}", strip_0x(&proof.public_inputs.nullifier));
+    begin_transfer(wallet, consumed_idx, &nullifier, outputs)?;
+    wallet.pending.last_mut().expect("begin_transfer pushed a record").proof = Some(proof);
+    Ok(())
+}
+
+/// The transfer landed: add its outputs to the wallet.
+pub fn confirm_transfer(wallet: &mut WalletData, nullifier: &str) -> Result<()> {
+    let pending = take_pending(wallet, nullifier)?;
+    wallet.notes.extend(pending.outputs);
+    Ok(())
+}
+
+/// The transfer failed: unmark the consumed note and drop the outputs.
+pub fn rollback_transfer(wallet: &mut WalletData, nullifier: &str) -> Result<()> {
+    let pending = take_pending(wallet, nullifier)?;
+    if let Some(note) = wallet.notes.iter_mut().find(|n| n.commitment == pending.consumed) {
+        note.spent = false;
+    }
+    Ok(())
+}
+
+fn take_pending(wallet: &mut WalletData, nullifier: &str) -> Result<PendingTransfer> {
+    let pos = wallet
+        .pending
+        .iter()
+        .position(|p| p.nullifier == nullifier)
+        .with_context(|| format!("no pending transfer {nullifier}"))?;
+    Ok(wallet.pending.remove(pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+
+    fn note(value: u64, commitment: u64) -> NoteEntry {
+        NoteEntry {
+            value,
+            app_tag: 1,
+            owner: "0x01".into(),
+            nonce: "0x02".into(),
+            unlock_after: 0,
+            commitment: Commitment(Fr::from(commitment)),
+            index: Some(crate::LeafIndex(0)),
+            spent: false,
+            meta: Default::default(),
+        }
+    }
+
+    fn wallet() -> WalletData {
+        let mut w = WalletData::for_test(&crate::SecretKey(Fr::from(1u64)));
+        w.notes = vec![note(500, 0xa0), note(300, 0xa1)];
+        w
+    }
+
+    fn outputs() -> Vec<NoteEntry> {
+        let mut out = vec![note(200, 0xb0), note(300, 0xb1)];
+        for n in &mut out {
+            n.index = None;
+        }
+        out
+    }
+
+    #[test]
+    fn begin_reserves_note_without_adding_outputs() {
+        let mut w = wallet();
+        begin_transfer(&mut w, 0, "0xnf", outputs()).unwrap();
+        assert!(w.notes[0].spent);
+        assert_eq!(w.notes.len(), 2);
+        assert_eq!(w.pending.len(), 1);
+        assert_eq!(w.pending[0].consumed, Commitment(Fr::from(0xa0u64)));
+    }
+
+    #[test]
+    fn confirm_adds_outputs() {
+        let mut w = wallet();
+        begin_transfer(&mut w, 0, "0xnf", outputs()).unwrap();
+        confirm_transfer(&mut w, "0xnf").unwrap();
+        assert!(w.pending.is_empty());
+        assert!(w.notes[0].spent);
+        let values: Vec<u64> = w.notes.iter().map(|n| n.value).collect();
+        assert_eq!(values, [500, 300, 200, 300]);
+    }
+
+    #[test]
+    fn rollback_restores_note() {
+        let mut w = wallet();
+        begin_transfer(&mut w, 0, "0xnf", outputs()).unwrap();
+        rollback_transfer(&mut w, "0xnf").unwrap();
+        assert!(w.pending.is_empty());
+        assert!(!w.notes[0].spent);
+        assert_eq!(w.notes.len(), 2);
+    }
+
+    #[test]
+    fn begin_rejects_spent_note_and_duplicate() {
+        let mut w = wallet();
+        begin_transfer(&mut w, 0, "0xnf", outputs()).unwrap();
+        assert!(begin_transfer(&mut w, 0, "0xother", outputs()).is_err());
+        assert!(begin_transfer(&mut w, 1, "0xnf", outputs()).is_err());
+        assert!(begin_transfer(&mut w, 7, "0xnew", outputs()).is_err());
+        assert!(!w.notes[1].spent);
+        assert_eq!(w.pending.len(), 1);
+    }
+
+    #[test]
+    fn unknown_nullifier_is_an_error() {
+        let mut w = wallet();
+        assert!(confirm_transfer(&mut w, "0xnf").is_err());
+        assert!(rollback_transfer(&mut w, "0xnf").is_err());
+    }
+
+    #[test]
+    fn parked_proof_survives_a_round_trip() {
+        let proved: ProvedTransfer = serde_json::from_str(
+            r#"{"proof":{"a":"aa","b":"bb","c":"cc"},
+                "public_inputs":{"old_root":"01","nullifier":"0f","cm_0":"03","cm_1":"04","current_ledger":5}}"#,
+        )
+        .unwrap();
+        let mut w = wallet();
+        begin_transfer_with_proof(&mut w, 0, proved, outputs()).unwrap();
+        assert!(w.notes[0].spent);
+
+        let w: WalletData = serde_json::from_str(&serde_json::to_string(&w).unwrap()).unwrap();
+        assert_eq!(w.pending[0].nullifier, "0x0f");
+        let proof = w.pending[0].proof.as_ref().unwrap();
+        assert_eq!((proof.proof.c.as_str(), proof.public_inputs.current_ledger), ("cc", 5));
+
+        // records without a proof don't write the field
+        let mut w = wallet();
+        begin_transfer(&mut w, 0, "0xnf", outputs()).unwrap();
+        assert!(!serde_json::to_string(&w.pending[0]).unwrap().contains("proof"));
+    }
+
+    #[test]
+    fn wallet_without_pending_field_loads() {
+        let mut json = serde_json::to_value(wallet()).unwrap();
+        json.as_object_mut().unwrap().remove("pending");
+        let w: WalletData = serde_json::from_value(json).unwrap();
+        assert!(w.pending.is_empty());
+    }
+}
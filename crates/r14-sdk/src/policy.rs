@@ -0,0 +1,199 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Spending limits kept in the wallet.
+//!
+//! A custodial or corporate deployment can put a [`SpendingPolicy`] in
+//! [`WalletData::policy`](crate::wallet::WalletData::policy):
+//! a cap on each transfer, a cap on the total sent per UTC day, and a list
+//! of owners transfers may pay. `R14Client::from_wallet` picks it up, and
+//! `R14Client::transfer` then refuses a transfer that breaks it with
+//! `R14Error::PolicyViolation` before any proving.
+//!
+//! Transfers back to the wallet's own owner hash move nothing out, so the
+//! policy doesn't apply to them. The daily total starts from the
+//! transfers in the wallet's history for today and grows as the client
+//! sends more. The policy lives in the wallet file, so it guards against
+//! mistakes and misuse of the SDK, not against whoever can edit that file.
+//!
+//! ```rust
+//! use ark_bls12_381::Fr;
+//! use r14_sdk::policy::SpendingPolicy;
+//!
+//! let policy = SpendingPolicy {
+//!     max_per_transfer: Some(500),
+//!     daily_limit: Some(1_000),
+//!     allowlist: None,
+//! };
+//! let bob = Fr::from(7u64);
+//! assert!(policy.check(&bob, 400, 0).is_ok());
+//! assert!(policy.check(&bob, 600, 0).is_err()); // over the per-transfer cap
+//! assert!(policy.check(&bob, 400, 700).is_err()); // over the daily total
+//! ```
+
+use ark_bls12_381::Fr;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{R14Error, R14Result};
+use crate::history::{HistoryEntry, HistoryKind};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Limits on outgoing transfers; `None` fields don't limit anything
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingPolicy {
+    /// Largest value a single transfer may send
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_per_transfer: Option<u64>,
+    /// Most the wallet may send per UTC day, summed over transfers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_limit: Option<u64>,
+    /// Owners transfers may pay, as owner hashes or `r14o1` addresses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowlist: Option<Vec<String>>,
+}
+
+impl SpendingPolicy {
+    /// `PolicyViolation` unless sending `value` to `recipient` is allowed
+    /// after `spent_today` has already gone out today
+    pub fn check(&self, recipient: &Fr, value: u64, spent_today: u64) -> R14Result<()> {
+        if let Some(max) = self.max_per_transfer {
+            if value > max {
+                return Err(R14Error::PolicyViolation(format!(
+                    "transfer of {value} exceeds the per-transfer limit {max}"
+                )));
+            }
+        }
+        if let Some(limit) = self.daily_limit {
+            if spent_today.saturating_add(value) > limit {
+                return Err(R14Error::PolicyViolation(format!(
+                    "transfer of {value} exceeds the daily limit {limit} ({spent_today} sent today)"
+                )));
+            }
+        }
+        if let Some(allowlist) = &self.allowlist {
+            let mut allowed = false;
+            for entry in allowlist {
+                let owner = crate::wallet::parse_owner(entry)
+                    .map_err(|e| R14Error::Config(format!("spending policy allowlist: {e:#}")))?;
+                allowed |= owner == *recipient;
+            }
+            if !allowed {
+                return Err(R14Error::PolicyViolation(format!(
+                    "recipient {} is not on the allowlist",
+                    crate::wallet::fr_to_hex(recipient)
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Current UTC day, counted from the Unix epoch
+pub fn today() -> u64 {
+    crate::wallet::now_unix().unwrap_or(0) / SECONDS_PER_DAY
+}
+
+/// Value `history` records sent to owners other than `own_owner` on UTC
+/// day `day`. Change and self-transfers don't count; entries without a
+/// timestamp can't be placed on a day and are skipped.
+pub fn spent_on(history: &[HistoryEntry], own_owner: &Fr, day: u64) -> u64 {
+    history
+        .iter()
+        .filter(|e| e.kind == HistoryKind::Transfer)
+        .filter(|e| e.timestamp.is_some_and(|t| t / SECONDS_PER_DAY == day))
+        .filter(|e| {
+            e.counterparty
+                .as_deref()
+                .and_then(|c| crate::wallet::hex_to_fr(c).ok())
+                .is_none_or(|c| c != *own_owner)
+        })
+        .map(|e| e.value)
+        .fold(0, u64::saturating_add)
+}
+
+/// A policy with the running daily total, as the client keeps it
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub(crate) struct PolicyState {
+    policy: SpendingPolicy,
+    day: u64,
+    spent: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl PolicyState {
+    pub(crate) fn new(policy: SpendingPolicy, spent_today: u64) -> Self {
+        Self { policy, day: today(), spent: spent_today }
+    }
+
+    /// Start a new daily total once the day has changed
+    fn roll_over(&mut self) {
+        let day = today();
+        if day != self.day {
+            self.day = day;
+            self.spent = 0;
+        }
+    }
+
+    pub(crate) fn check(&mut self, owner: &Fr, recipient: &Fr, value: u64) -> R14Result<()> {
+        if owner == recipient {
+            return Ok(());
+        }
+        self.roll_over();
+        self.policy.check(recipient, value, self.spent)
+    }
+
+    /// Count a transfer that was submitted
+    pub(crate) fn record(&mut self, owner: &Fr, recipient: &Fr, value: u64) {
+        if owner != recipient {
+            self.roll_over();
+            self.spent = self.spent.saturating_add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Commitment;
+
+    #[test]
+    fn allowlist_takes_hex_or_addresses() {
+        let (alice, bob, carol) = (Fr::from(1u64), Fr::from(2u64), Fr::from(3u64));
+        let policy = SpendingPolicy {
+            allowlist: Some(vec![crate::wallet::fr_to_hex(&alice), crate::wallet::owner_address(&bob)]),
+            ..Default::default()
+        };
+        assert!(policy.check(&alice, 5, 0).is_ok() && policy.check(&bob, 5, 0).is_ok());
+        let err = policy.check(&carol, 5, 0).unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::PolicyViolation);
+
+        let broken = SpendingPolicy { allowlist: Some(vec!["nope".into()]), ..Default::default() };
+        assert_eq!(broken.check(&alice, 5, 0).unwrap_err().code(), crate::error::ErrorCode::Config);
+    }
+
+    #[test]
+    fn daily_total_skips_self_transfers_and_other_days() {
+        let (me, bob) = (Fr::from(1u64), Fr::from(2u64));
+        let cm = [Commitment(Fr::from(8u64)), Commitment(Fr::from(9u64))];
+        let sent = |value, to: &Fr, day: u64| HistoryEntry {
+            timestamp: Some(day * SECONDS_PER_DAY + 60),
+            ..HistoryEntry::transfer(value, 1, "0x01", cm, crate::wallet::fr_to_hex(to), "ok")
+        };
+        let history = vec![
+            sent(100, &bob, 9),
+            sent(30, &bob, 10),
+            sent(500, &me, 10),
+            HistoryEntry { timestamp: Some(10 * SECONDS_PER_DAY), ..HistoryEntry::deposit(70, 1, cm[0], "ok") },
+            sent(20, &bob, 10),
+        ];
+        assert_eq!(spent_on(&history, &me, 10), 50);
+
+        let mut state = PolicyState::new(SpendingPolicy { daily_limit: Some(60), ..Default::default() }, 50);
+        assert!(state.check(&me, &me, 1_000).is_ok());
+        assert!(state.check(&me, &bob, 10).is_ok());
+        state.record(&me, &bob, 10);
+        assert!(state.check(&me, &bob, 1).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! `.r14proof` files: a proof with everything needed to submit it.
+//!
+//! A proof generated on one machine (say, an offline box holding the
+//! secret key) can be carried to another and submitted from there. The
+//! file pins the circuit_id the proof was made for, so the submitter can
+//! check it against the contract before spending a transaction on it.
+//!
+//! # Layout
+//!
+//! All integers are big-endian.
+//!
+//! | Field | Size |
+//! |---|---|
+//! | magic `R14P` | 4 |
+//! | version (currently 1) | 2 |
+//! | circuit_id | 32 |
+//! | `a`, `b`, `c`, each as length + bytes | 2 + n, three times |
+//! | public input count, then each input | 2 + 32 × count |
+//! | metadata length, then a JSON object of strings (0 = none) | 4 + n |
+//!
+//! Points and public inputs use the [`crate::serialize`] encodings.
+//! Readers reject other versions and trailing bytes, so a file never has
+//! two interpretations.
+//!
+//! ```rust,no_run
+//! use r14_sdk::proof_file::{read_proof_file, write_proof_file};
+//!
+//! # fn example(file: r14_sdk::proof_file::ProofFile) -> anyhow::Result<()> {
+//! write_proof_file("transfer.r14proof".as_ref(), &file)?;
+//! let back = read_proof_file("transfer.r14proof".as_ref())?;
+//! let prebuilt = back.transfer_proof()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, ensure, Context, Result};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::PrebuiltProof;
+use crate::serialize::SerializedProofBytes;
+
+pub const MAGIC: [u8; 4] = *b"R14P";
+pub const VERSION: u16 = 1;
+/// File extension, without the dot
+pub const EXTENSION: &str = "r14proof";
+
+/// A proof, its public inputs, and the circuit they belong to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofFile {
+    pub circuit_id: [u8; 32],
+    pub proof: SerializedProofBytes,
+    /// Big-endian field elements, in verifier order
+    pub public_inputs: Vec<[u8; 32]>,
+    /// Free-form notes for whoever submits it (e.g. `circuit = "transfer"`)
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl ProofFile {
+    pub fn new(circuit_id: [u8; 32], proof: SerializedProofBytes, public_inputs: Vec<[u8; 32]>) -> Self {
+        Self {
+            circuit_id,
+            proof,
+            public_inputs,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&VERSION.to_be_bytes());
+        out.extend_from_slice(&self.circuit_id);
+        for point in [&self.proof.a, &self.proof.b, &self.proof.c] {
+            out.extend_from_slice(&u16::try_from(point.len()).expect("curve point fits in u16").to_be_bytes());
+            out.extend_from_slice(point);
+        }
+        let count = u16::try_from(self.public_inputs.len()).expect("public input count fits in u16");
+        out.extend_from_slice(&count.to_be_bytes());
+        for input in &self.public_inputs {
+            out.extend_from_slice(input);
+        }
+        let metadata = if self.metadata.is_empty() {
+            Vec::new()
+        } else {
+            serde_json::to_vec(&self.metadata).expect("string map serializes")
+        };
+        out.extend_from_slice(&u32::try_from(metadata.len()).expect("metadata fits in u32").to_be_bytes());
+        out.extend_from_slice(&metadata);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut r = Reader(bytes);
+        ensure!(r.take(4)? == MAGIC, "not an .r14proof file");
+        let version = u16::from_be_bytes(r.array()?);
+        if version != VERSION {
+            bail!("unsupported .r14proof version {version} (this build reads {VERSION})");
+        }
+        let circuit_id = r.array()?;
+        let mut point = || -> Result<Vec<u8>> {
+            let len = u16::from_be_bytes(r.array()?) as usize;
+            Ok(r.take(len)?.to_vec())
+        };
+        let proof = SerializedProofBytes {
+            a: point()?,
+            b: point()?,
+            c: point()?,
+        };
+        let count = u16::from_be_bytes(r.array()?);
+        let public_inputs = (0..count).map(|_| r.array()).collect::<Result<_>>()?;
+        let len = u32::from_be_bytes(r.array()?) as usize;
+        let metadata = match r.take(len)? {
+            [] => BTreeMap::new(),
+            json => serde_json::from_slice(json).context("bad .r14proof metadata")?,
+        };
+        ensure!(r.0.is_empty(), "{} trailing bytes after .r14proof", r.0.len());
+        Ok(Self {
+            circuit_id,
+            proof,
+            public_inputs,
+            metadata,
+        })
+    }
+
+    /// Read as a transfer proof (public inputs old_root, nullifier, cm_0,
+    /// cm_1, current_ledger), ready for [`crate::R14Client::submit_transfer`]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn transfer_proof(&self) -> Result<PrebuiltProof> {
+        let [old_root, nullifier, cm_0, cm_1, ledger] = self.public_inputs[..] else {
+            bail!("transfer proofs have 5 public inputs, file has {}", self.public_inputs.len());
+        };
+        let (high, low) = ledger.split_at(24);
+        ensure!(high.iter().all(|&b| b == 0), "current_ledger public input is not a u64");
+        let sp = self.proof.to_hex();
+        Ok(PrebuiltProof {
+            proof_json: format!(r#"{{"a":"{}","b":"{}","c":"{}"}}"#, sp.a, sp.b, sp.c),
+            old_root: hex::encode(old_root),
+            nullifier: hex::encode(nullifier),
+            cm_0: hex::encode(cm_0),
+            cm_1: hex::encode(cm_1),
+            current_ledger: u64::from_be_bytes(low.try_into().unwrap()),
+        })
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        ensure!(self.0.len() >= n, "truncated .r14proof file");
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+pub fn write_proof_file(path: &Path, file: &ProofFile) -> Result<()> {
+    std::fs::write(path, file.to_bytes()).with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn read_proof_file(path: &Path) -> Result<ProofFile> {
+    let bytes = std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    ProofFile::from_bytes(&bytes).with_context(|| format!("bad proof file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> ProofFile {
+        let proof = SerializedProofBytes {
+            a: vec![1; 96],
+            b: vec![2; 192],
+            c: vec![3; 96],
+        };
+        let mut ledger = [0u8; 32];
+        ledger[31] = 100;
+        let inputs = vec![[4; 32], [5; 32], [6; 32], [7; 32], ledger];
+        ProofFile::new([9; 32], proof, inputs).with_metadata("circuit", "transfer")
+    }
+
+    #[test]
+    fn roundtrip() {
+        let file = sample();
+        let bytes = file.to_bytes();
+        assert_eq!(&bytes[..6], b"R14P\x00\x01");
+        assert_eq!(ProofFile::from_bytes(&bytes).unwrap(), file);
+
+        let bare = ProofFile { metadata: BTreeMap::new(), ..file };
+        assert_eq!(&bare.to_bytes()[bare.to_bytes().len() - 4..], &[0; 4]);
+        assert_eq!(ProofFile::from_bytes(&bare.to_bytes()).unwrap(), bare);
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        let bytes = sample().to_bytes();
+        let err = |b: &[u8]| ProofFile::from_bytes(b).unwrap_err().to_string();
+        assert_eq!(err(b"nope"), "not an .r14proof file");
+        let mut v2 = bytes.clone();
+        v2[5] = 2;
+        assert!(err(&v2).contains("version 2"));
+        assert_eq!(err(&bytes[..bytes.len() - 1]), "truncated .r14proof file");
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(err(&trailing).contains("trailing"));
+    }
+
+    #[test]
+    fn transfer_proof_fields() {
+        let prebuilt = sample().transfer_proof().unwrap();
+        assert_eq!(prebuilt.nullifier, "05".repeat(32));
+        assert_eq!(prebuilt.current_ledger, 100);
+        assert!(prebuilt.proof_json.starts_with(r#"{"a":"0101"#));
+
+        let mut file = sample();
+        file.public_inputs.pop();
+        assert!(file.transfer_proof().is_err());
+    }
+}
@@ -9,12 +9,206 @@
 //! [dependencies]
 //! r14-sdk = { workspace = true, features = ["prove"] }
 //! ```
+//!
+//! [`spawn_prove`] runs a transfer proof on its own thread and returns a
+//! [`ProvingHandle`] to wait on, await, watch or cancel, so an interactive
+//! app can drop a transfer the user gave up on:
+//!
+//! ```rust,no_run
+//! # async fn example(
+//! #     pk: std::sync::Arc<ark_groth16::ProvingKey<ark_bls12_381::Bls12_381>>,
+//! #     sk: ark_bls12_381::Fr,
+//! #     note: r14_sdk::Note,
+//! #     path: r14_sdk::MerklePath,
+//! #     outputs: [r14_sdk::Note; 2],
+//! # ) -> Result<(), r14_sdk::prove::CircuitError> {
+//! let handle = r14_sdk::prove::spawn_prove(pk, sk, note, path, outputs, 0);
+//! let cancel = handle.cancel_token(); // e.g. for a "Cancel" button
+//! let (proof, pi) = handle.await?;    // `Err(CircuitError::Cancelled)` if it was pressed
+//! # Ok(())
+//! # }
+//! ```
 
 pub use r14_circuit::{
-    constraint_count, prove, setup, verify_offchain, PublicInputs, TransferCircuit,
+    constraint_count, profile, prove, prove_association, prove_at_depth, prove_audited, prove_cancellable,
+    prove_delegated, prove_signed, prove_with_progress, setup, prove_swap, setup_association, setup_at_depth, setup_audited, setup_delegated,
+    setup_for, setup_signed, setup_swap,
+    signed_public_inputs, spend_digest, swap_digest, swap_public_inputs, verify_association_offchain, verify_audited_offchain, verify_delegated_offchain,
+    transfer_digest, verify_offchain, verify_swap_offchain,
+    AssociationCircuit, AssociationPublicInputs, AuditCiphertext, AuditedOutput, AuditedPublicInputs,
+    AuditedTransferCircuit, AuditorPublicKey, AuditorSecretKey, CancelToken, CircuitError, ConstraintProfile, DelegatedPublicInputs,
+    DelegatedTransferCircuit, ProveProgress, PublicInputs, SignedTransferCircuit, SpendAuthKey, SpendAuthPublicKey,
+    SpendAuthSignature, SwapCircuit, SwapLeg, SwapPublicInputs, TransferCircuit,
 };
 
+/// Stage-by-stage proving behind [`prove_with_progress`]
+pub use r14_circuit::progress;
+
+/// SnarkPack-style aggregation of Groth16 proofs, verified off-chain
+pub use r14_circuit::aggregate;
+
+/// Transfer circuit on BN254, for verifiers without BLS12-381 (`bn254` feature)
+#[cfg(feature = "bn254")]
+pub use r14_circuit::bn254;
+
+/// PLONK backend over a universal setup, verified off-chain (`plonk` feature)
+#[cfg(feature = "plonk")]
+pub use r14_circuit::plonk;
+
 // Re-export serialization from r14-sdk::serialize for convenience
 pub use crate::serialize::{
     serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK,
 };
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use handle::{spawn_prove, ProvingHandle};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod handle {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+    use std::thread::JoinHandle;
+
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_groth16::{Proof, ProvingKey};
+    use tokio::sync::oneshot;
+
+    use super::{CancelToken, CircuitError, ProveProgress, PublicInputs};
+    use crate::{MerklePath, Note};
+
+    type Proved = Result<(Proof<Bls12_381>, PublicInputs), CircuitError>;
+
+    /// Start proving a transfer on a new thread, with a fresh RNG. Takes
+    /// the same witness as [`prove`](super::prove).
+    pub fn spawn_prove(
+        pk: Arc<ProvingKey<Bls12_381>>,
+        secret_key: Fr,
+        consumed_note: Note,
+        merkle_path: MerklePath,
+        created_notes: [Note; 2],
+        current_ledger: u64,
+    ) -> ProvingHandle {
+        let cancel = CancelToken::new();
+        let progress = Arc::new(Mutex::new(None));
+        let (tx, result) = oneshot::channel();
+        let thread = {
+            let (cancel, progress) = (cancel.clone(), progress.clone());
+            std::thread::Builder::new()
+                .name("r14-prove".into())
+                .spawn(move || {
+                    let proved = super::prove_cancellable(
+                        &pk,
+                        secret_key,
+                        consumed_note,
+                        merkle_path,
+                        created_notes,
+                        current_ledger,
+                        &mut crate::wallet::crypto_rng(),
+                        &cancel,
+                        |p| *progress.lock().unwrap_or_else(|e| e.into_inner()) = Some(p),
+                    );
+                    // nobody is waiting if the handle was dropped
+                    let _ = tx.send(proved);
+                })
+                .expect("spawn proving thread")
+        };
+        ProvingHandle {
+            cancel,
+            progress,
+            result,
+            thread: Some(thread),
+        }
+    }
+
+    /// A proof running on its own thread. [`join`](Self::join) it, or
+    /// `.await` it from async code. Dropping the handle cancels the proof.
+    pub struct ProvingHandle {
+        cancel: CancelToken,
+        progress: Arc<Mutex<Option<ProveProgress>>>,
+        result: oneshot::Receiver<Proved>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl ProvingHandle {
+        /// Stop at the next stage or MSM chunk; the result is then
+        /// `Err(CircuitError::Cancelled)`
+        pub fn cancel(&self) {
+            self.cancel.cancel();
+        }
+
+        /// Token that cancels this proof, e.g. to hand to a UI callback
+        pub fn cancel_token(&self) -> CancelToken {
+            self.cancel.clone()
+        }
+
+        /// The latest stage reported, `None` before proving starts
+        pub fn progress(&self) -> Option<ProveProgress> {
+            *self.progress.lock().unwrap_or_else(|e| e.into_inner())
+        }
+
+        pub fn is_finished(&self) -> bool {
+            self.thread.as_ref().is_none_or(|t| t.is_finished())
+        }
+
+        /// Block until the proof is done or cancelled
+        pub fn join(mut self) -> Proved {
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+            self.result.try_recv().unwrap_or_else(|_| Err(panicked()))
+        }
+    }
+
+    impl Future for ProvingHandle {
+        type Output = Proved;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Proved> {
+            Pin::new(&mut self.result)
+                .poll(cx)
+                .map(|r| r.unwrap_or_else(|_| Err(panicked())))
+        }
+    }
+
+    impl Drop for ProvingHandle {
+        fn drop(&mut self) {
+            self.cancel.cancel();
+        }
+    }
+
+    fn panicked() -> CircuitError {
+        CircuitError::Synthesis("proving thread panicked".into())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ark_ff::UniformRand;
+        use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+        #[tokio::test]
+        async fn spawned_proofs_finish_or_cancel() {
+            let mut rng = StdRng::seed_from_u64(3);
+            let (pk, vk) = super::super::setup(&mut rng).unwrap();
+            let pk = Arc::new(pk);
+            let sk = Fr::rand(&mut rng);
+            let owner = crate::owner_hash(&crate::SecretKey(sk)).0;
+            let consumed = Note::new(10, 1, owner, &mut rng);
+            let outputs = [Note::new(4, 1, owner, &mut rng), Note::new(6, 1, owner, &mut rng)];
+            let path = MerklePath {
+                siblings: (0..crate::MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect(),
+                indices: vec![false; crate::MERKLE_DEPTH],
+            };
+
+            let handle = spawn_prove(pk.clone(), sk, consumed.clone(), path.clone(), outputs.clone(), 0);
+            handle.cancel();
+            assert!(matches!(handle.join(), Err(CircuitError::Cancelled)));
+
+            let mut handle = spawn_prove(pk, sk, consumed, path, outputs, 0);
+            let (proof, pi) = (&mut handle).await.unwrap();
+            assert!(super::super::verify_offchain(&vk, &proof, &pi));
+            assert_eq!(handle.progress(), Some(ProveProgress::Done));
+        }
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Client for the `r14-prover` daemon, and the JSON it speaks.
+//!
+//! For callers that can't run a Groth16 prove themselves, browsers above
+//! all: build a [`TransferWitness`] from wallet notes and an indexer path,
+//! post it, and submit the [`ProvedTransfer`] that comes back. The daemon
+//! sees the secret key, so only point this at a prover you trust.
+//!
+//! ```rust,no_run
+//! use r14_sdk::prover::{ProverClient, TransferWitness};
+//!
+//! # async fn example(witness: TransferWitness) -> r14_sdk::error::R14Result<()> {
+//! let prover = ProverClient::new("http://localhost:3001");
+//! let proved = prover.prove_transfer(&witness).await?;
+//! println!("nullifier {}", proved.public_inputs.nullifier);
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use ark_bls12_381::Fr;
+
+use crate::error::{ErrorBody, ErrorCode, R14Error, R14Result};
+use crate::wallet::fr_to_hex;
+use crate::{MerklePath, Note};
+
+/// Note as sent to the prover
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NoteJson {
+    pub value: u64,
+    pub app_tag: u32,
+    pub owner: String,
+    pub nonce: String,
+    /// Timelock ledger; omitted or 0 for an unlocked note
+    #[serde(default)]
+    pub unlock_after: u64,
+}
+
+impl From<&Note> for NoteJson {
+    fn from(note: &Note) -> Self {
+        Self {
+            value: note.value,
+            app_tag: note.app_tag,
+            owner: fr_to_hex(&note.owner),
+            nonce: fr_to_hex(&note.nonce),
+            unlock_after: note.unlock_after,
+        }
+    }
+}
+
+/// Merkle path to the consumed note's commitment
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MerklePathJson {
+    pub siblings: Vec<String>,
+    pub indices: Vec<bool>,
+}
+
+impl From<&MerklePath> for MerklePathJson {
+    fn from(path: &MerklePath) -> Self {
+        Self {
+            siblings: path.siblings.iter().map(fr_to_hex).collect(),
+            indices: path.indices.clone(),
+        }
+    }
+}
+
+/// Full private witness for the 1-in-2-out transfer circuit
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransferWitness {
+    pub secret_key: String,
+    pub consumed_note: NoteJson,
+    pub merkle_path: MerklePathJson,
+    pub created_notes: [NoteJson; 2],
+    /// Ledger to prove at; must not be ahead of the chain when submitted
+    #[serde(default)]
+    pub current_ledger: u64,
+}
+
+impl TransferWitness {
+    pub fn new(
+        secret_key: &Fr,
+        consumed_note: &Note,
+        merkle_path: &MerklePath,
+        created_notes: [&Note; 2],
+        current_ledger: u64,
+    ) -> Self {
+        Self {
+            secret_key: fr_to_hex(secret_key),
+            consumed_note: consumed_note.into(),
+            merkle_path: merkle_path.into(),
+            created_notes: created_notes.map(NoteJson::from),
+            current_ledger,
+        }
+    }
+}
+
+/// Hex-encoded Groth16 proof elements
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProofJson {
+    pub a: String,
+    pub b: String,
+    pub c: String,
+}
+
+/// Public inputs, named after the transfer contract's arguments
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PublicInputsJson {
+    pub old_root: String,
+    pub nullifier: String,
+    pub cm_0: String,
+    pub cm_1: String,
+    pub current_ledger: u64,
+}
+
+/// Response body: proof plus the public inputs it was generated against
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ProvedTransfer {
+    pub proof: ProofJson,
+    pub public_inputs: PublicInputsJson,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<ProvedTransfer> for crate::client::PrebuiltProof {
+    fn from(proved: ProvedTransfer) -> Self {
+        let ProofJson { a, b, c } = proved.proof;
+        let pi = proved.public_inputs;
+        Self {
+            proof_json: format!(r#"{{"a":"{a}","b":"{b}","c":"{c}"}}"#),
+            old_root: pi.old_root,
+            nullifier: pi.nullifier,
+            cm_0: pi.cm_0,
+            cm_1: pi.cm_1,
+            current_ledger: pi.current_ledger,
+        }
+    }
+}
+
+/// The inverse, for handing a locally built proof to something that
+/// speaks this JSON (e.g. a relayer)
+#[cfg(not(target_arch = "wasm32"))]
+impl TryFrom<&crate::client::PrebuiltProof> for ProvedTransfer {
+    type Error = R14Error;
+
+    fn try_from(prebuilt: &crate::client::PrebuiltProof) -> R14Result<Self> {
+        let proof = serde_json::from_str(&prebuilt.proof_json)
+            .map_err(|e| R14Error::Other(anyhow::anyhow!("bad proof_json: {e}")))?;
+        Ok(Self {
+            proof,
+            public_inputs: PublicInputsJson {
+                old_root: prebuilt.old_root.clone(),
+                nullifier: prebuilt.nullifier.clone(),
+                cm_0: prebuilt.cm_0.clone(),
+                cm_1: prebuilt.cm_1.clone(),
+                current_ledger: prebuilt.current_ledger,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProverClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl ProverClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `/v1/health` — `Ok` when the daemon is up
+    pub async fn health(&self) -> R14Result<()> {
+        let resp = self.http.get(format!("{}/v1/health", self.base_url)).send().await;
+        decode::<serde_json::Value>(resp).await.map(|_| ())
+    }
+
+    /// `POST /v1/prove/transfer`. A full queue comes back as
+    /// `R14Error::Prover` with `ErrorCode::Unavailable`; retry later.
+    pub async fn prove_transfer(&self, witness: &TransferWitness) -> R14Result<ProvedTransfer> {
+        let resp = self
+            .http
+            .post(format!("{}/v1/prove/transfer", self.base_url))
+            .json(witness)
+            .send()
+            .await;
+        decode(resp).await
+    }
+}
+
+fn prover_error(code: ErrorCode, message: impl Into<String>) -> R14Error {
+    R14Error::Prover { code, message: message.into() }
+}
+
+/// Decode a 2xx body as `T`; otherwise keep the daemon's error code
+async fn decode<T: serde::de::DeserializeOwned>(
+    resp: Result<reqwest::Response, reqwest::Error>,
+) -> R14Result<T> {
+    let resp = resp.map_err(|e| prover_error(ErrorCode::Unavailable, e.to_string()))?;
+    let status = resp.status();
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| prover_error(ErrorCode::Unavailable, e.to_string()))?;
+    if !status.is_success() {
+        return Err(match serde_json::from_slice::<ErrorBody>(&bytes) {
+            Ok(body) => prover_error(body.code, body.error),
+            Err(_) => prover_error(ErrorCode::Internal, format!("HTTP {status}")),
+        });
+    }
+    serde_json::from_slice(&bytes).map_err(|e| prover_error(ErrorCode::Internal, format!("bad response: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn witness_uses_wallet_hex() {
+        let mut rng = ark_std::test_rng();
+        let note = Note::new(7, 1, Fr::from(3u64), &mut rng).locked_until(9);
+        let path = MerklePath { siblings: vec![Fr::from(1u64)], indices: vec![true] };
+        let w = TransferWitness::new(&Fr::from(2u64), &note, &path, [&note, &note], 12);
+        assert_eq!(w.secret_key, fr_to_hex(&Fr::from(2u64)));
+        assert_eq!(w.consumed_note.nonce, fr_to_hex(&note.nonce));
+        assert_eq!(w.consumed_note.unlock_after, 9);
+        assert_eq!(w.merkle_path.siblings, [fr_to_hex(&Fr::from(1u64))]);
+    }
+
+    #[test]
+    fn proved_transfer_becomes_prebuilt() {
+        let proved: ProvedTransfer = serde_json::from_str(
+            r#"{"proof":{"a":"aa","b":"bb","c":"cc"},
+                "public_inputs":{"old_root":"01","nullifier":"02","cm_0":"03","cm_1":"04","current_ledger":5}}"#,
+        )
+        .unwrap();
+        let prebuilt = crate::client::PrebuiltProof::from(proved);
+        assert_eq!(prebuilt.proof_json, r#"{"a":"aa","b":"bb","c":"cc"}"#);
+        assert_eq!((prebuilt.nullifier.as_str(), prebuilt.current_ledger), ("02", 5));
+
+        let back = ProvedTransfer::try_from(&prebuilt).unwrap();
+        assert_eq!((back.proof.b.as_str(), back.public_inputs.cm_1.as_str()), ("bb", "04"));
+        let bad = crate::client::PrebuiltProof { proof_json: "{}".into(), ..prebuilt };
+        assert!(ProvedTransfer::try_from(&bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn unreachable_prover_is_unavailable() {
+        let err = ProverClient::new("http://127.0.0.1:9").health().await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Unavailable);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Client for an r14 relayer, which submits transfers for users who hold
+//! no XLM.
+//!
+//! The user proves locally and posts a [`RelayRequest`]: the proof, its
+//! public inputs and the new root, exactly what r14-transfer's `transfer`
+//! takes. The relayer calls the contract with its own Stellar key and pays
+//! the fee. Nothing in the request is secret; the proof already binds the
+//! outputs, so a relayer can drop a transfer but not redirect it.
+//!
+//! `R14Client::transfer_via_relayer` does all of this in one call.
+//!
+//! ```rust,no_run
+//! use r14_sdk::relayer::{RelayRequest, RelayerClient};
+//!
+//! # async fn example(request: RelayRequest) -> r14_sdk::error::R14Result<()> {
+//! let relayer = RelayerClient::new("https://relayer.example.com");
+//! let receipt = relayer.submit_transfer(&request).await?;
+//! println!("{}", receipt.tx_result);
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorBody, ErrorCode, R14Error, R14Result};
+use crate::prover::{ProofJson, ProvedTransfer, PublicInputsJson};
+
+/// Body of `POST /v1/relay/transfer`
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RelayRequest {
+    /// Transfer contract to call
+    pub contract_id: String,
+    pub proof: ProofJson,
+    pub public_inputs: PublicInputsJson,
+    /// Root after appending `cm_0` and `cm_1`, raw hex
+    pub new_root: String,
+}
+
+impl RelayRequest {
+    pub fn new(contract_id: &str, proved: ProvedTransfer, new_root: &str) -> Self {
+        Self {
+            contract_id: contract_id.to_string(),
+            proof: proved.proof,
+            public_inputs: proved.public_inputs,
+            new_root: crate::wallet::strip_0x(new_root),
+        }
+    }
+}
+
+/// Response body: the contract call's output, as
+/// `R14Client::submit_transfer` returns it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RelayReceipt {
+    pub tx_result: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct RelayerClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl RelayerClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `/v1/health` — `Ok` when the relayer is up
+    pub async fn health(&self) -> R14Result<()> {
+        let resp = self.http.get(format!("{}/v1/health", self.base_url)).send().await;
+        decode::<serde_json::Value>(resp).await.map(|_| ())
+    }
+
+    /// `POST /v1/relay/transfer`. Rejections come back as
+    /// `R14Error::Relayer` with the relayer's code, e.g. `StaleRoot`.
+    pub async fn submit_transfer(&self, request: &RelayRequest) -> R14Result<RelayReceipt> {
+        let resp = self
+            .http
+            .post(format!("{}/v1/relay/transfer", self.base_url))
+            .json(request)
+            .send()
+            .await;
+        decode(resp).await
+    }
+}
+
+fn relayer_error(code: ErrorCode, message: impl Into<String>) -> R14Error {
+    R14Error::Relayer { code, message: message.into() }
+}
+
+/// Decode a 2xx body as `T`; otherwise keep the relayer's error code
+async fn decode<T: serde::de::DeserializeOwned>(
+    resp: Result<reqwest::Response, reqwest::Error>,
+) -> R14Result<T> {
+    let resp = resp.map_err(|e| relayer_error(ErrorCode::Unavailable, e.to_string()))?;
+    let status = resp.status();
+    let bytes = resp
+        .bytes()
+        .await
+        .map_err(|e| relayer_error(ErrorCode::Unavailable, e.to_string()))?;
+    if !status.is_success() {
+        return Err(match serde_json::from_slice::<ErrorBody>(&bytes) {
+            Ok(body) => relayer_error(body.code, body.error),
+            Err(_) => relayer_error(ErrorCode::Internal, format!("HTTP {status}")),
+        });
+    }
+    serde_json::from_slice(&bytes).map_err(|e| relayer_error(ErrorCode::Internal, format!("bad response: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_carries_the_contract_arguments() {
+        let proved: ProvedTransfer = serde_json::from_str(
+            r#"{"proof":{"a":"aa","b":"bb","c":"cc"},
+                "public_inputs":{"old_root":"01","nullifier":"02","cm_0":"03","cm_1":"04","current_ledger":5}}"#,
+        )
+        .unwrap();
+        let json = serde_json::to_value(RelayRequest::new("CXFER", proved, "0x0f")).unwrap();
+        assert_eq!(json["contract_id"], "CXFER");
+        assert_eq!(json["new_root"], "0f");
+        assert_eq!(json["proof"]["c"], "cc");
+        assert_eq!(json["public_inputs"]["current_ledger"], 5);
+    }
+
+    #[tokio::test]
+    async fn unreachable_relayer_is_unavailable() {
+        let err = RelayerClient::new("http://127.0.0.1:9").health().await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Unavailable);
+        assert!(err.to_string().starts_with("relayer: "), "{err}");
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Exponential-backoff retry for indexer and contract reads.
+//!
+//! Only idempotent calls retry: indexer GETs on transport errors, timeouts
+//! and 5xx responses, and read-only contract calls (`is_spent`,
+//! `latest_root`, ...) on any failure. Submissions never retry, since a
+//! call that timed out may still land, and resubmitting a deposit would
+//! create a second note. [`crate::pending`] covers that case instead.
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use r14_sdk::retry::RetryPolicy;
+//!
+//! let policy = RetryPolicy { max_attempts: 4, ..RetryPolicy::default() };
+//! assert_eq!(policy.backoff(1), Duration::from_millis(200));
+//! assert_eq!(policy.backoff(3), Duration::from_millis(800));
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::error::R14Result;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total tries, including the first; 1 disables retry
+    pub max_attempts: u32,
+    /// Wait after the first failure, doubled after each further one
+    pub initial_backoff: Duration,
+    /// Cap on a single wait
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Try once. The default for clients built with `new`.
+    pub const fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    /// Wait before retry number `attempt` (1 = after the first failure)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// 3 attempts, waiting 200 ms then 400 ms
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// `setTimeout` wrapped in a promise; it exists on windows and workers
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    let ms = js_sys::Number::from(duration.as_millis().min(i32::MAX as u128) as i32);
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let global = js_sys::global();
+        let set_timeout = js_sys::Reflect::get(&global, &js_sys::JsString::from("setTimeout"))
+            .map(js_sys::Function::from)
+            .expect("global scope has setTimeout");
+        let _ = set_timeout.call2(&global, &resolve, &ms);
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// Run `op` until it succeeds or `policy` runs out of attempts
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn retry<T, Fut>(policy: &RetryPolicy, mut op: impl FnMut() -> Fut) -> R14Result<T>
+where
+    Fut: Future<Output = R14Result<T>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Err(_) if attempt < policy.max_attempts => {
+                sleep(policy.backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::R14Error;
+    use std::cell::Cell;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        let waits: Vec<_> = (1..=5).map(|a| policy.backoff(a).as_millis()).collect();
+        assert_eq!(waits, [100, 200, 400, 500, 500]);
+        assert_eq!(policy.backoff(64), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn retries_until_success_or_out_of_attempts() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        };
+        let calls = Cell::new(0);
+        let flaky = || {
+            calls.set(calls.get() + 1);
+            let n = calls.get();
+            async move {
+                if n < 3 {
+                    Err(R14Error::Indexer("down".into()))
+                } else {
+                    Ok(n)
+                }
+            }
+        };
+        assert_eq!(retry(&policy, flaky).await.unwrap(), 3);
+
+        calls.set(0);
+        let failing = || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>(R14Error::Indexer("down".into())) }
+        };
+        assert!(retry(&policy, failing).await.is_err());
+        assert_eq!(calls.get(), 3);
+
+        calls.set(0);
+        let once = || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>(R14Error::Indexer("down".into())) }
+        };
+        assert!(retry(&RetryPolicy::none(), once).await.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -0,0 +1,177 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Contract arguments as `stellar_xdr` [`ScVal`]s.
+//!
+//! The `stellar` CLI takes proofs and VKs as JSON strings and builds the
+//! XDR itself. Code that builds invocations directly (simulating or
+//! submitting over RPC) needs the values as [`ScVal`]s instead, laid out
+//! as the contracts' `#[contracttype]`s encode them:
+//!
+//! | Contract type | `ScVal` |
+//! |---|---|
+//! | `Proof`, `VerificationKey` | `Map`, symbol keys in sorted order |
+//! | `G1Affine`, `G2Affine`, `BytesN<32>` | `Bytes` (96 / 192 / 32) |
+//! | `Fr` | `U256`, big-endian |
+//! | `Vec<T>` | `Vec` |
+//! | `u32`, `u64` | `U32`, `U64` |
+//!
+//! The r14-core tests check each conversion against soroban-sdk's own
+//! encoding of the contract types.
+//!
+//! ```rust
+//! use r14_sdk::scval::{self, ScVal};
+//! use r14_sdk::serialize::SerializedProofBytes;
+//!
+//! let proof = SerializedProofBytes { a: vec![1; 96], b: vec![2; 192], c: vec![3; 96] };
+//! let ScVal::Map(Some(map)) = scval::proof_bytes(&proof) else { unreachable!() };
+//! assert_eq!(map.len(), 3);
+//! assert!(matches!(scval::bytes_n(&[0; 32]), ScVal::Bytes(_)));
+//! ```
+
+use stellar_xdr::curr::{ScBytes, ScMap, ScMapEntry, ScSymbol, ScVec, UInt256Parts};
+
+pub use stellar_xdr::curr::ScVal;
+
+use crate::serialize::{hex_to_bytes_n, BytesError, SerializedProof, SerializedProofBytes, SerializedVK};
+
+fn symbol(name: &str) -> ScVal {
+    ScVal::Symbol(ScSymbol(name.try_into().expect("field names are short symbols")))
+}
+
+fn bytes(b: &[u8]) -> ScVal {
+    ScVal::Bytes(ScBytes(b.to_vec().try_into().expect("bytes are under the XDR limit")))
+}
+
+fn vec(items: Vec<ScVal>) -> ScVal {
+    ScVal::Vec(Some(ScVec(items.try_into().expect("vector is under the XDR limit"))))
+}
+
+/// A `#[contracttype]` struct: a map keyed by field name, sorted
+fn structure(fields: Vec<(&str, ScVal)>) -> ScVal {
+    let entries = fields.into_iter().map(|(k, val)| ScMapEntry { key: symbol(k), val });
+    ScVal::Map(Some(ScMap::sorted_from_entries(entries).expect("field names are distinct")))
+}
+
+fn decode(s: &str) -> Result<Vec<u8>, BytesError> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|_| BytesError::InvalidHex)
+}
+
+/// A `BytesN<32>` argument: roots, nullifiers, commitments, circuit ids
+pub fn bytes_n(b: &[u8; 32]) -> ScVal {
+    bytes(b)
+}
+
+/// [`bytes_n`] from hex, with or without `0x`
+pub fn hex_bytes_n(s: &str) -> Result<ScVal, BytesError> {
+    Ok(bytes_n(&hex_to_bytes_n::<32>(s)?))
+}
+
+/// A BLS12-381 `Fr` from its 32 big-endian bytes, as
+/// [`serialize_fr_bytes`](crate::serialize::serialize_fr_bytes) gives it
+pub fn fr(be: &[u8; 32]) -> ScVal {
+    let word = |i: usize| u64::from_be_bytes(be[i * 8..i * 8 + 8].try_into().expect("8 bytes"));
+    ScVal::U256(UInt256Parts {
+        hi_hi: word(0),
+        hi_lo: word(1),
+        lo_hi: word(2),
+        lo_lo: word(3),
+    })
+}
+
+/// `Vec<Fr>` public inputs, as r14-core's `verify` takes them
+pub fn public_inputs(inputs: &[[u8; 32]]) -> ScVal {
+    vec(inputs.iter().map(fr).collect())
+}
+
+/// [`public_inputs`] from the hex of `serialize_proof_for_soroban`
+pub fn hex_public_inputs(inputs: &[String]) -> Result<ScVal, BytesError> {
+    let frs = inputs
+        .iter()
+        .map(|s| hex_to_bytes_n::<32>(s).map(|b| fr(&b)))
+        .collect::<Result<_, _>>()?;
+    Ok(vec(frs))
+}
+
+/// r14-core's `Proof`
+pub fn proof_bytes(proof: &SerializedProofBytes) -> ScVal {
+    structure(vec![("a", bytes(&proof.a)), ("b", bytes(&proof.b)), ("c", bytes(&proof.c))])
+}
+
+/// [`proof_bytes`] from hex
+pub fn proof(proof: &SerializedProof) -> Result<ScVal, BytesError> {
+    Ok(proof_bytes(&proof.to_bytes()?))
+}
+
+/// r14-core's `VerificationKey`, as `register` takes it
+pub fn vk(svk: &SerializedVK) -> Result<ScVal, BytesError> {
+    let ic = svk
+        .ic
+        .iter()
+        .map(|p| decode(p).map(|b| bytes(&b)))
+        .collect::<Result<_, _>>()?;
+    Ok(structure(vec![
+        ("alpha_g1", bytes(&decode(&svk.alpha_g1)?)),
+        ("beta_g2", bytes(&decode(&svk.beta_g2)?)),
+        ("gamma_g2", bytes(&decode(&svk.gamma_g2)?)),
+        ("delta_g2", bytes(&decode(&svk.delta_g2)?)),
+        ("ic", vec(ic)),
+        ("depth", ScVal::U32(svk.depth)),
+    ]))
+}
+
+/// Arguments of r14-transfer's `transfer`, in order: proof, old_root,
+/// nullifier, cm_0, cm_1, current_ledger, new_root
+#[cfg(not(target_arch = "wasm32"))]
+pub fn transfer_args(prebuilt: &crate::client::PrebuiltProof, new_root: &str) -> Result<Vec<ScVal>, BytesError> {
+    #[derive(serde::Deserialize)]
+    struct ProofJson {
+        a: String,
+        b: String,
+        c: String,
+    }
+    let p: ProofJson = serde_json::from_str(&prebuilt.proof_json).map_err(|_| BytesError::InvalidHex)?;
+    Ok(vec![
+        proof(&SerializedProof { a: p.a, b: p.b, c: p.c })?,
+        hex_bytes_n(&prebuilt.old_root)?,
+        hex_bytes_n(&prebuilt.nullifier)?,
+        hex_bytes_n(&prebuilt.cm_0)?,
+        hex_bytes_n(&prebuilt.cm_1)?,
+        ScVal::U64(prebuilt.current_ledger),
+        hex_bytes_n(new_root)?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structs_are_sorted_maps() {
+        let svk = SerializedVK {
+            alpha_g1: "01".into(),
+            beta_g2: "02".into(),
+            gamma_g2: "03".into(),
+            delta_g2: "04".into(),
+            ic: vec!["05".into(), "06".into()],
+            depth: 20,
+        };
+        let ScVal::Map(Some(map)) = vk(&svk).unwrap() else { panic!("not a map") };
+        let keys: Vec<_> = map.iter().map(|e| e.key.clone()).collect();
+        let expected = ["alpha_g1", "beta_g2", "delta_g2", "depth", "gamma_g2", "ic"].map(symbol);
+        assert_eq!(keys, expected);
+        assert_eq!(map[3].val, ScVal::U32(20));
+
+        assert_eq!(vk(&SerializedVK { alpha_g1: "zz".into(), ..svk }), Err(BytesError::InvalidHex));
+    }
+
+    #[test]
+    fn fr_is_big_endian_u256() {
+        let mut be = [0u8; 32];
+        be[31] = 7;
+        be[0] = 1;
+        let ScVal::U256(parts) = fr(&be) else { panic!("not a U256") };
+        assert_eq!((parts.hi_hi, parts.lo_lo), (1 << 56, 7));
+        assert_eq!(hex_bytes_n("0x12"), Err(BytesError::WrongLength { expected: 32, got: 1 }));
+    }
+}
@@ -0,0 +1,411 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Coin selection: which unspent notes fund a transfer.
+//!
+//! Only on-chain (indexed), unspent notes are eligible, and every note in a
+//! selection shares one `app_tag` (the circuit enforces it). `max_inputs` is
+//! the number of notes the circuit consumes — 1 for today's transfer
+//! circuit ([`TRANSFER_INPUTS`]); larger values pick multi-note sets for a
+//! multi-input circuit.
+//!
+//! ```rust
+//! use r14_sdk::selection::{select_notes, CoinSelection, TRANSFER_INPUTS};
+//! # use r14_sdk::wallet::NoteEntry;
+//! # use r14_sdk::{Commitment, LeafIndex};
+//! # fn note(value: u64) -> NoteEntry {
+//! #     NoteEntry { value, app_tag: 1, owner: "0x1".into(), nonce: "0x2".into(), unlock_after: 0,
+//! #                 commitment: Commitment(3u64.into()), index: Some(LeafIndex(0)), spent: false,
+//! #                 meta: Default::default() }
+//! # }
+//! let notes = vec![note(500), note(120), note(900)];
+//! let picked = select_notes(&notes, 100, CoinSelection::SmallestSufficient, TRANSFER_INPUTS, 0).unwrap();
+//! assert_eq!(picked, vec![1]);
+//! ```
+//!
+//! [`plan_consolidation`] uses the same eligibility rules to plan
+//! self-transfers that merge small notes, for circuits that take more than
+//! one input.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{R14Error, R14Result};
+use crate::wallet::NoteEntry;
+
+/// Notes consumed by the transfer circuit
+pub const TRANSFER_INPUTS: usize = 1;
+
+/// How to choose the notes that fund a transfer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CoinSelection {
+    /// First sufficient note in wallet order
+    #[default]
+    FirstFit,
+    /// Spend the biggest notes first, consolidating the wallet
+    LargestFirst,
+    /// Fewest notes, then the smallest total that covers the amount
+    SmallestSufficient,
+    /// Least change left over, then fewest notes
+    MinimizeChange,
+}
+
+impl CoinSelection {
+    pub const ALL: [CoinSelection; 4] = [
+        Self::FirstFit,
+        Self::LargestFirst,
+        Self::SmallestSufficient,
+        Self::MinimizeChange,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::FirstFit => "first-fit",
+            Self::LargestFirst => "largest-first",
+            Self::SmallestSufficient => "smallest-sufficient",
+            Self::MinimizeChange => "minimize-change",
+        }
+    }
+}
+
+impl fmt::Display for CoinSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CoinSelection {
+    type Err = R14Error;
+
+    fn from_str(s: &str) -> R14Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(|c| c.as_str()).collect();
+                R14Error::Config(format!("unknown coin selection `{s}` (expected {})", names.join(", ")))
+            })
+    }
+}
+
+/// Pick up to `max_inputs` notes covering `value`, returned as indices into
+/// `notes` in ascending order. Ties go to the earliest notes in the wallet.
+/// Notes timelocked past `current_ledger` are skipped.
+pub fn select_notes(
+    notes: &[NoteEntry],
+    value: u64,
+    strategy: CoinSelection,
+    max_inputs: usize,
+    current_ledger: u64,
+) -> R14Result<Vec<usize>> {
+    let eligible: Vec<usize> = (0..notes.len())
+        .filter(|&i| {
+            let n = &notes[i];
+            !n.spent && n.index.is_some() && n.unlock_after <= current_ledger
+        })
+        .collect();
+
+    let mut best: Option<(Vec<usize>, u64)> = None;
+    let mut set = Vec::with_capacity(max_inputs);
+    for_each_set(notes, &eligible, 0, max_inputs, &mut set, &mut |set, total| {
+        if total < value {
+            return;
+        }
+        let better = match &best {
+            None => true,
+            Some((b, b_total)) => ranks_before(strategy, set, total, b, *b_total),
+        };
+        if better {
+            best = Some((set.to_vec(), total));
+        }
+    });
+
+    best.map(|(set, _)| set).ok_or_else(|| R14Error::InsufficientBalance {
+        needed: value,
+        best: best_total(notes, &eligible, max_inputs),
+    })
+}
+
+/// Visit every non-empty same-`app_tag` set of at most `max` eligible notes
+fn for_each_set(
+    notes: &[NoteEntry],
+    eligible: &[usize],
+    start: usize,
+    max: usize,
+    set: &mut Vec<usize>,
+    visit: &mut impl FnMut(&[usize], u64),
+) {
+    if set.len() == max {
+        return;
+    }
+    for pos in start..eligible.len() {
+        let i = eligible[pos];
+        if let Some(&first) = set.first() {
+            if notes[i].app_tag != notes[first].app_tag {
+                continue;
+            }
+        }
+        set.push(i);
+        let total = set.iter().map(|&j| notes[j].value).fold(0u64, u64::saturating_add);
+        visit(set, total);
+        for_each_set(notes, eligible, pos + 1, max, set, visit);
+        set.pop();
+    }
+}
+
+/// Whether candidate `a` beats the current best `b` under `strategy`
+fn ranks_before(strategy: CoinSelection, a: &[usize], a_total: u64, b: &[usize], b_total: u64) -> bool {
+    use std::cmp::Ordering;
+
+    let by_count = a.len().cmp(&b.len());
+    let primary = match strategy {
+        CoinSelection::FirstFit => by_count,
+        CoinSelection::LargestFirst => by_count.then(b_total.cmp(&a_total)),
+        CoinSelection::SmallestSufficient => by_count.then(a_total.cmp(&b_total)),
+        CoinSelection::MinimizeChange => a_total.cmp(&b_total).then(by_count),
+    };
+    // wallet order breaks ties
+    primary.then_with(|| a.cmp(b)) == Ordering::Less
+}
+
+/// Largest amount any allowed set could fund (for the insufficient-balance error)
+fn best_total(notes: &[NoteEntry], eligible: &[usize], max_inputs: usize) -> u64 {
+    let mut by_tag: std::collections::BTreeMap<u32, Vec<u64>> = Default::default();
+    for &i in eligible {
+        by_tag.entry(notes[i].app_tag).or_default().push(notes[i].value);
+    }
+    by_tag
+        .into_values()
+        .map(|mut values| {
+            values.sort_unstable_by(|a, b| b.cmp(a));
+            values.into_iter().take(max_inputs).fold(0u64, u64::saturating_add)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// A note spent by a consolidation step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlanInput {
+    /// Index into the wallet's notes
+    Note(usize),
+    /// The note produced by an earlier step, by step number
+    Step(usize),
+}
+
+/// One self-transfer: spend `inputs`, receive a single note worth `value`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsolidationStep {
+    pub inputs: Vec<PlanInput>,
+    pub app_tag: u32,
+    pub value: u64,
+}
+
+/// Self-transfers to run in order. A step that spends an earlier step's
+/// output has to wait until that output is indexed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsolidationPlan {
+    pub steps: Vec<ConsolidationStep>,
+    /// Eligible notes left once every step has run
+    pub final_count: usize,
+}
+
+/// Plan self-transfers of up to `max_inputs` notes each that bring the
+/// eligible notes down to `target_count`, merging the smallest first.
+///
+/// Notes only merge with notes of the same `app_tag`, so the plan stops
+/// short of `target_count` when every tag is down to one note; check
+/// [`ConsolidationPlan::final_count`]. The second output of each
+/// self-transfer is worth 0 and isn't counted.
+pub fn plan_consolidation(
+    notes: &[NoteEntry],
+    target_count: usize,
+    max_inputs: usize,
+    current_ledger: u64,
+) -> R14Result<ConsolidationPlan> {
+    if max_inputs < 2 {
+        return Err(R14Error::Config(format!(
+            "consolidation needs a transfer circuit with at least 2 inputs, this one takes {max_inputs}"
+        )));
+    }
+
+    // per app_tag, the spendable notes as (value, input)
+    let mut groups: std::collections::BTreeMap<u32, Vec<(u64, PlanInput)>> = Default::default();
+    for (i, n) in notes.iter().enumerate() {
+        if !n.spent && n.index.is_some() && n.unlock_after <= current_ledger {
+            groups.entry(n.app_tag).or_default().push((n.value, PlanInput::Note(i)));
+        }
+    }
+
+    let mut plan = ConsolidationPlan::default();
+    let mut count: usize = groups.values().map(Vec::len).sum();
+    while count > target_count {
+        // the most fragmented tag; ties go to the lowest tag
+        let Some((&app_tag, group)) = groups
+            .iter_mut()
+            .filter(|(_, g)| g.len() > 1)
+            .max_by(|(ta, a), (tb, b)| a.len().cmp(&b.len()).then(tb.cmp(ta)))
+        else {
+            break;
+        };
+        // stable: equal values keep wallet order
+        group.sort_by_key(|&(value, _)| value);
+        let take = max_inputs.min(count - target_count + 1).min(group.len());
+        let merged: Vec<_> = group.drain(..take).collect();
+        let value = merged.iter().map(|&(v, _)| v).fold(0u64, u64::saturating_add);
+        group.push((value, PlanInput::Step(plan.steps.len())));
+        plan.steps.push(ConsolidationStep {
+            inputs: merged.into_iter().map(|(_, input)| input).collect(),
+            app_tag,
+            value,
+        });
+        count -= take - 1;
+    }
+    plan.final_count = count;
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(value: u64, app_tag: u32) -> NoteEntry {
+        NoteEntry {
+            value,
+            app_tag,
+            owner: "0x01".into(),
+            nonce: "0x02".into(),
+            unlock_after: 0,
+            commitment: crate::Commitment(3u64.into()),
+            index: Some(crate::LeafIndex(0)),
+            spent: false,
+            meta: Default::default(),
+        }
+    }
+
+    fn wallet(values: &[u64]) -> Vec<NoteEntry> {
+        values.iter().map(|&v| note(v, 1)).collect()
+    }
+
+    fn pick(notes: &[NoteEntry], value: u64, strategy: CoinSelection, max: usize) -> Vec<usize> {
+        select_notes(notes, value, strategy, max, 0).unwrap()
+    }
+
+    #[test]
+    fn single_input_strategies() {
+        let w = wallet(&[500, 120, 900, 300]);
+        assert_eq!(pick(&w, 200, CoinSelection::FirstFit, 1), vec![0]);
+        assert_eq!(pick(&w, 200, CoinSelection::LargestFirst, 1), vec![2]);
+        assert_eq!(pick(&w, 200, CoinSelection::SmallestSufficient, 1), vec![3]);
+        assert_eq!(pick(&w, 200, CoinSelection::MinimizeChange, 1), vec![3]);
+    }
+
+    #[test]
+    fn skips_spent_and_unindexed_notes() {
+        let mut w = wallet(&[500, 300, 250]);
+        w[1].spent = true;
+        w[2].index = None;
+        for strategy in CoinSelection::ALL {
+            assert_eq!(pick(&w, 200, strategy, 1), vec![0], "{strategy}");
+        }
+    }
+
+    #[test]
+    fn skips_notes_locked_past_current_ledger() {
+        let mut w = wallet(&[500, 300]);
+        w[0].unlock_after = 1_000;
+        assert_eq!(select_notes(&w, 200, CoinSelection::LargestFirst, 1, 999).unwrap(), vec![1]);
+        assert_eq!(select_notes(&w, 200, CoinSelection::LargestFirst, 1, 1_000).unwrap(), vec![0]);
+
+        let err = select_notes(&w, 400, CoinSelection::FirstFit, 1, 999).unwrap_err();
+        assert!(matches!(err, R14Error::InsufficientBalance { best: 300, .. }));
+    }
+
+    #[test]
+    fn insufficient_reports_best_single_note() {
+        let w = wallet(&[500, 300]);
+        let err = select_notes(&w, 600, CoinSelection::FirstFit, 1, 0).unwrap_err();
+        assert!(matches!(err, R14Error::InsufficientBalance { needed: 600, best: 500 }));
+    }
+
+    #[test]
+    fn multi_note_combines_when_no_single_note_covers() {
+        let w = wallet(&[500, 300, 250]);
+        assert_eq!(pick(&w, 600, CoinSelection::FirstFit, 2), vec![0, 1]);
+        assert_eq!(pick(&w, 600, CoinSelection::LargestFirst, 2), vec![0, 1]);
+        assert_eq!(pick(&w, 600, CoinSelection::SmallestSufficient, 2), vec![0, 2]);
+        assert_eq!(pick(&w, 600, CoinSelection::MinimizeChange, 2), vec![0, 2]);
+
+        let err = select_notes(&w, 900, CoinSelection::FirstFit, 2, 0).unwrap_err();
+        assert!(matches!(err, R14Error::InsufficientBalance { best: 800, .. }));
+    }
+
+    #[test]
+    fn minimize_change_prefers_exact_pair_over_single() {
+        let w = wallet(&[1000, 400, 200]);
+        // one note covers it, but two notes leave no change
+        assert_eq!(pick(&w, 600, CoinSelection::SmallestSufficient, 2), vec![0]);
+        assert_eq!(pick(&w, 600, CoinSelection::MinimizeChange, 2), vec![1, 2]);
+    }
+
+    #[test]
+    fn multi_note_sets_share_app_tag() {
+        let w = vec![note(400, 1), note(400, 2), note(300, 1)];
+        assert_eq!(pick(&w, 700, CoinSelection::FirstFit, 2), vec![0, 2]);
+
+        let err = select_notes(&w, 750, CoinSelection::FirstFit, 2, 0).unwrap_err();
+        assert!(matches!(err, R14Error::InsufficientBalance { best: 700, .. }));
+    }
+
+    #[test]
+    fn empty_wallet_is_insufficient() {
+        let err = select_notes(&[], 1, CoinSelection::LargestFirst, 1, 0).unwrap_err();
+        assert!(matches!(err, R14Error::InsufficientBalance { needed: 1, best: 0 }));
+    }
+
+    #[test]
+    fn consolidation_merges_smallest_first() {
+        let w = wallet(&[500, 10, 20, 30, 40]);
+        let plan = plan_consolidation(&w, 2, 3, 0).unwrap();
+        assert_eq!(plan.final_count, 2);
+        assert_eq!(
+            plan.steps,
+            vec![
+                ConsolidationStep {
+                    inputs: vec![PlanInput::Note(1), PlanInput::Note(2), PlanInput::Note(3)],
+                    app_tag: 1,
+                    value: 60,
+                },
+                ConsolidationStep {
+                    inputs: vec![PlanInput::Note(4), PlanInput::Step(0)],
+                    app_tag: 1,
+                    value: 100,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn consolidation_keeps_tags_apart() {
+        let mut w = vec![note(1, 1), note(2, 2), note(3, 1), note(4, 2)];
+        w.push(note(5, 1));
+        w[4].spent = true;
+        let plan = plan_consolidation(&w, 1, 4, 0).unwrap();
+        // one note per tag is as far as merging goes
+        assert_eq!(plan.final_count, 2);
+        assert!(plan.steps.iter().all(|s| s.inputs.len() == 2));
+        assert_eq!(plan.steps[0].inputs, vec![PlanInput::Note(0), PlanInput::Note(2)]);
+        assert_eq!(plan.steps[1].inputs, vec![PlanInput::Note(1), PlanInput::Note(3)]);
+
+        assert!(plan_consolidation(&w, 5, 4, 0).unwrap().steps.is_empty());
+        assert!(matches!(plan_consolidation(&w, 1, TRANSFER_INPUTS, 0), Err(R14Error::Config(_))));
+    }
+
+    #[test]
+    fn parses_names() {
+        for strategy in CoinSelection::ALL {
+            assert_eq!(strategy.to_string().parse::<CoinSelection>().unwrap(), strategy);
+        }
+        assert!("greedy".parse::<CoinSelection>().is_err());
+    }
+}
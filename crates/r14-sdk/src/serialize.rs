@@ -3,9 +3,15 @@
 
 //! Arkworks → hex serialization for Soroban contract consumption.
 //!
-//! Converts Groth16 proofs and verification keys (BLS12-381) into
-//! hex-encoded strings that Soroban contracts can decode via
-//! `BytesN<N>::from_hex`.
+//! Converts Groth16 proofs and verification keys into hex-encoded strings
+//! that Soroban contracts can decode via `BytesN<N>::from_hex`. Callers
+//! that build contract arguments directly (RPC submission, the indexer)
+//! can use the `_bytes` variants instead and skip the strings, and
+//! [`hex_to_bytes_n`] turns hex they were handed back into fixed-size
+//! arrays. The
+//! functions are generic over the pairing engine; Soroban only accepts
+//! BLS12-381, and the sizes below are for that curve (BN254: 64-byte G1,
+//! 128-byte G2, same 32-byte Fr).
 //!
 //! # Byte order
 //!
@@ -23,15 +29,15 @@
 //! #     proof: &ark_groth16::Proof<ark_bls12_381::Bls12_381>,
 //! #     public_inputs: &[ark_bls12_381::Fr],
 //! # ) {
-//! let svk = serialize_vk_for_soroban(vk);
+//! let svk = serialize_vk_for_soroban(vk, r14_sdk::MERKLE_DEPTH);
 //! let (sp, spi) = serialize_proof_for_soroban(proof, public_inputs);
 //! // sp.a, sp.b, sp.c — hex-encoded proof elements
 //! // spi — hex-encoded public inputs
 //! // svk.alpha_g1, svk.ic, ... — hex-encoded VK components
 //! # }
 
-use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
-use ark_serialize::CanonicalSerialize;
+use ark_ec::{pairing::Pairing, AffineRepr};
+use ark_ff::PrimeField;
 
 /// Serialized verification key (hex strings)
 pub struct SerializedVK {
@@ -41,6 +47,8 @@ pub struct SerializedVK {
     pub delta_g2: String,
     /// ic\[0\] = constant term, ic\[1..\] = public input coefficients
     pub ic: Vec<String>,
+    /// Merkle depth the circuit was built for, 0 if it has no tree
+    pub depth: u32,
 }
 
 /// Serialized Groth16 proof (hex strings)
@@ -50,46 +58,138 @@ pub struct SerializedProof {
     pub c: String,
 }
 
-/// Serialize G1 point to uncompressed hex (96 bytes = 192 hex chars)
-pub fn serialize_g1(point: &G1Affine) -> String {
+impl SerializedProof {
+    /// Decode back to raw bytes (96 / 192 / 96 on BLS12-381)
+    pub fn to_bytes(&self) -> Result<SerializedProofBytes, BytesError> {
+        let decode = |s: &str| hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|_| BytesError::InvalidHex);
+        Ok(SerializedProofBytes {
+            a: decode(&self.a)?,
+            b: decode(&self.b)?,
+            c: decode(&self.c)?,
+        })
+    }
+}
+
+/// Serialized Groth16 proof (raw bytes, same layout as [`SerializedProof`])
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializedProofBytes {
+    pub a: Vec<u8>,
+    pub b: Vec<u8>,
+    pub c: Vec<u8>,
+}
+
+impl SerializedProofBytes {
+    pub fn to_hex(&self) -> SerializedProof {
+        SerializedProof {
+            a: hex::encode(&self.a),
+            b: hex::encode(&self.b),
+            c: hex::encode(&self.c),
+        }
+    }
+}
+
+/// Why a hex string didn't decode to a `[u8; N]`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BytesError {
+    InvalidHex,
+    WrongLength { expected: usize, got: usize },
+}
+
+impl std::fmt::Display for BytesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidHex => write!(f, "invalid hex"),
+            Self::WrongLength { expected, got } => write!(f, "expected {expected} bytes, got {got}"),
+        }
+    }
+}
+
+impl std::error::Error for BytesError {}
+
+/// Serialize G1 point to uncompressed bytes (96 on BLS12-381)
+pub fn serialize_g1_bytes<G: AffineRepr>(point: &G) -> Vec<u8> {
     let mut bytes = Vec::new();
     point.serialize_uncompressed(&mut bytes).unwrap();
-    hex::encode(&bytes)
+    bytes
 }
 
-/// Serialize G2 point to uncompressed hex (192 bytes = 384 hex chars)
-pub fn serialize_g2(point: &G2Affine) -> String {
+/// Serialize G2 point to uncompressed bytes (192 on BLS12-381)
+pub fn serialize_g2_bytes<G: AffineRepr>(point: &G) -> Vec<u8> {
     let mut bytes = Vec::new();
     point.serialize_uncompressed(&mut bytes).unwrap();
-    hex::encode(&bytes)
+    bytes
 }
 
-/// Serialize Fr to big-endian hex (32 bytes = 64 hex chars)
+/// Serialize Fr to 32 big-endian bytes, the input of Soroban `Fr::from_bytes`
 ///
-/// arkworks uses LE serialization; Soroban Fr::from_bytes expects BE.
-/// serialize_compressed gives LE bytes; reverse to BE for Soroban Fr::from_bytes.
-pub fn serialize_fr(fr: &Fr) -> String {
+/// serialize_compressed gives LE bytes; reverse to BE.
+pub fn serialize_fr_bytes<F: PrimeField>(fr: &F) -> [u8; 32] {
     let mut bytes = Vec::new();
     fr.serialize_compressed(&mut bytes).unwrap();
     bytes.reverse();
-    hex::encode(&bytes)
+    bytes.try_into().expect("scalar field fits in 32 bytes")
+}
+
+/// Serialize G1 point to uncompressed hex (96 bytes = 192 hex chars)
+pub fn serialize_g1<G: AffineRepr>(point: &G) -> String {
+    hex::encode(serialize_g1_bytes(point))
 }
 
-/// Convert an arkworks VerifyingKey to hex-serialized form
-pub fn serialize_vk_for_soroban(vk: &ark_groth16::VerifyingKey<Bls12_381>) -> SerializedVK {
+/// Serialize G2 point to uncompressed hex (192 bytes = 384 hex chars)
+pub fn serialize_g2<G: AffineRepr>(point: &G) -> String {
+    hex::encode(serialize_g2_bytes(point))
+}
+
+/// Serialize Fr to big-endian hex (32 bytes = 64 hex chars)
+///
+/// arkworks uses LE serialization; Soroban Fr::from_bytes expects BE.
+pub fn serialize_fr<F: PrimeField>(fr: &F) -> String {
+    hex::encode(serialize_fr_bytes(fr))
+}
+
+/// Decode hex (with or without `0x`) of exactly `N` bytes, the shape of a
+/// Soroban `BytesN<N>` argument
+pub fn hex_to_bytes_n<const N: usize>(s: &str) -> Result<[u8; N], BytesError> {
+    let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|_| BytesError::InvalidHex)?;
+    let got = bytes.len();
+    bytes.try_into().map_err(|_| BytesError::WrongLength { expected: N, got })
+}
+
+/// Convert an arkworks VerifyingKey to hex-serialized form. `depth` is the
+/// Merkle depth the circuit was set up for (`MERKLE_DEPTH` for the transfer
+/// circuits), or 0 for circuits without a tree.
+pub fn serialize_vk_for_soroban<E: Pairing>(vk: &ark_groth16::VerifyingKey<E>, depth: usize) -> SerializedVK {
     SerializedVK {
         alpha_g1: serialize_g1(&vk.alpha_g1),
         beta_g2: serialize_g2(&vk.beta_g2),
         gamma_g2: serialize_g2(&vk.gamma_g2),
         delta_g2: serialize_g2(&vk.delta_g2),
         ic: vk.gamma_abc_g1.iter().map(serialize_g1).collect(),
+        depth: u32::try_from(depth).expect("merkle depth fits in u32"),
+    }
+}
+
+/// Content-addressed circuit_id that r14-core assigns on `register`:
+/// sha256(depth ++ alpha_g1 ++ beta_g2 ++ gamma_g2 ++ delta_g2 ++ ic[0..n]),
+/// depth as 4 big-endian bytes
+pub fn circuit_id(svk: &SerializedVK) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(svk.depth.to_be_bytes());
+    for part in [&svk.alpha_g1, &svk.beta_g2, &svk.gamma_g2, &svk.delta_g2]
+        .into_iter()
+        .chain(&svk.ic)
+    {
+        hasher.update(hex::decode(part).expect("serialized VK is valid hex"));
     }
+    hex::encode(hasher.finalize())
 }
 
 /// Convert an arkworks Proof + public inputs to hex-serialized form
-pub fn serialize_proof_for_soroban(
-    proof: &ark_groth16::Proof<Bls12_381>,
-    public_inputs: &[Fr],
+pub fn serialize_proof_for_soroban<E: Pairing>(
+    proof: &ark_groth16::Proof<E>,
+    public_inputs: &[E::ScalarField],
 ) -> (SerializedProof, Vec<String>) {
     let sp = SerializedProof {
         a: serialize_g1(&proof.a),
@@ -100,9 +200,47 @@ pub fn serialize_proof_for_soroban(
     (sp, pi)
 }
 
+/// Convert an arkworks Proof + public inputs to raw bytes, the same
+/// encoding as [`serialize_proof_for_soroban`] without the hex
+pub fn serialize_proof_bytes<E: Pairing>(
+    proof: &ark_groth16::Proof<E>,
+    public_inputs: &[E::ScalarField],
+) -> (SerializedProofBytes, Vec<[u8; 32]>) {
+    let sp = SerializedProofBytes {
+        a: serialize_g1_bytes(&proof.a),
+        b: serialize_g2_bytes(&proof.b),
+        c: serialize_g1_bytes(&proof.c),
+    };
+    let pi = public_inputs.iter().map(serialize_fr_bytes).collect();
+    (sp, pi)
+}
+
+/// Serialize a proof from one of the `r14-circuits` circuits (preimage,
+/// ownership, membership, range), with its public inputs in the order the
+/// circuit's verification key expects
+#[cfg(feature = "circuits")]
+pub fn serialize_for_soroban<C: r14_circuits::R14Circuit>(
+    proof: &ark_groth16::Proof<ark_bls12_381::Bls12_381>,
+    public_inputs: &C::PublicInputs,
+) -> (SerializedProof, Vec<String>) {
+    serialize_proof_for_soroban(proof, &C::public_inputs_to_vec(public_inputs))
+}
+
+/// Hex public inputs of an `r14-circuits` circuit, paired with their names
+/// (`C::PUBLIC_INPUTS`), in verifier order
+#[cfg(feature = "circuits")]
+pub fn named_public_inputs<C: r14_circuits::R14Circuit>(public_inputs: &C::PublicInputs) -> Vec<(&'static str, String)> {
+    C::PUBLIC_INPUTS
+        .iter()
+        .copied()
+        .zip(C::public_inputs_to_vec(public_inputs).iter().map(serialize_fr))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_bls12_381::Fr;
     use ark_ff::UniformRand;
     use ark_std::rand::{rngs::StdRng, SeedableRng};
 
@@ -122,6 +260,59 @@ mod tests {
         assert!(hex.chars().all(|c| c == '0'));
     }
 
+    #[test]
+    fn point_sizes_follow_the_curve() {
+        assert_eq!(serialize_g1(&ark_bls12_381::G1Affine::generator()).len(), 192);
+        assert_eq!(serialize_g2(&ark_bls12_381::G2Affine::generator()).len(), 384);
+        assert_eq!(serialize_g1(&ark_bn254::G1Affine::generator()).len(), 128);
+        assert_eq!(serialize_g2(&ark_bn254::G2Affine::generator()).len(), 256);
+        assert_eq!(serialize_fr(&ark_bn254::Fr::from(7u64)).len(), 64);
+    }
+
+    #[test]
+    fn circuit_id_commits_to_depth() {
+        let g1 = serialize_g1(&ark_bls12_381::G1Affine::generator());
+        let g2 = serialize_g2(&ark_bls12_381::G2Affine::generator());
+        let svk = |depth| SerializedVK {
+            alpha_g1: g1.clone(),
+            beta_g2: g2.clone(),
+            gamma_g2: g2.clone(),
+            delta_g2: g2.clone(),
+            ic: vec![g1.clone(); 2],
+            depth,
+        };
+        assert_eq!(circuit_id(&svk(20)), circuit_id(&svk(20)));
+        assert_ne!(circuit_id(&svk(20)), circuit_id(&svk(16)));
+        assert_ne!(circuit_id(&svk(20)), circuit_id(&svk(0)));
+    }
+
+    #[test]
+    fn bytes_match_hex() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let fr = Fr::rand(&mut rng);
+        assert_eq!(hex::encode(serialize_fr_bytes(&fr)), serialize_fr(&fr));
+        assert_eq!(hex_to_bytes_n::<32>(&serialize_fr(&fr)).unwrap(), serialize_fr_bytes(&fr));
+        assert_eq!(serialize_fr_bytes(&Fr::from(1u64))[31], 1);
+
+        let g1 = ark_bls12_381::G1Affine::generator();
+        let g2 = ark_bls12_381::G2Affine::generator();
+        assert_eq!(serialize_g1_bytes(&g1).len(), 96);
+        assert_eq!(serialize_g2_bytes(&g2).len(), 192);
+        let proof = ark_groth16::Proof::<ark_bls12_381::Bls12_381> { a: g1, b: g2, c: g1 };
+        let (sp, spi) = serialize_proof_for_soroban(&proof, &[fr]);
+        let (bytes, pi) = serialize_proof_bytes(&proof, &[fr]);
+        assert_eq!(sp.to_bytes().unwrap(), bytes);
+        assert_eq!(bytes.to_hex().b, sp.b);
+        assert_eq!(hex::encode(pi[0]), spi[0]);
+    }
+
+    #[test]
+    fn hex_to_bytes_n_rejects() {
+        assert_eq!(hex_to_bytes_n::<2>("0xabcd"), Ok([0xab, 0xcd]));
+        assert_eq!(hex_to_bytes_n::<2>("zz"), Err(BytesError::InvalidHex));
+        assert_eq!(hex_to_bytes_n::<32>("abcd"), Err(BytesError::WrongLength { expected: 32, got: 2 }));
+    }
+
     #[test]
     fn serialize_fr_deterministic() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -0,0 +1,201 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Throwaway session keys for dapp integrations.
+//!
+//! A session key is derived from the main secret key and can only spend
+//! notes owned by its [`session_owner`](r14_poseidon::session_owner) hash,
+//! which binds a [`SessionPolicy`] (max amount per transfer, app tag,
+//! expiry ledger) and a refund owner. The delegated transfer circuit
+//! enforces the policy, so a dapp holding the [`SessionKey`] bundle never
+//! sees the main spending key.
+//!
+//! # Flow
+//!
+//! 1. [`mint_session`] derives the next key and records it in the wallet.
+//! 2. Fund it by transferring notes to [`SessionKey::owner`].
+//! 3. Hand the serialized [`SessionKey`] to the dapp, which spends with
+//!    `R14Client::transfer_with_session`.
+//! 4. [`revoke_session`] marks it revoked and returns the bundle so the
+//!    wallet can sweep remaining notes back to the refund owner.
+//!
+//! ```rust,no_run
+//! use r14_sdk::session::{mint_session, revoke_session};
+//! use r14_sdk::SessionPolicy;
+//!
+//! # fn example() -> anyhow::Result<()> {
+//! let mut w = r14_sdk::wallet::load_wallet()?;
+//! let session = mint_session(&mut w, SessionPolicy::new(500, 1, 2_000_000))?;
+//! println!("fund session at {}", session.owner);
+//! // ... later
+//! let _sweep = revoke_session(&mut w, session.index)?;
+//! r14_sdk::wallet::save_wallet(&w)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anyhow::{Context, Result};
+use r14_types::{OwnerHash, SecretKey, SessionPolicy};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::wallet::{fr_to_hex, hex_to_fr, WalletData};
+
+/// Wallet-side record of a minted session (no secret material)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionEntry {
+    pub index: u64,
+    pub owner: String,
+    pub max_amount: u64,
+    pub app_tag: u32,
+    pub expiry: u64,
+    pub revoked: bool,
+}
+
+/// Scoped key bundle handed to a dapp. The secret key is wiped on drop
+/// and left out of `Debug`.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionKey {
+    pub index: u64,
+    pub secret_key: String,
+    pub owner: String,
+    pub refund_owner: String,
+    pub max_amount: u64,
+    pub app_tag: u32,
+    pub expiry: u64,
+}
+
+impl Drop for SessionKey {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SessionKey {}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionKey")
+            .field("index", &self.index)
+            .field("secret_key", &"<redacted>")
+            .field("owner", &self.owner)
+            .field("refund_owner", &self.refund_owner)
+            .field("max_amount", &self.max_amount)
+            .field("app_tag", &self.app_tag)
+            .field("expiry", &self.expiry)
+            .finish()
+    }
+}
+
+impl SessionKey {
+    fn derive(sk: &SecretKey, refund_owner: &OwnerHash, index: u64, policy: &SessionPolicy) -> Self {
+        let session_sk = r14_poseidon::session_key(sk, index);
+        let owner = r14_poseidon::session_owner(&session_sk, policy, refund_owner);
+        Self {
+            index,
+            secret_key: fr_to_hex(&session_sk.0),
+            owner: fr_to_hex(&owner.0),
+            refund_owner: fr_to_hex(&refund_owner.0),
+            max_amount: policy.max_amount,
+            app_tag: policy.app_tag,
+            expiry: policy.expiry,
+        }
+    }
+
+    pub fn policy(&self) -> SessionPolicy {
+        SessionPolicy::new(self.max_amount, self.app_tag, self.expiry)
+    }
+
+    pub fn session_secret(&self) -> Result<SecretKey> {
+        Ok(SecretKey(hex_to_fr(&self.secret_key)?))
+    }
+
+    pub fn refund_owner_hash(&self) -> Result<OwnerHash> {
+        Ok(OwnerHash(hex_to_fr(&self.refund_owner)?))
+    }
+}
+
+fn main_keys(wallet: &WalletData) -> Result<(SecretKey, OwnerHash)> {
+    let sk = SecretKey(hex_to_fr(&wallet.secret_key)?);
+    let owner = OwnerHash(hex_to_fr(&wallet.owner_hash)?);
+    Ok((sk, owner))
+}
+
+/// Derive the next session key under `policy` and record it in the wallet.
+pub fn mint_session(wallet: &mut WalletData, policy: SessionPolicy) -> Result<SessionKey> {
+    let (sk, refund_owner) = main_keys(wallet)?;
+    let index = wallet.sessions.iter().map(|s| s.index + 1).max().unwrap_or(0);
+    let key = SessionKey::derive(&sk, &refund_owner, index, &policy);
+
+    wallet.sessions.push(SessionEntry {
+        index,
+        owner: key.owner.clone(),
+        max_amount: policy.max_amount,
+        app_tag: policy.app_tag,
+        expiry: policy.expiry,
+        revoked: false,
+    });
+    Ok(key)
+}
+
+/// Mark a session revoked and re-derive its key so the wallet can sweep
+/// remaining session notes back to the refund owner.
+pub fn revoke_session(wallet: &mut WalletData, index: u64) -> Result<SessionKey> {
+    let (sk, refund_owner) = main_keys(wallet)?;
+    let entry = wallet
+        .sessions
+        .iter_mut()
+        .find(|s| s.index == index)
+        .with_context(|| format!("no session with index {index}"))?;
+    entry.revoked = true;
+    let policy = SessionPolicy::new(entry.max_amount, entry.app_tag, entry.expiry);
+    Ok(SessionKey::derive(&sk, &refund_owner, index, &policy))
+}
+
+/// Sessions that are neither revoked nor past `ledger`
+pub fn active_sessions(wallet: &WalletData, ledger: u64) -> Vec<&SessionEntry> {
+    wallet
+        .sessions
+        .iter()
+        .filter(|s| !s.revoked && s.expiry >= ledger)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+    fn test_wallet() -> WalletData {
+        let mut rng = StdRng::seed_from_u64(7);
+        WalletData::for_test(&SecretKey::random(&mut rng))
+    }
+
+    #[test]
+    fn mint_assigns_sequential_indices() {
+        let mut w = test_wallet();
+        let a = mint_session(&mut w, SessionPolicy::new(100, 1, 10)).unwrap();
+        let b = mint_session(&mut w, SessionPolicy::new(100, 1, 10)).unwrap();
+        assert_eq!((a.index, b.index), (0, 1));
+        assert_ne!(a.secret_key, b.secret_key);
+        assert_ne!(a.secret_key, w.secret_key);
+        assert_eq!(w.sessions.len(), 2);
+    }
+
+    #[test]
+    fn revoke_rederives_same_key() {
+        let mut w = test_wallet();
+        let minted = mint_session(&mut w, SessionPolicy::new(100, 1, 10)).unwrap();
+        let revoked = revoke_session(&mut w, minted.index).unwrap();
+        assert_eq!(minted.secret_key, revoked.secret_key);
+        assert_eq!(minted.owner, revoked.owner);
+        assert!(w.sessions[0].revoked);
+        assert!(active_sessions(&w, 0).is_empty());
+    }
+
+    #[test]
+    fn revoke_unknown_index_fails() {
+        let mut w = test_wallet();
+        assert!(revoke_session(&mut w, 3).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! End-to-end simulation with real proofs and no network.
+//!
+//! [`R14Simulator`] stands in for the indexer and the transfer contract at
+//! once: a [`SparseMerkleTree`] of commitments, the contract's root
+//! history and nullifier set, and a ledger counter. Transfers go through
+//! the same coin selection and proving code as [`R14Client::transfer`],
+//! with the keys from the same deterministic setup, and each proof is
+//! verified against the transfer VK before the simulator applies it.
+//!
+//! Unlike [`crate::testing::MockNetwork`] there is no HTTP and nothing
+//! async; in exchange the simulator has no `R14Client` to hand out, and
+//! offers the client's deposit, transfer and balance calls itself.
+//!
+//! Requires the `testing` and `prove` features. Setup and proving run the
+//! full transfer circuit, so build tests that use it with optimizations.
+//!
+//! ```rust,no_run
+//! use r14_sdk::simulator::R14Simulator;
+//! use r14_sdk::{owner_hash, CoinSelection, SecretKey};
+//!
+//! # fn example() -> r14_sdk::R14Result<()> {
+//! let mut sim = R14Simulator::new()?;
+//! let sk = SecretKey::random(&mut r14_sdk::wallet::crypto_rng());
+//! let owner = owner_hash(&sk).0;
+//! let recipient = ark_bls12_381::Fr::from(7u64);
+//!
+//! let mut notes = vec![sim.deposit(100, 1, &owner)?.note_entry];
+//! let sent = sim.transfer(&mut notes, &sk.0, &owner, &recipient, 30, CoinSelection::default())?;
+//! notes.push(sent.change_note);
+//! assert_eq!(sim.balance(&mut notes).total, 70);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`R14Client::transfer`]: crate::client::R14Client::transfer
+
+use std::collections::{HashMap, VecDeque};
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+
+use crate::client::{
+    prove_transfer, summarize_balance, BalanceResult, DepositResult, ProvenTransfer, TransferResult,
+    TRANSFER_SETUP_SEED,
+};
+use crate::error::{R14Error, R14Result};
+use crate::merkle::SparseMerkleTree;
+use crate::selection::{select_notes, CoinSelection, TRANSFER_INPUTS};
+use crate::wallet::{NoteEntry, NoteSource};
+use crate::{commitment, Commitment, LeafIndex, Note};
+
+/// Roots the transfer contract keeps; older ones are rejected
+pub const ROOT_HISTORY_SIZE: usize = 100;
+
+pub struct R14Simulator {
+    tree: SparseMerkleTree,
+    /// Oldest first, at most [`ROOT_HISTORY_SIZE`]
+    roots: VecDeque<Fr>,
+    /// Spent nullifier → ledger it was spent at
+    nullifiers: HashMap<Fr, u64>,
+    ledger: u64,
+    pk: ProvingKey<Bls12_381>,
+    vk: VerifyingKey<Bls12_381>,
+}
+
+impl R14Simulator {
+    /// Empty tree at ledger 1, with the transfer keys `R14Client` proves with
+    pub fn new() -> R14Result<Self> {
+        let (pk, vk) = crate::prove::setup(&mut StdRng::seed_from_u64(TRANSFER_SETUP_SEED))?;
+        let tree = SparseMerkleTree::new();
+        Ok(Self {
+            roots: VecDeque::from([tree.root().0]),
+            tree,
+            nullifiers: HashMap::new(),
+            ledger: 1,
+            pk,
+            vk,
+        })
+    }
+
+    pub fn ledger(&self) -> u64 {
+        self.ledger
+    }
+
+    /// Move the ledger forward, e.g. past a note's `unlock_after`
+    pub fn advance_ledger(&mut self, ledgers: u64) {
+        self.ledger += ledgers;
+    }
+
+    pub fn root(&self) -> Fr {
+        self.tree.root().0
+    }
+
+    pub fn leaves(&self) -> Vec<Commitment> {
+        self.tree.leaves()
+    }
+
+    pub fn is_known_root(&self, root: &Fr) -> bool {
+        self.roots.contains(root)
+    }
+
+    /// Whether a nullifier (hex, optional `0x`) has been spent
+    pub fn is_spent(&self, nullifier: &str) -> R14Result<bool> {
+        let nf = crate::wallet::hex_to_fr(nullifier).map_err(R14Error::Other)?;
+        Ok(self.nullifiers.contains_key(&nf))
+    }
+
+    /// Create a note for `owner` and add it to the tree
+    pub fn deposit(&mut self, value: u64, app_tag: u32, owner: &Fr) -> R14Result<DepositResult> {
+        let note = Note::new(value, app_tag, *owner, &mut crate::wallet::crypto_rng());
+        let cm = commitment(&note);
+        self.append(&[cm]);
+        Ok(DepositResult {
+            commitment: cm,
+            value,
+            app_tag,
+            tx_result: String::new(),
+            note_entry: NoteEntry::new(&note, &cm).with_source(NoteSource::Deposit),
+        })
+    }
+
+    /// Fill in the leaf index of notes now in the tree
+    pub fn sync_notes(&self, notes: &mut [NoteEntry]) {
+        let leaves = self.tree.leaves();
+        for note in notes.iter_mut().filter(|n| !n.spent && n.index.is_none()) {
+            note.index = leaves.iter().position(|l| *l == note.commitment).map(LeafIndex::from);
+        }
+    }
+
+    /// Sync notes and return the balance summary
+    pub fn balance(&self, notes: &mut [NoteEntry]) -> BalanceResult {
+        self.sync_notes(notes);
+        summarize_balance(notes)
+    }
+
+    /// Select a note, prove a transfer at the current ledger and apply it,
+    /// as `R14Client::transfer` does against the network.
+    ///
+    /// The consumed note is marked spent only if the transfer applies.
+    pub fn transfer(
+        &mut self,
+        notes: &mut [NoteEntry],
+        sk: &Fr,
+        owner: &Fr,
+        recipient: &Fr,
+        value: u64,
+        selection: CoinSelection,
+    ) -> R14Result<TransferResult> {
+        let note_idx = select_notes(notes, value, selection, TRANSFER_INPUTS, self.ledger)?[0];
+        let entry = &notes[note_idx];
+        let leaf_index = entry
+            .index
+            .filter(|i| i.as_usize() < self.tree.next_index())
+            .ok_or(R14Error::NoteNotOnChain)?;
+        let path = self.tree.proof(leaf_index);
+        let proven = prove_transfer(&self.pk, entry, path, sk, owner, recipient, value, self.ledger)?;
+
+        let tx_result = self.submit(&proven)?;
+        notes[note_idx].spent = true;
+        Ok(TransferResult {
+            nullifier: format!("0x{}", proven.prebuilt.nullifier),
+            out_commitment_0: proven.recipient.commitment,
+            out_commitment_1: proven.change.commitment,
+            tx_result,
+            recipient_note: proven.recipient,
+            change_note: proven.change,
+            consumed_note_index: note_idx,
+        })
+    }
+
+    /// What the transfer contract checks, in its order, then the state change
+    fn submit(&mut self, proven: &ProvenTransfer) -> R14Result<String> {
+        let pi = &proven.public_inputs;
+        if proven.prebuilt.current_ledger > self.ledger {
+            return Err(R14Error::Soroban("current_ledger is in the future".into()));
+        }
+        if !self.is_known_root(&pi.old_root) {
+            return Err(R14Error::StaleRoot(format!(
+                "root {} is not in the contract's root history",
+                proven.prebuilt.old_root
+            )));
+        }
+        if self.nullifiers.contains_key(&pi.nullifier) {
+            return Err(R14Error::Soroban("nullifier already spent".into()));
+        }
+        if !crate::prove::verify_offchain(&self.vk, &proven.proof, pi) {
+            return Err(R14Error::Soroban("proof verification failed".into()));
+        }
+        self.append(&[Commitment(pi.out_commitment_0), Commitment(pi.out_commitment_1)]);
+        self.nullifiers.insert(pi.nullifier, self.ledger);
+        Ok("true".into())
+    }
+
+    /// Add `cms` to the tree in a new ledger and record the root
+    fn append(&mut self, cms: &[Commitment]) {
+        for cm in cms {
+            self.tree.insert(*cm);
+        }
+        self.ledger += 1;
+        if self.roots.len() == ROOT_HISTORY_SIZE {
+            self.roots.pop_front();
+        }
+        self.roots.push_back(self.tree.root().0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{owner_hash, SecretKey};
+
+    #[test]
+    fn deposit_transfer_balance() {
+        let mut sim = R14Simulator::new().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let sk = SecretKey::random(&mut rng);
+        let owner = owner_hash(&sk).0;
+        let recipient = owner_hash(&SecretKey::random(&mut rng)).0;
+
+        let mut notes = vec![sim.deposit(100, 1, &owner).unwrap().note_entry];
+        assert_eq!(sim.balance(&mut notes).total, 100);
+        assert_eq!(notes[0].index, Some(LeafIndex(0)));
+
+        let sent = sim.transfer(&mut notes, &sk.0, &owner, &recipient, 30, CoinSelection::default()).unwrap();
+        assert!(notes[0].spent && sim.is_spent(&sent.nullifier).unwrap());
+        assert_eq!(sim.leaves(), [notes[0].commitment, sent.out_commitment_0, sent.out_commitment_1]);
+        notes.push(sent.change_note);
+        let balance = sim.balance(&mut notes);
+        assert_eq!((balance.total, notes[1].index), (70, Some(LeafIndex(2))));
+
+        // the same note again: a fresh proof, but the nullifier is spent
+        notes[0].spent = false;
+        let err = sim.transfer(&mut notes[..1], &sk.0, &owner, &recipient, 30, CoinSelection::default());
+        assert!(matches!(err, Err(R14Error::Soroban(ref m)) if m == "nullifier already spent"));
+        assert!(!notes[0].spent);
+
+        // selection only spends notes synced into the tree
+        let unsynced = sim.deposit(5, 1, &owner).unwrap().note_entry;
+        let err = sim.transfer(&mut [unsynced], &sk.0, &owner, &recipient, 5, CoinSelection::default());
+        assert!(matches!(err, Err(R14Error::InsufficientBalance { .. })));
+    }
+
+    #[test]
+    fn root_history_is_bounded() {
+        let mut sim = R14Simulator::new().unwrap();
+        let first = sim.root();
+        for v in 1..=ROOT_HISTORY_SIZE as u64 {
+            sim.append(&[Commitment(Fr::from(v))]);
+        }
+        assert!(!sim.is_known_root(&first));
+        assert!(sim.is_known_root(&sim.root()));
+        assert_eq!(sim.ledger(), 1 + ROOT_HISTORY_SIZE as u64);
+    }
+}
@@ -17,7 +17,7 @@
 //! // invoke a contract function
 //! let result = r14_sdk::soroban::invoke_contract(
 //!     "C_CONTRACT_ID",
-//!     "testnet",
+//!     &r14_sdk::Network::testnet(),
 //!     "S_SECRET...",
 //!     "deposit",
 //!     &[("cm", "deadbeef..."), ("new_root", "cafebabe...")],
@@ -25,10 +25,52 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! [`crate::R14Client`] goes through a [`ContractInvoker`], [`StellarCli`]
+//! unless the builder is given another, e.g. the mock in
+//! `r14_sdk::testing`.
+
+use std::future::Future;
+use std::pin::Pin;
 
 use anyhow::{Context, Result};
 use tokio::process::Command;
 
+use crate::network::Network;
+
+pub type InvokeFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+/// Runs contract calls for [`crate::R14Client`]
+pub trait ContractInvoker: Send + Sync {
+    /// Call `function` on `contract_id`, as [`invoke_contract`] does, and
+    /// return its output
+    fn invoke<'a>(
+        &'a self,
+        contract_id: &'a str,
+        network: &'a Network,
+        source_secret: &'a str,
+        function: &'a str,
+        args: &'a [(&'a str, &'a str)],
+    ) -> InvokeFuture<'a>;
+}
+
+/// [`invoke_contract`] through the `stellar` binary
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StellarCli;
+
+impl ContractInvoker for StellarCli {
+    fn invoke<'a>(
+        &'a self,
+        contract_id: &'a str,
+        network: &'a Network,
+        source_secret: &'a str,
+        function: &'a str,
+        args: &'a [(&'a str, &'a str)],
+    ) -> InvokeFuture<'a> {
+        Box::pin(invoke_contract(contract_id, network, source_secret, function, args))
+    }
+}
+
 /// Get the public key (G...) for a Stellar secret key
 pub async fn get_public_key(secret: &str) -> Result<String> {
     let output = Command::new("stellar")
@@ -50,9 +92,10 @@ pub async fn get_public_key(secret: &str) -> Result<String> {
 /// Invoke a Soroban contract function via the `stellar` CLI.
 ///
 /// `args` is a list of (arg_name, value) pairs passed as `--arg_name value`.
+/// `network` selects the RPC and passphrase; see [`Network::invoke_args`].
 pub async fn invoke_contract(
     contract_id: &str,
-    network: &str,
+    network: &Network,
     source_secret: &str,
     function: &str,
     args: &[(&str, &str)],
@@ -62,8 +105,7 @@ pub async fn invoke_contract(
         .arg("invoke")
         .arg("--id")
         .arg(contract_id)
-        .arg("--network")
-        .arg(network)
+        .args(network.invoke_args())
         .arg("--source")
         .arg(source_secret)
         .arg("--")
@@ -86,3 +128,38 @@ pub async fn invoke_contract(
         Err(anyhow::anyhow!("stellar contract invoke failed: {stderr}"))
     }
 }
+
+/// Whether a failed [`invoke_contract`] certainly didn't land: the CLI never
+/// ran, or the call was rejected in simulation or on-chain (a `HostError`).
+///
+/// Anything else, such as an RPC timeout after submission, may still land,
+/// so a spend it carried must stay pending rather than be rolled back.
+pub fn never_landed(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        let msg = cause.to_string();
+        msg.starts_with("failed to run `stellar` CLI") || msg.contains("simulation failed") || msg.contains("HostError")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejections_never_landed() {
+        let simulated = anyhow::anyhow!(
+            "stellar contract invoke failed: error: transaction simulation failed: HostError: Error(Contract, #3)"
+        );
+        assert!(never_landed(&simulated));
+        let spawn = Err::<(), _>(std::io::Error::from(std::io::ErrorKind::NotFound))
+            .context("failed to run `stellar` CLI — is it installed?")
+            .unwrap_err();
+        assert!(never_landed(&spawn));
+    }
+
+    #[test]
+    fn a_timeout_may_still_land() {
+        let timeout = anyhow::anyhow!("stellar contract invoke failed: error: transaction submission timeout");
+        assert!(!never_landed(&timeout));
+    }
+}
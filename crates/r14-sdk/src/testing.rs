@@ -0,0 +1,1142 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! In-memory indexer and contracts for testing dapps without a network.
+//!
+//! A [`MockNetwork`] holds one tree, nullifier set and root history and
+//! serves it the two ways an [`R14Client`] reads the chain: an HTTP server
+//! on localhost answering the r14-indexer REST API, so the ordinary
+//! [`IndexerClient`] talks to it unchanged, and a [`ContractInvoker`] that
+//! runs `deposit`, `deposit_batch`, `transfer` and the read-only contract
+//! calls against it instead of the `stellar` CLI. Everything lands at
+//! once; the indexer never lags.
+//!
+//! Proofs are not verified. The rest of what the transfer contract checks
+//! is: root history, double spends, `current_ledger` and session expiry.
+//! The circuit_id starts as a placeholder; tests that prove set the local
+//! one with [`MockNetwork::set_circuit_id`].
+//! `new_root` is also checked against the tree, which the contract leaves
+//! to the indexer, so a client computing the wrong root fails here.
+//!
+//! Requires the `testing` feature.
+//!
+//! ```rust
+//! use r14_sdk::testing::MockNetwork;
+//! use r14_sdk::{owner_hash, SecretKey};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> r14_sdk::R14Result<()> {
+//! let network = MockNetwork::start().await?;
+//! let client = network.client()?;
+//!
+//! let owner = owner_hash(&SecretKey::random(&mut r14_sdk::wallet::crypto_rng())).0;
+//! let deposit = client.deposit(100, 1, &owner).await?;
+//!
+//! let mut notes = vec![deposit.note_entry];
+//! assert_eq!(client.balance(&mut notes).await?.total, 100);
+//! assert_eq!(network.leaves(), [deposit.commitment]);
+//! assert_eq!(network.calls()[0].function, "deposit");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use ark_bls12_381::Fr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::client::{R14Client, R14Contracts};
+use crate::error::{ErrorBody, ErrorCode, R14Error, R14Result};
+use crate::indexer::IndexerClient;
+use crate::network::Network;
+use crate::soroban::{ContractInvoker, InvokeFuture};
+use crate::wallet::{fr_to_raw_hex, hex_to_fr};
+use crate::{Commitment, LeafIndex};
+
+pub const MOCK_CORE_CONTRACT: &str = "CMOCKCORE";
+pub const MOCK_TRANSFER_CONTRACT: &str = "CMOCKTRANSFER";
+
+/// Key the mock network's clients sign with; nothing checks it
+pub const MOCK_STELLAR_SECRET: &str = "SMOCK";
+
+/// A contract call the mock received
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockCall {
+    pub contract_id: String,
+    pub function: String,
+    pub args: Vec<(String, String)>,
+}
+
+struct Chain {
+    leaves: Vec<(Commitment, u64)>,
+    /// Spent nullifier → ledger it was spent at
+    nullifiers: HashMap<Fr, u64>,
+    /// Raw hex with the leaf count and ledger that set it, oldest first
+    roots: Vec<(String, usize, u64)>,
+    /// Deposit and transfer events emitted
+    deposits: u64,
+    transfers: u64,
+    circuit_id: String,
+    ledger: u64,
+    /// Ledgers a leaf stays pending for
+    confirmations: u64,
+    calls: Vec<MockCall>,
+    /// Function → error its next call fails with
+    failures: HashMap<String, String>,
+}
+
+/// Shared tree, nullifiers and ledger behind the mock indexer and contracts
+#[derive(Clone)]
+struct State(Arc<Mutex<Chain>>);
+
+impl State {
+    fn lock(&self) -> MutexGuard<'_, Chain> {
+        // a test that panicked mid-call leaves nothing half-written
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Chain {
+    fn new() -> Self {
+        Self {
+            leaves: Vec::new(),
+            nullifiers: HashMap::new(),
+            roots: vec![(crate::merkle::empty_root_hex(), 0, 1)],
+            deposits: 0,
+            transfers: 0,
+            circuit_id: fr_to_raw_hex(&Fr::from(14u64)),
+            ledger: 1,
+            confirmations: 0,
+            calls: Vec::new(),
+            failures: HashMap::new(),
+        }
+    }
+
+    fn commitments(&self) -> Vec<Commitment> {
+        self.leaves.iter().map(|(cm, _)| *cm).collect()
+    }
+
+    fn is_pending(&self, block_height: u64) -> bool {
+        self.confirmations > 0 && self.ledger < block_height + self.confirmations
+    }
+
+    fn latest_root(&self) -> &str {
+        &self.roots.last().expect("roots start with the empty root").0
+    }
+
+    fn knows_root(&self, root: &str) -> bool {
+        self.roots.iter().any(|(r, ..)| r == root)
+    }
+
+    /// A recorded root as the indexer serves it
+    fn historical_root(&(ref root, leaf_count, ledger): &(String, usize, u64)) -> serde_json::Value {
+        serde_json::json!({ "root": format!("0x{root}"), "leaf_count": leaf_count, "ledger": ledger })
+    }
+
+    /// Append `cms` in one ledger, rejecting a `new_root` that doesn't match
+    fn append(&mut self, cms: &[Commitment], new_root: &str) -> anyhow::Result<()> {
+        let mut leaves = self.commitments();
+        leaves.extend_from_slice(cms);
+        let root = crate::merkle::compute_root_from_leaves(&leaves);
+        anyhow::ensure!(
+            crate::wallet::strip_0x(new_root) == root,
+            "new_root does not match the tree (expected {root})"
+        );
+        self.ledger += 1;
+        self.leaves.extend(cms.iter().map(|cm| (*cm, self.ledger)));
+        self.roots.push((root, self.leaves.len(), self.ledger));
+        Ok(())
+    }
+
+    fn call(&mut self, contract_id: &str, function: &str, args: &[(&str, &str)]) -> anyhow::Result<String> {
+        self.calls.push(MockCall {
+            contract_id: contract_id.to_string(),
+            function: function.to_string(),
+            args: args.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        });
+        if let Some(message) = self.failures.remove(function) {
+            anyhow::bail!("{message}");
+        }
+        let arg = |name: &str| {
+            args.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| *v)
+                .ok_or_else(|| anyhow::anyhow!("{function}: missing argument {name}"))
+        };
+        let fr = |name: &str| hex_to_fr(arg(name)?);
+        let ledger = |name: &str| -> anyhow::Result<u64> { Ok(arg(name)?.parse()?) };
+        match function {
+            "deposit" => {
+                let cm = fr("cm")?;
+                anyhow::ensure!(cm != Fr::from(0u64), "zero commitment");
+                self.append(&[Commitment(cm)], arg("new_root")?)?;
+                self.deposits += 1;
+                Ok(String::new())
+            }
+            "deposit_batch" => {
+                let cms: Vec<String> = serde_json::from_str(arg("cms")?)?;
+                anyhow::ensure!(!cms.is_empty(), "empty batch");
+                let cms = cms.iter().map(|cm| hex_to_fr(cm).map(Commitment)).collect::<anyhow::Result<Vec<_>>>()?;
+                anyhow::ensure!(cms.iter().all(|cm| cm.0 != Fr::from(0u64)), "zero commitment");
+                self.append(&cms, arg("new_root")?)?;
+                // one event per commitment
+                self.deposits += cms.len() as u64;
+                Ok(String::new())
+            }
+            "transfer" | "transfer_delegated" => {
+                if function == "transfer" {
+                    anyhow::ensure!(ledger("current_ledger")? <= self.ledger, "current_ledger is in the future");
+                } else {
+                    anyhow::ensure!(self.ledger <= ledger("valid_until")?, "session expired");
+                }
+                let old_root = crate::wallet::strip_0x(arg("old_root")?);
+                anyhow::ensure!(self.knows_root(&old_root), "unknown merkle root");
+                let nullifier = fr("nullifier")?;
+                anyhow::ensure!(!self.nullifiers.contains_key(&nullifier), "nullifier already spent");
+                self.append(&[Commitment(fr("cm_0")?), Commitment(fr("cm_1")?)], arg("new_root")?)?;
+                self.nullifiers.insert(nullifier, self.ledger);
+                self.transfers += 1;
+                Ok("true".into())
+            }
+            "is_spent" => Ok(self.nullifiers.contains_key(&fr("nullifier")?).to_string()),
+            "is_known_root" => {
+                let root = crate::wallet::strip_0x(arg("root")?);
+                Ok(self.knows_root(&root).to_string())
+            }
+            "latest_root" => Ok(format!("\"{}\"", self.latest_root())),
+            "circuit_id" => Ok(format!("\"{}\"", self.circuit_id)),
+            "register" => Ok(format!("\"{}\"", self.circuit_id)),
+            "init" => {
+                self.circuit_id = crate::wallet::strip_0x(arg("circuit_id")?);
+                Ok(String::new())
+            }
+            "init_delegated" => Ok(String::new()),
+            other => anyhow::bail!("mock contracts have no function `{other}`"),
+        }
+    }
+}
+
+/// [`ContractInvoker`] running calls against a [`MockNetwork`]'s state
+#[derive(Clone)]
+pub struct MockContracts(State);
+
+impl ContractInvoker for MockContracts {
+    fn invoke<'a>(
+        &'a self,
+        contract_id: &'a str,
+        _network: &'a Network,
+        _source_secret: &'a str,
+        function: &'a str,
+        args: &'a [(&'a str, &'a str)],
+    ) -> InvokeFuture<'a> {
+        Box::pin(std::future::ready(self.0.lock().call(contract_id, function, args)))
+    }
+}
+
+/// Mock indexer and contracts sharing one chain; the indexer stops when
+/// this is dropped
+pub struct MockNetwork {
+    state: State,
+    indexer_url: String,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl MockNetwork {
+    /// Serve the mock indexer on a free localhost port. Needs a tokio
+    /// runtime, which keeps serving while tests await the client.
+    pub async fn start() -> R14Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| R14Error::Other(anyhow::anyhow!("mock indexer: {e}")))?;
+        let indexer_url = format!(
+            "http://{}",
+            listener.local_addr().map_err(|e| R14Error::Other(e.into()))?
+        );
+        let state = State(Arc::new(Mutex::new(Chain::new())));
+        let server = tokio::spawn(serve(listener, state.clone()));
+        Ok(Self {
+            state,
+            indexer_url,
+            server,
+        })
+    }
+
+    pub fn indexer_url(&self) -> &str {
+        &self.indexer_url
+    }
+
+    pub fn contracts(&self) -> R14Contracts {
+        R14Contracts {
+            core: MOCK_CORE_CONTRACT.into(),
+            transfer: MOCK_TRANSFER_CONTRACT.into(),
+        }
+    }
+
+    /// Invoker for building your own client, e.g. with a custom retry policy
+    pub fn invoker(&self) -> Arc<dyn ContractInvoker> {
+        Arc::new(MockContracts(self.state.clone()))
+    }
+
+    pub fn indexer(&self) -> IndexerClient {
+        IndexerClient::new(&self.indexer_url)
+    }
+
+    /// Client wired to the mock indexer and contracts
+    pub fn client(&self) -> R14Result<R14Client> {
+        R14Client::builder(&self.indexer_url, self.contracts(), MOCK_STELLAR_SECRET)
+            .invoker(self.invoker())
+            .build()
+    }
+
+    /// Indexed commitments in insertion order
+    pub fn leaves(&self) -> Vec<Commitment> {
+        self.state.lock().commitments()
+    }
+
+    /// Latest root, raw hex
+    pub fn root(&self) -> String {
+        self.state.lock().latest_root().to_string()
+    }
+
+    pub fn ledger(&self) -> u64 {
+        self.state.lock().ledger
+    }
+
+    /// Move the ledger forward, e.g. past a timelock or session expiry
+    pub fn advance_ledger(&self, ledgers: u64) {
+        self.state.lock().ledger += ledgers;
+    }
+
+    /// circuit_id the mock transfer contract reports, as `init` would set
+    /// it. It starts as a placeholder, so `R14Client` refuses to prove
+    /// until a test sets the local circuit's id.
+    pub fn set_circuit_id(&self, circuit_id: &str) {
+        self.state.lock().circuit_id = crate::wallet::strip_0x(circuit_id);
+    }
+
+    /// Keep leaves `pending` until `ledgers` more have passed, as an
+    /// indexer run with `R14_CONFIRMATIONS` does
+    pub fn set_confirmations(&self, ledgers: u64) {
+        self.state.lock().confirmations = ledgers;
+    }
+
+    /// Index `cm` as if someone else had deposited it
+    pub fn insert_leaf(&self, cm: Commitment) {
+        let mut chain = self.state.lock();
+        let mut leaves = chain.commitments();
+        leaves.push(cm);
+        let root = crate::merkle::compute_root_from_leaves(&leaves);
+        chain
+            .append(&[cm], &root)
+            .expect("root computed from the same leaves");
+    }
+
+    /// Contract calls received so far, oldest first
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state.lock().calls.clone()
+    }
+
+    /// Make the next call to `function` fail with `message`, as a reverted
+    /// or dropped submission would
+    pub fn fail_next(&self, function: &str, message: &str) {
+        self.state
+            .lock()
+            .failures
+            .insert(function.to_string(), message.to_string());
+    }
+}
+
+impl Drop for MockNetwork {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, state: State) {
+    while let Ok((sock, _)) = listener.accept().await {
+        tokio::spawn(answer(sock, state.clone()));
+    }
+}
+
+/// Read one request and answer it, closing the connection after
+async fn answer(mut sock: TcpStream, state: State) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    let head_end = loop {
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if request.len() >= 16 * 1024 {
+            return;
+        }
+        match sock.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..head_end]).into_owned();
+    let content_length = head
+        .lines()
+        .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap_or(0)))
+        .unwrap_or(0usize);
+    while request.len() < head_end + content_length {
+        match sock.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&buf[..n]),
+        }
+    }
+    let body = &request[head_end..head_end + content_length];
+    let path = head.split_whitespace().nth(1).unwrap_or("/");
+    let (status, body) = match route(&state, path, body) {
+        Ok(body) => (200, body),
+        Err((code, message)) => (
+            code.http_status(),
+            serde_json::to_value(ErrorBody::new(code, message)).expect("error body serializes"),
+        ),
+    };
+    let body = body.to_string();
+    let resp = format!(
+        "HTTP/1.1 {status} X\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = sock.write_all(resp.as_bytes()).await;
+}
+
+/// The indexer's `/v1` routes over the mock chain; `body` is only read by
+/// `POST /v1/proofs`
+fn route(state: &State, path: &str, body: &[u8]) -> Result<serde_json::Value, (ErrorCode, String)> {
+    let invalid = |e: anyhow::Error| (ErrorCode::InvalidInput, e.to_string());
+    let chain = state.lock();
+    let (path, query) = path.split_once('?').unwrap_or((path, ""));
+    let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+    match segments[..] {
+        ["v1", "health"] => Ok(serde_json::json!({ "status": "ok" })),
+        // the mock indexer is always caught up
+        ["v1", "stats"] => Ok(serde_json::json!({
+            "leaf_count": chain.leaves.len(),
+            "nullifier_count": chain.nullifiers.len(),
+            "deposits": chain.deposits,
+            "transfers": chain.transfers,
+            "first_ledger": chain.leaves.first().map(|(_, ledger)| ledger),
+            "last_ledger": chain.ledger,
+            "network_ledger": chain.ledger,
+            "root": format!("0x{}", chain.latest_root()),
+        })),
+        ["v1", "root"] => Ok(serde_json::json!({
+            "root": format!("0x{}", chain.latest_root()),
+            "ledger": chain.ledger,
+        })),
+        // the indexer records roots as it indexes leaves, so not the empty one
+        ["v1", "roots"] => {
+            let limit = query
+                .split('&')
+                .find_map(|kv| kv.strip_prefix("limit="))
+                .map_or(Ok(100), str::parse::<usize>)
+                .map_err(|_| (ErrorCode::InvalidInput, format!("bad query `{query}`")))?;
+            let roots: Vec<_> = chain.roots[1..].iter().rev().take(limit).map(Chain::historical_root).collect();
+            Ok(serde_json::json!({ "roots": roots }))
+        }
+        ["v1", "root", "at", leaf_count] => {
+            let leaf_count: usize = leaf_count
+                .parse()
+                .map_err(|_| (ErrorCode::InvalidInput, format!("bad leaf count `{leaf_count}`")))?;
+            chain.roots[1..]
+                .iter()
+                .find(|(_, n, _)| *n == leaf_count)
+                .map(Chain::historical_root)
+                .ok_or((ErrorCode::NotFound, format!("no root recorded at {leaf_count} leaves")))
+        }
+        ["v1", "leaves"] => {
+            let param = |name: &str| {
+                query
+                    .split('&')
+                    .find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='))
+                    .map(str::parse::<usize>)
+                    .transpose()
+                    .map_err(|_| (ErrorCode::InvalidInput, format!("bad query `{query}`")))
+            };
+            let mut from = param("from_index")?.or(param("from")?).unwrap_or(0);
+            if let Some(ledger) = param("since_ledger")? {
+                from = from.max(chain.leaves.iter().filter(|&&(_, l)| (l as usize) < ledger).count());
+            }
+            let leaves = chain.commitments();
+            let page = leaves.get(from..).unwrap_or_default();
+            let page = &page[..page.len().min(param("limit")?.unwrap_or(usize::MAX))];
+            let end = from + page.len();
+            Ok(serde_json::json!({
+                "from": from,
+                "leaves": page,
+                "root": format!("0x{}", chain.latest_root()),
+                "confirmed": chain.leaves.iter().take_while(|(_, ledger)| !chain.is_pending(*ledger)).count(),
+                "next": (end < leaves.len()).then_some(end),
+            }))
+        }
+        ["v1", "leaf", cm] => {
+            let cm = hex_to_fr(cm).map_err(invalid)?;
+            chain
+                .leaves
+                .iter()
+                .position(|(c, _)| c.0 == cm)
+                .map(|i| {
+                    let block_height = chain.leaves[i].1;
+                    serde_json::json!({ "index": i, "block_height": block_height, "pending": chain.is_pending(block_height) })
+                })
+                .ok_or((ErrorCode::NotFound, "commitment not found".into()))
+        }
+        ["v1", "nullifier", nf] => {
+            let nf = hex_to_fr(nf).map_err(invalid)?;
+            chain
+                .nullifiers
+                .get(&nf)
+                .map(|ledger| serde_json::json!({ "nullifier": format!("0x{}", fr_to_raw_hex(&nf)), "block_height": ledger }))
+                .ok_or((ErrorCode::NotFound, "nullifier not found".into()))
+        }
+        ["v1", "proof", "by-commitment", cm] => {
+            let cm = hex_to_fr(cm).map_err(invalid)?;
+            let leaves = chain.commitments();
+            let index = leaves
+                .iter()
+                .position(|c| c.0 == cm)
+                .ok_or((ErrorCode::NotFound, "commitment not found".into()))?;
+            let (root, leaf_count) = match query.split('&').find_map(|kv| kv.strip_prefix("root=")) {
+                None => (chain.latest_root().to_string(), leaves.len()),
+                Some(root) => {
+                    let root = crate::wallet::strip_0x(root);
+                    let &(_, leaf_count, _) = chain.roots[1..]
+                        .iter()
+                        .find(|(r, ..)| *r == root)
+                        .ok_or((ErrorCode::NotFound, "root not recorded".into()))?;
+                    (root, leaf_count)
+                }
+            };
+            let block_height = chain.leaves[index].1;
+            let path = crate::merkle::path_from_leaves(&leaves[..leaf_count], LeafIndex(index as u64))
+                .ok_or((ErrorCode::InvalidInput, format!("leaf {index} is not under that root")))?;
+            Ok(serde_json::json!({
+                "index": index,
+                "block_height": block_height,
+                "pending": chain.is_pending(block_height),
+                "root": format!("0x{root}"),
+                "leaf_count": leaf_count,
+                "path": path,
+            }))
+        }
+        ["v1", "proofs"] => {
+            #[derive(serde::Deserialize)]
+            struct Request {
+                #[serde(default)]
+                indices: Vec<u64>,
+                #[serde(default)]
+                commitments: Vec<String>,
+            }
+            let request: Request = serde_json::from_slice(body).map_err(|e| invalid(e.into()))?;
+            let leaves = chain.commitments();
+            let mut indices = request.indices;
+            for cm in &request.commitments {
+                let cm = hex_to_fr(cm).map_err(invalid)?;
+                let index = leaves
+                    .iter()
+                    .position(|c| c.0 == cm)
+                    .ok_or((ErrorCode::NotFound, "commitment not found".into()))?;
+                indices.push(index as u64);
+            }
+            let proofs = indices
+                .into_iter()
+                .map(|index| {
+                    let path = crate::merkle::path_from_leaves(&leaves, LeafIndex(index))
+                        .ok_or((ErrorCode::NotFound, format!("leaf {index} not found")))?;
+                    Ok(serde_json::json!({ "index": index, "path": path }))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(serde_json::json!({
+                "root": format!("0x{}", chain.latest_root()),
+                "leaf_count": leaves.len(),
+                "proofs": proofs,
+            }))
+        }
+        ["v1", "proof", index] => {
+            let index: u64 = index
+                .parse()
+                .map_err(|_| (ErrorCode::InvalidInput, format!("bad leaf index `{index}`")))?;
+            let path = crate::merkle::path_from_leaves(&chain.commitments(), LeafIndex(index))
+                .ok_or((ErrorCode::NotFound, format!("leaf {index} not found")))?;
+            Ok(serde_json::to_value(path).expect("merkle path serializes"))
+        }
+        _ => Err((ErrorCode::NotFound, format!("no route {path}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::PrebuiltProof;
+    use crate::{commitment, owner_hash, Note, SecretKey};
+
+    fn proof(old_root: &str, nf: u64, cm_0: Commitment, cm_1: Commitment, current_ledger: u64) -> PrebuiltProof {
+        PrebuiltProof {
+            proof_json: "{}".into(),
+            old_root: old_root.into(),
+            nullifier: fr_to_raw_hex(&Fr::from(nf)),
+            cm_0: fr_to_raw_hex(&cm_0.0),
+            cm_1: fr_to_raw_hex(&cm_1.0),
+            current_ledger,
+        }
+    }
+
+    #[tokio::test]
+    async fn deposit_and_transfer_against_the_mock() {
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let owner = owner_hash(&SecretKey::random(&mut rng)).0;
+
+        let deposit = client.deposit(100, 1, &owner).await.unwrap();
+        let mut notes = vec![deposit.note_entry];
+        assert_eq!(client.balance(&mut notes).await.unwrap().total, 100);
+        assert_eq!(notes[0].index, Some(LeafIndex(0)));
+        let path = client.indexer().merkle_proof(LeafIndex(0)).await.unwrap();
+        assert!(crate::merkle::verify_proof(deposit.commitment, &path, client.indexer().root().await.unwrap()));
+
+        let old_root = client.latest_root().await.unwrap();
+        let outs = [60, 40].map(|v| commitment(&Note::new(v, 1, owner, &mut rng)));
+        let p = proof(&old_root, 7, outs[0], outs[1], network.ledger());
+        assert_eq!(client.submit_transfer(&p).await.unwrap(), "true");
+        assert_eq!(network.leaves(), [deposit.commitment, outs[0], outs[1]]);
+        assert!(client.is_spent(&p.nullifier).await.unwrap());
+        let nf = crate::Nullifier(Fr::from(7u64));
+        assert_eq!(client.indexer().nullifier(&nf).await.unwrap().unwrap().block_height, network.ledger());
+        let stats = client.indexer().stats().await.unwrap();
+        assert_eq!((stats.leaf_count, stats.nullifier_count, stats.deposits, stats.transfers), (3, 1, 1, 1));
+        assert_eq!((stats.first_ledger, stats.last_ledger), (Some(network.ledger() - 1), Some(network.ledger())));
+        let query = crate::indexer::LeavesQuery { limit: Some(2), ..Default::default() };
+        let page = client.indexer().leaves_page(query).await.unwrap();
+        assert_eq!((page.leaves, page.next), (vec![deposit.commitment, outs[0]], Some(LeafIndex(2))));
+        let query = crate::indexer::LeavesQuery { since_ledger: Some(network.ledger()), ..Default::default() };
+        let page = client.indexer().leaves_page(query).await.unwrap();
+        assert_eq!((page.from, page.leaves, page.next), (LeafIndex(1), outs.to_vec(), None));
+
+        // the old root is still in history, but the nullifier isn't fresh
+        let err = client.submit_transfer(&p).await.unwrap_err();
+        assert!(err.to_string().contains("nullifier already spent"), "{err}");
+        assert!(!client.is_known_root(&"00".repeat(32)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn deposit_many_lands_in_one_ledger() {
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let owner = owner_hash(&SecretKey::random(&mut crate::wallet::crypto_rng())).0;
+
+        let deposits = client.deposit_many(&[(100, 1), (50, 1), (7, 2)], &owner).await.unwrap();
+        assert_eq!(deposits.iter().map(|d| (d.value, d.app_tag)).collect::<Vec<_>>(), [(100, 1), (50, 1), (7, 2)]);
+        assert_eq!(network.leaves(), deposits.iter().map(|d| d.commitment).collect::<Vec<_>>());
+        assert_eq!(network.ledger(), 2);
+        assert_eq!(network.calls().len(), 1);
+
+        let mut notes: Vec<_> = deposits.into_iter().map(|d| d.note_entry).collect();
+        assert_eq!(client.balance(&mut notes).await.unwrap().total, 157);
+        let Err(err) = client.deposit_many(&[], &owner).await else { panic!("empty batch deposited") };
+        assert_eq!(err.code(), ErrorCode::Config);
+
+        let config = crate::denomination::DenominationConfig::default();
+        let split = client.deposit_split(1_230, 3, &owner, &config).await.unwrap();
+        assert_eq!(split.iter().map(|d| d.value).collect::<Vec<_>>(), [1_000, 100, 100, 10, 10, 10]);
+        assert_eq!(network.leaves().len(), 9);
+    }
+
+    #[tokio::test]
+    async fn historical_roots_follow_contract_calls() {
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let owner = owner_hash(&SecretKey::random(&mut crate::wallet::crypto_rng())).0;
+        client.deposit_many(&[(1, 1), (2, 1)], &owner).await.unwrap();
+        client.deposit(3, 1, &owner).await.unwrap();
+
+        let indexer = network.indexer();
+        let roots = indexer.roots(10).await.unwrap();
+        assert_eq!(roots.iter().map(|r| (r.leaf_count, r.ledger)).collect::<Vec<_>>(), [(3, 3), (2, 2)]);
+        assert_eq!(crate::wallet::fr_to_raw_hex(&roots[0].root.0), network.root());
+        assert_eq!(indexer.roots(1).await.unwrap(), roots[..1]);
+        // the batch set one root, so there is none between its leaves
+        assert_eq!(indexer.root_at(2).await.unwrap(), Some(roots[1].clone()));
+        assert_eq!(indexer.root_at(1).await.unwrap(), None);
+        assert!(client.is_known_root(&crate::wallet::fr_to_raw_hex(&roots[1].root.0)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn proofs_by_commitment_pick_the_root() {
+        let network = MockNetwork::start().await.unwrap();
+        let cms = [1u64, 2, 3].map(|v| Commitment(Fr::from(v)));
+        for cm in cms {
+            network.insert_leaf(cm);
+        }
+        let indexer = network.indexer();
+
+        let latest = indexer.proof_by_commitment(&cms[1], None).await.unwrap().unwrap();
+        assert_eq!((latest.index, latest.leaf_count), (LeafIndex(1), 3));
+        assert!(crate::merkle::verify_proof(cms[1], &latest.path, latest.root.0));
+
+        let older = indexer.root_at(2).await.unwrap().unwrap();
+        let proof = indexer.proof_by_commitment(&cms[1], Some(&older.root)).await.unwrap().unwrap();
+        assert_eq!((proof.root.clone(), proof.leaf_count), (older.root.clone(), 2));
+        assert!(crate::merkle::verify_proof(cms[1], &proof.path, older.root.0));
+
+        // the third leaf isn't under the older root, and unknown leaves are None
+        let err = indexer.proof_by_commitment(&cms[2], Some(&older.root)).await.unwrap_err();
+        assert!(err.to_string().contains("not under that root"), "{err}");
+        assert!(indexer.proof_by_commitment(&Commitment(Fr::from(9u64)), None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn batch_proofs_share_one_root() {
+        let network = MockNetwork::start().await.unwrap();
+        let cms = [1u64, 2, 3].map(|v| Commitment(Fr::from(v)));
+        for cm in cms {
+            network.insert_leaf(cm);
+        }
+        let indexer = network.indexer();
+
+        let batch = indexer.merkle_proofs(&[LeafIndex(2), LeafIndex(0)]).await.unwrap().unwrap();
+        assert_eq!((fr_to_raw_hex(&batch.root.0), batch.leaf_count), (network.root(), 3));
+        assert_eq!(batch.proofs.iter().map(|p| p.index).collect::<Vec<_>>(), [LeafIndex(2), LeafIndex(0)]);
+        assert!(crate::merkle::verify_proof(cms[2], &batch.proofs[0].path, batch.root.0));
+
+        let by_cm = indexer.merkle_proofs_by_commitment(&[cms[1]]).await.unwrap().unwrap();
+        assert_eq!(by_cm.proofs[0].index, LeafIndex(1));
+        assert!(crate::merkle::verify_proof(cms[1], &by_cm.proofs[0].path, by_cm.root.0));
+
+        let err = indexer.merkle_proofs(&[LeafIndex(0), LeafIndex(7)]).await.unwrap_err();
+        assert!(err.to_string().contains("leaf 7 not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn checks_ledger_and_injected_failures() {
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        network.insert_leaf(Commitment(Fr::from(5u64)));
+        let root = network.root();
+
+        let ahead = proof(&root, 1, Commitment(Fr::from(8u64)), Commitment(Fr::from(9u64)), network.ledger() + 1);
+        let err = client.submit_transfer(&ahead).await.unwrap_err();
+        assert!(err.to_string().contains("current_ledger is in the future"), "{err}");
+        network.advance_ledger(1);
+
+        network.fail_next("transfer", "tx dropped");
+        let err = client.submit_transfer(&ahead).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::Soroban);
+        assert!(err.to_string().contains("tx dropped"), "{err}");
+        client.submit_transfer(&ahead).await.unwrap();
+
+        let calls = network.calls();
+        assert_eq!(calls.iter().filter(|c| c.function == "transfer").count(), 3);
+        assert!(calls.iter().all(|c| c.contract_id == MOCK_TRANSFER_CONTRACT));
+        assert_eq!(client.indexer().leaf(&Commitment(Fr::from(1u64))).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn client_computes_roots_through_the_leaf_cache() {
+        let network = MockNetwork::start().await.unwrap();
+        let client = R14Client::builder(network.indexer_url(), network.contracts(), MOCK_STELLAR_SECRET)
+            .invoker(network.invoker())
+            .leaf_cache(crate::leaf_cache::LeafCache::new())
+            .build()
+            .unwrap();
+        let owner = Fr::from(3u64);
+        network.insert_leaf(Commitment(Fr::from(5u64)));
+
+        // the mock rejects a deposit whose new_root doesn't match the tree
+        client.deposit(10, 1, &owner).await.unwrap();
+        network.insert_leaf(Commitment(Fr::from(6u64)));
+        client.deposit(20, 1, &owner).await.unwrap();
+        assert_eq!(network.leaves().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn root_consistency_against_other_indexers() {
+        use crate::client::RootStatus;
+
+        let network = MockNetwork::start().await.unwrap();
+        network.insert_leaf(Commitment(Fr::from(5u64)));
+        let client = network.client().unwrap();
+        let report = client.verify_root_consistency().await.unwrap();
+        assert_eq!((report.status, report.indexer_root.as_str()), (RootStatus::InSync, network.root().as_str()));
+
+        // an indexer still at the empty root, and one on another pool
+        let behind = MockNetwork::start().await.unwrap();
+        let other = MockNetwork::start().await.unwrap();
+        other.insert_leaf(Commitment(Fr::from(6u64)));
+        network.insert_leaf(Commitment(Fr::from(7u64)));
+        let client = client.with_indexer(behind.indexer());
+        let report = client.verify_root_consistency().await.unwrap();
+        assert_eq!(report.status, RootStatus::Behind);
+        report.ensure_usable().unwrap();
+
+        let client = client.with_indexer(other.indexer());
+        let report = client.verify_root_consistency().await.unwrap();
+        assert_eq!((report.status, &report.contract_root), (RootStatus::Diverged, &network.root()));
+        let err = report.ensure_usable().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::StaleRoot);
+    }
+
+    /// circuit_id of the keys `R14Client` proves with
+    #[cfg(feature = "prove")]
+    fn local_circuit_id() -> String {
+        let (_, vk) = crate::artifacts::transfer_keys().unwrap();
+        crate::serialize::circuit_id(&crate::prove::serialize_vk_for_soroban(&vk, crate::MERKLE_DEPTH))
+    }
+
+    fn wallet(network: &MockNetwork, sk: &Fr, notes: Vec<crate::wallet::NoteEntry>) -> crate::wallet::WalletData {
+        let mut w = crate::wallet::WalletData::for_test(&SecretKey(*sk));
+        w.stellar_secret = MOCK_STELLAR_SECRET.into();
+        w.notes = notes;
+        w.indexer_url = network.indexer_url().into();
+        w.rpc_url = String::new();
+        w.core_contract_id = MOCK_CORE_CONTRACT.into();
+        w.transfer_contract_id = MOCK_TRANSFER_CONTRACT.into();
+        w
+    }
+
+    #[tokio::test]
+    async fn rescan_rebuilds_owned_notes_from_the_chain() {
+        use crate::client::RescanReport;
+        use crate::wallet::NoteEntry;
+
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let sk = SecretKey::random(&mut rng);
+        let owner = owner_hash(&sk).0;
+        let early = client.deposit(10, 1, &owner).await.unwrap().note_entry;
+        network.advance_ledger(100);
+        let from_ledger = network.ledger();
+        let kept = client.deposit(20, 1, &owner).await.unwrap().note_entry;
+        let spent = client.deposit(30, 1, &owner).await.unwrap().note_entry;
+        let unknown = Note::new(40, 1, owner, &mut rng);
+        let someone_elses = Note::new(50, 1, Fr::from(9u64), &mut rng);
+
+        // spend `spent` behind the wallet's back
+        let nonce = hex_to_fr(&spent.nonce).unwrap();
+        let nf = crate::nullifier(&sk, &nonce, LeafIndex(2));
+        let outs = [1, 2].map(|v| Commitment(Fr::from(v as u64)));
+        let p = PrebuiltProof { nullifier: fr_to_raw_hex(&nf.0), ..proof(&network.root(), 0, outs[0], outs[1], network.ledger()) };
+        client.submit_transfer(&p).await.unwrap();
+
+        let strip = |mut n: NoteEntry| {
+            n.index = None;
+            n
+        };
+        let mut notes = vec![
+            strip(early.clone()),
+            strip(kept.clone()),
+            kept.clone(),
+            strip(spent),
+            NoteEntry::new(&unknown, &commitment(&unknown)),
+            NoteEntry::new(&someone_elses, &commitment(&someone_elses)),
+        ];
+        let report = client.rescan(&mut notes, &sk.0, from_ledger).await.unwrap();
+        assert_eq!(report, RescanReport { checked: 4, located: 2, spent: 1, missing: 1, duplicates: 1 });
+        assert_eq!(notes.len(), 5);
+        assert_eq!(notes[0].index, None, "landed before from_ledger");
+        assert_eq!((notes[1].index, notes[1].spent), (Some(LeafIndex(1)), false));
+        assert_eq!((notes[2].index, notes[2].spent), (Some(LeafIndex(2)), true));
+
+        let report = client.rescan(&mut notes, &sk.0, 0).await.unwrap();
+        assert_eq!((report.located, report.spent, report.duplicates), (1, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn parked_proofs_resume_after_an_outage() {
+        use crate::client::ResumeOutcome;
+        use crate::wallet::{NoteEntry, WalletData};
+
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let owner = owner_hash(&SecretKey::random(&mut rng)).0;
+        let deposits = [client.deposit(100, 1, &owner).await.unwrap(), client.deposit(50, 1, &owner).await.unwrap()];
+        let outs = [60, 40].map(|v| {
+            let note = Note::new(v, 1, owner, &mut rng);
+            NoteEntry::new(&note, &commitment(&note))
+        });
+        let mut w = wallet(&network, &Fr::from(1u64), deposits.map(|d| d.note_entry).to_vec());
+
+        let p = PrebuiltProof {
+            proof_json: r#"{"a":"aa","b":"bb","c":"cc"}"#.into(),
+            ..proof(&network.root(), 7, outs[0].commitment, outs[1].commitment, network.ledger())
+        };
+        client.save_pending_proof(&mut w, 0, &p, outs[0].clone(), outs[1].clone()).unwrap();
+        // a proof on a root the contract will have forgotten by the time it's resumed
+        let old = PrebuiltProof { old_root: "00".repeat(32), nullifier: fr_to_raw_hex(&Fr::from(8u64)), ..p.clone() };
+        client.save_pending_proof(&mut w, 1, &old, outs[0].clone(), outs[1].clone()).unwrap();
+        let mut w: WalletData = serde_json::from_str(&serde_json::to_string(&w).unwrap()).unwrap();
+
+        network.fail_next("transfer", "connection reset");
+        let resumed = client.resume_pending(&mut w).await.unwrap();
+        assert!(matches!(resumed[0].outcome, ResumeOutcome::Failed(_)));
+        assert!(matches!(resumed[1].outcome, ResumeOutcome::Expired));
+        assert_eq!(w.pending.len(), 1);
+        assert!(w.notes[0].spent && !w.notes[1].spent);
+
+        let resumed = client.resume_pending(&mut w).await.unwrap();
+        assert!(matches!(resumed[0].outcome, ResumeOutcome::Submitted(ref tx) if tx == "true"));
+        assert!(w.pending.is_empty());
+        assert_eq!(w.notes.len(), 4);
+        assert_eq!(network.leaves()[2..], [outs[0].commitment, outs[1].commitment]);
+        assert_eq!(w.history.last().unwrap().tx_result.as_deref(), Some("true"));
+        assert!(client.resume_pending(&mut w).await.unwrap().is_empty());
+    }
+
+    #[cfg(feature = "prove")]
+    #[tokio::test]
+    async fn rotation_sweeps_every_note_to_the_new_key() {
+        use crate::history::HistoryKind;
+
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let (old_sk, new_sk) = (SecretKey::random(&mut rng), SecretKey::random(&mut rng));
+        let old_owner = owner_hash(&old_sk).0;
+        network.set_circuit_id(&local_circuit_id());
+        let notes = vec![
+            client.deposit(70, 1, &old_owner).await.unwrap().note_entry,
+            client.deposit(30, 2, &old_owner).await.unwrap().note_entry,
+        ];
+        let mut w = wallet(&network, &old_sk.0, notes);
+
+        network.fail_next("transfer", "transaction simulation failed: HostError: Error(Contract, #3)");
+        assert!(client.rotate_key(&mut w, &new_sk.0).await.is_err());
+        assert_eq!(hex_to_fr(&w.secret_key).unwrap(), old_sk.0);
+        assert!(w.notes.iter().all(|n| !n.spent) && w.history.is_empty() && w.pending.is_empty());
+
+        let rotation = client.rotate_key(&mut w, &new_sk.0).await.unwrap();
+        let new_owner = crate::wallet::fr_to_hex(&owner_hash(&new_sk).0);
+        assert_eq!((rotation.swept, rotation.transfers.len()), (100, 2));
+        assert_eq!((&w.owner_hash, hex_to_fr(&w.secret_key).unwrap()), (&new_owner, new_sk.0));
+        assert!(w.notes[..2].iter().all(|n| n.spent));
+        let unspent: Vec<_> = w.notes.iter().filter(|n| !n.spent).collect();
+        assert_eq!(unspent.iter().map(|n| (n.value, n.app_tag)).collect::<Vec<_>>(), [(70, 1), (30, 2)]);
+        assert!(unspent.iter().all(|n| n.owner == new_owner));
+        let kinds: Vec<_> = w.history.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, [HistoryKind::Transfer, HistoryKind::Transfer, HistoryKind::Rotation]);
+        assert_eq!(network.leaves().len(), 6);
+    }
+
+    #[cfg(feature = "prove")]
+    #[tokio::test]
+    async fn spending_policy_refuses_before_proving() {
+        use crate::policy::SpendingPolicy;
+        use crate::CoinSelection;
+
+        let network = MockNetwork::start().await.unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let sk = SecretKey::random(&mut rng);
+        let (owner, bob) = (owner_hash(&sk).0, owner_hash(&SecretKey::random(&mut rng)).0);
+        let deposit = network.client().unwrap().deposit(1_000, 1, &owner).await.unwrap();
+        let mut w = wallet(&network, &sk.0, vec![deposit.note_entry]);
+        w.policy = Some(SpendingPolicy { max_per_transfer: Some(500), ..Default::default() });
+
+        let client = R14Client::from_wallet(&w).unwrap();
+        let Err(err) = client.transfer(&mut w, &sk.0, &owner, &bob, 600, CoinSelection::default()).await else {
+            panic!("transfer over the limit went through");
+        };
+        assert_eq!(err.code(), ErrorCode::PolicyViolation, "{err}");
+        assert!(!w.notes[0].spent);
+        assert_eq!(network.calls().iter().filter(|c| c.function == "transfer").count(), 0);
+    }
+
+    #[tokio::test]
+    async fn confirmed_only_clients_skip_pending_notes() {
+        use crate::CoinSelection;
+
+        let network = MockNetwork::start().await.unwrap();
+        network.set_confirmations(3);
+        let client = R14Client::builder(network.indexer_url(), network.contracts(), MOCK_STELLAR_SECRET)
+            .invoker(network.invoker())
+            .confirmed_only()
+            .build()
+            .unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let owner = owner_hash(&SecretKey::random(&mut rng)).0;
+        let bob = owner_hash(&SecretKey::random(&mut rng)).0;
+
+        let mut notes = vec![client.deposit(50, 1, &owner).await.unwrap().note_entry];
+        network.advance_ledger(3);
+        notes.push(client.deposit(100, 1, &owner).await.unwrap().note_entry);
+        client.sync_notes(&mut notes).await.unwrap();
+        assert!(!client.indexer().leaf(&notes[0].commitment).await.unwrap().unwrap().pending);
+        assert!(client.indexer().leaf(&notes[1].commitment).await.unwrap().unwrap().pending);
+
+        // only the pending note covers 80
+        let err = client.prepare_transfer(&notes, &owner, &bob, 80, CoinSelection::default()).await.err().unwrap();
+        assert!(matches!(err, R14Error::InsufficientBalance { needed: 80, best: 50 }), "{err}");
+
+        // the path leads to the root of the confirmed leaf alone
+        let bundle = client.prepare_transfer(&notes, &owner, &bob, 40, CoinSelection::default()).await.unwrap();
+        assert_eq!(bundle.note.commitment, notes[0].commitment);
+        let confirmed_root = crate::wallet::hex_to_fr(&crate::merkle::compute_root_from_leaves(&[notes[0].commitment])).unwrap();
+        assert!(crate::merkle::verify_proof(notes[0].commitment, &bundle.merkle_path, confirmed_root));
+
+        network.advance_ledger(3);
+        let bundle = client.prepare_transfer(&notes, &owner, &bob, 80, CoinSelection::default()).await.unwrap();
+        assert_eq!(bundle.note.commitment, notes[1].commitment);
+    }
+
+    #[cfg(feature = "prove")]
+    #[tokio::test]
+    async fn diverged_circuit_fails_before_proving() {
+        use crate::CoinSelection;
+
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let sk = SecretKey::random(&mut rng);
+        let (owner, bob) = (owner_hash(&sk).0, owner_hash(&SecretKey::random(&mut rng)).0);
+        let mut notes = vec![client.deposit(100, 1, &owner).await.unwrap().note_entry];
+        client.sync_notes(&mut notes).await.unwrap();
+        let mut w = wallet(&network, &sk.0, notes);
+
+        // the mock still reports its placeholder circuit_id
+        let Err(err) = client.transfer(&mut w, &sk.0, &owner, &bob, 60, CoinSelection::default()).await else {
+            panic!("proved against a diverged verifier");
+        };
+        let R14Error::CircuitMismatch { local, deployed } = &err else { panic!("{err}") };
+        assert_eq!((local.as_str(), deployed.as_str()), (local_circuit_id().as_str(), fr_to_raw_hex(&Fr::from(14u64)).as_str()));
+        assert_eq!(err.id(), "R14_CIRCUIT_MISMATCH");
+        assert!(!w.notes[0].spent);
+        assert!(network.calls().iter().all(|c| c.function != "transfer"));
+
+        network.set_circuit_id(&local_circuit_id());
+        client.transfer(&mut w, &sk.0, &owner, &bob, 60, CoinSelection::default()).await.unwrap();
+    }
+
+    #[cfg(feature = "prove")]
+    #[tokio::test]
+    async fn transfer_rolls_back_only_what_cannot_have_landed() {
+        use crate::CoinSelection;
+
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        network.set_circuit_id(&local_circuit_id());
+        let mut rng = crate::wallet::crypto_rng();
+        let sk = SecretKey::random(&mut rng);
+        let (owner, bob) = (owner_hash(&sk).0, owner_hash(&SecretKey::random(&mut rng)).0);
+        let mut notes = vec![client.deposit(100, 1, &owner).await.unwrap().note_entry];
+        client.sync_notes(&mut notes).await.unwrap();
+        let mut w = wallet(&network, &sk.0, notes);
+
+        // rejected in simulation: the note is free again
+        network.fail_next("transfer", "transaction simulation failed: HostError: Error(Contract, #3)");
+        assert!(client.transfer(&mut w, &sk.0, &owner, &bob, 60, CoinSelection::default()).await.is_err());
+        assert!(!w.notes[0].spent && w.pending.is_empty());
+
+        // timed out after submission: it may still land, so the note stays reserved
+        network.fail_next("transfer", "transaction submission timeout");
+        assert!(client.transfer(&mut w, &sk.0, &owner, &bob, 60, CoinSelection::default()).await.is_err());
+        assert!(w.notes[0].spent);
+        assert_eq!(w.pending.len(), 1);
+        assert_eq!(w.pending[0].outputs.iter().map(|n| n.value).collect::<Vec<_>>(), [60, 40]);
+        assert_eq!(w.notes.len(), 1);
+        assert!(w.history.is_empty());
+    }
+
+    #[cfg(feature = "prove")]
+    #[tokio::test]
+    async fn offline_signing_round_trip() {
+        use crate::offline::{prove_and_sign, SignedTransfer, TransferBundle};
+        use crate::CoinSelection;
+
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        let mut rng = crate::wallet::crypto_rng();
+        let sk = SecretKey::random(&mut rng);
+        let (owner, bob) = (owner_hash(&sk).0, owner_hash(&SecretKey::random(&mut rng)).0);
+        let mut notes = vec![client.deposit(100, 1, &owner).await.unwrap().note_entry];
+        client.sync_notes(&mut notes).await.unwrap();
+        let mut w = wallet(&network, &sk.0, notes);
+        let notes = w.notes.clone();
+
+        // the mock starts with a placeholder circuit_id
+        let bundle = client.prepare_transfer(&notes, &owner, &bob, 60, CoinSelection::default()).await.unwrap();
+        let Err(err) = prove_and_sign(&bundle, &sk.0) else { panic!("proved for the wrong circuit") };
+        assert!(matches!(err, R14Error::CircuitMismatch { .. }), "{err}");
+        network.invoker().invoke(MOCK_TRANSFER_CONTRACT, client.network(), "", "init", &[("circuit_id", &local_circuit_id())]).await.unwrap();
+
+        let bundle = client.prepare_transfer(&notes, &owner, &bob, 60, CoinSelection::default()).await.unwrap();
+        assert!(network.calls().iter().all(|c| c.function != "transfer"));
+        let bundle = TransferBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        let other = SecretKey::random(&mut rng);
+        let Err(err) = prove_and_sign(&bundle, &other.0) else { panic!("proved with a key that doesn't own the note") };
+        assert_eq!(err.code(), ErrorCode::Config);
+
+        let signed = prove_and_sign(&bundle, &sk.0).unwrap();
+        let signed: SignedTransfer = serde_json::from_str(&serde_json::to_string(&signed).unwrap()).unwrap();
+        let result = client.submit_signed(&mut w, &signed).await.unwrap();
+        assert!(w.notes[0].spent);
+        assert_eq!((result.recipient_note.value, result.change_note.value), (60, 40));
+        assert_eq!(network.leaves()[1..], [result.out_commitment_0, result.out_commitment_1]);
+        assert_eq!(w.notes.len(), 3);
+        // the note is spent now, so the same transfer can't go twice
+        assert!(client.submit_signed(&mut w, &signed).await.is_err());
+    }
+
+    /// Relayer that answers one request with `{"tx_result":"true"}` and
+    /// returns the body it was sent
+    async fn relayer_stub() -> (String, tokio::task::JoinHandle<serde_json::Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut req = Vec::new();
+            let mut buf = [0u8; 4096];
+            let body = loop {
+                let n = sock.read(&mut buf).await.unwrap();
+                req.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&req).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let len: usize = head
+                        .lines()
+                        .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                        .unwrap();
+                    if body.len() >= len {
+                        break body.to_string();
+                    }
+                }
+            };
+            let resp = r#"{"tx_result":"true"}"#;
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n", resp.len());
+            sock.write_all(format!("{head}{resp}").as_bytes()).await.unwrap();
+            serde_json::from_str(&body).unwrap()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn relayed_submission_sends_the_contract_arguments() {
+        let network = MockNetwork::start().await.unwrap();
+        let client = network.client().unwrap();
+        network.insert_leaf(Commitment(Fr::from(5u64)));
+        let (cm_0, cm_1) = (Commitment(Fr::from(8u64)), Commitment(Fr::from(9u64)));
+        let p = PrebuiltProof {
+            proof_json: r#"{"a":"aa","b":"bb","c":"cc"}"#.into(),
+            ..proof(&network.root(), 1, cm_0, cm_1, network.ledger())
+        };
+
+        let (url, relayer) = relayer_stub().await;
+        assert_eq!(client.submit_via_relayer(&url, &p).await.unwrap(), "true");
+        let body = relayer.await.unwrap();
+        assert_eq!(body["contract_id"], MOCK_TRANSFER_CONTRACT);
+        assert_eq!(body["proof"]["b"], "bb");
+        assert_eq!(body["public_inputs"]["nullifier"], p.nullifier.as_str());
+        let expected = crate::merkle::compute_root_from_leaves(&[Commitment(Fr::from(5u64)), cm_0, cm_1]);
+        assert_eq!(body["new_root"], expected);
+        // the relayer paid; nothing went through this client's invoker
+        assert!(network.calls().iter().all(|c| c.function != "transfer"));
+
+        // a root the contract has forgotten is caught before posting
+        let stale = PrebuiltProof { old_root: "00".repeat(32), ..p };
+        let err = client.submit_via_relayer("http://127.0.0.1:9", &stale).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::StaleRoot);
+    }
+}
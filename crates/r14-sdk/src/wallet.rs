@@ -5,18 +5,31 @@
 //!
 //! Stores keys, notes, and config as JSON at `~/.r14/wallet.json`.
 //!
+//! # Concurrent access
+//!
+//! [`save_wallet`] writes a temporary file and renames it over the wallet,
+//! so a crash mid-write never leaves a truncated file. That alone doesn't
+//! stop two processes from each loading the wallet, adding a note, and
+//! saving over the other's. Hold a [`WalletLock`] from load to save for
+//! that.
+//!
 //! # Hex format
 //!
 //! [`fr_to_hex`] produces `0x`-prefixed big-endian hex (66 chars).
 //! [`hex_to_fr`] accepts both `0x`-prefixed and raw hex, and zero-pads
 //! short inputs to 32 bytes.
 //!
+//! Owner hashes also have a checksummed `r14o1...` form for sharing
+//! ([`owner_address`]). Parse recipients with [`parse_owner`], which takes
+//! either, so a typo in an address fails instead of paying a stranger.
+//!
 //! # Example
 //!
 //! ```rust,no_run
 //! use r14_sdk::wallet::{load_wallet, save_wallet, fr_to_hex, hex_to_fr};
 //!
 //! # fn example() -> anyhow::Result<()> {
+//! let _lock = r14_sdk::wallet::WalletLock::acquire()?;
 //! let mut w = load_wallet()?;
 //! let owner_fr = hex_to_fr(&w.owner_hash)?;
 //! // ... use owner_fr in note creation ...
@@ -27,43 +40,227 @@
 
 use anyhow::{Context, Result};
 use ark_bls12_381::Fr;
-use ark_ff::{BigInteger, PrimeField};
 use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_types::{Commitment, LeafIndex, Note, SecretKey};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-/// Timestamp-seeded RNG for note nonces only — NOT a CSPRNG.
+pub use crate::backup::{export_notes, import_notes, restore_notes, ImportSummary};
+
+/// RNG for note nonces, secret keys and proof randomness. Same as
+/// [`os_rng`] on every target.
 pub fn crypto_rng() -> StdRng {
-    StdRng::seed_from_u64(
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64,
-    )
+    os_rng()
+}
+
+/// RNG seeded from the operating system's CSPRNG (`crypto.getRandomValues`
+/// in browsers), for secret keys and proof randomness
+pub fn os_rng() -> StdRng {
+    let mut seed = <StdRng as SeedableRng>::Seed::default();
+    getrandom::getrandom(&mut seed).expect("the OS RNG is available");
+    StdRng::from_seed(seed)
 }
 
+/// Wallet file contents. The secret key and Stellar secret are wiped on
+/// drop and left out of `Debug`.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct WalletData {
     pub secret_key: String,
     pub owner_hash: String,
     pub stellar_secret: String,
     pub notes: Vec<NoteEntry>,
+    #[serde(default)]
+    pub sessions: Vec<crate::session::SessionEntry>,
+    #[serde(default)]
+    pub pending: Vec<crate::pending::PendingTransfer>,
+    /// Submitted deposits and transfers, oldest first; see [`crate::history`]
+    #[serde(default)]
+    pub history: Vec<crate::history::HistoryEntry>,
+    /// Limits `R14Client::transfer` enforces; see [`crate::policy`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<crate::policy::SpendingPolicy>,
+    /// Named recipients, alias → owner; see [`crate::contacts`]
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub contacts: std::collections::BTreeMap<String, String>,
     pub indexer_url: String,
     pub rpc_url: String,
+    /// Preset (`testnet`, `futurenet`, `mainnet`) or any name; see
+    /// [`crate::network::Network::from_wallet`]
+    #[serde(default = "default_network")]
+    pub network: String,
+    /// Passphrase for a network that isn't a preset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_passphrase: Option<String>,
     pub core_contract_id: String,
     pub transfer_contract_id: String,
 }
 
+fn default_network() -> String {
+    crate::network::DEFAULT_NETWORK.to_string()
+}
+
+impl WalletData {
+    /// A wallet for `sk` with no notes, a local indexer and placeholder
+    /// contracts and Stellar key, for tests to adjust field by field
+    pub fn for_test(sk: &SecretKey) -> Self {
+        WalletData {
+            secret_key: fr_to_hex(&sk.0),
+            owner_hash: fr_to_hex(&r14_poseidon::owner_hash(sk).0),
+            stellar_secret: "PLACEHOLDER".into(),
+            notes: vec![],
+            sessions: vec![],
+            pending: vec![],
+            history: vec![],
+            policy: None,
+            contacts: Default::default(),
+            indexer_url: "http://localhost:3000".into(),
+            rpc_url: "https://soroban-testnet.stellar.org:443".into(),
+            network: default_network(),
+            network_passphrase: None,
+            core_contract_id: "PLACEHOLDER".into(),
+            transfer_contract_id: "PLACEHOLDER".into(),
+        }
+    }
+}
+
+impl Drop for WalletData {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+        self.stellar_secret.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for WalletData {}
+
+impl std::fmt::Debug for WalletData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WalletData")
+            .field("secret_key", &"<redacted>")
+            .field("owner_hash", &self.owner_hash)
+            .field("stellar_secret", &"<redacted>")
+            .field("notes", &self.notes.len())
+            .field("sessions", &self.sessions.len())
+            .field("pending", &self.pending.len())
+            .field("history", &self.history.len())
+            .field("policy", &self.policy)
+            .field("contacts", &self.contacts.len())
+            .field("indexer_url", &self.indexer_url)
+            .field("rpc_url", &self.rpc_url)
+            .field("network", &self.network)
+            .field("core_contract_id", &self.core_contract_id)
+            .field("transfer_contract_id", &self.transfer_contract_id)
+            .finish()
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct NoteEntry {
     pub value: u64,
     pub app_tag: u32,
     pub owner: String,
     pub nonce: String,
-    pub commitment: String,
-    pub index: Option<u64>,
+    /// Ledger the note unlocks at; 0 (the default) means no timelock
+    #[serde(default)]
+    pub unlock_after: u64,
+    pub commitment: Commitment,
+    pub index: Option<LeafIndex>,
     pub spent: bool,
+    #[serde(flatten)]
+    pub meta: NoteMeta,
+}
+
+/// How a note entered the wallet
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteSource {
+    Deposit,
+    /// Output paid to someone else, kept as a record of the payment
+    Transfer,
+    /// Output returned to the sender of a transfer
+    Change,
+}
+
+impl std::fmt::Display for NoteSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Deposit => "deposit",
+            Self::Transfer => "transfer",
+            Self::Change => "change",
+        })
+    }
+}
+
+/// Bookkeeping for the user; none of it goes on-chain. Every field is
+/// optional, so wallets written before it existed still load.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NoteMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Unix seconds when the entry was created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<NoteSource>,
+    /// Who is on the other side, e.g. a recipient's owner hash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterparty: Option<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn now_unix() -> Option<u64> {
+    let since_epoch = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_secs())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn now_unix() -> Option<u64> {
+    Some((js_sys::Date::now() / 1000.0) as u64)
+}
+
+impl NoteEntry {
+    /// A not-yet-indexed, unspent entry for `note` with commitment `cm`,
+    /// stamped with the current time
+    pub fn new(note: &Note, cm: &Commitment) -> Self {
+        Self {
+            value: note.value,
+            app_tag: note.app_tag,
+            owner: fr_to_hex(&note.owner),
+            nonce: fr_to_hex(&note.nonce),
+            unlock_after: note.unlock_after,
+            commitment: *cm,
+            index: None,
+            spent: false,
+            meta: NoteMeta {
+                created_at: now_unix(),
+                ..NoteMeta::default()
+            },
+        }
+    }
+
+    pub fn with_source(mut self, source: NoteSource) -> Self {
+        self.meta.source = Some(source);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.meta.label = Some(label.into());
+        self
+    }
+
+    pub fn with_counterparty(mut self, counterparty: impl Into<String>) -> Self {
+        self.meta.counterparty = Some(counterparty.into());
+        self
+    }
+
+    /// The note this entry records
+    pub fn note(&self) -> Result<Note> {
+        let owner = hex_to_fr(&self.owner).context("bad note owner")?;
+        let nonce = hex_to_fr(&self.nonce).context("bad note nonce")?;
+        Ok(Note::with_nonce(self.value, self.app_tag, owner, nonce).locked_until(self.unlock_after))
+    }
 }
 
 pub fn wallet_path() -> Result<PathBuf> {
@@ -71,6 +268,61 @@ pub fn wallet_path() -> Result<PathBuf> {
     Ok(home.join(".r14").join("wallet.json"))
 }
 
+/// Exclusive advisory lock on the wallet, released on drop.
+///
+/// It lives on a sidecar `wallet.json.lock`, since saving replaces
+/// `wallet.json` itself. Advisory means it only excludes other holders:
+/// the CLI takes it for every command that saves, and SDK code sharing the
+/// wallet should too.
+pub struct WalletLock {
+    _file: fs::File,
+}
+
+impl WalletLock {
+    /// Wait for the lock
+    pub fn acquire() -> Result<Self> {
+        let path = lock_path()?;
+        let file = open_lock_file(&path)?;
+        file.lock()
+            .with_context(|| format!("cannot lock {}", path.display()))?;
+        Ok(Self { _file: file })
+    }
+
+    /// `None` if another process holds the lock
+    pub fn try_acquire() -> Result<Option<Self>> {
+        try_lock_at(&lock_path()?)
+    }
+}
+
+fn lock_path() -> Result<PathBuf> {
+    let mut path = wallet_path()?.into_os_string();
+    path.push(".lock");
+    Ok(path.into())
+}
+
+fn open_lock_file(path: &Path) -> Result<fs::File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .with_context(|| format!("cannot open {}", path.display()))
+}
+
+fn try_lock_at(path: &Path) -> Result<Option<WalletLock>> {
+    let file = open_lock_file(path)?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(WalletLock { _file: file })),
+        Err(fs::TryLockError::WouldBlock) => Ok(None),
+        Err(fs::TryLockError::Error(e)) => {
+            Err(e).with_context(|| format!("cannot lock {}", path.display()))
+        }
+    }
+}
+
 pub fn load_wallet() -> Result<WalletData> {
     let path = wallet_path()?;
     let data = fs::read_to_string(&path)
@@ -84,46 +336,54 @@ pub fn save_wallet(wallet: &WalletData) -> Result<()> {
         fs::create_dir_all(parent)?;
     }
     let json = serde_json::to_string_pretty(wallet)?;
-    fs::write(&path, json)?;
-    Ok(())
+    write_atomic(&path, json.as_bytes())
+}
+
+/// Write `bytes` to a sibling temp file, flush it to disk, then rename it
+/// over `path`, so readers see the old contents or the new, never a mix.
+/// On unix the file is owner-only (0600), since wallets and backups hold keys.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp.{}", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    let write = || -> std::io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    };
+    write()
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+        .with_context(|| format!("cannot write {}", path.display()))
 }
 
 pub fn fr_to_hex(fr: &Fr) -> String {
-    let bigint = fr.into_bigint();
-    let bytes = bigint.to_bytes_be();
-    format!("0x{}", hex::encode(bytes))
+    r14_types::hex::encode(fr)
 }
 
 pub fn hex_to_fr(s: &str) -> Result<Fr> {
-    let s = s.strip_prefix("0x").unwrap_or(s);
-    let bytes = hex::decode(s).context("invalid hex")?;
-
-    // 1. Zero-pad BE hex bytes to exactly 32 bytes
-    let mut padded = vec![0u8; 32 - bytes.len().min(32)];
-    padded.extend_from_slice(&bytes[..bytes.len().min(32)]);
-
-    // 2. Convert 32 BE bytes → 4 LE u64 limbs (rchunks iterates low bytes first)
-    let bigint = <Fr as PrimeField>::BigInt::try_from(
-        ark_ff::BigInt::<4>::new({
-            let mut limbs = [0u64; 4];
-            for (i, chunk) in padded.rchunks(8).enumerate() {
-                if i >= 4 { break; }
-                let mut buf = [0u8; 8];
-                let start = 8 - chunk.len();
-                buf[start..].copy_from_slice(chunk);
-                limbs[i] = u64::from_be_bytes(buf);
-            }
-            limbs
-        })
-    ).unwrap();
+    Ok(r14_types::hex::decode(s)?)
+}
+
+/// `r14o1...` address of an owner hash, for handing to payers
+pub fn owner_address(owner: &Fr) -> String {
+    r14_types::address::encode_owner(owner)
+}
 
-    // 3. Construct Fr, rejecting values >= field modulus
-    Fr::from_bigint(bigint).context("value not in field")
+/// Owner hash from an `r14o1...` address or hex
+pub fn parse_owner(s: &str) -> Result<Fr> {
+    r14_types::address::parse_owner(s).with_context(|| format!("bad owner `{s}`"))
 }
 
 /// Fr → raw hex (no 0x prefix, 64 chars). For Soroban BytesN<32>.
 pub fn fr_to_raw_hex(fr: &Fr) -> String {
-    hex::encode(fr.into_bigint().to_bytes_be())
+    strip_0x(&fr_to_hex(fr))
 }
 
 /// Strip leading "0x" prefix if present.
@@ -157,6 +417,80 @@ mod tests {
         assert_eq!(hex_to_fr(&hex_with).unwrap(), hex_to_fr(hex_without).unwrap());
     }
 
+    #[test]
+    fn parse_owner_takes_address_or_hex() {
+        let owner = Fr::from(1234u64);
+        let address = owner_address(&owner);
+        assert_eq!(parse_owner(&address).unwrap(), owner);
+        assert_eq!(parse_owner(&fr_to_hex(&owner)).unwrap(), owner);
+
+        let typo = format!("{}{}{}", &address[..10], if &address[10..11] == "q" { "p" } else { "q" }, &address[11..]);
+        let err = format!("{:#}", parse_owner(&typo).unwrap_err());
+        assert!(err.contains("checksum"), "{err}");
+    }
+
+    #[test]
+    fn note_meta_is_optional() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let note = Note::new(5, 1, Fr::from(2u64), &mut rng);
+        let entry = NoteEntry::new(&note, &crate::commitment(&note))
+            .with_source(NoteSource::Change)
+            .with_label("rent");
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["source"], "change");
+        assert_eq!(json["label"], "rent");
+        assert!(json.get("counterparty").is_none());
+
+        // an entry from before the metadata fields
+        let mut old = json.as_object().unwrap().clone();
+        for key in ["source", "label", "created_at"] {
+            old.remove(key);
+        }
+        let back: NoteEntry = serde_json::from_value(old.into()).unwrap();
+        assert_eq!(back.meta, NoteMeta::default());
+    }
+
+    #[test]
+    fn atomic_write_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.json");
+        write_atomic(&path, b"old").unwrap();
+        write_atomic(&path, b"new").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        // no temp file left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn atomic_write_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.json");
+        write_atomic(&path, b"secret").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn lock_excludes_a_second_holder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wallet.json.lock");
+        let held = try_lock_at(&path).unwrap().expect("lock is free");
+        assert!(try_lock_at(&path).unwrap().is_none());
+        drop(held);
+        assert!(try_lock_at(&path).unwrap().is_some());
+    }
+
+    #[test]
+    fn wallet_debug_hides_secrets() {
+        let mut w = WalletData::for_test(&SecretKey(Fr::from(0x5ec7e7u64)));
+        w.stellar_secret = "SSTELLARSECRET".into();
+        let shown = format!("{w:?}");
+        assert!(!shown.contains("5ec7e7") && !shown.contains("SSTELLARSECRET"), "{shown}");
+        assert!(shown.contains(&w.owner_hash) && shown.contains("PLACEHOLDER"));
+    }
+
     #[test]
     fn hex_to_fr_zero() {
         let fr = hex_to_fr("0x0000000000000000000000000000000000000000000000000000000000000000").unwrap();
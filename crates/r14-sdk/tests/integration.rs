@@ -23,9 +23,9 @@ fn reexported_types_accessible() {
     let cm = r14_sdk::commitment(&note);
 
     // commitment is a field element, should survive hex roundtrip
-    let hex = r14_sdk::wallet::fr_to_hex(&cm);
+    let hex = r14_sdk::wallet::fr_to_hex(&cm.0);
     let recovered = r14_sdk::wallet::hex_to_fr(&hex).unwrap();
-    assert_eq!(cm, recovered);
+    assert_eq!(cm, r14_sdk::Commitment(recovered));
 }
 
 #[test]
@@ -42,31 +42,25 @@ fn reexported_hash2() {
 
 #[test]
 fn merkle_depth_constant() {
-    assert!(r14_sdk::MERKLE_DEPTH > 0);
+    const { assert!(r14_sdk::MERKLE_DEPTH > 0) };
 }
 
 // ── wallet module ──
 
 #[test]
 fn wallet_types_constructible() {
-    let wallet = r14_sdk::wallet::WalletData {
-        secret_key: "0xdead".into(),
-        owner_hash: "0xbeef".into(),
-        stellar_secret: "S_TEST".into(),
-        notes: vec![r14_sdk::wallet::NoteEntry {
-            value: 500,
-            app_tag: 1,
-            owner: "0xaa".into(),
-            nonce: "0xbb".into(),
-            commitment: "0xcc".into(),
-            index: Some(0),
-            spent: false,
-        }],
-        indexer_url: "http://localhost:3000".into(),
-        rpc_url: "https://example.com".into(),
-        core_contract_id: "C_CORE".into(),
-        transfer_contract_id: "C_TRANSFER".into(),
-    };
+    let mut wallet = r14_sdk::wallet::WalletData::for_test(&r14_sdk::SecretKey(ark_bls12_381::Fr::from(0xdeadu64)));
+    wallet.notes = vec![r14_sdk::wallet::NoteEntry {
+        value: 500,
+        app_tag: 1,
+        owner: "0xaa".into(),
+        nonce: "0xbb".into(),
+        unlock_after: 0,
+        commitment: r14_sdk::Commitment(ark_bls12_381::Fr::from(0xccu64)),
+        index: Some(r14_sdk::LeafIndex(0)),
+        spent: false,
+        meta: Default::default(),
+    }];
     assert_eq!(wallet.notes.len(), 1);
     assert_eq!(wallet.notes[0].value, 500);
 }
@@ -97,7 +91,7 @@ fn merkle_empty_root_via_sdk() {
 #[test]
 fn merkle_compute_root_via_sdk() {
     let mut rng = rng();
-    let leaf = ark_bls12_381::Fr::rand(&mut rng);
+    let leaf = r14_sdk::Commitment(ark_bls12_381::Fr::rand(&mut rng));
     let root = r14_sdk::merkle::compute_root_from_leaves(&[leaf]);
     assert_eq!(root.len(), 64);
     assert_ne!(root, r14_sdk::merkle::empty_root_hex());
@@ -133,7 +127,7 @@ fn end_to_end_note_to_root() {
     assert_eq!(root.len(), 64);
 
     // nullifier derivable
-    let nul = r14_sdk::nullifier(&sk, &note_a.nonce);
+    let nul = r14_sdk::nullifier(&sk, &note_a.nonce, r14_sdk::LeafIndex(0));
     let nul_hex = r14_sdk::wallet::fr_to_hex(&nul.0);
     assert!(nul_hex.starts_with("0x"));
 }
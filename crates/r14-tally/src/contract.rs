@@ -0,0 +1,153 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Private voting tally — delegates proof verification to r14-core
+
+use soroban_sdk::crypto::bls12_381::{Fr, G1Affine, G2Affine};
+use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, IntoVal, Symbol, Vec};
+
+/// Groth16 proof (same layout as r14-core::Proof — identical XDR encoding)
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct Proof {
+    pub a: G1Affine,
+    pub b: G2Affine,
+    pub c: G1Affine,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct VoteEvent {
+    pub nullifier: BytesN<32>,
+    pub choice: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum DataKey {
+    CoreContract,
+    CircuitId,
+    ProposalId,
+    VoterRoot,
+    Tally,
+    Nullifier(BytesN<32>),
+}
+
+const PERSISTENT_TTL: u32 = 535_680; // ~30 days
+const PERSISTENT_THRESHOLD: u32 = 267_840; // ~15 days
+
+#[contract]
+pub struct R14Tally;
+
+#[contractimpl]
+impl R14Tally {
+    /// Open a proposal: the vote circuit registered with r14-core, the root
+    /// of the voter tree, and the number of choices the circuit was set up for
+    pub fn init(
+        env: Env,
+        core_contract: Address,
+        circuit_id: BytesN<32>,
+        proposal_id: u64,
+        voter_root: BytesN<32>,
+        num_choices: u32,
+    ) {
+        if env.storage().instance().has(&DataKey::CoreContract) {
+            panic!("already initialized");
+        }
+        if num_choices < 2 {
+            panic!("need at least two choices");
+        }
+        let mut tally: Vec<u32> = Vec::new(&env);
+        for _ in 0..num_choices {
+            tally.push_back(0);
+        }
+        let storage = env.storage().instance();
+        storage.set(&DataKey::CoreContract, &core_contract);
+        storage.set(&DataKey::CircuitId, &circuit_id);
+        storage.set(&DataKey::ProposalId, &proposal_id);
+        storage.set(&DataKey::VoterRoot, &voter_root);
+        storage.set(&DataKey::Tally, &tally);
+        storage.extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+    }
+
+    /// Count one vote. The proof shows the nullifier belongs to a key in
+    /// the voter tree and binds it to `choice`; each nullifier votes once.
+    pub fn vote(env: Env, proof: Proof, nullifier: BytesN<32>, choice: u32) -> bool {
+        let mut tally: Vec<u32> = env
+            .storage()
+            .instance()
+            .get(&DataKey::Tally)
+            .expect("not initialized");
+        let Some(count) = tally.get(choice) else {
+            panic!("invalid choice");
+        };
+        let nf_key = DataKey::Nullifier(nullifier.clone());
+        if env.storage().persistent().has(&nf_key) {
+            panic!("already voted");
+        }
+
+        let storage = env.storage().instance();
+        let core_addr: Address = storage.get(&DataKey::CoreContract).unwrap();
+        let circuit_id: BytesN<32> = storage.get(&DataKey::CircuitId).unwrap();
+        let proposal_id: u64 = storage.get(&DataKey::ProposalId).unwrap();
+        let voter_root: BytesN<32> = storage.get(&DataKey::VoterRoot).unwrap();
+
+        let public_inputs = Vec::from_array(
+            &env,
+            [
+                Fr::from_bytes(voter_root),
+                Self::u64_input(&env, proposal_id),
+                Fr::from_bytes(nullifier.clone()),
+                Self::u64_input(&env, choice as u64),
+            ],
+        );
+        let args: Vec<soroban_sdk::Val> = (circuit_id, proof, public_inputs).into_val(&env);
+        let verified =
+            env.try_invoke_contract::<bool, soroban_sdk::Error>(&core_addr, &Symbol::new(&env, "verify"), args);
+        if !matches!(verified, Ok(Ok(true))) {
+            panic!("proof verification failed");
+        }
+
+        env.storage().persistent().set(&nf_key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&nf_key, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        tally.set(choice, count + 1);
+        storage.set(&DataKey::Tally, &tally);
+        storage.extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+
+        #[allow(deprecated)]
+        env.events().publish(("vote",), VoteEvent { nullifier, choice });
+
+        true
+    }
+
+    /// Votes counted so far, indexed by choice
+    pub fn tally(env: Env) -> Vec<u32> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Tally)
+            .expect("not initialized")
+    }
+
+    /// Check if a vote nullifier has been used
+    pub fn has_voted(env: Env, nullifier: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Nullifier(nullifier))
+    }
+
+    /// Get the voter tree root this proposal checks membership against
+    pub fn voter_root(env: Env) -> BytesN<32> {
+        env.storage()
+            .instance()
+            .get(&DataKey::VoterRoot)
+            .expect("not initialized")
+    }
+
+    fn u64_input(env: &Env, v: u64) -> Fr {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&v.to_be_bytes());
+        Fr::from_bytes(BytesN::from_array(env, &bytes))
+    }
+}
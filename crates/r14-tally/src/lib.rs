@@ -0,0 +1,10 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! r14-tally: Private voting tally using r14-core verifier
+
+#![no_std]
+
+mod contract;
+
+pub use contract::*;
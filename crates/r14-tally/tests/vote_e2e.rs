@@ -0,0 +1,119 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Private voting: r14_circuits::vote::prove → r14-tally vote → tally
+
+mod common;
+
+use common::{build_soroban_proof, deploy_core, hex_to_bytes32};
+use r14_core::R14Core;
+use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_tally::{Proof, R14Tally, R14TallyClient};
+use soroban_sdk::crypto::bls12_381::{G1Affine, G2Affine};
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+// ── Test scenario ──
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_circuits::vote;
+use r14_types::MERKLE_DEPTH;
+
+const PROPOSAL_ID: u64 = 7;
+const NUM_CHOICES: usize = 3;
+
+struct TestScenario {
+    proof: SerializedProof,
+    public_inputs: std::vec::Vec<String>,
+    svk: SerializedVK,
+}
+
+/// One voter votes for choice 2 on `PROPOSAL_ID`
+fn setup_and_prove_vote() -> TestScenario {
+    let mut rng = StdRng::seed_from_u64(42);
+    let sk = Fr::rand(&mut rng);
+    let siblings: std::vec::Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect();
+    let indices: std::vec::Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+
+    let (pk, vk) = vote::setup(NUM_CHOICES, &mut rng);
+    let (proof, pi) = vote::prove(&pk, NUM_CHOICES, sk, siblings, indices, PROPOSAL_ID, 2, &mut rng);
+    assert!(vote::verify_offchain(&vk, &proof, &pi));
+
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
+    let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
+    TestScenario {
+        proof: sp,
+        public_inputs: spi,
+        svk,
+    }
+}
+
+/// Deploy r14-core with the vote VK registered and a tally for `voter_root`
+fn deploy_contracts(env: &Env, svk: &SerializedVK, voter_root: &BytesN<32>) -> Address {
+    let (core_id, circuit_id) = deploy_core(env, svk);
+
+    let tally_id = env.register(R14Tally, ());
+    let tally_client = R14TallyClient::new(env, &tally_id);
+    tally_client.init(&core_id, &circuit_id, &PROPOSAL_ID, voter_root, &(NUM_CHOICES as u32));
+
+    tally_id
+}
+
+// ── Tests ──
+
+#[test]
+fn test_vote_and_tally() {
+    let scenario = setup_and_prove_vote();
+    let env = Env::default();
+
+    let voter_root = hex_to_bytes32(&env, &scenario.public_inputs[0]);
+    let nullifier = hex_to_bytes32(&env, &scenario.public_inputs[2]);
+    let tally_addr = deploy_contracts(&env, &scenario.svk, &voter_root);
+    let client = R14TallyClient::new(&env, &tally_addr);
+    let proof = build_soroban_proof(&env, &scenario.proof);
+
+    // the proof is bound to its choice
+    assert!(client.try_vote(&proof, &nullifier, &0).is_err());
+    assert!(!client.has_voted(&nullifier));
+
+    assert!(client.vote(&proof, &nullifier, &2));
+    assert!(client.has_voted(&nullifier));
+    assert_eq!(client.tally(), Vec::from_array(&env, [0u32, 0, 1]));
+
+    // one vote per nullifier
+    assert!(client.try_vote(&proof, &nullifier, &2).is_err());
+    assert_eq!(client.tally(), Vec::from_array(&env, [0u32, 0, 1]));
+}
+
+#[test]
+fn test_other_voter_tree_rejected() {
+    let scenario = setup_and_prove_vote();
+    let env = Env::default();
+
+    let other_root = BytesN::from_array(&env, &[0x01u8; 32]);
+    let nullifier = hex_to_bytes32(&env, &scenario.public_inputs[2]);
+    let tally_addr = deploy_contracts(&env, &scenario.svk, &other_root);
+    let client = R14TallyClient::new(&env, &tally_addr);
+    let proof = build_soroban_proof(&env, &scenario.proof);
+
+    assert!(client.try_vote(&proof, &nullifier, &2).is_err());
+    assert_eq!(client.tally(), Vec::from_array(&env, [0u32, 0, 0]));
+}
+
+#[test]
+#[should_panic(expected = "invalid choice")]
+fn test_choice_out_of_range() {
+    let env = Env::default();
+    let core_id = env.register(R14Core, ());
+    let client = R14TallyClient::new(&env, &env.register(R14Tally, ()));
+    let id = BytesN::from_array(&env, &[0u8; 32]);
+    client.init(&core_id, &id, &PROPOSAL_ID, &id, &2);
+
+    let proof = Proof {
+        a: G1Affine::from_bytes(BytesN::from_array(&env, &[0u8; 96])),
+        b: G2Affine::from_bytes(BytesN::from_array(&env, &[0u8; 192])),
+        c: G1Affine::from_bytes(BytesN::from_array(&env, &[0u8; 96])),
+    };
+    client.vote(&proof, &id, &2);
+}
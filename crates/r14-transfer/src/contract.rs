@@ -4,7 +4,9 @@
 //! Private transfer contract — delegates proof verification to r14-core
 
 use soroban_sdk::crypto::bls12_381::{Fr, G1Affine, G2Affine};
-use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, IntoVal, Symbol, Vec};
+use soroban_sdk::{
+    contract, contractimpl, contracttype, Address, BytesN, Env, IntoVal, Map, Symbol, Vec,
+};
 
 /// Groth16 proof (same layout as r14-core::Proof — identical XDR encoding)
 #[contracttype]
@@ -29,11 +31,68 @@ pub struct TransferEvent {
     pub cm_1: BytesN<32>,
 }
 
+/// Public key of the auditor for an audited app tag: a Jubjub point, whose
+/// coordinates are BLS12-381 scalars
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditorKey {
+    pub x: BytesN<32>,
+    pub y: BytesN<32>,
+}
+
+/// Both outputs' (value, owner) encrypted to the app tag's auditor; the
+/// audited circuit proves it matches the output commitments
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AuditCiphertext {
+    pub ephemeral_x: BytesN<32>,
+    pub ephemeral_y: BytesN<32>,
+    /// `[value_0, owner_0, value_1, owner_1]`, each plus its pad
+    pub data: Vec<BytesN<32>>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct AuditEvent {
+    pub nullifier: BytesN<32>,
+    pub app_tag: u32,
+    pub audit: AuditCiphertext,
+}
+
+/// One transfer within a `transfer_batch` call, or the spend half of
+/// `transfer_audited`
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct TransferArgs {
+    pub proof: Proof,
+    pub old_root: BytesN<32>,
+    pub nullifier: BytesN<32>,
+    pub cm_0: BytesN<32>,
+    pub cm_1: BytesN<32>,
+    pub current_ledger: u64,
+}
+
+/// One side of a `transfer_swap`: a spend whose proof covers both sides
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SwapLeg {
+    pub old_root: BytesN<32>,
+    pub nullifier: BytesN<32>,
+    pub cm_0: BytesN<32>,
+    pub cm_1: BytesN<32>,
+}
+
 #[contracttype]
 #[derive(Clone)]
 enum DataKey {
+    Admin,
     CoreContract,
     CircuitId,
+    DelegatedCircuitId,
+    AuditCircuitId,
+    SignedCircuitId,
+    SwapCircuitId,
+    Auditor(u32),
     Nullifier(BytesN<32>),
     Root(BytesN<32>),
     RootIndex,
@@ -43,17 +102,28 @@ enum DataKey {
 const PERSISTENT_TTL: u32 = 535_680; // ~30 days
 const PERSISTENT_THRESHOLD: u32 = 267_840; // ~15 days
 const ROOT_HISTORY_SIZE: u32 = 100;
+/// App tags with this bit set must spend through `transfer_audited`
+/// (mirrors `r14_types::AUDITED_APP_TAG`)
+const AUDITED_APP_TAG: u32 = 1 << 31;
 
 #[contract]
 pub struct R14Transfer;
 
 #[contractimpl]
 impl R14Transfer {
-    /// Initialize with core contract address, circuit_id, and empty tree root
-    pub fn init(env: Env, core_contract: Address, circuit_id: BytesN<32>, empty_root: BytesN<32>) {
+    /// Initialize with the admin who may register the optional circuits,
+    /// core contract address, circuit_id, and empty tree root
+    pub fn init(
+        env: Env,
+        admin: Address,
+        core_contract: Address,
+        circuit_id: BytesN<32>,
+        empty_root: BytesN<32>,
+    ) {
         if env.storage().instance().has(&DataKey::CoreContract) {
             panic!("already initialized");
         }
+        env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage()
             .instance()
             .set(&DataKey::CoreContract, &core_contract);
@@ -72,10 +142,148 @@ impl R14Transfer {
             panic!("zero commitment");
         }
         Self::commit_root(&env, new_root);
+        #[allow(deprecated)]
         env.events().publish(("deposit",), DepositEvent { cm });
     }
 
-    /// Verify a private transfer and mark nullifier as spent
+    /// Deposit several commitments under one new root, appended in order.
+    /// Emits the same per-commitment deposit events as `deposit`.
+    pub fn deposit_batch(env: Env, cms: Vec<BytesN<32>>, new_root: BytesN<32>) {
+        if cms.is_empty() {
+            panic!("empty batch");
+        }
+        let zero = BytesN::from_array(&env, &[0u8; 32]);
+        if cms.iter().any(|cm| cm == zero) {
+            panic!("zero commitment");
+        }
+        Self::commit_root(&env, new_root);
+        for cm in cms.iter() {
+            #[allow(deprecated)]
+            env.events().publish(("deposit",), DepositEvent { cm });
+        }
+    }
+
+    /// Register the circuit_id used for session-key (delegated) transfers
+    /// (admin only)
+    pub fn init_delegated(env: Env, circuit_id: BytesN<32>) {
+        Self::admin(env.clone()).require_auth();
+        if env.storage().instance().has(&DataKey::DelegatedCircuitId) {
+            panic!("delegated circuit already set");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::DelegatedCircuitId, &circuit_id);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+    }
+
+    /// Register the circuit_id for transfers authorized by a spend-auth
+    /// signature (hardware wallets) (admin only)
+    pub fn init_signed(env: Env, circuit_id: BytesN<32>) {
+        Self::admin(env.clone()).require_auth();
+        if env.storage().instance().has(&DataKey::SignedCircuitId) {
+            panic!("signed circuit already set");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SignedCircuitId, &circuit_id);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+    }
+
+    /// Register the circuit_id used for two-asset swaps (admin only)
+    pub fn init_swap(env: Env, circuit_id: BytesN<32>) {
+        Self::admin(env.clone()).require_auth();
+        if env.storage().instance().has(&DataKey::SwapCircuitId) {
+            panic!("swap circuit already set");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SwapCircuitId, &circuit_id);
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+    }
+
+    /// Register the audited transfer circuit and each audited app tag's
+    /// auditor key (admin only). One-shot: auditors can't be swapped
+    /// afterwards.
+    pub fn init_audit(env: Env, circuit_id: BytesN<32>, auditors: Map<u32, AuditorKey>) {
+        Self::admin(env.clone()).require_auth();
+        if env.storage().instance().has(&DataKey::AuditCircuitId) {
+            panic!("audit circuit already set");
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::AuditCircuitId, &circuit_id);
+        for (app_tag, key) in auditors.iter() {
+            if app_tag & AUDITED_APP_TAG == 0 {
+                panic!("app_tag is not audited");
+            }
+            env.storage()
+                .instance()
+                .set(&DataKey::Auditor(app_tag), &key);
+        }
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+    }
+
+    /// Admin set at init, who registers the optional circuits
+    pub fn admin(env: Env) -> Address {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .expect("not initialized")
+    }
+
+    /// Auditor key registered for `app_tag`, if any
+    pub fn auditor(env: Env, app_tag: u32) -> Option<AuditorKey> {
+        env.storage().instance().get(&DataKey::Auditor(app_tag))
+    }
+
+    /// Circuit_id registered at init (read-only, used by deployment checks)
+    pub fn circuit_id(env: Env) -> BytesN<32> {
+        env.storage()
+            .instance()
+            .get(&DataKey::CircuitId)
+            .expect("not initialized")
+    }
+
+    /// True if `nullifier` has already been consumed
+    pub fn is_spent(env: Env, nullifier: BytesN<32>) -> bool {
+        env.storage()
+            .persistent()
+            .has(&DataKey::Nullifier(nullifier))
+    }
+
+    /// True if `root` is still in the recent-root history
+    pub fn is_known_root(env: Env, root: BytesN<32>) -> bool {
+        env.storage().persistent().has(&DataKey::Root(root))
+    }
+
+    /// Most recently committed merkle root
+    pub fn latest_root(env: Env) -> BytesN<32> {
+        let next: u32 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::RootIndex)
+            .expect("not initialized");
+        let idx = (next + ROOT_HISTORY_SIZE - 1) % ROOT_HISTORY_SIZE;
+        env.storage()
+            .persistent()
+            .get(&DataKey::RootAt(idx))
+            .expect("not initialized")
+    }
+
+    /// Verify a private transfer and mark nullifier as spent.
+    ///
+    /// `current_ledger` is the ledger the proof was built for, checked by the
+    /// circuit against the consumed note's timelock. It may trail the real
+    /// sequence (proving takes time) but not run ahead of it.
+    #[allow(clippy::too_many_arguments)]
     pub fn transfer(
         env: Env,
         proof: Proof,
@@ -83,71 +291,374 @@ impl R14Transfer {
         nullifier: BytesN<32>,
         cm_0: BytesN<32>,
         cm_1: BytesN<32>,
+        current_ledger: u64,
         new_root: BytesN<32>,
     ) -> bool {
-        // Validate old_root is known
-        if !env
-            .storage()
-            .persistent()
-            .has(&DataKey::Root(old_root.clone()))
-        {
-            panic!("unknown merkle root");
+        Self::check_ledger(&env, current_ledger);
+        let public_inputs =
+            Self::public_inputs(&env, &old_root, &nullifier, &cm_0, &cm_1, current_ledger);
+        Self::spend(
+            &env,
+            DataKey::CircuitId,
+            proof,
+            public_inputs,
+            old_root,
+            nullifier,
+            cm_0,
+            cm_1,
+            new_root,
+        )
+    }
+
+    /// Verify a transfer whose note is owned by a spend-auth key. Same
+    /// public inputs as `transfer`; only the circuit differs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_signed(
+        env: Env,
+        proof: Proof,
+        old_root: BytesN<32>,
+        nullifier: BytesN<32>,
+        cm_0: BytesN<32>,
+        cm_1: BytesN<32>,
+        current_ledger: u64,
+        new_root: BytesN<32>,
+    ) -> bool {
+        Self::check_ledger(&env, current_ledger);
+        let public_inputs =
+            Self::public_inputs(&env, &old_root, &nullifier, &cm_0, &cm_1, current_ledger);
+        Self::spend(
+            &env,
+            DataKey::SignedCircuitId,
+            proof,
+            public_inputs,
+            old_root,
+            nullifier,
+            cm_0,
+            cm_1,
+            new_root,
+        )
+    }
+
+    /// Verify a transfer of an audited app tag. The proof shows `audit`
+    /// encrypts both outputs to the auditor registered for `app_tag`, and
+    /// the ciphertext is published for that auditor to pick up.
+    pub fn transfer_audited(
+        env: Env,
+        transfer: TransferArgs,
+        app_tag: u32,
+        audit: AuditCiphertext,
+        new_root: BytesN<32>,
+    ) -> bool {
+        let t = transfer;
+        Self::check_ledger(&env, t.current_ledger);
+        if audit.data.len() != 4 {
+            panic!("malformed audit ciphertext");
         }
+        let auditor = Self::auditor(env.clone(), app_tag).expect("no auditor for app_tag");
 
-        // Check nullifier not already spent
-        let nf_key = DataKey::Nullifier(nullifier.clone());
-        if env.storage().persistent().has(&nf_key) {
-            panic!("nullifier already spent");
+        let mut public_inputs =
+            Self::public_inputs(&env, &t.old_root, &t.nullifier, &t.cm_0, &t.cm_1, t.current_ledger);
+        public_inputs.push_back(Self::u64_input(&env, app_tag as u64));
+        public_inputs.push_back(Fr::from_bytes(auditor.x));
+        public_inputs.push_back(Fr::from_bytes(auditor.y));
+        public_inputs.push_back(Fr::from_bytes(audit.ephemeral_x.clone()));
+        public_inputs.push_back(Fr::from_bytes(audit.ephemeral_y.clone()));
+        for c in audit.data.iter() {
+            public_inputs.push_back(Fr::from_bytes(c));
         }
 
-        // Build public inputs
-        let old_root_fr = Fr::from_bytes(old_root);
-        let nullifier_fr = Fr::from_bytes(nullifier.clone());
-        let cm_0_fr = Fr::from_bytes(cm_0.clone());
-        let cm_1_fr = Fr::from_bytes(cm_1.clone());
+        Self::spend(
+            &env,
+            DataKey::AuditCircuitId,
+            t.proof,
+            public_inputs,
+            t.old_root,
+            t.nullifier.clone(),
+            t.cm_0,
+            t.cm_1,
+            new_root,
+        );
+        #[allow(deprecated)]
+        env.events().publish(
+            ("audit", app_tag),
+            AuditEvent {
+                nullifier: t.nullifier,
+                app_tag,
+                audit,
+            },
+        );
+        true
+    }
+
+    /// Verify a transfer spent by a session key; rejected once the ledger
+    /// sequence passes `valid_until`
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_delegated(
+        env: Env,
+        proof: Proof,
+        old_root: BytesN<32>,
+        nullifier: BytesN<32>,
+        cm_0: BytesN<32>,
+        cm_1: BytesN<32>,
+        valid_until: u64,
+        new_root: BytesN<32>,
+    ) -> bool {
+        if env.ledger().sequence() as u64 > valid_until {
+            panic!("session expired");
+        }
+        let public_inputs =
+            Self::public_inputs(&env, &old_root, &nullifier, &cm_0, &cm_1, valid_until);
+        Self::spend(
+            &env,
+            DataKey::DelegatedCircuitId,
+            proof,
+            public_inputs,
+            old_root,
+            nullifier,
+            cm_0,
+            cm_1,
+            new_root,
+        )
+    }
 
-        let public_inputs: Vec<Fr> =
-            Vec::from_array(&env, [old_root_fr, nullifier_fr, cm_0_fr, cm_1_fr]);
+    /// Verify a swap of two notes in different assets and spend both
+    /// nullifiers, or neither. `legs` holds exactly two spends, in the order
+    /// the proof was built for; both were proven at `current_ledger`.
+    pub fn transfer_swap(
+        env: Env,
+        proof: Proof,
+        legs: Vec<SwapLeg>,
+        current_ledger: u64,
+        new_root: BytesN<32>,
+    ) -> bool {
+        if legs.len() != 2 {
+            panic!("swap needs two legs");
+        }
+        Self::check_ledger(&env, current_ledger);
+        let (a, b) = (legs.get_unchecked(0), legs.get_unchecked(1));
+        if a.nullifier == b.nullifier {
+            panic!("duplicate nullifier in swap");
+        }
+
+        let mut public_inputs: Vec<Fr> = Vec::new(&env);
+        for leg in legs.iter() {
+            Self::check_spendable(&env, &leg.old_root, &leg.nullifier);
+            public_inputs.append(&Self::public_inputs(
+                &env,
+                &leg.old_root,
+                &leg.nullifier,
+                &leg.cm_0,
+                &leg.cm_1,
+                current_ledger,
+            ));
+        }
 
-        // Cross-contract call to r14-core via env.invoke_contract
-        let core_addr: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::CoreContract)
-            .expect("not initialized");
         let circuit_id: BytesN<32> = env
             .storage()
             .instance()
-            .get(&DataKey::CircuitId)
+            .get(&DataKey::SwapCircuitId)
             .expect("not initialized");
-
         let args: Vec<soroban_sdk::Val> = (circuit_id, proof, public_inputs).into_val(&env);
-        let verified: bool =
-            env.invoke_contract(&core_addr, &Symbol::new(&env, "verify"), args);
+        Self::core_verify(&env, "verify", args);
 
-        if !verified {
-            panic!("proof verification failed");
+        for leg in legs.iter() {
+            Self::mark_spent(&env, &leg.nullifier);
+        }
+        env.storage()
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        Self::commit_root(&env, new_root);
+
+        for leg in legs.iter() {
+            #[allow(deprecated)]
+            env.events().publish(
+                ("transfer",),
+                TransferEvent {
+                    nullifier: leg.nullifier,
+                    cm_0: leg.cm_0,
+                    cm_1: leg.cm_1,
+                },
+            );
         }
 
-        // Mark nullifier as spent
-        env.storage().persistent().set(&nf_key, &true);
+        true
+    }
+
+    /// Verify several transfers with one cross-contract call and commit a
+    /// single root covering all their outputs (for relayers)
+    pub fn transfer_batch(env: Env, transfers: Vec<TransferArgs>, new_root: BytesN<32>) -> bool {
+        if transfers.is_empty() {
+            panic!("empty batch");
+        }
+
+        let mut proofs: Vec<Proof> = Vec::new(&env);
+        let mut inputs: Vec<Vec<Fr>> = Vec::new(&env);
+        let mut seen: Map<BytesN<32>, bool> = Map::new(&env);
+        for t in transfers.iter() {
+            Self::check_ledger(&env, t.current_ledger);
+            Self::check_spendable(&env, &t.old_root, &t.nullifier);
+            if seen.contains_key(t.nullifier.clone()) {
+                panic!("duplicate nullifier in batch");
+            }
+            seen.set(t.nullifier.clone(), true);
+            proofs.push_back(t.proof.clone());
+            inputs.push_back(Self::public_inputs(
+                &env,
+                &t.old_root,
+                &t.nullifier,
+                &t.cm_0,
+                &t.cm_1,
+                t.current_ledger,
+            ));
+        }
+
+        let circuit_id = Self::circuit_id(env.clone());
+        let args: Vec<soroban_sdk::Val> = (circuit_id, proofs, inputs).into_val(&env);
+        Self::core_verify(&env, "verify_batch", args);
+
+        for t in transfers.iter() {
+            Self::mark_spent(&env, &t.nullifier);
+        }
         env.storage()
-            .persistent()
-            .extend_ttl(&nf_key, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+            .instance()
+            .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+        Self::commit_root(&env, new_root);
+
+        for t in transfers.iter() {
+            #[allow(deprecated)]
+            env.events().publish(
+                ("transfer",),
+                TransferEvent {
+                    nullifier: t.nullifier,
+                    cm_0: t.cm_0,
+                    cm_1: t.cm_1,
+                },
+            );
+        }
+
+        true
+    }
+
+    /// Shared spend path: root + nullifier checks, cross-contract verify, state update
+    #[allow(clippy::too_many_arguments)]
+    fn spend(
+        env: &Env,
+        circuit_key: DataKey,
+        proof: Proof,
+        public_inputs: Vec<Fr>,
+        old_root: BytesN<32>,
+        nullifier: BytesN<32>,
+        cm_0: BytesN<32>,
+        cm_1: BytesN<32>,
+        new_root: BytesN<32>,
+    ) -> bool {
+        Self::check_spendable(env, &old_root, &nullifier);
+
+        let circuit_id: BytesN<32> = env
+            .storage()
+            .instance()
+            .get(&circuit_key)
+            .expect("not initialized");
+        let args: Vec<soroban_sdk::Val> = (circuit_id, proof, public_inputs).into_val(env);
+        Self::core_verify(env, "verify", args);
+
+        Self::mark_spent(env, &nullifier);
         env.storage()
             .instance()
             .extend_ttl(PERSISTENT_THRESHOLD, PERSISTENT_TTL);
 
         // Store new merkle root
-        Self::commit_root(&env, new_root);
+        Self::commit_root(env, new_root);
 
         // Emit event
+        #[allow(deprecated)]
         env.events()
             .publish(("transfer",), TransferEvent { nullifier, cm_0, cm_1 });
 
         true
     }
 
+    /// Reject a `current_ledger` ahead of the real sequence, which would
+    /// unlock timelocked notes early
+    fn check_ledger(env: &Env, current_ledger: u64) {
+        if current_ledger > env.ledger().sequence() as u64 {
+            panic!("current_ledger is in the future");
+        }
+    }
+
+    /// Reject unknown roots and already-spent nullifiers
+    fn check_spendable(env: &Env, old_root: &BytesN<32>, nullifier: &BytesN<32>) {
+        if !env
+            .storage()
+            .persistent()
+            .has(&DataKey::Root(old_root.clone()))
+        {
+            panic!("unknown merkle root");
+        }
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Nullifier(nullifier.clone()))
+        {
+            panic!("nullifier already spent");
+        }
+    }
+
+    /// Public inputs in circuit order. The last one is a ledger:
+    /// `current_ledger` for transfers, `valid_until` for delegated spends.
+    fn public_inputs(
+        env: &Env,
+        old_root: &BytesN<32>,
+        nullifier: &BytesN<32>,
+        cm_0: &BytesN<32>,
+        cm_1: &BytesN<32>,
+        ledger: u64,
+    ) -> Vec<Fr> {
+        Vec::from_array(
+            env,
+            [
+                Fr::from_bytes(old_root.clone()),
+                Fr::from_bytes(nullifier.clone()),
+                Fr::from_bytes(cm_0.clone()),
+                Fr::from_bytes(cm_1.clone()),
+                Self::u64_input(env, ledger),
+            ],
+        )
+    }
+
+    /// A u64 as a field element public input
+    fn u64_input(env: &Env, v: u64) -> Fr {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&v.to_be_bytes());
+        Fr::from_bytes(BytesN::from_array(env, &bytes))
+    }
+
+    /// Cross-contract call to r14-core; any error or `false` aborts the spend.
+    /// r14-core returns a contract error (e.g. InvalidProof) on rejection.
+    fn core_verify(env: &Env, function: &str, args: Vec<soroban_sdk::Val>) {
+        let core_addr: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::CoreContract)
+            .expect("not initialized");
+        let verified = env.try_invoke_contract::<bool, soroban_sdk::Error>(
+            &core_addr,
+            &Symbol::new(env, function),
+            args,
+        );
+        if !matches!(verified, Ok(Ok(true))) {
+            panic!("proof verification failed");
+        }
+    }
+
+    fn mark_spent(env: &Env, nullifier: &BytesN<32>) {
+        let nf_key = DataKey::Nullifier(nullifier.clone());
+        env.storage().persistent().set(&nf_key, &true);
+        env.storage()
+            .persistent()
+            .extend_ttl(&nf_key, PERSISTENT_THRESHOLD, PERSISTENT_TTL);
+    }
+
     /// Store a root in the circular buffer
     fn commit_root(env: &Env, root: BytesN<32>) {
         let idx: u32 = env
@@ -0,0 +1,42 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Only the admin set at init registers the optional circuits
+
+use r14_transfer::{AuditorKey, R14Transfer, R14TransferClient};
+use soroban_sdk::testutils::{Address as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::{Address, BytesN, Env, IntoVal, Map};
+
+#[test]
+fn test_only_the_admin_registers_circuits() {
+    let env = Env::default();
+    let (admin, imposter) = (Address::generate(&env), Address::generate(&env));
+    let transfer_id = env.register(R14Transfer, ());
+    let client = R14TransferClient::new(&env, &transfer_id);
+    let id = BytesN::from_array(&env, &[0x14u8; 32]);
+    client.init(&admin, &Address::generate(&env), &id, &BytesN::from_array(&env, &[0xEEu8; 32]));
+    assert_eq!(client.admin(), admin);
+
+    // signed by someone else, every init_* fails
+    let signed_by = |address: &Address, fn_name: &'static str, args: soroban_sdk::Vec<soroban_sdk::Val>| {
+        env.mock_auths(&[MockAuth {
+            address,
+            invoke: &MockAuthInvoke { contract: &transfer_id, fn_name, args, sub_invokes: &[] },
+        }]);
+    };
+    let auditors: Map<u32, AuditorKey> = Map::new(&env);
+    signed_by(&imposter, "init_delegated", (&id,).into_val(&env));
+    assert!(client.try_init_delegated(&id).is_err());
+    signed_by(&imposter, "init_signed", (&id,).into_val(&env));
+    assert!(client.try_init_signed(&id).is_err());
+    signed_by(&imposter, "init_swap", (&id,).into_val(&env));
+    assert!(client.try_init_swap(&id).is_err());
+    signed_by(&imposter, "init_audit", (&id, &auditors).into_val(&env));
+    assert!(client.try_init_audit(&id, &auditors).is_err());
+
+    // and the admin can still set them afterwards
+    signed_by(&admin, "init_signed", (&id,).into_val(&env));
+    client.init_signed(&id);
+    signed_by(&admin, "init_audit", (&id, &auditors).into_val(&env));
+    client.init_audit(&id, &auditors);
+}
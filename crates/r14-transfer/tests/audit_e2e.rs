@@ -0,0 +1,145 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Audited transfers: off-chain prove_audited → transfer_audited
+
+mod common;
+
+use common::{build_soroban_proof, deploy_contracts, hex_to_bytes32};
+use r14_core::R14Core;
+use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_transfer::{AuditCiphertext, AuditorKey, R14Transfer, R14TransferClient, TransferArgs};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env, Map, Vec};
+
+// ── Test scenario ──
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_circuit::AuditorSecretKey;
+use r14_sdk::wallet::fr_to_raw_hex;
+use r14_types::{MerklePath, Note, SecretKey, AUDITED_APP_TAG, MERKLE_DEPTH};
+
+const APP_TAG: u32 = 1 | AUDITED_APP_TAG;
+
+struct TestScenario {
+    proof: SerializedProof,
+    public_inputs: std::vec::Vec<String>,
+    svk: SerializedVK,
+    auditor: AuditorSecretKey,
+}
+
+fn setup_and_prove_audited() -> TestScenario {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let sk = SecretKey::random(&mut rng);
+    let owner = r14_poseidon::owner_hash(&sk);
+    let consumed = Note::new(1000, APP_TAG, owner.0, &mut rng);
+    let siblings: std::vec::Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect();
+    let indices: std::vec::Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+    let path = MerklePath { siblings, indices };
+
+    let recipient = Fr::rand(&mut rng);
+    let note_0 = Note::new(300, APP_TAG, recipient, &mut rng);
+    let note_1 = Note::new(700, APP_TAG, owner.0, &mut rng);
+
+    let auditor = AuditorSecretKey::random(&mut rng);
+    let (pk, vk) = r14_circuit::setup_audited(&mut rng).unwrap();
+    let (proof, pi) = r14_circuit::prove_audited(
+        &pk,
+        &auditor.public_key(),
+        sk.0,
+        consumed,
+        path,
+        [note_0, note_1],
+        0,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(r14_circuit::verify_audited_offchain(&vk, &proof, &pi));
+
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
+    let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
+    TestScenario {
+        proof: sp,
+        public_inputs: spi,
+        svk,
+        auditor,
+    }
+}
+
+fn auditor_key(env: &Env, auditor: &AuditorSecretKey) -> AuditorKey {
+    let [x, y] = auditor.public_key().to_fields();
+    AuditorKey {
+        x: hex_to_bytes32(env, &fr_to_raw_hex(&x)),
+        y: hex_to_bytes32(env, &fr_to_raw_hex(&y)),
+    }
+}
+
+fn submit(env: &Env, scenario: &TestScenario, auditor: AuditorKey) -> bool {
+    let pi = &scenario.public_inputs;
+    let old_root = hex_to_bytes32(env, &pi[0]);
+    let (transfer_addr, circuit_id) = deploy_contracts(env, &scenario.svk, std::slice::from_ref(&old_root));
+    let client = R14TransferClient::new(env, &transfer_addr);
+    let mut auditors = Map::new(env);
+    auditors.set(APP_TAG, auditor);
+    client.init_audit(&circuit_id, &auditors);
+
+    let transfer = TransferArgs {
+        proof: build_soroban_proof(env, &scenario.proof),
+        old_root,
+        nullifier: hex_to_bytes32(env, &pi[1]),
+        cm_0: hex_to_bytes32(env, &pi[2]),
+        cm_1: hex_to_bytes32(env, &pi[3]),
+        current_ledger: 0,
+    };
+    let mut data = Vec::new(env);
+    for c in &pi[10..14] {
+        data.push_back(hex_to_bytes32(env, c));
+    }
+    let audit = AuditCiphertext {
+        ephemeral_x: hex_to_bytes32(env, &pi[8]),
+        ephemeral_y: hex_to_bytes32(env, &pi[9]),
+        data,
+    };
+    let new_root = BytesN::from_array(env, &[0xAAu8; 32]);
+
+    client.transfer_audited(&transfer, &APP_TAG, &audit, &new_root)
+}
+
+// ── Tests ──
+
+#[test]
+fn test_audited_transfer() {
+    let scenario = setup_and_prove_audited();
+    let env = Env::default();
+    let auditor = auditor_key(&env, &scenario.auditor);
+    assert!(submit(&env, &scenario, auditor));
+}
+
+#[test]
+#[should_panic(expected = "proof verification failed")]
+fn test_wrong_auditor_rejected() {
+    let scenario = setup_and_prove_audited();
+    let env = Env::default();
+    let other = AuditorSecretKey::random(&mut StdRng::seed_from_u64(7));
+    submit(&env, &scenario, auditor_key(&env, &other));
+}
+
+#[test]
+#[should_panic(expected = "app_tag is not audited")]
+fn test_unaudited_tag_rejected_at_init() {
+    let env = Env::default();
+    let core_id = env.register(R14Core, ());
+    let transfer_id = env.register(R14Transfer, ());
+    let client = R14TransferClient::new(&env, &transfer_id);
+    let zero = BytesN::from_array(&env, &[0u8; 32]);
+    client.init(&Address::generate(&env), &core_id, &zero, &zero);
+    env.mock_all_auths();
+
+    let auditor = auditor_key(&env, &AuditorSecretKey::random(&mut StdRng::seed_from_u64(7)));
+    let mut auditors = Map::new(&env);
+    auditors.set(1u32, auditor);
+    client.init_audit(&zero, &auditors);
+}
@@ -0,0 +1,136 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Relayer batches: several off-chain proofs → one transfer_batch call
+
+mod common;
+
+use common::{build_soroban_proof, deploy_contracts, hex_to_bytes32};
+use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_transfer::{R14TransferClient, TransferArgs};
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+// ── Test scenario: two transfers under one VK ──
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_types::{MerklePath, Note, SecretKey, MERKLE_DEPTH};
+
+fn build_dummy_merkle_path(rng: &mut impl ark_std::rand::RngCore) -> MerklePath {
+    let siblings: std::vec::Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(rng)).collect();
+    let indices: std::vec::Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+    MerklePath { siblings, indices }
+}
+
+struct BatchScenario {
+    svk: SerializedVK,
+    /// (proof, [old_root, nullifier, cm_0, cm_1])
+    transfers: std::vec::Vec<(SerializedProof, std::vec::Vec<String>)>,
+}
+
+fn setup_and_prove_batch(n: usize) -> BatchScenario {
+    let mut rng = StdRng::seed_from_u64(42);
+    let (pk, vk) = r14_circuit::setup(&mut rng).unwrap();
+
+    let transfers = (0..n)
+        .map(|_| {
+            let sk = SecretKey::random(&mut rng);
+            let owner = r14_poseidon::owner_hash(&sk);
+            let consumed = Note::new(1000, 1, owner.0, &mut rng);
+            let path = build_dummy_merkle_path(&mut rng);
+            let recipient = r14_poseidon::owner_hash(&SecretKey::random(&mut rng));
+            let note_0 = Note::new(600, 1, recipient.0, &mut rng);
+            let note_1 = Note::new(400, 1, owner.0, &mut rng);
+
+            let (proof, pi) =
+                r14_circuit::prove(&pk, sk.0, consumed, path, [note_0, note_1], 0, &mut rng).unwrap();
+            assert!(r14_circuit::verify_offchain(&vk, &proof, &pi));
+            serialize_proof_for_soroban(&proof, &pi.to_vec())
+        })
+        .collect();
+
+    BatchScenario {
+        svk: serialize_vk_for_soroban(&vk, MERKLE_DEPTH),
+        transfers,
+    }
+}
+
+/// Deploy both contracts and seed every old_root into the root history
+fn deploy(env: &Env, scenario: &BatchScenario) -> Address {
+    let roots: std::vec::Vec<_> = scenario.transfers.iter().map(|(_, pi)| hex_to_bytes32(env, &pi[0])).collect();
+    deploy_contracts(env, &scenario.svk, &roots).0
+}
+
+fn build_args(env: &Env, scenario: &BatchScenario) -> Vec<TransferArgs> {
+    let mut args = Vec::new(env);
+    for (sp, pi) in &scenario.transfers {
+        args.push_back(TransferArgs {
+            proof: build_soroban_proof(env, sp),
+            old_root: hex_to_bytes32(env, &pi[0]),
+            nullifier: hex_to_bytes32(env, &pi[1]),
+            cm_0: hex_to_bytes32(env, &pi[2]),
+            cm_1: hex_to_bytes32(env, &pi[3]),
+            current_ledger: 0,
+        });
+    }
+    args
+}
+
+// ── Tests ──
+
+#[test]
+fn test_transfer_batch() {
+    let scenario = setup_and_prove_batch(2);
+    let env = Env::default();
+    let client = R14TransferClient::new(&env, &deploy(&env, &scenario));
+
+    let args = build_args(&env, &scenario);
+    let new_root = BytesN::from_array(&env, &[0xAAu8; 32]);
+    assert!(client.transfer_batch(&args, &new_root));
+
+    for t in args.iter() {
+        assert!(client.is_spent(&t.nullifier));
+    }
+    assert_eq!(client.latest_root(), new_root);
+}
+
+#[test]
+#[should_panic(expected = "duplicate nullifier in batch")]
+fn test_batch_duplicate_nullifier_rejected() {
+    let scenario = setup_and_prove_batch(1);
+    let env = Env::default();
+    let client = R14TransferClient::new(&env, &deploy(&env, &scenario));
+
+    let mut args = build_args(&env, &scenario);
+    args.push_back(args.get(0).unwrap());
+    client.transfer_batch(&args, &BytesN::from_array(&env, &[0xAAu8; 32]));
+}
+
+#[test]
+fn test_batch_with_invalid_proof_spends_nothing() {
+    let scenario = setup_and_prove_batch(2);
+    let env = Env::default();
+    let client = R14TransferClient::new(&env, &deploy(&env, &scenario));
+
+    // Swap the second transfer's proof for the first's
+    let mut args = build_args(&env, &scenario);
+    let mut bad = args.get(1).unwrap();
+    bad.proof = args.get(0).unwrap().proof;
+    args.set(1, bad);
+
+    let res = client.try_transfer_batch(&args, &BytesN::from_array(&env, &[0xAAu8; 32]));
+    assert!(res.is_err());
+    for t in args.iter() {
+        assert!(!client.is_spent(&t.nullifier));
+    }
+}
+
+#[test]
+#[should_panic(expected = "empty batch")]
+fn test_empty_batch_rejected() {
+    let scenario = setup_and_prove_batch(1);
+    let env = Env::default();
+    let client = R14TransferClient::new(&env, &deploy(&env, &scenario));
+    client.transfer_batch(&Vec::new(&env), &BytesN::from_array(&env, &[0xAAu8; 32]));
+}
@@ -0,0 +1,87 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Scaffolding shared by the e2e tests: hex decoding, Soroban type
+//! builders, and contract deployment
+
+#![allow(dead_code)]
+
+use r14_core::{R14Core, R14CoreClient, VerificationKey};
+use r14_sdk::serialize::{SerializedProof, SerializedVK};
+use r14_transfer::{Proof, R14Transfer, R14TransferClient};
+use soroban_sdk::crypto::bls12_381::{G1Affine, G2Affine};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+// ── Hex helpers ──
+
+pub fn hex_to_g1(env: &Env, h: &str) -> G1Affine {
+    let bytes: [u8; 96] = hex::decode(h).unwrap().try_into().unwrap();
+    G1Affine::from_bytes(BytesN::from_array(env, &bytes))
+}
+
+pub fn hex_to_g2(env: &Env, h: &str) -> G2Affine {
+    let bytes: [u8; 192] = hex::decode(h).unwrap().try_into().unwrap();
+    G2Affine::from_bytes(BytesN::from_array(env, &bytes))
+}
+
+pub fn hex_to_bytes32(env: &Env, h: &str) -> BytesN<32> {
+    let bytes: [u8; 32] = hex::decode(h).unwrap().try_into().unwrap();
+    BytesN::from_array(env, &bytes)
+}
+
+// ── Soroban type builders (unified IC) ──
+
+pub fn build_soroban_vk(env: &Env, svk: &SerializedVK) -> VerificationKey {
+    let mut ic = Vec::new(env);
+    for ic_hex in &svk.ic {
+        ic.push_back(hex_to_g1(env, ic_hex));
+    }
+    VerificationKey {
+        alpha_g1: hex_to_g1(env, &svk.alpha_g1),
+        beta_g2: hex_to_g2(env, &svk.beta_g2),
+        gamma_g2: hex_to_g2(env, &svk.gamma_g2),
+        delta_g2: hex_to_g2(env, &svk.delta_g2),
+        ic,
+        depth: svk.depth,
+    }
+}
+
+pub fn build_soroban_proof(env: &Env, sp: &SerializedProof) -> Proof {
+    Proof {
+        a: hex_to_g1(env, &sp.a),
+        b: hex_to_g2(env, &sp.b),
+        c: hex_to_g1(env, &sp.c),
+    }
+}
+
+// ── Deployment ──
+
+/// Deploy r14-core with `svk` registered and r14-transfer on top of it,
+/// with each of `roots` seeded into the known roots by a dummy deposit.
+///
+/// `svk` is also set as the regular transfer circuit, so a test of another
+/// entry point registers the returned circuit id for it with `init_*`.
+/// All auths are mocked.
+pub fn deploy_contracts(env: &Env, svk: &SerializedVK, roots: &[BytesN<32>]) -> (Address, BytesN<32>) {
+    let admin = Address::generate(env);
+
+    let core_id = env.register(R14Core, ());
+    let core_client = R14CoreClient::new(env, &core_id);
+    core_client.init(&admin);
+
+    let vk = build_soroban_vk(env, svk);
+    env.mock_all_auths();
+    let circuit_id = core_client.register(&admin, &vk);
+
+    let transfer_id = env.register(R14Transfer, ());
+    let transfer_client = R14TransferClient::new(env, &transfer_id);
+    transfer_client.init(&admin, &core_id, &circuit_id, &BytesN::from_array(env, &[0xEEu8; 32]));
+
+    for (i, root) in roots.iter().enumerate() {
+        let dummy_cm = BytesN::from_array(env, &[i as u8 + 1; 32]);
+        transfer_client.deposit(&dummy_cm, root);
+    }
+
+    (transfer_id, circuit_id)
+}
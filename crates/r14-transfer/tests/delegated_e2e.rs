@@ -0,0 +1,103 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Session-key transfers: off-chain delegated prove → transfer_delegated
+
+mod common;
+
+use common::{build_soroban_proof, deploy_contracts, hex_to_bytes32};
+use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_transfer::R14TransferClient;
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::{BytesN, Env};
+
+// ── Test scenario ──
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_types::{MerklePath, Note, SecretKey, SessionPolicy, MERKLE_DEPTH};
+
+const EXPIRY: u64 = 1_000;
+
+struct TestScenario {
+    proof: SerializedProof,
+    public_inputs: std::vec::Vec<String>,
+    svk: SerializedVK,
+}
+
+fn setup_and_prove_delegated() -> TestScenario {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let sk = SecretKey::random(&mut rng);
+    let session_key = r14_poseidon::session_key(&sk, 0);
+    let policy = SessionPolicy::new(500, 1, EXPIRY);
+    let refund_owner = r14_poseidon::owner_hash(&sk);
+    let session_owner = r14_poseidon::session_owner(&session_key, &policy, &refund_owner);
+
+    let consumed = Note::new(1000, 1, session_owner.0, &mut rng);
+    let siblings: std::vec::Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect();
+    let indices: std::vec::Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+    let path = MerklePath { siblings, indices };
+
+    let recipient = Fr::rand(&mut rng);
+    let note_0 = Note::new(300, 1, recipient, &mut rng);
+    let note_1 = Note::new(700, 1, session_owner.0, &mut rng);
+
+    let (pk, vk) = r14_circuit::setup_delegated(&mut rng).unwrap();
+    let (proof, pi) = r14_circuit::prove_delegated(
+        &pk,
+        &session_key,
+        &policy,
+        &refund_owner,
+        consumed,
+        path,
+        [note_0, note_1],
+        EXPIRY,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(r14_circuit::verify_delegated_offchain(&vk, &proof, &pi));
+
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
+    let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
+    TestScenario {
+        proof: sp,
+        public_inputs: spi,
+        svk,
+    }
+}
+
+fn submit(env: &Env, scenario: &TestScenario) -> bool {
+    let old_root = hex_to_bytes32(env, &scenario.public_inputs[0]);
+    let (transfer_addr, circuit_id) = deploy_contracts(env, &scenario.svk, std::slice::from_ref(&old_root));
+    let client = R14TransferClient::new(env, &transfer_addr);
+    client.init_delegated(&circuit_id);
+
+    let proof = build_soroban_proof(env, &scenario.proof);
+    let nullifier = hex_to_bytes32(env, &scenario.public_inputs[1]);
+    let cm_0 = hex_to_bytes32(env, &scenario.public_inputs[2]);
+    let cm_1 = hex_to_bytes32(env, &scenario.public_inputs[3]);
+    let new_root = BytesN::from_array(env, &[0xAAu8; 32]);
+
+    client.transfer_delegated(&proof, &old_root, &nullifier, &cm_0, &cm_1, &EXPIRY, &new_root)
+}
+
+// ── Tests ──
+
+#[test]
+fn test_delegated_transfer() {
+    let scenario = setup_and_prove_delegated();
+    let env = Env::default();
+    env.ledger().set_sequence_number(EXPIRY as u32 - 10);
+    assert!(submit(&env, &scenario));
+}
+
+#[test]
+#[should_panic(expected = "session expired")]
+fn test_expired_session_rejected() {
+    let scenario = setup_and_prove_delegated();
+    let env = Env::default();
+    env.ledger().set_sequence_number(EXPIRY as u32 + 1);
+    submit(&env, &scenario);
+}
@@ -0,0 +1,57 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Batched deposits: several commitments → one deposit_batch call
+
+use r14_transfer::{R14Transfer, R14TransferClient};
+use soroban_sdk::{testutils::{Address as _, Events}, Address, BytesN, Env, Vec};
+
+/// Deploy r14-transfer alone; deposits never call the core contract
+fn deploy_transfer(env: &Env) -> Address {
+    let transfer_id = env.register(R14Transfer, ());
+    let client = R14TransferClient::new(env, &transfer_id);
+    client.init(
+        &Address::generate(env),
+        &Address::generate(env),
+        &BytesN::from_array(env, &[0x14u8; 32]),
+        &BytesN::from_array(env, &[0xEEu8; 32]),
+    );
+    transfer_id
+}
+
+fn commitments(env: &Env, bytes: &[u8]) -> Vec<BytesN<32>> {
+    let mut cms = Vec::new(env);
+    for b in bytes {
+        cms.push_back(BytesN::from_array(env, &[*b; 32]));
+    }
+    cms
+}
+
+#[test]
+fn test_deposit_batch() {
+    let env = Env::default();
+    let transfer_id = deploy_transfer(&env);
+    let client = R14TransferClient::new(&env, &transfer_id);
+
+    let new_root = BytesN::from_array(&env, &[0xAAu8; 32]);
+    client.deposit_batch(&commitments(&env, &[1, 2, 3]), &new_root);
+    // one deposit event per commitment, as the indexer expects
+    assert_eq!(env.events().all().filter_by_contract(&transfer_id).events().len(), 3);
+    assert_eq!(client.latest_root(), new_root);
+}
+
+#[test]
+#[should_panic(expected = "empty batch")]
+fn test_empty_deposit_batch_rejected() {
+    let env = Env::default();
+    let client = R14TransferClient::new(&env, &deploy_transfer(&env));
+    client.deposit_batch(&Vec::new(&env), &BytesN::from_array(&env, &[0xAAu8; 32]));
+}
+
+#[test]
+#[should_panic(expected = "zero commitment")]
+fn test_zero_commitment_in_batch_rejected() {
+    let env = Env::default();
+    let client = R14TransferClient::new(&env, &deploy_transfer(&env));
+    client.deposit_batch(&commitments(&env, &[1, 0]), &BytesN::from_array(&env, &[0xAAu8; 32]));
+}
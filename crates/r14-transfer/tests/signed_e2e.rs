@@ -0,0 +1,100 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Signature-authorized transfers: sign the spend digest, prove_signed → transfer_signed
+
+mod common;
+
+use common::{build_soroban_proof, deploy_contracts, hex_to_bytes32};
+use r14_core::R14Core;
+use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_transfer::{R14Transfer, R14TransferClient};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env};
+
+// ── Test scenario ──
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_circuit::SpendAuthKey;
+use r14_types::{MerklePath, Note, SecretKey, MERKLE_DEPTH};
+
+struct TestScenario {
+    proof: SerializedProof,
+    public_inputs: std::vec::Vec<String>,
+    svk: SerializedVK,
+}
+
+fn setup_and_prove_signed() -> TestScenario {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    // the spend-auth key would live on a hardware wallet; the prover holds
+    // only the nullifier key and the public key
+    let ask = SpendAuthKey::random(&mut rng);
+    let ak = ask.public_key();
+    let nk = SecretKey::random(&mut rng);
+    let owner = ak.owner(&nk);
+
+    let consumed = Note::new(1000, 1, owner.0, &mut rng);
+    let siblings: std::vec::Vec<Fr> = (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect();
+    let indices: std::vec::Vec<bool> = (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect();
+    let path = MerklePath { siblings, indices };
+
+    let recipient = Fr::rand(&mut rng);
+    let created = [
+        Note::new(300, 1, recipient, &mut rng),
+        Note::new(700, 1, owner.0, &mut rng),
+    ];
+
+    let pi = r14_circuit::signed_public_inputs(nk.0, &consumed, &path, &created, 0).unwrap();
+    let signature = ask.sign(r14_circuit::spend_digest(&pi), &mut rng);
+
+    let (pk, vk) = r14_circuit::setup_signed(&mut rng).unwrap();
+    let (proof, pi) =
+        r14_circuit::prove_signed(&pk, &ak, nk.0, &signature, consumed, path, created, 0, &mut rng).unwrap();
+    assert!(r14_circuit::verify_offchain(&vk, &proof, &pi));
+
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
+    let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
+    TestScenario {
+        proof: sp,
+        public_inputs: spi,
+        svk,
+    }
+}
+
+// ── Tests ──
+
+#[test]
+fn test_signed_transfer() {
+    let scenario = setup_and_prove_signed();
+    let env = Env::default();
+
+    let old_root = hex_to_bytes32(&env, &scenario.public_inputs[0]);
+    let (transfer_addr, circuit_id) = deploy_contracts(&env, &scenario.svk, std::slice::from_ref(&old_root));
+    let client = R14TransferClient::new(&env, &transfer_addr);
+    client.init_signed(&circuit_id);
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let nullifier = hex_to_bytes32(&env, &scenario.public_inputs[1]);
+    let cm_0 = hex_to_bytes32(&env, &scenario.public_inputs[2]);
+    let cm_1 = hex_to_bytes32(&env, &scenario.public_inputs[3]);
+    let new_root = BytesN::from_array(&env, &[0xAAu8; 32]);
+
+    assert!(client.transfer_signed(&proof, &old_root, &nullifier, &cm_0, &cm_1, &0, &new_root));
+    assert!(client.is_spent(&nullifier));
+}
+
+#[test]
+#[should_panic(expected = "signed circuit already set")]
+fn test_signed_circuit_set_once() {
+    let env = Env::default();
+    let core_id = env.register(R14Core, ());
+    let client = R14TransferClient::new(&env, &env.register(R14Transfer, ()));
+    let id = BytesN::from_array(&env, &[0u8; 32]);
+    client.init(&Address::generate(&env), &core_id, &id, &id);
+    env.mock_all_auths();
+    client.init_signed(&id);
+    client.init_signed(&id);
+}
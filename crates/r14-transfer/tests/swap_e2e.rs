@@ -0,0 +1,132 @@
+// Copyright 2026 abhirupbanerjee
+// Licensed under the Apache License, Version 2.0
+
+//! Two-asset swaps: prove_swap → transfer_swap spends both nullifiers
+
+mod common;
+
+use common::{build_soroban_proof, deploy_contracts, hex_to_bytes32};
+use r14_core::R14Core;
+use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_transfer::{Proof, R14Transfer, R14TransferClient, SwapLeg};
+use soroban_sdk::crypto::bls12_381::{G1Affine, G2Affine};
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, BytesN, Env, Vec};
+
+// ── Test scenario ──
+
+use ark_bls12_381::Fr;
+use ark_ff::UniformRand;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use r14_circuit::{SpendAuthKey, SwapLeg as LegWitness};
+use r14_types::{MerklePath, Note, SecretKey, MERKLE_DEPTH};
+
+struct TestScenario {
+    proof: SerializedProof,
+    public_inputs: std::vec::Vec<String>,
+    svk: SerializedVK,
+}
+
+fn setup_and_prove_swap() -> TestScenario {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    // Alice trades 100 of asset 1 for Bob's 40 of asset 2, each signing
+    // with their own spend-auth key
+    let keys = [SpendAuthKey::random(&mut rng), SpendAuthKey::random(&mut rng)];
+    let nks = [SecretKey::random(&mut rng), SecretKey::random(&mut rng)];
+    let alice_owner = keys[0].public_key().owner(&nks[0]).0;
+    let bob_owner = keys[1].public_key().owner(&nks[1]).0;
+
+    let mut path = || MerklePath {
+        siblings: (0..MERKLE_DEPTH).map(|_| Fr::rand(&mut rng)).collect(),
+        indices: (0..MERKLE_DEPTH).map(|i| i % 2 == 0).collect(),
+    };
+    let (path_a, path_b) = (path(), path());
+    let legs = [
+        LegWitness {
+            auth_key: keys[0].public_key(),
+            nullifier_key: nks[0].0,
+            consumed_note: Note::new(150, 1, alice_owner, &mut rng),
+            merkle_path: path_a,
+            created_notes: [Note::new(100, 1, bob_owner, &mut rng), Note::new(50, 1, alice_owner, &mut rng)],
+        },
+        LegWitness {
+            auth_key: keys[1].public_key(),
+            nullifier_key: nks[1].0,
+            consumed_note: Note::new(40, 2, bob_owner, &mut rng),
+            merkle_path: path_b,
+            created_notes: [Note::new(40, 2, alice_owner, &mut rng), Note::new(0, 2, bob_owner, &mut rng)],
+        },
+    ];
+    let digest = r14_circuit::swap_digest(&r14_circuit::swap_public_inputs(&legs, 0).unwrap());
+    let signatures = keys.map(|k| k.sign(digest, &mut rng));
+
+    let (pk, vk) = r14_circuit::setup_swap(&mut rng).unwrap();
+    let (proof, pi) = r14_circuit::prove_swap(&pk, legs, signatures, 0, &mut rng).unwrap();
+    assert!(r14_circuit::verify_swap_offchain(&vk, &proof, &pi));
+
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
+    let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
+    TestScenario {
+        proof: sp,
+        public_inputs: spi,
+        svk,
+    }
+}
+
+/// Leg `i` of the swap, from its five public inputs
+fn leg(env: &Env, public_inputs: &[String], i: usize) -> SwapLeg {
+    let pi = &public_inputs[5 * i..];
+    SwapLeg {
+        old_root: hex_to_bytes32(env, &pi[0]),
+        nullifier: hex_to_bytes32(env, &pi[1]),
+        cm_0: hex_to_bytes32(env, &pi[2]),
+        cm_1: hex_to_bytes32(env, &pi[3]),
+    }
+}
+
+// ── Tests ──
+
+#[test]
+fn test_swap() {
+    let scenario = setup_and_prove_swap();
+    let env = Env::default();
+
+    let (a, b) = (leg(&env, &scenario.public_inputs, 0), leg(&env, &scenario.public_inputs, 1));
+    let roots = [a.old_root.clone(), b.old_root.clone()];
+    let (transfer_addr, circuit_id) = deploy_contracts(&env, &scenario.svk, &roots);
+    let client = R14TransferClient::new(&env, &transfer_addr);
+    client.init_swap(&circuit_id);
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let new_root = BytesN::from_array(&env, &[0xAAu8; 32]);
+
+    // legs out of order don't match the proof, and nothing is spent
+    let swapped = Vec::from_array(&env, [b.clone(), a.clone()]);
+    assert!(client.try_transfer_swap(&proof, &swapped, &0, &new_root).is_err());
+    assert!(!client.is_spent(&a.nullifier));
+
+    let legs = Vec::from_array(&env, [a.clone(), b.clone()]);
+    assert!(client.transfer_swap(&proof, &legs, &0, &new_root));
+    assert!(client.is_spent(&a.nullifier));
+    assert!(client.is_spent(&b.nullifier));
+}
+
+#[test]
+#[should_panic(expected = "swap needs two legs")]
+fn test_swap_needs_two_legs() {
+    let env = Env::default();
+    let core_id = env.register(R14Core, ());
+    let client = R14TransferClient::new(&env, &env.register(R14Transfer, ()));
+    let id = BytesN::from_array(&env, &[0u8; 32]);
+    client.init(&Address::generate(&env), &core_id, &id, &id);
+    env.mock_all_auths();
+    client.init_swap(&id);
+
+    let proof = Proof {
+        a: G1Affine::from_bytes(BytesN::from_array(&env, &[0u8; 96])),
+        b: G2Affine::from_bytes(BytesN::from_array(&env, &[0u8; 192])),
+        c: G1Affine::from_bytes(BytesN::from_array(&env, &[0u8; 96])),
+    };
+    client.transfer_swap(&proof, &Vec::new(&env), &0, &id);
+}
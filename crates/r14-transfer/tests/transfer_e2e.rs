@@ -4,52 +4,13 @@
 //! End-to-end integration test: off-chain prove → two-contract verify
 //! Deploy r14-core + r14-transfer, register VK, then transfer
 
-use r14_core::{R14Core, R14CoreClient, VerificationKey};
-use r14_sdk::serialize::{serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
-use r14_transfer::{Proof, R14Transfer, R14TransferClient};
-use soroban_sdk::crypto::bls12_381::{G1Affine, G2Affine};
-use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, Vec};
-
-// ── Hex helpers ──
-
-fn hex_to_g1(env: &Env, h: &str) -> G1Affine {
-    let bytes: [u8; 96] = hex::decode(h).unwrap().try_into().unwrap();
-    G1Affine::from_bytes(BytesN::from_array(env, &bytes))
-}
-
-fn hex_to_g2(env: &Env, h: &str) -> G2Affine {
-    let bytes: [u8; 192] = hex::decode(h).unwrap().try_into().unwrap();
-    G2Affine::from_bytes(BytesN::from_array(env, &bytes))
-}
-
-fn hex_to_bytes32(env: &Env, h: &str) -> BytesN<32> {
-    let bytes: [u8; 32] = hex::decode(h).unwrap().try_into().unwrap();
-    BytesN::from_array(env, &bytes)
-}
-
-// ── Soroban type builders (unified IC) ──
-
-fn build_soroban_vk(env: &Env, svk: &SerializedVK) -> VerificationKey {
-    let mut ic = Vec::new(env);
-    for ic_hex in &svk.ic {
-        ic.push_back(hex_to_g1(env, ic_hex));
-    }
-    VerificationKey {
-        alpha_g1: hex_to_g1(env, &svk.alpha_g1),
-        beta_g2: hex_to_g2(env, &svk.beta_g2),
-        gamma_g2: hex_to_g2(env, &svk.gamma_g2),
-        delta_g2: hex_to_g2(env, &svk.delta_g2),
-        ic,
-    }
-}
+mod common;
 
-fn build_soroban_proof(env: &Env, sp: &SerializedProof) -> Proof {
-    Proof {
-        a: hex_to_g1(env, &sp.a),
-        b: hex_to_g2(env, &sp.b),
-        c: hex_to_g1(env, &sp.c),
-    }
-}
+use common::{build_soroban_proof, build_soroban_vk, hex_to_bytes32, hex_to_g1, hex_to_g2};
+use r14_core::{R14Core, R14CoreClient};
+use r14_sdk::serialize::{circuit_id, serialize_proof_for_soroban, serialize_vk_for_soroban, SerializedProof, SerializedVK};
+use r14_transfer::{Proof, R14Transfer, R14TransferClient};
+use soroban_sdk::{testutils::{Address as _, Ledger}, Address, BytesN, Env};
 
 // ── Test scenario ──
 
@@ -87,12 +48,12 @@ fn setup_and_prove() -> TestScenario {
     let note_0 = Note::new(700, 1, recipient_owner.0, &mut rng);
     let note_1 = Note::new(300, 1, owner.0, &mut rng);
 
-    let (pk, vk) = r14_circuit::setup(&mut rng);
-    let (proof, pi) = r14_circuit::prove(&pk, sk.0, consumed, path, [note_0, note_1], &mut rng);
+    let (pk, vk) = r14_circuit::setup(&mut rng).unwrap();
+    let (proof, pi) = r14_circuit::prove(&pk, sk.0, consumed, path, [note_0, note_1], 0, &mut rng).unwrap();
 
     assert!(r14_circuit::verify_offchain(&vk, &proof, &pi));
 
-    let svk = serialize_vk_for_soroban(&vk);
+    let svk = serialize_vk_for_soroban(&vk, MERKLE_DEPTH);
     let (sp, spi) = serialize_proof_for_soroban(&proof, &pi.to_vec());
 
     TestScenario {
@@ -131,7 +92,7 @@ fn deploy_contracts(env: &Env, svk: &SerializedVK, old_root: &BytesN<32>) -> Add
     let transfer_id = env.register(R14Transfer, ());
     let transfer_client = R14TransferClient::new(env, &transfer_id);
     let empty_root = test_empty_root(env);
-    transfer_client.init(&core_id, &circuit_id, &empty_root);
+    transfer_client.init(&admin, &core_id, &circuit_id, &empty_root);
 
     // Deposit a dummy commitment to seed old_root into known roots
     let dummy_cm = BytesN::from_array(env, &[0x01u8; 32]);
@@ -142,6 +103,19 @@ fn deploy_contracts(env: &Env, svk: &SerializedVK, old_root: &BytesN<32>) -> Add
 
 // ── Tests ──
 
+#[test]
+fn test_circuit_id_matches_sdk() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+
+    let old_root = hex_to_bytes32(&env, &scenario.public_inputs[0]);
+    let transfer_addr = deploy_contracts(&env, &scenario.svk, &old_root);
+    let client = R14TransferClient::new(&env, &transfer_addr);
+
+    let expected = hex_to_bytes32(&env, &circuit_id(&scenario.svk));
+    assert_eq!(client.circuit_id(), expected);
+}
+
 #[test]
 fn test_transfer_e2e() {
     let scenario = setup_and_prove();
@@ -157,10 +131,38 @@ fn test_transfer_e2e() {
     let cm_1 = hex_to_bytes32(&env, &scenario.public_inputs[3]);
     let new_root = test_new_root(&env);
 
-    let result = client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &new_root);
+    let result = client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &0, &new_root);
     assert!(result);
 }
 
+#[test]
+fn test_view_functions() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+
+    let old_root = hex_to_bytes32(&env, &scenario.public_inputs[0]);
+    let transfer_addr = deploy_contracts(&env, &scenario.svk, &old_root);
+    let client = R14TransferClient::new(&env, &transfer_addr);
+
+    let nullifier = hex_to_bytes32(&env, &scenario.public_inputs[1]);
+    let new_root = test_new_root(&env);
+
+    assert_eq!(client.latest_root(), old_root);
+    assert!(client.is_known_root(&old_root));
+    assert!(client.is_known_root(&test_empty_root(&env)));
+    assert!(!client.is_known_root(&new_root));
+    assert!(!client.is_spent(&nullifier));
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let cm_0 = hex_to_bytes32(&env, &scenario.public_inputs[2]);
+    let cm_1 = hex_to_bytes32(&env, &scenario.public_inputs[3]);
+    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &0, &new_root);
+
+    assert!(client.is_spent(&nullifier));
+    assert_eq!(client.latest_root(), new_root);
+    assert!(client.is_known_root(&new_root));
+}
+
 #[test]
 #[should_panic(expected = "nullifier already spent")]
 fn test_double_spend_rejected() {
@@ -177,9 +179,9 @@ fn test_double_spend_rejected() {
     let cm_1 = hex_to_bytes32(&env, &scenario.public_inputs[3]);
     let new_root = test_new_root(&env);
 
-    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &new_root);
+    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &0, &new_root);
     // Second call with same nullifier should panic
-    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &new_root);
+    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &0, &new_root);
 }
 
 #[test]
@@ -204,7 +206,7 @@ fn test_invalid_proof_rejected() {
         c: hex_to_g1(&env, &scenario.proof.c),
     };
 
-    client.transfer(&tampered_proof, &old_root, &nullifier, &cm_0, &cm_1, &new_root);
+    client.transfer(&tampered_proof, &old_root, &nullifier, &cm_0, &cm_1, &0, &new_root);
 }
 
 #[test]
@@ -224,7 +226,7 @@ fn test_wrong_nullifier_rejected() {
 
     let wrong_nullifier = BytesN::from_array(&env, &[0xABu8; 32]);
 
-    client.transfer(&proof, &old_root, &wrong_nullifier, &cm_0, &cm_1, &new_root);
+    client.transfer(&proof, &old_root, &wrong_nullifier, &cm_0, &cm_1, &0, &new_root);
 }
 
 #[test]
@@ -245,7 +247,7 @@ fn test_unknown_root_rejected() {
 
     // Use a root that was never committed
     let fake_root = BytesN::from_array(&env, &[0xFFu8; 32]);
-    client.transfer(&proof, &fake_root, &nullifier, &cm_0, &cm_1, &new_root);
+    client.transfer(&proof, &fake_root, &nullifier, &cm_0, &cm_1, &0, &new_root);
 }
 
 #[test]
@@ -262,3 +264,44 @@ fn test_zero_commitment_rejected() {
     let new_root = test_new_root(&env);
     client.deposit(&zero_cm, &new_root);
 }
+
+#[test]
+#[should_panic(expected = "current_ledger is in the future")]
+fn test_future_ledger_rejected() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    env.ledger().set_sequence_number(100);
+
+    let old_root = hex_to_bytes32(&env, &scenario.public_inputs[0]);
+    let transfer_addr = deploy_contracts(&env, &scenario.svk, &old_root);
+    let client = R14TransferClient::new(&env, &transfer_addr);
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let nullifier = hex_to_bytes32(&env, &scenario.public_inputs[1]);
+    let cm_0 = hex_to_bytes32(&env, &scenario.public_inputs[2]);
+    let cm_1 = hex_to_bytes32(&env, &scenario.public_inputs[3]);
+    let new_root = test_new_root(&env);
+
+    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &101, &new_root);
+}
+
+#[test]
+#[should_panic(expected = "proof verification failed")]
+fn test_wrong_ledger_rejected() {
+    let scenario = setup_and_prove();
+    let env = Env::default();
+    env.ledger().set_sequence_number(100);
+
+    let old_root = hex_to_bytes32(&env, &scenario.public_inputs[0]);
+    let transfer_addr = deploy_contracts(&env, &scenario.svk, &old_root);
+    let client = R14TransferClient::new(&env, &transfer_addr);
+
+    let proof = build_soroban_proof(&env, &scenario.proof);
+    let nullifier = hex_to_bytes32(&env, &scenario.public_inputs[1]);
+    let cm_0 = hex_to_bytes32(&env, &scenario.public_inputs[2]);
+    let cm_1 = hex_to_bytes32(&env, &scenario.public_inputs[3]);
+    let new_root = test_new_root(&env);
+
+    // proven at ledger 0; claiming a later one changes the public input
+    client.transfer(&proof, &old_root, &nullifier, &cm_0, &cm_1, &50, &new_root);
+}
@@ -0,0 +1,236 @@
+//! Owner hashes as checksummed `r14o1...` addresses.
+//!
+//! An address is the owner hash's 32 big-endian bytes in bech32m (BIP-350)
+//! with human-readable part `r14o`, 63 characters in all. The checksum
+//! catches any single typo and any swap of two adjacent characters, where
+//! a mistyped hex owner hash is just a different, unspendable owner.
+//!
+//! [`parse_owner`] takes either form, so anything that accepted hex keeps
+//! doing so.
+
+use ark_bls12_381::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::CanonicalDeserialize;
+
+/// Human-readable part of an owner address
+pub const OWNER_HRP: &str = "r14o";
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const CHECKSUM_LEN: usize = 6;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AddressError {
+    /// Not an `r14o1` address
+    WrongPrefix,
+    MixedCase,
+    InvalidChar(char),
+    /// The address has a typo
+    BadChecksum,
+    BadLength,
+    NotInField,
+}
+
+impl core::fmt::Display for AddressError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongPrefix => write!(f, "not an {OWNER_HRP}1 address"),
+            Self::MixedCase => write!(f, "address mixes upper and lower case"),
+            Self::InvalidChar(c) => write!(f, "invalid address character {c:?}"),
+            Self::BadChecksum => write!(f, "address checksum mismatch (typo?)"),
+            Self::BadLength => write!(f, "address does not hold 32 bytes"),
+            Self::NotInField => write!(f, "address value not in field"),
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    let mut chk = 1u32;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> impl Iterator<Item = u8> + '_ {
+    hrp.bytes().map(|b| b >> 5).chain([0]).chain(hrp.bytes().map(|b| b & 31))
+}
+
+/// Regroup `data` from `from`-bit to `to`-bit values, padding the last
+/// group when encoding and rejecting nonzero padding when decoding
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let (mut acc, mut bits) = (0u32, 0u32);
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &v in data {
+        acc = (acc << from) | u32::from(v);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// `hrp`, separator, 5-bit `data` and its checksum
+fn checksummed(hrp: &str, data: Vec<u8>) -> String {
+    let chk = polymod(hrp_expand(hrp).chain(data.iter().copied()).chain([0; CHECKSUM_LEN])) ^ BECH32M_CONST;
+    let checksum = (0..CHECKSUM_LEN).map(|i| ((chk >> (5 * (CHECKSUM_LEN - 1 - i))) & 31) as u8);
+    let mut out = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LEN);
+    out.push_str(hrp);
+    out.push('1');
+    out.extend(data.into_iter().chain(checksum).map(|d| CHARSET[d as usize] as char));
+    out
+}
+
+/// `r14o1...` address of an owner hash
+pub fn encode_owner(owner: &Fr) -> String {
+    let data = convert_bits(&owner.into_bigint().to_bytes_be(), 8, 5, true).expect("padding allowed");
+    checksummed(OWNER_HRP, data)
+}
+
+/// Parse an `r14o1...` address, in all-lower or all-upper case
+pub fn decode_owner(s: &str) -> Result<Fr, AddressError> {
+    if s.bytes().any(|b| b.is_ascii_lowercase()) && s.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(AddressError::MixedCase);
+    }
+    let s = s.to_ascii_lowercase();
+    let data = s
+        .strip_prefix(OWNER_HRP)
+        .and_then(|rest| rest.strip_prefix('1'))
+        .ok_or(AddressError::WrongPrefix)?;
+    let values = data
+        .chars()
+        .map(|c| {
+            CHARSET
+                .iter()
+                .position(|&x| x as char == c)
+                .map(|p| p as u8)
+                .ok_or(AddressError::InvalidChar(c))
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    if values.len() < CHECKSUM_LEN {
+        return Err(AddressError::BadLength);
+    }
+    if polymod(hrp_expand(OWNER_HRP).chain(values.iter().copied())) != BECH32M_CONST {
+        return Err(AddressError::BadChecksum);
+    }
+    let mut bytes = convert_bits(&values[..values.len() - CHECKSUM_LEN], 5, 8, false)
+        .filter(|b| b.len() == 32)
+        .ok_or(AddressError::BadLength)?;
+    bytes.reverse();
+    Fr::deserialize_compressed(&bytes[..]).map_err(|_| AddressError::NotInField)
+}
+
+/// Whether `s` looks like an owner address rather than hex
+pub fn is_owner_address(s: &str) -> bool {
+    s.get(..OWNER_HRP.len() + 1)
+        .is_some_and(|p| p.eq_ignore_ascii_case(&format!("{OWNER_HRP}1")))
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OwnerParseError {
+    Address(AddressError),
+    Hex(crate::hex::HexError),
+}
+
+#[cfg(feature = "serde")]
+impl core::fmt::Display for OwnerParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Address(e) => e.fmt(f),
+            Self::Hex(e) => e.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for OwnerParseError {}
+
+/// Owner hash as an `r14o1...` address or as hex
+#[cfg(feature = "serde")]
+pub fn parse_owner(s: &str) -> Result<Fr, OwnerParseError> {
+    if is_owner_address(s) {
+        decode_owner(s).map_err(OwnerParseError::Address)
+    } else {
+        crate::hex::decode(s).map_err(OwnerParseError::Hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut rng = test_rng();
+        for _ in 0..10 {
+            let owner = Fr::rand(&mut rng);
+            let addr = encode_owner(&owner);
+            assert_eq!(addr.len(), 63);
+            assert!(addr.starts_with("r14o1"));
+            assert_eq!(decode_owner(&addr).unwrap(), owner);
+            assert_eq!(decode_owner(&addr.to_ascii_uppercase()).unwrap(), owner);
+        }
+        assert_eq!(decode_owner(&encode_owner(&Fr::from(0u64))).unwrap(), Fr::from(0u64));
+    }
+
+    #[test]
+    fn test_catches_typos() {
+        let addr = encode_owner(&Fr::from(42u64));
+        let swap = |i: usize, c: char| {
+            let mut s: Vec<char> = addr.chars().collect();
+            s[i] = c;
+            s.into_iter().collect::<String>()
+        };
+        for i in 5..addr.len() {
+            let c = addr.as_bytes()[i] as char;
+            let other = if c == 'q' { 'p' } else { 'q' };
+            assert_eq!(decode_owner(&swap(i, other)), Err(AddressError::BadChecksum), "position {i}");
+        }
+        assert_eq!(decode_owner(&swap(7, 'b')), Err(AddressError::InvalidChar('b')));
+        assert_eq!(decode_owner(&swap(0, 'R')), Err(AddressError::MixedCase));
+        assert_eq!(decode_owner(&addr.replacen("r14o", "r14x", 1)), Err(AddressError::WrongPrefix));
+        assert_eq!(decode_owner("r14o1qqqqqq"), Err(AddressError::BadChecksum));
+    }
+
+    #[test]
+    fn test_rejects_out_of_field() {
+        // checksummed, but 2^256 - 1 is above the modulus
+        let addr = checksummed(OWNER_HRP, convert_bits(&[0xff; 32], 8, 5, true).unwrap());
+        assert_eq!(decode_owner(&addr), Err(AddressError::NotInField));
+
+        let short = checksummed(OWNER_HRP, convert_bits(&[1; 31], 8, 5, true).unwrap());
+        assert_eq!(decode_owner(&short), Err(AddressError::BadLength));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_parse_owner_takes_both() {
+        let owner = Fr::from(7u64);
+        assert_eq!(parse_owner(&encode_owner(&owner)).unwrap(), owner);
+        assert_eq!(parse_owner(&crate::hex::encode(&owner)).unwrap(), owner);
+        assert_eq!(parse_owner("07").unwrap(), owner);
+        assert!(matches!(parse_owner("r14o1zz"), Err(OwnerParseError::Address(_))));
+    }
+}
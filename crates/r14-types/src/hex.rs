@@ -0,0 +1,152 @@
+//! Field elements as hex strings, and the serde format of every type here.
+//!
+//! [`encode`] writes `0x`-prefixed big-endian hex, 64 digits for a 32-byte
+//! field. [`decode`] also accepts raw hex, and zero-pads short inputs. It
+//! rejects anything longer than the field's byte size or not below the
+//! modulus, so one value never has two encodings.
+//!
+//! Use [`field`] and [`field_vec`] as `#[serde(with = ...)]` for
+//! field-element members of other structs.
+
+use ark_ff::{BigInteger, PrimeField};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HexError {
+    InvalidHex,
+    TooLong { bytes: usize, max: usize },
+    NotInField,
+}
+
+impl core::fmt::Display for HexError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHex => write!(f, "invalid hex"),
+            Self::TooLong { bytes, max } => write!(f, "{bytes} bytes, field elements are at most {max}"),
+            Self::NotInField => write!(f, "value not in field"),
+        }
+    }
+}
+
+impl std::error::Error for HexError {}
+
+/// `0x`-prefixed big-endian hex
+pub fn encode<F: PrimeField>(f: &F) -> String {
+    format!("0x{}", ::hex::encode(f.into_bigint().to_bytes_be()))
+}
+
+/// Parse big-endian hex, with or without `0x`
+pub fn decode<F: PrimeField>(s: &str) -> Result<F, HexError> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let bytes = ::hex::decode(s).map_err(|_| HexError::InvalidHex)?;
+    let max = F::MODULUS_BIT_SIZE.div_ceil(8) as usize;
+    if bytes.len() > max {
+        return Err(HexError::TooLong { bytes: bytes.len(), max });
+    }
+    let mut padded = vec![0u8; max - bytes.len()];
+    padded.extend_from_slice(&bytes);
+    let mut bigint = F::BigInt::default();
+    for (limb, chunk) in bigint.as_mut().iter_mut().zip(padded.rchunks(8)) {
+        let mut buf = [0u8; 8];
+        buf[8 - chunk.len()..].copy_from_slice(chunk);
+        *limb = u64::from_be_bytes(buf);
+    }
+    F::from_bigint(bigint).ok_or(HexError::NotInField)
+}
+
+/// `#[serde(with = "r14_types::hex::field")]` for a field element
+pub mod field {
+    use ark_ff::PrimeField;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<F: PrimeField, S: Serializer>(f: &F, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&super::encode(f))
+    }
+
+    pub fn deserialize<'de, F: PrimeField, D: Deserializer<'de>>(d: D) -> Result<F, D::Error> {
+        let s = String::deserialize(d)?;
+        super::decode(&s).map_err(|e| D::Error::custom(format_args!("`{s}`: {e}")))
+    }
+}
+
+/// `#[serde(with = "r14_types::hex::field_vec")]` for a list of field elements
+pub mod field_vec {
+    use ark_ff::PrimeField;
+    use serde::{de::Error, ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<F: PrimeField, S: Serializer>(fs: &[F], s: S) -> Result<S::Ok, S::Error> {
+        let mut seq = s.serialize_seq(Some(fs.len()))?;
+        for f in fs {
+            seq.serialize_element(&super::encode(f))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, F: PrimeField, D: Deserializer<'de>>(d: D) -> Result<Vec<F>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|s| super::decode(s).map_err(|e| D::Error::custom(format_args!("`{s}`: {e}"))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Commitment, LeafIndex, MerklePath, MerkleRoot, Note, Nullifier, OwnerHash};
+    use ark_bls12_381::Fr;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        let mut rng = test_rng();
+        for _ in 0..10 {
+            let f = Fr::rand(&mut rng);
+            let hex = encode(&f);
+            assert_eq!(hex.len(), 66);
+            assert_eq!(decode::<Fr>(&hex).unwrap(), f);
+            assert_eq!(decode::<Fr>(&hex[2..]).unwrap(), f);
+        }
+        assert_eq!(decode::<Fr>("0x2a").unwrap(), Fr::from(42u64));
+    }
+
+    #[test]
+    fn test_decode_rejects() {
+        assert_eq!(decode::<Fr>("0xzz"), Err(HexError::InvalidHex));
+        assert_eq!(decode::<Fr>(&"ab".repeat(33)), Err(HexError::TooLong { bytes: 33, max: 32 }));
+        assert_eq!(decode::<Fr>(&"ff".repeat(32)), Err(HexError::NotInField));
+    }
+
+    #[test]
+    fn test_serde_format() {
+        let mut rng = test_rng();
+        let owner = Fr::rand(&mut rng);
+        let note = Note::new(1000, 1, owner, &mut rng).locked_until(500);
+        let json = serde_json::to_value(&note).unwrap();
+        assert_eq!(json["owner"], encode(&owner));
+        assert_eq!(json["unlock_after"], 500);
+        let back: Note = serde_json::from_value(json).unwrap();
+        assert_eq!((back.owner, back.nonce, back.unlock_after), (note.owner, note.nonce, 500));
+
+        // unlock_after defaults to 0 for notes serialized without it
+        let json = serde_json::json!({ "value": 1, "app_tag": 1, "owner": "0x01", "nonce": "0x02" });
+        assert!(!serde_json::from_value::<Note>(json).unwrap().is_locked());
+
+        let path = MerklePath { siblings: vec![owner, Fr::from(1u64)], indices: vec![true, false] };
+        let json = serde_json::to_value(&path).unwrap();
+        assert_eq!(json["siblings"][1], encode(&Fr::from(1u64)));
+        assert_eq!(serde_json::from_value::<MerklePath>(json).unwrap().siblings, path.siblings);
+
+        for json in [
+            serde_json::to_value(MerkleRoot(owner)).unwrap(),
+            serde_json::to_value(Nullifier(owner)).unwrap(),
+            serde_json::to_value(OwnerHash(owner)).unwrap(),
+            serde_json::to_value(Commitment(owner)).unwrap(),
+        ] {
+            assert_eq!(json, encode(&owner));
+        }
+        assert_eq!(serde_json::from_value::<Nullifier>(encode(&owner).into()).unwrap().0, owner);
+        assert!(serde_json::from_value::<MerkleRoot>("0xzz".into()).is_err());
+        assert_eq!(serde_json::to_value(LeafIndex(7)).unwrap(), 7);
+    }
+}
@@ -1,22 +1,49 @@
 use ark_bls12_381::Fr;
 use ark_ff::UniformRand;
+use ark_serialize::CanonicalSerialize;
 use ark_std::rand::Rng;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-#[derive(Clone, Debug)]
+/// Spending key. Wiped on drop; `Debug` shows only [`SecretKey::fingerprint`].
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SecretKey(pub Fr);
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct OwnerHash(pub Fr);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct OwnerHash(#[cfg_attr(feature = "serde", serde(with = "crate::hex::field"))] pub Fr);
 
 impl SecretKey {
     pub fn random<R: Rng>(rng: &mut R) -> Self {
         Self(Fr::rand(rng))
     }
+
+    /// First 4 bytes of sha256("r14_sk_fp" ++ sk), enough to tell keys
+    /// apart in logs without revealing them
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let mut bytes = [0u8; 32];
+        self.0
+            .serialize_compressed(&mut bytes[..])
+            .expect("Fr fits in 32 bytes");
+        let digest = Sha256::new().chain_update(b"r14_sk_fp").chain_update(bytes).finalize();
+        bytes.zeroize();
+        [digest[0], digest[1], digest[2], digest[3]]
+    }
+}
+
+impl core::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let [a, b, c, d] = self.fingerprint();
+        write!(f, "SecretKey(<redacted {a:02x}{b:02x}{c:02x}{d:02x}>)")
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    extern crate alloc;
+
     use super::*;
+    use alloc::format;
     use ark_std::test_rng;
 
     #[test]
@@ -26,4 +53,21 @@ mod tests {
         let sk2 = SecretKey::random(&mut rng);
         assert_ne!(sk1.0, sk2.0);
     }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let mut rng = test_rng();
+        let sk = SecretKey::random(&mut rng);
+        let shown = format!("{sk:?}");
+        assert!(!shown.contains(&format!("{}", sk.0)), "{shown}");
+        assert_eq!(shown, format!("{:?}", sk.clone()));
+        assert_ne!(sk.fingerprint(), SecretKey::random(&mut rng).fingerprint());
+    }
+
+    #[test]
+    fn test_zeroize() {
+        let mut sk = SecretKey(Fr::from(42u64));
+        sk.zeroize();
+        assert_eq!(sk.0, Fr::from(0u64));
+    }
 }
@@ -1,11 +1,17 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "std")]
+pub mod address;
+#[cfg(feature = "serde")]
+pub mod hex;
 pub mod keys;
 pub mod merkle;
 pub mod note;
 pub mod nullifier;
+pub mod session;
 
 pub use keys::{OwnerHash, SecretKey};
-pub use merkle::{MerklePath, MerkleRoot, MERKLE_DEPTH};
-pub use note::Note;
+pub use merkle::{LeafIndex, MerklePath, MerkleRoot, MERKLE_DEPTH};
+pub use note::{Commitment, Note, AUDITED_APP_TAG};
 pub use nullifier::Nullifier;
+pub use session::SessionPolicy;
@@ -6,10 +6,60 @@ use ark_bls12_381::Fr;
 pub const MERKLE_DEPTH: usize = 20;
 
 #[derive(Clone, Debug)]
-pub struct MerklePath {
-    pub siblings: Vec<Fr>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "F: ark_ff::PrimeField"))]
+pub struct MerklePath<F = Fr> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex::field_vec"))]
+    pub siblings: Vec<F>,
     pub indices: Vec<bool>,
 }
 
+impl<F> MerklePath<F> {
+    /// Position of the leaf the path starts from. `indices[0]` is the
+    /// lowest bit: it says whether the leaf is a right child.
+    pub fn leaf_index(&self) -> LeafIndex {
+        LeafIndex(self.indices.iter().rev().fold(0, |acc, &is_right| acc << 1 | is_right as u64))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct MerkleRoot(pub Fr);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct MerkleRoot(#[cfg_attr(feature = "serde", serde(with = "crate::hex::field"))] pub Fr);
+
+/// Position of a leaf in the Merkle tree, in insertion order
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct LeafIndex(pub u64);
+
+impl LeafIndex {
+    pub fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<usize> for LeafIndex {
+    fn from(index: usize) -> Self {
+        Self(index as u64)
+    }
+}
+
+impl core::fmt::Display for LeafIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_leaf_index_from_path_bits() {
+        let path = |indices: Vec<bool>| MerklePath::<Fr> { siblings: Vec::new(), indices };
+        assert_eq!(path(vec![false; MERKLE_DEPTH]).leaf_index(), LeafIndex(0));
+        assert_eq!(path(vec![true, false, true]).leaf_index(), LeafIndex(5));
+        assert_eq!(path(vec![false, true, true, false]).leaf_index(), LeafIndex(6));
+        assert_eq!(path(vec![true; MERKLE_DEPTH]).leaf_index(), LeafIndex((1 << MERKLE_DEPTH) - 1));
+    }
+}
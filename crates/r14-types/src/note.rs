@@ -1,38 +1,86 @@
 use ark_bls12_381::Fr;
-use ark_ff::UniformRand;
+use ark_ff::PrimeField;
 use ark_std::rand::Rng;
 
+/// App tags with this bit set are audited. Their notes can only be spent
+/// through the audited transfer circuit, which discloses the outputs to the
+/// app's auditor.
+pub const AUDITED_APP_TAG: u32 = 1 << 31;
+
+/// A note over the scalar field `F` (BLS12-381 unless a circuit is
+/// instantiated on another curve)
 #[derive(Clone, Debug)]
-pub struct Note {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = "F: PrimeField"))]
+pub struct Note<F = Fr> {
     pub value: u64,
     pub app_tag: u32,
-    pub owner: Fr,
-    pub nonce: Fr,
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex::field"))]
+    pub owner: F,
+    #[cfg_attr(feature = "serde", serde(with = "crate::hex::field"))]
+    pub nonce: F,
+    /// First ledger at which the note can be spent; 0 means no timelock
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unlock_after: u64,
 }
 
-impl Note {
-    pub fn new<R: Rng>(value: u64, app_tag: u32, owner: Fr, rng: &mut R) -> Self {
+impl<F: PrimeField> Note<F> {
+    pub fn new<R: Rng>(value: u64, app_tag: u32, owner: F, rng: &mut R) -> Self {
         Self {
             value,
             app_tag,
             owner,
-            nonce: Fr::rand(rng),
+            nonce: F::rand(rng),
+            unlock_after: 0,
         }
     }
 
-    pub fn with_nonce(value: u64, app_tag: u32, owner: Fr, nonce: Fr) -> Self {
+    pub fn with_nonce(value: u64, app_tag: u32, owner: F, nonce: F) -> Self {
         Self {
             value,
             app_tag,
             owner,
             nonce,
+            unlock_after: 0,
         }
     }
+
+    /// Timelock the note until ledger `unlock_after`
+    pub fn locked_until(mut self, unlock_after: u64) -> Self {
+        self.unlock_after = unlock_after;
+        self
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.unlock_after != 0
+    }
+
+    /// Whether the note's app tag is in the audited range
+    pub fn is_audited(&self) -> bool {
+        self.app_tag & AUDITED_APP_TAG != 0
+    }
+}
+
+/// Commitment to a note, the leaf it becomes in the Merkle tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+#[cfg_attr(feature = "serde", serde(bound = "F: PrimeField"))]
+pub struct Commitment<F = Fr>(#[cfg_attr(feature = "serde", serde(with = "crate::hex::field"))] pub F);
+
+impl<F> Commitment<F> {
+    pub fn from_fr(fr: F) -> Self {
+        Self(fr)
+    }
+
+    pub fn as_fr(&self) -> &F {
+        &self.0
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_ff::UniformRand;
     use ark_std::test_rng;
 
     #[test]
@@ -45,5 +93,9 @@ mod tests {
         assert_ne!(n1.nonce, n2.nonce);
         assert_eq!(n1.value, 1000);
         assert_eq!(n1.app_tag, 1);
+        assert!(!n1.is_audited());
+        assert!(Note::new(1000, 1 | AUDITED_APP_TAG, owner, &mut rng).is_audited());
+        assert!(!n1.is_locked());
+        assert!(n1.locked_until(500).is_locked());
     }
 }
@@ -1,7 +1,8 @@
 use ark_bls12_381::Fr;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Nullifier(pub Fr);
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Nullifier(#[cfg_attr(feature = "serde", serde(with = "crate::hex::field"))] pub Fr);
 
 impl Nullifier {
     pub fn from_fr(fr: Fr) -> Self {
@@ -0,0 +1,40 @@
+/// Spending limits bound into a session owner hash.
+///
+/// A session can only move notes of `app_tag`, send at most `max_amount`
+/// to a third party per transfer, and only while the ledger sequence is
+/// at or below `expiry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionPolicy {
+    pub max_amount: u64,
+    pub app_tag: u32,
+    pub expiry: u64,
+}
+
+impl SessionPolicy {
+    pub fn new(max_amount: u64, app_tag: u32, expiry: u64) -> Self {
+        Self {
+            max_amount,
+            app_tag,
+            expiry,
+        }
+    }
+
+    /// True if a transfer of `value` at ledger `ledger` is within the policy
+    pub fn allows(&self, value: u64, app_tag: u32, ledger: u64) -> bool {
+        value <= self.max_amount && app_tag == self.app_tag && ledger <= self.expiry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_allows() {
+        let policy = SessionPolicy::new(500, 1, 1_000);
+        assert!(policy.allows(500, 1, 1_000));
+        assert!(!policy.allows(501, 1, 1_000));
+        assert!(!policy.allows(100, 2, 1_000));
+        assert!(!policy.allows(100, 1, 1_001));
+    }
+}